@@ -6,6 +6,7 @@
  * - Rate limiting per IP
  * - CORS handling for Tauri clients
  * - Feedback endpoint (creates GitHub issues)
+ * - Offline ETF dataset downloads (served from R2)
  */
 
 // Rate limiting configuration
@@ -22,6 +23,8 @@ const MAX_PAYLOAD_SIZE = 10 * 1024 // 10KB
 const SYMBOL_PATTERN = /^[A-Z0-9./-]{1,10}$/i
 // Search queries: 1-50 chars, alphanumeric + basic punctuation, no injection patterns
 const QUERY_PATTERN = /^[A-Za-z0-9\s./'&,-]{1,50}$/
+// Offline dataset files: the manifest and one constituents file per fund ISIN
+const DATASET_PATH_PATTERN = /^\/dataset\/(manifest\.json|funds\/[A-Z]{2}[A-Z0-9]{9}[0-9]\.json)$/
 
 /**
  * Validate request payload size
@@ -74,6 +77,45 @@ function validateQuery(query) {
   return trimmed
 }
 
+/**
+ * Serve a file of the offline ETF dataset from the DATASET_BUCKET R2 binding.
+ * Bytes are passed through untouched (the app verifies their SHA-256) and
+ * Range requests are honored so interrupted downloads resume.
+ *
+ * @param {Request} request - The incoming GET request
+ * @param {string} key - Object key, e.g. "dataset/manifest.json"
+ * @param {object} env - Worker environment bindings
+ * @param {string} origin - Request origin for CORS
+ * @returns {Promise<Response>}
+ */
+async function serveDatasetFile(request, key, env, origin) {
+  const headers = { 'Content-Type': 'application/json', ...corsHeaders(origin, env) }
+  if (!env.DATASET_BUCKET) {
+    return new Response(JSON.stringify({ error: 'Dataset not available' }), {
+      status: 503,
+      headers,
+    })
+  }
+
+  const object = await env.DATASET_BUCKET.get(key, { range: request.headers })
+  if (!object) {
+    return new Response(JSON.stringify({ error: 'Not found' }), { status: 404, headers })
+  }
+
+  // The manifest changes with every release; fund files are immutable per checksum
+  headers['Cache-Control'] = key.endsWith('manifest.json')
+    ? 'public, max-age=300'
+    : 'public, max-age=86400'
+  headers['Accept-Ranges'] = 'bytes'
+  if (object.range && request.headers.has('Range')) {
+    const start = object.range.offset ?? 0
+    const end = start + (object.range.length ?? object.size - start) - 1
+    headers['Content-Range'] = `bytes ${start}-${end}/${object.size}`
+    return new Response(object.body, { status: 206, headers })
+  }
+  return new Response(object.body, { status: 200, headers })
+}
+
 // In-memory rate limit store (fallback when KV is not available)
 const rateLimitStore = new Map()
 
@@ -389,6 +431,16 @@ export default {
       }
     }
 
+    if (request.method === 'GET' && url.pathname.startsWith('/dataset/')) {
+      if (!DATASET_PATH_PATTERN.test(url.pathname)) {
+        return new Response(JSON.stringify({ error: 'Not found' }), {
+          status: 404,
+          headers: { 'Content-Type': 'application/json', ...corsHeaders(origin, env) },
+        })
+      }
+      return serveDatasetFile(request, url.pathname.slice(1), env, origin)
+    }
+
     try {
       let data
       const body = request.method === 'POST' ? await request.json() : {}
@@ -0,0 +1,140 @@
+"""
+Publish Dataset Script
+
+Builds a release of the offline ETF decomposition dataset from the Hive and
+uploads it to the R2 bucket the proxy serves at /dataset/*:
+
+- dataset/funds/<ISIN>.json: {"etfIsin", "name", "holdings": [{"isin", "name", "weight"}]}
+- dataset/manifest.json: version, publish date and each fund's SHA-256
+
+Funds default to the ETFs of default_config/adapter_registry.json. Funds
+without community holdings are left out. Fund files are uploaded before the
+manifest, so clients never see a manifest naming files that do not exist yet.
+
+Usage:
+    python scripts/publish_dataset.py --out output/dataset            # build only
+    python scripts/publish_dataset.py --out output/dataset --upload   # build and upload
+"""
+
+import argparse
+import hashlib
+import json
+import subprocess
+import sys
+from datetime import date, datetime, timezone
+from pathlib import Path
+
+from dotenv import load_dotenv
+
+# Add project root to path for module imports
+sys.path.insert(0, str(Path(__file__).parent.parent / "src-tauri" / "python"))
+
+from portfolio_src.data.hive_client import get_hive_client
+
+load_dotenv()
+
+REGISTRY_PATH = (
+    Path(__file__).parent.parent / "src-tauri" / "python" / "default_config" / "adapter_registry.json"
+)
+BUCKET = "portfolio-prism-dataset"
+
+
+def default_isins() -> list[str]:
+    registry = json.loads(REGISTRY_PATH.read_text())
+    return sorted(isin for isin, adapter in registry.items() if adapter != "ignore")
+
+
+def build(isins: list[str], out_dir: Path, version: str) -> dict:
+    """Write the fund files and manifest of a release; returns the manifest."""
+    client = get_hive_client()
+    if not client.is_configured:
+        raise SystemExit("ERROR: Supabase URL or Key not configured in .env.")
+
+    funds_dir = out_dir / "funds"
+    funds_dir.mkdir(parents=True, exist_ok=True)
+
+    funds = []
+    for isin in isins:
+        holdings = client.get_etf_holdings(isin)
+        if holdings is None or holdings.empty:
+            print(f"  skipped {isin}: no community holdings")
+            continue
+
+        rows = [
+            {
+                "isin": str(row["isin"]),
+                "name": str(row.get("name") or row["isin"]),
+                "weight": float(row["weight"]),
+            }
+            for _, row in holdings.iterrows()
+        ]
+        name = str(holdings["etf_name"].iloc[0]) if "etf_name" in holdings.columns else isin
+        content = json.dumps(
+            {"etfIsin": isin, "name": name, "holdings": rows}, indent=2, sort_keys=True
+        ).encode()
+        (funds_dir / f"{isin}.json").write_bytes(content)
+
+        funds.append(
+            {
+                "isin": isin,
+                "name": name,
+                "sha256": hashlib.sha256(content).hexdigest(),
+                "holdingsCount": len(rows),
+            }
+        )
+        print(f"  added {isin}: {len(rows)} holdings")
+
+    manifest = {
+        "version": version,
+        "publishedAt": datetime.now(timezone.utc).isoformat(),
+        "funds": funds,
+    }
+    (out_dir / "manifest.json").write_text(json.dumps(manifest, indent=2))
+    return manifest
+
+
+def upload(out_dir: Path, manifest: dict) -> None:
+    """Upload fund files first and the manifest last with wrangler."""
+
+    def put(key: str, path: Path) -> None:
+        subprocess.run(
+            [
+                "wrangler",
+                "r2",
+                "object",
+                "put",
+                f"{BUCKET}/{key}",
+                "--file",
+                str(path),
+                "--content-type",
+                "application/json",
+                "--remote",
+            ],
+            check=True,
+        )
+
+    for fund in manifest["funds"]:
+        put(f"dataset/funds/{fund['isin']}.json", out_dir / "funds" / f"{fund['isin']}.json")
+    put("dataset/manifest.json", out_dir / "manifest.json")
+
+
+def main() -> None:
+    parser = argparse.ArgumentParser(description=__doc__.split("\n\n")[0])
+    parser.add_argument("--out", type=Path, required=True, help="Directory for the release")
+    parser.add_argument("--version", default=date.today().isoformat(), help="Release version")
+    parser.add_argument("--isin", action="append", help="Fund to include (repeatable)")
+    parser.add_argument("--upload", action="store_true", help="Upload to R2 with wrangler")
+    args = parser.parse_args()
+
+    isins = args.isin or default_isins()
+    print(f"Building dataset {args.version} for {len(isins)} funds")
+    manifest = build(isins, args.out, args.version)
+    print(f"Wrote {len(manifest['funds'])} funds to {args.out}")
+
+    if args.upload:
+        upload(args.out, manifest)
+        print(f"Uploaded dataset {args.version} to {BUCKET}")
+
+
+if __name__ == "__main__":
+    main()
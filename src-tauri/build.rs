@@ -1,3 +1,190 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
 fn main() {
+    generate_command_schemas();
+    embed_build_hash();
+    embed_sidecar_checksum();
     tauri_build::build()
 }
+
+/// Expose the commit the shell was built from as `PRISM_BUILD_HASH`: the
+/// `PRISM_BUILD_HASH` or `GITHUB_SHA` environment variable in CI, otherwise
+/// `git rev-parse`, otherwise `unknown`.
+fn embed_build_hash() {
+    println!("cargo:rerun-if-env-changed=PRISM_BUILD_HASH");
+    println!("cargo:rerun-if-env-changed=GITHUB_SHA");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+
+    let hash = std::env::var("PRISM_BUILD_HASH")
+        .or_else(|_| std::env::var("GITHUB_SHA"))
+        .ok()
+        .or_else(|| {
+            let output = std::process::Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .filter(|hash| !hash.is_empty())
+        .map(|hash| hash.chars().take(12).collect())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=PRISM_BUILD_HASH={}", hash);
+}
+
+/// Expose the SHA-256 of the bundled `prism-headless` binary as
+/// `PRISM_SIDECAR_SHA256`, which the shell checks before every spawn. A
+/// `PRISM_SIDECAR_SHA256` environment variable wins, for pipelines that
+/// build the sidecar elsewhere. Without either, verification is skipped.
+fn embed_sidecar_checksum() {
+    println!("cargo:rerun-if-env-changed=PRISM_SIDECAR_SHA256");
+    println!("cargo:rerun-if-changed=binaries");
+
+    let checksum = std::env::var("PRISM_SIDECAR_SHA256")
+        .ok()
+        .filter(|checksum| !checksum.is_empty())
+        .or_else(|| {
+            let target = std::env::var("TARGET").ok()?;
+            let suffix = if target.contains("windows") { ".exe" } else { "" };
+            let path = format!("binaries/prism-headless-{}{}", target, suffix);
+            let mut file = fs::File::open(path).ok()?;
+            let mut hasher = Sha256::new();
+            std::io::copy(&mut file, &mut hasher).ok()?;
+            Some(format!("{:x}", hasher.finalize()))
+        });
+    if let Some(checksum) = checksum {
+        println!("cargo:rustc-env=PRISM_SIDECAR_SHA256={}", checksum.to_lowercase());
+    }
+}
+
+/// Parameters Tauri injects itself; they are not part of the IPC payload.
+const INJECTED_TYPES: &[&str] = &["State<", "AppHandle", "Window", "WebviewWindow", "Webview"];
+
+/// Scan `src/commands/*.rs` for `#[tauri::command]` signatures and write the
+/// argument and return types to `$OUT_DIR/command_schemas.rs`, which backs
+/// the `get_api_manifest` command.
+fn generate_command_schemas() {
+    println!("cargo:rerun-if-changed=src/commands");
+
+    let mut entries = vec![];
+    let mut files: Vec<_> = fs::read_dir("src/commands")
+        .expect("src/commands must exist")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    files.sort();
+
+    for path in files {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source = fs::read_to_string(&path).expect("command module must be readable");
+        let mut rest = source.as_str();
+        // Top-level attribute only, so mentions in docs and tests are skipped
+        while let Some(start) = rest.find("\n#[tauri::command]\n") {
+            rest = &rest[start + "\n#[tauri::command]\n".len()..];
+            let Some(fn_start) = rest.find("fn ") else {
+                break;
+            };
+            let Some(body_start) = rest[fn_start..].find('{') else {
+                break;
+            };
+            entries.push(schema_entry(&rest[fn_start + 3..fn_start + body_start]));
+        }
+    }
+
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("command_schemas.rs");
+    let generated = format!(
+        "/// (command, [(argument, type, optional)], return type)\n\
+         pub static COMMAND_SCHEMAS: &[(&str, &[(&str, &str, bool)], &str)] = &[\n{}];\n",
+        entries.concat()
+    );
+    fs::write(out, generated).expect("failed to write command schemas");
+}
+
+/// One `(name, args, returns)` tuple from a signature like
+/// `name(app_handle: AppHandle, portfolio_id: u32) -> Result<T, String>`.
+fn schema_entry(signature: &str) -> String {
+    let name: String = signature
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    let open = signature.find('(').unwrap_or(signature.len());
+    let close = signature.rfind(')').unwrap_or(signature.len());
+    let params = signature.get(open + 1..close).unwrap_or_default();
+
+    let args: Vec<String> = split_top_level(params)
+        .into_iter()
+        .filter_map(|param| {
+            let (arg, ty) = param.split_once(':')?;
+            let arg = arg.trim().trim_start_matches("mut ").trim();
+            let ty = normalize(ty);
+            if INJECTED_TYPES.iter().any(|injected| ty.starts_with(injected)) {
+                return None;
+            }
+            let optional = ty.starts_with("Option<");
+            Some(format!("({:?}, {:?}, {})", camel_case(arg), ty, optional))
+        })
+        .collect();
+
+    let returns = signature
+        .get(close + 1..)
+        .and_then(|rest| rest.split_once("->"))
+        .map(|(_, ty)| normalize(ty))
+        .map(|ty| unwrap_result(&ty))
+        .unwrap_or_else(|| "()".to_string());
+
+    format!("    ({:?}, &[{}], {:?}),\n", name, args.join(", "), returns)
+}
+
+/// Split on commas outside of `<>`, `()` and `[]`.
+fn split_top_level(params: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&params[start..]);
+    parts.into_iter().filter(|part| !part.trim().is_empty()).collect()
+}
+
+fn normalize(ty: &str) -> String {
+    ty.split_whitespace().collect::<Vec<_>>().join(" ").replace("< ", "<").replace(" >", ">")
+}
+
+/// `Result<T, String>` -> `T`
+fn unwrap_result(ty: &str) -> String {
+    ty.strip_prefix("Result<")
+        .and_then(|inner| inner.strip_suffix('>'))
+        .and_then(|inner| inner.rsplit_once(','))
+        .map(|(ok, _)| ok.trim().to_string())
+        .unwrap_or_else(|| ty.to_string())
+}
+
+/// Tauri's default argument casing
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
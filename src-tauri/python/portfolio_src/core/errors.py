@@ -97,6 +97,10 @@ class PipelineResult:
         return [e.anonymize() for e in self.errors]
 
 
+class PipelineCancelled(Exception):
+    """Raised inside a pipeline run when the user cancelled it."""
+
+
 class SchemaError(PipelineError):
     """Raised when DataFrame schema doesn't match expected format."""
 
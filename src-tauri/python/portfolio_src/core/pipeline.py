@@ -13,11 +13,12 @@ Contains NO business logic — that lives in the services.
 
 import os
 import time
-from typing import Callable, Optional, List, Dict, Any, Tuple, Set, cast
+from typing import Callable, Collection, Optional, List, Dict, Any, Tuple, Set, cast
 from pathlib import Path
 import pandas as pd
 
 from portfolio_src.core.errors import (
+    PipelineCancelled,
     PipelineError,
     PipelineResult,
     ErrorPhase,
@@ -68,6 +69,14 @@ from portfolio_src.headless.transports.echo_bridge import (
 
 logger = get_logger(__name__)
 
+# Stages a portfolio's pipeline config can enable, in run order. A disabled
+# decompose or enrich stage only serves what is cached locally instead of
+# asking the Hive, provider adapters or enrichment APIs.
+PIPELINE_STAGES = ["load", "decompose", "enrich", "aggregate", "report", "harvest"]
+REQUIRED_STAGES = ["load", "aggregate", "report"]
+# Remote sources the fetching stages may query; the local cache is always used.
+DATA_SOURCES = ["hive", "adapters", "apis"]
+
 
 class PipelineMonitor:
     """Tracks pipeline performance, community hit rates, and data provenance."""
@@ -137,6 +146,9 @@ class Pipeline:
         debug: bool = False,
         portfolio_id: int = 1,
         snapshot_repo: Optional[SnapshotRepository] = None,
+        stages: Optional[Collection[str]] = None,
+        data_sources: Optional[Collection[str]] = None,
+        should_cancel: Optional[Callable[[], bool]] = None,
     ):
         """
         Initialize the pipeline.
@@ -146,6 +158,9 @@ class Pipeline:
             debug: Enable debug mode (writes intermediate snapshots)
             portfolio_id: Portfolio ID to load positions from (default: 1)
             snapshot_repo: Repository for writing snapshots (injected for testing)
+            stages: Stages to run (see PIPELINE_STAGES); empty means every stage
+            data_sources: Remote sources to query (see DATA_SOURCES); empty means all
+            should_cancel: Polled at every progress report; True stops the run
         """
         # Dev-only: load .env if not in production
         if not os.getenv("PRISM_DATA_DIR"):
@@ -160,6 +175,9 @@ class Pipeline:
         self.debug = debug or os.getenv("DEBUG_PIPELINE", "false").lower() == "true"
         self._portfolio_id = portfolio_id
         self._snapshot_repo = snapshot_repo or SnapshotRepository()
+        self._stages = set(stages or PIPELINE_STAGES) | set(REQUIRED_STAGES)
+        self._data_sources = set(data_sources or DATA_SOURCES)
+        self._should_cancel = should_cancel
 
         # Services are initialized lazily when run() is called
         self._decomposer: Optional[Decomposer] = None
@@ -180,13 +198,34 @@ class Pipeline:
 
         holdings_cache = get_holdings_cache()
         adapter_registry = AdapterRegistry()
-        enrichment_service = HiveEnrichmentService()
+        enrichment_service = HiveEnrichmentService(data_sources=self._sources_for("enrich"))
 
         isin_resolver = ISINResolver(tier1_threshold=0.1)
-        self._decomposer = Decomposer(holdings_cache, adapter_registry, isin_resolver)
+        self._decomposer = Decomposer(
+            holdings_cache,
+            adapter_registry,
+            isin_resolver,
+            data_sources=self._sources_for("decompose"),
+        )
         self._enricher = Enricher(enrichment_service)
         self._aggregator = Aggregator()
 
+    def _cancellable(
+        self, callback: Callable[[str, float, str], None]
+    ) -> Callable[[str, float, str], None]:
+        """Make every progress report a point where a cancelled run stops."""
+
+        def report(msg: str, pct: float, phase: str) -> None:
+            if self._should_cancel is not None and self._should_cancel():
+                raise PipelineCancelled("Pipeline cancelled")
+            callback(msg, pct, phase)
+
+        return report
+
+    def _sources_for(self, stage: str) -> Set[str]:
+        """Remote sources a fetching stage may query; none when it is disabled."""
+        return set(self._data_sources) if stage in self._stages else set()
+
     def _dump_debug_snapshot(self, phase: str, data: Any):
         """Dump intermediate data for debugging."""
         if not self.debug:
@@ -347,12 +386,17 @@ class Pipeline:
 
         Returns:
             PipelineResult with success status, metrics, and errors
+
+        Raises:
+            PipelineCancelled: If should_cancel returned True; no reports are written.
         """
         # Default progress callback if none provided
         if progress_callback is None:
             progress_callback = lambda msg, pct, phase: logger.info(
                 f"[{pct * 100:.0f}%] [{phase}] {msg}"
             )
+        progress_callback = self._cancellable(progress_callback)
+        cancelled = False
 
         errors = []
         warnings = []
@@ -561,8 +605,9 @@ class Pipeline:
             monitor.record_phase("reporting", time.time() - start)
 
             # Phase 6: Auto-harvest (non-fatal)
-            progress_callback("Harvesting new securities...", 0.95, "reporting")
-            harvested_count = self._harvest()
+            if "harvest" in self._stages:
+                progress_callback("Harvesting new securities...", 0.95, "reporting")
+                harvested_count = self._harvest()
 
             progress_callback("Analysis complete!", 1.0, "complete")
 
@@ -589,6 +634,10 @@ class Pipeline:
                 harvested_count=harvested_count,
             )
 
+        except PipelineCancelled:
+            cancelled = True
+            logger.info("Pipeline cancelled")
+            raise
         except Exception as e:
             logger.error(
                 "Pipeline failed",
@@ -611,34 +660,36 @@ class Pipeline:
                 errors=errors,
             )
         finally:
-            try:
-                self._write_health_report(
-                    errors,
-                    direct_positions,
-                    etf_positions,
-                    holdings_map,
-                    monitor,
-                    self._decomposer,
-                    self._validation_gates,
-                )
+            # A cancelled run keeps the previous run's reports
+            if not cancelled:
+                try:
+                    self._write_health_report(
+                        errors,
+                        direct_positions,
+                        etf_positions,
+                        holdings_map,
+                        monitor,
+                        self._decomposer,
+                        self._validation_gates,
+                    )
 
-                if self._validation_gates:
-                    pipeline_quality = self._validation_gates.get_pipeline_quality()
-                    telemetry = get_telemetry()
-                    session_id = telemetry.get_session_id()
-                    telemetry.report_quality_summary(pipeline_quality, session_id)
+                    if self._validation_gates:
+                        pipeline_quality = self._validation_gates.get_pipeline_quality()
+                        telemetry = get_telemetry()
+                        session_id = telemetry.get_session_id()
+                        telemetry.report_quality_summary(pipeline_quality, session_id)
 
-                report_holdings = locals().get("enriched_holdings") or holdings_map
+                    report_holdings = locals().get("enriched_holdings") or holdings_map
 
-                self._write_breakdown_report(direct_positions, etf_positions, report_holdings)
+                    self._write_breakdown_report(direct_positions, etf_positions, report_holdings)
 
-                self._write_errors(errors)
+                    self._write_errors(errors)
 
-            except Exception as e:
-                logger.error(
-                    "Failed to write final reports",
-                    extra={"error": str(e), "error_type": type(e).__name__},
-                )
+                except Exception as e:
+                    logger.error(
+                        "Failed to write final reports",
+                        extra={"error": str(e), "error_type": type(e).__name__},
+                    )
 
     def _load_portfolio(self) -> Tuple[pd.DataFrame, pd.DataFrame]:
         from portfolio_src.data.database import get_positions
@@ -1,5 +1,5 @@
 # core/services/decomposer.py
-from typing import Dict, List, Tuple, Optional, Any, Callable, TYPE_CHECKING
+from typing import Collection, Dict, List, Tuple, Optional, Any, Callable, TYPE_CHECKING
 import pandas as pd
 import threading
 
@@ -80,10 +80,13 @@ class Decomposer:
         holdings_cache,
         adapter_registry,
         isin_resolver: Optional["ISINResolver"] = None,
+        data_sources: Optional[Collection[str]] = None,
     ):
         self.holdings_cache = holdings_cache
         self.adapter_registry = adapter_registry
         self.isin_resolver = isin_resolver
+        # Remote sources beyond the local cache ("hive", "adapters"); None means all
+        self._data_sources = None if data_sources is None else set(data_sources)
         self._resolution_stats: Dict[str, Dict[str, Any]] = {}
         self._etf_sources: Dict[str, str] = {}
 
@@ -187,6 +190,9 @@ class Decomposer:
         )
         return holdings_map, errors
 
+    def _uses(self, source: str) -> bool:
+        return self._data_sources is None or source in self._data_sources
+
     def _get_holdings(
         self, isin: str
     ) -> Tuple[Optional[pd.DataFrame], Optional[str], Optional[PipelineError]]:
@@ -196,8 +202,9 @@ class Decomposer:
         Returns:
             Tuple of (holdings_df, source, error) where source is one of:
             - "cached" - from local cache
-            - "hive" - from Hive community database
+            - "hive" - from Hive community database, directly or via the shell cache
             - "{adapter_name}_adapter" - from provider adapter (e.g., "ishares_adapter")
+            - "offline_dataset" - from the shell's offline starter dataset
         """
         holdings = None
         source = None
@@ -213,7 +220,7 @@ class Decomposer:
                 extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
             )
 
-        if holdings is None:
+        if holdings is None and self._uses("hive"):
             try:
                 hive_client = get_hive_client()
                 if hive_client.is_configured:
@@ -229,57 +236,52 @@ class Decomposer:
                     extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
                 )
 
-        if holdings is None:
-            try:
-                adapter = self.adapter_registry.get_adapter(isin)
-                if not adapter:
-                    return (
-                        None,
-                        None,
-                        PipelineError(
-                            phase=ErrorPhase.ETF_DECOMPOSITION,
-                            error_type=ErrorType.NO_ADAPTER,
-                            item=isin,
-                            message="No adapter registered for this ISIN",
-                            fix_hint=f"Add adapter or upload to manual_holdings/{isin}.csv",
-                        ),
-                    )
+        adapter_error = None
+        if holdings is None and self._uses("adapters"):
+            holdings, source, adapter_error = self._fetch_from_adapter(isin)
+        elif holdings is None:
+            adapter_error = PipelineError(
+                phase=ErrorPhase.ETF_DECOMPOSITION,
+                error_type=ErrorType.CACHE_MISS,
+                item=isin,
+                message="Not cached and provider adapters are disabled for this portfolio",
+                fix_hint=f"Enable decomposition or upload to manual_holdings/{isin}.csv",
+            )
 
-                adapter_holdings = adapter.fetch_holdings(isin)
-                if adapter_holdings is not None and not adapter_holdings.empty:
-                    try:
-                        self.holdings_cache._save_to_local_cache(
-                            isin, adapter_holdings, source="adapter"
-                        )
-                    except Exception as e:
-                        logger.warning(
-                            "Failed to cache result",
-                            extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
-                        )
+        # The shell serves its Hive cache and the offline dataset
+        if holdings is None and self._uses("hive"):
+            holdings, source = self._fetch_from_shell(isin)
 
-                    _contribute_to_hive_async(isin, adapter_holdings)
+        if holdings is None:
+            return None, None, adapter_error
 
-                    holdings = adapter_holdings
-                    adapter_name = type(adapter).__name__.lower().replace("adapter", "")
-                    source = f"{adapter_name}_adapter"
-                else:
-                    return (
-                        None,
-                        None,
-                        PipelineError(
-                            phase=ErrorPhase.ETF_DECOMPOSITION,
-                            error_type=ErrorType.API_FAILURE,
-                            item=isin,
-                            message="Adapter returned empty holdings",
-                            fix_hint="Check provider website or API limits",
-                        ),
-                    )
+        if holdings is not None and not holdings.empty:
+            holdings = _normalize_weight_format(holdings, isin)
+            holdings, resolution_stats = self._resolve_holdings_isins(holdings, isin)
+            self._resolution_stats[isin] = resolution_stats
 
-            except Exception as e:
-                logger.warning(
-                    "Adapter failed",
-                    extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+        return holdings, source, None
+
+    def _fetch_from_adapter(
+        self, isin: str
+    ) -> Tuple[Optional[pd.DataFrame], Optional[str], Optional[PipelineError]]:
+        try:
+            adapter = self.adapter_registry.get_adapter(isin)
+            if not adapter:
+                return (
+                    None,
+                    None,
+                    PipelineError(
+                        phase=ErrorPhase.ETF_DECOMPOSITION,
+                        error_type=ErrorType.NO_ADAPTER,
+                        item=isin,
+                        message="No adapter registered for this ISIN",
+                        fix_hint=f"Add adapter or upload to manual_holdings/{isin}.csv",
+                    ),
                 )
+
+            adapter_holdings = adapter.fetch_holdings(isin)
+            if adapter_holdings is None or adapter_holdings.empty:
                 return (
                     None,
                     None,
@@ -287,17 +289,64 @@ class Decomposer:
                         phase=ErrorPhase.ETF_DECOMPOSITION,
                         error_type=ErrorType.API_FAILURE,
                         item=isin,
-                        message=f"Adapter fetch failed: {str(e)}",
-                        fix_hint="Check network connectivity",
+                        message="Adapter returned empty holdings",
+                        fix_hint="Check provider website or API limits",
                     ),
                 )
 
-        if holdings is not None and not holdings.empty:
-            holdings = _normalize_weight_format(holdings, isin)
-            holdings, resolution_stats = self._resolve_holdings_isins(holdings, isin)
-            self._resolution_stats[isin] = resolution_stats
+            try:
+                self.holdings_cache._save_to_local_cache(isin, adapter_holdings, source="adapter")
+            except Exception as e:
+                logger.warning(
+                    "Failed to cache result",
+                    extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+                )
 
-        return holdings, source, None
+            _contribute_to_hive_async(isin, adapter_holdings)
+
+            adapter_name = type(adapter).__name__.lower().replace("adapter", "")
+            return adapter_holdings, f"{adapter_name}_adapter", None
+
+        except Exception as e:
+            logger.warning(
+                "Adapter failed",
+                extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+            )
+            return (
+                None,
+                None,
+                PipelineError(
+                    phase=ErrorPhase.ETF_DECOMPOSITION,
+                    error_type=ErrorType.API_FAILURE,
+                    item=isin,
+                    message=f"Adapter fetch failed: {str(e)}",
+                    fix_hint="Check network connectivity",
+                ),
+            )
+
+    def _fetch_from_shell(self, isin: str) -> Tuple[Optional[pd.DataFrame], Optional[str]]:
+        from portfolio_src.data import shell_decomposition
+
+        try:
+            holdings, source = shell_decomposition.fetch_holdings(isin)
+        except Exception as e:
+            logger.warning(
+                "Shell decomposition lookup failed",
+                extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+            )
+            return None, None
+        if holdings is None:
+            return None, None
+
+        logger.info("Resolved via shell fallback", extra={"isin": isin, "source": source})
+        try:
+            self.holdings_cache._save_to_local_cache(isin, holdings, source=source)
+        except Exception as e:
+            logger.warning(
+                "Failed to cache result",
+                extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+            )
+        return holdings, source
 
     def _resolve_holdings_isins(
         self,
@@ -5,7 +5,7 @@ Enricher Service - Adds sector, geography, and asset class metadata.
 UI-agnostic, reusable with React.
 """
 
-from typing import Dict, List, Tuple, Any, Optional, Callable
+from typing import Collection, Dict, List, Tuple, Any, Optional, Callable
 import pandas as pd
 from dataclasses import dataclass
 
@@ -34,12 +34,19 @@ class HiveEnrichmentService:
     Multi-tier enrichment service that prioritizes Supabase Hive.
 
     Flow: Hive (Community) -> API Fallbacks (Finnhub/yfinance) -> Contribution
+
+    `data_sources` limits the remote tiers ("hive", "apis"); None means all.
+    The local cache is always consulted.
     """
 
-    def __init__(self):
+    def __init__(self, data_sources: Optional[Collection[str]] = None):
         self.hive_client = get_hive_client()
         self.local_cache = get_local_cache()
         self.fallback_service = EnrichmentService()
+        self._data_sources = None if data_sources is None else set(data_sources)
+
+    def _uses(self, source: str) -> bool:
+        return self._data_sources is None or source in self._data_sources
 
     def get_metadata_batch(self, isins: List[str]) -> EnrichmentResult:
         """
@@ -80,7 +87,9 @@ class HiveEnrichmentService:
 
         # Step 2: Try HiveClient.batch_lookup for remaining ISINs
         missing_isins = []
-        if remaining_isins:
+        if remaining_isins and not self._uses("hive"):
+            missing_isins = remaining_isins
+        elif remaining_isins:
             hive_results = self.hive_client.batch_lookup(remaining_isins)
 
             for isin in remaining_isins:
@@ -110,7 +119,12 @@ class HiveEnrichmentService:
 
         contributed_isins: List[str] = []
 
-        if missing_isins:
+        if missing_isins and not self._uses("apis"):
+            logger.info(
+                "Enrichment APIs disabled, leaving ISINs unenriched",
+                extra={"miss_count": len(missing_isins)},
+            )
+        elif missing_isins:
             examples = ", ".join(missing_isins[:3]) + ("..." if len(missing_isins) > 3 else "")
             logger.info(
                 "Hive miss, calling fallback APIs",
@@ -14,6 +14,7 @@ from portfolio_src.models.sync import (
     ClassifiedPosition,
     PipelineResult,
     PortfolioSyncResult,
+    PositionSyncFailure,
 )
 from portfolio_src.prism_utils.logging_config import get_logger
 
@@ -238,12 +239,14 @@ class SyncService:
         self,
         portfolio_id: int,
         progress_callback: Callable[[int, str, str], None] | None = None,
+        isins: list[str] | None = None,
     ) -> PortfolioSyncResult:
         """Synchronize portfolio data from Trade Republic.
 
         Args:
             portfolio_id: Portfolio ID to sync (typically 1).
             progress_callback: Optional callback(progress%, message, phase).
+            isins: Only refresh these positions; None syncs the whole portfolio.
 
         Returns:
             PortfolioSyncResult with sync statistics.
@@ -295,7 +298,9 @@ class SyncService:
         fetcher = TRDataFetcher(bridge)
         emit(30, "Fetching portfolio...", "sync")
 
-        raw_positions = self._fetch_positions_sync(fetcher, executor)
+        raw_positions, failures = self._select_positions(
+            self._fetch_positions_sync(fetcher, executor), isins
+        )
 
         emit(50, f"Processing {len(raw_positions)} positions...", "sync")
 
@@ -344,20 +349,30 @@ class SyncService:
             etf_count=counts["etf"],
             crypto_count=counts["crypto"],
             stock_count=counts["stock"],
+            failures=failures,
         )
 
     def run_pipeline(
         self,
         progress_callback: Callable[[int, str, str], None] | None = None,
+        portfolio_id: int = 1,
+        stages: list[str] | None = None,
+        data_sources: list[str] | None = None,
+        should_cancel: Callable[[], bool] | None = None,
     ) -> PipelineResult:
         """Run the analytics pipeline (decomposition, enrichment, aggregation).
 
         Args:
             progress_callback: Optional callback(progress%, message, phase).
+            portfolio_id: Portfolio whose positions are analyzed.
+            stages: Stages to run; None or empty runs every stage.
+            data_sources: Remote sources the stages may query; None or empty allows all.
+            should_cancel: Polled during the run; True stops it without writing reports.
 
         Returns:
             PipelineResult with success status and any errors.
         """
+        from portfolio_src.core.errors import PipelineCancelled
         from portfolio_src.core.pipeline import Pipeline
 
         def emit(progress: int, message: str, phase: str = "pipeline") -> None:
@@ -371,8 +386,21 @@ class SyncService:
             time.sleep(0.1)  # Small delay to prevent flooding
             emit(int(pct * 100), msg, phase)
 
-        pipeline = Pipeline()
-        result = pipeline.run(pipeline_progress)
+        pipeline = Pipeline(
+            portfolio_id=portfolio_id,
+            stages=stages,
+            data_sources=data_sources,
+            should_cancel=should_cancel,
+        )
+        try:
+            result = pipeline.run(pipeline_progress)
+        except PipelineCancelled:
+            return PipelineResult(
+                success=False,
+                errors=["Pipeline cancelled"],
+                duration_ms=int((time.time() - start_time) * 1000),
+                cancelled=True,
+            )
 
         duration_ms = int((time.time() - start_time) * 1000)
 
@@ -401,6 +429,39 @@ class SyncService:
         future = executor.submit(fetcher.fetch_portfolio_sync)
         return future.result(timeout=120)
 
+    def _select_positions(
+        self, raw_positions: list[dict[str, Any]], isins: list[str] | None
+    ) -> tuple[list[dict[str, Any]], list[PositionSyncFailure]]:
+        """Positions to write, and the ones that cannot be synced.
+
+        Positions without a price are left out so their stored price stays;
+        the quote may be available on a retry. Requested ISINs the broker no
+        longer reports are not retryable.
+        """
+        if isins is not None:
+            wanted = set(isins)
+            found = {pos["isin"] for pos in raw_positions}
+            raw_positions = [pos for pos in raw_positions if pos["isin"] in wanted]
+            failures = [
+                PositionSyncFailure(isin=isin, reason="Not in the Trade Republic portfolio")
+                for isin in isins
+                if isin not in found
+            ]
+        else:
+            failures = []
+
+        selected = []
+        for pos in raw_positions:
+            if pos["quantity"] > 0 and pos["current_price"] <= 0:
+                failures.append(
+                    PositionSyncFailure(
+                        isin=pos["isin"], reason="No price available", retryable=True
+                    )
+                )
+            else:
+                selected.append(pos)
+        return selected, failures
+
     def _classify_positions(
         self, raw_positions: list[dict[str, Any]]
     ) -> tuple[list[ClassifiedPosition], dict[str, int]]:
@@ -1,6 +1,6 @@
 #!/usr/bin/env python3
 """
-Unit tests for Pipeline Services (Decomposer, Enricher, Aggregator).
+Unit tests for Services (Decomposer, Enricher, Aggregator, SyncService).
 Run with: pytest tests/test_services.py -v
 """
 
@@ -17,6 +17,7 @@ sys.path.insert(0, str(Path(__file__).parent.parent / "portfolio_src"))
 from portfolio_src.core.services.decomposer import Decomposer
 from portfolio_src.core.services.enricher import Enricher, EnrichmentResult
 from portfolio_src.core.services.aggregator import Aggregator
+from portfolio_src.core.services.sync_service import SyncService
 from portfolio_src.core.errors import PipelineError, ErrorPhase, ErrorType
 
 
@@ -82,6 +83,41 @@ class TestDecomposer:
         assert errors[0].error_type == ErrorType.NO_ADAPTER
         assert errors[0].phase == ErrorPhase.ETF_DECOMPOSITION
 
+    def test_decompose_falls_back_to_shell_without_adapter(self, setup_decomposer):
+        decomposer, cache, registry = setup_decomposer
+
+        isin = "IE00B4L5Y983"
+        cache.get_holdings.return_value = None
+        registry.get_adapter.return_value = None
+        shell_df = pd.DataFrame([{"isin": "US0378331005", "name": "Apple", "weight": 4.5}])
+
+        with patch(
+            "portfolio_src.data.shell_decomposition.fetch_holdings",
+            return_value=(shell_df, "offline_dataset"),
+        ):
+            holdings_map, errors = decomposer.decompose(pd.DataFrame([{"ISIN": isin}]))
+
+        assert isin in holdings_map
+        assert not errors
+        assert decomposer.get_etf_sources()[isin] == "offline_dataset"
+        cache._save_to_local_cache.assert_called_with(isin, shell_df, source="offline_dataset")
+
+    def test_decompose_without_remote_sources_uses_cache_only(self):
+        cache = MagicMock()
+        registry = MagicMock()
+        decomposer = Decomposer(cache, registry, data_sources=[])
+
+        isin = "IE00B4L5Y983"
+        cache.get_holdings.return_value = None
+
+        with patch("portfolio_src.core.services.decomposer.get_hive_client") as hive:
+            holdings_map, errors = decomposer.decompose(pd.DataFrame([{"ISIN": isin}]))
+
+        assert isin not in holdings_map
+        assert errors[0].error_type == ErrorType.CACHE_MISS
+        hive.assert_not_called()
+        registry.get_adapter.assert_not_called()
+
 
 class TestEnricher:
     """Tests for Enricher service."""
@@ -201,3 +237,35 @@ class TestAggregator:
         assert len(errors) > 0
         assert errors[0].phase == ErrorPhase.AGGREGATION
         assert agg_df.empty
+
+
+class TestSyncServiceSelection:
+    """Tests for the positions a sync writes and the ones it reports."""
+
+    @staticmethod
+    def position(isin, price):
+        return {
+            "isin": isin,
+            "name": isin,
+            "quantity": 2.0,
+            "avg_cost": 10.0,
+            "current_price": price,
+        }
+
+    def test_full_sync_reports_unpriced_positions(self):
+        raw = [self.position("DE0007164600", 120.0), self.position("US0378331005", 0.0)]
+
+        selected, failures = SyncService()._select_positions(raw, None)
+
+        assert [pos["isin"] for pos in selected] == ["DE0007164600"]
+        assert [(f.isin, f.retryable) for f in failures] == [("US0378331005", True)]
+
+    def test_targeted_sync_keeps_requested_isins(self):
+        raw = [self.position("DE0007164600", 120.0), self.position("US0378331005", 180.0)]
+
+        selected, failures = SyncService()._select_positions(
+            raw, ["US0378331005", "IE00B4L5Y983"]
+        )
+
+        assert [pos["isin"] for pos in selected] == ["US0378331005"]
+        assert [(f.isin, f.retryable) for f in failures] == [("IE00B4L5Y983", False)]
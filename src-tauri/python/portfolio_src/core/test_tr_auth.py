@@ -304,6 +304,46 @@ class TestTryRestoreSession:
         assert result.state == AuthState.WAITING_FOR_2FA
 
 
+class TestReconnectSession:
+    """Tests for reconnect_session method."""
+
+    @pytest.mark.asyncio
+    async def test_reconnect_success(self, mock_bridge):
+        mock_bridge.reconnect.return_value = {
+            "status": "authenticated",
+            "message": "Session reconnected",
+        }
+
+        with patch("portfolio_src.core.tr_auth.TRBridge") as mock_bridge_class:
+            mock_bridge_class.get_instance.return_value = mock_bridge
+            manager = TRAuthManager()
+
+            result = await manager.reconnect_session()
+
+        assert result.success is True
+        assert result.state == AuthState.AUTHENTICATED
+        mock_bridge.login.assert_not_called()
+
+    @pytest.mark.asyncio
+    async def test_reconnect_session_expired(self, mock_bridge):
+        mock_bridge.reconnect.return_value = {
+            "status": "error",
+            "code": "SESSION_RESTORE_FAILED",
+        }
+
+        with patch("portfolio_src.core.tr_auth.TRBridge") as mock_bridge_class:
+            mock_bridge_class.get_instance.return_value = mock_bridge
+            manager = TRAuthManager()
+            manager._state = AuthState.AUTHENTICATED
+
+            result = await manager.reconnect_session()
+
+        assert result.success is False
+        assert result.state == AuthState.IDLE
+        assert manager.state == AuthState.IDLE
+        assert "expired" in result.message.lower()
+
+
 class TestCredentialStorage:
     """Tests for credential storage methods."""
 
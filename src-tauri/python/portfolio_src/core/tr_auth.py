@@ -276,6 +276,38 @@ class TRAuthManager:
                 message=f"Session restore failed: {str(e)}",
             )
 
+    async def reconnect_session(self) -> AuthResult:
+        """
+        Reconnect an active session whose websocket may have died, e.g. after
+        the machine slept. Uses the saved cookies; no credentials are sent.
+        """
+        try:
+            loop = asyncio.get_event_loop()
+            result = await loop.run_in_executor(self._executor, self.bridge.reconnect)
+        except Exception as e:
+            return AuthResult(
+                success=False,
+                state=self._state,
+                message=f"Session reconnect failed: {str(e)}",
+            )
+
+        status = result.get("status")
+        if status == "authenticated":
+            self._state = AuthState.AUTHENTICATED
+            return AuthResult(
+                success=True,
+                state=AuthState.AUTHENTICATED,
+                message=result.get("message", "Session reconnected."),
+                session_token="reconnected",
+            )
+        if self._state == AuthState.AUTHENTICATED:
+            self._state = AuthState.IDLE
+        if result.get("code") == "SESSION_RESTORE_FAILED":
+            message = "Session expired. Please log in again."
+        else:
+            message = result.get("message", "Reconnect failed")
+        return AuthResult(success=False, state=AuthState.IDLE, message=message)
+
     def save_credentials(self, phone: str, pin: str) -> bool:
         """Save credentials to local file (User requested file-based storage)."""
         # Force file storage for reliability as requested
@@ -287,15 +319,18 @@ class TRAuthManager:
         return self._load_from_file()
 
     def delete_credentials(self) -> bool:
-        """Remove credentials from keychain and file."""
-        # Clean file
+        """Remove credentials from keychain and file.
+
+        Returns:
+            False when either copy is still there afterwards.
+        """
         try:
             if self.data_dir:
-                cred_file = self.data_dir / "config" / ".credentials.json"
+                cred_file = self._credentials_file()
                 if cred_file.exists():
                     cred_file.unlink()
-        except Exception:
-            pass
+        except OSError:
+            return False
 
         try:
             import keyring
@@ -313,15 +348,20 @@ class TRAuthManager:
         except Exception:
             return False
 
+    def _credentials_file(self) -> Path:
+        """Saved login file; in the app's decrypted session dir when provided."""
+        session_dir = os.getenv("PRISM_SESSION_DIR")
+        base = Path(session_dir) if session_dir else self.data_dir
+        return base / "config" / ".credentials.json"
+
     def _save_to_file(self, phone: str, pin: str) -> bool:
         """Save credentials to a local JSON file (Dev Mode/Fallback)."""
         try:
             if not self.data_dir:
                 self.data_dir = DATA_DIR
 
-            config_dir = self.data_dir / "config"
-            config_dir.mkdir(parents=True, exist_ok=True)
-            cred_file = config_dir / ".credentials.json"
+            cred_file = self._credentials_file()
+            cred_file.parent.mkdir(parents=True, exist_ok=True)
 
             # Simple encoding to avoid plain text staring at you
             import base64
@@ -341,7 +381,7 @@ class TRAuthManager:
             if not self.data_dir:
                 self.data_dir = DATA_DIR
 
-            cred_file = self.data_dir / "config" / ".credentials.json"
+            cred_file = self._credentials_file()
             if not cred_file.exists():
                 return None, None
 
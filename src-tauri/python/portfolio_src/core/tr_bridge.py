@@ -287,6 +287,10 @@ class TRBridge:
         """Get daemon status."""
         return self._send_command(TRMethod.GET_STATUS.value)
 
+    def reconnect(self) -> Dict[str, Any]:
+        """Reconnect the active session, e.g. after the machine slept."""
+        return self._send_command(TRMethod.RECONNECT.value)
+
     def shutdown(self) -> None:
         """Shutdown daemon gracefully."""
         try:
@@ -71,13 +71,20 @@ class TRDaemon:
         else:
             return home / ".local" / "share" / "PortfolioPrism"
 
+    def _get_session_dir(self) -> Path:
+        """Get the session file directory, respecting PRISM_SESSION_DIR env var."""
+        env_dir = os.getenv("PRISM_SESSION_DIR")
+        if env_dir:
+            return Path(env_dir)
+        return self._get_data_dir()
+
     async def _ensure_api(self, phone: Optional[str] = None, pin: Optional[str] = None):
         if self.api is None:
             from pytr.api import TradeRepublicApi
 
-            data_dir = self._get_data_dir()
-            data_dir.mkdir(parents=True, exist_ok=True)
-            cookies_file = data_dir / "tr_cookies.txt"
+            session_dir = self._get_session_dir()
+            session_dir.mkdir(parents=True, exist_ok=True)
+            cookies_file = session_dir / "tr_cookies.txt"
             phone_to_use = phone or self._pending_phone
             pin_to_use = pin or self._pending_pin
             if phone_to_use is None or pin_to_use is None:
@@ -165,7 +172,7 @@ class TRDaemon:
         try:
             self.api = None
             self._cached_auth_status = "idle"
-            cookies_file = self._get_data_dir() / "tr_cookies.txt"
+            cookies_file = self._get_session_dir() / "tr_cookies.txt"
             if cookies_file.exists():
                 cookies_file.unlink()
             return {"status": "logged_out", "message": "Logged out"}
@@ -231,6 +238,38 @@ class TRDaemon:
         """
         return {"status": self._cached_auth_status}
 
+    async def handle_reconnect(self) -> Dict[str, Any]:
+        """
+        Replace the API client of an active session and resume the web session
+        from the saved cookies. After the machine slept the websocket is dead
+        while the cached status still says authenticated.
+        """
+        if self._cached_auth_status != "authenticated":
+            return {"status": self._cached_auth_status, "message": "No active session"}
+
+        old_api, self.api = self.api, None
+        self._cached_auth_status = "idle"
+        ws = getattr(old_api, "_ws", None)
+        if ws is not None:
+            try:
+                await ws.close()
+            except Exception:
+                pass  # Already dead
+
+        try:
+            await self._ensure_api()
+            if self.api is not None and self.api.resume_websession():
+                logger.info("Session reconnected from cookies")
+                self._cached_auth_status = "authenticated"
+                return {"status": "authenticated", "message": "Session reconnected"}
+        except Exception as e:
+            return {"status": "error", "message": f"Reconnect failed: {str(e)}"}
+        return {
+            "status": "error",
+            "message": "Session could not be restored",
+            "code": "SESSION_RESTORE_FAILED",
+        }
+
     async def process_request(self, request: TRRequest) -> str:
         method = request.method
         params = request.params
@@ -249,6 +288,8 @@ class TRDaemon:
                 result = await self.handle_fetch_portfolio()
             elif method == TRMethod.GET_STATUS.value:
                 result = await self.handle_get_status()
+            elif method == TRMethod.RECONNECT.value:
+                result = await self.handle_reconnect()
             elif method == TRMethod.SHUTDOWN.value:
                 sys.exit(0)
             else:
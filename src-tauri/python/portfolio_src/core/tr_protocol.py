@@ -19,6 +19,7 @@ class TRMethod(Enum):
     CONFIRM_2FA = "confirm_2fa"
     FETCH_PORTFOLIO = "fetch_portfolio"
     GET_STATUS = "get_status"
+    RECONNECT = "reconnect"
     SHUTDOWN = "shutdown"
 
 
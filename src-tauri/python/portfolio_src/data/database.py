@@ -96,27 +96,30 @@ def init_db(db_path: Optional[str] = None) -> sqlite3.Connection:
             schema_sql = f.read()
         conn.executescript(schema_sql)
 
-        # Migration: Add new columns to system_logs if they don't exist
+        # Migration: Add new columns to existing tables if they don't exist
         # Wrapped in IMMEDIATE transaction to prevent race conditions
         try:
             conn.execute("BEGIN IMMEDIATE")
-            cursor = conn.execute("PRAGMA table_info(system_logs)")
-            columns = [row["name"] for row in cursor.fetchall()]
-
-            new_cols = [
-                ("component", "TEXT"),
-                ("category", "TEXT"),
-                ("error_hash", "TEXT"),
-                ("reported_at", "DATETIME"),
-            ]
-
-            for col_name, col_type in new_cols:
-                if col_name not in columns:
-                    logger.info(
-                        "Migrating: adding column to system_logs",
-                        extra={"column_name": col_name},
-                    )
-                    conn.execute(f"ALTER TABLE system_logs ADD COLUMN {col_name} {col_type}")
+            new_cols = {
+                "system_logs": [
+                    ("component", "TEXT"),
+                    ("category", "TEXT"),
+                    ("error_hash", "TEXT"),
+                    ("reported_at", "DATETIME"),
+                ],
+                "positions": [("notes", "TEXT")],
+            }
+
+            for table, table_cols in new_cols.items():
+                cursor = conn.execute(f"PRAGMA table_info({table})")
+                columns = [row["name"] for row in cursor.fetchall()]
+                for col_name, col_type in table_cols:
+                    if col_name not in columns:
+                        logger.info(
+                            f"Migrating: adding column to {table}",
+                            extra={"column_name": col_name},
+                        )
+                        conn.execute(f"ALTER TABLE {table} ADD COLUMN {col_name} {col_type}")
 
             conn.commit()
         except Exception as e:
@@ -181,6 +184,18 @@ def transaction():
             raise
 
 
+def vacuum() -> dict:
+    """Compact the database file; returns its size before and after."""
+    db_path = get_db_path()
+    size_before = db_path.stat().st_size if db_path.exists() else 0
+    with get_connection() as conn:
+        # Fold the WAL into the main file first so VACUUM sees all pages
+        conn.execute("PRAGMA wal_checkpoint(TRUNCATE)")
+        conn.execute("VACUUM")
+        conn.execute("PRAGMA wal_checkpoint(TRUNCATE)")
+    return {"size_before": size_before, "size_after": db_path.stat().st_size}
+
+
 # =============================================================================
 # Query Helpers
 # =============================================================================
@@ -206,6 +221,7 @@ def get_positions(portfolio_id: int = 1) -> list[dict]:
                 p.quantity,
                 p.cost_basis,
                 p.current_price,
+                p.notes,
                 p.updated_at,
                 a.name,
                 a.symbol,
@@ -306,6 +322,99 @@ def mark_logs_processed(log_ids: list[int]) -> None:
         conn.commit()
 
 
+def get_last_prices(isins: list[str]) -> dict[str, dict]:
+    """Name and latest stored position price per ISIN, for assets the
+    database knows; the price is None when no position carries one."""
+    if not isins:
+        return {}
+    placeholders = ",".join(["?"] * len(isins))
+    with get_connection() as conn:
+        cursor = conn.execute(
+            f"""
+            SELECT a.isin, a.name,
+                (SELECT p.current_price FROM positions p
+                 WHERE p.isin = a.isin AND p.current_price IS NOT NULL
+                 ORDER BY p.updated_at DESC LIMIT 1) AS price
+            FROM assets a
+            WHERE a.isin IN ({placeholders})
+        """,
+            isins,
+        )
+        return {row["isin"]: {"name": row["name"], "price": row["price"]} for row in cursor}
+
+
+def get_asset(isin: str) -> Optional[dict]:
+    """Stored metadata of an asset, or None if the database does not know it."""
+    with get_connection() as conn:
+        row = conn.execute(
+            """
+            SELECT isin, symbol, name, asset_class, sector, region, country
+            FROM assets WHERE isin = ?
+        """,
+            (isin,),
+        ).fetchone()
+        return dict(row) if row else None
+
+
+def get_price_history(isin: str) -> list[dict]:
+    """Cached daily closes of an asset, oldest first."""
+    with get_connection() as conn:
+        cursor = conn.execute(
+            """
+            SELECT date_str AS date, close_price AS price
+            FROM historical_prices WHERE isin = ?
+            ORDER BY date_str
+        """,
+            (isin,),
+        )
+        return [dict(row) for row in cursor]
+
+
+def save_price_history(isin: str, points: list[tuple[str, float]], currency: str = "EUR") -> None:
+    """Cache daily closes (YYYY-MM-DD, price) of a known asset."""
+    with transaction() as conn:
+        conn.executemany(
+            """
+            INSERT OR REPLACE INTO historical_prices (isin, date_str, close_price, currency)
+            VALUES (?, ?, ?, ?)
+        """,
+            [(isin, date, price, currency) for date, price in points],
+        )
+
+
+def get_transactions(
+    portfolio_id: int,
+    date_from: Optional[str] = None,
+    date_to: Optional[str] = None,
+    types: Optional[list[str]] = None,
+    isin: Optional[str] = None,
+) -> list[dict]:
+    """Ledger entries of a portfolio, oldest first. Dates are inclusive
+    YYYY-MM-DD bounds on the day of the entry."""
+    query = """
+        SELECT id, portfolio_id, isin, type, date, quantity, amount, currency, notes
+        FROM transactions
+        WHERE portfolio_id = ?
+    """
+    params: list = [portfolio_id]
+    if date_from:
+        query += " AND date(date) >= date(?)"
+        params.append(date_from)
+    if date_to:
+        query += " AND date(date) <= date(?)"
+        params.append(date_to)
+    if types:
+        query += f" AND type IN ({','.join(['?'] * len(types))})"
+        params.extend(types)
+    if isin:
+        query += " AND isin = ?"
+        params.append(isin)
+    query += " ORDER BY date, created_at"
+
+    with get_connection() as conn:
+        return [dict(row) for row in conn.execute(query, params).fetchall()]
+
+
 # =============================================================================
 # Write Functions (for TR sync)
 # =============================================================================
@@ -360,6 +469,20 @@ def upsert_position(
         conn.commit()
 
 
+def set_position_note(portfolio_id: int, isin: str, note: Optional[str]) -> bool:
+    """Set or clear (None) the note on a position.
+
+    Returns:
+        False when the portfolio holds no position in the ISIN.
+    """
+    with transaction() as conn:
+        cursor = conn.execute(
+            "UPDATE positions SET notes = ? WHERE portfolio_id = ? AND isin = ?",
+            (note, portfolio_id, isin),
+        )
+        return cursor.rowcount > 0
+
+
 def sync_positions_from_tr(portfolio_id: int, tr_positions: list[dict]) -> dict:
     """
     Bulk sync positions from Trade Republic data.
@@ -461,3 +584,137 @@ def sync_positions_from_tr(portfolio_id: int, tr_positions: list[dict]) -> dict:
         "updated_positions": updated_positions,
         "total_value": round(total_value, 2),
     }
+
+
+def import_broker_holdings(
+    portfolio_id: int,
+    assets: list[dict],
+    positions: list[dict],
+    transactions: list[dict],
+) -> dict:
+    """
+    Merge holdings parsed from a broker statement into a portfolio.
+
+    Args:
+        portfolio_id: Portfolio ID to import into
+        assets: Dicts with isin, name and asset_class; known assets keep
+            their stored metadata
+        positions: Dicts with isin, quantity, cost_basis and current_price
+            (EUR, either may be None); a position replaces the portfolio's
+            position in the same ISIN, missing prices keep the stored ones
+        transactions: Ledger rows with id, isin, type, date, quantity,
+            amount and currency; ids already in the ledger are skipped
+
+    Returns:
+        Dict with new_positions, updated_positions, transactions_imported
+        and duplicate_transactions
+    """
+    new_positions = 0
+    transactions_imported = 0
+
+    with transaction() as conn:
+        existing_isins = {
+            row[0]
+            for row in conn.execute(
+                "SELECT isin FROM positions WHERE portfolio_id = ?", (portfolio_id,)
+            )
+        }
+        conn.executemany(
+            """
+            INSERT INTO assets (isin, name, asset_class) VALUES (?, ?, ?)
+            ON CONFLICT(isin) DO NOTHING
+        """,
+            [(a["isin"], a["name"], a["asset_class"]) for a in assets],
+        )
+
+        for pos in positions:
+            conn.execute(
+                """
+                INSERT INTO positions (portfolio_id, isin, quantity, cost_basis, current_price)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(portfolio_id, isin) DO UPDATE SET
+                    quantity = excluded.quantity,
+                    cost_basis = COALESCE(excluded.cost_basis, positions.cost_basis),
+                    current_price = COALESCE(excluded.current_price, positions.current_price),
+                    updated_at = CURRENT_TIMESTAMP
+            """,
+                (
+                    portfolio_id,
+                    pos["isin"],
+                    pos["quantity"],
+                    pos["cost_basis"],
+                    pos["current_price"],
+                ),
+            )
+            if pos["isin"] not in existing_isins:
+                new_positions += 1
+
+        for tx in transactions:
+            cursor = conn.execute(
+                """
+                INSERT OR IGNORE INTO transactions
+                    (id, portfolio_id, isin, type, date, quantity, amount, currency)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            """,
+                (
+                    tx["id"],
+                    portfolio_id,
+                    tx["isin"],
+                    tx["type"],
+                    tx["date"],
+                    tx["quantity"],
+                    tx["amount"],
+                    tx["currency"],
+                ),
+            )
+            transactions_imported += cursor.rowcount
+
+    return {
+        "new_positions": new_positions,
+        "updated_positions": len(positions) - new_positions,
+        "transactions_imported": transactions_imported,
+        "duplicate_transactions": len(transactions) - transactions_imported,
+    }
+
+
+# =============================================================================
+# Portfolio Management
+# =============================================================================
+
+
+def list_portfolios() -> list[dict]:
+    with get_connection() as conn:
+        cursor = conn.execute("SELECT id, name, currency, created_at FROM portfolios ORDER BY id")
+        return [dict(row) for row in cursor.fetchall()]
+
+
+def create_portfolio(name: str, currency: str = "EUR") -> dict:
+    with transaction() as conn:
+        cursor = conn.execute(
+            "INSERT INTO portfolios (name, currency) VALUES (?, ?)", (name, currency)
+        )
+        row = conn.execute(
+            "SELECT id, name, currency, created_at FROM portfolios WHERE id = ?",
+            (cursor.lastrowid,),
+        ).fetchone()
+        return dict(row)
+
+
+def rename_portfolio(portfolio_id: int, name: str) -> Optional[dict]:
+    """Returns the renamed portfolio, or None when it does not exist."""
+    with transaction() as conn:
+        cursor = conn.execute("UPDATE portfolios SET name = ? WHERE id = ?", (name, portfolio_id))
+        if cursor.rowcount == 0:
+            return None
+    return get_portfolio(portfolio_id)
+
+
+def delete_portfolio(portfolio_id: int) -> bool:
+    """Delete a portfolio; its positions and transactions cascade.
+
+    Returns:
+        False when it does not exist.
+    """
+    with transaction() as conn:
+        cursor = conn.execute("DELETE FROM portfolios WHERE id = ?", (portfolio_id,))
+        return cursor.rowcount > 0
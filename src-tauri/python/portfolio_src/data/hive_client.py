@@ -3,7 +3,8 @@ Hive Client - Community Asset Universe Sync
 
 Syncs with Supabase backend for:
 - Downloading community-contributed normalized asset data (Assets + Listings)
-- Contributing new discoveries via safe RPC functions
+- Contributing new discoveries via safe RPC functions, after the shell
+  reviewed them (see hive_review)
 - Local caching with TTL (Asset-level)
 """
 
@@ -18,6 +19,7 @@ from dataclasses import dataclass
 from dotenv import load_dotenv
 from portfolio_src.prism_utils.logging_config import get_logger
 from portfolio_src.prism_utils.isin_validator import is_valid_isin
+from portfolio_src.data.hive_review import request_review
 
 load_dotenv()
 
@@ -521,79 +523,79 @@ class HiveClient:
     def batch_contribute(self, assets_data: List[AssetEntry]) -> bool:
         """
         Contribute multiple asset entries to the Hive.
-        Uses RPC functions for atomic, safe upserts.
+        Submitted for shell review; uploaded via RPC once approved.
         """
         if not self._is_contribution_allowed():
             return False
-        try:
-            client = self._get_client()
-            if client is None:
-                logger.warning("Cannot contribute assets: Supabase client not available")
-                return False
+        if not self.is_configured:
+            logger.warning("Cannot contribute assets: Supabase client not available")
+            return False
 
-            valid_asset_classes = {"Equity", "ETF", "Cash", "Crypto", "Bond", "Fund"}
+        valid_asset_classes = {"Equity", "ETF", "Cash", "Crypto", "Bond", "Fund"}
 
-            valid_assets = [
-                asset for asset in assets_data if asset.asset_class in valid_asset_classes
-            ]
+        valid_assets = [asset for asset in assets_data if asset.asset_class in valid_asset_classes]
 
-            if not valid_assets:
-                logger.debug("No valid assets to contribute (all have Unknown asset_class)")
-                return True
+        if not valid_assets:
+            logger.debug("No valid assets to contribute (all have Unknown asset_class)")
+            return True
 
-            if len(valid_assets) < len(assets_data):
-                skipped = len(assets_data) - len(valid_assets)
-                logger.debug(
-                    "Skipping assets with invalid asset_class", extra={"skipped_count": skipped}
-                )
+        if len(valid_assets) < len(assets_data):
+            skipped = len(assets_data) - len(valid_assets)
+            logger.debug(
+                "Skipping assets with invalid asset_class", extra={"skipped_count": skipped}
+            )
 
-            assets_dict = [
-                {
-                    "isin": asset.isin,
-                    "name": asset.name,
-                    "asset_class": asset.asset_class,
-                    "base_currency": asset.base_currency,
-                    "enrichment_status": asset.enrichment_status,
-                }
-                for asset in valid_assets
-            ]
+        request_review(
+            "assets",
+            {
+                "assets": [
+                    {
+                        "isin": asset.isin,
+                        "name": asset.name,
+                        "assetClass": asset.asset_class,
+                        "baseCurrency": asset.base_currency,
+                        "enrichmentStatus": asset.enrichment_status,
+                    }
+                    for asset in valid_assets
+                ]
+            },
+            self._upload_assets,
+        )
+        return True
 
-            # Use RPC function for atomic batch upsert
-            response = client.rpc("batch_contribute_assets", {"assets": assets_dict}).execute()
+    def _upload_assets(self, contribution: Dict[str, Any]) -> bool:
+        client = self._get_client()
+        if client is None:
+            return False
 
-            if response.data and response.data[0].get("success"):
-                logger.info(
-                    "Successfully contributed assets to Hive",
-                    extra={"asset_count": len(valid_assets)},
-                )
-                return True
-            else:
-                logger.error(
-                    "Failed to contribute assets",
-                    extra={"response_data": response.data},
-                )
-                return False
-        except Exception as e:
-            logger.error(
-                "Hive batch contribution failed",
-                extra={"error": str(e), "error_type": type(e).__name__},
-                exc_info=True,
+        assets_dict = [
+            {
+                "isin": asset.get("isin"),
+                "name": asset.get("name"),
+                "asset_class": asset.get("assetClass"),
+                "base_currency": asset.get("baseCurrency"),
+                "enrichment_status": asset.get("enrichmentStatus"),
+            }
+            for asset in contribution.get("assets", [])
+        ]
+
+        # Use RPC function for atomic batch upsert
+        response = client.rpc("batch_contribute_assets", {"assets": assets_dict}).execute()
+
+        if response.data and response.data[0].get("success"):
+            logger.info(
+                "Successfully contributed assets to Hive",
+                extra={"asset_count": len(assets_dict)},
             )
-            return False
+            return True
+        logger.error(
+            "Failed to contribute assets",
+            extra={"response_data": response.data},
+        )
+        return False
 
-    def contribute_asset(
-        self,
-        isin: str,
-        ticker: str,
-        exchange: str,
-        name: str,
-        asset_class: str,
-        base_currency: str,
-        trading_currency: str,
-    ) -> HiveResult:
-        """
-        Contribute a new asset record and its primary listing to the Hive.
-        """
+    def _submit(self, kind: str, isin: str, contribution: Dict[str, Any], upload) -> HiveResult:
+        """Common checks of the single-record contributions, then shell review."""
         if not self._is_contribution_allowed():
             return HiveResult(success=False, error="Hive contribution disabled by user")
 
@@ -601,33 +603,19 @@ class HiveClient:
         if not is_valid_isin(isin):
             return HiveResult(success=False, error=f"Invalid ISIN format: {isin}")
 
+        if not self.is_configured:
+            return HiveResult(success=False, error="Supabase client not configured")
+
+        request_id = request_review(kind, contribution, upload)
+        return HiveResult(success=True, data={"requestId": request_id, "pendingReview": True})
+
+    def _rpc_contribution(self, rpc: str, params: Dict[str, Any]) -> HiveResult:
         client = self._get_client()
         if not client:
             return HiveResult(success=False, error="Supabase client not configured")
 
         try:
-            response = client.rpc(
-                "contribute_asset",
-                {
-                    "p_isin": isin,
-                    "p_ticker": ticker,
-                    "p_exchange": exchange,
-                    "p_name": name,
-                    "p_asset_class": asset_class,
-                    "p_base_currency": base_currency,
-                    "p_trading_currency": trading_currency,
-                },
-            ).execute()
-
-            if response.data and response.data[0].get("success"):
-                self._cache_loaded_at = None
-                return HiveResult(success=True, data=response.data[0])
-            else:
-                return HiveResult(
-                    success=False,
-                    error=response.data[0].get("error_message", "Contribution failed at RPC level"),
-                )
-
+            response = client.rpc(rpc, params).execute()
         except Exception as e:
             error_msg = str(e)
             if "policy" in error_msg.lower() or "permission" in error_msg.lower():
@@ -637,6 +625,57 @@ class HiveClient:
                 )
             return HiveResult(success=False, error=f"RPC call failed: {error_msg}")
 
+        if response.data and response.data[0].get("success"):
+            return HiveResult(success=True, data=response.data[0])
+        error = response.data[0].get("error_message") if response.data else None
+        logger.warning("Hive contribution rejected", extra={"rpc": rpc, "error": error})
+        return HiveResult(success=False, error=error or "Contribution failed at RPC level")
+
+    def contribute_asset(
+        self,
+        isin: str,
+        ticker: str,
+        exchange: str,
+        name: str,
+        asset_class: str,
+        base_currency: str,
+        trading_currency: str,
+    ) -> HiveResult:
+        """
+        Contribute a new asset record and its primary listing to the Hive.
+        """
+        return self._submit(
+            "asset",
+            isin,
+            {
+                "isin": isin,
+                "ticker": ticker,
+                "exchange": exchange,
+                "name": name,
+                "assetClass": asset_class,
+                "baseCurrency": base_currency,
+                "tradingCurrency": trading_currency,
+            },
+            self._upload_asset,
+        )
+
+    def _upload_asset(self, contribution: Dict[str, Any]) -> HiveResult:
+        result = self._rpc_contribution(
+            "contribute_asset",
+            {
+                "p_isin": contribution.get("isin"),
+                "p_ticker": contribution.get("ticker"),
+                "p_exchange": contribution.get("exchange"),
+                "p_name": contribution.get("name"),
+                "p_asset_class": contribution.get("assetClass"),
+                "p_base_currency": contribution.get("baseCurrency"),
+                "p_trading_currency": contribution.get("tradingCurrency"),
+            },
+        )
+        if result.success:
+            self._cache_loaded_at = None
+        return result
+
     def contribute_listing(
         self,
         isin: str,
@@ -647,37 +686,23 @@ class HiveClient:
         """
         Contribute a new secondary listing to the Hive.
         """
-        if not self._is_contribution_allowed():
-            return HiveResult(success=False, error="Hive contribution disabled by user")
-
-        # Validate ISIN format before RPC call
-        if not is_valid_isin(isin):
-            return HiveResult(success=False, error=f"Invalid ISIN format: {isin}")
-
-        client = self._get_client()
-        if not client:
-            return HiveResult(success=False, error="Supabase client not configured")
-
-        try:
-            response = client.rpc(
-                "contribute_listing",
-                {
-                    "p_isin": isin,
-                    "p_ticker": ticker,
-                    "p_exchange": exchange,
-                    "p_currency": currency,
-                },
-            ).execute()
+        return self._submit(
+            "listing",
+            isin,
+            {"isin": isin, "ticker": ticker, "exchange": exchange, "currency": currency},
+            self._upload_listing,
+        )
 
-            if response.data and response.data[0].get("success"):
-                return HiveResult(success=True, data=response.data[0])
-            else:
-                return HiveResult(
-                    success=False,
-                    error=response.data[0].get("error_message", "Listing contribution failed"),
-                )
-        except Exception as e:
-            return HiveResult(success=False, error=f"RPC call failed: {str(e)}")
+    def _upload_listing(self, contribution: Dict[str, Any]) -> HiveResult:
+        return self._rpc_contribution(
+            "contribute_listing",
+            {
+                "p_isin": contribution.get("isin"),
+                "p_ticker": contribution.get("ticker"),
+                "p_exchange": contribution.get("exchange"),
+                "p_currency": contribution.get("currency"),
+            },
+        )
 
     def contribute_mapping(
         self,
@@ -688,36 +713,22 @@ class HiveClient:
         """
         Contribute a non-ticker alias to the provider_mappings table.
         """
-        if not self._is_contribution_allowed():
-            return HiveResult(success=False, error="Hive contribution disabled by user")
-
-        # Validate ISIN format before RPC call
-        if not is_valid_isin(isin):
-            return HiveResult(success=False, error=f"Invalid ISIN format: {isin}")
-
-        client = self._get_client()
-        if not client:
-            return HiveResult(success=False, error="Supabase client not configured")
-
-        try:
-            response = client.rpc(
-                "contribute_mapping",
-                {
-                    "p_isin": isin,
-                    "p_provider": provider,
-                    "p_provider_id": provider_id,
-                },
-            ).execute()
+        return self._submit(
+            "mapping",
+            isin,
+            {"isin": isin, "provider": provider, "providerId": provider_id},
+            self._upload_mapping,
+        )
 
-            if response.data and response.data[0].get("success"):
-                return HiveResult(success=True, data=response.data[0])
-            else:
-                return HiveResult(
-                    success=False,
-                    error=response.data[0].get("error_message", "Mapping contribution failed"),
-                )
-        except Exception as e:
-            return HiveResult(success=False, error=f"RPC call failed: {str(e)}")
+    def _upload_mapping(self, contribution: Dict[str, Any]) -> HiveResult:
+        return self._rpc_contribution(
+            "contribute_mapping",
+            {
+                "p_isin": contribution.get("isin"),
+                "p_provider": contribution.get("provider"),
+                "p_provider_id": contribution.get("providerId"),
+            },
+        )
 
     def contribute_alias(
         self,
@@ -732,43 +743,41 @@ class HiveClient:
         currency_source: Optional[str] = None,
         contributor_hash: Optional[str] = None,
     ) -> HiveResult:
-        if not self._is_contribution_allowed():
-            return HiveResult(success=False, error="Hive contribution disabled by user")
-
-        # Validate ISIN format before RPC call
-        if not is_valid_isin(isin):
-            return HiveResult(success=False, error=f"Invalid ISIN format: {isin}")
-
-        client = self._get_client()
-        if not client:
-            return HiveResult(success=False, error="Supabase client not configured")
-
-        try:
-            response = client.rpc(
-                "contribute_alias",
-                {
-                    "p_alias": alias,
-                    "p_isin": isin,
-                    "p_alias_type": alias_type,
-                    "p_language": language,
-                    "p_source": source,
-                    "p_confidence": confidence,
-                    "p_currency": currency,
-                    "p_exchange": exchange,
-                    "p_currency_source": currency_source,
-                    "p_contributor_hash": contributor_hash,
-                },
-            ).execute()
+        # contributor_hash identifies the contributor; the shell strips it
+        return self._submit(
+            "alias",
+            isin,
+            {
+                "alias": alias,
+                "isin": isin,
+                "aliasType": alias_type,
+                "language": language,
+                "source": source,
+                "confidence": confidence,
+                "currency": currency,
+                "exchange": exchange,
+                "currencySource": currency_source,
+                "contributorHash": contributor_hash,
+            },
+            self._upload_alias,
+        )
 
-            if response.data and response.data[0].get("success"):
-                return HiveResult(success=True, data=response.data[0])
-            else:
-                return HiveResult(
-                    success=False,
-                    error=response.data[0].get("error_message", "Alias contribution failed"),
-                )
-        except Exception as e:
-            return HiveResult(success=False, error=f"RPC call failed: {str(e)}")
+    def _upload_alias(self, contribution: Dict[str, Any]) -> HiveResult:
+        return self._rpc_contribution(
+            "contribute_alias",
+            {
+                "p_alias": contribution.get("alias"),
+                "p_isin": contribution.get("isin"),
+                "p_alias_type": contribution.get("aliasType", "name"),
+                "p_language": contribution.get("language"),
+                "p_source": contribution.get("source", "user"),
+                "p_confidence": contribution.get("confidence", 0.80),
+                "p_currency": contribution.get("currency"),
+                "p_exchange": contribution.get("exchange"),
+                "p_currency_source": contribution.get("currencySource"),
+                "p_contributor_hash": contribution.get("contributorHash"),
+            },
+        )
 
     def resolve_ticker(
         self,
@@ -1065,7 +1074,7 @@ class HiveClient:
     def contribute_etf_holdings(self, etf_isin: str, holdings_df: pd.DataFrame) -> bool:
         """
         Contribute ETF holdings to the Hive.
-        Uses RPC for atomic batch upsert.
+        Submitted for shell review; uploaded via RPC once approved.
         """
         if not self._is_contribution_allowed():
             return False
@@ -1078,58 +1087,69 @@ class HiveClient:
             )
             return False
 
-        client = self._get_client()
-        if not client:
+        if not self.is_configured:
             return False
 
-        try:
-            # Transform DataFrame to list of dicts for RPC
-            # We need to match the Supabase schema
-            holdings_list = []
-            for _, row in holdings_df.iterrows():
-                holdings_list.append(
-                    {
-                        "etf_isin": etf_isin,
-                        "holding_isin": str(row.get("isin", row.get("ISIN", ""))),
-                        "holding_name": str(row.get("name", row.get("Name", "Unknown"))),
-                        "weight_percentage": float(
-                            row.get("weight", row.get("Weight", 0.0)) or 0.0
-                        ),
-                        "sector": str(row.get("sector", "Unknown")),
-                        "geography": str(row.get("geography", "Unknown")),
-                    }
-                )
+        # Weights and public constituent data only
+        holdings_list = []
+        for _, row in holdings_df.iterrows():
+            holdings_list.append(
+                {
+                    "isin": str(row.get("isin", row.get("ISIN", ""))),
+                    "name": str(row.get("name", row.get("Name", "Unknown"))),
+                    "weight": float(row.get("weight", row.get("Weight", 0.0)) or 0.0),
+                    "sector": str(row.get("sector", "Unknown")),
+                    "geography": str(row.get("geography", "Unknown")),
+                }
+            )
 
-            if not holdings_list:
-                return False
+        if not holdings_list:
+            return False
 
-            # Use RPC function for atomic batch upsert
-            # This function should handle clearing old holdings and inserting new ones
-            response = client.rpc(
-                "batch_contribute_holdings",
-                {"p_etf_isin": etf_isin, "p_holdings": holdings_list},
-            ).execute()
+        request_review(
+            "holdings",
+            {"etfIsin": etf_isin, "holdings": holdings_list},
+            self._upload_etf_holdings,
+        )
+        return True
 
-            if response.data and response.data[0].get("success"):
-                logger.info(
-                    "Successfully contributed holdings for ETF to Hive",
-                    extra={"holdings_count": len(holdings_list), "etf_isin": etf_isin},
-                )
-                return True
-            else:
-                logger.error(
-                    "Failed to contribute holdings",
-                    extra={"response_data": response.data},
-                )
-                return False
+    def _upload_etf_holdings(self, contribution: Dict[str, Any]) -> bool:
+        client = self._get_client()
+        if not client:
+            return False
 
-        except Exception as e:
-            logger.error(
-                "Hive holdings contribution failed",
-                extra={"etf_isin": etf_isin, "error": str(e), "error_type": type(e).__name__},
-                exc_info=True,
+        etf_isin = contribution.get("etfIsin")
+        # Transform to the Supabase schema
+        holdings_list = [
+            {
+                "etf_isin": etf_isin,
+                "holding_isin": row.get("isin", ""),
+                "holding_name": row.get("name", "Unknown"),
+                "weight_percentage": float(row.get("weight") or 0.0),
+                "sector": row.get("sector", "Unknown"),
+                "geography": row.get("geography", "Unknown"),
+            }
+            for row in contribution.get("holdings", [])
+        ]
+
+        # Use RPC function for atomic batch upsert
+        # This function should handle clearing old holdings and inserting new ones
+        response = client.rpc(
+            "batch_contribute_holdings",
+            {"p_etf_isin": etf_isin, "p_holdings": holdings_list},
+        ).execute()
+
+        if response.data and response.data[0].get("success"):
+            logger.info(
+                "Successfully contributed holdings for ETF to Hive",
+                extra={"holdings_count": len(holdings_list), "etf_isin": etf_isin},
             )
-            return False
+            return True
+        logger.error(
+            "Failed to contribute holdings",
+            extra={"response_data": response.data},
+        )
+        return False
 
     def get_stats(self) -> Dict[str, Any]:
         """Get statistics about the cached universe."""
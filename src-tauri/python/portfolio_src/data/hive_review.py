@@ -0,0 +1,113 @@
+"""
+Hive Contribution Review - Shell-side anonymization round trip
+
+No contribution is uploaded to the Hive directly. Each one is announced to
+the Tauri shell as a `hive_contribution_request` event and kept here until
+the shell answers:
+
+- `hive_contribution_approved` carries the sanitized contribution, which is
+  what gets uploaded (never the original)
+- `hive_contribution_blocked` drops it
+
+Without a shell (headless CLI, tests) no answer arrives and nothing leaves
+the machine.
+"""
+
+import threading
+import uuid
+from collections import OrderedDict
+from typing import Any, Callable, Optional
+
+from portfolio_src.headless.protocol import write_protocol
+from portfolio_src.prism_utils.logging_config import get_logger
+
+logger = get_logger(__name__)
+
+# Contributions without an answer are dropped beyond this many
+MAX_PENDING = 256
+
+Uploader = Callable[[dict[str, Any]], Any]
+
+_pending: "OrderedDict[str, tuple[str, Uploader]]" = OrderedDict()
+_lock = threading.Lock()
+
+
+def request_review(kind: str, contribution: dict[str, Any], upload: Uploader) -> str:
+    """Ask the shell to review a contribution; `upload` runs on approval.
+
+    Args:
+        kind: Contribution kind the shell validates against, e.g. "holdings".
+        contribution: camelCase fields as they would be uploaded.
+        upload: Called with the sanitized contribution once approved.
+
+    Returns:
+        The request id the shell answers with.
+    """
+    request_id = uuid.uuid4().hex
+    with _lock:
+        while len(_pending) >= MAX_PENDING:
+            dropped, (dropped_kind, _) = _pending.popitem(last=False)
+            logger.warning(
+                "Dropping unanswered Hive contribution",
+                extra={"request_id": dropped, "kind": dropped_kind},
+            )
+        _pending[request_id] = (kind, upload)
+
+    write_protocol(
+        {
+            "event": "hive_contribution_request",
+            "data": {"requestId": request_id, "kind": kind, "contribution": contribution},
+        }
+    )
+    return request_id
+
+
+def take(request_id: str) -> Optional[tuple[str, Uploader]]:
+    """Remove a pending contribution; None when it is unknown (already
+    answered or dropped)."""
+    with _lock:
+        return _pending.pop(request_id, None)
+
+
+def upload(kind: str, uploader: Uploader, contribution: dict[str, Any]) -> None:
+    """Upload the sanitized contribution of an approved request."""
+    try:
+        uploader(contribution)
+    except Exception as e:
+        logger.error(
+            "Approved Hive contribution failed",
+            extra={"kind": kind, "error": str(e), "error_type": type(e).__name__},
+            exc_info=True,
+        )
+
+
+def approve(request_id: str, contribution: dict[str, Any]) -> bool:
+    """Upload an approved contribution in the calling thread.
+
+    Returns:
+        False when the request is unknown.
+    """
+    entry = take(request_id)
+    if entry is None:
+        return False
+    upload(entry[0], entry[1], contribution)
+    return True
+
+
+def block(request_id: str, reason: Optional[str]) -> bool:
+    """Drop a contribution the shell blocked.
+
+    Returns:
+        False when the request is unknown (already answered or dropped).
+    """
+    entry = take(request_id)
+    if entry is None:
+        return False
+
+    logger.warning("Hive contribution blocked", extra={"kind": entry[0], "reason": reason})
+    return True
+
+
+def pending_count() -> int:
+    with _lock:
+        return len(_pending)
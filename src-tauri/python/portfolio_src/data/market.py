@@ -2,7 +2,7 @@ import yfinance as yf
 import json
 import os
 import pandas as pd
-from typing import Dict, List, Optional, Any
+from typing import Dict, List, Optional, Any, Tuple
 from portfolio_src.config import CONFIG_DIR
 from portfolio_src.data.hive_client import get_hive_client
 from portfolio_src.prism_utils.logging_config import get_logger
@@ -167,6 +167,11 @@ def _get_fx_rate(from_currency: str, to_currency: str = "EUR") -> float:
     return 1.0
 
 
+def fx_rate_to_eur(currency: str) -> float:
+    """Rate converting amounts in a currency to EUR; 1.0 when unavailable."""
+    return _get_fx_rate(currency, "EUR")
+
+
 def _fetch_prices_batch(tickers: List[str]) -> Dict[str, float]:
     """Robust batch fetching with escalation strategy."""
     prices = {}
@@ -281,6 +286,63 @@ def get_price_map(isins: List[str]) -> Dict[str, float]:
     return result
 
 
+def fetch_price_history(isin: str, period: str = "5y") -> List[Tuple[str, float]]:
+    """Daily closes (YYYY-MM-DD, EUR price), oldest first; empty when no ticker resolves.
+
+    Converted at today's FX rate, which is close enough for return charts.
+    """
+    ticker = resolve_ticker(isin)
+    if not ticker:
+        return []
+
+    hist = yf.Ticker(ticker).history(period=period)
+    if hist is None or hist.empty or "Close" not in hist.columns:
+        return []
+
+    rate = _get_fx_rate(_get_ticker_currency(ticker), "EUR")
+    return [
+        (str(index)[:10], float(close) * rate) for index, close in hist["Close"].dropna().items()
+    ]
+
+
+def fetch_event_calendar(isin: str) -> List[Dict[str, Any]]:
+    """Next ex-dividend and earnings dates of an asset.
+
+    Events carry 'kind' ("exDividend" or "earnings") and 'date' (YYYY-MM-DD);
+    ex-dividend events add the last dividend per share in EUR as
+    'amountPerShare' when known. Empty when no ticker resolves.
+    """
+    ticker = resolve_ticker(isin)
+    if not ticker:
+        return []
+
+    t = yf.Ticker(ticker)
+    calendar = t.calendar
+    if not isinstance(calendar, dict):
+        return []
+
+    events: List[Dict[str, Any]] = []
+    ex_date = calendar.get("Ex-Dividend Date")
+    if ex_date:
+        event: Dict[str, Any] = {"kind": "exDividend", "date": str(ex_date)[:10]}
+        try:
+            amount = t.info.get("lastDividendValue")
+            if amount:
+                rate = _get_fx_rate(_get_ticker_currency(ticker), "EUR")
+                event["amountPerShare"] = float(amount) * rate
+        except Exception:
+            pass
+        events.append(event)
+
+    earnings = calendar.get("Earnings Date") or []
+    if not isinstance(earnings, list):
+        earnings = [earnings]
+    if earnings:
+        events.append({"kind": "earnings", "date": str(earnings[0])[:10]})
+
+    return events
+
+
 def fetch_current_price(isin: str) -> Optional[float]:
     """Helper for single price (legacy support)"""
     res = get_price_map([isin])
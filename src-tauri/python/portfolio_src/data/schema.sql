@@ -52,6 +52,7 @@ CREATE TABLE IF NOT EXISTS positions (
     quantity REAL NOT NULL CHECK (quantity >= 0),
     cost_basis REAL,
     current_price REAL,
+    notes TEXT,
     updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
     PRIMARY KEY (portfolio_id, isin),
     FOREIGN KEY (portfolio_id) REFERENCES portfolios(id) ON DELETE CASCADE,
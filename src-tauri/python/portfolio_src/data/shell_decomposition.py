@@ -0,0 +1,110 @@
+"""
+Shell Decomposition Fallback - Hive cache and offline dataset via the shell
+
+When no adapter can decompose a fund, the engine asks the Tauri shell with a
+`hive_decomposition_request` event. The shell answers with the
+`hive_decomposition_response` command, carrying the fund's constituents from
+its Hive cache or, failing that, from the offline starter dataset.
+
+The asking thread blocks until the answer arrives or the wait times out, so
+requests are only made from pipeline worker threads and only when the stdin
+transport is serving a shell; without one (HTTP bridge, CLI, tests) lookups
+return None immediately.
+"""
+
+import threading
+import uuid
+from typing import Any, Optional
+
+import pandas as pd
+
+from portfolio_src.headless.protocol import write_protocol
+from portfolio_src.prism_utils.logging_config import get_logger
+
+logger = get_logger(__name__)
+
+# Longer than the shell's Hive request timeout, which it may spend first
+RESPONSE_TIMEOUT_SECS = 30.0
+
+_attached = False
+_pending: dict[str, tuple[threading.Event, dict[str, Any]]] = {}
+_lock = threading.Lock()
+
+
+def attach() -> None:
+    """Note that a shell is reading the engine's events."""
+    global _attached
+    _attached = True
+
+
+def request(isin: str, timeout: float = RESPONSE_TIMEOUT_SECS) -> Optional[dict[str, Any]]:
+    """Ask the shell for a fund decomposition and wait for the answer.
+
+    Returns:
+        The shell's answer ('source', 'decomposition', 'error'), or None
+        without a shell or when it does not answer in time.
+    """
+    if not _attached:
+        return None
+
+    request_id = uuid.uuid4().hex
+    answered = threading.Event()
+    answer: dict[str, Any] = {}
+    with _lock:
+        _pending[request_id] = (answered, answer)
+
+    write_protocol(
+        {"event": "hive_decomposition_request", "data": {"requestId": request_id, "isin": isin}}
+    )
+    try:
+        if not answered.wait(timeout):
+            logger.warning("Shell did not answer decomposition request", extra={"isin": isin})
+            return None
+        return answer
+    finally:
+        with _lock:
+            _pending.pop(request_id, None)
+
+
+def resolve(request_id: str, answer: dict[str, Any]) -> bool:
+    """Hand the shell's answer to the waiting request.
+
+    Returns:
+        False when the request is unknown (timed out or never made).
+    """
+    with _lock:
+        entry = _pending.get(request_id)
+    if entry is None:
+        return False
+    answered, slot = entry
+    slot.update(answer)
+    answered.set()
+    return True
+
+
+def fetch_holdings(isin: str) -> tuple[Optional[pd.DataFrame], Optional[str]]:
+    """Constituents of a fund from the shell as [isin, name, weight] rows
+    (weights in percent) with their source ("hive" or "offline_dataset")."""
+    answer = request(isin)
+    decomposition = (answer or {}).get("decomposition")
+    if not isinstance(decomposition, dict):
+        if answer and answer.get("error"):
+            logger.info(
+                "No shell decomposition", extra={"isin": isin, "error": answer.get("error")}
+            )
+        return None, None
+
+    rows = [
+        {
+            "isin": holding.get("isin"),
+            "name": holding.get("name") or holding.get("isin"),
+            "weight": float(holding.get("weight") or 0.0),
+        }
+        for holding in decomposition.get("holdings") or []
+        if isinstance(holding, dict) and holding.get("isin")
+    ]
+    if not rows:
+        return None, None
+
+    source = "offline_dataset" if answer.get("source") == "offlineDataset" else "hive"
+    return pd.DataFrame(rows), source
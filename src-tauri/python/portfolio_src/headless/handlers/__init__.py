@@ -16,6 +16,12 @@ Handler Domains:
     - sync: Portfolio synchronization and pipeline
     - holdings: ETF holdings and true exposure analysis
     - telemetry: Logging and error reporting
+    - hive: Shell review of outgoing Hive contributions, shell decompositions
+    - portfolios: Portfolio creation, renaming and deletion, position notes
+    - transactions: Transaction ledger
+    - imports: Broker statement imports
+    - market: Current prices, instrument details and event calendar
+    - maintenance: Database housekeeping
 """
 
 from typing import Any, Callable, Coroutine, Union
@@ -31,13 +37,17 @@ from portfolio_src.headless.handlers.tr_auth import (
     handle_tr_check_saved_session,
     handle_tr_get_stored_credentials,
     handle_tr_restore_session,
+    handle_tr_reconnect_session,
     handle_tr_login,
     handle_tr_submit_2fa,
     handle_tr_logout,
+    handle_clear_credentials,
 )
 from portfolio_src.headless.handlers.sync import (
     handle_sync_portfolio,
     handle_run_pipeline,
+    handle_get_pipeline_capabilities,
+    handle_cancel_pipeline,
 )
 from portfolio_src.headless.handlers.holdings import (
     handle_upload_holdings,
@@ -45,6 +55,7 @@ from portfolio_src.headless.handlers.holdings import (
     handle_commit_holdings_upload,
     handle_get_true_holdings,
     handle_get_pipeline_report,
+    handle_upload_holdings_chunk,
 )
 from portfolio_src.headless.handlers.telemetry import (
     handle_log_event,
@@ -54,6 +65,33 @@ from portfolio_src.headless.handlers.telemetry import (
 from portfolio_src.headless.handlers.settings import (
     handle_set_hive_contribution,
     handle_get_hive_contribution,
+    handle_configure,
+)
+from portfolio_src.headless.handlers.hive import (
+    handle_hive_contribution_approved,
+    handle_hive_contribution_blocked,
+    handle_hive_decomposition_response,
+)
+from portfolio_src.headless.handlers.portfolios import (
+    handle_list_portfolios,
+    handle_create_portfolio,
+    handle_rename_portfolio,
+    handle_delete_portfolio,
+    handle_set_position_note,
+)
+from portfolio_src.headless.handlers.transactions import (
+    handle_get_transactions,
+)
+from portfolio_src.headless.handlers.imports import (
+    handle_import_holdings,
+)
+from portfolio_src.headless.handlers.market import (
+    handle_get_quotes,
+    handle_get_asset_details,
+    handle_get_event_calendar,
+)
+from portfolio_src.headless.handlers.maintenance import (
+    handle_vacuum_database,
 )
 
 # Type alias for handler functions
@@ -75,18 +113,23 @@ HANDLER_REGISTRY: dict[str, HandlerFunc] = {
     "tr_check_saved_session": handle_tr_check_saved_session,
     "tr_get_stored_credentials": handle_tr_get_stored_credentials,
     "tr_restore_session": handle_tr_restore_session,
+    "tr_reconnect_session": handle_tr_reconnect_session,
     "tr_login": handle_tr_login,
     "tr_submit_2fa": handle_tr_submit_2fa,
     "tr_logout": handle_tr_logout,
+    "clear_credentials": handle_clear_credentials,
     # Sync
     "sync_portfolio": handle_sync_portfolio,
     "run_pipeline": handle_run_pipeline,
+    "get_pipeline_capabilities": handle_get_pipeline_capabilities,
+    "cancel_pipeline": handle_cancel_pipeline,
     # Holdings
     "upload_holdings": handle_upload_holdings,
     "preview_holdings_upload": handle_preview_holdings_upload,
     "commit_holdings_upload": handle_commit_holdings_upload,
     "get_true_holdings": handle_get_true_holdings,
     "get_pipeline_report": handle_get_pipeline_report,
+    "upload_holdings_chunk": handle_upload_holdings_chunk,
     # Telemetry
     "log_event": handle_log_event,
     "get_recent_reports": handle_get_recent_reports,
@@ -94,6 +137,27 @@ HANDLER_REGISTRY: dict[str, HandlerFunc] = {
     # Settings
     "set_hive_contribution": handle_set_hive_contribution,
     "get_hive_contribution": handle_get_hive_contribution,
+    "configure": handle_configure,
+    # Hive review
+    "hive_contribution_approved": handle_hive_contribution_approved,
+    "hive_contribution_blocked": handle_hive_contribution_blocked,
+    "hive_decomposition_response": handle_hive_decomposition_response,
+    # Portfolios
+    "list_portfolios": handle_list_portfolios,
+    "create_portfolio": handle_create_portfolio,
+    "rename_portfolio": handle_rename_portfolio,
+    "delete_portfolio": handle_delete_portfolio,
+    "set_position_note": handle_set_position_note,
+    # Transactions
+    "get_transactions": handle_get_transactions,
+    # Broker imports
+    "import_holdings": handle_import_holdings,
+    # Market data
+    "get_quotes": handle_get_quotes,
+    "get_asset_details": handle_get_asset_details,
+    "get_event_calendar": handle_get_event_calendar,
+    # Maintenance
+    "vacuum_database": handle_vacuum_database,
 }
 
 __all__ = [
@@ -110,18 +174,23 @@ __all__ = [
     "handle_tr_check_saved_session",
     "handle_tr_get_stored_credentials",
     "handle_tr_restore_session",
+    "handle_tr_reconnect_session",
     "handle_tr_login",
     "handle_tr_submit_2fa",
     "handle_tr_logout",
+    "handle_clear_credentials",
     # Sync
     "handle_sync_portfolio",
     "handle_run_pipeline",
+    "handle_get_pipeline_capabilities",
+    "handle_cancel_pipeline",
     # Holdings
     "handle_upload_holdings",
     "handle_preview_holdings_upload",
     "handle_commit_holdings_upload",
     "handle_get_true_holdings",
     "handle_get_pipeline_report",
+    "handle_upload_holdings_chunk",
     # Telemetry
     "handle_log_event",
     "handle_get_recent_reports",
@@ -129,4 +198,25 @@ __all__ = [
     # Settings
     "handle_set_hive_contribution",
     "handle_get_hive_contribution",
+    "handle_configure",
+    # Hive review
+    "handle_hive_contribution_approved",
+    "handle_hive_contribution_blocked",
+    "handle_hive_decomposition_response",
+    # Portfolios
+    "handle_list_portfolios",
+    "handle_create_portfolio",
+    "handle_rename_portfolio",
+    "handle_delete_portfolio",
+    "handle_set_position_note",
+    # Transactions
+    "handle_get_transactions",
+    # Broker imports
+    "handle_import_holdings",
+    # Market data
+    "handle_get_quotes",
+    "handle_get_asset_details",
+    "handle_get_event_calendar",
+    # Maintenance
+    "handle_vacuum_database",
 ]
@@ -0,0 +1,73 @@
+"""Hive Review and Decomposition Handlers.
+
+The shell's answers to `hive_contribution_request` events: an approval with
+the sanitized contribution to upload, or a block with the reason. Also its
+answers to `hive_decomposition_request` events with a fund decomposition.
+"""
+
+import threading
+from typing import Any
+
+from portfolio_src.data import hive_review, shell_decomposition
+from portfolio_src.headless.responses import error_response, success_response
+
+
+def handle_hive_contribution_approved(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Upload an approved contribution in the background.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: 'requestId' and the sanitized 'contribution'.
+    """
+    request_id = payload.get("requestId")
+    contribution = payload.get("contribution")
+
+    if not request_id or not isinstance(contribution, dict):
+        return error_response(cmd_id, "INVALID_PARAMS", "requestId and contribution are required")
+
+    entry = hive_review.take(str(request_id))
+    if entry is None:
+        return error_response(cmd_id, "UNKNOWN_REQUEST", f"No pending contribution {request_id}")
+
+    # The upload talks to Supabase; the command loop does not wait for it
+    kind, uploader = entry
+    threading.Thread(
+        target=hive_review.upload,
+        args=(kind, uploader, contribution),
+        name="hive-contribution",
+        daemon=True,
+    ).start()
+    return success_response(cmd_id, {"requestId": request_id, "kind": kind, "uploading": True})
+
+
+def handle_hive_contribution_blocked(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Drop a contribution the shell blocked.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: 'requestId' and the 'reason'.
+    """
+    request_id = payload.get("requestId")
+    if not request_id:
+        return error_response(cmd_id, "INVALID_PARAMS", "requestId is required")
+
+    if not hive_review.block(str(request_id), payload.get("reason")):
+        return error_response(cmd_id, "UNKNOWN_REQUEST", f"No pending contribution {request_id}")
+    return success_response(cmd_id, {"requestId": request_id, "dropped": True})
+
+
+def handle_hive_decomposition_response(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Hand a fund decomposition to the pipeline run waiting for it.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: 'requestId' and the 'decomposition' with its 'source', or
+            a null decomposition with the 'error'.
+    """
+    request_id = payload.get("requestId")
+    if not request_id:
+        return error_response(cmd_id, "INVALID_PARAMS", "requestId is required")
+
+    if not shell_decomposition.resolve(str(request_id), payload):
+        return error_response(cmd_id, "UNKNOWN_REQUEST", f"No pending decomposition {request_id}")
+    return success_response(cmd_id, {"requestId": request_id, "delivered": True})
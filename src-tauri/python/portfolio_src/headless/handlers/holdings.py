@@ -16,6 +16,11 @@ from portfolio_src.prism_utils.logging_config import get_logger
 logger = get_logger(__name__)
 PIPELINE_REPORT_VERSION = 1
 
+# Batches of rows sent with `upload_holdings_chunk`, by upload id, until the
+# upload or preview command that references them
+_CHUNKED_UPLOADS: dict[str, dict[str, Any]] = {}
+MAX_PENDING_UPLOADS = 4
+
 
 class HoldingsUploadError(Exception):
     """Typed upload error that preserves stable IPC error codes."""
@@ -82,6 +87,74 @@ def _prepare_clean_holdings(file_path: str) -> pd.DataFrame:
         joined = ", ".join(missing_columns)
         raise ValueError(f"Missing required normalized columns: {joined}")
 
+    return _normalize_holdings(df_clean)
+
+
+def _prepare_row_holdings(rows: list[Any]) -> pd.DataFrame:
+    """Clean holdings rows the shell already converted (e.g. from .xlsx)."""
+    if not rows:
+        raise HoldingsUploadError("CLEANUP_FAILED", "No valid holdings found in file")
+
+    df_clean = pd.DataFrame(rows)
+    missing_columns = [column for column in ("isin", "name", "weight") if column not in df_clean.columns]
+    if missing_columns:
+        joined = ", ".join(missing_columns)
+        raise HoldingsUploadError("INVALID_PARAMS", f"Missing holding fields: {joined}")
+
+    return _normalize_holdings(df_clean)
+
+
+def _has_source(payload: dict[str, Any]) -> bool:
+    return bool(payload.get("filePath") or payload.get("uploadId")) or "holdings" in payload
+
+
+def _load_upload(payload: dict[str, Any]) -> tuple[pd.DataFrame, str]:
+    """Clean holdings of an upload payload and the name of its file.
+
+    The payload carries either `filePath` for the engine to read, or the
+    rows with the original `fileName`: in `holdings`, or as the batches of
+    `uploadId` sent earlier with `upload_holdings_chunk`.
+    """
+    file_path = payload.get("filePath")
+    if file_path:
+        return _prepare_clean_holdings(file_path), os.path.basename(file_path)
+
+    upload_id = payload.get("uploadId")
+    if upload_id:
+        rows = _take_chunked_rows(str(upload_id), payload)
+        return _prepare_row_holdings(rows), os.path.basename(str(payload.get("fileName") or ""))
+
+    rows = payload.get("holdings")
+    if not isinstance(rows, list):
+        raise HoldingsUploadError("INVALID_PARAMS", "filePath or holdings is required")
+    return _prepare_row_holdings(rows), os.path.basename(str(payload.get("fileName") or ""))
+
+
+def _take_chunked_rows(upload_id: str, payload: dict[str, Any]) -> list[Any]:
+    """Remove a chunked upload from the buffer and return its rows in order."""
+    upload = _CHUNKED_UPLOADS.pop(upload_id, None)
+    if upload is None:
+        raise HoldingsUploadError("UPLOAD_INCOMPLETE", f"Unknown upload {upload_id}")
+    if upload["etfIsin"] != payload.get("etfIsin"):
+        raise HoldingsUploadError("INVALID_PARAMS", "Upload belongs to a different ETF")
+
+    missing = [index for index in range(upload["batches"]) if index not in upload["chunks"]]
+    if missing:
+        raise HoldingsUploadError(
+            "UPLOAD_INCOMPLETE",
+            f"Upload {upload_id} is missing {len(missing)} of {upload['batches']} batches",
+        )
+
+    rows = [row for index in range(upload["batches"]) for row in upload["chunks"][index]]
+    row_count = payload.get("rowCount")
+    if row_count is not None and row_count != len(rows):
+        raise HoldingsUploadError(
+            "UPLOAD_INCOMPLETE", f"Expected {row_count} rows, received {len(rows)}"
+        )
+    return rows
+
+
+def _normalize_holdings(df_clean: pd.DataFrame) -> pd.DataFrame:
     df_clean = df_clean.copy()
     df_clean["isin"] = df_clean["isin"].astype(str).str.strip()
     df_clean["name"] = df_clean["name"].astype(str).str.strip()
@@ -117,23 +190,25 @@ def handle_upload_holdings(cmd_id: int, payload: dict[str, Any]) -> dict[str, An
 
     Args:
         cmd_id: IPC command identifier.
-        payload: Must contain 'filePath' and 'etfIsin'.
+        payload: Must contain 'etfIsin' and either 'filePath' or the
+            converted rows in 'holdings' with their 'fileName'.
 
     Returns:
         Success response with upload results, or error response.
     """
-    file_path = payload.get("filePath")
     etf_isin = payload.get("etfIsin")
 
-    if not file_path or not etf_isin:
-        return error_response(cmd_id, "INVALID_PARAMS", "filePath and etfIsin are required")
+    if not etf_isin or not _has_source(payload):
+        return error_response(
+            cmd_id, "INVALID_PARAMS", "etfIsin and filePath, holdings or uploadId are required"
+        )
 
     try:
+        df_clean, file_name = _load_upload(payload)
         logger.info(
-            "Uploading holdings for ETF", extra={"etf_isin": etf_isin, "file_path": file_path}
+            "Uploading holdings for ETF", extra={"etf_isin": etf_isin, "file_name": file_name}
         )
 
-        df_clean = _prepare_clean_holdings(file_path)
         total_weight = float(df_clean["weight"].sum())
         contribution_success = _save_holdings_to_cache(etf_isin, df_clean)
 
@@ -168,16 +243,71 @@ def handle_upload_holdings(cmd_id: int, payload: dict[str, Any]) -> dict[str, An
         return error_response(cmd_id, "UPLOAD_FAILED", str(e))
 
 
+def handle_upload_holdings_chunk(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Buffer one batch of a large upload converted by the shell.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: 'uploadId', 'etfIsin', the batch's 'sequence' (from 0), the
+            number of 'batches' and its 'holdings' rows.
+
+    Returns:
+        Success response with the number of batches received so far.
+    """
+    upload_id = payload.get("uploadId")
+    etf_isin = payload.get("etfIsin")
+    sequence = payload.get("sequence")
+    batches = payload.get("batches")
+    rows = payload.get("holdings")
+
+    if (
+        not upload_id
+        or not etf_isin
+        or not isinstance(sequence, int)
+        or not isinstance(batches, int)
+        or not isinstance(rows, list)
+    ):
+        return error_response(
+            cmd_id,
+            "INVALID_PARAMS",
+            "uploadId, etfIsin, sequence, batches and holdings are required",
+        )
+    if batches < 1 or not 0 <= sequence < batches:
+        return error_response(
+            cmd_id, "INVALID_PARAMS", f"Batch {sequence} is outside 0..{batches - 1}"
+        )
+
+    upload = _CHUNKED_UPLOADS.get(upload_id)
+    if upload is None or sequence == 0:
+        # Abandoned uploads would otherwise stay in memory until the engine stops
+        while len(_CHUNKED_UPLOADS) >= MAX_PENDING_UPLOADS:
+            _CHUNKED_UPLOADS.pop(next(iter(_CHUNKED_UPLOADS)))
+        upload = {"etfIsin": etf_isin, "batches": batches, "chunks": {}}
+        _CHUNKED_UPLOADS[upload_id] = upload
+    elif upload["etfIsin"] != etf_isin or upload["batches"] != batches:
+        return error_response(
+            cmd_id, "INVALID_PARAMS", "Batch does not match the upload it continues"
+        )
+
+    upload["chunks"][sequence] = rows
+    return success_response(
+        cmd_id,
+        {"uploadId": upload_id, "received": len(upload["chunks"]), "batches": batches},
+    )
+
+
 def handle_preview_holdings_upload(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
     """Preview holdings upload before saving it to cache."""
     file_path = payload.get("filePath")
     etf_isin = payload.get("etfIsin")
 
-    if not file_path or not etf_isin:
-        return error_response(cmd_id, "INVALID_PARAMS", "filePath and etfIsin are required")
+    if not etf_isin or not _has_source(payload):
+        return error_response(
+            cmd_id, "INVALID_PARAMS", "etfIsin and filePath, holdings or uploadId are required"
+        )
 
     try:
-        df_clean = _prepare_clean_holdings(file_path)
+        df_clean, file_name = _load_upload(payload)
         total_weight = float(df_clean["weight"].sum())
         rows = _build_preview_rows(df_clean)
         warnings = _build_preview_warnings(df_clean, total_weight)
@@ -186,7 +316,7 @@ def handle_preview_holdings_upload(cmd_id: int, payload: dict[str, Any]) -> dict
             "Generated holdings preview",
             extra={
                 "etf_isin": etf_isin,
-                "file_path": file_path,
+                "file_name": file_name,
                 "holdings_count": len(rows),
             },
         )
@@ -196,13 +326,15 @@ def handle_preview_holdings_upload(cmd_id: int, payload: dict[str, Any]) -> dict
             {
                 "isin": etf_isin,
                 "filePath": file_path,
-                "fileName": os.path.basename(file_path),
+                "fileName": file_name,
                 "holdingsCount": len(rows),
                 "totalWeight": round(total_weight, 2),
                 "warnings": warnings,
                 "rows": rows,
             },
         )
+    except HoldingsUploadError as e:
+        return error_response(cmd_id, e.code, str(e))
     except Exception as e:
         logger.error(
             "Holdings preview failed",
@@ -0,0 +1,159 @@
+"""Broker Import Handlers.
+
+Merges holdings and transactions the shell parsed from broker statements
+(DeGiro, IBKR, Scalable Capital, comdirect) into a portfolio, so a second
+account shows up in the same dashboard. The shell normalizes ISINs, dates
+and currencies; prices are converted to EUR here.
+"""
+
+import asyncio
+import hashlib
+from typing import Any
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.sync import emit_invalidated
+from portfolio_src.headless.responses import error_response, success_response
+from portfolio_src.headless.state import get_executor
+from portfolio_src.prism_utils.logging_config import get_logger
+from portfolio_src.prism_utils.validation import is_valid_isin
+
+logger = get_logger(__name__)
+
+TRANSACTION_TYPES = {"Buy", "Sell", "Dividend", "Interest", "Fee", "Transfer"}
+
+
+def _transaction_id(broker: str, tx: dict[str, Any]) -> str:
+    """Stable ledger id, so importing the same statement twice adds nothing.
+
+    Uses the broker's order reference when the statement has one, otherwise
+    a digest of the entry itself.
+    """
+    if tx.get("reference"):
+        return f"{broker}:{tx['reference']}"
+    key = "|".join(
+        str(tx.get(field)) for field in ("isin", "type", "date", "quantity", "amount", "currency")
+    )
+    return f"{broker}:{hashlib.sha256(key.encode()).hexdigest()[:16]}"
+
+
+def _in_eur(value: Any, rate: float) -> float | None:
+    return None if value is None else float(value) * rate
+
+
+def _import(
+    portfolio_id: int,
+    broker: str,
+    positions: list[dict[str, Any]],
+    transactions: list[dict[str, Any]],
+) -> dict[str, Any]:
+    from portfolio_src.core.services import AssetClassifier
+    from portfolio_src.data.market import fx_rate_to_eur
+
+    # Cash entries (interest, fees) without an instrument have no asset row
+    ledger = [
+        tx
+        for tx in transactions
+        if is_valid_isin(tx.get("isin") or "") and tx.get("type") in TRANSACTION_TYPES
+    ]
+
+    names: dict[str, str] = {}
+    for entry in [*positions, *ledger]:
+        names.setdefault(entry["isin"], entry.get("name") or entry["isin"])
+    classifier = AssetClassifier()
+    assets = [
+        {"isin": isin, "name": name, "asset_class": classifier.classify(isin, name).value}
+        for isin, name in names.items()
+        if database.get_asset(isin) is None
+    ]
+
+    rates: dict[str, float] = {}
+    db_positions = []
+    for pos in positions:
+        currency = pos.get("currency") or "EUR"
+        rate = rates.setdefault(currency, fx_rate_to_eur(currency))
+        db_positions.append(
+            {
+                "isin": pos["isin"],
+                "quantity": float(pos["quantity"]),
+                "cost_basis": _in_eur(pos.get("avgBuyPrice"), rate),
+                "current_price": _in_eur(pos.get("price"), rate),
+            }
+        )
+
+    db_transactions = [
+        {
+            "id": _transaction_id(broker, tx),
+            "isin": tx["isin"],
+            "type": tx["type"],
+            "date": tx["date"],
+            "quantity": tx.get("quantity"),
+            "amount": float(tx["amount"]),
+            "currency": tx.get("currency") or "EUR",
+        }
+        for tx in ledger
+    ]
+
+    result = database.import_broker_holdings(portfolio_id, assets, db_positions, db_transactions)
+    result["skipped_transactions"] = len(transactions) - len(ledger)
+    return result
+
+
+async def handle_import_holdings(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Merge a parsed broker statement into a portfolio.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'portfolioId' and 'broker'; 'positions'
+            (isin, name, quantity, avgBuyPrice, price, currency) replace the
+            portfolio's positions in the same ISINs, 'transactions' (isin,
+            name, type, date, quantity, amount, currency, reference) are
+            added to the ledger unless already imported.
+
+    Returns:
+        Success response with import counts, or error response.
+    """
+    portfolio_id = payload.get("portfolioId")
+    if isinstance(portfolio_id, bool) or not isinstance(portfolio_id, int):
+        return error_response(cmd_id, "INVALID_PARAMS", "portfolioId is required")
+    broker = payload.get("broker")
+    if not isinstance(broker, str) or not broker:
+        return error_response(cmd_id, "INVALID_PARAMS", "broker is required")
+    positions = payload.get("positions") or []
+    transactions = payload.get("transactions") or []
+    if not isinstance(positions, list) or not isinstance(transactions, list):
+        return error_response(
+            cmd_id, "INVALID_PARAMS", "positions and transactions must be lists"
+        )
+    invalid = [pos.get("isin") for pos in positions if not is_valid_isin(pos.get("isin") or "")]
+    if invalid:
+        return error_response(cmd_id, "INVALID_PARAMS", f"Invalid ISIN: {invalid[0]}")
+    if database.get_portfolio(portfolio_id) is None:
+        return error_response(
+            cmd_id, "PORTFOLIO_NOT_FOUND", f"Portfolio {portfolio_id} does not exist"
+        )
+
+    loop = asyncio.get_event_loop()
+    try:
+        result = await loop.run_in_executor(
+            get_executor(), _import, portfolio_id, broker, positions, transactions
+        )
+    except Exception as e:
+        logger.error(
+            "Broker import failed",
+            extra={"broker": broker, "error": str(e), "error_type": type(e).__name__},
+            exc_info=True,
+        )
+        return error_response(cmd_id, "IMPORT_FAILED", str(e))
+
+    logger.info("Broker statement imported", extra={"broker": broker, **result})
+    emit_invalidated("positions")
+    return success_response(
+        cmd_id,
+        {
+            "newPositions": result["new_positions"],
+            "updatedPositions": result["updated_positions"],
+            "transactionsImported": result["transactions_imported"],
+            "duplicateTransactions": result["duplicate_transactions"],
+            "skippedTransactions": result["skipped_transactions"],
+        },
+    )
@@ -0,0 +1,41 @@
+"""Maintenance Handlers.
+
+Housekeeping the shell's maintenance coordinator runs in its nightly window.
+"""
+
+import asyncio
+from typing import Any
+
+from portfolio_src.data import database
+from portfolio_src.headless.responses import error_response, success_response
+from portfolio_src.headless.state import get_executor
+from portfolio_src.prism_utils.logging_config import get_logger
+
+logger = get_logger(__name__)
+
+
+async def handle_vacuum_database(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Compact the SQLite database.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Command payload (unused).
+
+    Returns:
+        Success response with the file size before and after, or error response.
+    """
+    try:
+        loop = asyncio.get_event_loop()
+        sizes = await loop.run_in_executor(get_executor(), database.vacuum)
+    except Exception as e:
+        logger.error(
+            "Database vacuum failed",
+            extra={"error": str(e), "error_type": type(e).__name__},
+            exc_info=True,
+        )
+        return error_response(cmd_id, "VACUUM_FAILED", str(e))
+
+    logger.info("Database compacted", extra=sizes)
+    return success_response(
+        cmd_id, {"sizeBefore": sizes["size_before"], "sizeAfter": sizes["size_after"]}
+    )
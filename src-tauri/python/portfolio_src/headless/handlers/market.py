@@ -0,0 +1,176 @@
+"""Market Data Handlers.
+
+Serves current prices for the shell's price alerts, instrument details
+with price history for the asset view and custom benchmarks, and the
+dividend and earnings calendar for event alerts.
+"""
+
+import asyncio
+from datetime import date
+from typing import Any
+
+from portfolio_src.data import database
+from portfolio_src.headless.responses import error_response, success_response
+from portfolio_src.headless.state import get_executor
+from portfolio_src.prism_utils.logging_config import get_logger
+from portfolio_src.prism_utils.validation import is_valid_isin
+
+logger = get_logger(__name__)
+
+
+def _quotes(isins: list[str]) -> list[dict[str, Any]]:
+    """Live EUR prices where a ticker resolves, else the last synced price."""
+    stored = database.get_last_prices(isins)
+    try:
+        from portfolio_src.data.market import get_price_map
+
+        live = get_price_map(isins)
+    except Exception as e:
+        logger.warning(
+            "Live prices unavailable, using stored prices",
+            extra={"error": str(e), "error_type": type(e).__name__},
+        )
+        live = {}
+
+    quotes = []
+    for isin in isins:
+        known = stored.get(isin, {})
+        price = live.get(isin, known.get("price"))
+        if price is None:
+            continue
+        quotes.append({"isin": isin, "name": known.get("name"), "price": float(price)})
+    return quotes
+
+
+async def handle_get_quotes(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Get current prices.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'isins'.
+
+    Returns:
+        Success response with one quote per ISIN that has a price; ISINs
+        without one are left out.
+    """
+    isins = payload.get("isins")
+    if not isinstance(isins, list) or not all(isinstance(isin, str) for isin in isins):
+        return error_response(cmd_id, "INVALID_PARAMS", "isins must be a list of ISINs")
+
+    loop = asyncio.get_event_loop()
+    quotes = await loop.run_in_executor(get_executor(), _quotes, isins)
+    return success_response(cmd_id, {"quotes": quotes})
+
+
+def _price_history(isin: str, known: bool) -> list[dict[str, Any]]:
+    """Fresh daily EUR closes, cached for known assets; the cache when offline."""
+    try:
+        from portfolio_src.data.market import fetch_price_history
+
+        points = fetch_price_history(isin)
+    except Exception as e:
+        logger.warning(
+            "Price history unavailable, using cached prices",
+            extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+        )
+        points = []
+
+    if points and known:
+        database.save_price_history(isin, points)
+    if points:
+        return [{"date": date, "price": price} for date, price in points]
+    return database.get_price_history(isin)
+
+
+def _asset_details(isin: str) -> dict[str, Any] | None:
+    asset = database.get_asset(isin)
+    history = _price_history(isin, known=asset is not None)
+    if asset is None and not history:
+        return None
+
+    asset = asset or {}
+    return {
+        "name": asset.get("name"),
+        "ticker": asset.get("symbol") or None,
+        "instrumentType": asset.get("asset_class"),
+        "currency": "EUR",
+        "sector": asset.get("sector"),
+        "country": asset.get("country") or asset.get("region"),
+        "priceHistory": history,
+    }
+
+
+async def handle_get_asset_details(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Get an instrument's metadata and daily price history.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'isin'.
+
+    Returns:
+        Success response with name, ticker, instrumentType, sector, country
+        and 'priceHistory' (date, EUR price; oldest first). NOT_FOUND when
+        the asset is neither stored nor priced.
+    """
+    isin = payload.get("isin")
+    if not isinstance(isin, str) or not is_valid_isin(isin):
+        return error_response(cmd_id, "INVALID_PARAMS", "isin must be a valid ISIN")
+
+    loop = asyncio.get_event_loop()
+    details = await loop.run_in_executor(get_executor(), _asset_details, isin)
+    if details is None:
+        return error_response(cmd_id, "NOT_FOUND", f"No data for {isin}")
+    return success_response(cmd_id, details)
+
+
+def _event_calendar(isins: list[str], date_from: str, date_to: str) -> list[dict[str, Any]]:
+    """Events of the ISINs dated within [date_from, date_to], soonest first."""
+    from portfolio_src.data.market import fetch_event_calendar
+
+    events = []
+    for isin in isins:
+        try:
+            found = fetch_event_calendar(isin)
+        except Exception as e:
+            logger.warning(
+                "Event calendar unavailable",
+                extra={"isin": isin, "error": str(e), "error_type": type(e).__name__},
+            )
+            continue
+        events.extend(
+            {"isin": isin, **event} for event in found if date_from <= event["date"] <= date_to
+        )
+    return sorted(events, key=lambda event: event["date"])
+
+
+def _is_date(value: Any) -> bool:
+    try:
+        date.fromisoformat(value)
+        return True
+    except (TypeError, ValueError):
+        return False
+
+
+async def handle_get_event_calendar(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Get upcoming ex-dividend and earnings dates.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'isins', 'from' and 'to' (YYYY-MM-DD, inclusive).
+
+    Returns:
+        Success response with 'events' (isin, kind, date, optional
+        amountPerShare in EUR); ISINs without calendar data are left out.
+    """
+    isins = payload.get("isins")
+    if not isinstance(isins, list) or not all(isinstance(isin, str) for isin in isins):
+        return error_response(cmd_id, "INVALID_PARAMS", "isins must be a list of ISINs")
+    date_from, date_to = payload.get("from"), payload.get("to")
+    if not _is_date(date_from) or not _is_date(date_to):
+        return error_response(cmd_id, "INVALID_PARAMS", "from and to must be YYYY-MM-DD dates")
+
+    loop = asyncio.get_event_loop()
+    events = await loop.run_in_executor(
+        get_executor(), _event_calendar, isins, date_from, date_to
+    )
+    return success_response(cmd_id, {"events": events})
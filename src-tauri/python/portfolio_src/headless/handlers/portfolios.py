@@ -0,0 +1,142 @@
+"""Portfolio Management Handlers.
+
+Create, rename, delete and list the portfolios in the engine database, and
+annotate their positions. The shell validates names, currencies and note
+lengths, keeps sandbox portfolios to itself and refuses to delete the last
+portfolio; the checks here only guard the database.
+"""
+
+from typing import Any, Optional
+
+from portfolio_src.data import database
+from portfolio_src.headless.responses import error_response, success_response
+from portfolio_src.prism_utils.logging_config import get_logger
+
+logger = get_logger(__name__)
+
+
+def _portfolio(row: dict[str, Any]) -> dict[str, Any]:
+    return {
+        "id": row["id"],
+        "name": row["name"],
+        "currency": row["currency"] or "EUR",
+        "createdAt": row["created_at"],
+    }
+
+
+def _portfolio_id(payload: dict[str, Any]) -> Optional[int]:
+    portfolio_id = payload.get("portfolioId")
+    if isinstance(portfolio_id, bool) or not isinstance(portfolio_id, int):
+        return None
+    return portfolio_id
+
+
+def _name(payload: dict[str, Any]) -> Optional[str]:
+    name = payload.get("name")
+    if not isinstance(name, str) or not name.strip():
+        return None
+    return name.strip()
+
+
+def handle_list_portfolios(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """List all portfolios.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Command payload (unused).
+
+    Returns:
+        Success response with the portfolios, oldest first.
+    """
+    portfolios = [_portfolio(row) for row in database.list_portfolios()]
+    return success_response(cmd_id, {"portfolios": portfolios})
+
+
+def handle_create_portfolio(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Create a portfolio.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'name'; 'currency' defaults to EUR.
+
+    Returns:
+        Success response with the new portfolio, or error response.
+    """
+    name = _name(payload)
+    if name is None:
+        return error_response(cmd_id, "INVALID_PARAMS", "name is required")
+
+    currency = payload.get("currency") or "EUR"
+    row = database.create_portfolio(name, currency)
+    logger.info("Portfolio created", extra={"portfolio_id": row["id"]})
+    return success_response(cmd_id, _portfolio(row))
+
+
+def handle_rename_portfolio(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Rename a portfolio.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'portfolioId' and 'name'.
+
+    Returns:
+        Success response with the renamed portfolio, or error response.
+    """
+    portfolio_id = _portfolio_id(payload)
+    name = _name(payload)
+    if portfolio_id is None or name is None:
+        return error_response(cmd_id, "INVALID_PARAMS", "portfolioId and name are required")
+
+    row = database.rename_portfolio(portfolio_id, name)
+    if row is None:
+        return error_response(
+            cmd_id, "PORTFOLIO_NOT_FOUND", f"Portfolio {portfolio_id} does not exist"
+        )
+    return success_response(cmd_id, _portfolio(row))
+
+
+def handle_delete_portfolio(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Delete a portfolio with its positions and transactions.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'portfolioId'.
+
+    Returns:
+        Success response, or error response.
+    """
+    portfolio_id = _portfolio_id(payload)
+    if portfolio_id is None:
+        return error_response(cmd_id, "INVALID_PARAMS", "portfolioId is required")
+
+    if not database.delete_portfolio(portfolio_id):
+        return error_response(
+            cmd_id, "PORTFOLIO_NOT_FOUND", f"Portfolio {portfolio_id} does not exist"
+        )
+    logger.info("Portfolio deleted", extra={"portfolio_id": portfolio_id})
+    return success_response(cmd_id, {"portfolioId": portfolio_id, "deleted": True})
+
+
+def handle_set_position_note(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Set or clear (empty string) the note on a position.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'portfolioId', 'isin' and 'note'.
+
+    Returns:
+        Success response, or error response.
+    """
+    portfolio_id = _portfolio_id(payload)
+    isin = payload.get("isin")
+    note = payload.get("note")
+    if portfolio_id is None or not isinstance(isin, str) or not isinstance(note, str):
+        return error_response(cmd_id, "INVALID_PARAMS", "portfolioId, isin and note are required")
+
+    if not database.set_position_note(portfolio_id, isin, note.strip() or None):
+        return error_response(
+            cmd_id,
+            "POSITION_NOT_FOUND",
+            f"Portfolio {portfolio_id} holds no position in {isin}",
+        )
+    return success_response(cmd_id, {"portfolioId": portfolio_id, "isin": isin})
@@ -1,12 +1,15 @@
 """Settings Handlers.
 
-Handles user preference management including Hive contribution settings.
+Handles user preference management including Hive contribution settings,
+and the app settings the shell pushes with `configure` or passes in the
+launch environment.
 """
 
+import os
 from typing import Any
 
 from portfolio_src.data.database import get_connection
-from portfolio_src.headless.responses import success_response
+from portfolio_src.headless.responses import error_response, success_response
 from portfolio_src.prism_utils.logging_config import get_logger
 
 logger = get_logger(__name__)
@@ -44,3 +47,70 @@ def handle_get_hive_contribution(request_id: int, payload: dict[str, Any]) -> di
 def is_hive_contribution_enabled() -> bool:
     value = get_setting("hive_contribution_enabled", "true")
     return value.lower() == "true"
+
+
+# Launch environment variables carrying the shell's settings
+ENGINE_SETTINGS_ENV = {
+    "currency": "PRISM_CURRENCY",
+    "locale": "PRISM_LOCALE",
+    "hiveContributions": "PRISM_HIVE_CONTRIBUTIONS",
+}
+
+
+def apply_engine_settings(settings: dict[str, Any]) -> dict[str, Any]:
+    """Store the shell's settings; keys that are absent keep their values.
+
+    Raises:
+        ValueError: If a setting has the wrong type.
+    """
+    applied: dict[str, Any] = {}
+    for key in ("currency", "locale"):
+        value = settings.get(key)
+        if value is None:
+            continue
+        if not isinstance(value, str) or not value.strip():
+            raise ValueError(f"{key} must be a non-empty string")
+        set_setting(key, value.strip())
+        applied[key] = value.strip()
+
+    enabled = settings.get("hiveContributions")
+    if enabled is not None:
+        if not isinstance(enabled, bool):
+            raise ValueError("hiveContributions must be a boolean")
+        set_setting("hive_contribution_enabled", "true" if enabled else "false")
+        applied["hiveContributions"] = enabled
+    return applied
+
+
+def configure_from_env() -> None:
+    """Apply the settings the shell passed in the launch environment."""
+    settings: dict[str, Any] = {
+        key: os.environ[name] for key, name in ENGINE_SETTINGS_ENV.items() if name in os.environ
+    }
+    if "hiveContributions" in settings:
+        settings["hiveContributions"] = settings["hiveContributions"] == "1"
+    try:
+        applied = apply_engine_settings(settings)
+    except ValueError as e:
+        logger.warning("Ignoring launch settings", extra={"error": str(e)})
+        return
+    if applied:
+        logger.info("Launch settings applied", extra={"settings": sorted(applied)})
+
+
+def handle_configure(request_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Apply settings changed in the shell while the engine runs.
+
+    Args:
+        request_id: IPC command identifier.
+        payload: Any of 'currency', 'locale' and 'hiveContributions'.
+
+    Returns:
+        Success response with the applied settings, or error response.
+    """
+    try:
+        applied = apply_engine_settings(payload)
+    except ValueError as e:
+        return error_response(request_id, "INVALID_PARAMS", str(e))
+    logger.info("Engine configured", extra={"settings": sorted(applied)})
+    return success_response(request_id, applied)
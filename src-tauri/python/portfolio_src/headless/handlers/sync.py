@@ -4,6 +4,9 @@ Thin presentation layer for portfolio synchronization IPC.
 Delegates business logic to SyncService.
 """
 
+import asyncio
+import functools
+import threading
 from typing import Any
 
 from portfolio_src.core.services.sync_service import AuthenticationError
@@ -14,6 +17,14 @@ from portfolio_src.prism_utils.logging_config import get_logger
 
 logger = get_logger(__name__)
 
+# How long cancel_pipeline waits for the run to stop; shorter than the
+# shell's grace period so it can still restart the engine as a last resort.
+CANCEL_WAIT_SECS = 4.0
+
+# The running pipeline and its cancel flag. The run executes in a worker
+# thread so the event loop stays free to handle cancel_pipeline.
+_active_run: "tuple[asyncio.Future[Any], threading.Event] | None" = None
+
 
 def emit_progress(progress: int, message: str, phase: str = "pipeline") -> None:
     """Emit sync progress event via IPC protocol AND SSE broadcast."""
@@ -32,19 +43,41 @@ def emit_progress(progress: int, message: str, phase: str = "pipeline") -> None:
         pass
 
 
+def emit_invalidated(scope: str) -> None:
+    """Tell the shell which cached views a change made stale.
+
+    Scopes: "positions" (holdings and totals), "allocations" (sector, region
+    and asset class breakdowns) and "report" (look-through and overlap).
+    """
+    write_protocol({"event": "data_invalidated", "data": {"scope": scope}})
+
+
 async def handle_sync_portfolio(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
     """Sync portfolio data from Trade Republic.
 
-    Thin handler that delegates to SyncService.
+    Thin handler that delegates to SyncService. An optional 'isins' list
+    refreshes only those positions, e.g. when the shell retries failures.
+
+    Returns:
+        Success response with the sync statistics and 'failures': the
+        positions that could not be synced, each with 'isin', 'reason' and
+        'retryable'.
     """
     portfolio_id = payload.get("portfolioId", 1)
+    isins = payload.get("isins")
+    if isins is not None and (
+        not isinstance(isins, list) or not all(isinstance(isin, str) for isin in isins)
+    ):
+        return error_response(cmd_id, "INVALID_PARAMS", "isins must be a list of ISINs")
     service = get_sync_service()
 
     try:
         result = service.sync_portfolio(
             portfolio_id=portfolio_id,
             progress_callback=emit_progress,
+            isins=isins,
         )
+        emit_invalidated("positions")
 
         return success_response(
             cmd_id,
@@ -54,6 +87,10 @@ async def handle_sync_portfolio(cmd_id: int, payload: dict[str, Any]) -> dict[st
                 "updatedPositions": result.updated_positions,
                 "totalValue": result.total_value,
                 "durationMs": result.duration_ms,
+                "failures": [
+                    {"isin": f.isin, "reason": f.reason, "retryable": f.retryable}
+                    for f in result.failures
+                ],
             },
         )
     except AuthenticationError as e:
@@ -68,15 +105,93 @@ async def handle_sync_portfolio(cmd_id: int, payload: dict[str, Any]) -> dict[st
         return error_response(cmd_id, "TR_SYNC_FAILED", str(e))
 
 
+def _pipeline_options(payload: dict[str, Any]) -> tuple[list[str], list[str]]:
+    """Stages and data sources of the pipeline config sent by the shell.
+
+    A single 'stage' overrides the configured stages: it runs together with
+    the required stages, and the other fetching stages serve cached data.
+
+    Raises:
+        ValueError: If the payload names a stage or source the engine lacks.
+    """
+    from portfolio_src.core.pipeline import DATA_SOURCES, PIPELINE_STAGES, REQUIRED_STAGES
+
+    config = payload.get("config") or {}
+    stages = list(config.get("enabledStages") or [])
+    data_sources = list(config.get("dataSources") or [])
+
+    stage = payload.get("stage")
+    if stage is not None:
+        if stage not in PIPELINE_STAGES:
+            raise ValueError(f"Unknown pipeline stage: {stage}")
+        stages = [name for name in PIPELINE_STAGES if name == stage or name in REQUIRED_STAGES]
+
+    unknown_stages = [stage for stage in stages if stage not in PIPELINE_STAGES]
+    if unknown_stages:
+        raise ValueError(f"Unknown pipeline stage: {', '.join(unknown_stages)}")
+    unknown_sources = [source for source in data_sources if source not in DATA_SOURCES]
+    if unknown_sources:
+        raise ValueError(f"Unknown data source: {', '.join(unknown_sources)}")
+    return stages, data_sources
+
+
+def handle_get_pipeline_capabilities(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Declare the pipeline stages and data sources a config may select.
+
+    Returns:
+        Success response with 'stages' in run order, 'dataSources' and the
+        'requiredStages' that always run.
+    """
+    from portfolio_src.core.pipeline import DATA_SOURCES, PIPELINE_STAGES, REQUIRED_STAGES
+
+    return success_response(
+        cmd_id,
+        {
+            "stages": PIPELINE_STAGES,
+            "dataSources": DATA_SOURCES,
+            "requiredStages": REQUIRED_STAGES,
+        },
+    )
+
+
 async def handle_run_pipeline(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
     """Run the analytics pipeline.
 
-    Thin handler that delegates to SyncService.
+    Thin handler that delegates to SyncService. An optional 'portfolioId'
+    (default 1) and 'config' with 'enabledStages' and 'dataSources' select
+    what the run analyzes and which remote sources it may query; an optional
+    'stage' runs only that stage (see _pipeline_options).
     """
+    global _active_run
+
+    try:
+        stages, data_sources = _pipeline_options(payload)
+    except ValueError as e:
+        return error_response(cmd_id, "INVALID_PARAMS", str(e))
+
+    if _active_run is not None and not _active_run[0].done():
+        return error_response(cmd_id, "PIPELINE_RUNNING", "A pipeline run is already in progress")
+
     service = get_sync_service()
+    cancel = threading.Event()
+    run = asyncio.get_running_loop().run_in_executor(
+        None,
+        functools.partial(
+            service.run_pipeline,
+            progress_callback=emit_progress,
+            portfolio_id=payload.get("portfolioId", 1),
+            stages=stages,
+            data_sources=data_sources,
+            should_cancel=cancel.is_set,
+        ),
+    )
+    _active_run = (run, cancel)
 
     try:
-        result = service.run_pipeline(progress_callback=emit_progress)
+        result = await run
+        if not result.cancelled:
+            emit_invalidated("allocations")
+            emit_invalidated("report")
 
         return success_response(
             cmd_id,
@@ -84,6 +199,7 @@ async def handle_run_pipeline(cmd_id: int, payload: dict[str, Any]) -> dict[str,
                 "success": result.success,
                 "errors": result.errors,
                 "durationMs": result.duration_ms,
+                "cancelled": result.cancelled,
             },
         )
     except Exception as e:
@@ -93,3 +209,33 @@ async def handle_run_pipeline(cmd_id: int, payload: dict[str, Any]) -> dict[str,
             exc_info=True,
         )
         return error_response(cmd_id, "PIPELINE_ERROR", str(e))
+    finally:
+        if _active_run is not None and _active_run[0] is run:
+            _active_run = None
+
+
+async def handle_cancel_pipeline(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Stop the running pipeline at its next progress report.
+
+    The pending run_pipeline call then responds with 'cancelled'. Reports of
+    the previous run are kept.
+
+    Returns:
+        Success response with 'cancelled' (False when nothing was running)
+        once the run has stopped, or an error if it did not stop within
+        CANCEL_WAIT_SECS.
+    """
+    active = _active_run
+    if active is None or active[0].done():
+        return success_response(cmd_id, {"cancelled": False})
+
+    run, cancel = active
+    cancel.set()
+    try:
+        await asyncio.wait_for(asyncio.shield(run), CANCEL_WAIT_SECS)
+    except asyncio.TimeoutError:
+        return error_response(cmd_id, "CANCEL_TIMEOUT", "The pipeline did not stop in time")
+    except Exception:
+        # The run failed on its own; either way it has stopped
+        pass
+    return success_response(cmd_id, {"cancelled": True})
@@ -0,0 +1,231 @@
+"""Tests for headless/handlers/hive.py - Hive contribution review handlers."""
+
+import json
+import threading
+import time
+from unittest.mock import MagicMock, patch
+
+import pandas as pd
+import pytest
+
+from portfolio_src.data import hive_review, shell_decomposition
+from portfolio_src.data.hive_client import HiveClient
+from portfolio_src.headless.handlers.hive import (
+    handle_hive_contribution_approved,
+    handle_hive_contribution_blocked,
+    handle_hive_decomposition_response,
+)
+
+
+@pytest.fixture(autouse=True)
+def clear_pending():
+    hive_review._pending.clear()
+    yield
+    hive_review._pending.clear()
+
+
+def _request(capsys):
+    return json.loads(capsys.readouterr().out.strip().splitlines()[-1])
+
+
+class TestRequestReview:
+    """Tests for hive_review.request_review()."""
+
+    def test_emits_request_event(self, capsys):
+        """Announces the contribution to the shell instead of uploading it."""
+        uploader = MagicMock()
+        request_id = hive_review.request_review("listing", {"isin": "US0378331005"}, uploader)
+
+        event = _request(capsys)
+        assert event["event"] == "hive_contribution_request"
+        assert event["data"]["requestId"] == request_id
+        assert event["data"]["kind"] == "listing"
+        assert event["data"]["contribution"] == {"isin": "US0378331005"}
+        uploader.assert_not_called()
+
+    def test_approval_uploads_sanitized_contribution(self, capsys):
+        """The uploader receives the shell's version, not the original."""
+        uploader = MagicMock()
+        request_id = hive_review.request_review(
+            "listing", {"isin": "US0378331005", "extra": 1}, uploader
+        )
+
+        assert hive_review.approve(request_id, {"isin": "US0378331005"}) is True
+        uploader.assert_called_once_with({"isin": "US0378331005"})
+        assert hive_review.approve(request_id, {}) is False
+
+    def test_pending_requests_are_bounded(self, capsys):
+        """The oldest unanswered contribution is dropped beyond the limit."""
+        with patch.object(hive_review, "MAX_PENDING", 2):
+            first = hive_review.request_review("listing", {}, MagicMock())
+            hive_review.request_review("listing", {}, MagicMock())
+            hive_review.request_review("listing", {}, MagicMock())
+
+        assert hive_review.pending_count() == 2
+        assert hive_review.take(first) is None
+
+
+class TestHandleHiveContributionApproved:
+    """Tests for handle_hive_contribution_approved()."""
+
+    def test_uploads_in_background(self, capsys):
+        """Starts the upload with the sanitized contribution."""
+        uploader = MagicMock()
+        request_id = hive_review.request_review("mapping", {"isin": "X"}, uploader)
+
+        with patch("portfolio_src.headless.handlers.hive.threading.Thread") as thread:
+            result = handle_hive_contribution_approved(
+                1, {"requestId": request_id, "contribution": {"isin": "X"}}
+            )
+
+        assert result["success"] is True
+        assert result["data"]["kind"] == "mapping"
+        args = thread.call_args.kwargs["args"]
+        assert args == ("mapping", uploader, {"isin": "X"})
+        thread.return_value.start.assert_called_once()
+
+    def test_unknown_request_is_an_error(self):
+        """An answer to nothing pending is reported."""
+        result = handle_hive_contribution_approved(
+            1, {"requestId": "missing", "contribution": {}}
+        )
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "UNKNOWN_REQUEST"
+
+    def test_requires_contribution(self):
+        """The sanitized contribution is required."""
+        result = handle_hive_contribution_approved(1, {"requestId": "abc"})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+
+class TestHandleHiveContributionBlocked:
+    """Tests for handle_hive_contribution_blocked()."""
+
+    def test_drops_contribution(self, capsys):
+        """A blocked contribution is never uploaded."""
+        uploader = MagicMock()
+        request_id = hive_review.request_review("holdings", {"holdings": []}, uploader)
+
+        result = handle_hive_contribution_blocked(
+            1, {"requestId": request_id, "reason": "forbidden field"}
+        )
+
+        assert result["success"] is True
+        assert hive_review.pending_count() == 0
+        uploader.assert_not_called()
+
+    def test_unknown_request_is_an_error(self):
+        result = handle_hive_contribution_blocked(1, {"requestId": "missing"})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "UNKNOWN_REQUEST"
+
+
+class TestHiveClientContributions:
+    """Contributions go through the review instead of straight to Supabase."""
+
+    @pytest.fixture
+    def client(self):
+        client = HiveClient.__new__(HiveClient)
+        client._client = MagicMock()
+        client.supabase_url = "https://example.supabase.co"
+        client.supabase_key = "key"
+        return client
+
+    def test_holdings_wait_for_approval(self, client, capsys):
+        """No RPC runs until the shell approves the weights-only contribution."""
+        holdings = pd.DataFrame(
+            {"isin": ["US0378331005"], "name": ["Apple"], "weight": [5.0], "value": [123.0]}
+        )
+        with patch.object(HiveClient, "is_configured", True), patch.object(
+            client, "_is_contribution_allowed", return_value=True
+        ):
+            assert client.contribute_etf_holdings("IE00B4L5Y983", holdings) is True
+
+            event = _request(capsys)
+            contribution = event["data"]["contribution"]
+            assert event["data"]["kind"] == "holdings"
+            assert "value" not in contribution["holdings"][0]
+            client._client.rpc.assert_not_called()
+
+            hive_review.approve(event["data"]["requestId"], contribution)
+
+        rpc, params = client._client.rpc.call_args[0]
+        assert rpc == "batch_contribute_holdings"
+        assert params["p_holdings"][0]["weight_percentage"] == 5.0
+
+    def test_disabled_contributions_send_nothing(self, client, capsys):
+        """With contributions off nothing is even announced."""
+        with patch.object(client, "_is_contribution_allowed", return_value=False):
+            result = client.contribute_listing("US0378331005", "AAPL", "XNAS", "USD")
+
+        assert result.success is False
+        assert capsys.readouterr().out == ""
+        assert hive_review.pending_count() == 0
+
+
+class TestDecompositionResponse:
+    """Tests for the hive_decomposition_request round trip."""
+
+    @pytest.fixture(autouse=True)
+    def attached(self, monkeypatch):
+        monkeypatch.setattr(shell_decomposition, "_attached", True)
+        shell_decomposition._pending.clear()
+        yield
+        shell_decomposition._pending.clear()
+
+    def _answer_when_asked(self, payload):
+        """Answer the next request from another thread, as the shell would."""
+
+        def answer():
+            while not shell_decomposition._pending:
+                time.sleep(0.01)
+            request_id = next(iter(shell_decomposition._pending))
+            handle_hive_decomposition_response(1, {"requestId": request_id, **payload})
+
+        thread = threading.Thread(target=answer)
+        thread.start()
+        return thread
+
+    def test_delivers_decomposition_to_waiting_request(self, capsys):
+        thread = self._answer_when_asked(
+            {
+                "source": "offlineDataset",
+                "decomposition": {"holdings": [{"isin": "US0378331005", "weight": 4.5}]},
+            },
+        )
+        holdings, source = shell_decomposition.fetch_holdings("IE00B4L5Y983")
+        thread.join()
+
+        event = json.loads(capsys.readouterr().out.strip().splitlines()[0])
+        assert event["event"] == "hive_decomposition_request"
+        assert event["data"]["isin"] == "IE00B4L5Y983"
+        assert source == "offline_dataset"
+        assert holdings.to_dict("records") == [
+            {"isin": "US0378331005", "name": "US0378331005", "weight": 4.5}
+        ]
+
+    def test_missing_decomposition_returns_none(self, capsys):
+        thread = self._answer_when_asked({"decomposition": None, "error": "offline"})
+        holdings, source = shell_decomposition.fetch_holdings("IE00B4L5Y983")
+        thread.join()
+
+        assert holdings is None and source is None
+
+    def test_unanswered_request_times_out(self, capsys):
+        assert shell_decomposition.request("IE00B4L5Y983", timeout=0.01) is None
+        assert not shell_decomposition._pending
+
+    def test_without_shell_nothing_is_requested(self, capsys, monkeypatch):
+        monkeypatch.setattr(shell_decomposition, "_attached", False)
+
+        assert shell_decomposition.request("IE00B4L5Y983") is None
+        assert capsys.readouterr().out == ""
+
+    def test_unknown_request_is_rejected(self):
+        result = handle_hive_decomposition_response(1, {"requestId": "nope", "decomposition": None})
+
+        assert result["error"]["code"] == "UNKNOWN_REQUEST"
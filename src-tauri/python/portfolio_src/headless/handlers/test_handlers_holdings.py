@@ -9,6 +9,8 @@ import pandas as pd
 
 from portfolio_src.headless.handlers.holdings import (
     handle_upload_holdings,
+    handle_preview_holdings_upload,
+    handle_upload_holdings_chunk,
     handle_get_true_holdings,
     handle_get_pipeline_report,
 )
@@ -94,6 +96,171 @@ class TestHandleUploadHoldings:
         assert result["data"]["isin"] == "IE00B4L5Y983"
 
 
+class TestConvertedRowsUpload:
+    """Uploads of rows the shell converted from an Excel file."""
+
+    ROWS = [
+        {"isin": "US1234567890", "name": "Stock A", "weight": 60.0},
+        {"isin": "US0987654321", "name": "Stock B", "ticker": "B", "weight": 40.0},
+        {"isin": "", "name": "Cash", "weight": 0.5},
+    ]
+
+    def test_upload_accepts_rows_without_file_path(self):
+        """Saves converted rows, dropping the ones without an ISIN."""
+        mock_hive = MagicMock()
+        mock_hive.is_configured = False
+
+        with patch("portfolio_src.data.holdings_cache.get_holdings_cache") as get_cache:
+            with patch(
+                "portfolio_src.data.hive_client.get_hive_client",
+                return_value=mock_hive,
+            ):
+                result = handle_upload_holdings(
+                    1,
+                    {
+                        "fileName": "/Downloads/amundi.xlsx",
+                        "etfIsin": "IE00B4L5Y983",
+                        "holdings": self.ROWS,
+                    },
+                )
+
+        assert result["success"] is True
+        assert result["data"]["holdingsCount"] == 2
+        assert result["data"]["totalWeight"] == 100.0
+        saved = get_cache.return_value._save_to_local_cache.call_args[0][1]
+        assert list(saved["isin"]) == ["US1234567890", "US0987654321"]
+
+    def test_preview_reports_original_file_name(self):
+        """The preview names the converted workbook, not a temporary file."""
+        result = handle_preview_holdings_upload(
+            1,
+            {
+                "fileName": "/Downloads/amundi.xlsx",
+                "etfIsin": "IE00B4L5Y983",
+                "holdings": self.ROWS,
+            },
+        )
+
+        assert result["success"] is True
+        assert result["data"]["fileName"] == "amundi.xlsx"
+        assert result["data"]["holdingsCount"] == 2
+
+    def test_rows_missing_fields_are_rejected(self):
+        """Rows without a weight column are invalid params."""
+        result = handle_upload_holdings(
+            1,
+            {
+                "fileName": "funds.xlsx",
+                "etfIsin": "IE00B4L5Y983",
+                "holdings": [{"isin": "US1234567890", "name": "Stock A"}],
+            },
+        )
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+    def test_empty_rows_fail_cleanup(self):
+        """An empty sheet is reported like an empty file."""
+        result = handle_preview_holdings_upload(
+            1, {"fileName": "funds.xlsx", "etfIsin": "IE00B4L5Y983", "holdings": []}
+        )
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "CLEANUP_FAILED"
+
+
+class TestChunkedUpload:
+    """Large uploads sent in `upload_holdings_chunk` batches."""
+
+    def setup_method(self):
+        from portfolio_src.headless.handlers import holdings
+
+        holdings._CHUNKED_UPLOADS.clear()
+
+    @staticmethod
+    def _chunk(sequence, rows, upload_id="up-1", batches=2):
+        return handle_upload_holdings_chunk(
+            1,
+            {
+                "uploadId": upload_id,
+                "etfIsin": "IE00B4L5Y983",
+                "sequence": sequence,
+                "batches": batches,
+                "holdings": rows,
+            },
+        )
+
+    def test_preview_assembles_batches_in_order(self):
+        """Batches arriving out of order are joined by sequence."""
+        second = self._chunk(1, [{"isin": "US0987654321", "name": "Stock B", "weight": 40.0}])
+        first = self._chunk(0, [{"isin": "US1234567890", "name": "Stock A", "weight": 60.0}])
+
+        assert first["success"] is True
+        assert second["data"]["received"] == 1
+        result = handle_preview_holdings_upload(
+            1,
+            {"fileName": "big.csv", "etfIsin": "IE00B4L5Y983", "uploadId": "up-1", "rowCount": 2},
+        )
+
+        assert result["success"] is True
+        assert [row["isin"] for row in result["data"]["rows"]] == [
+            "US1234567890",
+            "US0987654321",
+        ]
+
+    def test_missing_batch_is_reported(self):
+        """An upload with a batch missing is rejected and dropped."""
+        self._chunk(0, [{"isin": "US1234567890", "name": "Stock A", "weight": 60.0}])
+
+        result = handle_upload_holdings(
+            1, {"fileName": "big.csv", "etfIsin": "IE00B4L5Y983", "uploadId": "up-1"}
+        )
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "UPLOAD_INCOMPLETE"
+        from portfolio_src.headless.handlers import holdings
+
+        assert "up-1" not in holdings._CHUNKED_UPLOADS
+
+    def test_unknown_upload_is_reported(self):
+        """Referencing an upload that was never sent fails."""
+        result = handle_upload_holdings(
+            1, {"fileName": "big.csv", "etfIsin": "IE00B4L5Y983", "uploadId": "nope"}
+        )
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "UPLOAD_INCOMPLETE"
+
+    def test_row_count_mismatch_is_reported(self):
+        """The final command's rowCount must match the rows received."""
+        self._chunk(0, [{"isin": "US1234567890", "name": "Stock A", "weight": 100.0}], batches=1)
+
+        result = handle_preview_holdings_upload(
+            1,
+            {"fileName": "big.csv", "etfIsin": "IE00B4L5Y983", "uploadId": "up-1", "rowCount": 5},
+        )
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "UPLOAD_INCOMPLETE"
+
+    def test_invalid_sequence_is_rejected(self):
+        """A sequence outside the declared batches is invalid."""
+        result = self._chunk(2, [], batches=2)
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+    def test_pending_uploads_are_bounded(self):
+        """The oldest abandoned upload is dropped beyond the limit."""
+        from portfolio_src.headless.handlers import holdings
+
+        for index in range(holdings.MAX_PENDING_UPLOADS + 1):
+            self._chunk(0, [], upload_id=f"up-{index}")
+
+        assert len(holdings._CHUNKED_UPLOADS) == holdings.MAX_PENDING_UPLOADS
+        assert "up-0" not in holdings._CHUNKED_UPLOADS
+
+
 class TestHandleGetTrueHoldings:
     """Tests for handle_get_true_holdings()."""
 
@@ -0,0 +1,137 @@
+"""Unit tests for broker import handlers."""
+
+from unittest.mock import patch
+
+import pytest
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.imports import handle_import_holdings
+from portfolio_src.models import AssetClass
+
+SAP = "DE0007164600"
+APPLE = "US0378331005"
+
+
+@pytest.fixture
+def portfolio_id(tmp_path, monkeypatch):
+    """Portfolio holding SAP in a temp engine database, with no remote lookups."""
+    monkeypatch.setenv("PRISM_DATA_DIR", str(tmp_path))
+    database.init_db().close()
+    portfolio = database.create_portfolio("Main")
+    database.upsert_asset(SAP, "SAP SE", "SAP", "Stock")
+    database.upsert_position(portfolio["id"], SAP, 2.0, 100.0, current_price=120.0)
+    with (
+        patch(
+            "portfolio_src.core.services.AssetClassifier.classify",
+            return_value=AssetClass.STOCK,
+        ),
+        patch(
+            "portfolio_src.data.market.fx_rate_to_eur",
+            side_effect=lambda currency: {"EUR": 1.0, "USD": 0.5}[currency],
+        ),
+        patch("portfolio_src.headless.handlers.imports.emit_invalidated"),
+    ):
+        yield portfolio["id"]
+
+
+def _statement(portfolio_id, **overrides):
+    statement = {
+        "portfolioId": portfolio_id,
+        "broker": "degiro",
+        "positions": [
+            {
+                "isin": APPLE,
+                "name": "Apple Inc.",
+                "quantity": 3.0,
+                "avgBuyPrice": 150.0,
+                "price": 200.0,
+                "currency": "USD",
+            }
+        ],
+        "transactions": [
+            {
+                "isin": APPLE,
+                "name": "Apple Inc.",
+                "type": "Buy",
+                "date": "2025-01-10",
+                "quantity": 3.0,
+                "amount": 450.0,
+                "currency": "USD",
+                "reference": "order-1",
+            },
+            {
+                "isin": "",
+                "name": "Interest",
+                "type": "Interest",
+                "date": "2025-01-31",
+                "quantity": None,
+                "amount": 1.2,
+                "currency": "EUR",
+                "reference": None,
+            },
+        ],
+    }
+    statement.update(overrides)
+    return statement
+
+
+class TestImportHoldings:
+    @pytest.mark.asyncio
+    async def test_merges_positions_in_eur_and_ledger(self, portfolio_id):
+        result = await handle_import_holdings(cmd_id=1, payload=_statement(portfolio_id))
+
+        assert result["success"] is True
+        assert result["data"] == {
+            "newPositions": 1,
+            "updatedPositions": 0,
+            "transactionsImported": 1,
+            "duplicateTransactions": 0,
+            "skippedTransactions": 1,
+        }
+        positions = {p["isin"]: p for p in database.get_positions(portfolio_id)}
+        assert positions[SAP]["quantity"] == 2.0
+        assert positions[APPLE]["quantity"] == 3.0
+        assert positions[APPLE]["cost_basis"] == 75.0
+        assert positions[APPLE]["current_price"] == 100.0
+        assert positions[APPLE]["name"] == "Apple Inc."
+        [transaction] = database.get_transactions(portfolio_id)
+        assert transaction["id"] == "degiro:order-1"
+        assert transaction["currency"] == "USD"
+
+    @pytest.mark.asyncio
+    async def test_reimport_skips_known_transactions(self, portfolio_id):
+        await handle_import_holdings(cmd_id=1, payload=_statement(portfolio_id))
+        result = await handle_import_holdings(cmd_id=2, payload=_statement(portfolio_id))
+
+        assert result["data"]["updatedPositions"] == 1
+        assert result["data"]["transactionsImported"] == 0
+        assert result["data"]["duplicateTransactions"] == 1
+        assert len(database.get_transactions(portfolio_id)) == 1
+
+    @pytest.mark.asyncio
+    async def test_keeps_stored_prices_the_statement_lacks(self, portfolio_id):
+        position = {"isin": SAP, "name": "SAP SE", "quantity": 5.0, "currency": "EUR"}
+        await handle_import_holdings(
+            cmd_id=1,
+            payload=_statement(portfolio_id, positions=[position], transactions=[]),
+        )
+
+        [sap] = database.get_positions(portfolio_id)
+        assert sap["quantity"] == 5.0
+        assert sap["cost_basis"] == 100.0
+        assert sap["current_price"] == 120.0
+
+    @pytest.mark.asyncio
+    async def test_rejects_unknown_portfolio(self, portfolio_id):
+        result = await handle_import_holdings(cmd_id=1, payload=_statement(portfolio_id + 1))
+
+        assert result["error"]["code"] == "PORTFOLIO_NOT_FOUND"
+
+    @pytest.mark.asyncio
+    async def test_rejects_invalid_isin(self, portfolio_id):
+        position = {"isin": "NOT-AN-ISIN", "name": "?", "quantity": 1.0, "currency": "EUR"}
+        result = await handle_import_holdings(
+            cmd_id=1, payload=_statement(portfolio_id, positions=[position])
+        )
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
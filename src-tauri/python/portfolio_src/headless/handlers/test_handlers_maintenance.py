@@ -0,0 +1,38 @@
+"""Unit tests for maintenance handlers."""
+
+from unittest.mock import patch
+
+import pytest
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.maintenance import handle_vacuum_database
+
+
+@pytest.fixture
+def db(tmp_path, monkeypatch):
+    """Engine database with freed pages from deleted portfolios."""
+    monkeypatch.setenv("PRISM_DATA_DIR", str(tmp_path))
+    database.init_db().close()
+    for index in range(200):
+        database.create_portfolio(f"Portfolio {index}")
+    for portfolio in database.list_portfolios():
+        database.delete_portfolio(portfolio["id"])
+    return tmp_path
+
+
+class TestVacuumDatabase:
+    @pytest.mark.asyncio
+    async def test_reports_sizes_and_keeps_data_usable(self, db):
+        result = await handle_vacuum_database(cmd_id=1, payload={})
+
+        assert result["success"] is True
+        assert result["data"]["sizeAfter"] <= result["data"]["sizeBefore"]
+        assert database.create_portfolio("After vacuum")["name"] == "After vacuum"
+
+    @pytest.mark.asyncio
+    async def test_failure_is_reported(self, db):
+        with patch.object(database, "vacuum", side_effect=RuntimeError("database is locked")):
+            result = await handle_vacuum_database(cmd_id=2, payload={})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "VACUUM_FAILED"
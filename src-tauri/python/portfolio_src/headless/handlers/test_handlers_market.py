@@ -0,0 +1,141 @@
+"""Unit tests for market data handlers."""
+
+from unittest.mock import patch
+
+import pytest
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.market import (
+    handle_get_asset_details,
+    handle_get_event_calendar,
+    handle_get_quotes,
+)
+
+SAP = "DE0007164600"
+APPLE = "US0378331005"
+
+
+@pytest.fixture
+def db(tmp_path, monkeypatch):
+    """Engine database with a synced SAP position and a known Apple asset."""
+    monkeypatch.setenv("PRISM_DATA_DIR", str(tmp_path))
+    database.init_db().close()
+    portfolio = database.create_portfolio("Main")
+    database.upsert_asset(SAP, "SAP SE", "SAP", "Stock")
+    database.upsert_asset(APPLE, "Apple Inc.", "AAPL", "Stock")
+    database.upsert_position(portfolio["id"], SAP, 2.0, 100.0, current_price=120.0)
+    return tmp_path
+
+
+class TestGetQuotes:
+    @pytest.mark.asyncio
+    async def test_prefers_live_prices(self, db):
+        with patch("portfolio_src.data.market.get_price_map", return_value={SAP: 125.5}):
+            result = await handle_get_quotes(cmd_id=1, payload={"isins": [SAP]})
+
+        assert result["success"] is True
+        assert result["data"]["quotes"] == [{"isin": SAP, "name": "SAP SE", "price": 125.5}]
+
+    @pytest.mark.asyncio
+    async def test_falls_back_to_stored_prices(self, db):
+        with patch("portfolio_src.data.market.get_price_map", side_effect=OSError("offline")):
+            result = await handle_get_quotes(cmd_id=1, payload={"isins": [SAP, APPLE]})
+
+        # Apple has no position, so no price to fall back to
+        assert result["data"]["quotes"] == [{"isin": SAP, "name": "SAP SE", "price": 120.0}]
+
+    @pytest.mark.asyncio
+    async def test_rejects_invalid_isins(self, db):
+        result = await handle_get_quotes(cmd_id=1, payload={"isins": SAP})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+
+class TestGetAssetDetails:
+    @pytest.mark.asyncio
+    async def test_returns_metadata_and_caches_history(self, db):
+        history = [("2025-01-02", 118.0), ("2025-01-03", 119.5)]
+        with patch("portfolio_src.data.market.fetch_price_history", return_value=history):
+            result = await handle_get_asset_details(cmd_id=1, payload={"isin": SAP})
+
+        data = result["data"]
+        assert data["name"] == "SAP SE"
+        assert data["ticker"] == "SAP"
+        assert data["instrumentType"] == "Stock"
+        assert data["priceHistory"] == [
+            {"date": "2025-01-02", "price": 118.0},
+            {"date": "2025-01-03", "price": 119.5},
+        ]
+        assert database.get_price_history(SAP) == data["priceHistory"]
+
+    @pytest.mark.asyncio
+    async def test_falls_back_to_cached_history(self, db):
+        database.save_price_history(SAP, [("2025-01-02", 118.0)])
+        with patch(
+            "portfolio_src.data.market.fetch_price_history", side_effect=OSError("offline")
+        ):
+            result = await handle_get_asset_details(cmd_id=1, payload={"isin": SAP})
+
+        assert result["data"]["priceHistory"] == [{"date": "2025-01-02", "price": 118.0}]
+
+    @pytest.mark.asyncio
+    async def test_unknown_asset_without_prices_is_not_found(self, db):
+        with patch("portfolio_src.data.market.fetch_price_history", return_value=[]):
+            result = await handle_get_asset_details(cmd_id=1, payload={"isin": "US5949181045"})
+
+        assert result["error"]["code"] == "NOT_FOUND"
+
+    @pytest.mark.asyncio
+    async def test_rejects_invalid_isin(self, db):
+        result = await handle_get_asset_details(cmd_id=1, payload={"isin": "SAP"})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+
+class TestGetEventCalendar:
+    @pytest.mark.asyncio
+    async def test_returns_events_within_window_soonest_first(self, db):
+        calendars = {
+            SAP: [
+                {"kind": "earnings", "date": "2025-04-22"},
+                {"kind": "exDividend", "date": "2025-05-14", "amountPerShare": 2.35},
+            ],
+            APPLE: [{"kind": "exDividend", "date": "2025-05-12", "amountPerShare": 0.23}],
+        }
+        with patch(
+            "portfolio_src.data.market.fetch_event_calendar",
+            side_effect=lambda isin: calendars[isin],
+        ):
+            result = await handle_get_event_calendar(
+                cmd_id=1,
+                payload={"isins": [SAP, APPLE], "from": "2025-05-01", "to": "2025-05-31"},
+            )
+
+        assert result["data"]["events"] == [
+            {"isin": APPLE, "kind": "exDividend", "date": "2025-05-12", "amountPerShare": 0.23},
+            {"isin": SAP, "kind": "exDividend", "date": "2025-05-14", "amountPerShare": 2.35},
+        ]
+
+    @pytest.mark.asyncio
+    async def test_skips_assets_whose_calendar_fails(self, db):
+        def calendar(isin):
+            if isin == SAP:
+                raise OSError("offline")
+            return [{"kind": "earnings", "date": "2025-05-01"}]
+
+        with patch("portfolio_src.data.market.fetch_event_calendar", side_effect=calendar):
+            result = await handle_get_event_calendar(
+                cmd_id=1,
+                payload={"isins": [SAP, APPLE], "from": "2025-05-01", "to": "2025-05-01"},
+            )
+
+        assert [event["isin"] for event in result["data"]["events"]] == [APPLE]
+
+    @pytest.mark.asyncio
+    async def test_rejects_invalid_dates(self, db):
+        result = await handle_get_event_calendar(
+            cmd_id=1, payload={"isins": [SAP], "from": "soon", "to": "2025-05-01"}
+        )
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
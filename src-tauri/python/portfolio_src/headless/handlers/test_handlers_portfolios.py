@@ -0,0 +1,126 @@
+"""Unit tests for portfolio management handlers."""
+
+import pytest
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.portfolios import (
+    handle_create_portfolio,
+    handle_delete_portfolio,
+    handle_list_portfolios,
+    handle_rename_portfolio,
+    handle_set_position_note,
+)
+
+
+@pytest.fixture
+def db(tmp_path, monkeypatch):
+    """Empty engine database in a temp data dir."""
+    monkeypatch.setenv("PRISM_DATA_DIR", str(tmp_path))
+    database.init_db().close()
+    return tmp_path
+
+
+class TestCreateAndList:
+    def test_created_portfolio_is_listed(self, db):
+        created = handle_create_portfolio(
+            cmd_id=1, payload={"name": " Retirement ", "currency": "USD"}
+        )
+
+        assert created["success"] is True
+        assert created["data"]["name"] == "Retirement"
+        assert created["data"]["currency"] == "USD"
+
+        listed = handle_list_portfolios(cmd_id=2, payload={})
+        assert [p["id"] for p in listed["data"]["portfolios"]] == [created["data"]["id"]]
+
+    def test_currency_defaults_to_eur(self, db):
+        created = handle_create_portfolio(cmd_id=1, payload={"name": "Main"})
+
+        assert created["data"]["currency"] == "EUR"
+
+    def test_missing_name_is_rejected(self, db):
+        result = handle_create_portfolio(cmd_id=1, payload={"name": "  "})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+
+class TestRename:
+    def test_renames_existing_portfolio(self, db):
+        portfolio_id = handle_create_portfolio(cmd_id=1, payload={"name": "Old"})["data"]["id"]
+
+        result = handle_rename_portfolio(
+            cmd_id=2, payload={"portfolioId": portfolio_id, "name": "New"}
+        )
+
+        assert result["success"] is True
+        assert result["data"]["name"] == "New"
+
+    def test_unknown_portfolio_is_reported(self, db):
+        result = handle_rename_portfolio(cmd_id=1, payload={"portfolioId": 99, "name": "New"})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "PORTFOLIO_NOT_FOUND"
+
+
+class TestDelete:
+    def test_deletes_portfolio_with_its_positions(self, db):
+        created = handle_create_portfolio(cmd_id=1, payload={"name": "Trading"})
+        portfolio_id = created["data"]["id"]
+        database.upsert_asset("DE0007164600", "SAP SE", "SAP", "Stock")
+        database.upsert_position(portfolio_id, "DE0007164600", 2.0, 120.0)
+
+        result = handle_delete_portfolio(cmd_id=2, payload={"portfolioId": portfolio_id})
+
+        assert result["success"] is True
+        assert handle_list_portfolios(cmd_id=3, payload={})["data"]["portfolios"] == []
+        assert database.get_positions(portfolio_id) == []
+
+    def test_unknown_portfolio_is_reported(self, db):
+        result = handle_delete_portfolio(cmd_id=1, payload={"portfolioId": 42})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "PORTFOLIO_NOT_FOUND"
+
+    def test_portfolio_id_must_be_an_integer(self, db):
+        result = handle_delete_portfolio(cmd_id=1, payload={"portfolioId": "1"})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+
+class TestSetPositionNote:
+    ISIN = "DE0007164600"
+
+    @pytest.fixture
+    def portfolio_id(self, db):
+        portfolio = database.create_portfolio("Main")
+        database.upsert_asset(self.ISIN, "SAP SE", "SAP", "Stock")
+        database.upsert_position(portfolio["id"], self.ISIN, 2.0, 100.0, current_price=120.0)
+        return portfolio["id"]
+
+    def test_note_is_stored_and_survives_sync(self, portfolio_id):
+        result = handle_set_position_note(
+            cmd_id=1,
+            payload={"portfolioId": portfolio_id, "isin": self.ISIN, "note": " Long-term hold "},
+        )
+        database.upsert_position(portfolio_id, self.ISIN, 3.0, 100.0, current_price=125.0)
+
+        assert result["success"] is True
+        [position] = database.get_positions(portfolio_id)
+        assert position["notes"] == "Long-term hold"
+
+    def test_empty_note_clears_it(self, portfolio_id):
+        payload = {"portfolioId": portfolio_id, "isin": self.ISIN, "note": "Trim at 150"}
+        handle_set_position_note(cmd_id=1, payload=payload)
+        handle_set_position_note(cmd_id=2, payload={**payload, "note": ""})
+
+        [position] = database.get_positions(portfolio_id)
+        assert position["notes"] is None
+
+    def test_unknown_position_is_reported(self, portfolio_id):
+        result = handle_set_position_note(
+            cmd_id=1,
+            payload={"portfolioId": portfolio_id, "isin": "US0378331005", "note": "Watch"},
+        )
+
+        assert result["error"]["code"] == "POSITION_NOT_FOUND"
@@ -0,0 +1,61 @@
+"""Unit tests for settings handlers."""
+
+import pytest
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.settings import (
+    configure_from_env,
+    get_setting,
+    handle_configure,
+    is_hive_contribution_enabled,
+)
+
+
+@pytest.fixture
+def db(tmp_path, monkeypatch):
+    """Empty engine database in a temp data dir."""
+    monkeypatch.setenv("PRISM_DATA_DIR", str(tmp_path))
+    database.init_db().close()
+    return tmp_path
+
+
+class TestConfigure:
+    def test_stores_pushed_settings(self, db):
+        result = handle_configure(
+            1, {"currency": "USD", "locale": "en-US", "hiveContributions": True}
+        )
+
+        assert result["success"] is True
+        assert get_setting("currency") == "USD"
+        assert get_setting("locale") == "en-US"
+        assert is_hive_contribution_enabled() is True
+
+    def test_absent_settings_are_kept(self, db):
+        handle_configure(1, {"currency": "USD"})
+        handle_configure(2, {"hiveContributions": False})
+
+        assert get_setting("currency") == "USD"
+        assert is_hive_contribution_enabled() is False
+
+    def test_wrong_types_are_rejected(self, db):
+        result = handle_configure(1, {"hiveContributions": "yes"})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
+
+
+class TestConfigureFromEnv:
+    def test_keeps_hive_opt_in_without_a_choice(self, db, monkeypatch):
+        monkeypatch.setenv("PRISM_CURRENCY", "CHF")
+        monkeypatch.delenv("PRISM_HIVE_CONTRIBUTIONS", raising=False)
+
+        configure_from_env()
+
+        assert get_setting("currency") == "CHF"
+        assert is_hive_contribution_enabled() is True
+
+    def test_applies_the_shell_choice(self, db, monkeypatch):
+        monkeypatch.setenv("PRISM_HIVE_CONTRIBUTIONS", "0")
+
+        configure_from_env()
+
+        assert is_hive_contribution_enabled() is False
@@ -1,15 +1,22 @@
 """Tests for headless/handlers/sync.py - Sync and pipeline handlers."""
 
+import asyncio
 import json
+import threading
+import time
 from unittest.mock import MagicMock, patch
 
 import pytest
 
 from portfolio_src.headless.handlers.sync import (
+    emit_invalidated,
     emit_progress,
+    handle_cancel_pipeline,
+    handle_get_pipeline_capabilities,
     handle_run_pipeline,
     handle_sync_portfolio,
 )
+from portfolio_src.models.sync import PipelineResult
 
 
 class TestEmitProgress:
@@ -54,6 +61,20 @@ class TestEmitProgress:
         assert output["data"]["message"] == ""
 
 
+class TestEmitInvalidated:
+    """Tests for emit_invalidated()."""
+
+    def test_emits_scope_to_stdout(self, capsys):
+        """Emits a data_invalidated event carrying the scope."""
+        emit_invalidated("positions")
+
+        captured = capsys.readouterr()
+        output = json.loads(captured.out.strip())
+
+        assert output["event"] == "data_invalidated"
+        assert output["data"] == {"scope": "positions"}
+
+
 class TestHandleRunPipeline:
     """Tests for handle_run_pipeline()."""
 
@@ -135,6 +156,170 @@ class TestHandleRunPipeline:
                 # Should emit at least start and end progress
                 assert mock_emit.call_count >= 2
 
+    @pytest.mark.asyncio
+    async def test_invalidates_allocations_and_report(self):
+        """Marks allocations and the report stale after a run."""
+        mock_result = MagicMock()
+        mock_result.success = True
+        mock_result.errors = []
+
+        mock_pipeline = MagicMock()
+        mock_pipeline.run.return_value = mock_result
+
+        with patch(
+            "portfolio_src.core.pipeline.Pipeline",
+            return_value=mock_pipeline,
+        ):
+            with patch("portfolio_src.headless.handlers.sync.emit_progress"):
+                with patch(
+                    "portfolio_src.headless.handlers.sync.emit_invalidated"
+                ) as mock_invalidated:
+                    await handle_run_pipeline(1, {})
+
+        scopes = [call.args[0] for call in mock_invalidated.call_args_list]
+        assert scopes == ["allocations", "report"]
+
+
+    @pytest.mark.asyncio
+    async def test_passes_portfolio_config_to_service(self):
+        """Runs the requested portfolio with its configured stages and sources."""
+        mock_service = MagicMock()
+        mock_service.run_pipeline.return_value = PipelineResult(
+            success=True, errors=[], duration_ms=10
+        )
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            await handle_run_pipeline(
+                1,
+                {
+                    "portfolioId": 3,
+                    "config": {
+                        "enabledStages": ["load", "aggregate", "report"],
+                        "dataSources": ["hive"],
+                        "refreshCadence": "manual",
+                    },
+                },
+            )
+
+        call_kwargs = mock_service.run_pipeline.call_args[1]
+        assert call_kwargs["portfolio_id"] == 3
+        assert call_kwargs["stages"] == ["load", "aggregate", "report"]
+        assert call_kwargs["data_sources"] == ["hive"]
+
+    @pytest.mark.asyncio
+    async def test_rejects_unknown_stage(self):
+        """Rejects a config naming a stage the engine does not declare."""
+        mock_service = MagicMock()
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            result = await handle_run_pipeline(1, {"config": {"enabledStages": ["prices"]}})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
+        mock_service.run_pipeline.assert_not_called()
+
+
+    @pytest.mark.asyncio
+    async def test_single_stage_runs_with_required_stages_only(self):
+        """A stage run enables that stage and the required ones, in run order."""
+        mock_service = MagicMock()
+        mock_service.run_pipeline.return_value = PipelineResult(
+            success=True, errors=[], duration_ms=10
+        )
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            await handle_run_pipeline(
+                1,
+                {
+                    "stage": "decompose",
+                    "config": {"enabledStages": [], "dataSources": ["adapters"]},
+                },
+            )
+
+        call_kwargs = mock_service.run_pipeline.call_args[1]
+        assert call_kwargs["stages"] == ["load", "decompose", "aggregate", "report"]
+        assert call_kwargs["data_sources"] == ["adapters"]
+
+    @pytest.mark.asyncio
+    async def test_rejects_unknown_single_stage(self):
+        """Rejects a stage run for a stage the engine does not declare."""
+        mock_service = MagicMock()
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            result = await handle_run_pipeline(1, {"stage": "prices"})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
+        mock_service.run_pipeline.assert_not_called()
+
+
+class TestHandleGetPipelineCapabilities:
+    """Tests for handle_get_pipeline_capabilities()."""
+
+    def test_declares_stages_sources_and_required_stages(self):
+        """Required stages are a subset of the declared stages."""
+        result = handle_get_pipeline_capabilities(1, {})
+
+        data = result["data"]
+        assert data["stages"][0] == "load"
+        assert "decompose" in data["stages"]
+        assert set(data["requiredStages"]) <= set(data["stages"])
+        assert data["dataSources"] == ["hive", "adapters", "apis"]
+
+
+class TestHandleCancelPipeline:
+    """Tests for handle_cancel_pipeline()."""
+
+    @pytest.mark.asyncio
+    async def test_stops_running_pipeline(self):
+        """Sets the cancel flag and waits until the run has stopped."""
+        started = threading.Event()
+
+        def run_pipeline(**kwargs):
+            started.set()
+            while not kwargs["should_cancel"]():
+                time.sleep(0.01)
+            return PipelineResult(
+                success=False, errors=["Pipeline cancelled"], duration_ms=5, cancelled=True
+            )
+
+        mock_service = MagicMock()
+        mock_service.run_pipeline.side_effect = run_pipeline
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            with patch(
+                "portfolio_src.headless.handlers.sync.emit_invalidated"
+            ) as mock_invalidated:
+                run = asyncio.create_task(handle_run_pipeline(1, {}))
+                await asyncio.get_running_loop().run_in_executor(None, started.wait, 5)
+                cancel = await handle_cancel_pipeline(2, {})
+                result = await run
+
+        assert cancel["data"]["cancelled"] is True
+        assert result["data"]["cancelled"] is True
+        mock_invalidated.assert_not_called()
+
+    @pytest.mark.asyncio
+    async def test_reports_nothing_to_cancel(self):
+        """Acknowledges without cancelling when no pipeline is running."""
+        result = await handle_cancel_pipeline(1, {})
+
+        assert result["success"] is True
+        assert result["data"]["cancelled"] is False
+
 
 class TestHandleSyncPortfolio:
     """Tests for handle_sync_portfolio()."""
@@ -210,3 +395,78 @@ class TestHandleSyncPortfolio:
         assert result["data"]["newPositions"] == 1
         assert result["data"]["totalValue"] == 1100.0
         assert "durationMs" in result["data"]
+
+    @pytest.mark.asyncio
+    async def test_invalidates_positions_on_success(self):
+        """Marks positions stale after a successful sync."""
+        from portfolio_src.models.sync import PortfolioSyncResult
+
+        mock_service = MagicMock()
+        mock_service.sync_portfolio.return_value = PortfolioSyncResult(
+            synced_positions=1,
+            new_positions=0,
+            updated_positions=1,
+            total_value=500.0,
+            duration_ms=80,
+        )
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            with patch("portfolio_src.headless.handlers.sync.emit_progress"):
+                with patch(
+                    "portfolio_src.headless.handlers.sync.emit_invalidated"
+                ) as mock_invalidated:
+                    await handle_sync_portfolio(1, {})
+
+        mock_invalidated.assert_called_once_with("positions")
+
+    @pytest.mark.asyncio
+    async def test_syncs_requested_isins_and_reports_failures(self):
+        """Passes 'isins' to the service and returns its failures."""
+        from portfolio_src.models.sync import PortfolioSyncResult, PositionSyncFailure
+
+        mock_service = MagicMock()
+        mock_service.sync_portfolio.return_value = PortfolioSyncResult(
+            synced_positions=1,
+            new_positions=0,
+            updated_positions=1,
+            total_value=500.0,
+            duration_ms=80,
+            failures=[
+                PositionSyncFailure(
+                    isin="US0378331005", reason="No price available", retryable=True
+                )
+            ],
+        )
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            with patch("portfolio_src.headless.handlers.sync.emit_progress"):
+                with patch("portfolio_src.headless.handlers.sync.emit_invalidated"):
+                    result = await handle_sync_portfolio(
+                        1, {"isins": ["DE0007164600", "US0378331005"], "force": True}
+                    )
+
+        call_kwargs = mock_service.sync_portfolio.call_args[1]
+        assert call_kwargs["isins"] == ["DE0007164600", "US0378331005"]
+        assert result["data"]["failures"] == [
+            {"isin": "US0378331005", "reason": "No price available", "retryable": True}
+        ]
+
+    @pytest.mark.asyncio
+    async def test_rejects_invalid_isins(self):
+        """Rejects an 'isins' value that is not a list of strings."""
+        mock_service = MagicMock()
+
+        with patch(
+            "portfolio_src.headless.handlers.sync.get_sync_service",
+            return_value=mock_service,
+        ):
+            result = await handle_sync_portfolio(1, {"isins": "DE0007164600"})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
+        mock_service.sync_portfolio.assert_not_called()
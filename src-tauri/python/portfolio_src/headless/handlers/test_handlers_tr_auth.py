@@ -8,8 +8,10 @@ from portfolio_src.headless.handlers.tr_auth import (
     handle_tr_logout,
     handle_tr_get_auth_status,
     handle_tr_restore_session,
+    handle_tr_reconnect_session,
     handle_tr_submit_2fa,
     handle_tr_check_saved_session,
+    handle_clear_credentials,
 )
 
 
@@ -146,6 +148,40 @@ class TestTRLogout:
         assert "session cleared" in result["data"]["message"].lower()
 
 
+class TestClearCredentials:
+    """Tests for handle_clear_credentials handler."""
+
+    @pytest.mark.asyncio
+    @patch("portfolio_src.headless.handlers.tr_auth.os.path.exists", return_value=False)
+    @patch("portfolio_src.headless.handlers.tr_auth.get_auth_manager")
+    async def test_logs_out_and_deletes_saved_login(self, mock_get_auth, _mock_exists):
+        """Should end the session and delete the stored credentials."""
+        mock_auth = MagicMock()
+        mock_auth.delete_credentials = MagicMock(return_value=True)
+        mock_get_auth.return_value = mock_auth
+
+        result = await handle_clear_credentials(cmd_id=7, payload={})
+
+        assert result["success"] is True
+        assert result["data"]["credentialsCleared"] is True
+        mock_auth.logout.assert_called_once()
+        mock_auth.delete_credentials.assert_called_once()
+
+    @pytest.mark.asyncio
+    @patch("portfolio_src.headless.handlers.tr_auth.os.path.exists", return_value=False)
+    @patch("portfolio_src.headless.handlers.tr_auth.get_auth_manager")
+    async def test_reports_credentials_it_could_not_delete(self, mock_get_auth, _mock_exists):
+        """Should fail instead of claiming the login is gone."""
+        mock_auth = MagicMock()
+        mock_auth.delete_credentials = MagicMock(return_value=False)
+        mock_get_auth.return_value = mock_auth
+
+        result = await handle_clear_credentials(cmd_id=8, payload={})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "CLEAR_CREDENTIALS_FAILED"
+
+
 class TestTRRestoreSession:
     """Tests for handle_tr_restore_session handler."""
 
@@ -188,3 +224,39 @@ class TestTRRestoreSession:
         assert result["success"] is True
         assert result["data"]["authState"] == "idle"
         assert "expired" in result["data"]["message"].lower()
+
+
+class TestTRReconnectSession:
+    """Tests for handle_tr_reconnect_session handler."""
+
+    @pytest.mark.asyncio
+    @patch("portfolio_src.headless.handlers.tr_auth.get_auth_manager")
+    async def test_returns_authenticated_when_reconnect_succeeds(self, mock_get_auth):
+        """Should return authenticated state when the session reconnects."""
+        mock_auth = MagicMock()
+        mock_auth.reconnect_session = AsyncMock(
+            return_value=MagicMock(
+                success=True,
+                state=MagicMock(value="authenticated"),
+                message="Session reconnected",
+            )
+        )
+        mock_get_auth.return_value = mock_auth
+
+        result = await handle_tr_reconnect_session(cmd_id=12, payload={})
+
+        assert result["success"] is True
+        assert result["data"]["authState"] == "authenticated"
+
+    @pytest.mark.asyncio
+    @patch("portfolio_src.headless.handlers.tr_auth.get_auth_manager")
+    async def test_reconnect_exception_returns_error(self, mock_get_auth):
+        """Should return an error response when the reconnect raises."""
+        mock_auth = MagicMock()
+        mock_auth.reconnect_session = AsyncMock(side_effect=RuntimeError("daemon gone"))
+        mock_get_auth.return_value = mock_auth
+
+        result = await handle_tr_reconnect_session(cmd_id=13, payload={})
+
+        assert result["success"] is False
+        assert result["error"]["code"] == "TR_RECONNECT_ERROR"
@@ -0,0 +1,74 @@
+"""Unit tests for transaction ledger handlers."""
+
+import pytest
+
+from portfolio_src.data import database
+from portfolio_src.headless.handlers.transactions import handle_get_transactions
+
+ISIN = "DE0007164600"
+OTHER_ISIN = "US0378331005"
+
+
+@pytest.fixture
+def portfolio_id(tmp_path, monkeypatch):
+    """Portfolio with a buy, a dividend and a sell in a temp engine database."""
+    monkeypatch.setenv("PRISM_DATA_DIR", str(tmp_path))
+    database.init_db().close()
+    portfolio = database.create_portfolio("Main")
+    database.upsert_asset(ISIN, "SAP SE", "SAP", "Stock")
+    database.upsert_asset(OTHER_ISIN, "Apple Inc.", "AAPL", "Stock")
+    rows = [
+        ("t1", ISIN, "Buy", "2025-01-10", 2.0, -240.0, None),
+        ("t2", OTHER_ISIN, "Dividend", "2025-02-15", None, 3.5, "Q1"),
+        ("t3", ISIN, "Sell", "2025-03-01T09:30:00", 1.0, 130.0, None),
+    ]
+    with database.transaction() as conn:
+        conn.executemany(
+            """
+            INSERT INTO transactions (id, portfolio_id, isin, type, date, quantity, amount,
+                                      currency, notes)
+            VALUES (?, ?, ?, ?, ?, ?, ?, 'EUR', ?)
+            """,
+            [
+                (id_, portfolio["id"], isin, type_, date, qty, amount, notes)
+                for id_, isin, type_, date, qty, amount, notes in rows
+            ],
+        )
+    return portfolio["id"]
+
+
+class TestGetTransactions:
+    def test_returns_ledger_oldest_first(self, portfolio_id):
+        result = handle_get_transactions(cmd_id=1, payload={"portfolioId": portfolio_id})
+
+        assert result["success"] is True
+        transactions = result["data"]["transactions"]
+        assert [t["id"] for t in transactions] == ["t1", "t2", "t3"]
+        assert transactions[0]["portfolioId"] == portfolio_id
+        assert transactions[0]["quantity"] == 2.0
+        assert "quantity" not in transactions[1]
+        assert transactions[1]["notes"] == "Q1"
+
+    def test_date_bounds_are_inclusive_days(self, portfolio_id):
+        result = handle_get_transactions(
+            cmd_id=1,
+            payload={"portfolioId": portfolio_id, "from": "2025-02-15", "to": "2025-03-01"},
+        )
+
+        assert [t["id"] for t in result["data"]["transactions"]] == ["t2", "t3"]
+
+    def test_filters_by_type_and_isin(self, portfolio_id):
+        by_type = handle_get_transactions(
+            cmd_id=1, payload={"portfolioId": portfolio_id, "types": ["Buy", "Sell"]}
+        )
+        by_isin = handle_get_transactions(
+            cmd_id=2, payload={"portfolioId": portfolio_id, "isin": OTHER_ISIN}
+        )
+
+        assert [t["id"] for t in by_type["data"]["transactions"]] == ["t1", "t3"]
+        assert [t["id"] for t in by_isin["data"]["transactions"]] == ["t2"]
+
+    def test_missing_portfolio_id_is_rejected(self, portfolio_id):
+        result = handle_get_transactions(cmd_id=1, payload={})
+
+        assert result["error"]["code"] == "INVALID_PARAMS"
@@ -12,7 +12,7 @@ from portfolio_src.headless.responses import success_response, error_response
 from portfolio_src.headless.state import get_auth_manager, get_bridge, get_executor
 from portfolio_src.prism_utils.logging_config import get_logger
 from portfolio_src.prism_utils.validation import (
-    get_safe_data_dir,
+    get_session_dir,
     is_safe_path_within_directory,
 )
 
@@ -79,20 +79,20 @@ async def handle_tr_check_saved_session(cmd_id: int, payload: dict[str, Any]) ->
         loop = asyncio.get_event_loop()
         executor = get_executor()
 
-        # SECURITY: Use validated data directory to prevent path traversal attacks
+        # SECURITY: Use validated session directory to prevent path traversal attacks
         try:
-            data_dir = get_safe_data_dir()
+            session_dir = get_session_dir()
         except ValueError as e:
             logger.error(
-                "Invalid PRISM_DATA_DIR configuration",
+                "Invalid session directory configuration",
                 extra={"error": str(e), "error_type": type(e).__name__},
             )
             return error_response(cmd_id, "TR_CONFIG_ERROR", "Invalid data directory configuration")
 
-        cookies_file = os.path.join(data_dir, ALLOWED_COOKIE_FILENAME)
+        cookies_file = os.path.join(session_dir, ALLOWED_COOKIE_FILENAME)
 
         # SECURITY: Validate the cookie file path is within allowed directory
-        if not is_safe_path_within_directory(cookies_file, data_dir):
+        if not is_safe_path_within_directory(cookies_file, session_dir):
             logger.warning(
                 "Cookie file path validation failed", extra={"cookies_file": cookies_file}
             )
@@ -209,6 +209,35 @@ async def handle_tr_restore_session(cmd_id: int, payload: dict[str, Any]) -> dic
         return error_response(cmd_id, "TR_RESTORE_ERROR", str(e))
 
 
+async def handle_tr_reconnect_session(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Reconnect the active Trade Republic session.
+
+    Sent by the shell after the system resumed from sleep, when the daemon's
+    websocket is likely dead although the session still reads as authenticated.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Command payload (unused).
+
+    Returns:
+        Success response with auth state, or error response.
+    """
+    try:
+        auth_manager = get_auth_manager()
+        result = await auth_manager.reconnect_session()
+        return success_response(
+            cmd_id,
+            {"authState": result.state.value, "message": result.message},
+        )
+    except Exception as e:
+        logger.error(
+            "Reconnect session error",
+            extra={"error": str(e), "error_type": type(e).__name__},
+            exc_info=True,
+        )
+        return error_response(cmd_id, "TR_RECONNECT_ERROR", str(e))
+
+
 async def handle_tr_login(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
     """Start Trade Republic login process with phone + PIN.
 
@@ -327,21 +356,21 @@ async def handle_tr_logout(cmd_id: int, payload: dict[str, Any]) -> dict[str, An
         auth_manager = get_auth_manager()
         await loop.run_in_executor(executor, auth_manager.logout)
 
-        # SECURITY: Use validated data directory to prevent path traversal attacks
+        # SECURITY: Use validated session directory to prevent path traversal attacks
         try:
-            data_dir = get_safe_data_dir()
+            session_dir = get_session_dir()
         except ValueError as e:
             logger.error(
-                "Invalid PRISM_DATA_DIR configuration",
+                "Invalid session directory configuration",
                 extra={"error": str(e), "error_type": type(e).__name__},
             )
             return error_response(cmd_id, "TR_CONFIG_ERROR", "Invalid data directory configuration")
 
-        cookies_file = os.path.join(data_dir, ALLOWED_COOKIE_FILENAME)
+        cookies_file = os.path.join(session_dir, ALLOWED_COOKIE_FILENAME)
 
         # SECURITY: Validate the cookie file path before deletion
         # Prevents attackers from using symlinks to delete arbitrary files
-        if not is_safe_path_within_directory(cookies_file, data_dir):
+        if not is_safe_path_within_directory(cookies_file, session_dir):
             logger.warning(
                 "Cookie file path validation failed", extra={"cookies_file": cookies_file}
             )
@@ -368,3 +397,42 @@ async def handle_tr_logout(cmd_id: int, payload: dict[str, Any]) -> dict[str, An
             "Logout error", extra={"error": str(e), "error_type": type(e).__name__}, exc_info=True
         )
         return error_response(cmd_id, "TR_LOGOUT_ERROR", str(e))
+
+
+async def handle_clear_credentials(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Forget the broker login: end the session and delete the saved phone
+    number and PIN from the credentials file and the keyring.
+
+    Used by the shell's credentials reset and data deletion, which report a
+    failure here instead of claiming the login is gone.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Command payload (unused).
+
+    Returns:
+        Success response with auth state, or error response.
+    """
+    logout = await handle_tr_logout(cmd_id, payload)
+    if not logout["success"]:
+        return logout
+
+    try:
+        loop = asyncio.get_event_loop()
+        auth_manager = get_auth_manager()
+        deleted = await loop.run_in_executor(get_executor(), auth_manager.delete_credentials)
+    except Exception as e:
+        logger.error(
+            "Clearing credentials failed",
+            extra={"error": str(e), "error_type": type(e).__name__},
+            exc_info=True,
+        )
+        return error_response(cmd_id, "CLEAR_CREDENTIALS_FAILED", str(e))
+
+    if not deleted:
+        return error_response(
+            cmd_id, "CLEAR_CREDENTIALS_FAILED", "Saved broker login could not be deleted"
+        )
+
+    logger.info("Broker credentials cleared")
+    return success_response(cmd_id, {"authState": "idle", "credentialsCleared": True})
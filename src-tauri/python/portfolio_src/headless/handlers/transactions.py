@@ -0,0 +1,52 @@
+"""Transaction Ledger Handlers.
+
+Serves the trade, dividend and fee ledger of a portfolio. The shell
+validates dates, types and ISINs before asking.
+"""
+
+from typing import Any
+
+from portfolio_src.data import database
+from portfolio_src.headless.responses import error_response, success_response
+
+
+def _transaction(row: dict[str, Any]) -> dict[str, Any]:
+    transaction = {
+        "id": row["id"],
+        "portfolioId": row["portfolio_id"],
+        "isin": row["isin"],
+        "type": row["type"],
+        "date": row["date"],
+        "amount": row["amount"],
+        "currency": row["currency"],
+    }
+    if row["quantity"] is not None:
+        transaction["quantity"] = row["quantity"]
+    if row["notes"]:
+        transaction["notes"] = row["notes"]
+    return transaction
+
+
+def handle_get_transactions(cmd_id: int, payload: dict[str, Any]) -> dict[str, Any]:
+    """Get ledger entries, optionally filtered.
+
+    Args:
+        cmd_id: IPC command identifier.
+        payload: Must contain 'portfolioId'; optional 'from' and 'to'
+            (YYYY-MM-DD, inclusive), 'types' and 'isin'.
+
+    Returns:
+        Success response with the transactions, oldest first.
+    """
+    portfolio_id = payload.get("portfolioId")
+    if isinstance(portfolio_id, bool) or not isinstance(portfolio_id, int):
+        return error_response(cmd_id, "INVALID_PARAMS", "portfolioId is required")
+
+    rows = database.get_transactions(
+        portfolio_id,
+        date_from=payload.get("from"),
+        date_to=payload.get("to"),
+        types=payload.get("types"),
+        isin=payload.get("isin"),
+    )
+    return success_response(cmd_id, {"transactions": [_transaction(row) for row in rows]})
@@ -200,9 +200,12 @@ def install_default_config() -> None:
 def init_database() -> None:
     """Initialize the SQLite database.
 
-    Creates tables if they don't exist and applies any pending migrations.
+    Creates tables if they don't exist, applies any pending migrations and
+    stores the settings the shell passed in the launch environment.
     """
     from portfolio_src.data.database import init_db
+    from portfolio_src.headless.handlers.settings import configure_from_env
 
     init_db()
     logger.debug("Database initialized")
+    configure_from_env()
@@ -6,8 +6,12 @@ This module has NO dependencies on handlers or dispatcher to avoid circular impo
 
 import json
 import sys
+import threading
 from typing import Any
 
+# Pipeline runs report progress from a worker thread
+_write_lock = threading.Lock()
+
 
 def write_protocol(data: dict[str, Any]) -> None:
     """Write JSON protocol message to stdout (IPC channel).
@@ -15,5 +19,7 @@ def write_protocol(data: dict[str, Any]) -> None:
     This is the designated method for all IPC protocol output.
     Logging should use get_logger() which writes to stderr.
     """
-    print(json.dumps(data))
-    sys.stdout.flush()
+    line = json.dumps(data)
+    with _write_lock:
+        print(line)
+        sys.stdout.flush()
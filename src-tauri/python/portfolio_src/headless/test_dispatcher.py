@@ -25,6 +25,7 @@ class TestHandlerRegistry:
             "tr_get_auth_status",
             "tr_check_saved_session",
             "tr_restore_session",
+            "tr_reconnect_session",
             "tr_get_stored_credentials",
             "tr_login",
             "tr_submit_2fa",
@@ -41,13 +42,32 @@ class TestHandlerRegistry:
             "get_pending_reviews",
             "set_hive_contribution",
             "get_hive_contribution",
+            "upload_holdings_chunk",
+            "hive_contribution_approved",
+            "hive_contribution_blocked",
+            "clear_credentials",
+            "list_portfolios",
+            "create_portfolio",
+            "rename_portfolio",
+            "delete_portfolio",
+            "get_transactions",
+            "get_quotes",
+            "vacuum_database",
+            "get_pipeline_capabilities",
+            "cancel_pipeline",
+            "get_asset_details",
+            "get_event_calendar",
+            "import_holdings",
+            "set_position_note",
+            "configure",
+            "hive_decomposition_response",
         }
 
         assert set(HANDLER_REGISTRY.keys()) == expected_commands
 
     def test_registry_has_expected_handler_count(self):
         """Should have expected number of handlers registered."""
-        assert len(HANDLER_REGISTRY) == 23
+        assert len(HANDLER_REGISTRY) == 43
 
     def test_all_handlers_are_callable(self):
         """All registered handlers should be callable."""
@@ -151,7 +171,7 @@ class TestDispatcherHelpers:
         commands = get_available_commands()
 
         assert isinstance(commands, list)
-        assert len(commands) == 23
+        assert len(commands) == 43
         assert commands == sorted(commands)
 
     def test_is_command_registered_returns_true_for_valid(self):
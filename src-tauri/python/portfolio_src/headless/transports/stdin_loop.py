@@ -21,6 +21,14 @@ logger = get_logger(__name__)
 # Engine version - should match the version in lifecycle
 VERSION = "0.1.0"
 
+# Long-running commands are dispatched as tasks so the loop keeps reading
+# stdin meanwhile, e.g. to deliver cancel_pipeline during a pipeline run.
+BACKGROUND_COMMANDS = frozenset({"run_pipeline"})
+
+
+async def _dispatch_and_respond(cmd: dict) -> None:
+    write_protocol(await dispatch(cmd))
+
 
 async def run_stdin_loop() -> None:
     """Run the stdin/stdout command loop.
@@ -31,7 +39,7 @@ async def run_stdin_loop() -> None:
     Protocol:
         1. On startup, emits a ready signal: {"status": "ready", "version": "...", "pid": ...}
         2. Reads one JSON command per line from stdin
-        3. Dispatches command to handler
+        3. Dispatches command to handler (BACKGROUND_COMMANDS without waiting)
         4. Writes JSON response to stdout
         5. Repeats until stdin closes or KeyboardInterrupt
 
@@ -39,8 +47,12 @@ async def run_stdin_loop() -> None:
         Uses a ThreadPoolExecutor for blocking stdin.readline() to avoid
         blocking the asyncio event loop.
     """
+    from portfolio_src.data import shell_decomposition
     from portfolio_src.prism_utils.sentinel import audit_previous_session
 
+    # The shell answers hive_decomposition_request events on this channel
+    shell_decomposition.attach()
+
     # Start background audit of previous session logs
     asyncio.create_task(audit_previous_session())
 
@@ -56,6 +68,7 @@ async def run_stdin_loop() -> None:
 
     loop = asyncio.get_event_loop()
     executor = ThreadPoolExecutor(max_workers=1, thread_name_prefix="stdin")
+    background_tasks: set[asyncio.Task[None]] = set()
 
     while True:
         try:
@@ -89,6 +102,12 @@ async def run_stdin_loop() -> None:
                 )
                 continue
 
+            if isinstance(cmd, dict) and cmd.get("command") in BACKGROUND_COMMANDS:
+                task = asyncio.create_task(_dispatch_and_respond(cmd))
+                background_tasks.add(task)
+                task.add_done_callback(background_tasks.discard)
+                continue
+
             response = await dispatch(cmd)
             write_protocol(response)
 
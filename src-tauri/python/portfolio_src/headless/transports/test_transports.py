@@ -209,10 +209,10 @@ class TestIntegration:
     """Integration tests for the refactored headless engine."""
 
     def test_all_handlers_registered(self):
-        """All 23 handlers are registered in the registry."""
+        """All 43 handlers are registered in the registry."""
         from portfolio_src.headless.handlers import HANDLER_REGISTRY
 
-        assert len(HANDLER_REGISTRY) == 23
+        assert len(HANDLER_REGISTRY) == 43
 
     def test_dispatch_available(self):
         """Dispatch function is importable from main package."""
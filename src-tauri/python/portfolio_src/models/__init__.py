@@ -26,6 +26,7 @@ from .dashboard import (
 from .sync import (
     SyncProgress,
     PortfolioSyncResult,
+    PositionSyncFailure,
     PipelineResult,
     ClassifiedPosition,
     AuthStatus,
@@ -56,6 +57,7 @@ __all__ = [
     # Sync DTOs
     "SyncProgress",
     "PortfolioSyncResult",
+    "PositionSyncFailure",
     "PipelineResult",
     "ClassifiedPosition",
     "AuthStatus",
@@ -22,6 +22,20 @@ class SyncProgress(BaseModel):
     phase: str = "pipeline"
 
 
+class PositionSyncFailure(BaseModel):
+    """Position a sync could not refresh.
+
+    Attributes:
+        isin: International Securities Identification Number.
+        reason: Human-readable failure reason.
+        retryable: Whether a later targeted sync may succeed.
+    """
+
+    isin: str
+    reason: str
+    retryable: bool = False
+
+
 class PortfolioSyncResult(BaseModel):
     """Result of portfolio synchronization with Trade Republic.
 
@@ -34,6 +48,7 @@ class PortfolioSyncResult(BaseModel):
         etf_count: Number of positions classified as ETF.
         crypto_count: Number of positions classified as crypto.
         stock_count: Number of positions classified as stock.
+        failures: Positions that could not be synced; empty on full success.
     """
 
     synced_positions: int = Field(ge=0)
@@ -44,6 +59,7 @@ class PortfolioSyncResult(BaseModel):
     etf_count: int = Field(ge=0, default=0)
     crypto_count: int = Field(ge=0, default=0)
     stock_count: int = Field(ge=0, default=0)
+    failures: list[PositionSyncFailure] = Field(default_factory=list)
 
 
 class PipelineResult(BaseModel):
@@ -53,11 +69,13 @@ class PipelineResult(BaseModel):
         success: Whether pipeline completed without errors.
         errors: List of error messages if any.
         duration_ms: Pipeline execution duration in milliseconds.
+        cancelled: Whether the run was stopped by cancel_pipeline.
     """
 
     success: bool
     errors: list[str] = Field(default_factory=list)
     duration_ms: int = Field(ge=0)
+    cancelled: bool = False
 
 
 class ClassifiedPosition(BaseModel):
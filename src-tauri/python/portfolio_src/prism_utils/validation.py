@@ -102,6 +102,31 @@ def get_safe_data_dir() -> str:
     return resolved
 
 
+def get_session_dir() -> str:
+    """
+    Get the directory holding the broker session files (cookies, saved login).
+
+    The app decrypts them into a private temp dir passed as PRISM_SESSION_DIR
+    and encrypts them back into the data dir; without it they live in the
+    data dir.
+
+    Returns:
+        Validated session directory path (expanded and absolute)
+
+    Raises:
+        ValueError: If PRISM_SESSION_DIR or PRISM_DATA_DIR contains path
+            traversal patterns
+    """
+    session_dir = os.environ.get("PRISM_SESSION_DIR")
+    if not session_dir:
+        return get_safe_data_dir()
+
+    if PATH_TRAVERSAL_PATTERN.search(session_dir):
+        raise ValueError("PRISM_SESSION_DIR contains invalid path traversal patterns")
+
+    return str(Path(os.path.expanduser(session_dir)).resolve())
+
+
 def is_valid_isin(identifier: str) -> bool:
     """
     Validates if the given string is a valid ISIN (International Securities Identification Number).
@@ -0,0 +1,465 @@
+//! App Lock
+//!
+//! Keeps portfolio data behind a lock screen. The lock is active when the
+//! `require_os_auth` setting is on or an app PIN is set; while locked, the
+//! command router rejects every command except the few in
+//! `UNGATED_COMMANDS` that show the lock state or unlock. Settings, backups,
+//! resets and exports are gated like the portfolio itself, so the lock
+//! cannot be switched off from behind it.
+//!
+//! The app starts locked and locks again after `auto_lock_minutes` without a
+//! gated command, or on `lock_app`; each time an `app-locked` event is
+//! emitted so the frontend shows its lock screen. It unlocks through the
+//! operating system's owner check (Touch ID or the account password via
+//! LocalAuthentication on macOS, Windows Hello on Windows) or the app PIN.
+//!
+//! The PIN is stored in the keychain as a salted, iterated SHA-256 hash.
+//! After `MAX_PIN_ATTEMPTS` wrong PINs, every further attempt has to wait
+//! `PIN_LOCKOUT_SECS` after the previous failure. The count is kept in the
+//! keychain too, so restarting the app does not reset it. Platforms without
+//! an owner check cannot turn on `require_os_auth`; the PIN works everywhere.
+
+use crate::app_settings::{self, GeneralSettings};
+use crate::keychain;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use zeroize::Zeroizing;
+
+pub const EVENT: &str = "app-locked";
+
+/// Commands that run while the app is locked: lock state and unlocking.
+/// Everything else needs an unlocked app.
+pub const UNGATED_COMMANDS: &[&str] = &[
+    "get_app_lock_status",
+    "get_os_auth_status",
+    "lock_app",
+    "unlock_with_os_auth",
+    "verify_app_pin",
+];
+
+pub const LOCKED_ERROR: &str = "App is locked: unlock to continue";
+
+/// Text shown in the system prompt
+const REASON: &str = "unlock your portfolio";
+
+/// Keychain entry holding the PIN hash
+pub const PIN_KEY: &str = "app_pin";
+
+/// Keychain entry holding the wrong PIN count and the last failure,
+/// `count:unix_seconds`
+pub const PIN_FAILURES_KEY: &str = "app_pin_failures";
+
+/// Allowed PIN length, in digits
+const PIN_DIGITS: (usize, usize) = (4, 8);
+
+/// SHA-256 rounds per PIN check
+const PIN_HASH_ROUNDS: u32 = 100_000;
+
+/// Wrong PINs accepted before attempts are throttled
+const MAX_PIN_ATTEMPTS: u32 = 5;
+
+/// Wait after a wrong PIN once `MAX_PIN_ATTEMPTS` is reached
+const PIN_LOCKOUT_SECS: u64 = 60;
+
+/// How often the inactivity timer checks
+const TIMER_INTERVAL_SECS: u64 = 15;
+
+static OS_AUTH_REQUIRED: AtomicBool = AtomicBool::new(false);
+
+static PIN_SET: AtomicBool = AtomicBool::new(false);
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+static AUTO_LOCK_MINUTES: AtomicU64 = AtomicU64::new(0);
+
+/// Last gated command or unlock, unix seconds
+static LAST_USE: AtomicI64 = AtomicI64::new(0);
+
+/// Serializes PIN checks so concurrent attempts cannot skip the count
+static PIN_CHECK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLockStatus {
+    pub locked: bool,
+    pub os_auth_required: bool,
+    /// The platform offers an owner check
+    pub os_auth_available: bool,
+    pub pin_set: bool,
+    pub auto_lock_minutes: u64,
+}
+
+/// Lock state in the shape of the former `get_os_auth_status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OsAuthStatus {
+    pub required: bool,
+    /// The platform offers an owner check
+    pub available: bool,
+    pub locked: bool,
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn is_required() -> bool {
+    OS_AUTH_REQUIRED.load(Ordering::Relaxed) || PIN_SET.load(Ordering::Relaxed)
+}
+
+/// Load the settings and PIN at launch; the app starts locked when a lock is
+/// configured.
+pub fn init(data_dir: &Path) {
+    configure(&app_settings::load_general(data_dir));
+    match keychain::get_secret(PIN_KEY) {
+        Ok(pin) => PIN_SET.store(pin.is_some(), Ordering::Relaxed),
+        Err(e) => tracing::warn!("Failed to read app PIN: {}", e),
+    }
+    LOCKED.store(is_required(), Ordering::Relaxed);
+}
+
+/// Apply a settings change. Turning the lock on does not lock the app right
+/// away, the user changing the setting is present.
+pub fn configure(settings: &GeneralSettings) {
+    OS_AUTH_REQUIRED.store(settings.require_os_auth, Ordering::Relaxed);
+    AUTO_LOCK_MINUTES.store(settings.auto_lock_minutes, Ordering::Relaxed);
+    LAST_USE.store(now(), Ordering::Relaxed);
+}
+
+/// Lock the app and tell the frontend; no-op without a configured lock.
+pub fn lock(app_handle: &AppHandle) {
+    if !is_required() || LOCKED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    tracing::info!("App locked");
+    let _ = app_handle.emit(EVENT, status());
+}
+
+/// Drop the in-memory PIN state after the PIN was deleted from the keychain
+/// (see `app_reset`); unlocks unless OS authentication is still required.
+pub fn forget_pin() {
+    PIN_SET.store(false, Ordering::Relaxed);
+    if !is_required() {
+        unlock();
+    }
+}
+
+fn unlock() {
+    LAST_USE.store(now(), Ordering::Relaxed);
+    LOCKED.store(false, Ordering::Relaxed);
+}
+
+/// Called by the command router before `command` runs.
+pub fn check(command: &str) -> Result<(), &'static str> {
+    if UNGATED_COMMANDS.contains(&command) || !is_required() {
+        return Ok(());
+    }
+    if LOCKED.load(Ordering::Relaxed) {
+        return Err(LOCKED_ERROR);
+    }
+    LAST_USE.store(now(), Ordering::Relaxed);
+    Ok(())
+}
+
+/// Lock the app after `auto_lock_minutes` without a gated command.
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(TIMER_INTERVAL_SECS)).await;
+            let idle_secs = now() - LAST_USE.load(Ordering::Relaxed);
+            let limit_secs = AUTO_LOCK_MINUTES.load(Ordering::Relaxed) as i64 * 60;
+            if limit_secs > 0 && idle_secs >= limit_secs {
+                lock(&app_handle);
+            }
+        }
+    });
+}
+
+pub fn status() -> AppLockStatus {
+    AppLockStatus {
+        locked: is_required() && LOCKED.load(Ordering::Relaxed),
+        os_auth_required: OS_AUTH_REQUIRED.load(Ordering::Relaxed),
+        os_auth_available: os_auth_available(),
+        pin_set: PIN_SET.load(Ordering::Relaxed),
+        auto_lock_minutes: AUTO_LOCK_MINUTES.load(Ordering::Relaxed),
+    }
+}
+
+pub fn os_auth_status() -> OsAuthStatus {
+    let status = status();
+    OsAuthStatus {
+        required: status.os_auth_required,
+        available: status.os_auth_available,
+        locked: status.locked,
+    }
+}
+
+// =============================================================================
+// OS Authentication
+// =============================================================================
+
+/// Whether the platform can check the device owner
+pub fn os_auth_available() -> bool {
+    platform::available()
+}
+
+/// Show the system prompt and unlock the app when it passes. Returns `false`
+/// when the user cancelled or failed the check.
+pub async fn authenticate(app_handle: &AppHandle) -> Result<bool, String> {
+    let app_handle = app_handle.clone();
+    let passed = tauri::async_runtime::spawn_blocking(move || {
+        platform::authenticate(&app_handle, REASON)
+    })
+    .await
+    .map_err(|e| format!("Authentication failed: {}", e))??;
+    if passed {
+        unlock();
+    }
+    Ok(passed)
+}
+
+/// Ask for the owner check before a change that weakens the lock, such as
+/// turning `require_os_auth` off; fails unless it passes. Being unlocked is
+/// not enough: the app may have been left open.
+pub async fn confirm_owner(app_handle: &AppHandle) -> Result<(), String> {
+    let app_handle = app_handle.clone();
+    let passed = tauri::async_runtime::spawn_blocking(move || {
+        platform::authenticate(&app_handle, REASON)
+    })
+    .await
+    .map_err(|e| format!("Authentication failed: {}", e))??;
+    if !passed {
+        return Err("Authentication is required to turn off the OS lock".to_string());
+    }
+    LAST_USE.store(now(), Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_local_authentication::{LAContext, LAPolicy};
+    use tauri::AppHandle;
+
+    /// Biometrics with the account password as fallback
+    const POLICY: LAPolicy = LAPolicy::DeviceOwnerAuthentication;
+
+    pub fn available() -> bool {
+        let context = unsafe { LAContext::new() };
+        unsafe { context.canEvaluatePolicy_error(POLICY) }.is_ok()
+    }
+
+    pub fn authenticate(_app_handle: &AppHandle, reason: &str) -> Result<bool, String> {
+        let context = unsafe { LAContext::new() };
+        let (tx, rx) = std::sync::mpsc::channel();
+        // Called once, on a private queue, when the prompt is dismissed
+        let reply = RcBlock::new(move |success: Bool, _error: *mut NSError| {
+            let _ = tx.send(success.as_bool());
+        });
+        unsafe {
+            context.evaluatePolicy_localizedReason_reply(
+                POLICY,
+                &NSString::from_str(reason),
+                &reply,
+            );
+        }
+        rx.recv()
+            .map_err(|_| "Touch ID prompt closed without a result".to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use tauri::{AppHandle, Manager};
+    use windows::core::{factory, HSTRING};
+    use windows::Foundation::IAsyncOperation;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::WinRT::IUserConsentVerifierInterop;
+
+    pub fn available() -> bool {
+        UserConsentVerifier::CheckAvailabilityAsync()
+            .and_then(|operation| operation.get())
+            .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+    }
+
+    /// The main window, so the prompt opens in front of it with focus
+    fn owner_window(app_handle: &AppHandle) -> Option<HWND> {
+        let window = app_handle.get_webview_window("main")?;
+        window.hwnd().ok().map(|hwnd| HWND(hwnd.0))
+    }
+
+    pub fn authenticate(app_handle: &AppHandle, reason: &str) -> Result<bool, String> {
+        let message = HSTRING::from(reason);
+        let operation: windows::core::Result<IAsyncOperation<UserConsentVerificationResult>> =
+            match owner_window(app_handle) {
+                Some(hwnd) => factory::<UserConsentVerifier, IUserConsentVerifierInterop>()
+                    // SAFETY: `hwnd` is the live main window of this process
+                    .and_then(|interop| unsafe {
+                        interop.RequestVerificationForWindowAsync(hwnd, &message)
+                    }),
+                None => UserConsentVerifier::RequestVerificationAsync(&message),
+            };
+        let result = operation
+            .and_then(|operation| operation.get())
+            .map_err(|e| format!("Windows Hello failed: {}", e))?;
+        Ok(result == UserConsentVerificationResult::Verified)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub fn available() -> bool {
+        false
+    }
+
+    pub fn authenticate(_app_handle: &tauri::AppHandle, _reason: &str) -> Result<bool, String> {
+        Err("OS authentication is not available on this platform".to_string())
+    }
+}
+
+// =============================================================================
+// App PIN
+// =============================================================================
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hash_pin(salt: &[u8], pin: &str) -> String {
+    let mut digest = Sha256::new()
+        .chain_update(salt)
+        .chain_update(pin.as_bytes())
+        .finalize();
+    for _ in 1..PIN_HASH_ROUNDS {
+        digest = Sha256::new()
+            .chain_update(digest)
+            .chain_update(salt)
+            .finalize();
+    }
+    to_hex(&digest)
+}
+
+/// Compare without stopping at the first differing byte
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn validate_pin(pin: &str) -> Result<(), String> {
+    let (min, max) = PIN_DIGITS;
+    if !(min..=max).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("PIN must be {} to {} digits", min, max));
+    }
+    Ok(())
+}
+
+/// Wrong PINs in a row and when the last one was entered, unix seconds
+fn failed_pins() -> Result<(u32, i64), String> {
+    let stored = keychain::get_secret(PIN_FAILURES_KEY)?;
+    Ok(stored
+        .and_then(|stored| {
+            let (count, at) = stored.split_once(':')?;
+            Some((count.parse().ok()?, at.parse().ok()?))
+        })
+        .unwrap_or((0, 0)))
+}
+
+fn record_pin_result(matches: bool, failed: (u32, i64)) -> Result<(), String> {
+    if matches {
+        return keychain::delete_secret(PIN_FAILURES_KEY);
+    }
+    keychain::set_secret(
+        PIN_FAILURES_KEY,
+        &format!("{}:{}", failed.0.saturating_add(1), now()),
+    )
+}
+
+/// Check `pin` against the stored hash, counting wrong attempts.
+fn check_pin(pin: &str) -> Result<bool, String> {
+    let stored = keychain::get_secret(PIN_KEY)?.ok_or_else(|| "No app PIN is set".to_string())?;
+    let _guard = PIN_CHECK.lock().map_err(|e| e.to_string())?;
+    let failed = failed_pins()?;
+    if failed.0 >= MAX_PIN_ATTEMPTS {
+        // A clock set back counts as just failed
+        let since_failure = u64::try_from(now() - failed.1).unwrap_or(0);
+        let wait = PIN_LOCKOUT_SECS.saturating_sub(since_failure);
+        if wait > 0 {
+            return Err(format!(
+                "Too many wrong PINs, try again in {} seconds",
+                wait
+            ));
+        }
+    }
+
+    let (salt, hash) = stored
+        .split_once('$')
+        .ok_or_else(|| "Stored app PIN is invalid".to_string())?;
+    let salt = (0..salt.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(salt.get(i..i + 2).unwrap_or("zz"), 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| "Stored app PIN is invalid".to_string())?;
+    let matches = constant_time_eq(hash_pin(&salt, pin).as_bytes(), hash.as_bytes());
+    // Without a recorded failure the attempt does not count as checked
+    record_pin_result(matches, failed)?;
+    Ok(matches)
+}
+
+/// Unlock with the app PIN. Returns `false` for a wrong PIN.
+pub async fn verify_pin(pin: Zeroizing<String>) -> Result<bool, String> {
+    let matches = tauri::async_runtime::spawn_blocking(move || check_pin(&pin))
+        .await
+        .map_err(|e| format!("PIN check failed: {}", e))??;
+    if matches {
+        unlock();
+    }
+    Ok(matches)
+}
+
+/// Set, change (`pin`) or remove (`None`) the app PIN. Changing or removing
+/// an existing PIN needs `current_pin`.
+pub async fn set_pin(
+    pin: Option<Zeroizing<String>>,
+    current_pin: Option<Zeroizing<String>>,
+) -> Result<(), String> {
+    if let Some(pin) = &pin {
+        validate_pin(pin)?;
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        if keychain::get_secret(PIN_KEY)?.is_some() {
+            let current = current_pin.ok_or_else(|| "Current PIN is required".to_string())?;
+            if !check_pin(&current)? {
+                return Err("Current PIN is wrong".to_string());
+            }
+        }
+        match &pin {
+            Some(pin) => {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                let stored = format!("{}${}", to_hex(&salt), hash_pin(&salt, pin));
+                keychain::set_secret(PIN_KEY, &stored)?;
+            }
+            None => keychain::delete_secret(PIN_KEY)?,
+        }
+        PIN_SET.store(pin.is_some(), Ordering::Relaxed);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to set PIN: {}", e))??;
+
+    // The user setting the PIN is present
+    unlock();
+    Ok(())
+}
@@ -0,0 +1,271 @@
+//! Application Menu
+//!
+//! The native menu bar (the window menu on Windows and Linux), so the main
+//! actions have keyboard shortcuts and work without finding their button:
+//! - File: Import Statement (CmdOrCtrl+O), Export to Excel (CmdOrCtrl+E)
+//! - Edit: the standard clipboard items, which the webview needs on macOS
+//! - Portfolio: Sync Now (CmdOrCtrl+Shift+S), Run Pipeline (CmdOrCtrl+Shift+P)
+//! - Help: Open Logs Folder, Open Data Folder
+//!
+//! Items call the existing commands from the shell. Import and export ask
+//! for the file with a native dialog and act on the scheduled portfolio, the
+//! same one the tray syncs; an import shows what the statement holds and
+//! asks before writing. Sync and pipeline runs report through notifications
+//! like the tray's; import, export and folder errors through a dialog.
+//!
+//! Item ids differ from the tray's, since menu events reach both handlers.
+
+use crate::broker_import;
+use crate::commands::{portfolio, settings};
+use crate::scheduler;
+use crate::store;
+use serde_json::json;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const IMPORT: &str = "menu-import";
+const EXPORT: &str = "menu-export";
+const SYNC: &str = "menu-sync";
+const PIPELINE: &str = "menu-pipeline";
+const OPEN_LOGS: &str = "menu-open-logs";
+const OPEN_DATA: &str = "menu-open-data";
+
+/// Build the menu bar. Passed to `Builder::menu`.
+pub fn build(app_handle: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let item = |id: &str, text: &str, accelerator: Option<&str>| {
+        MenuItem::with_id(app_handle, id, text, true, accelerator)
+    };
+    let separator = || PredefinedMenuItem::separator(app_handle);
+
+    let file = Submenu::with_items(
+        app_handle,
+        "File",
+        true,
+        &[
+            &item(IMPORT, "Import Statement…", Some("CmdOrCtrl+O"))?,
+            &item(EXPORT, "Export to Excel…", Some("CmdOrCtrl+E"))?,
+            &separator()?,
+            &PredefinedMenuItem::close_window(app_handle, None)?,
+            #[cfg(not(target_os = "macos"))]
+            &PredefinedMenuItem::quit(app_handle, None)?,
+        ],
+    )?;
+    let edit = Submenu::with_items(
+        app_handle,
+        "Edit",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app_handle, None)?,
+            &PredefinedMenuItem::redo(app_handle, None)?,
+            &separator()?,
+            &PredefinedMenuItem::cut(app_handle, None)?,
+            &PredefinedMenuItem::copy(app_handle, None)?,
+            &PredefinedMenuItem::paste(app_handle, None)?,
+            &PredefinedMenuItem::select_all(app_handle, None)?,
+        ],
+    )?;
+    let portfolio = Submenu::with_items(
+        app_handle,
+        "Portfolio",
+        true,
+        &[
+            &item(SYNC, "Sync Now", Some("CmdOrCtrl+Shift+S"))?,
+            &item(PIPELINE, "Run Pipeline", Some("CmdOrCtrl+Shift+P"))?,
+        ],
+    )?;
+    let window = Submenu::with_items(
+        app_handle,
+        "Window",
+        true,
+        &[
+            &PredefinedMenuItem::minimize(app_handle, None)?,
+            &PredefinedMenuItem::maximize(app_handle, None)?,
+            &PredefinedMenuItem::fullscreen(app_handle, None)?,
+        ],
+    )?;
+    let help = Submenu::with_items(
+        app_handle,
+        "Help",
+        true,
+        &[
+            &item(OPEN_LOGS, "Open Logs Folder", None)?,
+            &item(OPEN_DATA, "Open Data Folder", None)?,
+        ],
+    )?;
+
+    let menu = Menu::with_items(app_handle, &[&file, &edit, &portfolio, &window, &help])?;
+
+    // macOS puts the first submenu under the app name
+    #[cfg(target_os = "macos")]
+    {
+        let about = tauri::menu::AboutMetadata {
+            name: Some("Portfolio Prism".to_string()),
+            version: Some(app_handle.package_info().version.to_string()),
+            ..Default::default()
+        };
+        let app = Submenu::with_items(
+            app_handle,
+            "Portfolio Prism",
+            true,
+            &[
+                &PredefinedMenuItem::about(app_handle, None, Some(about))?,
+                &separator()?,
+                &PredefinedMenuItem::services(app_handle, None)?,
+                &separator()?,
+                &PredefinedMenuItem::hide(app_handle, None)?,
+                &PredefinedMenuItem::hide_others(app_handle, None)?,
+                &PredefinedMenuItem::show_all(app_handle, None)?,
+                &separator()?,
+                &PredefinedMenuItem::quit(app_handle, None)?,
+            ],
+        )?;
+        menu.prepend(&app)?;
+    }
+    Ok(menu)
+}
+
+/// Handle a menu bar click. Passed to `Builder::on_menu_event`.
+pub fn handle(app_handle: &AppHandle, id: &str) {
+    let app_handle = app_handle.clone();
+    match id {
+        SYNC => {
+            tauri::async_runtime::spawn(async move { scheduler::sync_now(&app_handle).await });
+        }
+        PIPELINE => {
+            tauri::async_runtime::spawn(async move { scheduler::pipeline_now(&app_handle).await });
+        }
+        IMPORT => {
+            tauri::async_runtime::spawn(async move {
+                report("Import failed", import(&app_handle).await).await
+            });
+        }
+        EXPORT => {
+            tauri::async_runtime::spawn(async move {
+                report("Export failed", export(&app_handle).await).await
+            });
+        }
+        OPEN_LOGS => {
+            tauri::async_runtime::spawn(async move {
+                let opened = settings::open_logs_dir(app_handle).await;
+                report("Could not open the logs folder", opened.map(|_| None)).await
+            });
+        }
+        OPEN_DATA => {
+            tauri::async_runtime::spawn(async move {
+                let opened = settings::open_data_dir(app_handle).await;
+                report("Could not open the data folder", opened.map(|_| None)).await
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Portfolio the menu acts on: the scheduled one, as in the tray
+fn menu_portfolio(app_handle: &AppHandle) -> Result<u32, String> {
+    Ok(scheduler::sync_status(&store::data_dir(app_handle)?)?
+        .settings
+        .portfolio_id)
+}
+
+/// Pick a statement, preview it and import it after confirmation. Returns
+/// the message to show, or `None` if the user backed out.
+async fn import(app_handle: &AppHandle) -> Result<Option<String>, String> {
+    let extensions: Vec<&str> = broker_import::formats()
+        .iter()
+        .flat_map(|format| format.extensions.iter().copied())
+        .collect();
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_title("Import a broker statement")
+        .add_filter("Broker statements", &extensions)
+        .pick_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    let path = file.path().display().to_string();
+    let portfolio_id = menu_portfolio(app_handle)?;
+
+    let preview = portfolio::import_broker_statement(
+        portfolio_id,
+        path.clone(),
+        true,
+        app_handle.state(),
+        app_handle.state(),
+    )
+    .await?;
+    let statement = &preview.statement;
+    let confirmed = rfd::AsyncMessageDialog::new()
+        .set_title("Import statement")
+        .set_description(format!(
+            "Import {} holdings and {} transactions from {} into portfolio {}?",
+            statement.positions.len(),
+            statement.transactions.len(),
+            statement.broker,
+            portfolio_id
+        ))
+        .set_buttons(rfd::MessageButtons::OkCancel)
+        .show()
+        .await;
+    if confirmed != rfd::MessageDialogResult::Ok {
+        return Ok(None);
+    }
+
+    portfolio::import_broker_statement(
+        portfolio_id,
+        path,
+        false,
+        app_handle.state(),
+        app_handle.state(),
+    )
+    .await?;
+    let _ = app_handle.emit(
+        "portfolio-updated",
+        json!({ "timestamp": chrono::Utc::now().to_rfc3339(), "portfolioId": portfolio_id }),
+    );
+    Ok(Some(format!("Imported {}", file.file_name())))
+}
+
+/// Ask where to save and export the workbook
+async fn export(app_handle: &AppHandle) -> Result<Option<String>, String> {
+    let file_name = format!(
+        "portfolio-prism-{}.xlsx",
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_title("Export to Excel")
+        .add_filter("Excel workbook", &["xlsx"])
+        .set_file_name(file_name)
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    let portfolio_id = menu_portfolio(app_handle)?;
+    let summary = portfolio::export_xlsx(
+        app_handle.clone(),
+        portfolio_id,
+        file.path().display().to_string(),
+        app_handle.state(),
+    )
+    .await?;
+    Ok(Some(format!("Exported to {}", summary.path)))
+}
+
+/// Show the outcome of a menu action in a dialog
+async fn report(failure_title: &str, result: Result<Option<String>, String>) {
+    let (level, title, message) = match result {
+        Ok(Some(message)) => (rfd::MessageLevel::Info, "Portfolio Prism", message),
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("{}: {}", failure_title, e);
+            (rfd::MessageLevel::Error, failure_title, e)
+        }
+    };
+    rfd::AsyncMessageDialog::new()
+        .set_level(level)
+        .set_title(title)
+        .set_description(message)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show()
+        .await;
+}
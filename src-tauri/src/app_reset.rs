@@ -0,0 +1,566 @@
+//! App Data Reset
+//!
+//! Deletes part or all of the app data directory without the user having to
+//! find and empty it by hand. Scopes:
+//!
+//! - `caches`: Hive and dashboard caches, offline datasets, benchmark series,
+//!   IPC traces and pipeline outputs; all of it is rebuilt on demand
+//! - `engineDatabase`: `prism.db` with its WAL/SHM files (the last-good backup
+//!   under `backups/` is kept)
+//! - `shellStores`: settings and state owned by the shell (alerts, sandboxes,
+//!   benchmarks, pipeline configs, snapshots, schedules, logs)
+//! - `credentials`: every keychain secret of `data_registry` (app PIN, SMTP
+//!   and proxy passwords, local API token, session key) and the broker login
+//!   stored by the engine
+//! - `everything`: all of the above and anything else in the data dir
+//!
+//! Everything except caches is copied to `reset_backups/{timestamp}/` before
+//! deletion; keychain secrets are never copied. Scopes touching the database
+//! stop the engine sidecars first and restart them afterwards.
+//!
+//! `delete_all` is the GDPR erasure: no backup is taken, earlier reset backups,
+//! the log and cache dirs and the record of a moved data dir go too, and file
+//! contents are overwritten with zeros before removal. It needs a short-lived,
+//! single-use confirmation token from `DataDeletion::issue_token`.
+
+use crate::app_lock;
+use crate::app_settings;
+use crate::data_location;
+use crate::data_registry::{
+    self, CACHE_ENTRIES, ENGINE_DB_ENTRIES, ENGINE_KEYCHAIN_SECRETS, KEYCHAIN_SECRETS,
+    SHELL_STORE_FILES,
+};
+use crate::keychain;
+use crate::local_api;
+use crate::proxy;
+use crate::python_engine::{EnginePool, EngineRole, EngineState, PythonEngine};
+use crate::session_vault;
+use crate::store;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Pre-reset backups inside the app data dir; never deleted by a reset
+pub(crate) const BACKUP_DIR: &str = "reset_backups";
+
+/// Kept by `everything`: the running instance holds it
+pub(crate) const LOCK_FILE: &str = ".instance.lock";
+
+/// How long a stopped sidecar may take to exit before files are deleted
+const ENGINE_EXIT_WAIT: Duration = Duration::from_secs(5);
+
+/// How long a deletion confirmation token stays valid
+const DELETION_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// Block size used to overwrite files before deleting them
+const SHRED_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResetScope {
+    Caches,
+    EngineDatabase,
+    ShellStores,
+    Credentials,
+    Everything,
+}
+
+impl ResetScope {
+    fn includes(self, scope: ResetScope) -> bool {
+        self == scope || self == ResetScope::Everything
+    }
+
+    /// The engine must not hold the database open while it is deleted
+    fn stops_engine(self) -> bool {
+        self.includes(ResetScope::EngineDatabase)
+    }
+
+    /// In-memory state (feature flags, schedules) is only reloaded on launch
+    fn requires_app_restart(self) -> bool {
+        self.includes(ResetScope::ShellStores)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedEntry {
+    /// Path relative to the app data dir
+    pub path: String,
+    pub bytes: u64,
+    pub backed_up: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetSummary {
+    pub scope: ResetScope,
+    /// `None` when nothing needed a backup
+    pub backup_path: Option<String>,
+    pub deleted: Vec<DeletedEntry>,
+    pub freed_bytes: u64,
+    /// Keychain entries and engine logins that were removed
+    pub credentials_cleared: Vec<String>,
+    pub engine_restarted: bool,
+    pub requires_app_restart: bool,
+    /// Non-fatal failures; the rest of the reset went ahead
+    pub errors: Vec<String>,
+}
+
+/// One top-level entry of the data dir to delete
+struct Target {
+    name: String,
+    backup: bool,
+}
+
+fn targets(data_dir: &Path, scope: ResetScope) -> Result<Vec<Target>, String> {
+    if scope == ResetScope::Everything {
+        let entries = std::fs::read_dir(data_dir)
+            .map_err(|e| format!("Failed to read {}: {}", data_dir.display(), e))?;
+        return Ok(entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name != BACKUP_DIR && name != LOCK_FILE)
+            .map(|name| Target {
+                backup: !CACHE_ENTRIES.contains(&name.as_str()),
+                name,
+            })
+            .collect());
+    }
+
+    let mut names: Vec<(String, bool)> = vec![];
+    if scope.includes(ResetScope::Caches) {
+        names.extend(CACHE_ENTRIES.iter().map(|name| (name.to_string(), false)));
+    }
+    if scope.includes(ResetScope::EngineDatabase) {
+        names.extend(
+            ENGINE_DB_ENTRIES
+                .iter()
+                .map(|name| (name.to_string(), true)),
+        );
+    }
+    if scope.includes(ResetScope::ShellStores) {
+        for file in SHELL_STORE_FILES {
+            let backup = store::backup_path(Path::new(file));
+            names.push((file.to_string(), true));
+            names.push((backup.to_string_lossy().into_owned(), true));
+        }
+        names.extend(
+            data_registry::shell_store_entries()
+                .into_iter()
+                .map(|name| (name, true)),
+        );
+    }
+    Ok(names
+        .into_iter()
+        .filter(|(name, _)| data_dir.join(name).exists())
+        .map(|(name, backup)| Target { name, backup })
+        .collect())
+}
+
+pub(crate) fn entry_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+pub(crate) fn copy_entry(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+pub(crate) fn remove_entry(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Back up and delete `targets`. A failed backup aborts before anything is
+/// deleted; failed deletions are recorded and skipped.
+fn delete_targets(
+    data_dir: &Path,
+    targets: &[Target],
+    summary: &mut ResetSummary,
+) -> Result<(), String> {
+    if targets.iter().any(|target| target.backup) {
+        let backup_dir = data_dir
+            .join(BACKUP_DIR)
+            .join(Utc::now().format("%Y%m%dT%H%M%SZ").to_string());
+        std::fs::create_dir_all(&backup_dir)
+            .map_err(|e| format!("Failed to create {}: {}", backup_dir.display(), e))?;
+        for target in targets.iter().filter(|target| target.backup) {
+            copy_entry(&data_dir.join(&target.name), &backup_dir.join(&target.name))
+                .map_err(|e| format!("Backup of {} failed, nothing deleted: {}", target.name, e))?;
+        }
+        summary.backup_path = Some(backup_dir.to_string_lossy().into_owned());
+    }
+
+    for target in targets {
+        let path = data_dir.join(&target.name);
+        let bytes = entry_size(&path);
+        match remove_entry(&path) {
+            Ok(()) => {
+                summary.freed_bytes += bytes;
+                summary.deleted.push(DeletedEntry {
+                    path: target.name.clone(),
+                    bytes,
+                    backed_up: target.backup,
+                });
+            }
+            Err(e) => summary
+                .errors
+                .push(format!("Failed to delete {}: {}", target.name, e)),
+        }
+    }
+    Ok(())
+}
+
+async fn clear_credentials(data_dir: &Path, engine: &PythonEngine, summary: &mut ResetSummary) {
+    if !engine.is_connected().await {
+        // Nothing holds the session files; drop them directly
+        session_vault::discard(data_dir);
+        summary
+            .credentials_cleared
+            .push("session:broker".to_string());
+    } else {
+        match engine.request("clear_credentials", json!({})).await {
+            Ok(_) => {
+                // Drop the encrypted copies of the files the engine just removed
+                if let Err(e) = session_vault::seal(data_dir) {
+                    summary.errors.push(e);
+                }
+                summary
+                    .credentials_cleared
+                    .push("engine:broker".to_string())
+            }
+            Err(e) => summary
+                .errors
+                .push(format!("Failed to clear broker login: {}", e)),
+        }
+    }
+
+    // After the engine step: sealing needs the session key
+    for key in KEYCHAIN_SECRETS {
+        match keychain::delete_secret(key) {
+            Ok(()) => summary
+                .credentials_cleared
+                .push(format!("keychain:{}", key)),
+            Err(e) => summary.errors.push(e),
+        }
+    }
+    // The shell keeps copies of some secrets in memory
+    app_lock::forget_pin();
+    local_api::forget_token();
+    proxy::configure(&app_settings::load_general(data_dir).proxy);
+
+    // Also covers an engine that was not running
+    for key in ENGINE_KEYCHAIN_SECRETS {
+        match keychain::delete_engine_secret(key) {
+            Ok(()) => summary
+                .credentials_cleared
+                .push(format!("keychain:{}:{}", keychain::ENGINE_SERVICE, key)),
+            Err(e) => summary.errors.push(e),
+        }
+    }
+}
+
+/// Stop every running sidecar; returns the roles to restart.
+pub(crate) async fn stop_engines(
+    pool: &EnginePool,
+    errors: &mut Vec<String>,
+) -> Vec<(EngineRole, Arc<PythonEngine>)> {
+    let mut stopped = vec![];
+    for (role, engine) in [
+        (EngineRole::Primary, pool.primary()),
+        (EngineRole::Worker, pool.worker()),
+    ] {
+        // An idle engine has nothing to stop and starts on the new data
+        if matches!(engine.status().state, EngineState::Dead | EngineState::Idle) {
+            continue;
+        }
+        engine.shutdown().await;
+        if !engine.wait_for_exit(ENGINE_EXIT_WAIT).await {
+            errors.push(format!("{} did not exit in time", role.label()));
+        }
+        stopped.push((role, engine));
+    }
+    stopped
+}
+
+/// Respawn the engines `stop_engines` stopped. Callers restart even when
+/// their work failed, so the app stays usable. Returns whether every stopped
+/// engine came back (false when none was stopped) and the restart errors.
+pub(crate) fn restart_engines(
+    app_handle: &AppHandle,
+    stopped: Vec<(EngineRole, Arc<PythonEngine>)>,
+) -> (bool, Vec<String>) {
+    let restarted = !stopped.is_empty();
+    let mut errors = vec![];
+    for (role, engine) in stopped {
+        if let Err(e) = crate::respawn_engine(app_handle, engine, role) {
+            errors.push(format!("Failed to restart {}: {}", role.label(), e));
+        }
+    }
+    (restarted && errors.is_empty(), errors)
+}
+
+/// Reset `scope` and report what was removed.
+pub async fn reset(
+    app_handle: &AppHandle,
+    pool: &EnginePool,
+    scope: ResetScope,
+) -> Result<ResetSummary, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let mut summary = ResetSummary {
+        scope,
+        backup_path: None,
+        deleted: vec![],
+        freed_bytes: 0,
+        credentials_cleared: vec![],
+        engine_restarted: false,
+        requires_app_restart: scope.requires_app_restart(),
+        errors: vec![],
+    };
+
+    // The engine has to be up to forget the broker login
+    if scope.includes(ResetScope::Credentials) {
+        clear_credentials(&data_dir, &pool.primary(), &mut summary).await;
+    }
+
+    let stopped = if scope.stops_engine() {
+        stop_engines(pool, &mut summary.errors).await
+    } else {
+        vec![]
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let targets = targets(&data_dir, scope)?;
+        delete_targets(&data_dir, &targets, &mut summary).map(|()| summary)
+    })
+    .await
+    .map_err(|e| format!("Reset failed: {}", e))
+    .and_then(|result| result);
+
+    let (restarted, restart_errors) = restart_engines(app_handle, stopped);
+
+    let mut summary = result?;
+    summary.engine_restarted = restarted;
+    summary.errors.extend(restart_errors);
+    Ok(summary)
+}
+
+// =============================================================================
+// Delete All Data
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletionToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The pending confirmation token of `delete_all`; managed as Tauri state
+#[derive(Default)]
+pub struct DataDeletion {
+    pending: Mutex<Option<(String, Instant)>>,
+}
+
+impl DataDeletion {
+    /// Issue a new token, replacing any earlier one.
+    pub fn issue_token(&self) -> Result<DeletionToken, String> {
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+        *pending = Some((token.clone(), Instant::now()));
+        Ok(DeletionToken {
+            token,
+            expires_at: Utc::now() + DELETION_TOKEN_TTL,
+        })
+    }
+
+    /// Check `token` against the pending one. Any attempt uses it up.
+    fn consume(&self, token: &str) -> Result<(), String> {
+        let pending = self.pending.lock().map_err(|e| e.to_string())?.take();
+        match pending {
+            Some((expected, issued))
+                if app_lock::constant_time_eq(expected.as_bytes(), token.as_bytes()) =>
+            {
+                if issued.elapsed() > DELETION_TOKEN_TTL {
+                    return Err("Confirmation token expired; request a new one".to_string());
+                }
+                Ok(())
+            }
+            _ => Err("Invalid confirmation token; request a new one".to_string()),
+        }
+    }
+}
+
+/// Overwrite a file with zeros and flush it to disk before removing it.
+/// Best effort: copy-on-write file systems and SSD wear levelling may keep
+/// the old blocks around.
+pub(crate) fn shred_file(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; SHRED_CHUNK];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(SHRED_CHUNK as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+fn shred_entry(path: &Path) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if metadata.is_symlink() {
+        // Never follow links out of the app dirs
+        std::fs::remove_file(path)
+    } else if metadata.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            shred_entry(&entry?.path())?;
+        }
+        std::fs::remove_dir(path)
+    } else {
+        shred_file(path)
+    }
+}
+
+/// Shred every entry of `dir` except the instance lock. `label` prefixes the
+/// reported paths of dirs other than the data dir.
+fn shred_dir(dir: &Path, label: Option<&str>, summary: &mut ResetSummary) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        // Missing dirs have nothing to delete
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if label.is_none() && name == LOCK_FILE {
+            continue;
+        }
+        let path = entry.path();
+        let shown = match label {
+            Some(label) => format!("{}/{}", label, name),
+            None => name,
+        };
+        let bytes = entry_size(&path);
+        match shred_entry(&path) {
+            Ok(()) => {
+                summary.freed_bytes += bytes;
+                summary.deleted.push(DeletedEntry {
+                    path: shown,
+                    bytes,
+                    backed_up: false,
+                });
+            }
+            Err(e) => summary
+                .errors
+                .push(format!("Failed to delete {}: {}", shown, e)),
+        }
+    }
+}
+
+/// Irreversibly delete all app data, including the engine database, cached
+/// holdings, broker sessions, logs and pipeline outputs, then restart the
+/// engines on an empty data dir. `token` must come from `issue_token`.
+pub async fn delete_all(
+    app_handle: &AppHandle,
+    pool: &EnginePool,
+    deletion: &DataDeletion,
+    token: &str,
+) -> Result<ResetSummary, String> {
+    deletion.consume(token)?;
+
+    let data_dir = store::data_dir(app_handle)?;
+    // A moved data dir is recorded in the default dir, which is not shredded
+    let location_file = data_location::default_dir(app_handle)?.join(data_location::LOCATION_FILE);
+    let location_files = [store::backup_path(&location_file), location_file];
+    let extra_dirs: Vec<(PathBuf, &str)> = [
+        (app_handle.path().app_log_dir(), "logs"),
+        (app_handle.path().app_cache_dir(), "cache"),
+    ]
+    .into_iter()
+    .filter_map(|(dir, label)| Some((dir.ok()?, label)))
+    .filter(|(dir, _)| !dir.starts_with(&data_dir))
+    .collect();
+
+    let mut summary = ResetSummary {
+        scope: ResetScope::Everything,
+        backup_path: None,
+        deleted: vec![],
+        freed_bytes: 0,
+        credentials_cleared: vec![],
+        engine_restarted: false,
+        requires_app_restart: true,
+        errors: vec![],
+    };
+
+    // The engine has to be up to forget the broker session
+    clear_credentials(&data_dir, &pool.primary(), &mut summary).await;
+    let stopped = stop_engines(pool, &mut summary.errors).await;
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        session_vault::wipe();
+        shred_dir(&data_dir, None, &mut summary);
+        for (dir, label) in &extra_dirs {
+            shred_dir(dir, Some(label), &mut summary);
+        }
+        for file in location_files.iter().filter(|file| file.exists()) {
+            let bytes = entry_size(file);
+            match shred_file(file) {
+                Ok(()) => {
+                    summary.freed_bytes += bytes;
+                    summary.deleted.push(DeletedEntry {
+                        path: file.display().to_string(),
+                        bytes,
+                        backed_up: false,
+                    });
+                }
+                Err(e) => summary
+                    .errors
+                    .push(format!("Failed to delete {}: {}", file.display(), e)),
+            }
+        }
+        summary
+    })
+    .await
+    .map_err(|e| format!("Data deletion failed: {}", e));
+
+    let (restarted, restart_errors) = restart_engines(app_handle, stopped);
+
+    let mut summary = result?;
+    summary.engine_restarted = restarted;
+    summary.errors.extend(restart_errors);
+    Ok(summary)
+}
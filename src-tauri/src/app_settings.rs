@@ -0,0 +1,377 @@
+//! App Settings
+//!
+//! User preferences owned by the shell, stored in `settings.json` in the app
+//! data dir. Missing fields take their defaults and every update is
+//! validated before it is written. The sync schedule is part of the settings
+//! view but stays in `schedules.json`, which the scheduler owns.
+//!
+//! The engine receives the settings it needs (currency, locale, Hive opt-in)
+//! as `PRISM_*` environment variables at spawn and through a `configure`
+//! command whenever they change. Timeout overrides apply to the shell's
+//! engine handles directly. The Hive opt-in is `None` until the user picks
+//! one; until then the engine's own value (opted in) applies and is adopted
+//! into `settings.json` the first time it is read. The local API server follows its settings on
+//! every save. The proxy (see `proxy`) is passed to the engine in its spawn
+//! environment and applied to the shell's own requests on every save.
+
+use crate::app_lock;
+use crate::proxy;
+use crate::python_engine::{EngineState, PythonEngine};
+use crate::scheduler::{self, SyncSchedule};
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Settings file inside the app data dir
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+
+/// Allowed range of the command timeout override, in seconds
+const COMMAND_TIMEOUT_RANGE: (u64, u64) = (5, 600);
+
+/// Allowed range of the ready wait override, in seconds
+const READY_WAIT_RANGE: (u64, u64) = (1, 120);
+
+/// Longest allowed auto-lock delay, in minutes
+const MAX_AUTO_LOCK_MINUTES: u64 = 240;
+
+/// Lowest port the local API may use; lower ones need privileges
+const MIN_LOCAL_API_PORT: u16 = 1024;
+
+/// The engine's Hive opt-in when it has none stored
+const ENGINE_HIVE_DEFAULT: bool = true;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeoutOverrides {
+    /// How long a command waits for the engine's response
+    #[serde(default)]
+    pub command_secs: Option<u64>,
+    /// How long commands issued during launch wait for the engine
+    #[serde(default)]
+    pub ready_wait_secs: Option<u64>,
+}
+
+/// Opt-in read-only HTTP API on localhost (see `local_api`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_local_api_port")]
+    pub port: u16,
+}
+
+fn default_local_api_port() -> u16 {
+    8787
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_local_api_port(),
+        }
+    }
+}
+
+/// HTTP proxy for the engine's and the shell's connections (see `proxy`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `http://host:port`; the password is kept in the keychain
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    /// Hosts, domains (`.example.com`) or IPs reached without the proxy
+    #[serde(default)]
+    pub bypass: Vec<String>,
+}
+
+/// Settings stored in `settings.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneralSettings {
+    /// ISO 4217 code values are reported in
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// BCP 47 tag for number and date formatting
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    #[serde(default = "default_theme")]
+    pub theme: Theme,
+    /// Contribute anonymized ETF decompositions to the Hive; `None` until
+    /// chosen (see `hive_contributions`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hive_contributions: Option<bool>,
+    /// Allow `submit_error_report` to send scrubbed error bundles
+    #[serde(default)]
+    pub error_reporting: bool,
+    /// Send anonymous usage counts (see `telemetry`)
+    #[serde(default)]
+    pub telemetry: bool,
+    /// Lock portfolio data behind Touch ID / Windows Hello (see `app_lock`)
+    #[serde(default)]
+    pub require_os_auth: bool,
+    /// Idle minutes before a configured app lock engages; 0 never
+    #[serde(default = "default_auto_lock_minutes")]
+    pub auto_lock_minutes: u64,
+    /// Spawn the engine on the first command instead of at launch; takes
+    /// effect on the next launch
+    #[serde(default)]
+    pub lazy_engine_start: bool,
+    #[serde(default)]
+    pub timeouts: TimeoutOverrides,
+    #[serde(default)]
+    pub local_api: LocalApiSettings,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+}
+
+fn default_currency() -> String {
+    "EUR".to_string()
+}
+
+fn default_locale() -> String {
+    "de-DE".to_string()
+}
+
+fn default_theme() -> Theme {
+    Theme::System
+}
+
+fn default_auto_lock_minutes() -> u64 {
+    5
+}
+
+impl Default for GeneralSettings {
+    fn default() -> Self {
+        Self {
+            currency: default_currency(),
+            locale: default_locale(),
+            theme: default_theme(),
+            hive_contributions: None,
+            error_reporting: false,
+            telemetry: false,
+            require_os_auth: false,
+            auto_lock_minutes: default_auto_lock_minutes(),
+            lazy_engine_start: false,
+            timeouts: TimeoutOverrides::default(),
+            local_api: LocalApiSettings::default(),
+            proxy: ProxySettings::default(),
+        }
+    }
+}
+
+/// Settings as read and written by the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    #[serde(flatten)]
+    pub general: GeneralSettings,
+    #[serde(default)]
+    pub sync_schedule: SyncSchedule,
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILE)
+}
+
+/// Stored settings; defaults when the file is missing or unreadable.
+pub fn load_general(data_dir: &Path) -> GeneralSettings {
+    match store::read_json(&settings_path(data_dir)) {
+        Ok(settings) => settings.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("Using default settings: {}", e);
+            GeneralSettings::default()
+        }
+    }
+}
+
+pub fn load(data_dir: &Path) -> Result<AppSettings, String> {
+    Ok(AppSettings {
+        general: load_general(data_dir),
+        sync_schedule: scheduler::sync_status(data_dir)?.settings,
+    })
+}
+
+/// Validate and normalize `settings` (currency uppercased).
+fn validate(mut settings: GeneralSettings) -> Result<GeneralSettings, String> {
+    settings.currency = settings.currency.trim().to_uppercase();
+    if settings.currency.len() != 3 || !settings.currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!(
+            "Invalid currency: {} (expected a 3-letter ISO code)",
+            settings.currency
+        ));
+    }
+
+    settings.locale = settings.locale.trim().to_string();
+    let valid_locale = !settings.locale.is_empty()
+        && settings.locale.len() <= 35
+        && settings
+            .locale
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid_locale {
+        return Err(format!("Invalid locale: {}", settings.locale));
+    }
+
+    if settings.auto_lock_minutes > MAX_AUTO_LOCK_MINUTES {
+        return Err(format!(
+            "Auto-lock must be at most {} minutes",
+            MAX_AUTO_LOCK_MINUTES
+        ));
+    }
+
+    if settings.local_api.port < MIN_LOCAL_API_PORT {
+        return Err(format!(
+            "Local API port must be at least {}",
+            MIN_LOCAL_API_PORT
+        ));
+    }
+
+    let proxy_settings = &mut settings.proxy;
+    proxy_settings.url = proxy_settings.url.trim().to_string();
+    proxy_settings.username = proxy_settings.username.trim().to_string();
+    proxy_settings.bypass = proxy_settings
+        .bypass
+        .iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    if proxy_settings.enabled && proxy_settings.url.is_empty() {
+        return Err("Enter the proxy URL or turn the proxy off".to_string());
+    }
+    if !proxy_settings.url.is_empty() {
+        proxy::validate_url(&proxy_settings.url)?;
+    }
+    if let Some(entry) = proxy_settings
+        .bypass
+        .iter()
+        .find(|entry| entry.contains(|c: char| c == ',' || c.is_whitespace()))
+    {
+        return Err(format!("Invalid proxy bypass entry: {}", entry));
+    }
+
+    let timeouts = &settings.timeouts;
+    for (name, value, (min, max)) in [
+        (
+            "Command timeout",
+            timeouts.command_secs,
+            COMMAND_TIMEOUT_RANGE,
+        ),
+        ("Ready wait", timeouts.ready_wait_secs, READY_WAIT_RANGE),
+    ] {
+        if value.is_some_and(|value| !(min..=max).contains(&value)) {
+            return Err(format!(
+                "{} must be between {} and {} seconds",
+                name, min, max
+            ));
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Validate and save `settings`; returns the stored view.
+pub fn save(data_dir: &Path, settings: AppSettings) -> Result<AppSettings, String> {
+    let general = validate(settings.general)?;
+    if general.require_os_auth && !app_lock::os_auth_available() {
+        return Err("OS authentication is not available on this device".to_string());
+    }
+    scheduler::save_sync_schedule(data_dir, settings.sync_schedule)?;
+    store::write_json(&settings_path(data_dir), &general)?;
+    load(data_dir)
+}
+
+/// Environment variables passed to a newly spawned sidecar
+pub fn engine_env(settings: &GeneralSettings) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("PRISM_CURRENCY", settings.currency.clone()),
+        ("PRISM_LOCALE", settings.locale.clone()),
+    ];
+    // Without a choice the engine keeps its stored value
+    if let Some(enabled) = settings.hive_contributions {
+        env.push(("PRISM_HIVE_CONTRIBUTIONS", u8::from(enabled).to_string()));
+    }
+    env.extend(proxy::engine_env(&settings.proxy));
+    env
+}
+
+/// Apply timeout overrides to an engine handle. Unset overrides keep the
+/// current values (the launch environment or the built-in defaults).
+pub fn apply_timeouts(engine: &PythonEngine, timeouts: &TimeoutOverrides) {
+    if let Some(secs) = timeouts.command_secs {
+        engine.set_command_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = timeouts.ready_wait_secs {
+        engine.set_ready_wait(Duration::from_secs(secs));
+    }
+}
+
+/// Push engine-relevant settings to a running engine.
+pub async fn configure_engine(engine: &PythonEngine, settings: &GeneralSettings) {
+    apply_timeouts(engine, &settings.timeouts);
+    // Checked first: `is_connected` would start an idle engine
+    if engine.status().state == EngineState::Idle || !engine.is_connected().await {
+        // Picked up from the environment at the next spawn
+        return;
+    }
+    let mut payload = json!({
+        "currency": settings.currency,
+        "locale": settings.locale,
+    });
+    if let Some(enabled) = settings.hive_contributions {
+        payload["hiveContributions"] = json!(enabled);
+    }
+    if let Err(e) = engine.request("configure", payload).await {
+        tracing::warn!("Failed to configure engine: {}", e);
+    }
+}
+
+/// The Hive opt-in of `settings.json`.
+///
+/// Before the user chose one, the engine's current value is adopted and
+/// saved, so upgrades keep what the engine had. While the engine is not
+/// running, its default (opted in) is returned without saving.
+pub async fn hive_contributions(data_dir: &Path, engine: &PythonEngine) -> bool {
+    let mut settings = load_general(data_dir);
+    if let Some(enabled) = settings.hive_contributions {
+        return enabled;
+    }
+    // Checked first: `is_connected` would start an idle engine
+    if engine.status().state == EngineState::Idle || !engine.is_connected().await {
+        return ENGINE_HIVE_DEFAULT;
+    }
+    let enabled = match engine.request("get_hive_contribution", json!({})).await {
+        Ok(data) => data["enabled"].as_bool().unwrap_or(ENGINE_HIVE_DEFAULT),
+        Err(e) => {
+            tracing::warn!("Failed to read the engine's Hive opt-in: {}", e);
+            return ENGINE_HIVE_DEFAULT;
+        }
+    };
+    settings.hive_contributions = Some(enabled);
+    if let Err(e) = store::write_json(&settings_path(data_dir), &settings) {
+        tracing::warn!("Failed to adopt the engine's Hive opt-in: {}", e);
+    }
+    enabled
+}
+
+/// Store the Hive opt-in; returns the saved settings.
+pub fn set_hive_contributions(data_dir: &Path, enabled: bool) -> Result<AppSettings, String> {
+    let mut settings = load(data_dir)?;
+    settings.general.hive_contributions = Some(enabled);
+    save(data_dir, settings)
+}
@@ -0,0 +1,305 @@
+//! App Updates
+//!
+//! Shell updates come through `tauri-plugin-updater`: signed bundles from the
+//! release feed in `tauri.conf.json`, checked against the public key built
+//! into the shell (`PRISM_UPDATER_PUBKEY`). Builds without a key cannot
+//! update in-app.
+//!
+//! `install_update` downloads the bundle first (`update-progress` events),
+//! then stops both sidecars and seals the broker session before the binaries
+//! are swapped: the bundle replaces `prism-headless` as well, and a running
+//! executable cannot be replaced on Windows. The app then restarts into the
+//! new version. A failed install restarts the engines on the current one.
+//!
+//! Before installing, the versions are recorded in `update_state.json`. On
+//! the first start after an update the new bundle is verified: the sidecar
+//! is present and matches the built-in hash (`sidecar_check`), and the engine
+//! starts and reports a version this shell supports (`engine_compat`). The
+//! result is emitted as `update-verified` and kept for frontends that
+//! subscribe after the event went out.
+
+use crate::app_reset;
+use crate::commands::portfolio;
+use crate::pipeline_progress::PipelineRun;
+use crate::proxy;
+use crate::python_engine::EnginePool;
+use crate::session_vault;
+use crate::sidecar_check;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+pub const PROGRESS_EVENT: &str = "update-progress";
+pub const VERIFIED_EVENT: &str = "update-verified";
+
+/// Minisign public key for update bundles; unset in builds that cannot update
+const UPDATER_PUBKEY: Option<&str> = option_env!("PRISM_UPDATER_PUBKEY");
+
+pub(crate) const STATE_FILE: &str = "update_state.json";
+
+/// Update found by the last check, installed by `install`
+static AVAILABLE: Mutex<Option<Update>> = Mutex::new(None);
+
+static VERIFICATION: Mutex<Option<UpdateVerification>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub current_version: String,
+    /// Newer version, if one is available
+    pub version: Option<String>,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProgress {
+    pub version: String,
+    /// `downloading`, `installing` or `failed`
+    pub stage: &'static str,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
+/// Versions of an install in progress, kept across the restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateRecord {
+    from_version: String,
+    to_version: String,
+    installed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateVerification {
+    pub from_version: String,
+    pub to_version: String,
+    pub shell_version: String,
+    pub engine_version: Option<String>,
+    pub ok: bool,
+    /// What is wrong with the update, if anything
+    pub problem: Option<String>,
+}
+
+fn state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATE_FILE)
+}
+
+fn pubkey() -> Result<&'static str, String> {
+    UPDATER_PUBKEY
+        .filter(|key| !key.trim().is_empty())
+        .ok_or_else(|| "This build cannot update itself; download the new version".to_string())
+}
+
+/// Look for a newer release.
+pub async fn check(app_handle: &AppHandle) -> Result<UpdateInfo, String> {
+    let mut builder = app_handle.updater_builder().pubkey(pubkey()?);
+    if let Some(url) = proxy::url()? {
+        builder = builder.proxy(url);
+    }
+    let update = builder
+        .build()
+        .map_err(|e| format!("Failed to set up the updater: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?;
+
+    let info = UpdateInfo {
+        current_version: app_handle.package_info().version.to_string(),
+        version: update.as_ref().map(|update| update.version.clone()),
+        notes: update.as_ref().and_then(|update| update.body.clone()),
+        date: update
+            .as_ref()
+            .and_then(|update| update.date)
+            .map(|date| date.to_string()),
+    };
+    if let Ok(mut available) = AVAILABLE.lock() {
+        *available = update;
+    }
+    Ok(info)
+}
+
+/// Download and install the update found by `check`, then restart. Returns
+/// only on failure.
+pub async fn install(app_handle: &AppHandle, pool: &EnginePool) -> Result<(), String> {
+    if portfolio::sync_in_progress() || app_handle.state::<PipelineRun>().is_running() {
+        return Err("Wait for the running sync or pipeline to finish".to_string());
+    }
+    let update = AVAILABLE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("No update available; check for updates first")?;
+    let data_dir = store::data_dir(app_handle)?;
+
+    let mut progress = progress_for(&update);
+    let emit = |progress: &UpdateProgress| {
+        let _ = app_handle.emit(PROGRESS_EVENT, progress);
+    };
+    emit(&progress);
+    let downloaded = update
+        .download(
+            |chunk, total| {
+                progress.downloaded_bytes += chunk as u64;
+                progress.total_bytes = total;
+                emit(&progress);
+            },
+            || {},
+        )
+        .await;
+    let bytes = match downloaded {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            emit(&UpdateProgress {
+                stage: "failed",
+                ..progress_for(&update)
+            });
+            return Err(format!("Update download failed: {}", e));
+        }
+    };
+
+    tracing::info!("Installing update {}", update.version);
+    emit(&UpdateProgress {
+        stage: "installing",
+        downloaded_bytes: bytes.len() as u64,
+        total_bytes: Some(bytes.len() as u64),
+        ..progress_for(&update)
+    });
+    store::write_json(
+        &state_path(&data_dir),
+        &UpdateRecord {
+            from_version: update.current_version.clone(),
+            to_version: update.version.clone(),
+            installed_at: Utc::now(),
+        },
+    )?;
+
+    // The bundle replaces the sidecar too
+    let mut errors = vec![];
+    let stopped = app_reset::stop_engines(pool, &mut errors).await;
+    for error in errors {
+        tracing::warn!("Before update: {}", error);
+    }
+    session_vault::persist(app_handle);
+
+    if let Err(e) = update.install(bytes) {
+        let _ = std::fs::remove_file(state_path(&data_dir));
+        for (role, engine) in stopped {
+            if let Err(e) = crate::respawn_engine(app_handle, engine, role) {
+                tracing::error!("Failed to restart {}: {}", role.label(), e);
+            }
+        }
+        emit(&UpdateProgress {
+            stage: "failed",
+            ..progress_for(&update)
+        });
+        return Err(format!("Update install failed: {}", e));
+    }
+    app_handle.restart()
+}
+
+fn progress_for(update: &Update) -> UpdateProgress {
+    UpdateProgress {
+        version: update.version.clone(),
+        stage: "downloading",
+        downloaded_bytes: 0,
+        total_bytes: None,
+    }
+}
+
+/// Result of the check after the last update, if the app was updated in this
+/// session
+pub fn verification() -> Option<UpdateVerification> {
+    VERIFICATION.lock().ok()?.clone()
+}
+
+/// Verify an update installed before this start, if any. Call after the
+/// engine pool is managed.
+pub fn verify_after_update(app_handle: &AppHandle) {
+    let Ok(data_dir) = store::data_dir(app_handle) else {
+        return;
+    };
+    let path = state_path(&data_dir);
+    let record: UpdateRecord = match store::read_json(&path) {
+        Ok(Some(record)) => record,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Failed to read the update record: {}", e);
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+    };
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let verification = verify(&app_handle, record).await;
+        match &verification.problem {
+            Some(problem) => tracing::error!(
+                "Update {} -> {} failed verification: {}",
+                verification.from_version,
+                verification.to_version,
+                problem
+            ),
+            None => tracing::info!(
+                "Updated {} -> {}, engine v{}",
+                verification.from_version,
+                verification.to_version,
+                verification.engine_version.as_deref().unwrap_or("?")
+            ),
+        }
+        let _ = std::fs::remove_file(&path);
+        if let Ok(mut last) = VERIFICATION.lock() {
+            *last = Some(verification.clone());
+        }
+        let _ = app_handle.emit(VERIFIED_EVENT, &verification);
+    });
+}
+
+async fn verify(app_handle: &AppHandle, record: UpdateRecord) -> UpdateVerification {
+    let shell_version = app_handle.package_info().version.to_string();
+    let mut verification = UpdateVerification {
+        from_version: record.from_version,
+        to_version: record.to_version,
+        shell_version,
+        engine_version: None,
+        ok: false,
+        problem: None,
+    };
+
+    if verification.shell_version != verification.to_version {
+        verification.problem = Some(format!(
+            "The update to {} was not applied; the app is still on {}",
+            verification.to_version, verification.shell_version
+        ));
+        return verification;
+    }
+    if let Some(missing) = sidecar_check::check() {
+        verification.problem = Some(missing.message());
+        return verification;
+    }
+    if let Some(tampered) = sidecar_check::verify() {
+        verification.problem = Some(tampered.message());
+        return verification;
+    }
+
+    // A mismatched engine is stopped when it reports ready (`engine_compat`)
+    let engine = app_handle.state::<EnginePool>().primary();
+    if !engine.is_connected().await {
+        verification.problem = Some(
+            engine
+                .status()
+                .reason
+                .unwrap_or_else(|| "The engine did not start after the update".to_string()),
+        );
+        return verification;
+    }
+    verification.engine_version = engine.get_version().await;
+    verification.ok = true;
+    verification
+}
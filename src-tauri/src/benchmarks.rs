@@ -0,0 +1,307 @@
+//! Custom Benchmarks
+//!
+//! A benchmark is a weighted mix of indices/ETFs (e.g. 70% MSCI World and
+//! 30% Euro Aggregate). Definitions live in `benchmarks.json`; the composite
+//! series of each benchmark is built from the components' price history
+//! (`get_asset_details`) and cached in `benchmarks/{id}.json`:
+//!
+//! - the series starts at 100 on the first day every component has a price
+//! - components are rebalanced to their target weights daily; a component
+//!   without a price on a day carries its last price forward
+//! - a cached series older than `SERIES_MAX_AGE_HOURS` is rebuilt on access
+//!   while the engine is connected
+//!
+//! Comparison and attribution commands take a benchmark id and use
+//! `return_between` on the series.
+
+use crate::python_engine::PythonEngine;
+use crate::store;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Benchmark definitions inside the app data dir
+pub(crate) const BENCHMARKS_FILE: &str = "benchmarks.json";
+
+/// Cached composite series inside the app data dir
+pub(crate) const SERIES_DIR: &str = "benchmarks";
+
+/// Age after which a cached series is rebuilt
+const SERIES_MAX_AGE_HOURS: i64 = 24;
+
+/// Upper bound on components, to keep a rebuild to a few engine requests
+const MAX_COMPONENTS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkComponent {
+    pub isin: String,
+    /// Target weight; weights are normalized to sum to 1 on creation
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Benchmark {
+    pub id: u32,
+    pub name: String,
+    pub components: Vec<BenchmarkComponent>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkPoint {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkSeries {
+    pub benchmark_id: u32,
+    pub updated_at: DateTime<Utc>,
+    pub points: Vec<BenchmarkPoint>,
+}
+
+/// Benchmark return over a period, as attached to comparison results
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReturn {
+    pub benchmark_id: u32,
+    pub name: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// `None` when the series does not cover the period
+    pub return_percent: Option<f64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BenchmarksFile {
+    #[serde(default)]
+    benchmarks: Vec<Benchmark>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PricePoint {
+    date: String,
+    price: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PriceHistoryResponse {
+    #[serde(default)]
+    price_history: Vec<PricePoint>,
+}
+
+fn benchmarks_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(BENCHMARKS_FILE)
+}
+
+fn series_path(data_dir: &Path, id: u32) -> PathBuf {
+    data_dir.join(SERIES_DIR).join(format!("{}.json", id))
+}
+
+fn load(data_dir: &Path) -> Result<BenchmarksFile, String> {
+    Ok(store::read_json(&benchmarks_path(data_dir))?.unwrap_or_default())
+}
+
+pub fn list(data_dir: &Path) -> Result<Vec<Benchmark>, String> {
+    Ok(load(data_dir)?.benchmarks)
+}
+
+pub fn get(data_dir: &Path, id: u32) -> Result<Benchmark, String> {
+    list(data_dir)?
+        .into_iter()
+        .find(|benchmark| benchmark.id == id)
+        .ok_or_else(|| format!("Benchmark {} not found", id))
+}
+
+/// Store a new benchmark. Components must be distinct, validated ISINs with
+/// positive weights; the weights are normalized.
+pub fn create(
+    data_dir: &Path,
+    name: &str,
+    mut components: Vec<BenchmarkComponent>,
+) -> Result<Benchmark, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Benchmark name cannot be empty".to_string());
+    }
+    if components.is_empty() || components.len() > MAX_COMPONENTS {
+        return Err(format!(
+            "A benchmark needs between 1 and {} components",
+            MAX_COMPONENTS
+        ));
+    }
+    let mut seen = HashSet::new();
+    for component in &components {
+        if !component.weight.is_finite() || component.weight <= 0.0 {
+            return Err(format!("Weight of {} must be positive", component.isin));
+        }
+        if !seen.insert(component.isin.as_str()) {
+            return Err(format!("{} is listed twice", component.isin));
+        }
+    }
+    let total: f64 = components.iter().map(|component| component.weight).sum();
+    for component in &mut components {
+        component.weight /= total;
+    }
+
+    let mut file = load(data_dir)?;
+    let benchmark = Benchmark {
+        id: file.benchmarks.iter().map(|b| b.id).max().unwrap_or(0) + 1,
+        name: name.to_string(),
+        components,
+        created_at: Utc::now(),
+    };
+    file.benchmarks.push(benchmark.clone());
+    store::write_json(&benchmarks_path(data_dir), &file)?;
+    Ok(benchmark)
+}
+
+pub fn delete(data_dir: &Path, id: u32) -> Result<(), String> {
+    let mut file = load(data_dir)?;
+    let before = file.benchmarks.len();
+    file.benchmarks.retain(|benchmark| benchmark.id != id);
+    if file.benchmarks.len() == before {
+        return Err(format!("Benchmark {} not found", id));
+    }
+    store::write_json(&benchmarks_path(data_dir), &file)?;
+    let _ = std::fs::remove_file(series_path(data_dir, id));
+    Ok(())
+}
+
+/// Daily-rebalanced composite of the component price histories, indexed
+/// to 100 on the first day all components are priced.
+fn composite(components: &[(f64, BTreeMap<NaiveDate, f64>)]) -> Vec<BenchmarkPoint> {
+    let Some(start) = components
+        .iter()
+        .map(|(_, prices)| prices.keys().next().copied())
+        .collect::<Option<Vec<_>>>()
+        .and_then(|firsts| firsts.into_iter().max())
+    else {
+        return vec![];
+    };
+    let dates: BTreeSet<NaiveDate> = components
+        .iter()
+        .flat_map(|(_, prices)| prices.range(start..).map(|(date, _)| *date))
+        .collect();
+
+    // Every component has a price on or before `start`
+    let mut last: Vec<f64> = components
+        .iter()
+        .map(|(_, prices)| prices.range(..=start).next_back().map_or(0.0, |(_, p)| *p))
+        .collect();
+    let mut value = 100.0;
+    let mut points = vec![];
+    for date in &dates {
+        let mut daily_return = 0.0;
+        for (index, (weight, prices)) in components.iter().enumerate() {
+            if let Some(price) = prices.get(date) {
+                if last[index] > 0.0 {
+                    daily_return += weight * (price / last[index] - 1.0);
+                }
+                last[index] = *price;
+            }
+        }
+        value *= 1.0 + daily_return;
+        points.push(BenchmarkPoint { date: *date, value });
+    }
+    points
+}
+
+/// Rebuild the composite series from the engine's price history.
+pub async fn rebuild(
+    data_dir: &Path,
+    engine: &PythonEngine,
+    benchmark: &Benchmark,
+) -> Result<BenchmarkSeries, String> {
+    let mut components = vec![];
+    for component in &benchmark.components {
+        let data = engine
+            .request("get_asset_details", json!({ "isin": component.isin }))
+            .await?;
+        let history: PriceHistoryResponse = serde_json::from_value(data)
+            .map_err(|e| format!("Invalid price history for {}: {}", component.isin, e))?;
+        let prices: BTreeMap<NaiveDate, f64> = history
+            .price_history
+            .into_iter()
+            .filter_map(|point| {
+                let date = NaiveDate::parse_from_str(&point.date, "%Y-%m-%d").ok()?;
+                (point.price > 0.0).then_some((date, point.price))
+            })
+            .collect();
+        if prices.is_empty() {
+            return Err(format!("No price history for {}", component.isin));
+        }
+        components.push((component.weight, prices));
+    }
+
+    let series = BenchmarkSeries {
+        benchmark_id: benchmark.id,
+        updated_at: Utc::now(),
+        points: composite(&components),
+    };
+    store::write_json(&series_path(data_dir, benchmark.id), &series)?;
+    Ok(series)
+}
+
+/// Cached series, rebuilt when stale and the engine is connected.
+pub async fn series(
+    data_dir: &Path,
+    engine: &PythonEngine,
+    benchmark: &Benchmark,
+) -> Result<BenchmarkSeries, String> {
+    let cached: Option<BenchmarkSeries> = store::read_json(&series_path(data_dir, benchmark.id))?;
+    let stale = cached.as_ref().map_or(true, |series| {
+        Utc::now() - series.updated_at > chrono::Duration::hours(SERIES_MAX_AGE_HOURS)
+    });
+    if !stale || !engine.is_connected().await {
+        return cached.ok_or_else(|| engine.unavailable().into());
+    }
+    match rebuild(data_dir, engine, benchmark).await {
+        Ok(series) => Ok(series),
+        // Serve the old series rather than failing a comparison
+        Err(e) => cached.ok_or(e),
+    }
+}
+
+/// Last value on or before `date`
+fn value_on(points: &[BenchmarkPoint], date: NaiveDate) -> Option<f64> {
+    let index = points.partition_point(|point| point.date <= date);
+    index.checked_sub(1).map(|index| points[index].value)
+}
+
+/// Benchmark return between two dates, in percent
+pub fn return_between(series: &BenchmarkSeries, from: NaiveDate, to: NaiveDate) -> Option<f64> {
+    let first = series.points.first()?;
+    if from < first.date {
+        return None;
+    }
+    let start = value_on(&series.points, from)?;
+    let end = value_on(&series.points, to)?;
+    Some((end / start - 1.0) * 100.0)
+}
+
+/// Look up benchmark `id` and its return over a period.
+pub async fn period_return(
+    data_dir: &Path,
+    engine: &PythonEngine,
+    id: u32,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<BenchmarkReturn, String> {
+    let benchmark = get(data_dir, id)?;
+    let series = series(data_dir, engine, &benchmark).await?;
+    Ok(BenchmarkReturn {
+        benchmark_id: id,
+        name: benchmark.name,
+        from,
+        to,
+        return_percent: return_between(&series, from, to),
+    })
+}
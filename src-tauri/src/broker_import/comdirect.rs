@@ -0,0 +1,277 @@
+//! comdirect CSV exports
+//!
+//! Both depot exports start with a few lines of account information before
+//! the header row, are `;`-separated and use German number formats:
+//!
+//! - Depotübersicht: current holdings with `Stück/Nom.`, `Akt. Kurs` and the
+//!   buy price (`Kaufkurs`)
+//! - Depotumsätze: executions with `Buchungstag`, `Stück/Nom.`, `Geschäftsart`
+//!   (or the sign of `Umsatz`) and the amount; holdings come from the ledger
+//!
+//! Rows are matched to instruments by ISIN. Exports that only carry the WKN
+//! cannot be resolved and their rows are reported as skipped; summary rows
+//! without WKN or ISIN are ignored.
+
+use super::{
+    is_isin, parse_date, parse_german_number, positions_from_transactions, BrokerImportAdapter,
+    BrokerStatement, ImportFormat, ImportedPosition, ImportedTransaction, SkippedRow,
+    StatementFile,
+};
+
+pub const BROKER: &str = "comdirect";
+
+/// Account lines allowed before the header row
+const MAX_PREAMBLE_ROWS: usize = 10;
+
+const NAME: &[&str] = &["bezeichnung"];
+const WKN: &[&str] = &["wkn"];
+const ISIN: &[&str] = &["isin"];
+const QUANTITY: &[&str] = &["stück/nom.", "stück"];
+const PRICE: &[&str] = &["akt.kurs", "kurs"];
+const BUY_PRICE: &[&str] = &["kaufkurs", "kaufkursineur", "einstandskurs"];
+const CURRENCY: &[&str] = &["währung"];
+const DATE: &[&str] = &["buchungstag", "geschäftstag", "valuta"];
+const KIND: &[&str] = &["geschäftsart", "art"];
+const AMOUNT: &[&str] = &["umsatzineur", "umsatz", "betrag"];
+const REFERENCE: &[&str] = &["referenz", "ordernummer"];
+
+/// Column indices of one export, looked up by header name with spaces
+/// removed (comdirect writes both `Stück/Nom.` and `Stück / Nom.`)
+struct Columns<'a> {
+    header: &'a [String],
+}
+
+impl Columns<'_> {
+    fn find(&self, aliases: &[&str]) -> Option<usize> {
+        self.header.iter().position(|cell| {
+            let name: String = cell
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .to_lowercase();
+            aliases.contains(&name.as_str())
+        })
+    }
+}
+
+fn cell(row: &[String], index: Option<usize>) -> &str {
+    index
+        .and_then(|index| row.get(index))
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+/// Index of the header row after the account preamble
+fn header_index(rows: &[Vec<String>]) -> Option<usize> {
+    rows.iter().take(MAX_PREAMBLE_ROWS).position(|row| {
+        let columns = Columns { header: row };
+        columns.find(NAME).is_some()
+            && (columns.find(WKN).is_some() || columns.find(ISIN).is_some())
+            && columns.find(QUANTITY).is_some()
+    })
+}
+
+/// Whether `rows` are a comdirect depot export
+pub fn detect(rows: &[Vec<String>]) -> bool {
+    header_index(rows).is_some()
+}
+
+pub struct ComdirectAdapter;
+
+impl BrokerImportAdapter for ComdirectAdapter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat {
+            id: "comdirect_csv",
+            broker: BROKER,
+            label: "comdirect CSV",
+            extensions: &["csv"],
+            description: "Depotübersicht or Depotumsätze export with the ISIN column",
+        }
+    }
+
+    fn detect(&self, file: &StatementFile) -> bool {
+        file.rows().is_ok_and(detect)
+    }
+
+    fn parse(&self, file: &StatementFile) -> Result<BrokerStatement, String> {
+        parse(file.rows()?)
+    }
+}
+
+/// Parse a comdirect depot export, preamble included.
+pub fn parse(rows: &[Vec<String>]) -> Result<BrokerStatement, String> {
+    let Some(start) = header_index(rows) else {
+        return Err("Not a comdirect depot export".to_string());
+    };
+    let columns = Columns {
+        header: &rows[start],
+    };
+    // Line numbers of the body rows are 1-based and count the preamble
+    let body = rows[start + 1..]
+        .iter()
+        .enumerate()
+        .map(|(index, row)| (start + index + 2, row));
+    if columns.find(DATE).is_some() {
+        Ok(parse_transactions(&columns, body))
+    } else {
+        Ok(parse_positions(&columns, body))
+    }
+}
+
+/// ISIN of a row; `Err` carries the skip reason, `Ok(None)` marks a summary
+/// row
+fn row_isin(columns: &Columns, row: &[String]) -> Result<Option<String>, &'static str> {
+    let isin = cell(row, columns.find(ISIN)).to_uppercase();
+    if is_isin(&isin) {
+        return Ok(Some(isin));
+    }
+    if !isin.is_empty() {
+        return Err("Invalid ISIN");
+    }
+    if cell(row, columns.find(WKN)).is_empty() {
+        Ok(None)
+    } else {
+        Err("Only a WKN; export the depot with the ISIN column")
+    }
+}
+
+fn parse_positions<'a>(
+    columns: &Columns,
+    body: impl Iterator<Item = (usize, &'a Vec<String>)>,
+) -> BrokerStatement {
+    let name = columns.find(NAME);
+    let quantity = columns.find(QUANTITY);
+    let price = columns.find(PRICE);
+    let buy_price = columns.find(BUY_PRICE);
+    let currency = columns.find(CURRENCY);
+
+    let mut positions = vec![];
+    let mut skipped = vec![];
+    for (line, row) in body {
+        let mut skip = |reason: &str| {
+            skipped.push(SkippedRow {
+                line,
+                reason: reason.to_string(),
+            })
+        };
+        let isin = match row_isin(columns, row) {
+            Ok(Some(isin)) => isin,
+            Ok(None) => continue,
+            Err(reason) => {
+                skip(reason);
+                continue;
+            }
+        };
+        let Some(row_quantity) = parse_german_number(cell(row, quantity)).filter(|q| *q > 0.0)
+        else {
+            skip("Missing quantity");
+            continue;
+        };
+        positions.push(ImportedPosition {
+            isin,
+            name: cell(row, name).to_string(),
+            quantity: row_quantity,
+            avg_buy_price: parse_german_number(cell(row, buy_price)),
+            price: parse_german_number(cell(row, price)),
+            currency: Some(cell(row, currency))
+                .filter(|currency| !currency.is_empty())
+                .unwrap_or("EUR")
+                .to_string(),
+        });
+    }
+
+    BrokerStatement {
+        broker: BROKER,
+        kind: "positions",
+        positions,
+        transactions: vec![],
+        skipped,
+    }
+}
+
+fn parse_transactions<'a>(
+    columns: &Columns,
+    body: impl Iterator<Item = (usize, &'a Vec<String>)>,
+) -> BrokerStatement {
+    let name = columns.find(NAME);
+    let date = columns.find(DATE);
+    let quantity = columns.find(QUANTITY);
+    let kind = columns.find(KIND);
+    let amount = columns.find(AMOUNT);
+    let currency = columns.find(CURRENCY);
+    let reference = columns.find(REFERENCE);
+
+    let mut transactions = vec![];
+    let mut skipped = vec![];
+    for (line, row) in body {
+        let mut skip = |reason: &str| {
+            skipped.push(SkippedRow {
+                line,
+                reason: reason.to_string(),
+            })
+        };
+        let isin = match row_isin(columns, row) {
+            Ok(Some(isin)) => isin,
+            Ok(None) => continue,
+            Err(reason) => {
+                skip(reason);
+                continue;
+            }
+        };
+        let Some(row_date) = parse_date(cell(row, date)) else {
+            skip("Invalid date");
+            continue;
+        };
+        let Some(row_amount) = parse_german_number(cell(row, amount)).filter(|a| *a != 0.0) else {
+            skip("Missing amount");
+            continue;
+        };
+
+        let row_kind = cell(row, kind).to_lowercase();
+        let transaction_type = if row_kind.contains("verkauf") {
+            "Sell"
+        } else if row_kind.contains("kauf") {
+            "Buy"
+        } else if ["ertrag", "dividende", "ausschüttung"]
+            .iter()
+            .any(|word| row_kind.contains(word))
+        {
+            "Dividend"
+        } else if !row_kind.is_empty() {
+            skip("Unsupported booking type");
+            continue;
+        } else if row_amount < 0.0 {
+            // Without a booking type, money leaving the account is a purchase
+            "Buy"
+        } else {
+            "Sell"
+        };
+        let row_quantity = parse_german_number(cell(row, quantity)).map(f64::abs);
+        if transaction_type != "Dividend" && !row_quantity.is_some_and(|q| q > 0.0) {
+            skip("Missing quantity");
+            continue;
+        }
+
+        transactions.push(ImportedTransaction {
+            isin,
+            name: cell(row, name).to_string(),
+            transaction_type: transaction_type.to_string(),
+            date: row_date,
+            quantity: row_quantity.filter(|_| transaction_type != "Dividend"),
+            amount: row_amount.abs(),
+            currency: Some(cell(row, currency))
+                .filter(|currency| !currency.is_empty())
+                .unwrap_or("EUR")
+                .to_string(),
+            reference: Some(cell(row, reference).to_string()).filter(|id| !id.is_empty()),
+        });
+    }
+
+    BrokerStatement {
+        broker: BROKER,
+        kind: "transactions",
+        positions: positions_from_transactions(&transactions),
+        transactions,
+        skipped,
+    }
+}
@@ -0,0 +1,266 @@
+//! DeGiro CSV exports
+//!
+//! Two exports are recognized by their header row, in English, German and
+//! Dutch:
+//!
+//! - `Transactions.csv`: one row per execution with signed quantity, local
+//!   value, and value and fees in the account currency. Unnamed columns after
+//!   a value hold its currency. Holdings are derived from the ledger.
+//! - `Portfolio.csv`: current holdings with closing price; the local value
+//!   column holds the currency with the amount in the unnamed column after it.
+//!   Cash rows (no ISIN) are skipped.
+
+use super::{
+    is_isin, parse_date, parse_number, positions_from_transactions, BrokerImportAdapter,
+    BrokerStatement, ImportFormat, ImportedPosition, ImportedTransaction, SkippedRow,
+    StatementFile,
+};
+
+pub const BROKER: &str = "degiro";
+
+const DATE: &[&str] = &["date", "datum"];
+const PRODUCT: &[&str] = &["product", "produkt"];
+const ISIN: &[&str] = &["isin"];
+const SYMBOL_ISIN: &[&str] = &["symbol/isin", "symbool/isin"];
+const QUANTITY: &[&str] = &["quantity", "amount", "anzahl", "aantal"];
+const CLOSING: &[&str] = &["closing", "schlusskurs", "slotkoers"];
+const LOCAL_VALUE: &[&str] = &[
+    "local value",
+    "wert in lokalwährung",
+    "lokaler wert",
+    "lokale waarde",
+];
+const FEES: &[&str] = &[
+    "transaction and/or third party fees",
+    "transaction costs",
+    "transaktionskosten und/oder gebühren dritter",
+    "transaktionskosten",
+    "transactiekosten en/of kosten van derden",
+    "transactiekosten",
+];
+const VALUE: &[&str] = &["value", "wert", "waarde"];
+const ORDER_ID: &[&str] = &["order id", "order-id"];
+
+/// Column indices of one export, looked up by header name
+struct Columns<'a> {
+    header: &'a [String],
+}
+
+impl Columns<'_> {
+    fn find(&self, aliases: &[&str]) -> Option<usize> {
+        self.header
+            .iter()
+            .position(|cell| aliases.contains(&cell.to_lowercase().as_str()))
+    }
+}
+
+fn cell(row: &[String], index: Option<usize>) -> &str {
+    index
+        .and_then(|index| row.get(index))
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+fn is_currency(value: &str) -> bool {
+    value.len() == 3 && value.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Amount and currency of a value column: either `amount, CUR` or
+/// `CUR, amount` across the column and the unnamed one after it.
+fn money(row: &[String], index: Option<usize>) -> Option<(f64, String)> {
+    let index = index?;
+    let (first, second) = (cell(row, Some(index)), cell(row, Some(index + 1)));
+    if is_currency(first) {
+        return Some((parse_number(second)?, first.to_string()));
+    }
+    let currency = if is_currency(second) { second } else { "EUR" };
+    Some((parse_number(first)?, currency.to_string()))
+}
+
+/// Whether the header row belongs to a DeGiro export
+pub fn detect(header: &[String]) -> bool {
+    let columns = Columns { header };
+    let transactions = columns.find(DATE).is_some()
+        && columns.find(ISIN).is_some()
+        && columns.find(QUANTITY).is_some()
+        && columns.find(ORDER_ID).is_some();
+    let portfolio = columns.find(SYMBOL_ISIN).is_some() && columns.find(QUANTITY).is_some();
+    transactions || portfolio
+}
+
+/// Parse a DeGiro export; `rows` includes the header row.
+pub fn parse(rows: &[Vec<String>]) -> Result<BrokerStatement, String> {
+    let Some((header, body)) = rows.split_first() else {
+        return Err("The file is empty".to_string());
+    };
+    let columns = Columns { header };
+    if columns.find(SYMBOL_ISIN).is_some() {
+        Ok(parse_portfolio(&columns, body))
+    } else if detect(header) {
+        Ok(parse_transactions(&columns, body))
+    } else {
+        Err("Not a DeGiro transactions or portfolio export".to_string())
+    }
+}
+
+pub struct DegiroAdapter;
+
+impl BrokerImportAdapter for DegiroAdapter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat {
+            id: "degiro_csv",
+            broker: BROKER,
+            label: "DeGiro CSV",
+            extensions: &["csv"],
+            description: "Transactions.csv or Portfolio.csv (English, German, Dutch)",
+        }
+    }
+
+    fn detect(&self, file: &StatementFile) -> bool {
+        detect(file.header())
+    }
+
+    fn parse(&self, file: &StatementFile) -> Result<BrokerStatement, String> {
+        parse(file.rows()?)
+    }
+}
+
+fn parse_transactions(columns: &Columns, body: &[Vec<String>]) -> BrokerStatement {
+    let date = columns.find(DATE);
+    let product = columns.find(PRODUCT);
+    let isin = columns.find(ISIN);
+    let quantity = columns.find(QUANTITY);
+    let local_value = columns.find(LOCAL_VALUE);
+    let account_value = columns.find(VALUE);
+    let fees = columns.find(FEES);
+    let order_id = columns.find(ORDER_ID);
+
+    let mut transactions = vec![];
+    let mut skipped = vec![];
+    for (index, row) in body.iter().enumerate() {
+        let line = index + 2;
+        if row.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        let mut skip = |reason: &str| {
+            skipped.push(SkippedRow {
+                line,
+                reason: reason.to_string(),
+            })
+        };
+
+        let row_isin = cell(row, isin).to_uppercase();
+        if !is_isin(&row_isin) {
+            skip("Missing or invalid ISIN");
+            continue;
+        }
+        let Some(row_date) = parse_date(cell(row, date)) else {
+            skip("Invalid date");
+            continue;
+        };
+        let Some(row_quantity) = parse_number(cell(row, quantity)).filter(|q| *q != 0.0) else {
+            skip("Missing quantity");
+            continue;
+        };
+
+        // Value in the account currency, excluding fees (those become a
+        // separate transaction); older exports only have the local value
+        let value = money(row, account_value).or_else(|| money(row, local_value));
+        let Some((amount, currency)) = value else {
+            skip("Missing value");
+            continue;
+        };
+        let reference = Some(cell(row, order_id).to_string()).filter(|id| !id.is_empty());
+
+        let name = cell(row, product).to_string();
+        let fee = money(row, fees).map(|(fee, _)| fee.abs()).unwrap_or(0.0);
+        transactions.push(ImportedTransaction {
+            isin: row_isin.clone(),
+            name: name.clone(),
+            transaction_type: if row_quantity > 0.0 { "Buy" } else { "Sell" }.to_string(),
+            date: row_date.clone(),
+            quantity: Some(row_quantity.abs()),
+            amount: amount.abs(),
+            currency: currency.clone(),
+            reference: reference.clone(),
+        });
+        if fee > 0.0 {
+            transactions.push(ImportedTransaction {
+                isin: row_isin,
+                name,
+                transaction_type: "Fee".to_string(),
+                date: row_date,
+                quantity: None,
+                amount: fee,
+                currency,
+                reference: reference.map(|id| format!("{}-fee", id)),
+            });
+        }
+    }
+
+    BrokerStatement {
+        broker: BROKER,
+        kind: "transactions",
+        positions: positions_from_transactions(&transactions),
+        transactions,
+        skipped,
+    }
+}
+
+fn parse_portfolio(columns: &Columns, body: &[Vec<String>]) -> BrokerStatement {
+    let product = columns.find(PRODUCT);
+    let isin = columns.find(SYMBOL_ISIN);
+    let quantity = columns.find(QUANTITY);
+    let closing = columns.find(CLOSING);
+    let local_value = columns.find(LOCAL_VALUE);
+
+    let mut positions = vec![];
+    let mut skipped = vec![];
+    for (index, row) in body.iter().enumerate() {
+        let line = index + 2;
+        if row.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        let row_isin = cell(row, isin).to_uppercase();
+        if row_isin.is_empty() {
+            // Cash and money market rows carry no ISIN
+            continue;
+        }
+        if !is_isin(&row_isin) {
+            skipped.push(SkippedRow {
+                line,
+                reason: "Invalid ISIN".to_string(),
+            });
+            continue;
+        }
+        let Some(row_quantity) = parse_number(cell(row, quantity)).filter(|q| *q > 0.0) else {
+            skipped.push(SkippedRow {
+                line,
+                reason: "Missing quantity".to_string(),
+            });
+            continue;
+        };
+
+        let value = money(row, local_value);
+        let price = parse_number(cell(row, closing))
+            .or_else(|| value.as_ref().map(|(value, _)| value / row_quantity));
+        positions.push(ImportedPosition {
+            isin: row_isin,
+            name: cell(row, product).to_string(),
+            quantity: row_quantity,
+            avg_buy_price: None,
+            price,
+            currency: value
+                .map(|(_, currency)| currency)
+                .unwrap_or_else(|| "EUR".to_string()),
+        });
+    }
+
+    BrokerStatement {
+        broker: BROKER,
+        kind: "positions",
+        positions,
+        transactions: vec![],
+        skipped,
+    }
+}
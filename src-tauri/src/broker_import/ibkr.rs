@@ -0,0 +1,345 @@
+//! Interactive Brokers Flex Query statements
+//!
+//! Both Flex output formats are read:
+//!
+//! - XML: `OpenPosition`, `Trade` and `CashTransaction` elements anywhere
+//!   below `FlexQueryResponse` (several accounts are merged)
+//! - CSV: one header row per section; a section is recognized by its columns
+//!   (`Buy/Sell` for trades, `MarkPrice` for positions, `Amount` and `Type`
+//!   for cash transactions)
+//!
+//! CSV column names are normalized to the XML attribute names, so both go
+//! through the same mapping. Instruments without an ISIN (options, futures,
+//! FX) are reported as skipped. Withholding tax is recorded as a fee.
+
+use super::{
+    is_isin, parse_date, parse_number, positions_from_transactions, BrokerImportAdapter,
+    BrokerStatement, ImportFormat, ImportedPosition, ImportedTransaction, SkippedRow,
+    StatementFile,
+};
+use std::collections::HashMap;
+
+pub const BROKER: &str = "ibkr";
+
+/// CSV column names that differ from the XML attribute names (normalized)
+const CSV_ALIASES: &[(&str, &str)] = &[
+    ("assetclass", "assetcategory"),
+    ("currencyprimary", "currency"),
+];
+
+/// One Flex record with normalized (lowercase, alphanumeric) field names
+struct Record {
+    line: usize,
+    fields: HashMap<String, String>,
+}
+
+impl Record {
+    fn get(&self, name: &str) -> &str {
+        self.fields
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    fn number(&self, name: &str) -> Option<f64> {
+        parse_number(self.get(name))
+    }
+
+    /// Flex dates are `yyyyMMdd` or `yyyy-MM-dd`, optionally followed by a
+    /// time after `;` or a space
+    fn date(&self, names: &[&str]) -> Option<String> {
+        names.iter().find_map(|name| {
+            let value = self.get(name);
+            parse_date(value.split([';', ' ', 'T']).next().unwrap_or_default())
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Positions,
+    Trades,
+    Cash,
+}
+
+fn normalize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase();
+    CSV_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, canonical)| canonical.to_string())
+}
+
+/// Whether `text` is a Flex XML statement
+pub fn detect_xml(text: &str) -> bool {
+    text.trim_start().starts_with('<') && text.contains("<FlexQueryResponse")
+}
+
+/// Whether CSV `rows` are a Flex CSV statement
+pub fn detect_csv(rows: &[Vec<String>]) -> bool {
+    rows.first()
+        .is_some_and(|header| header.iter().any(|cell| cell == "ClientAccountID"))
+}
+
+fn section_of(header: &[String]) -> Option<Section> {
+    let has = |name: &str| header.iter().any(|cell| normalize(cell) == name);
+    if has("buysell") {
+        Some(Section::Trades)
+    } else if has("markprice") {
+        Some(Section::Positions)
+    } else if has("amount") && has("type") {
+        Some(Section::Cash)
+    } else {
+        None
+    }
+}
+
+/// Parse a Flex XML statement.
+pub fn parse_xml(text: &str) -> Result<BrokerStatement, String> {
+    let document =
+        roxmltree::Document::parse(text).map_err(|e| format!("Invalid Flex XML: {}", e))?;
+    let mut records = vec![];
+    for node in document.descendants().filter(|node| node.is_element()) {
+        let section = match node.tag_name().name() {
+            "OpenPosition" => Section::Positions,
+            "Trade" => Section::Trades,
+            "CashTransaction" => Section::Cash,
+            _ => continue,
+        };
+        let fields = node
+            .attributes()
+            .map(|attribute| (normalize(attribute.name()), attribute.value().to_string()))
+            .collect();
+        let line = document.text_pos_at(node.range().start).row as usize;
+        records.push((section, Record { line, fields }));
+    }
+    Ok(build(records))
+}
+
+/// Parse a Flex CSV statement; every section starts with its own header.
+pub fn parse_csv(rows: &[Vec<String>]) -> Result<BrokerStatement, String> {
+    let mut records = vec![];
+    let mut current: Option<(Section, Vec<String>)> = None;
+    for (index, row) in rows.iter().enumerate() {
+        if row.iter().any(|cell| cell == "ClientAccountID") {
+            current = section_of(row)
+                .map(|section| (section, row.iter().map(|cell| normalize(cell)).collect()));
+            continue;
+        }
+        let Some((section, header)) = &current else {
+            continue;
+        };
+        if row.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        let fields = header.iter().cloned().zip(row.iter().cloned()).collect();
+        records.push((
+            *section,
+            Record {
+                line: index + 1,
+                fields,
+            },
+        ));
+    }
+    if records.is_empty() {
+        return Err("No trades, positions or cash transactions in the statement".to_string());
+    }
+    Ok(build(records))
+}
+
+pub struct FlexXmlAdapter;
+
+impl BrokerImportAdapter for FlexXmlAdapter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat {
+            id: "ibkr_flex_xml",
+            broker: BROKER,
+            label: "IBKR Flex XML",
+            extensions: &["xml"],
+            description: "Flex Query with open positions, trades and cash transactions",
+        }
+    }
+
+    fn detect(&self, file: &StatementFile) -> bool {
+        detect_xml(&file.text)
+    }
+
+    fn parse(&self, file: &StatementFile) -> Result<BrokerStatement, String> {
+        parse_xml(&file.text)
+    }
+}
+
+pub struct FlexCsvAdapter;
+
+impl BrokerImportAdapter for FlexCsvAdapter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat {
+            id: "ibkr_flex_csv",
+            broker: BROKER,
+            label: "IBKR Flex CSV",
+            extensions: &["csv"],
+            description: "Flex Query with open positions, trades and cash transactions",
+        }
+    }
+
+    fn detect(&self, file: &StatementFile) -> bool {
+        file.rows().is_ok_and(detect_csv)
+    }
+
+    fn parse(&self, file: &StatementFile) -> Result<BrokerStatement, String> {
+        parse_csv(file.rows()?)
+    }
+}
+
+fn build(records: Vec<(Section, Record)>) -> BrokerStatement {
+    let mut positions = vec![];
+    let mut transactions = vec![];
+    let mut skipped = vec![];
+
+    for (section, record) in &records {
+        let mut skip = |reason: &str| {
+            skipped.push(SkippedRow {
+                line: record.line,
+                reason: reason.to_string(),
+            })
+        };
+        let isin = record.get("isin").to_uppercase();
+        let currency = Some(record.get("currency"))
+            .filter(|currency| !currency.is_empty())
+            .unwrap_or("USD")
+            .to_string();
+        let name = Some(record.get("description"))
+            .filter(|name| !name.is_empty())
+            .unwrap_or(record.get("symbol"))
+            .to_string();
+
+        match section {
+            Section::Positions => {
+                if !is_isin(&isin) {
+                    skip("No ISIN (options, futures and FX are not imported)");
+                    continue;
+                }
+                let Some(quantity) = record.number("position").filter(|q| *q > 0.0) else {
+                    skip("Missing or short position");
+                    continue;
+                };
+                positions.push(ImportedPosition {
+                    isin,
+                    name,
+                    quantity,
+                    avg_buy_price: record.number("costbasisprice"),
+                    price: record.number("markprice"),
+                    currency,
+                });
+            }
+            Section::Trades => {
+                if !is_isin(&isin) {
+                    skip("No ISIN (options, futures and FX are not imported)");
+                    continue;
+                }
+                let Some(date) = record.date(&["tradedate", "datetime"]) else {
+                    skip("Invalid trade date");
+                    continue;
+                };
+                let Some(quantity) = record.number("quantity").filter(|q| *q != 0.0) else {
+                    skip("Missing quantity");
+                    continue;
+                };
+                let amount = record
+                    .number("proceeds")
+                    .or_else(|| Some(quantity * record.number("tradeprice")?))
+                    .map(f64::abs);
+                let Some(amount) = amount else {
+                    skip("Missing proceeds");
+                    continue;
+                };
+                let sell = record.get("buysell").eq_ignore_ascii_case("SELL") || quantity < 0.0;
+                let reference = Some(record.get("tradeid").to_string()).filter(|id| !id.is_empty());
+                let commission = record.number("ibcommission").map(f64::abs).unwrap_or(0.0);
+
+                transactions.push(ImportedTransaction {
+                    isin: isin.clone(),
+                    name: name.clone(),
+                    transaction_type: if sell { "Sell" } else { "Buy" }.to_string(),
+                    date: date.clone(),
+                    quantity: Some(quantity.abs()),
+                    amount,
+                    currency: currency.clone(),
+                    reference: reference.clone(),
+                });
+                if commission > 0.0 {
+                    transactions.push(ImportedTransaction {
+                        isin,
+                        name,
+                        transaction_type: "Fee".to_string(),
+                        date,
+                        quantity: None,
+                        amount: commission,
+                        currency,
+                        reference: reference.map(|id| format!("{}-commission", id)),
+                    });
+                }
+            }
+            Section::Cash => {
+                let kind = record.get("type").to_lowercase();
+                let transaction_type = if kind.contains("deposit") || kind.contains("withdraw") {
+                    "Transfer"
+                } else if kind.contains("dividend") || kind.contains("in lieu") {
+                    "Dividend"
+                } else if kind.contains("interest") && kind.contains("received") {
+                    "Interest"
+                } else if kind.contains("tax") || kind.contains("fee") || kind.contains("interest")
+                {
+                    "Fee"
+                } else {
+                    skip("Unsupported cash transaction type");
+                    continue;
+                };
+                let Some(date) = record.date(&["datetime", "settledate", "reportdate"]) else {
+                    skip("Invalid date");
+                    continue;
+                };
+                let Some(amount) = record.number("amount").filter(|a| *a != 0.0) else {
+                    skip("Missing amount");
+                    continue;
+                };
+                transactions.push(ImportedTransaction {
+                    isin: if is_isin(&isin) { isin } else { String::new() },
+                    name,
+                    transaction_type: transaction_type.to_string(),
+                    date,
+                    quantity: None,
+                    // Transfers keep their sign (negative = withdrawal)
+                    amount: if transaction_type == "Transfer" {
+                        amount
+                    } else {
+                        amount.abs()
+                    },
+                    currency,
+                    reference: Some(record.get("transactionid").to_string())
+                        .filter(|id| !id.is_empty()),
+                });
+            }
+        }
+    }
+
+    // Statements without an open positions section imply holdings from trades
+    let has_positions = records
+        .iter()
+        .any(|(section, _)| *section == Section::Positions);
+    BrokerStatement {
+        broker: BROKER,
+        kind: "flexStatement",
+        positions: if has_positions {
+            positions
+        } else {
+            positions_from_transactions(&transactions)
+        },
+        transactions,
+        skipped,
+    }
+}
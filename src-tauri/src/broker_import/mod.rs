@@ -0,0 +1,367 @@
+//! Broker Statement Import
+//!
+//! Parses exports of brokers other than Trade Republic into normalized
+//! holdings and transactions, which are forwarded to the engine with
+//! `import_holdings` so a second account merges into the same dashboard.
+//! Gated by the `new_importers` flag.
+//!
+//! Every format is a `BrokerImportAdapter` (detect, parse, normalize) listed
+//! in `ADAPTERS`; the first adapter that recognizes a file parses it, so more
+//! specific formats come first. Adding a broker means adding an adapter
+//! there, nothing else.
+//!
+//! Parsing happens entirely in the shell; rows that cannot be read are
+//! reported back as skipped instead of failing the whole file. Files that are
+//! not valid UTF-8 are read as ISO-8859-1, the default of German brokers.
+
+pub mod comdirect;
+pub mod degiro;
+pub mod ibkr;
+pub mod scalable;
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// A holding as forwarded to the engine
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedPosition {
+    pub isin: String,
+    pub name: String,
+    pub quantity: f64,
+    /// Average buy price, when the statement allows deriving it
+    pub avg_buy_price: Option<f64>,
+    /// Last price from the statement, in `currency`
+    pub price: Option<f64>,
+    pub currency: String,
+}
+
+/// A ledger entry as forwarded to the engine; `transaction_type` uses the
+/// engine's types (`Buy`, `Sell`, `Dividend`, `Fee`, ...)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedTransaction {
+    pub isin: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub transaction_type: String,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub quantity: Option<f64>,
+    /// Amount in `currency`; unsigned except for transfers, where negative
+    /// means outbound
+    pub amount: f64,
+    pub currency: String,
+    /// Broker reference (order id), used by the engine to skip duplicates
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRow {
+    /// 1-based line in the file
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Result of parsing one statement file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerStatement {
+    pub broker: &'static str,
+    /// What kind of export was detected, e.g. `transactions` or `positions`
+    pub kind: &'static str,
+    pub positions: Vec<ImportedPosition>,
+    pub transactions: Vec<ImportedTransaction>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Rows of a CSV export; the delimiter (`,` or `;`) is detected from the
+/// first non-empty line and a UTF-8 BOM is ignored.
+pub fn csv_rows(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let header = text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default();
+    let delimiter = if header.matches(';').count() > header.matches(',').count() {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(|cell| cell.trim().to_string()).collect())
+                .map_err(|e| format!("Invalid CSV: {}", e))
+        })
+        .collect()
+}
+
+/// Decode a statement: UTF-8 when valid, otherwise ISO-8859-1 (with the
+/// Windows-1252 euro sign, which Excel writes as byte 0x80)
+fn decode(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| {
+        e.into_bytes()
+            .into_iter()
+            .map(|byte| match byte {
+                0x80 => '€',
+                byte => char::from(byte),
+            })
+            .collect()
+    })
+}
+
+pub fn read_text(path: &Path) -> Result<String, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(decode(bytes))
+}
+
+/// A supported statement format as listed to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFormat {
+    pub id: &'static str,
+    pub broker: &'static str,
+    pub label: &'static str,
+    /// File extensions the export comes with
+    pub extensions: &'static [&'static str],
+    pub description: &'static str,
+}
+
+/// A decoded statement file as handed to the adapters
+pub struct StatementFile {
+    pub text: String,
+    rows: Result<Vec<Vec<String>>, String>,
+}
+
+impl StatementFile {
+    pub fn new(text: String) -> Self {
+        let rows = if text.trim_start().starts_with('<') {
+            Err("Not a CSV file".to_string())
+        } else {
+            csv_rows(&text)
+        };
+        Self { text, rows }
+    }
+
+    /// CSV rows of the file, header included
+    pub fn rows(&self) -> Result<&[Vec<String>], String> {
+        self.rows.as_deref().map_err(Clone::clone)
+    }
+
+    /// First CSV row, empty when the file is not CSV
+    pub fn header(&self) -> &[String] {
+        self.rows
+            .as_ref()
+            .ok()
+            .and_then(|rows| rows.first())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// One statement format: recognizes its files, parses them and cleans up
+/// the result.
+pub trait BrokerImportAdapter: Sync {
+    fn format(&self) -> ImportFormat;
+
+    /// Whether `file` is in this format; must be cheap and never fail
+    fn detect(&self, file: &StatementFile) -> bool;
+
+    fn parse(&self, file: &StatementFile) -> Result<BrokerStatement, String>;
+
+    /// Clean-up applied after parsing; override for broker quirks
+    fn normalize(&self, statement: BrokerStatement) -> BrokerStatement {
+        normalize(statement)
+    }
+}
+
+/// Registered formats, in detection order
+const ADAPTERS: &[&dyn BrokerImportAdapter] = &[
+    &ibkr::FlexXmlAdapter,
+    &ibkr::FlexCsvAdapter,
+    &degiro::DegiroAdapter,
+    &scalable::ScalableAdapter,
+    &comdirect::ComdirectAdapter,
+];
+
+/// Formats `parse_file` understands
+pub fn formats() -> Vec<ImportFormat> {
+    ADAPTERS.iter().map(|adapter| adapter.format()).collect()
+}
+
+/// Shared clean-up: canonical ISIN and currency case, trimmed names,
+/// positions of the same instrument (e.g. from several accounts) merged, and
+/// transactions in date order.
+pub fn normalize(mut statement: BrokerStatement) -> BrokerStatement {
+    let mut merged: Vec<ImportedPosition> = vec![];
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for mut position in statement.positions {
+        position.isin = position.isin.trim().to_uppercase();
+        position.name = position.name.trim().to_string();
+        position.currency = position.currency.trim().to_uppercase();
+        let key = (position.isin.clone(), position.currency.clone());
+        match index.get(&key) {
+            Some(&at) => {
+                let existing = &mut merged[at];
+                let previous = existing.quantity;
+                existing.quantity += position.quantity;
+                // Weighted average buy price when both sides have one
+                existing.avg_buy_price = existing
+                    .avg_buy_price
+                    .zip(position.avg_buy_price)
+                    .map(|(a, b)| (a * previous + b * position.quantity) / existing.quantity);
+                existing.price = existing.price.or(position.price);
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push(position);
+            }
+        }
+    }
+    statement.positions = merged;
+
+    for transaction in &mut statement.transactions {
+        transaction.isin = transaction.isin.trim().to_uppercase();
+        transaction.name = transaction.name.trim().to_string();
+        transaction.currency = transaction.currency.trim().to_uppercase();
+    }
+    statement.transactions.sort_by(|a, b| a.date.cmp(&b.date));
+    statement
+}
+
+/// Detect the broker format of a statement file and parse it.
+pub fn parse_file(path: &Path) -> Result<BrokerStatement, String> {
+    let file = StatementFile::new(read_text(path)?);
+    let Some(adapter) = ADAPTERS.iter().find(|adapter| adapter.detect(&file)) else {
+        let supported: Vec<&str> = ADAPTERS
+            .iter()
+            .map(|adapter| adapter.format().label)
+            .collect();
+        return Err(format!(
+            "Unrecognized statement format (supported: {})",
+            supported.join(", ")
+        ));
+    };
+    adapter
+        .parse(&file)
+        .map(|statement| adapter.normalize(statement))
+}
+
+/// Parse a number in either `1,234.56` or `1.234,56` notation.
+pub fn parse_number(value: &str) -> Option<f64> {
+    let value: String = value
+        .trim()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'')
+        .collect();
+    if value.is_empty() {
+        return None;
+    }
+    let normalized = match (value.rfind(','), value.rfind('.')) {
+        // The later separator is the decimal one
+        (Some(comma), Some(dot)) if comma > dot => value.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => value.replace(',', ""),
+        // Several commas can only be thousands separators
+        (Some(_), None) if value.matches(',').count() > 1 => value.replace(',', ""),
+        (Some(_), None) => value.replace(',', "."),
+        _ => value,
+    };
+    normalized.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+/// Parse a number in German notation (`1.234,56`), where a dot is always a
+/// thousands separator. A trailing currency (`EUR`, `€`) is ignored.
+pub fn parse_german_number(value: &str) -> Option<f64> {
+    let value = value
+        .trim()
+        .trim_end_matches(|c: char| c.is_alphabetic() || c == '€' || c.is_whitespace());
+    if value.is_empty() {
+        return None;
+    }
+    let normalized: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '.')
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect();
+    normalized.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+/// Parse `DD-MM-YYYY`, `DD.MM.YYYY`, `DD/MM/YYYY` or `YYYY-MM-DD` into
+/// `YYYY-MM-DD`.
+pub fn parse_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    ["%d-%m-%Y", "%d.%m.%Y", "%d/%m/%Y", "%Y-%m-%d", "%Y%m%d"]
+        .iter()
+        .find_map(|format| chrono::NaiveDate::parse_from_str(value, format).ok())
+        .map(|date| date.to_string())
+}
+
+/// Whether `value` looks like an ISIN (format only; the checksum is left to
+/// the engine's resolver)
+pub fn is_isin(value: &str) -> bool {
+    value.len() == 12
+        && value
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && value[..2].chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Holdings implied by a transaction ledger: net quantity per ISIN with the
+/// average price of the remaining buys. Fully sold positions are dropped.
+pub fn positions_from_transactions(transactions: &[ImportedTransaction]) -> Vec<ImportedPosition> {
+    let mut by_isin: BTreeMap<&str, (ImportedPosition, f64)> = BTreeMap::new();
+    let mut ordered: Vec<&ImportedTransaction> = transactions.iter().collect();
+    ordered.sort_by(|a, b| a.date.cmp(&b.date));
+
+    for transaction in ordered {
+        let Some(quantity) = transaction.quantity else {
+            continue;
+        };
+        let (position, cost) = by_isin.entry(&transaction.isin).or_insert_with(|| {
+            (
+                ImportedPosition {
+                    isin: transaction.isin.clone(),
+                    name: transaction.name.clone(),
+                    quantity: 0.0,
+                    avg_buy_price: None,
+                    price: None,
+                    currency: transaction.currency.clone(),
+                },
+                0.0,
+            )
+        });
+        match transaction.transaction_type.as_str() {
+            "Buy" => {
+                position.quantity += quantity;
+                *cost += transaction.amount;
+            }
+            "Sell" if position.quantity > 0.0 => {
+                // Sales reduce cost at the average price
+                let sold = quantity.min(position.quantity);
+                *cost *= 1.0 - sold / position.quantity;
+                position.quantity -= sold;
+            }
+            _ => {}
+        }
+    }
+
+    by_isin
+        .into_values()
+        .filter(|(position, _)| position.quantity > 1e-9)
+        .map(|(mut position, cost)| {
+            position.avg_buy_price = Some(cost / position.quantity);
+            position
+        })
+        .collect()
+}
@@ -0,0 +1,196 @@
+//! Scalable Capital CSV exports
+//!
+//! The transactions export (`;`-separated, German number format) has one row
+//! per booking with `date`, `status`, `reference`, `description`, `type`,
+//! `isin`, `shares`, `price`, `amount`, `fee`, `tax` and `currency`. Only
+//! executed rows are imported; fees and taxes become separate transactions.
+//! Holdings are derived from the ledger.
+
+use super::{
+    is_isin, parse_date, parse_german_number, positions_from_transactions, BrokerImportAdapter,
+    BrokerStatement, ImportFormat, ImportedTransaction, SkippedRow, StatementFile,
+};
+
+pub const BROKER: &str = "scalable";
+
+const REQUIRED: &[&str] = &["date", "status", "type", "isin", "shares", "amount"];
+
+/// Column indices of the export, looked up by header name
+struct Columns<'a> {
+    header: &'a [String],
+}
+
+impl Columns<'_> {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.header
+            .iter()
+            .position(|cell| cell.eq_ignore_ascii_case(name))
+    }
+}
+
+fn cell(row: &[String], index: Option<usize>) -> &str {
+    index
+        .and_then(|index| row.get(index))
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+/// Whether the header row belongs to a Scalable Capital export
+pub fn detect(header: &[String]) -> bool {
+    let columns = Columns { header };
+    REQUIRED.iter().all(|name| columns.find(name).is_some())
+}
+
+/// Engine transaction type of a Scalable booking type
+fn transaction_type(kind: &str) -> Option<&'static str> {
+    match kind.to_lowercase().as_str() {
+        "buy" | "savings plan" => Some("Buy"),
+        "sell" => Some("Sell"),
+        "distribution" | "dividend" => Some("Dividend"),
+        "interest" => Some("Interest"),
+        "fee" | "taxes" => Some("Fee"),
+        "deposit" | "withdrawal" => Some("Transfer"),
+        _ => None,
+    }
+}
+
+pub struct ScalableAdapter;
+
+impl BrokerImportAdapter for ScalableAdapter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat {
+            id: "scalable_csv",
+            broker: BROKER,
+            label: "Scalable Capital CSV",
+            extensions: &["csv"],
+            description: "Transactions export",
+        }
+    }
+
+    fn detect(&self, file: &StatementFile) -> bool {
+        detect(file.header())
+    }
+
+    fn parse(&self, file: &StatementFile) -> Result<BrokerStatement, String> {
+        parse(file.rows()?)
+    }
+}
+
+/// Parse a Scalable Capital export; `rows` includes the header row.
+pub fn parse(rows: &[Vec<String>]) -> Result<BrokerStatement, String> {
+    let Some((header, body)) = rows.split_first() else {
+        return Err("The file is empty".to_string());
+    };
+    if !detect(header) {
+        return Err("Not a Scalable Capital transactions export".to_string());
+    }
+    let columns = Columns { header };
+    let date = columns.find("date");
+    let status = columns.find("status");
+    let reference = columns.find("reference");
+    let description = columns.find("description");
+    let kind = columns.find("type");
+    let isin = columns.find("isin");
+    let shares = columns.find("shares");
+    let amount = columns.find("amount");
+    let fee = columns.find("fee");
+    let tax = columns.find("tax");
+    let currency = columns.find("currency");
+
+    let mut transactions = vec![];
+    let mut skipped = vec![];
+    for (index, row) in body.iter().enumerate() {
+        let line = index + 2;
+        if row.iter().all(|cell| cell.is_empty()) {
+            continue;
+        }
+        let mut skip = |reason: &str| {
+            skipped.push(SkippedRow {
+                line,
+                reason: reason.to_string(),
+            })
+        };
+
+        if !cell(row, status).eq_ignore_ascii_case("executed") {
+            skip("Not executed");
+            continue;
+        }
+        let Some(transaction_type) = transaction_type(cell(row, kind)) else {
+            skip("Unsupported booking type");
+            continue;
+        };
+        let Some(row_date) = parse_date(cell(row, date)) else {
+            skip("Invalid date");
+            continue;
+        };
+        let row_isin = cell(row, isin).to_uppercase();
+        if matches!(transaction_type, "Buy" | "Sell" | "Dividend") && !is_isin(&row_isin) {
+            skip("Missing or invalid ISIN");
+            continue;
+        }
+        let quantity = parse_german_number(cell(row, shares)).map(f64::abs);
+        if matches!(transaction_type, "Buy" | "Sell") && !quantity.is_some_and(|q| q > 0.0) {
+            skip("Missing quantity");
+            continue;
+        }
+        let Some(row_amount) = parse_german_number(cell(row, amount)) else {
+            skip("Missing amount");
+            continue;
+        };
+
+        let row_currency = Some(cell(row, currency))
+            .filter(|currency| !currency.is_empty())
+            .unwrap_or("EUR")
+            .to_string();
+        let row_reference = Some(cell(row, reference).to_string()).filter(|id| !id.is_empty());
+        let name = cell(row, description).to_string();
+        let row_isin = if is_isin(&row_isin) {
+            row_isin
+        } else {
+            String::new()
+        };
+
+        if row_amount != 0.0 {
+            transactions.push(ImportedTransaction {
+                isin: row_isin.clone(),
+                name: name.clone(),
+                transaction_type: transaction_type.to_string(),
+                date: row_date.clone(),
+                quantity: quantity.filter(|_| matches!(transaction_type, "Buy" | "Sell")),
+                // Transfers keep their sign (negative = withdrawal)
+                amount: if transaction_type == "Transfer" {
+                    row_amount
+                } else {
+                    row_amount.abs()
+                },
+                currency: row_currency.clone(),
+                reference: row_reference.clone(),
+            });
+        }
+        for (column, suffix) in [(fee, "fee"), (tax, "tax")] {
+            let charge = parse_german_number(cell(row, column)).map_or(0.0, f64::abs);
+            if charge > 0.0 {
+                transactions.push(ImportedTransaction {
+                    isin: row_isin.clone(),
+                    name: name.clone(),
+                    transaction_type: "Fee".to_string(),
+                    date: row_date.clone(),
+                    quantity: None,
+                    amount: charge,
+                    currency: row_currency.clone(),
+                    reference: row_reference
+                        .as_ref()
+                        .map(|id| format!("{}-{}", id, suffix)),
+                });
+            }
+        }
+    }
+
+    Ok(BrokerStatement {
+        broker: BROKER,
+        kind: "transactions",
+        positions: positions_from_transactions(&transactions),
+        transactions,
+        skipped,
+    })
+}
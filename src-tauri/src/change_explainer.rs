@@ -0,0 +1,368 @@
+//! What-Changed Explanation
+//!
+//! Attributes the difference between two pipeline snapshots to causes, so the
+//! UI can answer "why did my numbers change overnight?":
+//!
+//! - `priceMove`: quantity held in the earlier snapshot times the price
+//!   change, in the instrument currency converted at the earlier rate
+//! - `fx`: the same quantity revalued by the change in the implied exchange
+//!   rate (value / (quantity * price))
+//! - `trade`: quantity bought or sold between the snapshots
+//! - `corporateAction`: a quantity change matched by an inverse price change
+//!   (splits, reverse splits); value-neutral apart from rounding
+//! - `newDecomposition`: ETFs whose decomposition source, status or size
+//!   changed; moves look-through exposure without changing total value
+//! - `dataCorrection`: look-through holdings whose sector or country changed
+//!
+//! Whatever is left of the total value change is reported as unexplained.
+
+use crate::benchmarks::BenchmarkReturn;
+use crate::pipeline_snapshots::{
+    PipelineSnapshot, SnapshotDecomposition, SnapshotHolding, SnapshotPosition,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Tolerance when matching a quantity ratio against an inverse price ratio
+const SPLIT_TOLERANCE: f64 = 0.02;
+
+/// Causes and details below this amount are dropped as noise
+const MIN_AMOUNT: f64 = 0.005;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeItem {
+    pub key: String,
+    pub name: String,
+    pub amount: f64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeCause {
+    /// `priceMove`, `fx`, `trade`, `corporateAction`, `newDecomposition` or
+    /// `dataCorrection`
+    pub kind: &'static str,
+    /// Effect on total value; look-through-only causes report the exposure moved
+    pub amount: f64,
+    pub affects_total: bool,
+    /// Largest contributors first
+    pub items: Vec<ChangeItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationShift {
+    pub dimension: &'static str,
+    pub bucket: String,
+    pub before_percent: f64,
+    pub after_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeExplanation {
+    pub before_snapshot: String,
+    pub after_snapshot: String,
+    pub total_value_before: f64,
+    pub total_value_after: f64,
+    pub causes: Vec<ChangeCause>,
+    /// Sector and country weights that moved by at least 0.1 percentage points
+    pub allocation_shifts: Vec<AllocationShift>,
+    pub unexplained: f64,
+    /// Custom benchmark return over the same period (set by the command)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<BenchmarkReturn>,
+}
+
+#[derive(Default)]
+struct Causes {
+    by_kind: BTreeMap<&'static str, Vec<ChangeItem>>,
+}
+
+impl Causes {
+    fn add(&mut self, kind: &'static str, item: ChangeItem) {
+        if item.amount.abs() >= MIN_AMOUNT {
+            self.by_kind.entry(kind).or_default().push(item);
+        }
+    }
+}
+
+/// Implied exchange rate from portfolio to instrument currency
+fn fx_rate(position: &SnapshotPosition) -> Option<f64> {
+    let local_value = position.quantity * position.price;
+    (local_value.abs() > f64::EPSILON).then(|| position.value / local_value)
+}
+
+fn is_split(before: &SnapshotPosition, after: &SnapshotPosition) -> bool {
+    if before.quantity <= 0.0 || after.price <= 0.0 {
+        return false;
+    }
+    let quantity_ratio = after.quantity / before.quantity;
+    let price_ratio = before.price / after.price;
+    (quantity_ratio - 1.0).abs() > SPLIT_TOLERANCE
+        && (quantity_ratio / price_ratio - 1.0).abs() <= SPLIT_TOLERANCE
+}
+
+fn attribute_positions(before: &PipelineSnapshot, after: &PipelineSnapshot, causes: &mut Causes) {
+    let empty = SnapshotPosition {
+        name: String::new(),
+        quantity: 0.0,
+        price: 0.0,
+        currency: String::new(),
+        value: 0.0,
+    };
+
+    let mut isins: Vec<&String> = before
+        .positions
+        .keys()
+        .chain(after.positions.keys())
+        .collect();
+    isins.sort();
+    isins.dedup();
+
+    for isin in isins {
+        let old = before.positions.get(isin).unwrap_or(&empty);
+        let new = after.positions.get(isin).unwrap_or(&empty);
+        let name = if new.name.is_empty() {
+            &old.name
+        } else {
+            &new.name
+        };
+        let item = |amount: f64, detail: String| ChangeItem {
+            key: isin.clone(),
+            name: name.clone(),
+            amount,
+            detail,
+        };
+
+        if is_split(old, new) {
+            causes.add(
+                "corporateAction",
+                item(
+                    new.value - old.value,
+                    format!(
+                        "Quantity {} -> {} at an inverse price",
+                        old.quantity, new.quantity
+                    ),
+                ),
+            );
+            continue;
+        }
+
+        let held = old.quantity;
+        match (fx_rate(old), fx_rate(new)) {
+            (Some(old_rate), Some(new_rate)) => {
+                causes.add(
+                    "priceMove",
+                    item(
+                        held * (new.price - old.price) * old_rate,
+                        format!("{:.2} -> {:.2} {}", old.price, new.price, new.currency),
+                    ),
+                );
+                causes.add(
+                    "fx",
+                    item(
+                        held * new.price * (new_rate - old_rate),
+                        format!("{} rate {:.4} -> {:.4}", new.currency, old_rate, new_rate),
+                    ),
+                );
+                let traded = new.quantity - old.quantity;
+                causes.add(
+                    "trade",
+                    item(traded * new.price * new_rate, format!("{:+} units", traded)),
+                );
+            }
+            // Opened or closed entirely between the snapshots
+            _ => causes.add(
+                "trade",
+                item(
+                    new.value - old.value,
+                    format!("{} -> {} units", old.quantity, new.quantity),
+                ),
+            ),
+        }
+    }
+}
+
+fn attribute_look_through(
+    before: &PipelineSnapshot,
+    after: &PipelineSnapshot,
+    causes: &mut Causes,
+) {
+    let mut etfs: Vec<&String> = before
+        .decompositions
+        .keys()
+        .chain(after.decompositions.keys())
+        .collect();
+    etfs.sort();
+    etfs.dedup();
+
+    for etf in etfs {
+        let old = before.decompositions.get(etf);
+        let new = after.decompositions.get(etf);
+        if old == new {
+            continue;
+        }
+        let describe = |entry: Option<&SnapshotDecomposition>| {
+            entry
+                .map(|entry| {
+                    format!(
+                        "{} via {} ({} holdings)",
+                        entry.status.as_deref().unwrap_or("unknown"),
+                        entry.source.as_deref().unwrap_or("unknown"),
+                        entry.holdings_count.unwrap_or(0)
+                    )
+                })
+                .unwrap_or_else(|| "not analyzed".to_string())
+        };
+        let name = after
+            .positions
+            .get(etf)
+            .or_else(|| before.positions.get(etf))
+            .map(|position| position.name.clone())
+            .unwrap_or_else(|| etf.clone());
+        // Exposure re-distributed is the fund's own value
+        let amount = after
+            .positions
+            .get(etf)
+            .or_else(|| before.positions.get(etf))
+            .map(|position| position.value)
+            .unwrap_or(0.0);
+
+        causes.add(
+            "newDecomposition",
+            ChangeItem {
+                key: etf.clone(),
+                name,
+                amount,
+                detail: format!("{} -> {}", describe(old), describe(new)),
+            },
+        );
+    }
+
+    for (key, new) in &after.holdings {
+        let Some(old) = before.holdings.get(key) else {
+            continue;
+        };
+        let mut changes = vec![];
+        if old.sector != new.sector {
+            changes.push(format!(
+                "sector {} -> {}",
+                old.sector.as_deref().unwrap_or("unknown"),
+                new.sector.as_deref().unwrap_or("unknown")
+            ));
+        }
+        if old.geography != new.geography {
+            changes.push(format!(
+                "country {} -> {}",
+                old.geography.as_deref().unwrap_or("unknown"),
+                new.geography.as_deref().unwrap_or("unknown")
+            ));
+        }
+        if !changes.is_empty() {
+            causes.add(
+                "dataCorrection",
+                ChangeItem {
+                    key: key.clone(),
+                    name: new.name.clone(),
+                    amount: new.value,
+                    detail: changes.join(", "),
+                },
+            );
+        }
+    }
+}
+
+fn weights(
+    snapshot: &PipelineSnapshot,
+    bucket: impl Fn(&SnapshotHolding) -> Option<&String>,
+) -> BTreeMap<String, f64> {
+    let total: f64 = snapshot
+        .holdings
+        .values()
+        .map(|holding| holding.value)
+        .sum();
+    let mut weights = BTreeMap::new();
+    if total <= 0.0 {
+        return weights;
+    }
+    for holding in snapshot.holdings.values() {
+        let key = bucket(holding)
+            .cloned()
+            .unwrap_or_else(|| "Unknown".to_string());
+        *weights.entry(key).or_insert(0.0) += holding.value / total * 100.0;
+    }
+    weights
+}
+
+fn allocation_shifts(before: &PipelineSnapshot, after: &PipelineSnapshot) -> Vec<AllocationShift> {
+    let mut shifts = vec![];
+    let dimensions: [(&'static str, fn(&SnapshotHolding) -> Option<&String>); 2] = [
+        ("sector", |holding| holding.sector.as_ref()),
+        ("country", |holding| holding.geography.as_ref()),
+    ];
+    for (dimension, bucket) in dimensions {
+        let old = weights(before, bucket);
+        let new = weights(after, bucket);
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let before_percent = old.get(key).copied().unwrap_or(0.0);
+            let after_percent = new.get(key).copied().unwrap_or(0.0);
+            if (after_percent - before_percent).abs() >= 0.1 {
+                shifts.push(AllocationShift {
+                    dimension,
+                    bucket: key.clone(),
+                    before_percent,
+                    after_percent,
+                });
+            }
+        }
+    }
+    shifts.sort_by(|a, b| {
+        let delta = |shift: &AllocationShift| (shift.after_percent - shift.before_percent).abs();
+        delta(b).total_cmp(&delta(a))
+    });
+    shifts
+}
+
+/// Explain the change from `before` to `after`.
+pub fn explain(before: &PipelineSnapshot, after: &PipelineSnapshot) -> ChangeExplanation {
+    let mut causes = Causes::default();
+    attribute_positions(before, after, &mut causes);
+    attribute_look_through(before, after, &mut causes);
+
+    let causes: Vec<ChangeCause> = causes
+        .by_kind
+        .into_iter()
+        .map(|(kind, mut items)| {
+            items.sort_by(|a, b| b.amount.abs().total_cmp(&a.amount.abs()));
+            ChangeCause {
+                kind,
+                amount: items.iter().map(|item| item.amount).sum(),
+                affects_total: !matches!(kind, "newDecomposition" | "dataCorrection"),
+                items,
+            }
+        })
+        .collect();
+
+    let explained: f64 = causes
+        .iter()
+        .filter(|cause| cause.affects_total)
+        .map(|cause| cause.amount)
+        .sum();
+
+    ChangeExplanation {
+        before_snapshot: before.id.clone(),
+        after_snapshot: after.id.clone(),
+        total_value_before: before.total_value,
+        total_value_after: after.total_value,
+        allocation_shifts: allocation_shifts(before, after),
+        unexplained: after.total_value - before.total_value - explained,
+        causes,
+        benchmark: None,
+    }
+}
@@ -0,0 +1,207 @@
+//! Closed Position History
+//!
+//! The engine only reports open positions, so a fully sold position used to
+//! vanish together with its history. After every sync the shell compares the
+//! open positions with the previous snapshot (`positions/<id>_open.json`);
+//! positions that disappeared are archived in `closed_positions.json` with
+//! their transactions, notes and realized P&L, and announced through a
+//! `positions-closed` event.
+//!
+//! Realized P&L is derived from the transaction ledger: sale proceeds plus
+//! dividends and interest, minus purchase cost and fees. Without ledger
+//! entries the P&L is left unknown rather than guessed.
+
+use crate::python_engine::PythonEngine;
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Archive file name inside the app data dir
+pub(crate) const STORE_FILE: &str = "closed_positions.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenPosition {
+    isin: String,
+    name: String,
+    #[serde(default)]
+    notes: String,
+    total_cost: f64,
+    first_seen: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosedPosition {
+    pub portfolio_id: u32,
+    pub isin: String,
+    pub name: String,
+    /// When the shell first saw the position open
+    pub first_seen: String,
+    pub closed_at: String,
+    pub total_invested: f64,
+    pub total_proceeds: f64,
+    /// Dividends and interest received while held
+    pub income: f64,
+    pub fees: f64,
+    pub realized_pnl: Option<f64>,
+    pub realized_pnl_percent: Option<f64>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub transactions: Vec<Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ClosedStore {
+    #[serde(default)]
+    positions: Vec<ClosedPosition>,
+}
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORE_FILE)
+}
+
+fn snapshot_path(data_dir: &Path, portfolio_id: u32) -> PathBuf {
+    data_dir
+        .join("positions")
+        .join(format!("{}_open.json", portfolio_id))
+}
+
+/// Drop the open-position snapshot of a deleted portfolio.
+pub fn forget_snapshot(data_dir: &Path, portfolio_id: u32) {
+    let _ = std::fs::remove_file(snapshot_path(data_dir, portfolio_id));
+}
+
+/// Archived positions of `portfolio_id` closed within `[from, to]`
+/// (inclusive `YYYY-MM-DD` bounds), most recent first.
+pub fn list(
+    data_dir: &Path,
+    portfolio_id: u32,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<ClosedPosition>, String> {
+    let archive: ClosedStore = store::read_json(&store_path(data_dir))?.unwrap_or_default();
+    let mut positions: Vec<ClosedPosition> = archive
+        .positions
+        .into_iter()
+        .filter(|position| position.portfolio_id == portfolio_id)
+        .filter(|position| {
+            let day = position.closed_at.get(..10).unwrap_or_default();
+            from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+        })
+        .collect();
+    positions.sort_by(|a, b| b.closed_at.cmp(&a.closed_at));
+    Ok(positions)
+}
+
+fn close(portfolio_id: u32, open: OpenPosition, transactions: Vec<Value>) -> ClosedPosition {
+    let mut invested = 0.0;
+    let mut proceeds = 0.0;
+    let mut income = 0.0;
+    let mut fees = 0.0;
+
+    for transaction in &transactions {
+        let amount = transaction["amount"].as_f64().unwrap_or(0.0).abs();
+        match transaction["type"].as_str() {
+            Some("Buy") => invested += amount,
+            Some("Sell") => proceeds += amount,
+            Some("Dividend") | Some("Interest") => income += amount,
+            Some("Fee") => fees += amount,
+            _ => {}
+        }
+    }
+
+    let has_ledger = invested > 0.0;
+    let realized_pnl = has_ledger.then(|| proceeds + income - invested - fees);
+
+    ClosedPosition {
+        portfolio_id,
+        isin: open.isin,
+        name: open.name,
+        first_seen: open.first_seen,
+        closed_at: chrono::Utc::now().to_rfc3339(),
+        total_invested: if has_ledger { invested } else { open.total_cost },
+        total_proceeds: proceeds,
+        income,
+        fees,
+        realized_pnl,
+        realized_pnl_percent: realized_pnl.map(|pnl| pnl / invested * 100.0),
+        notes: open.notes,
+        transactions,
+    }
+}
+
+/// Compare the engine's open positions with the last snapshot and archive
+/// every position that has been closed since.
+pub async fn reconcile(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    portfolio_id: u32,
+) -> Result<Vec<ClosedPosition>, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let snapshot_file = snapshot_path(&data_dir, portfolio_id);
+    let previous: Option<HashMap<String, OpenPosition>> = store::read_json(&snapshot_file)?;
+
+    let data = engine
+        .request("get_positions", json!({ "portfolioId": portfolio_id }))
+        .await?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut current: HashMap<String, OpenPosition> = HashMap::new();
+    for position in data["positions"].as_array().into_iter().flatten() {
+        let isin = position["isin"].as_str().unwrap_or_default();
+        let quantity = position["quantity"].as_f64().unwrap_or(0.0);
+        if isin.is_empty() || quantity <= 0.0 {
+            continue;
+        }
+        let first_seen = previous
+            .as_ref()
+            .and_then(|previous| previous.get(isin))
+            .map(|open| open.first_seen.clone())
+            .unwrap_or_else(|| now.clone());
+        current.insert(
+            isin.to_string(),
+            OpenPosition {
+                isin: isin.to_string(),
+                name: position["name"].as_str().unwrap_or(isin).to_string(),
+                notes: position["notes"].as_str().unwrap_or_default().to_string(),
+                total_cost: position["totalCost"].as_f64().unwrap_or(0.0),
+                first_seen,
+            },
+        );
+    }
+
+    let mut closed = vec![];
+    for (isin, open) in previous.into_iter().flatten() {
+        if current.contains_key(&isin) {
+            continue;
+        }
+        let payload = json!({ "portfolioId": portfolio_id, "isin": isin });
+        let transactions = engine
+            .request("get_transactions", payload)
+            .await
+            .map(|data| data["transactions"].as_array().cloned().unwrap_or_default())
+            .unwrap_or_else(|e| {
+                tracing::warn!("No transactions for closed position {}: {}", isin, e);
+                vec![]
+            });
+        closed.push(close(portfolio_id, open, transactions));
+    }
+
+    if !closed.is_empty() {
+        let path = store_path(&data_dir);
+        let mut archive: ClosedStore = store::read_json(&path)?.unwrap_or_default();
+        archive.positions.extend(closed.iter().cloned());
+        store::write_json(&path, &archive)?;
+        let _ = app_handle.emit(
+            "positions-closed",
+            json!({ "portfolioId": portfolio_id, "positions": closed }),
+        );
+    }
+    store::write_json(&snapshot_file, &current)?;
+
+    Ok(closed)
+}
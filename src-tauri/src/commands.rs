@@ -1,1248 +0,0 @@
-//! Tauri Commands for IPC Bridge
-//!
-//! These commands are invoked from the React frontend via `invoke()`.
-//! Commands communicate with the Python engine via stdin/stdout IPC.
-
-use crate::python_engine::PythonEngine;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager, State};
-
-// =============================================================================
-// Input Validation Helpers
-// =============================================================================
-
-/// Validate ISIN format and Luhn checksum.
-///
-/// ISIN format: 2 letter country code + 9 alphanumeric NSIN + 1 check digit
-///
-/// # Arguments
-/// * `isin` - The ISIN string to validate
-///
-/// # Returns
-/// * `Ok(String)` - Normalized uppercase ISIN if valid
-/// * `Err(String)` - User-friendly error message if invalid
-///
-/// # Examples
-/// ```
-/// assert!(validate_isin("US0378331005").is_ok());  // Apple Inc
-/// assert!(validate_isin("DE0007164600").is_ok());  // SAP SE
-/// assert!(validate_isin("invalid").is_err());
-/// ```
-fn validate_isin(isin: &str) -> Result<String, String> {
-    let isin = isin.trim().to_uppercase();
-
-    // Length check (must be exactly 12 characters)
-    if isin.len() != 12 {
-        return Err(format!(
-            "Invalid ISIN: must be exactly 12 characters (got {})",
-            isin.len()
-        ));
-    }
-
-    // Country code (first 2 chars must be uppercase ASCII letters)
-    let country_code = &isin[..2];
-    if !country_code.chars().all(|c| c.is_ascii_uppercase()) {
-        return Err("Invalid ISIN: first 2 characters must be letters (country code)".to_string());
-    }
-
-    // NSIN (chars 3-11, 9 chars, must be alphanumeric)
-    let nsin = &isin[2..11];
-    if !nsin.chars().all(|c| c.is_ascii_alphanumeric()) {
-        return Err(
-            "Invalid ISIN: characters 3-11 must be alphanumeric (security identifier)".to_string(),
-        );
-    }
-
-    // Check digit (last char must be a digit)
-    let check_digit = isin.chars().nth(11).unwrap();
-    if !check_digit.is_ascii_digit() {
-        return Err("Invalid ISIN: last character must be a digit (check digit)".to_string());
-    }
-
-    // Luhn checksum validation
-    if !validate_isin_luhn(&isin) {
-        return Err("Invalid ISIN: checksum validation failed".to_string());
-    }
-
-    Ok(isin)
-}
-
-/// Validate ISIN using Luhn algorithm.
-///
-/// Algorithm:
-/// 1. Convert letters to numbers (A=10, B=11, ..., Z=35)
-/// 2. Apply Luhn algorithm to resulting digit string
-/// 3. Valid if total mod 10 == 0
-fn validate_isin_luhn(isin: &str) -> bool {
-    // Convert ISIN to digit string (letters become 2-digit numbers)
-    let mut digits = String::new();
-    for c in isin.chars() {
-        if c.is_ascii_digit() {
-            digits.push(c);
-        } else if c.is_ascii_uppercase() {
-            // A=10, B=11, ..., Z=35
-            let value = (c as u32) - ('A' as u32) + 10;
-            digits.push_str(&value.to_string());
-        } else {
-            return false;
-        }
-    }
-
-    // Luhn algorithm (process from right to left)
-    let mut total: u32 = 0;
-    for (i, c) in digits.chars().rev().enumerate() {
-        let mut n = c.to_digit(10).unwrap_or(0);
-        // Double every second digit from right (0-indexed, so i % 2 == 1)
-        if i % 2 == 1 {
-            n *= 2;
-            if n > 9 {
-                n -= 9;
-            }
-        }
-        total += n;
-    }
-
-    total % 10 == 0
-}
-
-/// Allowed file extensions for holdings uploads.
-const ALLOWED_EXTENSIONS: &[&str] = &["csv", "xlsx", "xls", "json", "pdf"];
-
-/// Validate file path for holdings upload.
-///
-/// Checks:
-/// - File exists
-/// - Extension is allowed (csv, xlsx, xls, json, pdf)
-/// - Path is canonicalized (prevents path traversal attacks with `..`)
-///
-/// # Arguments
-/// * `path` - The file path to validate
-///
-/// # Returns
-/// * `Ok(String)` - Canonicalized absolute path if valid
-/// * `Err(String)` - User-friendly error message if invalid
-fn validate_file_path(path: &str) -> Result<String, String> {
-    use std::path::PathBuf;
-
-    let path = path.trim();
-    if path.is_empty() {
-        return Err("File path cannot be empty".to_string());
-    }
-
-    let path_buf = PathBuf::from(path);
-
-    // Check file exists
-    if !path_buf.exists() {
-        return Err(format!("File not found: {}", path));
-    }
-
-    // Check it's a file, not a directory
-    if !path_buf.is_file() {
-        return Err(format!("Path is not a file: {}", path));
-    }
-
-    // Validate extension
-    let extension = path_buf
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| ext.to_lowercase());
-
-    match extension {
-        Some(ref ext) if ALLOWED_EXTENSIONS.contains(&ext.as_str()) => {}
-        Some(ext) => {
-            return Err(format!(
-                "Unsupported file extension: .{}. Allowed: {}",
-                ext,
-                ALLOWED_EXTENSIONS.join(", ")
-            ));
-        }
-        None => {
-            return Err("File must have an extension (csv, xlsx, xls, json, or pdf)".to_string());
-        }
-    }
-
-    // Canonicalize path to prevent path traversal (resolves `..`, symlinks)
-    // This is defense-in-depth against directory traversal attacks
-    let canonical = path_buf
-        .canonicalize()
-        .map_err(|e| format!("Invalid file path: {}", e))?;
-
-    canonical
-        .to_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| "File path contains invalid characters".to_string())
-}
-
-// =============================================================================
-// Response Types (match TypeScript types in src/types/index.ts)
-// =============================================================================
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct EngineHealth {
-    pub version: String,
-    pub memory_usage_mb: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uptime_seconds: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub db_path: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Holding {
-    pub isin: String,
-    pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ticker: Option<String>,
-    pub value: f64,
-    pub weight: f64,
-    pub pnl: f64,
-    pub pnl_percentage: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub quantity: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub asset_class: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Allocations {
-    pub sector: std::collections::HashMap<String, f64>,
-    pub region: std::collections::HashMap<String, f64>,
-    #[serde(default)]
-    pub asset_class: std::collections::HashMap<String, f64>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct HistoryPoint {
-    pub date: String,
-    pub value: f64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DashboardData {
-    pub total_value: f64,
-    pub total_gain: f64,
-    pub gain_percentage: f64,
-    pub day_change: f64,
-    pub day_change_percent: f64,
-    #[serde(default)]
-    pub history: Vec<HistoryPoint>,
-    pub allocations: Allocations,
-    pub top_holdings: Vec<Holding>,
-    pub last_updated: Option<String>,
-    #[serde(default)]
-    pub is_empty: bool,
-    #[serde(default)]
-    pub position_count: u32,
-}
-
-// Note: SyncResult was replaced by PortfolioSyncResult
-
-// =============================================================================
-// Trade Republic Auth Types
-// =============================================================================
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AuthStatus {
-    pub auth_state: String,
-    pub has_stored_credentials: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_error: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct SessionCheck {
-    pub has_session: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub phone_number: Option<String>,
-    pub prompt: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AuthResponse {
-    pub auth_state: String,
-    pub message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub countdown: Option<u32>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct StoredCredentialsInfo {
-    pub has_credentials: bool,
-    pub masked_phone: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LogoutResponse {
-    pub auth_state: String,
-    pub message: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PortfolioSyncResult {
-    pub synced_positions: u32,
-    pub new_positions: u32,
-    pub updated_positions: u32,
-    pub total_value: f64,
-    pub duration_ms: u32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct SyncProgress {
-    pub status: String,
-    pub progress: u8,
-    pub message: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ManualHoldingDraft {
-    pub isin: String,
-    pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ticker: Option<String>,
-    pub weight: f64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Position {
-    pub isin: String,
-    pub name: String,
-    #[serde(default)]
-    pub ticker: String,
-    pub instrument_type: String,
-    pub quantity: f64,
-    pub avg_buy_price: f64,
-    pub current_price: f64,
-    pub current_value: f64,
-    pub total_cost: f64,
-    pub pnl_eur: f64,
-    pub pnl_percent: f64,
-    pub weight: f64,
-    pub currency: String,
-    #[serde(default)]
-    pub notes: String,
-    pub last_updated: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PositionsResponse {
-    pub positions: Vec<Position>,
-    pub total_value: f64,
-    pub total_cost: f64,
-    pub total_pnl: f64,
-    pub total_pnl_percent: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_sync_time: Option<String>,
-}
-
-// =============================================================================
-// Commands
-// =============================================================================
-
-/// Get engine health status
-#[tauri::command]
-pub async fn get_engine_health(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<EngineHealth, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("get_health", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let health = EngineHealth {
-                        version: data["version"].as_str().unwrap_or("0.0.0").to_string(),
-                        memory_usage_mb: data["memoryUsageMb"].as_f64().unwrap_or(0.0),
-                        uptime_seconds: data["uptimeSeconds"].as_f64(),
-                        db_path: data["dbPath"].as_str().map(|s| s.to_string()),
-                    };
-                    return Ok(health);
-                }
-                return Err("No data in engine health response".to_string());
-            }
-
-            Err(response
-                .error
-                .map(|e| e.message)
-                .unwrap_or_else(|| "Engine health check failed".to_string()))
-        }
-        Err(e) => Err(format!("Failed to get engine health: {}", e)),
-    }
-}
-
-/// Get dashboard data for a portfolio
-#[tauri::command]
-pub async fn get_dashboard_data(
-    portfolio_id: u32,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<DashboardData, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine
-        .send_command("get_dashboard_data", json!({"portfolioId": portfolio_id}))
-        .await
-    {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let dashboard: Result<DashboardData, _> = serde_json::from_value(data);
-                    match dashboard {
-                        Ok(d) => return Ok(d),
-                        Err(e) => {
-                            eprintln!("Failed to parse dashboard data: {}", e);
-                            return Err(format!("Failed to parse dashboard data: {}", e));
-                        }
-                    }
-                }
-                return Err("No data in dashboard response".to_string());
-            }
-
-            Err(response
-                .error
-                .map(|e| e.message)
-                .unwrap_or_else(|| "Failed to load dashboard data".to_string()))
-        }
-        Err(e) => Err(format!("Failed to get dashboard data: {}", e)),
-    }
-}
-
-/// Get all positions for a portfolio (full data for the table)
-#[tauri::command]
-pub async fn get_positions(
-    portfolio_id: u32,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<PositionsResponse, String> {
-    if !engine.is_connected().await {
-        // Return empty response if engine not connected
-        return Ok(PositionsResponse {
-            positions: vec![],
-            total_value: 0.0,
-            total_cost: 0.0,
-            total_pnl: 0.0,
-            total_pnl_percent: 0.0,
-            last_sync_time: None,
-        });
-    }
-
-    match engine
-        .send_command("get_positions", json!({"portfolioId": portfolio_id}))
-        .await
-    {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let positions_response: Result<PositionsResponse, _> =
-                        serde_json::from_value(data);
-                    match positions_response {
-                        Ok(p) => return Ok(p),
-                        Err(e) => {
-                            eprintln!("Failed to parse positions data: {}", e);
-                            return Err(format!("Failed to parse positions: {}", e));
-                        }
-                    }
-                }
-            }
-            if let Some(err) = response.error {
-                return Err(err.message);
-            }
-            Err("Unknown error getting positions".to_string())
-        }
-        Err(e) => Err(format!("Failed to get positions: {}", e)),
-    }
-}
-
-/// Trigger portfolio sync with real Trade Republic data
-#[tauri::command]
-pub async fn sync_portfolio(
-    app_handle: AppHandle,
-    portfolio_id: u32,
-    force: bool,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<PortfolioSyncResult, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let payload = json!({
-        "portfolioId": portfolio_id,
-        "force": force
-    });
-
-    // Events from Python (sync_progress) are handled in lib.rs stdout loop
-    // and emitted as Tauri events automatically
-
-    match engine.send_command("sync_portfolio", payload).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let sync_result: Result<PortfolioSyncResult, _> = serde_json::from_value(data);
-                    match sync_result {
-                        Ok(result) => {
-                            // Emit portfolio-updated event
-                            #[derive(Clone, Serialize)]
-                            #[serde(rename_all = "camelCase")]
-                            struct PortfolioUpdated {
-                                timestamp: String,
-                                portfolio_id: u32,
-                            }
-
-                            let _ = app_handle.emit(
-                                "portfolio-updated",
-                                PortfolioUpdated {
-                                    timestamp: chrono::Utc::now().to_rfc3339(),
-                                    portfolio_id,
-                                },
-                            );
-
-                            Ok(result)
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to parse sync result: {}", e);
-                            Err("Failed to parse sync result".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in sync response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Sync failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to sync portfolio: {}", e)),
-    }
-}
-
-/// Get current Trade Republic authentication status
-#[tauri::command]
-pub async fn tr_get_auth_status(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<AuthStatus, String> {
-    if !engine.is_connected().await {
-        return Ok(AuthStatus {
-            auth_state: "idle".to_string(),
-            has_stored_credentials: false,
-            last_error: Some("Python engine not connected".to_string()),
-        });
-    }
-
-    match engine.send_command("tr_get_auth_status", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let auth_status: Result<AuthStatus, _> = serde_json::from_value(data);
-                    match auth_status {
-                        Ok(status) => Ok(status),
-                        Err(e) => {
-                            eprintln!("Failed to parse auth status: {}", e);
-                            Err("Failed to parse auth status".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in auth status response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Auth status check failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to get auth status: {}", e)),
-    }
-}
-
-/// Check for saved Trade Republic session
-#[tauri::command]
-pub async fn tr_check_saved_session(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<SessionCheck, String> {
-    if !engine.is_connected().await {
-        return Ok(SessionCheck {
-            has_session: false,
-            phone_number: None,
-            prompt: "login_required".to_string(),
-        });
-    }
-
-    match engine
-        .send_command("tr_check_saved_session", json!({}))
-        .await
-    {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let session_check: Result<SessionCheck, _> = serde_json::from_value(data);
-                    match session_check {
-                        Ok(check) => Ok(check),
-                        Err(e) => {
-                            eprintln!("Failed to parse session check: {}", e);
-                            Err("Failed to parse session check".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in session check response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Session check failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to check session: {}", e)),
-    }
-}
-
-/// Check whether stored Trade Republic credentials are available.
-#[tauri::command]
-pub async fn tr_get_stored_credentials(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<StoredCredentialsInfo, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine
-        .send_command("tr_get_stored_credentials", json!({}))
-        .await
-    {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let credentials: Result<StoredCredentialsInfo, _> = serde_json::from_value(data);
-                    match credentials {
-                        Ok(info) => Ok(info),
-                        Err(e) => {
-                            eprintln!("Failed to parse stored credentials response: {}", e);
-                            Err("Failed to parse stored credentials response".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in stored credentials response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Stored credentials check failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to check stored credentials: {}", e)),
-    }
-}
-
-/// Attempt to restore a saved Trade Republic session
-#[tauri::command]
-pub async fn tr_restore_session(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<AuthResponse, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("tr_restore_session", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let auth_response: Result<AuthResponse, _> = serde_json::from_value(data);
-                    match auth_response {
-                        Ok(resp) => Ok(resp),
-                        Err(e) => {
-                            eprintln!("Failed to parse restore response: {}", e);
-                            Err("Failed to parse restore response".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in restore response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Session restore failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to restore session: {}", e)),
-    }
-}
-
-/// Start Trade Republic login process
-#[tauri::command]
-pub async fn tr_login(
-    phone: Option<String>,
-    pin: Option<String>,
-    remember: Option<bool>,
-    use_stored_credentials: Option<bool>,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<AuthResponse, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let remember = remember.unwrap_or(true);
-    let use_stored_credentials = use_stored_credentials.unwrap_or(false);
-
-    let payload = if use_stored_credentials {
-        json!({
-            "useStoredCredentials": true,
-            "remember": remember
-        })
-    } else {
-        let phone = phone
-            .filter(|value| !value.trim().is_empty())
-            .ok_or_else(|| "Phone number is required".to_string())?;
-        let pin = pin
-            .filter(|value| !value.trim().is_empty())
-            .ok_or_else(|| "PIN is required".to_string())?;
-
-        json!({
-            "phone": phone,
-            "pin": pin,
-            "remember": remember
-        })
-    };
-
-    match engine.send_command("tr_login", payload).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let auth_response: Result<AuthResponse, _> = serde_json::from_value(data);
-                    match auth_response {
-                        Ok(resp) => Ok(resp),
-                        Err(e) => {
-                            eprintln!("Failed to parse auth response: {}", e);
-                            Err("Failed to parse auth response".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in auth response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Login failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to login: {}", e)),
-    }
-}
-
-/// Submit 2FA code for Trade Republic
-#[tauri::command]
-pub async fn tr_submit_2fa(
-    code: String,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<AuthResponse, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let payload = json!({ "code": code });
-
-    match engine.send_command("tr_submit_2fa", payload).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let auth_response: Result<AuthResponse, _> = serde_json::from_value(data);
-                    match auth_response {
-                        Ok(resp) => Ok(resp),
-                        Err(e) => {
-                            eprintln!("Failed to parse 2FA response: {}", e);
-                            Err("Failed to parse 2FA response".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in 2FA response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "2FA verification failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to submit 2FA: {}", e)),
-    }
-}
-
-/// Logout from Trade Republic
-#[tauri::command]
-pub async fn tr_logout(engine: State<'_, Arc<PythonEngine>>) -> Result<LogoutResponse, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("tr_logout", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let logout_response: Result<LogoutResponse, _> = serde_json::from_value(data);
-                    match logout_response {
-                        Ok(resp) => Ok(resp),
-                        Err(e) => {
-                            eprintln!("Failed to parse logout response: {}", e);
-                            Err("Failed to parse logout response".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in logout response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Logout failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to logout: {}", e)),
-    }
-}
-
-/// Log a frontend event to the backend telemetry store.
-#[tauri::command]
-pub async fn log_event(
-    level: String,
-    message: String,
-    context: serde_json::Value,
-    component: String,
-    category: String,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<bool, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let payload = json!({
-        "level": level,
-        "message": message,
-        "context": context,
-        "component": component,
-        "category": category
-    });
-
-    match engine.send_command("log_event", payload).await {
-        Ok(response) => {
-            if response.success {
-                Ok(response.data.and_then(|value| value.as_bool()).unwrap_or(true))
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Failed to log event".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to log event: {}", e)),
-    }
-}
-
-/// Get recently processed backend reports.
-#[tauri::command]
-pub async fn get_recent_reports(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("get_recent_reports", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                return Ok(response
-                    .data
-                    .unwrap_or_else(|| serde_json::Value::Array(vec![])));
-            }
-
-            Err(response
-                .error
-                .map(|e| e.message)
-                .unwrap_or_else(|| "Failed to fetch recent reports".to_string()))
-        }
-        Err(e) => Err(format!("Failed to fetch recent reports: {}", e)),
-    }
-}
-
-/// Get backend reports that still require review.
-#[tauri::command]
-pub async fn get_pending_reviews(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("get_pending_reviews", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                return Ok(response
-                    .data
-                    .unwrap_or_else(|| serde_json::Value::Array(vec![])));
-            }
-
-            Err(response
-                .error
-                .map(|e| e.message)
-                .unwrap_or_else(|| "Failed to fetch pending reviews".to_string()))
-        }
-        Err(e) => Err(format!("Failed to fetch pending reviews: {}", e)),
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PipelineResult {
-    pub success: bool,
-    pub errors: Vec<String>,
-    pub duration_ms: u32,
-}
-
-/// Trigger analytics pipeline manually
-#[tauri::command]
-pub async fn run_pipeline(engine: State<'_, Arc<PythonEngine>>) -> Result<PipelineResult, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("run_pipeline", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let result: Result<PipelineResult, _> = serde_json::from_value(data);
-                    match result {
-                        Ok(p) => Ok(p),
-                        Err(e) => {
-                            eprintln!("Failed to parse pipeline result: {}", e);
-                            Err("Failed to parse pipeline result".to_string())
-                        }
-                    }
-                } else {
-                    Err("No data in pipeline response".to_string())
-                }
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Pipeline failed".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to run pipeline: {}", e)),
-    }
-}
-
-/// Get decomposed true holdings
-#[tauri::command]
-pub async fn get_true_holdings(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("get_true_holdings", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    return Ok(data);
-                }
-            }
-            if let Some(err) = response.error {
-                return Err(err.message);
-            }
-            Err("Unknown error getting true holdings".to_string())
-        }
-        Err(e) => Err(format!("Failed to get true holdings: {}", e)),
-    }
-}
-
-/// Get overlap analysis
-#[tauri::command]
-pub async fn get_overlap_analysis(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine.send_command("get_overlap_analysis", json!({})).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    return Ok(data);
-                }
-            }
-            if let Some(err) = response.error {
-                return Err(err.message);
-            }
-            Err("Unknown error getting overlap analysis".to_string())
-        }
-        Err(e) => Err(format!("Failed to get overlap analysis: {}", e)),
-    }
-}
-
-/// Upload manual ETF holdings
-///
-/// Validates file path and ISIN format before forwarding to Python engine.
-#[tauri::command]
-pub async fn upload_holdings(
-    file_path: String,
-    etf_isin: String,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    // Validate ISIN format before processing
-    let validated_isin = validate_isin(&etf_isin)?;
-
-    // Validate file path
-    let validated_path = validate_file_path(&file_path)?;
-
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let payload = json!({
-        "filePath": validated_path,
-        "etfIsin": validated_isin
-    });
-
-    match engine.send_command("upload_holdings", payload).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    return Ok(data);
-                }
-            }
-            if let Some(err) = response.error {
-                return Err(err.message);
-            }
-            Err("Unknown error uploading holdings".to_string())
-        }
-        Err(e) => Err(format!("Failed to upload holdings: {}", e)),
-    }
-}
-
-/// Generate a preview for a holdings upload without saving it.
-#[tauri::command]
-pub async fn preview_holdings_upload(
-    file_path: String,
-    etf_isin: String,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    let validated_isin = validate_isin(&etf_isin)?;
-    let validated_path = validate_file_path(&file_path)?;
-
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let payload = json!({
-        "filePath": validated_path,
-        "etfIsin": validated_isin
-    });
-
-    match engine.send_command("preview_holdings_upload", payload).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    return Ok(data);
-                }
-            }
-            if let Some(err) = response.error {
-                return Err(err.message);
-            }
-            Err("Unknown error previewing holdings upload".to_string())
-        }
-        Err(e) => Err(format!("Failed to preview holdings upload: {}", e)),
-    }
-}
-
-/// Persist reviewed holdings to the cache after user confirmation.
-#[tauri::command]
-pub async fn commit_holdings_upload(
-    etf_isin: String,
-    holdings: Vec<ManualHoldingDraft>,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<serde_json::Value, String> {
-    let validated_isin = validate_isin(&etf_isin)?;
-
-    if holdings.is_empty() {
-        return Err("At least one holding is required".to_string());
-    }
-
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    let payload = json!({
-        "etfIsin": validated_isin,
-        "holdings": holdings
-    });
-
-    match engine.send_command("commit_holdings_upload", payload).await {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    return Ok(data);
-                }
-            }
-            if let Some(err) = response.error {
-                return Err(err.message);
-            }
-            Err("Unknown error committing holdings upload".to_string())
-        }
-        Err(e) => Err(format!("Failed to commit holdings upload: {}", e)),
-    }
-}
-
-/// Open the native macOS file picker for holdings uploads.
-#[tauri::command]
-pub fn pick_holdings_file() -> Result<String, String> {
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-
-        let output = Command::new("osascript")
-            .args([
-                "-e",
-                "try",
-                "-e",
-                "POSIX path of (choose file with prompt \"Select a holdings file\")",
-                "-e",
-                "on error number -128",
-                "-e",
-                "return \"\"",
-                "-e",
-                "end try",
-            ])
-            .output()
-            .map_err(|e| format!("Failed to open native file picker: {}", e))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-            return Err(if stderr.is_empty() {
-                "Native file picker failed".to_string()
-            } else {
-                format!("Native file picker failed: {}", stderr)
-            });
-        }
-
-        let selected_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if selected_path.is_empty() {
-            return Err("File selection was cancelled".to_string());
-        }
-
-        validate_file_path(&selected_path)
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        Err("Native holdings file picker is only implemented on macOS".to_string())
-    }
-}
-
-/// Set Hive contribution preference
-#[tauri::command]
-pub async fn set_hive_contribution(
-    enabled: bool,
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<(), String> {
-    if !engine.is_connected().await {
-        return Err("Python engine not connected".to_string());
-    }
-
-    match engine
-        .send_command("set_hive_contribution", json!({ "enabled": enabled }))
-        .await
-    {
-        Ok(response) => {
-            if response.success {
-                Ok(())
-            } else {
-                Err(response
-                    .error
-                    .map(|e| e.message)
-                    .unwrap_or_else(|| "Failed to set hive contribution".to_string()))
-            }
-        }
-        Err(e) => Err(format!("Failed to set hive contribution: {}", e)),
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct HiveContributionStatus {
-    pub enabled: bool,
-}
-
-/// Get Hive contribution preference
-#[tauri::command]
-pub async fn get_hive_contribution(
-    engine: State<'_, Arc<PythonEngine>>,
-) -> Result<HiveContributionStatus, String> {
-    if !engine.is_connected().await {
-        return Ok(HiveContributionStatus { enabled: false });
-    }
-
-    match engine
-        .send_command("get_hive_contribution", json!({}))
-        .await
-    {
-        Ok(response) => {
-            if response.success {
-                if let Some(data) = response.data {
-                    let enabled = data["enabled"].as_bool().unwrap_or(false);
-                    return Ok(HiveContributionStatus { enabled });
-                }
-            }
-            Ok(HiveContributionStatus { enabled: false })
-        }
-        Err(_) => Ok(HiveContributionStatus { enabled: false }),
-    }
-}
-
-/// Get the latest pipeline health report from disk
-#[tauri::command]
-pub async fn get_pipeline_report(app_handle: AppHandle) -> Result<serde_json::Value, String> {
-    use std::fs;
-
-    // Resolve app data dir
-    let data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-
-    let report_path = data_dir.join("outputs").join("pipeline_health.json");
-
-    if !report_path.exists() {
-        return Err("Report file not found".to_string());
-    }
-
-    let content =
-        fs::read_to_string(report_path).map_err(|e| format!("Failed to read report: {}", e))?;
-
-    let json: serde_json::Value =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse report: {}", e))?;
-
-    Ok(json)
-}
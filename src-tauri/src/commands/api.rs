@@ -0,0 +1,183 @@
+//! IPC API Versioning
+//!
+//! Every registered command carries a [`CommandSpec`]: the API version that
+//! introduced its current shape and an optional deprecation note. Callers may
+//! pass `apiVersion` alongside their arguments; the router logs a warning and
+//! counts the call when a deprecated command is used or when the caller
+//! targets an older version than the command's current shape.
+//!
+//! `get_api_manifest` returns the full catalog, with argument and return
+//! types extracted from the command signatures by `build.rs`.
+
+use super::MODULES;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::ipc::InvokeBody;
+
+include!(concat!(env!("OUT_DIR"), "/command_schemas.rs"));
+
+/// Current IPC API version. Bump when a command changes shape and mark the
+/// command with `#[api(since = API_VERSION)]`.
+pub const API_VERSION: u32 = 1;
+
+// =============================================================================
+// Command Specs
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// API version that introduced the command's current shape
+    pub since: u32,
+    /// Deprecation note shown in warnings and the manifest
+    pub deprecated: Option<&'static str>,
+}
+
+impl CommandSpec {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            since: 1,
+            deprecated: None,
+        }
+    }
+
+    pub const fn since(self, since: u32) -> Self {
+        Self { since, ..self }
+    }
+
+    pub const fn deprecated(self, note: &'static str) -> Self {
+        Self {
+            deprecated: Some(note),
+            ..self
+        }
+    }
+}
+
+// =============================================================================
+// Usage Tracking
+// =============================================================================
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiUsage {
+    /// Calls to a deprecated command
+    pub deprecated_calls: u64,
+    /// Calls whose `apiVersion` predates the command's current shape
+    pub outdated_calls: u64,
+    /// Calls without `apiVersion`
+    pub unversioned_calls: u64,
+}
+
+static USAGE: Mutex<BTreeMap<&'static str, ApiUsage>> = Mutex::new(BTreeMap::new());
+
+/// Record a call and warn about deprecated or outdated shapes.
+pub fn observe(spec: &CommandSpec, payload: &InvokeBody) {
+    let requested = match payload {
+        InvokeBody::Json(Value::Object(args)) => args.get("apiVersion").and_then(Value::as_u64),
+        _ => None,
+    };
+
+    let Ok(mut usage) = USAGE.lock() else {
+        return;
+    };
+    let entry = usage.entry(spec.name).or_default();
+
+    match requested {
+        None => entry.unversioned_calls += 1,
+        Some(version) if version < spec.since as u64 => {
+            entry.outdated_calls += 1;
+            tracing::warn!(
+                "`{}` called with apiVersion {} but its shape changed in {} ({} calls)",
+                spec.name, version, spec.since, entry.outdated_calls
+            );
+        }
+        Some(version) if version > API_VERSION as u64 => {
+            tracing::warn!(
+                "`{}` called with apiVersion {}, shell supports up to {}",
+                spec.name, version, API_VERSION
+            );
+        }
+        Some(_) => {}
+    }
+
+    if let Some(note) = spec.deprecated {
+        entry.deprecated_calls += 1;
+        tracing::warn!(
+            "`{}` is deprecated: {} ({} calls)",
+            spec.name, note, entry.deprecated_calls
+        );
+    }
+}
+
+// =============================================================================
+// Manifest
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiArgument {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCommand {
+    pub name: &'static str,
+    pub module: &'static str,
+    pub since: u32,
+    pub deprecated: Option<&'static str>,
+    pub args: Vec<ApiArgument>,
+    pub returns: &'static str,
+    pub usage: ApiUsage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiManifest {
+    pub api_version: u32,
+    pub commands: Vec<ApiCommand>,
+}
+
+/// Full command catalog with versions, schemas and call counts
+#[tauri::command]
+pub fn get_api_manifest() -> Result<ApiManifest, String> {
+    let usage = USAGE.lock().map_err(|e| e.to_string())?;
+
+    let commands = MODULES
+        .iter()
+        .flat_map(|(module, specs)| specs.iter().map(move |spec| (*module, spec)))
+        .map(|(module, spec)| {
+            let schema = COMMAND_SCHEMAS.iter().find(|(name, _, _)| *name == spec.name);
+            let args = schema.map(|(_, args, _)| *args).unwrap_or_default();
+            let returns = schema.map(|(_, _, returns)| *returns).unwrap_or("unknown");
+
+            ApiCommand {
+                name: spec.name,
+                module,
+                since: spec.since,
+                deprecated: spec.deprecated,
+                args: args
+                    .iter()
+                    .map(|&(name, ty, optional)| ApiArgument { name, ty, optional })
+                    .collect(),
+                returns,
+                usage: usage.get(spec.name).cloned().unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    Ok(ApiManifest {
+        api_version: API_VERSION,
+        commands,
+    })
+}
+
+register_commands! {
+    get_api_manifest,
+}
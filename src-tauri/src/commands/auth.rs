@@ -0,0 +1,357 @@
+//! Trade Republic Authentication Commands
+//!
+//! Login, two-factor confirmation, session restore and logout. Credentials
+//! and sessions are handled by the engine; the shell relays state and
+//! encrypts the session files the engine wrote after each step (see
+//! `session_vault`).
+
+use crate::protocol;
+use crate::python_engine::PythonEngine;
+use crate::session_vault;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use zeroize::Zeroizing;
+
+// =============================================================================
+// Trade Republic Auth Types
+// =============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthStatus {
+    pub auth_state: String,
+    pub has_stored_credentials: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCheck {
+    pub has_session: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    pub prompt: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthResponse {
+    pub auth_state: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub countdown: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredCredentialsInfo {
+    pub has_credentials: bool,
+    pub masked_phone: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutResponse {
+    pub auth_state: String,
+    pub message: String,
+}
+
+/// `tr_login` payload, borrowed from the zeroized arguments
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginPayload<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pin: Option<&'a str>,
+    remember: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    use_stored_credentials: bool,
+}
+
+/// `tr_submit_2fa` payload
+#[derive(Serialize)]
+struct TwoFactorPayload<'a> {
+    code: &'a str,
+}
+
+// =============================================================================
+// Commands
+// =============================================================================
+
+/// Get current Trade Republic authentication status
+#[tauri::command]
+pub async fn tr_get_auth_status(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AuthStatus, String> {
+    if !engine.is_connected().await {
+        return Ok(AuthStatus {
+            auth_state: "idle".to_string(),
+            has_stored_credentials: false,
+            last_error: Some("Python engine not connected".to_string()),
+        });
+    }
+
+    match engine.send_command("tr_get_auth_status", json!({})).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_get_auth_status", data)
+                } else {
+                    Err("No data in auth status response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Auth status check failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to get auth status: {}", e)),
+    }
+}
+
+/// Check for saved Trade Republic session
+#[tauri::command]
+pub async fn tr_check_saved_session(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<SessionCheck, String> {
+    if !engine.is_connected().await {
+        return Ok(SessionCheck {
+            has_session: false,
+            phone_number: None,
+            prompt: "login_required".to_string(),
+        });
+    }
+
+    match engine
+        .send_command("tr_check_saved_session", json!({}))
+        .await
+    {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_check_saved_session", data)
+                } else {
+                    Err("No data in session check response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Session check failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to check session: {}", e)),
+    }
+}
+
+/// Check whether stored Trade Republic credentials are available.
+#[tauri::command]
+pub async fn tr_get_stored_credentials(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<StoredCredentialsInfo, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    match engine
+        .send_command("tr_get_stored_credentials", json!({}))
+        .await
+    {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_get_stored_credentials", data)
+                } else {
+                    Err("No data in stored credentials response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Stored credentials check failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to check stored credentials: {}", e)),
+    }
+}
+
+/// Attempt to restore a saved Trade Republic session
+#[tauri::command]
+pub async fn tr_restore_session(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AuthResponse, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let result = engine.send_command("tr_restore_session", json!({})).await;
+    session_vault::persist(&app_handle);
+
+    match result {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_restore_session", data)
+                } else {
+                    Err("No data in restore response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Session restore failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to restore session: {}", e)),
+    }
+}
+
+/// Start Trade Republic login process
+#[tauri::command]
+pub async fn tr_login(
+    app_handle: AppHandle,
+    phone: Option<Zeroizing<String>>,
+    pin: Option<Zeroizing<String>>,
+    remember: Option<bool>,
+    use_stored_credentials: Option<bool>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AuthResponse, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let remember = remember.unwrap_or(true);
+    let use_stored_credentials = use_stored_credentials.unwrap_or(false);
+
+    let payload = if use_stored_credentials {
+        LoginPayload {
+            phone: None,
+            pin: None,
+            remember,
+            use_stored_credentials: true,
+        }
+    } else {
+        let phone = phone
+            .as_deref()
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| "Phone number is required".to_string())?;
+        let pin = pin
+            .as_deref()
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| "PIN is required".to_string())?;
+
+        LoginPayload {
+            phone: Some(phone.as_str()),
+            pin: Some(pin.as_str()),
+            remember,
+            use_stored_credentials: false,
+        }
+    };
+
+    let result = engine.send_secret_command("tr_login", &payload).await;
+    session_vault::persist(&app_handle);
+
+    match result {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_login", data)
+                } else {
+                    Err("No data in auth response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Login failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to login: {}", e)),
+    }
+}
+
+/// Submit 2FA code for Trade Republic
+#[tauri::command]
+pub async fn tr_submit_2fa(
+    app_handle: AppHandle,
+    code: Zeroizing<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AuthResponse, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = TwoFactorPayload { code: &code };
+
+    let result = engine.send_secret_command("tr_submit_2fa", &payload).await;
+    session_vault::persist(&app_handle);
+
+    match result {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_submit_2fa", data)
+                } else {
+                    Err("No data in 2FA response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "2FA verification failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to submit 2FA: {}", e)),
+    }
+}
+
+/// Logout from Trade Republic
+#[tauri::command]
+pub async fn tr_logout(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<LogoutResponse, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let result = engine.send_command("tr_logout", json!({})).await;
+    session_vault::persist(&app_handle);
+
+    match result {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "tr_logout", data)
+                } else {
+                    Err("No data in logout response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Logout failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to logout: {}", e)),
+    }
+}
+
+register_commands! {
+    tr_get_auth_status,
+    tr_check_saved_session,
+    tr_get_stored_credentials,
+    tr_restore_session,
+    tr_login,
+    tr_submit_2fa,
+    tr_logout,
+}
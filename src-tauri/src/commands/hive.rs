@@ -0,0 +1,139 @@
+//! Hive Community Data Commands
+//!
+//! Contribution preferences and privacy controls, ETF decompositions served
+//! from the local Hive cache, and the opt-in community comparison.
+
+use super::portfolio::{DashboardData, PositionsResponse};
+use super::validate_isin;
+use crate::app_settings;
+use crate::community_stats::{self, CommunityComparison};
+use crate::feature_flags::FeatureFlags;
+use crate::hive_cache::{HiveCache, HiveCacheStats, HiveDecomposition};
+use crate::hive_guard::{self, HivePrivacyConfig};
+use crate::protocol;
+use crate::python_engine::{EnginePool, PythonEngine};
+use crate::sandbox;
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+// =============================================================================
+// Commands
+// =============================================================================
+
+/// Set Hive contribution preference
+///
+/// Stored in `settings.json` and pushed to the running engines.
+#[tauri::command]
+pub async fn set_hive_contribution(
+    app_handle: AppHandle,
+    enabled: bool,
+    pool: State<'_, EnginePool>,
+) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let saved = app_settings::set_hive_contributions(&data_dir, enabled)?;
+    for engine in [pool.primary(), pool.worker()] {
+        app_settings::configure_engine(&engine, &saved.general).await;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiveContributionStatus {
+    pub enabled: bool,
+}
+
+/// Get Hive contribution preference
+#[tauri::command]
+pub async fn get_hive_contribution(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<HiveContributionStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    Ok(HiveContributionStatus {
+        enabled: app_settings::hive_contributions(&data_dir, &engine).await,
+    })
+}
+
+/// Get Hive privacy settings enforced by the shell
+#[tauri::command]
+pub async fn get_hive_privacy_config(app_handle: AppHandle) -> Result<HivePrivacyConfig, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    hive_guard::load_config(&data_dir)
+}
+
+/// Set the currency the user contributes to the Hive in
+#[tauri::command]
+pub async fn set_hive_contribution_currency(
+    app_handle: AppHandle,
+    currency: String,
+) -> Result<HivePrivacyConfig, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    hive_guard::set_contribution_currency(&data_dir, &currency)
+}
+
+/// Get a community decomposition for an ETF, cached locally with a TTL
+#[tauri::command]
+pub async fn fetch_hive_decomposition(
+    isin: String,
+    force: Option<bool>,
+    cache: State<'_, HiveCache>,
+) -> Result<HiveDecomposition, String> {
+    let isin = validate_isin(&isin)?;
+    cache.get(&isin, force.unwrap_or(false)).await
+}
+
+/// Get Hive decomposition cache statistics
+#[tauri::command]
+pub async fn get_hive_cache_stats(cache: State<'_, HiveCache>) -> Result<HiveCacheStats, String> {
+    cache.stats()
+}
+
+/// Rank the portfolio's allocation metrics against anonymized Hive
+/// distributions. Opt-in via the `community_comparison` feature flag; only
+/// aggregates are downloaded and the ranking happens on this device.
+#[tauri::command]
+pub async fn get_community_comparison(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    force: Option<bool>,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<CommunityComparison, String> {
+    flags.require("community_comparison")?;
+    sandbox::reject(portfolio_id, "compared with the community")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({ "portfolioId": portfolio_id });
+    let data = engine.request("get_dashboard_data", payload.clone()).await?;
+    let dashboard: DashboardData = protocol::parse(&app_handle, "get_dashboard_data", data)?;
+    let data = engine.request("get_positions", payload).await?;
+    let positions: PositionsResponse = protocol::parse(&app_handle, "get_positions", data)?;
+
+    let data_dir = store::data_dir(&app_handle)?;
+    let (distributions, fetched_at, stale) =
+        community_stats::distributions(&data_dir, force.unwrap_or(false)).await?;
+    Ok(community_stats::compare(
+        portfolio_id,
+        &dashboard.allocations,
+        &positions.positions,
+        &distributions,
+        fetched_at,
+        stale,
+    ))
+}
+
+register_commands! {
+    set_hive_contribution,
+    get_hive_contribution,
+    get_hive_privacy_config,
+    set_hive_contribution_currency,
+    fetch_hive_decomposition,
+    get_hive_cache_stats,
+    get_community_comparison,
+}
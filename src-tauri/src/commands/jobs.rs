@@ -0,0 +1,235 @@
+//! Engine and Background Job Commands
+//!
+//! Engine health (including network status and queued syncs) and sidecar
+//! state, offline dataset updates, download settings, background maintenance
+//! and scheduled pipeline runs and syncs.
+
+use crate::dataset::{self, DatasetStatus};
+use crate::downloads::{self, DownloadSettings};
+use crate::maintenance::{
+    self, Maintenance, MaintenanceRun, MaintenanceSettings, MaintenanceStatus, MaintenanceTrigger,
+};
+use crate::network::{self, NetworkStatus};
+use crate::python_engine::{EnginePool, EngineStatus, PythonEngine};
+use crate::scheduler::{
+    self, PipelineSchedule, PipelineScheduleStatus, SyncSchedule, SyncScheduleStatus,
+};
+use crate::sidecar_check::{self, SidecarMissing};
+use crate::store;
+use crate::sync_queue::{self, QueuedSync};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+// =============================================================================
+// Engine
+// =============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineHealth {
+    pub version: String,
+    pub memory_usage_mb: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_seconds: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<String>,
+    /// Whether Trade Republic can be reached
+    pub network: NetworkStatus,
+    /// Syncs waiting for the connection to return
+    pub queued_syncs: Vec<QueuedSync>,
+}
+
+/// Get engine health status
+#[tauri::command]
+pub async fn get_engine_health(
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<EngineHealth, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    match engine.send_command("get_health", json!({})).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    let health = EngineHealth {
+                        version: data["version"].as_str().unwrap_or("0.0.0").to_string(),
+                        memory_usage_mb: data["memoryUsageMb"].as_f64().unwrap_or(0.0),
+                        uptime_seconds: data["uptimeSeconds"].as_f64(),
+                        db_path: data["dbPath"].as_str().map(|s| s.to_string()),
+                        network: network::status(),
+                        queued_syncs: sync_queue::pending(),
+                    };
+                    return Ok(health);
+                }
+                return Err("No data in engine health response".to_string());
+            }
+
+            Err(response
+                .error
+                .map(|e| e.message)
+                .unwrap_or_else(|| "Engine health check failed".to_string()))
+        }
+        Err(e) => Err(format!("Failed to get engine health: {}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineStates {
+    pub primary: EngineStatus,
+    pub worker: EngineStatus,
+}
+
+/// Get the connection state of the engine sidecars
+#[tauri::command]
+pub async fn get_engine_state(pool: State<'_, EnginePool>) -> Result<EngineStates, String> {
+    Ok(EngineStates {
+        primary: pool.primary().status(),
+        worker: pool.worker().status(),
+    })
+}
+
+/// Check the engine binary again; `None` when it is in place. The same
+/// finding is emitted as `sidecar-missing` when a start fails.
+#[tauri::command]
+pub async fn get_sidecar_missing() -> Result<Option<SidecarMissing>, String> {
+    Ok(sidecar_check::check())
+}
+
+// =============================================================================
+// Offline Dataset
+// =============================================================================
+
+/// Get the installed offline ETF dataset version and integrity
+#[tauri::command]
+pub async fn get_dataset_status(app_handle: AppHandle) -> Result<DatasetStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || dataset::status(&data_dir))
+        .await
+        .map_err(|e| format!("Dataset check failed: {}", e))?
+}
+
+/// Download a newer offline ETF dataset if one is published
+#[tauri::command]
+pub async fn update_dataset(
+    app_handle: AppHandle,
+    force: Option<bool>,
+) -> Result<DatasetStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    dataset::update(&app_handle, &data_dir, force.unwrap_or(false)).await
+}
+
+/// Get bandwidth limit and idle-hour scheduling for background downloads
+#[tauri::command]
+pub async fn get_download_settings(app_handle: AppHandle) -> Result<DownloadSettings, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    downloads::load_settings(&data_dir)
+}
+
+/// Update bandwidth limit and idle-hour scheduling for background downloads
+#[tauri::command]
+pub async fn set_download_settings(
+    app_handle: AppHandle,
+    mut settings: DownloadSettings,
+) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    // The schedule bookkeeping is owned by the shell, not the settings UI
+    settings.last_auto_update = downloads::load_settings(&data_dir)?.last_auto_update;
+    downloads::save_settings(&data_dir, &settings)
+}
+
+// =============================================================================
+// Maintenance
+// =============================================================================
+
+/// Get the maintenance schedule, last run and current power/idle state
+#[tauri::command]
+pub async fn get_maintenance_status(
+    app_handle: AppHandle,
+    maintenance: State<'_, Maintenance>,
+) -> Result<MaintenanceStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    maintenance.status(&data_dir)
+}
+
+/// Update the maintenance window and idle settings
+#[tauri::command]
+pub async fn set_maintenance_settings(
+    app_handle: AppHandle,
+    settings: MaintenanceSettings,
+) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    maintenance::save_settings(&data_dir, settings)
+}
+
+/// Run every maintenance task now, regardless of window or activity
+#[tauri::command]
+pub async fn run_maintenance_now(
+    app_handle: AppHandle,
+    maintenance: State<'_, Maintenance>,
+) -> Result<MaintenanceRun, String> {
+    maintenance.run(&app_handle, MaintenanceTrigger::Manual).await
+}
+
+// =============================================================================
+// Schedules
+// =============================================================================
+
+/// Get the automatic pipeline schedule, last scheduled run and next due time
+#[tauri::command]
+pub async fn get_pipeline_schedule(
+    app_handle: AppHandle,
+) -> Result<PipelineScheduleStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    scheduler::pipeline_status(&data_dir)
+}
+
+/// Update the automatic pipeline schedule (interval or daily time)
+#[tauri::command]
+pub async fn set_pipeline_schedule(
+    app_handle: AppHandle,
+    settings: PipelineSchedule,
+) -> Result<PipelineScheduleStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    scheduler::save_pipeline_schedule(&data_dir, settings)?;
+    scheduler::pipeline_status(&data_dir)
+}
+
+/// Get the background portfolio sync schedule, last scheduled sync and next
+/// due time
+#[tauri::command]
+pub async fn get_sync_schedule(app_handle: AppHandle) -> Result<SyncScheduleStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    scheduler::sync_status(&data_dir)
+}
+
+/// Enable, disable or reschedule the background portfolio sync
+#[tauri::command]
+pub async fn set_sync_schedule(
+    app_handle: AppHandle,
+    settings: SyncSchedule,
+) -> Result<SyncScheduleStatus, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    scheduler::save_sync_schedule(&data_dir, settings)?;
+    scheduler::sync_status(&data_dir)
+}
+
+register_commands! {
+    get_engine_health,
+    get_engine_state,
+    get_sidecar_missing,
+    get_dataset_status,
+    update_dataset,
+    get_download_settings,
+    set_download_settings,
+    get_maintenance_status,
+    set_maintenance_settings,
+    run_maintenance_now,
+    get_pipeline_schedule,
+    set_pipeline_schedule,
+    get_sync_schedule,
+    set_sync_schedule,
+}
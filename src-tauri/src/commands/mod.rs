@@ -0,0 +1,350 @@
+//! Tauri Commands for IPC Bridge
+//!
+//! These commands are invoked from the React frontend via `invoke()`.
+//! Commands communicate with the Python engine via stdin/stdout IPC.
+//!
+//! Commands are grouped into domain modules. Each module ends with a
+//! `register_commands!` list, which generates its handler and the list of
+//! names it answers; `command_modules!` routes every invocation to the module
+//! that registered it. Adding a command therefore touches only its module.
+//!
+//! Entries may carry `#[api(since = N)]` or `#[api(deprecated = "...")]`;
+//! the router reports deprecated or outdated calls through [`api::observe`].
+//! While the app is locked (see `app_lock`), it rejects all but the unlock
+//! commands.
+
+use tauri::ipc::Invoke;
+use tauri::Runtime;
+
+// =============================================================================
+// Registration
+// =============================================================================
+
+/// Declare the commands of a domain module. Expands to the module's
+/// `COMMANDS` spec list and a `handler()` built with `generate_handler!`.
+macro_rules! register_commands {
+    ($($(#[api($($key:ident = $value:expr),* $(,)?)])? $command:ident),* $(,)?) => {
+        /// Commands answered by this module, with their API versions
+        pub const COMMANDS: &[$crate::commands::api::CommandSpec] = &[$(
+            $crate::commands::api::CommandSpec::new(stringify!($command))$($(.$key($value))*)?
+        ),*];
+
+        /// Invoke handler for this module's commands
+        pub fn handler<R: tauri::Runtime>(
+        ) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+            tauri::generate_handler![$($command),*]
+        }
+    };
+}
+
+/// Declare the domain modules and build the combined invoke handler.
+macro_rules! command_modules {
+    ($($module:ident),* $(,)?) => {
+        $(pub mod $module;)*
+
+        /// Registered modules with their command specs
+        pub const MODULES: &[(&str, &[api::CommandSpec])] =
+            &[$((stringify!($module), $module::COMMANDS)),*];
+
+        /// Invoke handler routing each command to the module that registered it
+        pub fn handler<R: Runtime>() -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+            move |invoke| {
+                let command = invoke.message.command().to_string();
+                crate::maintenance::record_activity();
+                crate::telemetry::record_command(&command);
+                $(
+                    if let Some(spec) = $module::COMMANDS.iter().find(|c| c.name == command) {
+                        if let Err(e) = crate::app_lock::check(&command) {
+                            invoke.resolver.reject(e);
+                            return true;
+                        }
+                        api::observe(spec, invoke.message.payload());
+                        return $module::handler::<R>()(invoke);
+                    }
+                )*
+                false
+            }
+        }
+    };
+}
+
+command_modules!(api, auth, hive, jobs, navigation, pipeline, portfolio, settings);
+
+// =============================================================================
+// Input Validation Helpers
+// =============================================================================
+
+/// Validate ISIN format and Luhn checksum.
+///
+/// ISIN format: 2 letter country code + 9 alphanumeric NSIN + 1 check digit
+///
+/// # Arguments
+/// * `isin` - The ISIN string to validate
+///
+/// # Returns
+/// * `Ok(String)` - Normalized uppercase ISIN if valid
+/// * `Err(String)` - User-friendly error message if invalid
+///
+/// # Examples
+/// ```
+/// assert!(validate_isin("US0378331005").is_ok());  // Apple Inc
+/// assert!(validate_isin("DE0007164600").is_ok());  // SAP SE
+/// assert!(validate_isin("invalid").is_err());
+/// ```
+fn validate_isin(isin: &str) -> Result<String, String> {
+    let isin = isin.trim().to_uppercase();
+
+    // Length check (must be exactly 12 characters)
+    if isin.len() != 12 {
+        return Err(format!(
+            "Invalid ISIN: must be exactly 12 characters (got {})",
+            isin.len()
+        ));
+    }
+
+    // Country code (first 2 chars must be uppercase ASCII letters)
+    let country_code = &isin[..2];
+    if !country_code.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err("Invalid ISIN: first 2 characters must be letters (country code)".to_string());
+    }
+
+    // NSIN (chars 3-11, 9 chars, must be alphanumeric)
+    let nsin = &isin[2..11];
+    if !nsin.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(
+            "Invalid ISIN: characters 3-11 must be alphanumeric (security identifier)".to_string(),
+        );
+    }
+
+    // Check digit (last char must be a digit)
+    let check_digit = isin.chars().nth(11).unwrap();
+    if !check_digit.is_ascii_digit() {
+        return Err("Invalid ISIN: last character must be a digit (check digit)".to_string());
+    }
+
+    // Luhn checksum validation
+    if !validate_isin_luhn(&isin) {
+        return Err("Invalid ISIN: checksum validation failed".to_string());
+    }
+
+    Ok(isin)
+}
+
+/// Validate ISIN using Luhn algorithm.
+///
+/// Algorithm:
+/// 1. Convert letters to numbers (A=10, B=11, ..., Z=35)
+/// 2. Apply Luhn algorithm to resulting digit string
+/// 3. Valid if total mod 10 == 0
+fn validate_isin_luhn(isin: &str) -> bool {
+    // Convert ISIN to digit string (letters become 2-digit numbers)
+    let mut digits = String::new();
+    for c in isin.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else if c.is_ascii_uppercase() {
+            // A=10, B=11, ..., Z=35
+            let value = (c as u32) - ('A' as u32) + 10;
+            digits.push_str(&value.to_string());
+        } else {
+            return false;
+        }
+    }
+
+    // Luhn algorithm (process from right to left)
+    let mut total: u32 = 0;
+    for (i, c) in digits.chars().rev().enumerate() {
+        let mut n = c.to_digit(10).unwrap_or(0);
+        // Double every second digit from right (0-indexed, so i % 2 == 1)
+        if i % 2 == 1 {
+            n *= 2;
+            if n > 9 {
+                n -= 9;
+            }
+        }
+        total += n;
+    }
+
+    total % 10 == 0
+}
+
+/// Allowed file extensions for holdings uploads and broker statements.
+const ALLOWED_EXTENSIONS: &[&str] = &["csv", "xlsx", "xls", "json", "pdf", "xml"];
+
+/// Validate file path for holdings upload.
+///
+/// Checks:
+/// - File exists
+/// - Extension is allowed (csv, xlsx, xls, json, pdf, xml)
+/// - Path is canonicalized (prevents path traversal attacks with `..`)
+///
+/// # Arguments
+/// * `path` - The file path to validate
+///
+/// # Returns
+/// * `Ok(String)` - Canonicalized absolute path if valid
+/// * `Err(String)` - User-friendly error message if invalid
+fn validate_file_path(path: &str) -> Result<String, String> {
+    use std::path::PathBuf;
+
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("File path cannot be empty".to_string());
+    }
+
+    let path_buf = PathBuf::from(path);
+
+    // Check file exists
+    if !path_buf.exists() {
+        return Err(format!("File not found: {}", path));
+    }
+
+    // Check it's a file, not a directory
+    if !path_buf.is_file() {
+        return Err(format!("Path is not a file: {}", path));
+    }
+
+    // Validate extension
+    let extension = path_buf
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    match extension {
+        Some(ref ext) if ALLOWED_EXTENSIONS.contains(&ext.as_str()) => {}
+        Some(ext) => {
+            return Err(format!(
+                "Unsupported file extension: .{}. Allowed: {}",
+                ext,
+                ALLOWED_EXTENSIONS.join(", ")
+            ));
+        }
+        None => {
+            return Err(
+                "File must have an extension (csv, xlsx, xls, json, pdf, or xml)".to_string(),
+            );
+        }
+    }
+
+    // Canonicalize path to prevent path traversal (resolves `..`, symlinks)
+    // This is defense-in-depth against directory traversal attacks
+    let canonical = path_buf
+        .canonicalize()
+        .map_err(|e| format!("Invalid file path: {}", e))?;
+
+    canonical
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "File path contains invalid characters".to_string())
+}
+
+fn validate_date(date: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date (expected YYYY-MM-DD): {}", date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::api::COMMAND_SCHEMAS;
+    use super::MODULES;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    fn commands_dir() -> &'static Path {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src/commands"))
+    }
+
+    /// `#[tauri::command]` functions declared in a module's source
+    fn declared_commands(source: &str) -> Vec<String> {
+        let mut declared = vec![];
+        let mut pending = false;
+        for line in source.lines().map(str::trim) {
+            if line == "#[tauri::command]" {
+                pending = true;
+            } else if pending && !line.starts_with("#[") {
+                let signature = line.trim_start_matches("pub ").trim_start_matches("async ");
+                if let Some(rest) = signature.strip_prefix("fn ") {
+                    let name: String = rest
+                        .chars()
+                        .take_while(|c| c.is_alphanumeric() || *c == '_')
+                        .collect();
+                    declared.push(name);
+                }
+                pending = false;
+            }
+        }
+        declared
+    }
+
+    #[test]
+    fn every_module_file_is_registered() {
+        let registered: HashSet<&str> = MODULES.iter().map(|(name, _)| *name).collect();
+        for entry in std::fs::read_dir(commands_dir()).unwrap() {
+            let path = entry.unwrap().path();
+            let stem = path.file_stem().unwrap().to_str().unwrap();
+            if stem != "mod" {
+                assert!(
+                    registered.contains(stem),
+                    "module `{}` is not registered",
+                    stem
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_is_registered() {
+        for (module, commands) in MODULES {
+            let source =
+                std::fs::read_to_string(commands_dir().join(format!("{}.rs", module))).unwrap();
+            let declared = declared_commands(&source);
+            assert!(
+                !declared.is_empty(),
+                "module `{}` declares no commands",
+                module
+            );
+            for command in &declared {
+                assert!(
+                    commands.iter().any(|spec| spec.name == command),
+                    "`{}::{}` is missing from register_commands!",
+                    module,
+                    command
+                );
+            }
+            assert_eq!(
+                declared.len(),
+                commands.len(),
+                "`{}` registers extra names",
+                module
+            );
+        }
+    }
+
+    #[test]
+    fn command_names_are_unique() {
+        let mut seen = HashSet::new();
+        for (module, commands) in MODULES {
+            for spec in *commands {
+                assert!(
+                    seen.insert(spec.name),
+                    "`{}` registered twice ({})",
+                    spec.name,
+                    module
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_has_a_schema() {
+        for (_, commands) in MODULES {
+            for spec in *commands {
+                assert!(
+                    COMMAND_SCHEMAS.iter().any(|(name, _, _)| *name == spec.name),
+                    "no generated schema for `{}`",
+                    spec.name
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Navigation Commands
+//!
+//! View registry and focus control for keyboard and accessibility navigation,
+//! and `prism://` links waiting for the frontend.
+
+use crate::deep_link::{self, DeepLink};
+use crate::navigation::{NavigableView, Navigation, NavigationState};
+use tauri::{AppHandle, State};
+
+/// List navigable views with their actions and the focused view
+#[tauri::command]
+pub fn list_navigable_views(navigation: State<'_, Navigation>) -> Result<NavigationState, String> {
+    navigation.snapshot()
+}
+
+/// Focus a view (and optionally one of its actions) via `navigation-focus`
+#[tauri::command]
+pub fn focus_view(
+    app_handle: AppHandle,
+    id: String,
+    action_id: Option<String>,
+    navigation: State<'_, Navigation>,
+) -> Result<NavigationState, String> {
+    navigation.focus(&app_handle, &id, action_id)
+}
+
+/// Register or replace views and their actions
+#[tauri::command]
+pub fn register_navigable_views(
+    app_handle: AppHandle,
+    views: Vec<NavigableView>,
+    navigation: State<'_, Navigation>,
+) -> Result<NavigationState, String> {
+    navigation.register(&app_handle, views)
+}
+
+/// Take the last `prism://` link received, e.g. the one that launched the
+/// app before the frontend listened for `deep-link`
+#[tauri::command]
+pub fn take_pending_deep_link() -> Option<DeepLink> {
+    deep_link::take_pending()
+}
+
+register_commands! {
+    list_navigable_views,
+    focus_view,
+    register_navigable_views,
+    take_pending_deep_link,
+}
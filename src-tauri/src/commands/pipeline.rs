@@ -0,0 +1,1007 @@
+//! Analytics Pipeline Commands
+//!
+//! Pipeline runs, per-portfolio pipeline configuration, reports and
+//! run-to-run change explanations, look-through analytics and holdings
+//! uploads.
+
+use super::portfolio;
+use super::{validate_file_path, validate_isin};
+use crate::benchmarks;
+use crate::change_explainer::{self, ChangeExplanation};
+use crate::data_quality;
+use crate::holdings_file::{self, HoldingsFileReport};
+use crate::overlap_matrix::{self, Constituent, OverlapMatrix};
+use crate::pipeline_config::{self, PipelineCapabilities, PipelineConfig};
+use crate::pipeline_history::{self, PipelineRunRecord};
+use crate::pipeline_progress::{self, PipelineProgress, PipelineRun};
+use crate::pipeline_report;
+use crate::pipeline_snapshots::{self, SnapshotSummary};
+use crate::protocol;
+use crate::python_engine::{EnginePool, EngineRole, PythonEngine};
+use crate::sandbox;
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+// =============================================================================
+// Types
+// =============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualHoldingDraft {
+    pub isin: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    pub weight: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineResult {
+    pub success: bool,
+    pub errors: Vec<String>,
+    pub duration_ms: u32,
+    /// Set when the run was stopped by `cancel_pipeline`
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Outcome of `cancel_pipeline`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineCancellation {
+    /// The engine stopped the run itself
+    pub acknowledged: bool,
+    /// The engine did not stop in time and was restarted
+    pub engine_restarted: bool,
+}
+
+/// One fund in the portfolio that holds an asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetExposure {
+    pub etf: String,
+    pub value: f64,
+    pub weight: f64,
+}
+
+/// Look-through row of `get_true_holdings`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LookThroughHolding {
+    #[serde(default)]
+    isin: Option<String>,
+    stock: String,
+    #[serde(default)]
+    ticker: String,
+    total_value: f64,
+    #[serde(default)]
+    sector: Option<String>,
+    #[serde(default)]
+    geography: Option<String>,
+    #[serde(default)]
+    sources: Vec<AssetExposure>,
+    #[serde(default)]
+    resolution_status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LookThroughResponse {
+    holdings: Vec<LookThroughHolding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetPricePoint {
+    pub date: String,
+    pub price: f64,
+}
+
+/// Instrument metadata and price history from the engine
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AssetMetadata {
+    name: Option<String>,
+    ticker: Option<String>,
+    instrument_type: Option<String>,
+    currency: Option<String>,
+    sector: Option<String>,
+    country: Option<String>,
+    price_history: Vec<AssetPricePoint>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetDetails {
+    pub isin: String,
+    pub name: Option<String>,
+    pub ticker: Option<String>,
+    pub instrument_type: Option<String>,
+    pub currency: Option<String>,
+    pub sector: Option<String>,
+    pub country: Option<String>,
+    pub price_history: Vec<AssetPricePoint>,
+    /// Look-through value across direct holdings and funds
+    pub total_value: f64,
+    /// Funds in the portfolio containing the asset, largest first
+    pub contained_in: Vec<AssetExposure>,
+    pub resolution_status: Option<String>,
+    /// Set when the metadata request failed and only look-through data is shown
+    pub metadata_error: Option<String>,
+}
+
+// =============================================================================
+// Commands
+// =============================================================================
+
+/// Trigger analytics pipeline manually
+///
+/// With a `portfolio_id`, that portfolio's pipeline config is sent along.
+/// Resolves early with a `cancelled` result when `cancel_pipeline` is called.
+#[tauri::command]
+pub async fn run_pipeline(
+    app_handle: AppHandle,
+    portfolio_id: Option<u32>,
+    pool: State<'_, EnginePool>,
+    run: State<'_, PipelineRun>,
+) -> Result<PipelineResult, String> {
+    let payload = pipeline_payload(&app_handle, portfolio_id)?;
+    execute_pipeline(&app_handle, &pool, &run, payload, None, portfolio_id).await
+}
+
+/// `run_pipeline` payload of a full run, with the portfolio's pipeline config
+pub(crate) fn pipeline_payload(
+    app_handle: &AppHandle,
+    portfolio_id: Option<u32>,
+) -> Result<serde_json::Value, String> {
+    let Some(portfolio_id) = portfolio_id else {
+        return Ok(json!({}));
+    };
+    sandbox::reject(portfolio_id, "analyzed by the pipeline")?;
+    let data_dir = store::data_dir(app_handle)?;
+    Ok(json!({
+        "portfolioId": portfolio_id,
+        "config": pipeline_config::load(&data_dir, portfolio_id)?.unwrap_or_default(),
+    }))
+}
+
+/// Run a single pipeline stage, e.g. only re-decompose ETFs after uploading
+/// holdings or only rebuild the report
+///
+/// `stage` must be one of the engine's declared stages. Required stages
+/// (such as loading holdings) run along with it, while the other fetching
+/// stages serve cached data; the portfolio's configured data sources still
+/// apply.
+#[tauri::command]
+pub async fn run_pipeline_stage(
+    app_handle: AppHandle,
+    stage: String,
+    portfolio_id: Option<u32>,
+    pool: State<'_, EnginePool>,
+    run: State<'_, PipelineRun>,
+) -> Result<PipelineResult, String> {
+    if let Some(portfolio_id) = portfolio_id {
+        sandbox::reject(portfolio_id, "analyzed by the pipeline")?;
+    }
+    let capabilities = fetch_capabilities(&app_handle, &pool.primary()).await?;
+    if !capabilities.stages.contains(&stage) {
+        return Err(format!(
+            "Unknown pipeline stage: {} (available: {})",
+            stage,
+            capabilities.stages.join(", ")
+        ));
+    }
+
+    let mut config = match portfolio_id {
+        Some(portfolio_id) => {
+            let data_dir = store::data_dir(&app_handle)?;
+            pipeline_config::load(&data_dir, portfolio_id)?.unwrap_or_default()
+        }
+        None => PipelineConfig::default(),
+    };
+    config.enabled_stages = capabilities
+        .stages
+        .iter()
+        .filter(|name| **name == stage || capabilities.required_stages.contains(name))
+        .cloned()
+        .collect();
+
+    let mut payload = json!({ "stage": stage, "config": config });
+    if let Some(portfolio_id) = portfolio_id {
+        payload["portfolioId"] = json!(portfolio_id);
+    }
+    execute_pipeline(
+        &app_handle,
+        &pool,
+        &run,
+        payload,
+        Some(&stage),
+        portfolio_id,
+    )
+    .await
+}
+
+/// Send `run_pipeline` with progress events and cancellation, and record the
+/// run in the history. Freshness and the change snapshot are only recorded
+/// for full runs (no `stage`).
+pub(crate) async fn execute_pipeline(
+    app_handle: &AppHandle,
+    pool: &EnginePool,
+    run: &PipelineRun,
+    payload: serde_json::Value,
+    stage: Option<&str>,
+    portfolio_id: Option<u32>,
+) -> Result<PipelineResult, String> {
+    // Runs on the worker sidecar when available so the primary stays responsive
+    let engine = pool.for_command("run_pipeline").await;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let role = if Arc::ptr_eq(&engine, &pool.worker()) {
+        EngineRole::Worker
+    } else {
+        EngineRole::Primary
+    };
+    let cancelled = run.start(engine.clone(), role)?;
+    let started = Instant::now();
+    let started_at = chrono::Utc::now();
+    pipeline_progress::emit(app_handle, &PipelineProgress::started());
+
+    let response = tokio::select! {
+        response = engine.send_command("run_pipeline", payload) => Some(response),
+        _ = cancelled => None,
+    };
+    let stage_timings = run.finish();
+
+    let result = match response {
+        None => Ok(PipelineResult {
+            success: false,
+            errors: vec!["Pipeline cancelled".to_string()],
+            duration_ms: started.elapsed().as_millis() as u32,
+            cancelled: true,
+        }),
+        Some(Ok(response)) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    let result: Result<PipelineResult, _> =
+                        protocol::parse(app_handle, "run_pipeline", data);
+                    match result {
+                        Ok(p) => {
+                            if p.success && stage.is_none() {
+                                if let Err(e) = store::data_dir(app_handle)
+                                    .and_then(|dir| data_quality::record_pipeline_success(&dir))
+                                {
+                                    tracing::warn!("Failed to record pipeline freshness: {}", e);
+                                }
+                                capture_snapshot(app_handle.clone(), pool.primary());
+                            }
+                            Ok(p)
+                        }
+                        Err(e) => Err(e),
+                    }
+                } else {
+                    Err("No data in pipeline response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Pipeline failed".to_string()))
+            }
+        }
+        Some(Err(e)) => Err(format!("Failed to run pipeline: {}", e)),
+    };
+
+    let (progress, status, errors) = match &result {
+        Ok(p) if p.cancelled => (PipelineProgress::cancelled(), "cancelled", vec![]),
+        Ok(p) if p.success => (PipelineProgress::complete(), "success", p.errors.clone()),
+        Ok(p) => (
+            PipelineProgress::failed(
+                p.errors
+                    .first()
+                    .map(String::as_str)
+                    .unwrap_or("Pipeline failed"),
+            ),
+            "failed",
+            p.errors.clone(),
+        ),
+        Err(e) => (PipelineProgress::failed(e), "failed", vec![e.clone()]),
+    };
+    pipeline_progress::emit(app_handle, &progress);
+
+    let record = PipelineRunRecord::new(
+        started_at,
+        stage,
+        portfolio_id,
+        status,
+        stage_timings,
+        &errors,
+    );
+    if let Err(e) =
+        store::data_dir(app_handle).and_then(|dir| pipeline_history::record(&dir, &record))
+    {
+        tracing::warn!("Failed to record pipeline run: {}", e);
+    }
+    result
+}
+
+/// Get the most recent pipeline runs (newest first) with duration, stage
+/// timings and errors
+#[tauri::command]
+pub async fn get_pipeline_history(
+    app_handle: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<PipelineRunRecord>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    pipeline_history::recent(&data_dir, limit.unwrap_or(50))
+}
+
+/// How long `cancel_pipeline` waits for the engine to stop the run
+const CANCEL_GRACE_SECS: u64 = 5;
+
+/// Stop a running pipeline
+///
+/// The pending `run_pipeline` call resolves at once with a `cancelled`
+/// result. The engine stops the run at its next progress report and keeps
+/// the previous reports; only if it does not acknowledge within
+/// `CANCEL_GRACE_SECS` is the sidecar running it restarted as a last resort.
+#[tauri::command]
+pub async fn cancel_pipeline(
+    app_handle: AppHandle,
+    run: State<'_, PipelineRun>,
+) -> Result<PipelineCancellation, String> {
+    let Some((engine, role)) = run.cancel() else {
+        return Err("No pipeline is running".to_string());
+    };
+
+    let acknowledged = matches!(
+        tokio::time::timeout(
+            Duration::from_secs(CANCEL_GRACE_SECS),
+            engine.request("cancel_pipeline", json!({})),
+        )
+        .await,
+        Ok(Ok(_))
+    );
+    if acknowledged {
+        return Ok(PipelineCancellation {
+            acknowledged,
+            engine_restarted: false,
+        });
+    }
+
+    tracing::warn!(
+        "Python {} did not stop the pipeline, restarting it",
+        role.label()
+    );
+    engine.shutdown().await;
+    if !engine.wait_for_exit(Duration::from_secs(CANCEL_GRACE_SECS)).await {
+        tracing::warn!("Python {} did not exit in time", role.label());
+    }
+    crate::respawn_engine(&app_handle, engine, role)
+        .map_err(|e| format!("Pipeline cancelled, but restarting the engine failed: {}", e))?;
+    Ok(PipelineCancellation {
+        acknowledged,
+        engine_restarted: true,
+    })
+}
+
+/// Record a snapshot for `explain_changes` without delaying the response
+fn capture_snapshot(app_handle: AppHandle, engine: Arc<PythonEngine>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = pipeline_snapshots::capture(&app_handle, &engine).await {
+            tracing::warn!("Failed to capture pipeline snapshot: {}", e);
+        }
+    });
+}
+
+/// List pipeline snapshots, newest first
+#[tauri::command]
+pub async fn list_pipeline_snapshots(
+    app_handle: AppHandle,
+) -> Result<Vec<SnapshotSummary>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    pipeline_snapshots::list(&data_dir)
+}
+
+/// Attribute the change between two pipeline snapshots to its causes
+///
+/// With a `benchmark_id`, the custom benchmark's return between the two
+/// snapshots is included for context.
+#[tauri::command]
+pub async fn explain_changes(
+    app_handle: AppHandle,
+    before_snapshot: String,
+    after_snapshot: String,
+    benchmark_id: Option<u32>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<ChangeExplanation, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let before = pipeline_snapshots::load(&data_dir, &before_snapshot)?;
+    let after = pipeline_snapshots::load(&data_dir, &after_snapshot)?;
+    let mut explanation = change_explainer::explain(&before, &after);
+    if let Some(id) = benchmark_id {
+        let (from, to) = (before.taken_at.date_naive(), after.taken_at.date_naive());
+        explanation.benchmark =
+            Some(benchmarks::period_return(&data_dir, &engine, id, from, to).await?);
+    }
+    Ok(explanation)
+}
+
+/// Get a portfolio's pipeline config (defaults when none was saved)
+#[tauri::command]
+pub async fn get_pipeline_config(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+) -> Result<PipelineConfig, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    Ok(pipeline_config::load(&data_dir, portfolio_id)?.unwrap_or_default())
+}
+
+async fn fetch_capabilities(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+) -> Result<PipelineCapabilities, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let data = engine.request("get_pipeline_capabilities", json!({})).await?;
+    protocol::parse(app_handle, "get_pipeline_capabilities", data)
+}
+
+/// Get the pipeline stages and data sources the engine supports
+#[tauri::command]
+pub async fn get_pipeline_capabilities(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<PipelineCapabilities, String> {
+    fetch_capabilities(&app_handle, &engine).await
+}
+
+/// Validate a portfolio's pipeline config against the engine and save it
+#[tauri::command]
+pub async fn update_pipeline_config(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    config: PipelineConfig,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<PipelineConfig, String> {
+    sandbox::reject(portfolio_id, "analyzed by the pipeline")?;
+    let capabilities = fetch_capabilities(&app_handle, &engine).await?;
+    let config = pipeline_config::validate(config, &capabilities)?;
+
+    let data_dir = store::data_dir(&app_handle)?;
+    pipeline_config::save(&data_dir, portfolio_id, &config)?;
+    Ok(config)
+}
+
+/// Get the latest pipeline health report from disk
+#[tauri::command]
+pub async fn get_pipeline_report(app_handle: AppHandle) -> Result<serde_json::Value, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    pipeline_report::load(&data_dir)
+}
+
+/// Get decomposed true holdings
+#[tauri::command]
+pub async fn get_true_holdings(
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    match engine.send_command("get_true_holdings", json!({})).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    return Ok(data);
+                }
+            }
+            if let Some(err) = response.error {
+                return Err(err.message);
+            }
+            Err("Unknown error getting true holdings".to_string())
+        }
+        Err(e) => Err(format!("Failed to get true holdings: {}", e)),
+    }
+}
+
+/// Get metadata, price history and fund exposure of one asset
+///
+/// Combines the engine's instrument metadata with the look-through row of
+/// `get_true_holdings`, so a true-holdings row can open a detail view.
+#[tauri::command]
+pub async fn get_asset_details(
+    app_handle: AppHandle,
+    isin: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AssetDetails, String> {
+    let isin = validate_isin(&isin)?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let data = engine.request("get_true_holdings", json!({})).await?;
+    let look_through: LookThroughResponse =
+        protocol::parse(&app_handle, "get_true_holdings", data)?;
+    let row = look_through
+        .holdings
+        .into_iter()
+        .find(|holding| holding.isin.as_deref() == Some(isin.as_str()));
+
+    // Metadata is best-effort; the look-through part is still useful without it
+    let metadata = engine
+        .request("get_asset_details", json!({ "isin": isin }))
+        .await
+        .and_then(|data| protocol::parse::<AssetMetadata>(&app_handle, "get_asset_details", data));
+    let (metadata, metadata_error) = match metadata {
+        Ok(metadata) => (metadata, None),
+        Err(e) => (AssetMetadata::default(), Some(e)),
+    };
+
+    if row.is_none() && metadata_error.is_some() {
+        return Err(format!("No data for {}", isin));
+    }
+
+    let mut contained_in = row.as_ref().map(|row| row.sources.clone()).unwrap_or_default();
+    contained_in.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+    Ok(AssetDetails {
+        name: metadata.name.or_else(|| row.as_ref().map(|row| row.stock.clone())),
+        ticker: metadata
+            .ticker
+            .or_else(|| row.as_ref().map(|row| row.ticker.clone()).filter(|t| !t.is_empty())),
+        instrument_type: metadata.instrument_type,
+        currency: metadata.currency,
+        sector: metadata.sector.or_else(|| row.as_ref().and_then(|row| row.sector.clone())),
+        country: metadata
+            .country
+            .or_else(|| row.as_ref().and_then(|row| row.geography.clone())),
+        price_history: metadata.price_history,
+        total_value: row.as_ref().map(|row| row.total_value).unwrap_or(0.0),
+        contained_in,
+        resolution_status: row.and_then(|row| row.resolution_status),
+        metadata_error,
+        isin,
+    })
+}
+
+/// Get overlap analysis
+///
+/// The response is annotated with `uncertainty.fundsMissingPercent` so the UI
+/// can widen each pairwise overlap by the two funds' unknown constituent
+/// shares instead of presenting it as exact; `get_overlap_matrix` returns the
+/// same range per pair as `overlapBound`.
+#[tauri::command]
+pub async fn get_overlap_analysis(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    match engine.send_command("get_overlap_analysis", json!({})).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(mut data) = response.data {
+                    if let (Some(object), Ok(data_dir)) =
+                        (data.as_object_mut(), store::data_dir(&app_handle))
+                    {
+                        object.insert(
+                            "uncertainty".to_string(),
+                            json!({
+                                "fundsMissingPercent":
+                                    data_quality::missing_constituent_percent(&data_dir),
+                            }),
+                        );
+                    }
+                    return Ok(data);
+                }
+            }
+            if let Some(err) = response.error {
+                return Err(err.message);
+            }
+            Err("Unknown error getting overlap analysis".to_string())
+        }
+        Err(e) => Err(format!("Failed to get overlap analysis: {}", e)),
+    }
+}
+
+/// Get the pairwise overlap of the ETFs in a portfolio
+///
+/// The funds are the portfolio's ETF positions, compared on the look-through
+/// of the last pipeline run; see `overlap_matrix`. Each pair lists its largest
+/// shared constituents so the UI can show why two funds duplicate each other.
+#[tauri::command]
+pub async fn get_overlap_matrix(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<OverlapMatrix, String> {
+    sandbox::reject(portfolio_id, "analyzed by the pipeline")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let positions = portfolio::fetch_positions(&app_handle, &engine, portfolio_id).await?;
+    let look_through = engine.request("get_true_holdings", json!({})).await?;
+    overlap_of(&app_handle, &positions.positions, look_through)
+}
+
+/// Overlap matrix of the ETFs among `positions` from a `get_true_holdings`
+/// response; shared by `get_overlap_matrix`, the XLSX export and the
+/// progressive dashboard.
+pub(crate) fn overlap_of(
+    app_handle: &AppHandle,
+    positions: &[portfolio::Position],
+    look_through: serde_json::Value,
+) -> Result<OverlapMatrix, String> {
+    let funds: Vec<(String, String)> = positions
+        .iter()
+        .filter(|position| position.instrument_type.eq_ignore_ascii_case("etf"))
+        .map(|position| (position.isin.clone(), position.name.clone()))
+        .collect();
+
+    let look_through: LookThroughResponse =
+        protocol::parse(app_handle, "get_true_holdings", look_through)?;
+    let missing = store::data_dir(app_handle)
+        .map(|data_dir| data_quality::missing_constituent_percent(&data_dir))
+        .unwrap_or_default();
+
+    Ok(overlap_matrix::build(
+        &funds,
+        look_through.holdings.iter().map(|holding| Constituent {
+            isin: holding.isin.as_deref(),
+            name: &holding.stock,
+            sources: &holding.sources,
+        }),
+        &missing,
+    ))
+}
+
+/// Get backend reports that still require review.
+#[tauri::command]
+pub async fn get_pending_reviews(
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    match engine.send_command("get_pending_reviews", json!({})).await {
+        Ok(response) => {
+            if response.success {
+                return Ok(response
+                    .data
+                    .unwrap_or_else(|| serde_json::Value::Array(vec![])));
+            }
+
+            Err(response
+                .error
+                .map(|e| e.message)
+                .unwrap_or_else(|| "Failed to fetch pending reviews".to_string()))
+        }
+        Err(e) => Err(format!("Failed to fetch pending reviews: {}", e)),
+    }
+}
+
+/// Rows per `upload_holdings_chunk` command
+const UPLOAD_BATCH_ROWS: usize = 1000;
+
+/// CSV files with more lines are parsed in the shell and streamed in batches
+const STREAM_MIN_CSV_LINES: usize = 10_000;
+
+/// `upload-progress` event payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadProgress {
+    pub upload_id: String,
+    pub etf_isin: String,
+    /// `converting`, `sending`, `processing`, `done` or `failed`
+    pub stage: &'static str,
+    pub sent_rows: usize,
+    pub total_rows: usize,
+    pub percent: f64,
+}
+
+/// Whether the shell parses the file instead of the engine: Excel workbooks
+/// always, CSV files only when large enough to stream. Blocking; reads no
+/// further than the line threshold.
+fn converted_in_shell(path: &std::path::Path) -> bool {
+    if holdings_file::is_spreadsheet(path) {
+        return true;
+    }
+    let is_csv = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    is_csv
+        && std::fs::File::open(path).is_ok_and(|file| {
+            std::io::BufRead::split(std::io::BufReader::new(file), b'\n')
+                .nth(STREAM_MIN_CSV_LINES)
+                .is_some()
+        })
+}
+
+/// Send a holdings file to the engine with `command`. Files converted in the
+/// shell travel as rows, in `upload_holdings_chunk` batches when there are
+/// more than `UPLOAD_BATCH_ROWS`; the final command then references the
+/// batches by `uploadId`. Progress is emitted as `upload-progress`.
+async fn send_holdings_file(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    command: &str,
+    file_path: String,
+    etf_isin: String,
+) -> Result<serde_json::Value, String> {
+    let path = std::path::PathBuf::from(&file_path);
+    let converted = {
+        let path = path.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            converted_in_shell(&path)
+                .then(|| holdings_file::convert(&path))
+                .transpose()
+        })
+        .await
+        .map_err(|e| format!("Holdings file conversion failed: {}", e))??
+    };
+    let Some(sheet) = converted else {
+        let payload = json!({ "filePath": file_path, "etfIsin": etf_isin });
+        return engine.request(command, payload).await;
+    };
+
+    let total_rows = sheet.holdings.len();
+    let mut progress = UploadProgress {
+        upload_id: format!("{}-{}", etf_isin, chrono::Utc::now().timestamp_millis()),
+        etf_isin: etf_isin.clone(),
+        stage: "converting",
+        sent_rows: 0,
+        total_rows,
+        percent: 0.0,
+    };
+    let emit = |progress: &UploadProgress| {
+        let _ = app_handle.emit("upload-progress", progress);
+    };
+    emit(&progress);
+
+    let mut payload = json!({ "fileName": file_path, "etfIsin": etf_isin });
+    if total_rows <= UPLOAD_BATCH_ROWS {
+        payload["holdings"] = json!(sheet.holdings);
+    } else {
+        let batches = total_rows.div_ceil(UPLOAD_BATCH_ROWS);
+        progress.stage = "sending";
+        for (sequence, batch) in sheet.holdings.chunks(UPLOAD_BATCH_ROWS).enumerate() {
+            let chunk = json!({
+                "uploadId": progress.upload_id,
+                "etfIsin": etf_isin,
+                "sequence": sequence,
+                "batches": batches,
+                "holdings": batch,
+            });
+            if let Err(e) = engine.request("upload_holdings_chunk", chunk).await {
+                progress.stage = "failed";
+                emit(&progress);
+                return Err(format!(
+                    "Failed to send holdings batch {}: {}",
+                    sequence + 1,
+                    e
+                ));
+            }
+            progress.sent_rows += batch.len();
+            progress.percent = progress.sent_rows as f64 / total_rows as f64 * 90.0;
+            emit(&progress);
+        }
+        payload["uploadId"] = json!(progress.upload_id);
+        payload["rowCount"] = json!(total_rows);
+    }
+
+    progress.stage = "processing";
+    progress.sent_rows = total_rows;
+    progress.percent = 90.0;
+    emit(&progress);
+    let result = engine.request(command, payload).await;
+    progress.stage = if result.is_ok() { "done" } else { "failed" };
+    progress.percent = 100.0;
+    emit(&progress);
+
+    let mut data = result?;
+    if let Some(object) = data.as_object_mut() {
+        object.insert(
+            "conversion".to_string(),
+            json!({
+                "sheet": sheet.sheet,
+                "headerRow": sheet.header_row,
+                "columns": sheet.columns,
+                "skipped": sheet.skipped,
+            }),
+        );
+    }
+    Ok(data)
+}
+
+/// Upload manual ETF holdings
+///
+/// Validates file path and ISIN format before forwarding to Python engine.
+/// Excel workbooks (first sheet) and large CSV files are converted to rows
+/// in the shell and streamed in batches with `upload-progress` events.
+#[tauri::command]
+pub async fn upload_holdings(
+    app_handle: AppHandle,
+    file_path: String,
+    etf_isin: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    // Validate ISIN format before processing
+    let validated_isin = validate_isin(&etf_isin)?;
+
+    // Validate file path
+    let validated_path = validate_file_path(&file_path)?;
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    send_holdings_file(
+        &app_handle,
+        &engine,
+        "upload_holdings",
+        validated_path,
+        validated_isin,
+    )
+    .await
+    .map_err(|e| format!("Failed to upload holdings: {}", e))
+}
+
+/// Generate a preview for a holdings upload without saving it.
+#[tauri::command]
+pub async fn preview_holdings_upload(
+    app_handle: AppHandle,
+    file_path: String,
+    etf_isin: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    let validated_isin = validate_isin(&etf_isin)?;
+    let validated_path = validate_file_path(&file_path)?;
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    send_holdings_file(
+        &app_handle,
+        &engine,
+        "preview_holdings_upload",
+        validated_path,
+        validated_isin,
+    )
+    .await
+    .map_err(|e| format!("Failed to preview holdings upload: {}", e))
+}
+
+/// Check a CSV or Excel holdings file before uploading it: detected
+/// columns, row counts, unreadable rows and the suggested column mapping.
+/// Nothing is written and the engine is not involved.
+#[tauri::command]
+pub async fn validate_holdings_file(file_path: String) -> Result<HoldingsFileReport, String> {
+    let validated_path = validate_file_path(&file_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        holdings_file::validate(std::path::Path::new(&validated_path))
+    })
+    .await
+    .map_err(|e| format!("Holdings file check failed: {}", e))?
+}
+
+/// Persist reviewed holdings to the cache after user confirmation.
+#[tauri::command]
+pub async fn commit_holdings_upload(
+    etf_isin: String,
+    holdings: Vec<ManualHoldingDraft>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    let validated_isin = validate_isin(&etf_isin)?;
+
+    if holdings.is_empty() {
+        return Err("At least one holding is required".to_string());
+    }
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({
+        "etfIsin": validated_isin,
+        "holdings": holdings
+    });
+
+    match engine.send_command("commit_holdings_upload", payload).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    return Ok(data);
+                }
+            }
+            if let Some(err) = response.error {
+                return Err(err.message);
+            }
+            Err("Unknown error committing holdings upload".to_string())
+        }
+        Err(e) => Err(format!("Failed to commit holdings upload: {}", e)),
+    }
+}
+
+/// Open the native macOS file picker for holdings uploads.
+#[tauri::command]
+pub fn pick_holdings_file() -> Result<String, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        let output = Command::new("osascript")
+            .args([
+                "-e",
+                "try",
+                "-e",
+                "POSIX path of (choose file with prompt \"Select a holdings file\")",
+                "-e",
+                "on error number -128",
+                "-e",
+                "return \"\"",
+                "-e",
+                "end try",
+            ])
+            .output()
+            .map_err(|e| format!("Failed to open native file picker: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(if stderr.is_empty() {
+                "Native file picker failed".to_string()
+            } else {
+                format!("Native file picker failed: {}", stderr)
+            });
+        }
+
+        let selected_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if selected_path.is_empty() {
+            return Err("File selection was cancelled".to_string());
+        }
+
+        validate_file_path(&selected_path)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err("Native holdings file picker is only implemented on macOS".to_string())
+    }
+}
+
+register_commands! {
+    run_pipeline,
+    run_pipeline_stage,
+    cancel_pipeline,
+    get_pipeline_report,
+    get_pipeline_history,
+    get_true_holdings,
+    get_overlap_analysis,
+    get_overlap_matrix,
+    get_pending_reviews,
+    upload_holdings,
+    preview_holdings_upload,
+    validate_holdings_file,
+    commit_holdings_upload,
+    pick_holdings_file,
+    get_pipeline_config,
+    get_pipeline_capabilities,
+    update_pipeline_config,
+    get_asset_details,
+    list_pipeline_snapshots,
+    explain_changes,
+}
@@ -0,0 +1,2012 @@
+//! Portfolio Commands
+//!
+//! Dashboard, positions, transactions and sync, portfolio management,
+//! sandbox trading, portfolio analytics, benchmarks, broker imports, price
+//! and event alerts and exports.
+
+use super::pipeline;
+use super::{validate_date, validate_file_path, validate_isin};
+use crate::benchmarks::{
+    self, Benchmark, BenchmarkComponent, BenchmarkReturn, BenchmarkSeries,
+};
+use crate::broker_import::{self, BrokerStatement, ImportFormat};
+use crate::closed_positions::{self, ClosedPosition};
+use crate::dashboard_assembly;
+use crate::data_quality::{self, AllocationBounds, DataQuality};
+use crate::db_reader;
+use crate::email::{self, DeliveryKind};
+use crate::event_alerts::{self, DailyDigest, EventAlertRule, EventKind};
+use crate::feature_flags::FeatureFlags;
+use crate::fx_rates::{self, FxRates};
+use crate::insights::{self, MonthlyInsights};
+use crate::instrument_lifecycle::{self, DelistingAction, InstrumentRecord};
+use crate::mock_data;
+use crate::network;
+use crate::pipeline_config;
+use crate::positions_delta;
+use crate::price_alerts::{self, AlertCondition, PriceAlert};
+use crate::protocol;
+use crate::python_engine::PythonEngine;
+use crate::quote_fallback::{self, MissingQuote, QuoteRefresh};
+use crate::response_cache;
+use crate::sandbox::{self, SandboxTrade, SyncedPrice, TradeSide};
+use crate::store;
+use crate::sync_queue;
+use crate::taskbar_progress;
+use crate::turnover::{self, TurnoverMetrics};
+use crate::value_waterfall::{self, ValueWaterfall, WaterfallRange};
+use crate::xlsx_export::{self, ExportData, ExportSummary};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::oneshot;
+
+// =============================================================================
+// Response Types (match TypeScript types in src/types/index.ts)
+// =============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holding {
+    pub isin: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    pub value: f64,
+    pub weight: f64,
+    pub pnl: f64,
+    pub pnl_percentage: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_class: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Allocations {
+    pub sector: std::collections::HashMap<String, f64>,
+    pub region: std::collections::HashMap<String, f64>,
+    #[serde(default)]
+    pub asset_class: std::collections::HashMap<String, f64>,
+    /// Look-through ranges reflecting undecomposed funds (set by the shell)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<AllocationBounds>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPoint {
+    pub date: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardData {
+    pub total_value: f64,
+    pub total_gain: f64,
+    pub gain_percentage: f64,
+    pub day_change: f64,
+    pub day_change_percent: f64,
+    #[serde(default)]
+    pub history: Vec<HistoryPoint>,
+    pub allocations: Allocations,
+    pub top_holdings: Vec<Holding>,
+    pub last_updated: Option<String>,
+    #[serde(default)]
+    pub is_empty: bool,
+    #[serde(default)]
+    pub position_count: u32,
+    /// Trust indicator assembled by the shell (not sent by the engine)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_quality: Option<DataQuality>,
+    /// Read from the database by the shell while the engine was down
+    #[serde(default)]
+    pub stale: bool,
+}
+
+// Note: SyncResult was replaced by PortfolioSyncResult
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioSyncResult {
+    pub synced_positions: u32,
+    pub new_positions: u32,
+    pub updated_positions: u32,
+    pub total_value: f64,
+    pub duration_ms: u32,
+    /// Positions that could not be synced; empty on full success
+    #[serde(default)]
+    pub failures: Vec<PositionSyncFailure>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionSyncFailure {
+    pub isin: String,
+    pub reason: String,
+    #[serde(default)]
+    pub retryable: bool,
+}
+
+/// Payload of the `sync-partial` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPartial {
+    pub portfolio_id: u32,
+    pub synced_positions: u32,
+    pub failures: Vec<PositionSyncFailure>,
+    /// Retry attempt that produced this result (0 = the original sync)
+    pub attempt: u32,
+    /// Whether the shell will retry the retryable failures
+    pub retry_scheduled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    pub status: String,
+    pub progress: u8,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub isin: String,
+    pub name: String,
+    #[serde(default)]
+    pub ticker: String,
+    pub instrument_type: String,
+    pub quantity: f64,
+    pub avg_buy_price: f64,
+    pub current_price: f64,
+    pub current_value: f64,
+    pub total_cost: f64,
+    pub pnl_eur: f64,
+    pub pnl_percent: f64,
+    pub weight: f64,
+    pub currency: String,
+    #[serde(default)]
+    pub notes: String,
+    pub last_updated: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionsResponse {
+    pub positions: Vec<Position>,
+    pub total_value: f64,
+    pub total_cost: f64,
+    pub total_pnl: f64,
+    pub total_pnl_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sync_time: Option<String>,
+    /// Read from the database by the shell while the engine was down
+    #[serde(default)]
+    pub stale: bool,
+}
+
+// =============================================================================
+// Transaction Types
+// =============================================================================
+
+/// Transaction types recorded in the engine's ledger
+const TRANSACTION_TYPES: &[&str] = &["Buy", "Sell", "Dividend", "Interest", "Fee", "Transfer"];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub id: String,
+    pub portfolio_id: u32,
+    pub isin: String,
+    #[serde(rename = "type")]
+    pub transaction_type: String,
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f64>,
+    pub amount: f64,
+    pub currency: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsResponse {
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PortfolioKind {
+    /// Synced or imported holdings stored by the engine
+    #[default]
+    Real,
+    /// Hypothetical trades kept by the shell (see `sandbox`)
+    Sandbox,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Portfolio {
+    pub id: u32,
+    pub name: String,
+    pub currency: String,
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub kind: PortfolioKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfoliosResponse {
+    pub portfolios: Vec<Portfolio>,
+}
+
+// =============================================================================
+// Commands
+// =============================================================================
+
+/// Get dashboard data for a portfolio
+#[tauri::command]
+pub async fn get_dashboard_data(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<DashboardData, String> {
+    if sandbox::is_sandbox(portfolio_id) {
+        let valued = sandbox_positions(&app_handle, &engine, portfolio_id).await?;
+        return Ok(sandbox::dashboard(&valued));
+    }
+
+    if !engine.is_connected().await {
+        let data_dir = store::data_dir(&app_handle)?;
+        match db_reader::dashboard(&data_dir, portfolio_id) {
+            Ok(mut dashboard) => {
+                fx_rates::convert_dashboard(&data_dir, &mut dashboard);
+                return Ok(dashboard);
+            }
+            Err(e) => tracing::warn!("Stale dashboard unavailable: {}", e),
+        }
+        if mock_data::enabled(&flags) {
+            return protocol::parse(&app_handle, "get_dashboard_data", mock_data::dashboard());
+        }
+        return Err(engine.unavailable().into());
+    }
+
+    match engine
+        .send_command("get_dashboard_data", json!({"portfolioId": portfolio_id}))
+        .await
+    {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    let dashboard: Result<DashboardData, _> =
+                        protocol::parse(&app_handle, "get_dashboard_data", data);
+                    match dashboard {
+                        Ok(mut d) => {
+                            if let Ok(data_dir) = store::data_dir(&app_handle) {
+                                d.data_quality = data_quality::summarize(&data_dir)
+                                    .map_err(|e| tracing::warn!("Failed to assemble data quality: {}", e))
+                                    .ok();
+
+                                let etf_weights = load_positions(&app_handle, &engine, portfolio_id)
+                                    .await
+                                    .map(|positions| position_weights(&positions))
+                                    .unwrap_or_else(|e| {
+                                        tracing::warn!("Failed to weigh positions: {}", e);
+                                        Default::default()
+                                    });
+                                d.allocations.bounds = Some(data_quality::allocation_bounds(
+                                    &data_dir,
+                                    &etf_weights,
+                                    &d.allocations.sector,
+                                    &d.allocations.region,
+                                ));
+                                dashboard_assembly::remember(&data_dir, portfolio_id, &d);
+                                // After `remember`, which keeps the engine's euros
+                                fx_rates::convert_dashboard(&data_dir, &mut d);
+                            }
+                            return Ok(d);
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                return Err("No data in dashboard response".to_string());
+            }
+
+            Err(response
+                .error
+                .map(|e| e.message)
+                .unwrap_or_else(|| "Failed to load dashboard data".to_string()))
+        }
+        Err(e) => Err(format!("Failed to get dashboard data: {}", e)),
+    }
+}
+
+/// Assemble the dashboard progressively.
+///
+/// Returns an assembly id immediately; sections arrive as `dashboard-partial`
+/// events tagged with that id, ending with a `complete` marker.
+#[tauri::command]
+pub async fn assemble_dashboard(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<u64, String> {
+    flags.require("progressive_dashboard")?;
+
+    Ok(dashboard_assembly::start(
+        app_handle,
+        engine.inner().clone(),
+        portfolio_id,
+    ))
+}
+
+/// Get all positions for a portfolio (full data for the table)
+#[tauri::command]
+pub async fn get_positions(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<PositionsResponse, String> {
+    if !sandbox::is_sandbox(portfolio_id) && !engine.is_connected().await {
+        let data_dir = store::data_dir(&app_handle)?;
+        match db_reader::positions(&data_dir, portfolio_id) {
+            Ok(mut positions) => {
+                fx_rates::convert_positions(&data_dir, &mut positions);
+                return Ok(positions);
+            }
+            Err(e) => tracing::warn!("Stale positions unavailable: {}", e),
+        }
+        if mock_data::enabled(&flags) {
+            return protocol::parse(&app_handle, "get_positions", mock_data::positions());
+        }
+        return Err(engine.unavailable().into());
+    }
+
+    let positions = fetch_positions(&app_handle, &engine, portfolio_id).await?;
+    positions_delta::remember(portfolio_id, &positions);
+    Ok(positions)
+}
+
+/// Current positions of a portfolio from the sandbox or the engine in the
+/// display currency, without the fallbacks `get_positions` applies while the
+/// engine is down
+pub(crate) async fn fetch_positions(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    portfolio_id: u32,
+) -> Result<PositionsResponse, String> {
+    let mut positions = load_positions(app_handle, engine, portfolio_id).await?;
+    if let Ok(data_dir) = store::data_dir(app_handle) {
+        fx_rates::convert_positions(&data_dir, &mut positions);
+    }
+    Ok(positions)
+}
+
+/// Portfolio weight of every position as a fraction (0-1), by ISIN
+fn position_weights(positions: &PositionsResponse) -> std::collections::HashMap<String, f64> {
+    if positions.total_value <= 0.0 {
+        return Default::default();
+    }
+    let mut weights = std::collections::HashMap::new();
+    for position in &positions.positions {
+        *weights.entry(position.isin.clone()).or_insert(0.0) +=
+            position.current_value / positions.total_value;
+    }
+    weights
+}
+
+async fn load_positions(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    portfolio_id: u32,
+) -> Result<PositionsResponse, String> {
+    if sandbox::is_sandbox(portfolio_id) {
+        return sandbox_positions(app_handle, engine, portfolio_id).await;
+    }
+
+    match engine
+        .send_command("get_positions", json!({"portfolioId": portfolio_id}))
+        .await
+    {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    return protocol::parse(app_handle, "get_positions", data);
+                }
+            }
+            if let Some(err) = response.error {
+                return Err(err.message);
+            }
+            Err("Unknown error getting positions".to_string())
+        }
+        Err(e) => Err(format!("Failed to get positions: {}", e)),
+    }
+}
+
+/// Get trade, dividend and other ledger entries, optionally filtered
+#[tauri::command]
+pub async fn get_transactions(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    from: Option<String>,
+    to: Option<String>,
+    types: Option<Vec<String>>,
+    isin: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<TransactionsResponse, String> {
+    let from_date = from.as_deref().map(validate_date).transpose()?;
+    let to_date = to.as_deref().map(validate_date).transpose()?;
+    if let (Some(from_date), Some(to_date)) = (from_date, to_date) {
+        if from_date > to_date {
+            return Err("Start date must not be after end date".to_string());
+        }
+    }
+    if let Some(unknown) = types
+        .iter()
+        .flatten()
+        .find(|t| !TRANSACTION_TYPES.contains(&t.as_str()))
+    {
+        return Err(format!("Unknown transaction type: {}", unknown));
+    }
+    let isin = isin.as_deref().map(validate_isin).transpose()?;
+
+    if sandbox::is_sandbox(portfolio_id) {
+        let data_dir = store::data_dir(&app_handle)?;
+        let transactions = sandbox::transactions(&data_dir, portfolio_id)?
+            .into_iter()
+            .filter(|t| {
+                let day = t.date.get(..10).unwrap_or_default();
+                from.as_deref().is_none_or(|from| day >= from)
+                    && to.as_deref().is_none_or(|to| day <= to)
+                    && types.as_ref().is_none_or(|types| types.contains(&t.transaction_type))
+                    && isin.as_ref().is_none_or(|isin| t.isin == *isin)
+            })
+            .collect();
+        return Ok(TransactionsResponse { transactions });
+    }
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({
+        "portfolioId": portfolio_id,
+        "from": from,
+        "to": to,
+        "types": types,
+        "isin": isin,
+    });
+
+    match engine.send_command("get_transactions", payload).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    protocol::parse(&app_handle, "get_transactions", data)
+                } else {
+                    Err("No data in transactions response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Failed to load transactions".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to get transactions: {}", e)),
+    }
+}
+
+/// Trigger portfolio sync with real Trade Republic data
+#[tauri::command]
+pub async fn sync_portfolio(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    force: bool,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<PortfolioSyncResult, String> {
+    sandbox::reject(portfolio_id, "synced")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+    // Offline: run it when the connection returns instead of failing
+    if !network::is_online() && !network::check_now(&app_handle).await {
+        return Err(sync_queue::enqueue(&app_handle, portfolio_id, force));
+    }
+
+    sync_coalesced(&app_handle, engine.inner(), portfolio_id, force).await
+}
+
+/// Full sync waiting on the engine and the callers that joined it
+struct InFlightSync {
+    portfolio_id: u32,
+    waiters: Vec<oneshot::Sender<Result<PortfolioSyncResult, String>>>,
+}
+
+static IN_FLIGHT_SYNC: Mutex<Option<InFlightSync>> = Mutex::new(None);
+
+/// Latest `sync-progress` payload of the in-flight sync
+static LAST_SYNC_PROGRESS: Mutex<Option<serde_json::Value>> = Mutex::new(None);
+
+/// Whether a full portfolio sync is running; scheduled syncs skip then.
+pub(crate) fn sync_in_progress() -> bool {
+    IN_FLIGHT_SYNC.lock().is_ok_and(|sync| sync.is_some())
+}
+
+/// Remember the latest sync progress so joining callers can catch up.
+pub(crate) fn record_sync_progress(progress: &serde_json::Value) {
+    if let Ok(mut last) = LAST_SYNC_PROGRESS.lock() {
+        *last = Some(progress.clone());
+    }
+}
+
+/// Sync a portfolio, joining an in-flight sync of the same portfolio instead
+/// of sending a second `sync_portfolio`: the engine cannot run two at once.
+/// A joining caller gets the latest progress re-emitted and the shared
+/// result. A sync of another portfolio in flight is an error.
+pub(crate) async fn sync_coalesced(
+    app_handle: &AppHandle,
+    engine: &Arc<PythonEngine>,
+    portfolio_id: u32,
+    force: bool,
+) -> Result<PortfolioSyncResult, String> {
+    let waiter = {
+        let mut in_flight = IN_FLIGHT_SYNC.lock().map_err(|e| e.to_string())?;
+        match in_flight.as_mut() {
+            Some(sync) if sync.portfolio_id == portfolio_id => {
+                let (tx, rx) = oneshot::channel();
+                sync.waiters.push(tx);
+                Some(rx)
+            }
+            Some(sync) => {
+                return Err(format!(
+                    "Portfolio {} is already syncing; try again when it finishes",
+                    sync.portfolio_id
+                ));
+            }
+            None => {
+                *in_flight = Some(InFlightSync {
+                    portfolio_id,
+                    waiters: vec![],
+                });
+                if let Ok(mut last) = LAST_SYNC_PROGRESS.lock() {
+                    *last = None;
+                }
+                None
+            }
+        }
+    };
+
+    if let Some(rx) = waiter {
+        let progress = LAST_SYNC_PROGRESS.lock().ok().and_then(|last| last.clone());
+        if let Some(progress) = progress {
+            let _ = app_handle.emit("sync-progress", progress);
+        }
+        return rx
+            .await
+            .unwrap_or_else(|_| Err("The running sync was dropped".to_string()));
+    }
+
+    let result = run_sync(app_handle, engine, portfolio_id, force).await;
+    taskbar_progress::sync_finished(app_handle);
+    let waiters = IN_FLIGHT_SYNC
+        .lock()
+        .ok()
+        .and_then(|mut in_flight| in_flight.take())
+        .map(|sync| sync.waiters)
+        .unwrap_or_default();
+    for waiter in waiters {
+        let _ = waiter.send(result.clone());
+    }
+    result
+}
+
+/// Sync a portfolio and run the post-sync bookkeeping (instrument tracking,
+/// closed positions, partial-sync retries, `portfolio-updated`, cache
+/// prefetch).
+pub(crate) async fn run_sync(
+    app_handle: &AppHandle,
+    engine: &Arc<PythonEngine>,
+    portfolio_id: u32,
+    force: bool,
+) -> Result<PortfolioSyncResult, String> {
+    // Likely-delisted, archived and replaced instruments are not refreshed
+    let data_dir = store::data_dir(app_handle)?;
+    let payload = json!({
+        "portfolioId": portfolio_id,
+        "force": force,
+        "skipIsins": instrument_lifecycle::paused_isins(&data_dir)?,
+        "isinMappings": instrument_lifecycle::isin_mappings(&data_dir)?,
+    });
+
+    // Events from Python (sync_progress) are handled in lib.rs stdout loop
+    // and emitted as Tauri events automatically
+
+    match engine.send_command("sync_portfolio", payload).await {
+        Ok(response) => {
+            if response.success {
+                if let Some(data) = response.data {
+                    let sync_result: Result<PortfolioSyncResult, _> =
+                        protocol::parse(app_handle, "sync_portfolio", data);
+                    match sync_result {
+                        Ok(result) => {
+                            track_instrument_failures(app_handle, &result);
+                            archive_closed_positions(app_handle, engine.clone(), portfolio_id);
+                            if !result.failures.is_empty() {
+                                report_partial_sync(
+                                    app_handle,
+                                    engine.clone(),
+                                    portfolio_id,
+                                    &result,
+                                    0,
+                                );
+                            }
+
+                            // Emit portfolio-updated event
+                            #[derive(Clone, Serialize)]
+                            #[serde(rename_all = "camelCase")]
+                            struct PortfolioUpdated {
+                                timestamp: String,
+                                portfolio_id: u32,
+                            }
+
+                            let _ = app_handle.emit(
+                                "portfolio-updated",
+                                PortfolioUpdated {
+                                    timestamp: chrono::Utc::now().to_rfc3339(),
+                                    portfolio_id,
+                                },
+                            );
+                            // After the event, whose listeners clear the cache
+                            response_cache::prefetch(engine.clone(), portfolio_id);
+
+                            Ok(result)
+                        }
+                        Err(e) => Err(e),
+                    }
+                } else {
+                    Err("No data in sync response".to_string())
+                }
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Sync failed".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to sync portfolio: {}", e)),
+    }
+}
+
+/// Maximum number of targeted retries for positions that failed to sync
+const SYNC_RETRY_MAX_ATTEMPTS: u32 = 3;
+
+/// Base delay between targeted retries (doubles each attempt)
+const SYNC_RETRY_BASE_DELAY_SECS: u64 = 30;
+
+/// Emit `sync-partial` and schedule a targeted retry for retryable failures.
+fn report_partial_sync(
+    app_handle: &AppHandle,
+    engine: Arc<PythonEngine>,
+    portfolio_id: u32,
+    result: &PortfolioSyncResult,
+    attempt: u32,
+) {
+    let paused = store::data_dir(app_handle)
+        .and_then(|dir| instrument_lifecycle::paused_isins(&dir))
+        .unwrap_or_default();
+    let retry_isins: Vec<String> = result
+        .failures
+        .iter()
+        .filter(|failure| failure.retryable && !paused.contains(&failure.isin))
+        .map(|failure| failure.isin.clone())
+        .collect();
+    let retry_scheduled = !retry_isins.is_empty() && attempt < SYNC_RETRY_MAX_ATTEMPTS;
+
+    let _ = app_handle.emit(
+        "sync-partial",
+        SyncPartial {
+            portfolio_id,
+            synced_positions: result.synced_positions,
+            failures: result.failures.clone(),
+            attempt,
+            retry_scheduled,
+        },
+    );
+
+    if retry_scheduled {
+        schedule_sync_retry(app_handle.clone(), engine, portfolio_id, retry_isins, attempt + 1);
+    }
+}
+
+/// Archive positions that were fully sold, in the background.
+fn archive_closed_positions(app_handle: &AppHandle, engine: Arc<PythonEngine>, portfolio_id: u32) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = closed_positions::reconcile(&app_handle, &engine, portfolio_id).await {
+            tracing::warn!("Failed to archive closed positions: {}", e);
+        }
+    });
+}
+
+/// Update delisting failure streaks after a full sync and announce
+/// instruments that just crossed the threshold.
+fn track_instrument_failures(app_handle: &AppHandle, result: &PortfolioSyncResult) {
+    let failures: Vec<(String, String)> = result
+        .failures
+        .iter()
+        .map(|failure| (failure.isin.clone(), failure.reason.clone()))
+        .collect();
+
+    match store::data_dir(app_handle)
+        .and_then(|dir| instrument_lifecycle::record_sync(&dir, None, &failures))
+    {
+        Ok(flagged) if !flagged.is_empty() => {
+            let _ = app_handle.emit("instruments-delisted", json!({ "isins": flagged }));
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to record instrument sync failures: {}", e),
+    }
+}
+
+/// Reset the failure streaks of `isins` that a targeted sync refreshed.
+fn track_targeted_sync(app_handle: &AppHandle, isins: &[String], result: &PortfolioSyncResult) {
+    let succeeded: Vec<String> = isins
+        .iter()
+        .filter(|isin| !result.failures.iter().any(|failure| failure.isin == **isin))
+        .cloned()
+        .collect();
+    if let Err(e) = store::data_dir(app_handle)
+        .and_then(|dir| instrument_lifecycle::record_sync(&dir, Some(&succeeded), &[]))
+    {
+        tracing::warn!("Failed to record instrument sync results: {}", e);
+    }
+}
+
+/// Re-sync only `isins` after a backoff delay.
+fn schedule_sync_retry(
+    app_handle: AppHandle,
+    engine: Arc<PythonEngine>,
+    portfolio_id: u32,
+    isins: Vec<String>,
+    attempt: u32,
+) {
+    tauri::async_runtime::spawn(async move {
+        let delay = SYNC_RETRY_BASE_DELAY_SECS * 2u64.pow(attempt - 1);
+        tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+
+        if !engine.is_connected().await {
+            tracing::warn!("Skipping sync retry {}: engine not connected", attempt);
+            return;
+        }
+        // The engine cannot run a second sync alongside a full one
+        if sync_in_progress() {
+            tracing::warn!("Skipping sync retry {}: a sync is running", attempt);
+            return;
+        }
+
+        let payload = json!({
+            "portfolioId": portfolio_id,
+            "force": true,
+            "isins": isins
+        });
+
+        let response = match engine.send_command("sync_portfolio", payload).await {
+            Ok(response) if response.success => response,
+            Ok(response) => {
+                tracing::warn!(
+                    "Sync retry {} failed: {}",
+                    attempt,
+                    response.error.map(|e| e.message).unwrap_or_default()
+                );
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("Sync retry {} failed: {}", attempt, e);
+                return;
+            }
+        };
+
+        let result: PortfolioSyncResult = match response
+            .data
+            .map(|data| protocol::parse(&app_handle, "sync_portfolio", data))
+        {
+            Some(Ok(result)) => result,
+            _ => {
+                tracing::warn!("Failed to parse sync retry result");
+                return;
+            }
+        };
+
+        track_targeted_sync(&app_handle, &isins, &result);
+        let _ = app_handle.emit(
+            "portfolio-updated",
+            json!({
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "portfolioId": portfolio_id,
+            }),
+        );
+
+        if !result.failures.is_empty() {
+            report_partial_sync(&app_handle, engine, portfolio_id, &result, attempt);
+        }
+    });
+}
+
+// =============================================================================
+// Portfolio Management
+// =============================================================================
+
+/// Maximum portfolio name length
+const PORTFOLIO_NAME_MAX_LEN: usize = 64;
+
+fn validate_portfolio_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Portfolio name must not be empty".to_string());
+    }
+    if name.chars().count() > PORTFOLIO_NAME_MAX_LEN {
+        return Err(format!(
+            "Portfolio name must be at most {} characters",
+            PORTFOLIO_NAME_MAX_LEN
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Tell every window that portfolios were created, renamed or deleted.
+fn emit_portfolio_list_changed(app_handle: &AppHandle, action: &str, portfolio_id: u32) {
+    let _ = app_handle.emit(
+        "portfolio-list-changed",
+        json!({ "action": action, "portfolioId": portfolio_id }),
+    );
+}
+
+/// List all portfolios
+#[tauri::command]
+pub async fn list_portfolios(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<Vec<Portfolio>, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let data = engine.request("list_portfolios", json!({})).await?;
+    let response: PortfoliosResponse = protocol::parse(&app_handle, "list_portfolios", data)?;
+    let data_dir = store::data_dir(&app_handle)?;
+    let mut portfolios = response.portfolios;
+    portfolios.extend(sandbox::list(&data_dir)?);
+    Ok(portfolios)
+}
+
+/// Create a portfolio, e.g. to keep a retirement account apart from trading.
+/// Sandbox portfolios are kept by the shell and never reach the engine.
+#[tauri::command]
+pub async fn create_portfolio(
+    app_handle: AppHandle,
+    name: String,
+    currency: Option<String>,
+    kind: Option<PortfolioKind>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<Portfolio, String> {
+    let name = validate_portfolio_name(&name)?;
+    let currency = currency
+        .map(|c| c.trim().to_uppercase())
+        .unwrap_or_else(|| "EUR".to_string());
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Invalid currency code: {}", currency));
+    }
+
+    if kind == Some(PortfolioKind::Sandbox) {
+        let data_dir = store::data_dir(&app_handle)?;
+        let portfolio = sandbox::create(&data_dir, &name, &currency)?;
+        emit_portfolio_list_changed(&app_handle, "created", portfolio.id);
+        return Ok(portfolio);
+    }
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({ "name": name, "currency": currency });
+    let data = engine.request("create_portfolio", payload).await?;
+    let portfolio: Portfolio = protocol::parse(&app_handle, "create_portfolio", data)?;
+    emit_portfolio_list_changed(&app_handle, "created", portfolio.id);
+    Ok(portfolio)
+}
+
+/// Rename a portfolio
+#[tauri::command]
+pub async fn rename_portfolio(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    name: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<Portfolio, String> {
+    let name = validate_portfolio_name(&name)?;
+    if sandbox::is_sandbox(portfolio_id) {
+        let data_dir = store::data_dir(&app_handle)?;
+        let portfolio = sandbox::rename(&data_dir, portfolio_id, &name)?;
+        emit_portfolio_list_changed(&app_handle, "renamed", portfolio_id);
+        return Ok(portfolio);
+    }
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({ "portfolioId": portfolio_id, "name": name });
+    let data = engine.request("rename_portfolio", payload).await?;
+    let portfolio: Portfolio = protocol::parse(&app_handle, "rename_portfolio", data)?;
+    emit_portfolio_list_changed(&app_handle, "renamed", portfolio_id);
+    Ok(portfolio)
+}
+
+/// Delete a portfolio with its positions and transactions. The last
+/// remaining real portfolio cannot be deleted.
+#[tauri::command]
+pub async fn delete_portfolio(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<(), String> {
+    if sandbox::is_sandbox(portfolio_id) {
+        let data_dir = store::data_dir(&app_handle)?;
+        sandbox::delete(&data_dir, portfolio_id)?;
+        emit_portfolio_list_changed(&app_handle, "deleted", portfolio_id);
+        return Ok(());
+    }
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let data = engine.request("list_portfolios", json!({})).await?;
+    let existing: PortfoliosResponse = protocol::parse(&app_handle, "list_portfolios", data)?;
+    if !existing.portfolios.iter().any(|p| p.id == portfolio_id) {
+        return Err(format!("Portfolio {} does not exist", portfolio_id));
+    }
+    if existing.portfolios.len() == 1 {
+        return Err("The last portfolio cannot be deleted".to_string());
+    }
+
+    engine
+        .request("delete_portfolio", json!({ "portfolioId": portfolio_id }))
+        .await?;
+
+    // Shell-side per-portfolio state; the closed-position archive is kept
+    if let Ok(data_dir) = store::data_dir(&app_handle) {
+        closed_positions::forget_snapshot(&data_dir, portfolio_id);
+        dashboard_assembly::forget(&data_dir, portfolio_id);
+        pipeline_config::forget(&data_dir, portfolio_id);
+    }
+
+    emit_portfolio_list_changed(&app_handle, "deleted", portfolio_id);
+    Ok(())
+}
+
+/// Maximum position note length, in characters
+const POSITION_NOTE_MAX_LEN: usize = 2000;
+
+/// Store a position note in the engine and notify open views.
+async fn write_position_note(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    portfolio_id: u32,
+    isin: &str,
+    note: &str,
+) -> Result<(), String> {
+    if note.chars().count() > POSITION_NOTE_MAX_LEN {
+        return Err(format!(
+            "Note must be at most {} characters",
+            POSITION_NOTE_MAX_LEN
+        ));
+    }
+
+    let payload = json!({ "portfolioId": portfolio_id, "isin": isin, "note": note });
+    engine.request("set_position_note", payload).await?;
+    let _ = app_handle.emit(
+        "position-note-changed",
+        json!({ "portfolioId": portfolio_id, "isin": isin, "note": note }),
+    );
+    Ok(())
+}
+
+/// Set or clear (empty string) the note on a position
+#[tauri::command]
+pub async fn set_position_note(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    isin: String,
+    note: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<(), String> {
+    sandbox::reject(portfolio_id, "annotated")?;
+    let isin = validate_isin(&isin)?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    write_position_note(&app_handle, &engine, portfolio_id, &isin, note.trim()).await
+}
+
+// =============================================================================
+// Sandbox Portfolios
+// =============================================================================
+
+/// Current prices of all real positions, keyed by ISIN
+async fn synced_prices(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+) -> Result<HashMap<String, SyncedPrice>, String> {
+    let data = engine.request("list_portfolios", json!({})).await?;
+    let portfolios: PortfoliosResponse = protocol::parse(app_handle, "list_portfolios", data)?;
+
+    let mut prices = HashMap::new();
+    for portfolio in portfolios.portfolios {
+        let payload = json!({ "portfolioId": portfolio.id });
+        let data = engine.request("get_positions", payload).await?;
+        let positions: PositionsResponse = protocol::parse(app_handle, "get_positions", data)?;
+        for position in positions.positions {
+            prices.insert(
+                position.isin,
+                SyncedPrice {
+                    name: position.name,
+                    price: position.current_price,
+                    instrument_type: position.instrument_type,
+                    last_updated: position.last_updated,
+                },
+            );
+        }
+    }
+    Ok(prices)
+}
+
+/// Value a sandbox with synced prices (trade prices only if the engine is down)
+async fn sandbox_positions(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    portfolio_id: u32,
+) -> Result<PositionsResponse, String> {
+    let prices = if engine.is_connected().await {
+        synced_prices(app_handle, engine).await?
+    } else {
+        HashMap::new()
+    };
+    let data_dir = store::data_dir(app_handle)?;
+    sandbox::positions(&data_dir, portfolio_id, &prices)
+}
+
+/// Record a hypothetical trade in a sandbox portfolio. The price defaults to
+/// the current synced price of the instrument.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn record_sandbox_trade(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    isin: String,
+    side: TradeSide,
+    quantity: f64,
+    price: Option<f64>,
+    date: Option<String>,
+    note: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<SandboxTrade, String> {
+    if !sandbox::is_sandbox(portfolio_id) {
+        return Err("Hypothetical trades can only be recorded in sandbox portfolios".to_string());
+    }
+    let isin = validate_isin(&isin)?;
+    let date = match date.as_deref() {
+        Some(date) => validate_date(date)?,
+        None => chrono::Local::now().date_naive(),
+    };
+
+    let synced = if engine.is_connected().await {
+        synced_prices(&app_handle, &engine).await?.remove(&isin)
+    } else {
+        None
+    };
+    let price = price
+        .or_else(|| synced.as_ref().map(|s| s.price))
+        .ok_or_else(|| format!("No synced price for {}; enter a price", isin))?;
+
+    let trade = SandboxTrade {
+        id: 0,
+        isin,
+        name: synced.map(|s| s.name).unwrap_or_default(),
+        side,
+        quantity,
+        price,
+        date: date.to_string(),
+        note: note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+        converted_at: None,
+    };
+    let data_dir = store::data_dir(&app_handle)?;
+    let trade = sandbox::record_trade(&data_dir, portfolio_id, trade)?;
+    let _ = app_handle.emit(
+        "portfolio-updated",
+        json!({ "timestamp": chrono::Utc::now().to_rfc3339(), "portfolioId": portfolio_id }),
+    );
+    Ok(trade)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceSummary {
+    pub portfolio_id: u32,
+    pub total_value: f64,
+    pub total_cost: f64,
+    pub total_pnl: f64,
+    pub total_pnl_percent: f64,
+}
+
+impl PerformanceSummary {
+    fn of(portfolio_id: u32, positions: &PositionsResponse) -> Self {
+        Self {
+            portfolio_id,
+            total_value: positions.total_value,
+            total_cost: positions.total_cost,
+            total_pnl: positions.total_pnl,
+            total_pnl_percent: positions.total_pnl_percent,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxComparison {
+    pub sandbox: PerformanceSummary,
+    pub real: PerformanceSummary,
+    /// Sandbox return minus real return, in percentage points
+    pub return_difference: f64,
+    /// Benchmark return since the sandbox's first trade, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<BenchmarkReturn>,
+}
+
+/// Compare a sandbox's return with a real portfolio's, and optionally with a
+/// custom benchmark over the sandbox's lifetime
+#[tauri::command]
+pub async fn compare_sandbox_performance(
+    app_handle: AppHandle,
+    sandbox_id: u32,
+    portfolio_id: u32,
+    benchmark_id: Option<u32>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<SandboxComparison, String> {
+    if !sandbox::is_sandbox(sandbox_id) {
+        return Err(format!("Portfolio {} is not a sandbox", sandbox_id));
+    }
+    sandbox::reject(portfolio_id, "used as the real comparison")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let valued = sandbox_positions(&app_handle, &engine, sandbox_id).await?;
+    let data = engine
+        .request("get_positions", json!({ "portfolioId": portfolio_id }))
+        .await?;
+    let real: PositionsResponse = protocol::parse(&app_handle, "get_positions", data)?;
+
+    let benchmark = match benchmark_id {
+        Some(id) => {
+            let data_dir = store::data_dir(&app_handle)?;
+            let today = chrono::Local::now().date_naive();
+            let from = sandbox::transactions(&data_dir, sandbox_id)?
+                .iter()
+                .filter_map(|t| validate_date(&t.date).ok())
+                .min()
+                .unwrap_or(today);
+            Some(benchmarks::period_return(&data_dir, &engine, id, from, today).await?)
+        }
+        None => None,
+    };
+
+    Ok(SandboxComparison {
+        return_difference: valued.total_pnl_percent - real.total_pnl_percent,
+        benchmark,
+        sandbox: PerformanceSummary::of(sandbox_id, &valued),
+        real: PerformanceSummary::of(portfolio_id, &real),
+    })
+}
+
+/// Turn a hypothetical trade that was actually executed into a note on the
+/// real position. Returns the updated note.
+#[tauri::command]
+pub async fn convert_sandbox_trade_to_note(
+    app_handle: AppHandle,
+    sandbox_id: u32,
+    trade_id: u32,
+    portfolio_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<String, String> {
+    if !sandbox::is_sandbox(sandbox_id) {
+        return Err(format!("Portfolio {} is not a sandbox", sandbox_id));
+    }
+    sandbox::reject(portfolio_id, "the target of a converted trade")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let data_dir = store::data_dir(&app_handle)?;
+    let (sandbox_name, trade) = sandbox::mark_converted(&data_dir, sandbox_id, trade_id)?;
+
+    let data = engine
+        .request("get_positions", json!({ "portfolioId": portfolio_id }))
+        .await?;
+    let positions: PositionsResponse = protocol::parse(&app_handle, "get_positions", data)?;
+    let existing = positions
+        .positions
+        .iter()
+        .find(|p| p.isin == trade.isin)
+        .map(|p| p.notes.clone())
+        .unwrap_or_default();
+
+    let mut entry = format!(
+        "Executed from sandbox \"{}\": {:?} {} @ {:.2} on {}",
+        sandbox_name, trade.side, trade.quantity, trade.price, trade.date
+    );
+    if let Some(note) = &trade.note {
+        entry.push_str(&format!(" ({})", note));
+    }
+    let note = if existing.trim().is_empty() {
+        entry
+    } else {
+        format!("{}\n{}", existing.trim_end(), entry)
+    };
+
+    write_position_note(&app_handle, &engine, portfolio_id, &trade.isin, &note).await?;
+    Ok(note)
+}
+
+// =============================================================================
+// Analytics
+// =============================================================================
+
+/// Gather the ledger, value history and weights and compute insights.
+async fn build_monthly_insights(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    portfolio_id: u32,
+    month: Option<String>,
+) -> Result<MonthlyInsights, String> {
+    let month = match month.as_deref() {
+        Some(month) => insights::parse_month(month)?,
+        None => {
+            let today = chrono::Local::now().date_naive();
+            insights::parse_month(&today.format("%Y-%m").to_string())?
+        }
+    };
+    let month_end = month
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| "Month out of range".to_string())?;
+
+    let payload = json!({ "portfolioId": portfolio_id, "to": month_end.to_string() });
+    let data = engine.request("get_transactions", payload).await?;
+    let ledger: TransactionsResponse = protocol::parse(app_handle, "get_transactions", data)?;
+
+    let payload = json!({ "portfolioId": portfolio_id });
+    let data = engine.request("get_dashboard_data", payload.clone()).await?;
+    let dashboard: DashboardData = protocol::parse(app_handle, "get_dashboard_data", data)?;
+    let data = engine.request("get_positions", payload).await?;
+    let positions: PositionsResponse = protocol::parse(app_handle, "get_positions", data)?;
+    let weights: Vec<f64> = positions.positions.iter().map(|p| p.weight).collect();
+
+    let data_dir = store::data_dir(app_handle)?;
+    insights::compute(
+        &data_dir,
+        portfolio_id,
+        month,
+        &ledger.transactions,
+        &dashboard.history,
+        &weights,
+    )
+}
+
+/// Get behavioral insights (contribution consistency, panic sells,
+/// concentration creep, fee trend) for a month, `YYYY-MM`, default current.
+/// Opt-in via the `monthly_insights` feature flag; computed locally.
+#[tauri::command]
+pub async fn get_monthly_insights(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    month: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<MonthlyInsights, String> {
+    flags.require("monthly_insights")?;
+    sandbox::reject(portfolio_id, "analyzed for insights")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    build_monthly_insights(&app_handle, &engine, portfolio_id, month).await
+}
+
+/// Email the monthly digest, including the insights section, through the
+/// user's SMTP settings (requires monthly report emails to be enabled).
+#[tauri::command]
+pub async fn send_monthly_digest(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    month: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<MonthlyInsights, String> {
+    flags.require("monthly_insights")?;
+    sandbox::reject(portfolio_id, "analyzed for insights")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let insights = build_monthly_insights(&app_handle, &engine, portfolio_id, month).await?;
+    let subject = format!("Portfolio Prism monthly digest ({})", insights.month);
+    let body = insights::digest_text(&insights);
+    let data_dir = store::data_dir(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        email::deliver(&data_dir, DeliveryKind::MonthlyReport, &subject, &body)
+    })
+    .await
+    .map_err(|e| format!("Failed to send monthly digest: {}", e))??;
+
+    Ok(insights)
+}
+
+/// Get turnover ratio, average holding period and trade frequency.
+/// The range defaults to the last twelve months.
+#[tauri::command]
+pub async fn get_turnover_metrics(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    from: Option<String>,
+    to: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<TurnoverMetrics, String> {
+    sandbox::reject(portfolio_id, "analyzed for turnover")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let to_date = match to.as_deref() {
+        Some(to) => validate_date(to)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let from_date = match from.as_deref() {
+        Some(from) => validate_date(from)?,
+        None => to_date - chrono::Duration::days(365),
+    };
+    if from_date > to_date {
+        return Err("Start date must not be after end date".to_string());
+    }
+
+    // Full ledger up to the end of the range, so earlier lots can be matched
+    let payload = json!({ "portfolioId": portfolio_id, "to": to_date.to_string() });
+    let data = engine.request("get_transactions", payload).await?;
+    let ledger: TransactionsResponse = protocol::parse(&app_handle, "get_transactions", data)?;
+
+    let data = engine
+        .request("get_dashboard_data", json!({ "portfolioId": portfolio_id }))
+        .await?;
+    let dashboard: DashboardData = protocol::parse(&app_handle, "get_dashboard_data", data)?;
+    let (from_day, to_day) = (from_date.to_string(), to_date.to_string());
+    let values: Vec<f64> = dashboard
+        .history
+        .iter()
+        .filter(|point| {
+            let day = point.date.get(..10).unwrap_or_default();
+            day >= from_day.as_str() && day <= to_day.as_str()
+        })
+        .map(|point| point.value)
+        .collect();
+    let average_value = if values.is_empty() {
+        dashboard.total_value
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    };
+
+    Ok(turnover::compute(&ledger.transactions, from_date, to_date, average_value))
+}
+
+/// Decompose the value change over `range` into contributions, withdrawals,
+/// dividends, fees and market growth, as waterfall-chart steps
+#[tauri::command]
+pub async fn get_value_waterfall(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    range: WaterfallRange,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<ValueWaterfall, String> {
+    sandbox::reject(portfolio_id, "analyzed for value changes")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({ "portfolioId": portfolio_id });
+    let data = engine.request("get_transactions", payload.clone()).await?;
+    let ledger: TransactionsResponse = protocol::parse(&app_handle, "get_transactions", data)?;
+    let data = engine.request("get_dashboard_data", payload).await?;
+    let dashboard: DashboardData = protocol::parse(&app_handle, "get_dashboard_data", data)?;
+
+    let to = chrono::Local::now().date_naive();
+    // `All` starts the day before the first recorded activity
+    let from = range.start(to).unwrap_or_else(|| {
+        let first_day = |date: &str| validate_date(date.get(..10)?).ok();
+        ledger
+            .transactions
+            .iter()
+            .filter_map(|t| first_day(&t.date))
+            .chain(dashboard.history.iter().filter_map(|p| first_day(&p.date)))
+            .min()
+            .map_or(to, |first| first - chrono::Duration::days(1))
+    });
+
+    Ok(value_waterfall::compute(
+        portfolio_id,
+        range,
+        from,
+        to,
+        &ledger.transactions,
+        &dashboard.history,
+        dashboard.total_value,
+    ))
+}
+
+/// Get fully sold positions with realized P&L, transactions and notes
+#[tauri::command]
+pub async fn get_closed_positions(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<ClosedPosition>, String> {
+    for date in from.iter().chain(to.iter()) {
+        validate_date(date)?;
+    }
+    let data_dir = store::data_dir(&app_handle)?;
+    closed_positions::list(&data_dir, portfolio_id, from.as_deref(), to.as_deref())
+}
+
+// =============================================================================
+// Benchmarks
+// =============================================================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonPoint {
+    pub date: String,
+    /// Both series indexed to 100 on the first common date
+    pub portfolio: f64,
+    pub benchmark: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkComparison {
+    pub portfolio_id: u32,
+    /// Change of the portfolio value over the period, in percent
+    pub portfolio_return_percent: Option<f64>,
+    pub benchmark: BenchmarkReturn,
+    /// Portfolio return minus benchmark return, in percentage points
+    pub return_difference: Option<f64>,
+    pub series: Vec<ComparisonPoint>,
+}
+
+/// Create a custom benchmark from weighted components (e.g. 70% MSCI World,
+/// 30% Euro Aggregate); weights are normalized
+#[tauri::command]
+pub async fn create_benchmark(
+    app_handle: AppHandle,
+    name: String,
+    components: Vec<BenchmarkComponent>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<Benchmark, String> {
+    let components = components
+        .into_iter()
+        .map(|component| {
+            Ok(BenchmarkComponent {
+                isin: validate_isin(&component.isin)?,
+                weight: component.weight,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let data_dir = store::data_dir(&app_handle)?;
+    let benchmark = benchmarks::create(&data_dir, &name, components)?;
+
+    // Build the series now so the first comparison is fast; it is rebuilt on
+    // access otherwise
+    if engine.is_connected().await {
+        if let Err(e) = benchmarks::rebuild(&data_dir, &engine, &benchmark).await {
+            tracing::warn!("Failed to build benchmark series: {}", e);
+        }
+    }
+    Ok(benchmark)
+}
+
+/// List custom benchmarks
+#[tauri::command]
+pub async fn list_benchmarks(app_handle: AppHandle) -> Result<Vec<Benchmark>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    benchmarks::list(&data_dir)
+}
+
+/// Delete a custom benchmark and its cached series
+#[tauri::command]
+pub async fn delete_benchmark(app_handle: AppHandle, id: u32) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    benchmarks::delete(&data_dir, id)
+}
+
+/// Get the composite series of a custom benchmark (indexed to 100)
+#[tauri::command]
+pub async fn get_benchmark_series(
+    app_handle: AppHandle,
+    id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<BenchmarkSeries, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let benchmark = benchmarks::get(&data_dir, id)?;
+    benchmarks::series(&data_dir, &engine, &benchmark).await
+}
+
+/// Compare a portfolio's value history with a custom benchmark
+#[tauri::command]
+pub async fn compare_to_benchmark(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    benchmark_id: u32,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<BenchmarkComparison, String> {
+    sandbox::reject(portfolio_id, "compared with a benchmark")?;
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let data = engine
+        .request("get_dashboard_data", json!({ "portfolioId": portfolio_id }))
+        .await?;
+    let dashboard: DashboardData = protocol::parse(&app_handle, "get_dashboard_data", data)?;
+    let history: Vec<(chrono::NaiveDate, f64)> = dashboard
+        .history
+        .iter()
+        .filter_map(|point| Some((validate_date(point.date.get(..10)?).ok()?, point.value)))
+        .collect();
+
+    let data_dir = store::data_dir(&app_handle)?;
+    let benchmark = benchmarks::get(&data_dir, benchmark_id)?;
+    let series = benchmarks::series(&data_dir, &engine, &benchmark).await?;
+    let benchmark_on = |date: chrono::NaiveDate| {
+        let index = series.points.partition_point(|point| point.date <= date);
+        index.checked_sub(1).map(|index| series.points[index].value)
+    };
+
+    // Align on portfolio dates the benchmark covers
+    let aligned: Vec<(chrono::NaiveDate, f64, f64)> = history
+        .iter()
+        .filter(|(date, value)| {
+            *value > 0.0 && series.points.first().is_some_and(|first| first.date <= *date)
+        })
+        .filter_map(|(date, value)| Some((*date, *value, benchmark_on(*date)?)))
+        .collect();
+
+    let today = chrono::Local::now().date_naive();
+    let (from, to) = match (aligned.first(), aligned.last()) {
+        (Some(first), Some(last)) => (first.0, last.0),
+        _ => (today, today),
+    };
+    let series_points = aligned
+        .first()
+        .map(|(_, base_value, base_benchmark)| {
+            aligned
+                .iter()
+                .map(|(date, value, benchmark)| ComparisonPoint {
+                    date: date.to_string(),
+                    portfolio: value / base_value * 100.0,
+                    benchmark: benchmark / base_benchmark * 100.0,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let portfolio_return_percent = match (aligned.first(), aligned.last()) {
+        (Some(first), Some(last)) if aligned.len() > 1 => Some((last.1 / first.1 - 1.0) * 100.0),
+        _ => None,
+    };
+    let benchmark_return = BenchmarkReturn {
+        benchmark_id,
+        name: benchmark.name,
+        from,
+        to,
+        return_percent: benchmarks::return_between(&series, from, to),
+    };
+
+    Ok(BenchmarkComparison {
+        portfolio_id,
+        return_difference: portfolio_return_percent
+            .zip(benchmark_return.return_percent)
+            .map(|(portfolio, benchmark)| portfolio - benchmark),
+        portfolio_return_percent,
+        benchmark: benchmark_return,
+        series: series_points,
+    })
+}
+
+// =============================================================================
+// Broker Imports
+// =============================================================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerImportResult {
+    pub statement: BrokerStatement,
+    pub dry_run: bool,
+    /// Engine response to `import_holdings`; `None` for a dry run
+    pub imported: Option<serde_json::Value>,
+}
+
+/// Forward a parsed statement to the engine, or only return it as a preview
+async fn import_statement(
+    engine: &PythonEngine,
+    portfolio_id: u32,
+    statement: BrokerStatement,
+    dry_run: bool,
+) -> Result<BrokerImportResult, String> {
+    if statement.positions.is_empty() && statement.transactions.is_empty() {
+        return Err("No holdings found in the statement".to_string());
+    }
+    if dry_run {
+        return Ok(BrokerImportResult {
+            statement,
+            dry_run,
+            imported: None,
+        });
+    }
+
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+    let imported = engine
+        .request(
+            "import_holdings",
+            json!({
+                "portfolioId": portfolio_id,
+                "broker": statement.broker,
+                "positions": statement.positions,
+                "transactions": statement.transactions,
+            }),
+        )
+        .await?;
+    Ok(BrokerImportResult {
+        statement,
+        dry_run,
+        imported: Some(imported),
+    })
+}
+
+/// List the broker statement formats `import_broker_statement` detects
+#[tauri::command]
+pub fn list_import_formats(flags: State<'_, FeatureFlags>) -> Result<Vec<ImportFormat>, String> {
+    flags.require("new_importers")?;
+    Ok(broker_import::formats())
+}
+
+/// Import a broker statement in any of the `list_import_formats` formats
+/// into a portfolio. The format is detected from the file; with `dry_run`
+/// the parsed holdings and transactions are returned without importing them.
+#[tauri::command]
+pub async fn import_broker_statement(
+    portfolio_id: u32,
+    file_path: String,
+    dry_run: bool,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<BrokerImportResult, String> {
+    flags.require("new_importers")?;
+    sandbox::reject(portfolio_id, "imported into")?;
+    let path = validate_file_path(&file_path)?;
+    let statement = broker_import::parse_file(std::path::Path::new(&path))?;
+    import_statement(&engine, portfolio_id, statement, dry_run).await
+}
+
+/// Parse a DeGiro transactions or portfolio CSV export and merge the
+/// holdings into a portfolio
+#[tauri::command]
+pub async fn import_degiro_csv(
+    portfolio_id: u32,
+    file_path: String,
+    engine: State<'_, Arc<PythonEngine>>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<BrokerImportResult, String> {
+    flags.require("new_importers")?;
+    sandbox::reject(portfolio_id, "imported into")?;
+    let path = validate_file_path(&file_path)?;
+    if !path.to_lowercase().ends_with(".csv") {
+        return Err("DeGiro exports are CSV files".to_string());
+    }
+
+    let statement = broker_import::parse_file(std::path::Path::new(&path))?;
+    if statement.broker != broker_import::degiro::BROKER {
+        return Err("Not a DeGiro transactions or portfolio export".to_string());
+    }
+    import_statement(&engine, portfolio_id, statement, false).await
+}
+
+// =============================================================================
+// Delisted Instruments
+// =============================================================================
+
+/// Get instruments flagged as likely delisted (plus archived and replaced ones)
+#[tauri::command]
+pub async fn get_delisted_candidates(
+    app_handle: AppHandle,
+) -> Result<Vec<InstrumentRecord>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    instrument_lifecycle::candidates(&data_dir)
+}
+
+/// Archive, replace or force a retry of a likely-delisted instrument
+#[tauri::command]
+pub async fn resolve_delisted_candidate(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    isin: String,
+    action: DelistingAction,
+    replacement_isin: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<InstrumentRecord, String> {
+    let isin = validate_isin(&isin)?;
+    let replacement_isin = replacement_isin.as_deref().map(validate_isin).transpose()?;
+    let data_dir = store::data_dir(&app_handle)?;
+    let record = instrument_lifecycle::resolve(&data_dir, &isin, action, replacement_isin)?;
+
+    if action == DelistingAction::Retry && engine.is_connected().await && !sync_in_progress() {
+        let isins = vec![isin];
+        let payload = json!({ "portfolioId": portfolio_id, "force": true, "isins": isins });
+        let response = engine.send_command("sync_portfolio", payload).await?;
+        if let Some(data) = response.data.filter(|_| response.success) {
+            let result: PortfolioSyncResult = protocol::parse(&app_handle, "sync_portfolio", data)?;
+            track_targeted_sync(&app_handle, &isins, &result);
+        }
+    }
+
+    Ok(record)
+}
+
+// =============================================================================
+// Price Alerts
+// =============================================================================
+
+/// Create or re-arm a price alert; fires a notification when crossed
+#[tauri::command]
+pub async fn set_price_alert(
+    app_handle: AppHandle,
+    isin: String,
+    condition: AlertCondition,
+    threshold: f64,
+) -> Result<PriceAlert, String> {
+    let isin = validate_isin(&isin)?;
+    let data_dir = store::data_dir(&app_handle)?;
+    price_alerts::set(&data_dir, isin, condition, threshold)
+}
+
+/// List all price alerts with their last checked price
+#[tauri::command]
+pub async fn list_price_alerts(app_handle: AppHandle) -> Result<Vec<PriceAlert>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    price_alerts::list(&data_dir)
+}
+
+/// Delete a price alert
+#[tauri::command]
+pub async fn delete_price_alert(app_handle: AppHandle, id: u32) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    price_alerts::delete(&data_dir, id)
+}
+
+/// Create an ex-date/earnings alert rule, or update rule `id` when given
+#[tauri::command]
+pub async fn set_event_alert(
+    app_handle: AppHandle,
+    id: Option<u32>,
+    kinds: Vec<EventKind>,
+    days_before: u32,
+    min_amount: Option<f64>,
+    enabled: bool,
+) -> Result<EventAlertRule, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    event_alerts::set_rule(&data_dir, id, kinds, days_before, min_amount, enabled)
+}
+
+/// List ex-date/earnings alert rules
+#[tauri::command]
+pub async fn list_event_alerts(app_handle: AppHandle) -> Result<Vec<EventAlertRule>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    event_alerts::rules(&data_dir)
+}
+
+/// Delete an ex-date/earnings alert rule
+#[tauri::command]
+pub async fn delete_event_alert(app_handle: AppHandle, id: u32) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    event_alerts::delete_rule(&data_dir, id)
+}
+
+/// Silence one upcoming event of a rule for `days` days
+#[tauri::command]
+pub async fn snooze_event_alert(
+    app_handle: AppHandle,
+    rule_id: u32,
+    isin: String,
+    kind: EventKind,
+    date: String,
+    days: u32,
+) -> Result<(), String> {
+    if days == 0 {
+        return Err("Snooze for at least one day".to_string());
+    }
+    let isin = validate_isin(&isin)?;
+    let date = validate_date(&date)?;
+    let data_dir = store::data_dir(&app_handle)?;
+    let until = chrono::Local::now().date_naive() + chrono::Duration::days(days as i64 - 1);
+    event_alerts::snooze(&data_dir, rule_id, &isin, kind, date, until)
+}
+
+/// Upcoming events and price alerts of the last 24 hours
+#[tauri::command]
+pub async fn get_daily_digest(app_handle: AppHandle) -> Result<DailyDigest, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    event_alerts::digest(&data_dir)
+}
+
+// =============================================================================
+// Quotes
+// =============================================================================
+
+/// List instruments the engine found no quote for this session
+#[tauri::command]
+pub async fn get_missing_quotes(app_handle: AppHandle) -> Result<Vec<MissingQuote>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    Ok(quote_fallback::missing(&data_dir))
+}
+
+/// Fetch a single missing quote from Yahoo or Stooq and save it, without a
+/// pipeline run
+#[tauri::command]
+pub async fn refresh_quote(app_handle: AppHandle, isin: String) -> Result<QuoteRefresh, String> {
+    let isin = validate_isin(&isin)?;
+    let data_dir = store::data_dir(&app_handle)?;
+    let refreshed = quote_fallback::refresh(&data_dir, &isin).await?;
+    for portfolio_id in &refreshed.portfolio_ids {
+        let _ = app_handle.emit(
+            "portfolio-updated",
+            json!({ "timestamp": chrono::Utc::now().to_rfc3339(), "portfolioId": portfolio_id }),
+        );
+    }
+    Ok(refreshed)
+}
+
+// =============================================================================
+// FX Rates
+// =============================================================================
+
+/// Get the ECB euro reference rates used to convert values into the display
+/// currency
+#[tauri::command]
+pub async fn get_fx_rates(app_handle: AppHandle, force: Option<bool>) -> Result<FxRates, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    fx_rates::rates(&data_dir, force.unwrap_or(false)).await
+}
+
+// =============================================================================
+// Export
+// =============================================================================
+
+/// Export positions, allocations, true holdings and the overlap matrix of a
+/// portfolio to a multi-sheet XLSX workbook at `path`
+#[tauri::command]
+pub async fn export_xlsx(
+    app_handle: AppHandle,
+    portfolio_id: u32,
+    path: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<ExportSummary, String> {
+    let path = std::path::PathBuf::from(path.trim());
+    if path.extension().and_then(|ext| ext.to_str()) != Some("xlsx") {
+        return Err("Export file must end in .xlsx".to_string());
+    }
+    if !path.parent().is_some_and(|parent| parent.is_dir()) {
+        return Err(format!("Folder does not exist: {}", path.display()));
+    }
+
+    let (positions, allocations, true_holdings, overlap) = if sandbox::is_sandbox(portfolio_id) {
+        let positions = sandbox_positions(&app_handle, &engine, portfolio_id).await?;
+        let allocations = sandbox::dashboard(&positions).allocations;
+        (positions, allocations, None, None)
+    } else {
+        if !engine.is_connected().await {
+            return Err(engine.unavailable().into());
+        }
+        let payload = json!({ "portfolioId": portfolio_id });
+        let data = engine.request("get_positions", payload.clone()).await?;
+        let positions: PositionsResponse = protocol::parse(&app_handle, "get_positions", data)?;
+        let data = engine.request("get_dashboard_data", payload).await?;
+        let dashboard: DashboardData = protocol::parse(&app_handle, "get_dashboard_data", data)?;
+
+        // Look-through data only exists after a pipeline run
+        let true_holdings = engine.request("get_true_holdings", json!({})).await.ok();
+        let overlap = match &true_holdings {
+            Some(look_through) => Some(pipeline::overlap_of(
+                &app_handle,
+                &positions.positions,
+                look_through.clone(),
+            )?),
+            None => None,
+        };
+        (positions, dashboard.allocations, true_holdings, overlap)
+    };
+
+    let data = ExportData {
+        positions,
+        allocations,
+        true_holdings,
+        overlap,
+    };
+    tauri::async_runtime::spawn_blocking(move || xlsx_export::write(&path, &data))
+        .await
+        .map_err(|e| format!("Export failed: {}", e))?
+}
+
+register_commands! {
+    get_dashboard_data,
+    assemble_dashboard,
+    get_positions,
+    get_transactions,
+    sync_portfolio,
+    list_portfolios,
+    create_portfolio,
+    rename_portfolio,
+    delete_portfolio,
+    set_position_note,
+    record_sandbox_trade,
+    compare_sandbox_performance,
+    convert_sandbox_trade_to_note,
+    get_monthly_insights,
+    send_monthly_digest,
+    get_turnover_metrics,
+    get_closed_positions,
+    get_delisted_candidates,
+    resolve_delisted_candidate,
+    set_price_alert,
+    list_price_alerts,
+    delete_price_alert,
+    export_xlsx,
+    set_event_alert,
+    list_event_alerts,
+    delete_event_alert,
+    snooze_event_alert,
+    get_daily_digest,
+    create_benchmark,
+    list_benchmarks,
+    delete_benchmark,
+    get_benchmark_series,
+    compare_to_benchmark,
+    #[api(deprecated = "Use import_broker_statement, which detects the format")]
+    import_degiro_csv,
+    get_value_waterfall,
+    list_import_formats,
+    import_broker_statement,
+    get_missing_quotes,
+    refresh_quote,
+    get_fx_rates,
+}
@@ -0,0 +1,676 @@
+//! Settings and Diagnostics Commands
+//!
+//! App settings, the local API, the proxy password, telemetry, error and
+//! crash reports, email delivery, feature flags, app info, the self-test,
+//! backups, resetting and deleting app data, moving the data folder, app
+//! updates, and opening the data and log folders.
+
+use super::api::API_VERSION;
+use crate::app_lock::{self, AppLockStatus, OsAuthStatus};
+use crate::app_reset::{self, DataDeletion, DeletionToken, ResetScope, ResetSummary};
+use crate::app_settings::{self, AppSettings};
+use crate::app_update::{self, UpdateInfo, UpdateVerification};
+use crate::crash_reports::{self, CrashReport, CrashReportSummary};
+use crate::data_backup::{self, BackupSummary, RestoreSummary};
+use crate::data_location::{self, DataLocation, MigrationSummary};
+use crate::email::{self, DeliveryKind, DeliveryRecord, SmtpSettings};
+use crate::error_reports::{self, ErrorReport, SubmissionResult};
+use crate::feature_flags::{FeatureFlag, FeatureFlags};
+use crate::local_api::{self, LocalApiInfo};
+use crate::log_buffer::{self, LogEntry, LogLevel};
+use crate::logging;
+use crate::proxy;
+use crate::python_engine::{EnginePool, PythonEngine};
+use crate::self_test::{self, SelfTestReport};
+use crate::store;
+use crate::telemetry::{self, TelemetryPreview};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use zeroize::Zeroizing;
+
+// =============================================================================
+// App Settings
+// =============================================================================
+
+/// Get the app settings (currency, locale, theme, Hive opt-in, timeout
+/// overrides and the sync schedule), with defaults for anything unset
+#[tauri::command]
+pub async fn get_settings(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AppSettings, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let mut settings = app_settings::load(&data_dir)?;
+    // Same answer as `get_hive_contribution` before the user chose one
+    let enabled = app_settings::hive_contributions(&data_dir, &engine).await;
+    settings.general.hive_contributions = Some(enabled);
+    Ok(settings)
+}
+
+/// Validate and save the app settings, then push them to the engines.
+/// Turning `requireOsAuth` off shows the Touch ID / Windows Hello prompt.
+#[tauri::command]
+pub async fn set_settings(
+    app_handle: AppHandle,
+    settings: AppSettings,
+    pool: State<'_, EnginePool>,
+) -> Result<AppSettings, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    if app_settings::load_general(&data_dir).require_os_auth && !settings.general.require_os_auth {
+        app_lock::confirm_owner(&app_handle).await?;
+    }
+    let saved = app_settings::save(&data_dir, settings)?;
+    telemetry::set_enabled(&data_dir, saved.general.telemetry);
+    app_lock::configure(&saved.general);
+    proxy::configure(&saved.general.proxy);
+    for engine in [pool.primary(), pool.worker()] {
+        app_settings::configure_engine(&engine, &saved.general).await;
+    }
+    // A failure to start is reported by `get_local_api_info`
+    let _ = local_api::apply(&app_handle).await;
+    Ok(saved)
+}
+
+// =============================================================================
+// Local API
+// =============================================================================
+
+/// Address, state and bearer token of the local REST API
+#[tauri::command]
+pub async fn get_local_api_info(app_handle: AppHandle) -> Result<LocalApiInfo, String> {
+    local_api::info(&app_handle)
+}
+
+/// Replace the local API token; clients need the new one from then on
+#[tauri::command]
+pub async fn rotate_local_api_token(app_handle: AppHandle) -> Result<LocalApiInfo, String> {
+    local_api::rotate_token()?;
+    local_api::info(&app_handle)
+}
+
+// =============================================================================
+// Proxy
+// =============================================================================
+
+/// Whether a proxy password is stored in the keychain
+#[tauri::command]
+pub async fn has_proxy_password() -> Result<bool, String> {
+    Ok(proxy::has_password())
+}
+
+/// Store the proxy password in the OS keychain; empty or `null` removes it.
+/// The engine uses it from its next start.
+#[tauri::command]
+pub async fn set_proxy_password(
+    app_handle: AppHandle,
+    password: Option<Zeroizing<String>>,
+) -> Result<(), String> {
+    proxy::set_password(password.as_deref().map(String::as_str))?;
+    let data_dir = store::data_dir(&app_handle)?;
+    proxy::configure(&app_settings::load_general(&data_dir).proxy);
+    Ok(())
+}
+
+// =============================================================================
+// App Lock
+// =============================================================================
+
+/// Whether the app is locked and which unlock methods are configured
+#[tauri::command]
+pub async fn get_app_lock_status() -> Result<AppLockStatus, String> {
+    Ok(app_lock::status())
+}
+
+/// Whether OS authentication is required, available and currently pending
+#[tauri::command]
+pub async fn get_os_auth_status() -> Result<OsAuthStatus, String> {
+    Ok(app_lock::os_auth_status())
+}
+
+/// Lock the app now, e.g. from a lock button
+#[tauri::command]
+pub async fn lock_app(app_handle: AppHandle) -> Result<AppLockStatus, String> {
+    app_lock::lock(&app_handle);
+    Ok(app_lock::status())
+}
+
+/// Show the Touch ID / Windows Hello prompt; `true` once the app is unlocked
+#[tauri::command]
+pub async fn unlock_with_os_auth(app_handle: AppHandle) -> Result<bool, String> {
+    app_lock::authenticate(&app_handle).await
+}
+
+/// Set, change or remove (`pin: null`) the app PIN. Changing or removing an
+/// existing PIN needs `currentPin`.
+#[tauri::command]
+pub async fn set_app_pin(
+    pin: Option<Zeroizing<String>>,
+    current_pin: Option<Zeroizing<String>>,
+) -> Result<AppLockStatus, String> {
+    app_lock::set_pin(pin, current_pin).await?;
+    Ok(app_lock::status())
+}
+
+/// Unlock with the app PIN; `false` for a wrong PIN
+#[tauri::command]
+pub async fn verify_app_pin(pin: Zeroizing<String>) -> Result<bool, String> {
+    app_lock::verify_pin(pin).await
+}
+
+// =============================================================================
+// Telemetry
+// =============================================================================
+
+/// Show exactly what anonymous telemetry has queued and is counting, whether
+/// or not it is enabled
+#[tauri::command]
+pub async fn get_telemetry_preview(app_handle: AppHandle) -> Result<TelemetryPreview, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    telemetry::preview(&data_dir)
+}
+
+/// Log a frontend event to the backend telemetry store.
+#[tauri::command]
+pub async fn log_event(
+    level: String,
+    message: String,
+    context: serde_json::Value,
+    component: String,
+    category: String,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<bool, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let payload = json!({
+        "level": level,
+        "message": message,
+        "context": context,
+        "component": component,
+        "category": category
+    });
+
+    match engine.send_command("log_event", payload).await {
+        Ok(response) => {
+            if response.success {
+                Ok(response.data.and_then(|value| value.as_bool()).unwrap_or(true))
+            } else {
+                Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Failed to log event".to_string()))
+            }
+        }
+        Err(e) => Err(format!("Failed to log event: {}", e)),
+    }
+}
+
+/// Get recently processed backend reports.
+#[tauri::command]
+pub async fn get_recent_reports(
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<serde_json::Value, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    match engine.send_command("get_recent_reports", json!({})).await {
+        Ok(response) => {
+            if response.success {
+                return Ok(response
+                    .data
+                    .unwrap_or_else(|| serde_json::Value::Array(vec![])));
+            }
+
+            Err(response
+                .error
+                .map(|e| e.message)
+                .unwrap_or_else(|| "Failed to fetch recent reports".to_string()))
+        }
+        Err(e) => Err(format!("Failed to fetch recent reports: {}", e)),
+    }
+}
+
+/// Pending and already-reported error rows from the engine
+async fn fetch_error_rows(engine: &PythonEngine) -> Result<Vec<serde_json::Value>, String> {
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+
+    let mut rows = Vec::new();
+    for command in ["get_pending_reviews", "get_recent_reports"] {
+        match engine.send_command(command, json!({})).await {
+            Ok(response) if response.success => {
+                if let Some(serde_json::Value::Array(items)) = response.data {
+                    rows.extend(items);
+                }
+            }
+            Ok(response) => {
+                return Err(response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Failed to fetch error reports".to_string()))
+            }
+            Err(e) => return Err(format!("Failed to fetch error reports: {}", e)),
+        }
+    }
+    Ok(rows)
+}
+
+/// List locally captured errors grouped by signature.
+///
+/// Combines pending and already-reported rows from the engine so recurring
+/// errors show their full history. Pass `session_id` to restrict to one run.
+#[tauri::command]
+pub async fn list_error_reports(
+    app_handle: AppHandle,
+    session_id: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<Vec<ErrorReport>, String> {
+    let rows = fetch_error_rows(&engine).await?;
+    let data_dir = store::data_dir(&app_handle)?;
+    error_reports::aggregate(&data_dir, &rows, session_id.as_deref())
+}
+
+/// Submit the open errors of a session (default: the engine's current one)
+/// as a scrubbed, categorized bundle. Requires error reporting to be enabled
+/// in the settings; returns exactly what was sent.
+#[tauri::command]
+pub async fn submit_error_report(
+    app_handle: AppHandle,
+    session_id: Option<String>,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<SubmissionResult, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    if !app_settings::load_general(&data_dir).error_reporting {
+        return Err("Error reporting is disabled in the settings".to_string());
+    }
+
+    let rows = fetch_error_rows(&engine).await?;
+    let session_id = match session_id {
+        Some(session_id) => Some(session_id),
+        None => engine.session_id().await,
+    };
+    let bundle = error_reports::bundle(
+        &data_dir,
+        &rows,
+        session_id.as_deref(),
+        app_handle.package_info().version.to_string(),
+        engine.get_version().await,
+    )?;
+    error_reports::submit(&data_dir, bundle).await
+}
+
+/// Mark an error report resolved, or reopen it with `resolved: false`
+#[tauri::command]
+pub async fn set_error_report_resolved(
+    app_handle: AppHandle,
+    signature: String,
+    resolved: bool,
+) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    error_reports::set_resolved(&data_dir, &signature, resolved)
+}
+
+/// List engine crash reports (captured Python tracebacks), newest first
+#[tauri::command]
+pub async fn list_crash_reports(app_handle: AppHandle) -> Result<Vec<CrashReportSummary>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    crash_reports::list(&data_dir)
+}
+
+/// Get a crash report with its traceback, engine session and the commands
+/// sent before the crash
+#[tauri::command]
+pub async fn get_crash_report(app_handle: AppHandle, id: String) -> Result<CrashReport, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    crash_reports::get(&data_dir, &id)?.ok_or_else(|| format!("Crash report not found: {}", id))
+}
+
+// =============================================================================
+// Email Delivery
+// =============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailSettingsResponse {
+    pub settings: SmtpSettings,
+    pub has_password: bool,
+}
+
+/// Get SMTP settings for report and alert delivery
+#[tauri::command]
+pub async fn get_email_settings(app_handle: AppHandle) -> Result<EmailSettingsResponse, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let settings = email::load_settings(&data_dir)?;
+
+    Ok(EmailSettingsResponse {
+        settings,
+        has_password: email::has_password(),
+    })
+}
+
+/// Save SMTP settings. The password (if given) goes to the OS keychain.
+#[tauri::command]
+pub async fn set_email_settings(
+    app_handle: AppHandle,
+    settings: SmtpSettings,
+    password: Option<Zeroizing<String>>,
+) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    email::save_settings(&data_dir, &settings, password.as_deref().map(String::as_str))
+}
+
+/// Send a test email with the current SMTP settings
+#[tauri::command]
+pub async fn send_test_email(app_handle: AppHandle) -> Result<(), String> {
+    let data_dir = store::data_dir(&app_handle)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        email::deliver(
+            &data_dir,
+            DeliveryKind::Test,
+            "Portfolio Prism test email",
+            "This is a test message from Portfolio Prism.\n\nIf you received it, report and alert delivery is configured correctly.",
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to send test email: {}", e))?
+}
+
+/// Get the most recent email delivery attempts
+#[tauri::command]
+pub async fn get_email_deliveries(
+    app_handle: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<DeliveryRecord>, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    email::recent_deliveries(&data_dir, limit.unwrap_or(50))
+}
+
+// =============================================================================
+// Feature Flags
+// =============================================================================
+
+/// List all feature flags with their effective values
+#[tauri::command]
+pub fn get_feature_flags(flags: State<'_, FeatureFlags>) -> Vec<FeatureFlag> {
+    flags.list()
+}
+
+/// Override a feature flag locally; `enabled: null` restores the default
+#[tauri::command]
+pub fn set_feature_flag(
+    name: String,
+    enabled: Option<bool>,
+    flags: State<'_, FeatureFlags>,
+) -> Result<Vec<FeatureFlag>, String> {
+    flags.set(&name, enabled)?;
+    Ok(flags.list())
+}
+
+// =============================================================================
+// Diagnostics
+// =============================================================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppInfo {
+    pub shell_version: String,
+    /// `None` while the engine has not signalled ready
+    pub sidecar_version: Option<String>,
+    /// IPC API version of the shell's commands
+    pub protocol_version: u32,
+    pub data_dir: String,
+    /// `<os>-<arch>`
+    pub platform: String,
+    /// Commit the shell was built from (12 characters) or `unknown`
+    pub build_hash: String,
+}
+
+/// Version, platform and data location for the About dialog and support
+/// tickets
+#[tauri::command]
+pub async fn get_app_info(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<AppInfo, String> {
+    Ok(AppInfo {
+        shell_version: app_handle.package_info().version.to_string(),
+        sidecar_version: engine.get_version().await,
+        protocol_version: API_VERSION,
+        data_dir: store::data_dir(&app_handle)?.display().to_string(),
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        build_hash: env!("PRISM_BUILD_HASH").to_string(),
+    })
+}
+
+/// Entries `get_recent_logs` returns when no limit is given
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+/// The last `limit` log entries of the shell and the engine sidecars at
+/// `level` or more severe (default info), oldest first
+#[tauri::command]
+pub async fn get_recent_logs(
+    level: Option<LogLevel>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    Ok(log_buffer::recent(
+        level.unwrap_or(LogLevel::Info),
+        limit.unwrap_or(DEFAULT_LOG_LIMIT),
+    ))
+}
+
+/// Run the end-to-end self-test suite and return a shareable report
+#[tauri::command]
+pub async fn run_self_test(
+    app_handle: AppHandle,
+    engine: State<'_, Arc<PythonEngine>>,
+) -> Result<SelfTestReport, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let app_version = app_handle.package_info().version.to_string();
+
+    Ok(self_test::run(&app_handle, &engine, &data_dir, app_version).await)
+}
+
+/// Delete app data in `scope` after backing it up, restarting the engine
+/// when its database goes
+#[tauri::command]
+pub async fn reset_app_data(
+    app_handle: AppHandle,
+    scope: ResetScope,
+    pool: State<'_, EnginePool>,
+) -> Result<ResetSummary, String> {
+    app_reset::reset(&app_handle, &pool, scope).await
+}
+
+/// Write the app data (database, settings, cached holdings) to a zip archive
+/// at `path`, e.g. to move it to another machine
+#[tauri::command]
+pub async fn create_backup(app_handle: AppHandle, path: String) -> Result<BackupSummary, String> {
+    let data_dir = store::data_dir(&app_handle)?;
+    let app_version = app_handle.package_info().version.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        data_backup::create(&data_dir, Path::new(&path), app_version)
+    })
+    .await
+    .map_err(|e| format!("Backup failed: {}", e))?
+}
+
+/// Verify the backup archive at `path` and replace the app data with it,
+/// restarting the engine
+#[tauri::command]
+pub async fn restore_backup(
+    app_handle: AppHandle,
+    path: String,
+    pool: State<'_, EnginePool>,
+) -> Result<RestoreSummary, String> {
+    data_backup::restore(&app_handle, &pool, PathBuf::from(path)).await
+}
+
+/// Issue the confirmation token `delete_all_data` requires; valid for one
+/// minute and a single attempt
+#[tauri::command]
+pub async fn request_data_deletion_token(
+    deletion: State<'_, DataDeletion>,
+) -> Result<DeletionToken, String> {
+    deletion.issue_token()
+}
+
+/// Permanently delete all app data (database, cached holdings, broker
+/// sessions, logs, outputs and reset backups) without a backup, then restart
+/// the engine on a fresh data dir
+#[tauri::command]
+pub async fn delete_all_data(
+    app_handle: AppHandle,
+    token: String,
+    pool: State<'_, EnginePool>,
+    deletion: State<'_, DataDeletion>,
+) -> Result<ResetSummary, String> {
+    app_reset::delete_all(&app_handle, &pool, &deletion, &token).await
+}
+
+// =============================================================================
+// Data Location
+// =============================================================================
+
+/// Get the data folder in use and the platform default
+#[tauri::command]
+pub async fn get_data_location(app_handle: AppHandle) -> Result<DataLocation, String> {
+    data_location::location(&app_handle)
+}
+
+/// Move the app data (database, caches, broker sessions, instance lock) to
+/// the empty or new folder `new_path` and restart the engine there. Passing
+/// the default path moves it back.
+#[tauri::command]
+pub async fn migrate_data_dir(
+    app_handle: AppHandle,
+    new_path: String,
+    pool: State<'_, EnginePool>,
+) -> Result<MigrationSummary, String> {
+    data_location::migrate(&app_handle, &pool, PathBuf::from(new_path)).await
+}
+
+// =============================================================================
+// Updates
+// =============================================================================
+
+/// Check the release feed for a newer version of the app
+#[tauri::command]
+pub async fn check_for_update(app_handle: AppHandle) -> Result<UpdateInfo, String> {
+    app_update::check(&app_handle).await
+}
+
+/// Download and install the update found by `check_for_update`, stopping the
+/// engine first, and restart. Progress is emitted as `update-progress`.
+#[tauri::command]
+pub async fn install_update(
+    app_handle: AppHandle,
+    pool: State<'_, EnginePool>,
+) -> Result<(), String> {
+    app_update::install(&app_handle, &pool).await
+}
+
+/// Result of the check after an update installed before this start
+#[tauri::command]
+pub fn get_update_verification() -> Option<UpdateVerification> {
+    app_update::verification()
+}
+
+// =============================================================================
+// Folders
+// =============================================================================
+
+/// Show `dir` in Finder, Explorer or the desktop's file manager, creating it
+/// if it does not exist yet.
+fn reveal_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    // Not waited on: Explorer exits with 1 even when it opened the folder
+    std::process::Command::new(opener)
+        .arg(dir)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open {}: {}", dir.display(), e))
+}
+
+/// Reveal the app data folder (`pipeline_health.json`, settings, database)
+#[tauri::command]
+pub async fn open_data_dir(app_handle: AppHandle) -> Result<String, String> {
+    let dir = store::data_dir(&app_handle)?;
+    reveal_dir(&dir)?;
+    Ok(dir.display().to_string())
+}
+
+/// Reveal the app log folder
+#[tauri::command]
+pub async fn open_logs_dir(app_handle: AppHandle) -> Result<String, String> {
+    let dir = logging::log_dir(&store::data_dir(&app_handle)?);
+    reveal_dir(&dir)?;
+    Ok(dir.display().to_string())
+}
+
+/// Legacy greet command (can be removed later)
+#[tauri::command]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
+register_commands! {
+    get_settings,
+    set_settings,
+    get_local_api_info,
+    rotate_local_api_token,
+    has_proxy_password,
+    set_proxy_password,
+    get_app_lock_status,
+    #[api(deprecated = "Use get_app_lock_status, which also covers the app PIN")]
+    get_os_auth_status,
+    lock_app,
+    unlock_with_os_auth,
+    set_app_pin,
+    verify_app_pin,
+    get_telemetry_preview,
+    log_event,
+    get_recent_reports,
+    list_error_reports,
+    set_error_report_resolved,
+    submit_error_report,
+    list_crash_reports,
+    get_crash_report,
+    get_email_settings,
+    set_email_settings,
+    send_test_email,
+    get_email_deliveries,
+    get_feature_flags,
+    set_feature_flag,
+    get_app_info,
+    get_recent_logs,
+    run_self_test,
+    reset_app_data,
+    create_backup,
+    restore_backup,
+    request_data_deletion_token,
+    delete_all_data,
+    get_data_location,
+    migrate_data_dir,
+    check_for_update,
+    install_update,
+    get_update_verification,
+    open_data_dir,
+    open_logs_dir,
+    #[api(deprecated = "Template command from the app scaffold; no replacement")]
+    greet,
+}
@@ -0,0 +1,289 @@
+//! Community Comparison
+//!
+//! Opt-in (`community_comparison` flag) percentile ranking of the user's
+//! allocation against anonymized distributions aggregated by the Hive, e.g.
+//! "your North America exposure is higher than 78% of contributors".
+//!
+//! Data flows one way. The request for the distributions carries no
+//! parameters; metrics are computed from the local dashboard and positions
+//! and ranked on this device. Nothing is uploaded beyond the regular,
+//! guarded ETF contributions.
+//!
+//! Each distribution is a list of evenly spaced quantiles (p0 to p100) of one
+//! metric across contributors. Distributions backed by fewer than
+//! `MIN_CONTRIBUTORS` are ignored. The aggregates are cached in
+//! `cache/community_distributions.json` for a day; a stale copy is served
+//! when the Hive is unreachable.
+
+use crate::commands::portfolio::{Allocations, Position};
+use crate::proxy;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Cache file inside the app data dir; kept out of `hive_cache/`, whose
+/// eviction expects decompositions only
+const CACHE_FILE: &str = "community_distributions.json";
+
+/// How long fetched distributions are served without refetching
+const CACHE_TTL_HOURS: i64 = 24;
+
+/// Smallest number of contributors behind a distribution worth ranking in
+const MIN_CONTRIBUTORS: u32 = 20;
+
+/// Timeout for the Hive request
+const FETCH_TIMEOUT_SECS: u64 = 15;
+
+/// One row of `get_community_distributions_rpc`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Distribution {
+    /// `region:<name>`, `sector:<name>`, `assetClass:<name>`,
+    /// `largestPosition`, `topFiveWeight` or `positionCount`
+    pub metric: String,
+    pub contributors: u32,
+    /// Evenly spaced quantiles from minimum to maximum, ascending
+    pub quantiles: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedDistributions {
+    fetched_at: DateTime<Utc>,
+    distributions: Vec<Distribution>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricComparison {
+    pub metric: String,
+    pub label: String,
+    /// Percent for weights, a count for `positionCount`
+    pub value: f64,
+    pub median: f64,
+    /// Share of contributors below `value` (0-100)
+    pub percentile: f64,
+    pub contributors: u32,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommunityComparison {
+    pub portfolio_id: u32,
+    pub fetched_at: DateTime<Utc>,
+    /// Served from cache after a failed refresh
+    pub stale: bool,
+    pub metrics: Vec<MetricComparison>,
+    /// Local metrics the Hive has no (large enough) distribution for
+    pub unmatched: Vec<String>,
+}
+
+fn cache_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("cache").join(CACHE_FILE)
+}
+
+async fn fetch_remote() -> Result<Vec<Distribution>, String> {
+    let url = std::env::var("SUPABASE_URL").unwrap_or_default();
+    let key = std::env::var("SUPABASE_ANON_KEY").unwrap_or_default();
+    if url.is_empty() || key.is_empty() {
+        return Err("Hive is not configured".to_string());
+    }
+
+    // Deliberately parameterless: the Hive learns nothing about the caller
+    proxy::client()
+        .post(format!(
+            "{}/rest/v1/rpc/get_community_distributions_rpc",
+            url.trim_end_matches('/')
+        ))
+        .header("apikey", &key)
+        .bearer_auth(&key)
+        .json(&json!({}))
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Hive request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Hive response: {}", e))
+}
+
+/// Distributions from cache when fresh, otherwise from the Hive. Returns the
+/// fetch time and whether a stale copy was served.
+pub async fn distributions(
+    data_dir: &Path,
+    force: bool,
+) -> Result<(Vec<Distribution>, DateTime<Utc>, bool), String> {
+    let path = cache_path(data_dir);
+    let cached: Option<CachedDistributions> = store::read_json(&path)?;
+    let fresh = |entry: &CachedDistributions| {
+        Utc::now() - entry.fetched_at < chrono::Duration::hours(CACHE_TTL_HOURS)
+    };
+    if let Some(entry) = cached.as_ref().filter(|entry| !force && fresh(entry)) {
+        return Ok((entry.distributions.clone(), entry.fetched_at, false));
+    }
+
+    match fetch_remote().await {
+        Ok(distributions) => {
+            let entry = CachedDistributions {
+                fetched_at: Utc::now(),
+                distributions,
+            };
+            store::write_json(&path, &entry)?;
+            Ok((entry.distributions, entry.fetched_at, false))
+        }
+        Err(e) => match cached {
+            Some(entry) => {
+                tracing::warn!("Community stats refresh failed, serving stale cache: {}", e);
+                Ok((entry.distributions, entry.fetched_at, true))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Local metrics with their labels. Allocation weights are in percent,
+/// position weights (0-1) are converted.
+fn local_metrics(allocations: &Allocations, positions: &[Position]) -> Vec<(String, String, f64)> {
+    let mut metrics = vec![];
+    for (prefix, weights) in [
+        ("region", &allocations.region),
+        ("sector", &allocations.sector),
+        ("assetClass", &allocations.asset_class),
+    ] {
+        let mut weights: Vec<(&String, &f64)> = weights.iter().collect();
+        weights.sort_by(|a, b| a.0.cmp(b.0));
+        for (bucket, weight) in weights {
+            metrics.push((
+                format!("{}:{}", prefix, bucket),
+                format!("{} exposure", bucket),
+                *weight,
+            ));
+        }
+    }
+
+    let mut weights: Vec<f64> = positions.iter().map(|p| p.weight * 100.0).collect();
+    weights.sort_by(|a, b| b.total_cmp(a));
+    if let Some(largest) = weights.first() {
+        metrics.push((
+            "largestPosition".to_string(),
+            "Largest position weight".to_string(),
+            *largest,
+        ));
+        metrics.push((
+            "topFiveWeight".to_string(),
+            "Top five positions weight".to_string(),
+            weights.iter().take(5).sum(),
+        ));
+    }
+    metrics.push((
+        "positionCount".to_string(),
+        "Number of positions".to_string(),
+        positions.len() as f64,
+    ));
+    metrics
+}
+
+/// Share (0-100) of the distribution below `value`, interpolated between
+/// neighbouring quantiles
+fn percentile_rank(quantiles: &[f64], value: f64) -> f64 {
+    let (Some(first), Some(last)) = (quantiles.first(), quantiles.last()) else {
+        return 0.0;
+    };
+    if value <= *first {
+        return 0.0;
+    }
+    if value >= *last {
+        return 100.0;
+    }
+    let steps = (quantiles.len() - 1) as f64;
+    let segment = quantiles
+        .windows(2)
+        .position(|pair| value < pair[1])
+        .unwrap_or(quantiles.len() - 2);
+    let (low, high) = (quantiles[segment], quantiles[segment + 1]);
+    let within = if high > low {
+        (value - low) / (high - low)
+    } else {
+        0.0
+    };
+    (segment as f64 + within) / steps * 100.0
+}
+
+fn median(quantiles: &[f64]) -> f64 {
+    let middle = (quantiles.len() - 1) as f64 / 2.0;
+    let (low, high) = (
+        quantiles[middle.floor() as usize],
+        quantiles[middle.ceil() as usize],
+    );
+    (low + high) / 2.0
+}
+
+fn summary(metric: &str, label: &str, percentile: f64) -> String {
+    // Bucket names keep their capitalization; fixed labels are ASCII
+    let label = if metric.contains(':') {
+        label.to_string()
+    } else {
+        label[..1].to_lowercase() + &label[1..]
+    };
+    if percentile >= 50.0 {
+        format!(
+            "Your {} is higher than {:.0}% of contributors",
+            label, percentile
+        )
+    } else {
+        format!(
+            "Your {} is lower than {:.0}% of contributors",
+            label,
+            100.0 - percentile
+        )
+    }
+}
+
+/// Rank the local metrics of a portfolio against `distributions`.
+pub fn compare(
+    portfolio_id: u32,
+    allocations: &Allocations,
+    positions: &[Position],
+    distributions: &[Distribution],
+    fetched_at: DateTime<Utc>,
+    stale: bool,
+) -> CommunityComparison {
+    let by_metric: HashMap<&str, &Distribution> = distributions
+        .iter()
+        .filter(|d| d.contributors >= MIN_CONTRIBUTORS && d.quantiles.len() >= 2)
+        .map(|d| (d.metric.as_str(), d))
+        .collect();
+
+    let mut metrics = vec![];
+    let mut unmatched = vec![];
+    for (metric, label, value) in local_metrics(allocations, positions) {
+        let Some(distribution) = by_metric.get(metric.as_str()) else {
+            unmatched.push(metric);
+            continue;
+        };
+        let percentile = percentile_rank(&distribution.quantiles, value);
+        metrics.push(MetricComparison {
+            summary: summary(&metric, &label, percentile),
+            median: median(&distribution.quantiles),
+            contributors: distribution.contributors,
+            metric,
+            label,
+            value,
+            percentile,
+        });
+    }
+
+    CommunityComparison {
+        portfolio_id,
+        fetched_at,
+        stale,
+        metrics,
+        unmatched,
+    }
+}
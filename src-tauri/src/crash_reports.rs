@@ -0,0 +1,239 @@
+//! Engine Crash Reports
+//!
+//! Python tracebacks in a sidecar's stderr are collected line by line. Once
+//! the exception line ends one (or the sidecar exits mid-traceback) it is
+//! written to `crash_reports/{id}.json` together with the engine's session
+//! ID, the commands most recently sent to it and the shell and engine
+//! versions, and announced with an `engine-crash` event. Secrets in the
+//! traceback are redacted. Only the newest `MAX_REPORTS` are kept.
+
+use crate::python_engine::{EngineRole, PythonEngine, RecentCommand};
+use crate::redaction;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+pub(crate) const REPORT_DIR: &str = "crash_reports";
+
+pub const EVENT: &str = "engine-crash";
+
+/// Reports kept; older ones are deleted when a new one is written
+const MAX_REPORTS: usize = 50;
+
+/// Lines kept per traceback; deeper ones are cut from the middle
+const MAX_TRACEBACK_LINES: usize = 400;
+
+const TRACEBACK_START: &str = "Traceback (most recent call last):";
+
+/// Tracebacks being collected, per sidecar role
+static PENDING: Mutex<Vec<(EngineRole, Vec<String>)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    /// `primary` or `worker`
+    pub role: String,
+    pub app_version: String,
+    pub engine_version: Option<String>,
+    pub session_id: Option<String>,
+    /// Final line of the traceback, e.g. `KeyError: 'isin'`
+    pub exception: String,
+    pub traceback: String,
+    /// Commands sent to the engine before the crash, oldest first
+    pub recent_commands: Vec<RecentCommand>,
+    /// The sidecar exited before the traceback was complete
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub role: String,
+    pub exception: String,
+}
+
+impl From<&CrashReport> for CrashReportSummary {
+    fn from(report: &CrashReport) -> Self {
+        Self {
+            id: report.id.clone(),
+            created_at: report.created_at,
+            role: report.role.clone(),
+            exception: report.exception.clone(),
+        }
+    }
+}
+
+fn report_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(REPORT_DIR)
+}
+
+/// Lines that continue a traceback: indented frames and source lines, and
+/// the separators Python prints between chained exceptions
+fn continues_traceback(line: &str) -> bool {
+    line.starts_with(' ')
+        || line.starts_with('\t')
+        || line.starts_with(TRACEBACK_START)
+        || line.starts_with("During handling of the above exception")
+        || line.starts_with("The above exception was the direct cause")
+}
+
+/// What a stderr line did to the traceback being collected for a role
+enum Collected {
+    None,
+    Continued,
+    /// The traceback ended with this line
+    Complete(Vec<String>),
+}
+
+fn collect(role: EngineRole, line: &str) -> Collected {
+    let Ok(mut pending) = PENDING.lock() else {
+        return Collected::None;
+    };
+    let index = pending
+        .iter()
+        .position(|(pending_role, _)| *pending_role == role);
+
+    let Some(index) = index else {
+        if line.contains(TRACEBACK_START) {
+            pending.push((role, vec![line.to_string()]));
+            return Collected::Continued;
+        }
+        return Collected::None;
+    };
+    if line.trim().is_empty() {
+        return Collected::Continued;
+    }
+    let lines = &mut pending[index].1;
+    if lines.len() < MAX_TRACEBACK_LINES {
+        lines.push(line.to_string());
+    } else {
+        // Keep the head and the most recent frames
+        lines.remove(MAX_TRACEBACK_LINES / 2);
+        lines.push(line.to_string());
+    }
+    if continues_traceback(line) {
+        return Collected::Continued;
+    }
+    Collected::Complete(pending.remove(index).1)
+}
+
+/// Feed a raw stderr line of the sidecar running as `role`; writes a report
+/// when it completes a traceback.
+pub async fn stderr_line(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    role: EngineRole,
+    line: &str,
+) {
+    if let Collected::Complete(lines) = collect(role, line) {
+        write_report(app_handle, engine, role, lines, false).await;
+    }
+}
+
+/// Report a traceback cut short by the sidecar exiting.
+pub async fn sidecar_exited(app_handle: &AppHandle, engine: &PythonEngine, role: EngineRole) {
+    let lines = PENDING.lock().ok().and_then(|mut pending| {
+        let index = pending
+            .iter()
+            .position(|(pending_role, _)| *pending_role == role)?;
+        Some(pending.remove(index).1)
+    });
+    if let Some(lines) = lines {
+        write_report(app_handle, engine, role, lines, true).await;
+    }
+}
+
+async fn write_report(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    role: EngineRole,
+    lines: Vec<String>,
+    truncated: bool,
+) {
+    let created_at = Utc::now();
+    let report = CrashReport {
+        id: format!(
+            "{}-{}",
+            created_at.format("%Y%m%dT%H%M%S%3fZ"),
+            role.as_str()
+        ),
+        created_at,
+        role: role.as_str().to_string(),
+        app_version: app_handle.package_info().version.to_string(),
+        engine_version: engine.get_version().await,
+        session_id: engine.session_id().await,
+        exception: lines
+            .last()
+            .map(|line| redaction::redact(line.trim()))
+            .unwrap_or_default(),
+        traceback: redaction::redact(&lines.join("\n")),
+        recent_commands: engine.recent_commands().await,
+        truncated,
+    };
+
+    let saved = store::data_dir(app_handle).and_then(|data_dir| save(&data_dir, &report));
+    match saved {
+        Ok(()) => {
+            tracing::error!("Python {} crashed: {}", role.label(), report.exception);
+            let _ = app_handle.emit(EVENT, CrashReportSummary::from(&report));
+        }
+        Err(e) => tracing::warn!("Failed to write crash report: {}", e),
+    }
+}
+
+fn save(data_dir: &Path, report: &CrashReport) -> Result<(), String> {
+    let dir = report_dir(data_dir);
+    store::write_json(&dir.join(format!("{}.json", report.id)), report)?;
+
+    let mut ids = report_ids(&dir);
+    if ids.len() > MAX_REPORTS {
+        ids.sort();
+        for id in &ids[..ids.len() - MAX_REPORTS] {
+            let path = dir.join(format!("{}.json", id));
+            let _ = std::fs::remove_file(store::backup_path(&path));
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
+
+fn report_ids(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    name.strip_suffix(".json").map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// All stored reports, newest first.
+pub fn list(data_dir: &Path) -> Result<Vec<CrashReportSummary>, String> {
+    let mut ids = report_ids(&report_dir(data_dir));
+    ids.sort_by(|a, b| b.cmp(a));
+    let mut summaries = vec![];
+    for id in ids {
+        if let Some(report) = get(data_dir, &id)? {
+            summaries.push(CrashReportSummary::from(&report));
+        }
+    }
+    Ok(summaries)
+}
+
+pub fn get(data_dir: &Path, id: &str) -> Result<Option<CrashReport>, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid crash report ID: {}", id));
+    }
+    store::read_json(&report_dir(data_dir).join(format!("{}.json", id)))
+}
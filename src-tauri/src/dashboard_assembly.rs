@@ -0,0 +1,158 @@
+//! Progressive Dashboard Assembly
+//!
+//! Instead of one response that arrives only when every component is ready,
+//! an assembly task emits `dashboard-partial` events as pieces become
+//! available:
+//!
+//! 1. `totals` - headline numbers from the last persisted dashboard (instant)
+//! 2. `allocations` - fresh totals, allocations and top holdings from the engine
+//! 3. `lookThrough` - true holdings and the ETF overlap matrix (slowest)
+//! 4. `complete` - final marker listing any sections that failed
+
+use crate::commands::{pipeline, portfolio};
+use crate::python_engine::PythonEngine;
+use crate::store;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+/// Last successful dashboard response, used for the instant first paint
+const LAST_DASHBOARD_FILE: &str = "last_dashboard.json";
+
+static NEXT_ASSEMBLY_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Payload of the `dashboard-partial` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardPartial {
+    pub assembly_id: u64,
+    pub portfolio_id: u32,
+    pub section: &'static str,
+    pub data: Value,
+    /// Data came from the shell's last persisted dashboard, not the engine
+    pub cached: bool,
+    /// Set only on the final `complete` event
+    pub complete: bool,
+}
+
+fn last_dashboard_path(data_dir: &Path, portfolio_id: u32) -> PathBuf {
+    data_dir
+        .join("dashboard")
+        .join(format!("{}_{}", portfolio_id, LAST_DASHBOARD_FILE))
+}
+
+/// Persist the latest dashboard so the next assembly can paint totals first.
+pub fn remember<T: Serialize>(data_dir: &Path, portfolio_id: u32, dashboard: &T) {
+    if let Err(e) = store::write_json(&last_dashboard_path(data_dir, portfolio_id), dashboard) {
+        tracing::warn!("Failed to persist last dashboard: {}", e);
+    }
+}
+
+/// The last dashboard persisted by `remember`, if it still parses as `T`
+pub fn recall<T: DeserializeOwned>(data_dir: &Path, portfolio_id: u32) -> Option<T> {
+    store::read_json(&last_dashboard_path(data_dir, portfolio_id))
+        .ok()
+        .flatten()
+}
+
+/// Drop the persisted dashboard of a deleted portfolio.
+pub fn forget(data_dir: &Path, portfolio_id: u32) {
+    let _ = std::fs::remove_file(last_dashboard_path(data_dir, portfolio_id));
+}
+
+fn pick(data: &Value, keys: &[&str]) -> Value {
+    let picked: serde_json::Map<String, Value> = keys
+        .iter()
+        .filter_map(|key| data.get(*key).map(|value| (key.to_string(), value.clone())))
+        .collect();
+    Value::Object(picked)
+}
+
+/// Start assembling the dashboard for `portfolio_id` and return the assembly
+/// id carried by every `dashboard-partial` event of this run.
+pub fn start(app_handle: AppHandle, engine: Arc<PythonEngine>, portfolio_id: u32) -> u64 {
+    let assembly_id = NEXT_ASSEMBLY_ID.fetch_add(1, Ordering::SeqCst);
+
+    tauri::async_runtime::spawn(async move {
+        let emit = |section: &'static str, data: Value, cached: bool, complete: bool| {
+            let _ = app_handle.emit(
+                "dashboard-partial",
+                DashboardPartial {
+                    assembly_id,
+                    portfolio_id,
+                    section,
+                    data,
+                    cached,
+                    complete,
+                },
+            );
+        };
+        let mut missing: Vec<&'static str> = vec![];
+        let data_dir = store::data_dir(&app_handle).ok();
+
+        // 1. Totals from the last persisted dashboard
+        let cached = data_dir.as_ref().and_then(|dir| {
+            store::read_json::<Value>(&last_dashboard_path(dir, portfolio_id))
+                .ok()
+                .flatten()
+        });
+        if let Some(cached) = cached {
+            let totals = pick(
+                &cached,
+                &["totalValue", "totalGain", "gainPercentage", "dayChange", "dayChangePercent", "lastUpdated"],
+            );
+            emit("totals", totals, true, false);
+        }
+
+        // 2. Fresh totals and allocations from the engine
+        match engine.request("get_dashboard_data", json!({ "portfolioId": portfolio_id })).await {
+            Ok(dashboard) => {
+                if let Some(dir) = &data_dir {
+                    remember(dir, portfolio_id, &dashboard);
+                }
+                emit("allocations", dashboard, false, false);
+            }
+            Err(e) => {
+                tracing::warn!("Dashboard assembly {}: allocations failed: {}", assembly_id, e);
+                missing.push("allocations");
+            }
+        }
+
+        // 3. Look-through analytics
+        let true_holdings = engine.request("get_true_holdings", json!({})).await;
+        let overlap = match &true_holdings {
+            Ok(look_through) => portfolio::fetch_positions(&app_handle, &engine, portfolio_id)
+                .await
+                .and_then(|positions| {
+                    pipeline::overlap_of(&app_handle, &positions.positions, look_through.clone())
+                })
+                .map_err(|e| {
+                    tracing::warn!("Dashboard assembly {}: overlap failed: {}", assembly_id, e)
+                })
+                .ok(),
+            Err(_) => None,
+        };
+        if true_holdings.is_err() {
+            missing.push("lookThrough");
+        } else {
+            emit(
+                "lookThrough",
+                json!({
+                    "trueHoldings": true_holdings.as_ref().ok(),
+                    "overlap": overlap,
+                }),
+                false,
+                false,
+            );
+        }
+
+        // 4. Completeness marker
+        emit("complete", json!({ "missing": missing }), false, true);
+    });
+
+    assembly_id
+}
@@ -0,0 +1,393 @@
+//! Data Backup and Restore
+//!
+//! `create` writes the app data dir (engine database, settings, cached
+//! holdings and the other shell stores) to a zip archive so it can be moved to
+//! another machine; `restore` swaps it back in.
+//!
+//! The database is copied with SQLite's online backup API, so a running engine
+//! does not have to stop for a backup. Reset backups and the instance lock are
+//! left out. Every archive carries `backup_manifest.json` with the size and
+//! SHA-256 of each file; a restore checks the manifest, the file hashes and
+//! the database integrity before the engines are stopped. The data being
+//! replaced is moved to `reset_backups/pre-restore-{timestamp}/`.
+
+use crate::app_reset;
+use crate::db_recovery;
+use crate::python_engine::EnginePool;
+use crate::store;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const MANIFEST_FILE: &str = "backup_manifest.json";
+
+/// Archive layout version; newer archives are rejected
+const FORMAT_VERSION: u32 = 1;
+
+/// Restored files are unpacked here (inside the data dir) before the swap
+const STAGING_DIR: &str = ".restore-staging";
+
+/// Database snapshot taken for the archive, removed afterwards
+const DB_SNAPSHOT: &str = ".backup-snapshot.db";
+
+/// Not archived: the database goes in as a snapshot, the rest is
+/// instance-specific or derived
+fn skipped(name: &str) -> bool {
+    [
+        app_reset::BACKUP_DIR,
+        app_reset::LOCK_FILE,
+        STAGING_DIR,
+        DB_SNAPSHOT,
+        "prism.db-wal",
+        "prism.db-shm",
+    ]
+    .contains(&name)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    /// Path relative to the app data dir, `/`-separated
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub app_version: String,
+    pub files: Vec<BackupFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub file_count: usize,
+    /// Uncompressed size of the archived files
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSummary {
+    pub created_at: DateTime<Utc>,
+    pub app_version: String,
+    pub file_count: usize,
+    /// Where the replaced data was moved
+    pub previous_data_path: String,
+    pub engine_restarted: bool,
+    /// Shell state (flags, schedules) is only reloaded on launch
+    pub requires_app_restart: bool,
+    /// Non-fatal failures; the restore itself went through
+    pub errors: Vec<String>,
+}
+
+/// Writer that hashes and counts what passes through it
+struct Hashing<W> {
+    inner: W,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl<W: Write> Write for Hashing<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Copy `reader` into `writer`; returns the byte count and SHA-256.
+fn copy_hashed(reader: &mut impl Read, writer: impl Write) -> std::io::Result<(u64, String)> {
+    let mut hashing = Hashing {
+        inner: writer,
+        hasher: Sha256::new(),
+        bytes: 0,
+    };
+    std::io::copy(reader, &mut hashing)?;
+    Ok((hashing.bytes, format!("{:x}", hashing.hasher.finalize())))
+}
+
+/// Files to archive as (archive path, source path), depth first.
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut Vec<(String, PathBuf)>,
+) -> Result<(), String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if prefix.is_empty() && skipped(&name) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let archived = format!("{}{}", prefix, name);
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &format!("{}/", archived), files)?;
+        } else if file_type.is_file() {
+            files.push((archived, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+fn write_archive(
+    target: &Path,
+    files: &[(String, PathBuf)],
+    app_version: String,
+) -> Result<BackupManifest, String> {
+    let mut manifest = BackupManifest {
+        format_version: FORMAT_VERSION,
+        created_at: Utc::now(),
+        app_version,
+        files: vec![],
+    };
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    let file = File::create(target)
+        .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+
+    for (name, source) in files {
+        let mut reader =
+            File::open(source).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        let (bytes, sha256) = copy_hashed(&mut reader, &mut zip)
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        manifest.files.push(BackupFile {
+            path: name.clone(),
+            bytes,
+            sha256,
+        });
+    }
+
+    let content = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    zip.start_file(MANIFEST_FILE, options)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    zip.write_all(&content)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    zip.finish()
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    Ok(manifest)
+}
+
+/// Write the app data dir to the zip archive at `target`.
+pub fn create(
+    data_dir: &Path,
+    target: &Path,
+    app_version: String,
+) -> Result<BackupSummary, String> {
+    if target.starts_with(data_dir) {
+        return Err("Choose a location outside the app data folder".to_string());
+    }
+
+    let mut files = vec![];
+    collect_files(data_dir, "", &mut files)?;
+
+    // Archive a consistent snapshot instead of the live database and its WAL
+    let db = db_recovery::db_path(data_dir);
+    let snapshot = data_dir.join(DB_SNAPSHOT);
+    files.retain(|(name, _)| name != db_recovery::DB_FILE);
+    if db.exists() {
+        let source = Connection::open_with_flags(&db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("Cannot open database: {}", e))?;
+        source
+            .backup(rusqlite::DatabaseName::Main, &snapshot, None)
+            .map_err(|e| format!("Database backup failed: {}", e))?;
+        files.push((db_recovery::DB_FILE.to_string(), snapshot.clone()));
+    }
+
+    let result = write_archive(target, &files, app_version);
+    let _ = std::fs::remove_file(&snapshot);
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = std::fs::remove_file(target);
+            return Err(e);
+        }
+    };
+
+    Ok(BackupSummary {
+        path: target.display().to_string(),
+        created_at: manifest.created_at,
+        file_count: manifest.files.len(),
+        bytes: manifest.files.iter().map(|file| file.bytes).sum(),
+    })
+}
+
+/// Unpack and verify the archive at `source` into `staging`.
+fn unpack(source: &Path, staging: &Path) -> Result<BackupManifest, String> {
+    let file =
+        File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Not a valid backup archive: {}", e))?;
+
+    let manifest: BackupManifest = {
+        let entry = archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| "Not a Portfolio Prism backup (manifest missing)".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Invalid backup manifest: {}", e))?
+    };
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} is newer than this app supports ({}); update the app first",
+            manifest.format_version, FORMAT_VERSION
+        ));
+    }
+
+    for expected in &manifest.files {
+        let mut entry = archive
+            .by_name(&expected.path)
+            .map_err(|_| format!("Backup is missing {}", expected.path))?;
+        // Rejects absolute paths and `..` components
+        let relative = entry
+            .enclosed_name()
+            .filter(|name| !name.as_os_str().is_empty())
+            .ok_or_else(|| format!("Backup contains an unsafe path: {}", expected.path))?;
+        let path = staging.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let target = File::create(&path)
+            .map_err(|e| format!("Failed to unpack {}: {}", expected.path, e))?;
+        let (bytes, sha256) = copy_hashed(&mut entry, target)
+            .map_err(|e| format!("Failed to unpack {}: {}", expected.path, e))?;
+        if bytes != expected.bytes || sha256 != expected.sha256 {
+            return Err(format!(
+                "Backup is damaged: {} does not match its checksum",
+                expected.path
+            ));
+        }
+    }
+
+    let db = db_recovery::db_path(staging);
+    if db.exists() {
+        db_recovery::quick_check(&db)
+            .map_err(|e| format!("Backed-up database is damaged: {}", e))?;
+    }
+    Ok(manifest)
+}
+
+/// Move every top-level entry of `from` except `keep` into `to`; returns the
+/// names moved.
+fn move_entries(from: &Path, to: &Path, keep: &[&str]) -> Result<Vec<String>, String> {
+    std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries =
+        std::fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    let mut moved = vec![];
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if keep.contains(&name.as_str()) {
+            continue;
+        }
+        std::fs::rename(entry.path(), to.join(&name))
+            .map_err(|e| format!("Failed to move {}: {}", name, e))?;
+        moved.push(name);
+    }
+    Ok(moved)
+}
+
+/// Replace the data dir contents with `staging`, keeping the old contents in
+/// `previous`. Puts the old data back if the swap fails halfway.
+fn swap_in(data_dir: &Path, staging: &Path, previous: &Path) -> Result<(), String> {
+    let keep = [app_reset::BACKUP_DIR, app_reset::LOCK_FILE, STAGING_DIR];
+    move_entries(data_dir, previous, &keep)?;
+    if let Err(e) = move_entries(staging, data_dir, &[]) {
+        let restored: Vec<String> = std::fs::read_dir(data_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .filter(|name| !keep.contains(&name.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for name in restored {
+            let _ = std::fs::remove_dir_all(data_dir.join(&name))
+                .or_else(|_| std::fs::remove_file(data_dir.join(&name)));
+        }
+        let _ = move_entries(previous, data_dir, &[]);
+        return Err(format!("Restore failed, previous data kept: {}", e));
+    }
+    Ok(())
+}
+
+/// Verify the archive at `source`, then stop the engines, replace the app
+/// data with its contents and restart them.
+pub async fn restore(
+    app_handle: &AppHandle,
+    pool: &EnginePool,
+    source: PathBuf,
+) -> Result<RestoreSummary, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let staging = data_dir.join(STAGING_DIR);
+
+    let unpack_dir = data_dir.clone();
+    let manifest = tauri::async_runtime::spawn_blocking(move || {
+        let staging = unpack_dir.join(STAGING_DIR);
+        let _ = std::fs::remove_dir_all(&staging);
+        let result = unpack(&source, &staging);
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&staging);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Restore failed: {}", e))??;
+
+    let mut errors = vec![];
+    let stopped = app_reset::stop_engines(pool, &mut errors).await;
+
+    let previous = data_dir.join(app_reset::BACKUP_DIR).join(format!(
+        "pre-restore-{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    let swap_dir = data_dir.clone();
+    let swap_previous = previous.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let result = swap_in(&swap_dir, &staging, &swap_previous);
+        let _ = std::fs::remove_dir_all(&staging);
+        result
+    })
+    .await
+    .map_err(|e| format!("Restore failed: {}", e))
+    .and_then(|result| result);
+
+    let (engine_restarted, restart_errors) = app_reset::restart_engines(app_handle, stopped);
+
+    result?;
+    errors.extend(restart_errors);
+    Ok(RestoreSummary {
+        created_at: manifest.created_at,
+        app_version: manifest.app_version,
+        file_count: manifest.files.len(),
+        previous_data_path: previous.display().to_string(),
+        engine_restarted,
+        requires_app_restart: true,
+        errors,
+    })
+}
@@ -0,0 +1,297 @@
+//! Data Directory Location
+//!
+//! The app data dir defaults to the platform location and can be moved, e.g.
+//! onto an external or encrypted drive. A moved location is recorded in
+//! `data_location.json`, which always stays in the default dir; everything
+//! else lives in the active data dir.
+//!
+//! A move copies the data dir while the engines are stopped, verifies the
+//! copy, takes the instance lock in the new dir and only then rewrites the
+//! location file. Until that write the old dir stays authoritative, so an
+//! interrupted move leaves the app on its old, complete data. The old copy
+//! is removed last and the engines restart on the new dir.
+
+use crate::app_reset;
+use crate::instance_lock;
+use crate::python_engine::EnginePool;
+use crate::session_vault;
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Kept in the default dir; absent while the data lives there
+pub(crate) const LOCATION_FILE: &str = "data_location.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredLocation {
+    path: PathBuf,
+}
+
+/// Active data dir, resolved once per launch and on every move
+static ACTIVE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataLocation {
+    pub path: String,
+    pub default_path: String,
+    /// Whether the data dir was moved away from the default
+    pub custom: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationSummary {
+    pub previous_path: String,
+    pub path: String,
+    /// Top-level files and folders moved
+    pub entry_count: usize,
+    pub bytes: u64,
+    pub engine_restarted: bool,
+    /// Logs, caches and flags keep their old paths until the next launch
+    pub requires_app_restart: bool,
+    /// Non-fatal failures, e.g. leftovers in the old dir; the move went through
+    pub errors: Vec<String>,
+}
+
+/// Platform app data dir, which holds the location file
+pub fn default_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+fn set_active(dir: &Path) {
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = Some(dir.to_path_buf());
+    }
+}
+
+/// Resolve the active data dir. A moved data dir that is not mounted is an
+/// error rather than a silent fallback to an empty default dir.
+pub fn resolve(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(dir) = ACTIVE.lock().ok().and_then(|active| active.clone()) {
+        return Ok(dir);
+    }
+
+    let default = default_dir(app_handle)?;
+    let dir = match store::read_json::<StoredLocation>(&default.join(LOCATION_FILE))? {
+        Some(location) if !location.path.is_dir() => {
+            return Err(format!(
+                "The data folder {} is not available. Reconnect the drive it is on \
+                 and restart Portfolio Prism.",
+                location.path.display()
+            ));
+        }
+        Some(location) => location.path,
+        None => default,
+    };
+    set_active(&dir);
+    Ok(dir)
+}
+
+pub fn location(app_handle: &AppHandle) -> Result<DataLocation, String> {
+    let path = resolve(app_handle)?;
+    let default = default_dir(app_handle)?;
+    Ok(DataLocation {
+        path: path.display().to_string(),
+        default_path: default.display().to_string(),
+        custom: path != default,
+    })
+}
+
+/// Files that belong to the dir itself rather than to the data in it
+fn is_local(name: &str) -> bool {
+    name == app_reset::LOCK_FILE || name.starts_with(LOCATION_FILE)
+}
+
+fn data_entries(dir: &Path) -> Result<Vec<OsString>, String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.file_name())
+        .filter(|name| !is_local(&name.to_string_lossy()))
+        .collect())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn validate(from: &Path, to: &Path) -> Result<(), String> {
+    if !to.is_absolute() {
+        return Err("Choose an absolute folder path".to_string());
+    }
+    let (from, to) = (canonical(from), canonical(to));
+    if from == to {
+        return Err(format!("The data folder already is {}", to.display()));
+    }
+    if to.starts_with(&from) || from.starts_with(&to) {
+        return Err(
+            "The new folder cannot be inside the current data folder or contain it".to_string(),
+        );
+    }
+    if to.exists() {
+        if !to.is_dir() {
+            return Err(format!("{} is not a folder", to.display()));
+        }
+        if !data_entries(&to)?.is_empty() {
+            return Err(format!("{} is not empty", to.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Copy the data entries of `from` into `to`, checking every entry arrived
+/// in full. Returns the entry names and the bytes copied.
+fn copy_data(from: &Path, to: &Path) -> Result<(Vec<OsString>, u64), String> {
+    std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+
+    let names = data_entries(from)?;
+    let mut bytes = 0;
+    for name in &names {
+        let (source, target) = (from.join(name), to.join(name));
+        app_reset::copy_entry(&source, &target)
+            .map_err(|e| format!("Failed to copy {}: {}", name.to_string_lossy(), e))?;
+        let size = app_reset::entry_size(&source);
+        if app_reset::entry_size(&target) != size {
+            return Err(format!("Copy of {} is incomplete", name.to_string_lossy()));
+        }
+        bytes += size;
+    }
+    Ok((names, bytes))
+}
+
+/// Undo a partial copy into `to`
+fn discard_copy(to: &Path, created: bool) {
+    if created {
+        let _ = std::fs::remove_dir_all(to);
+    } else if let Ok(names) = data_entries(to) {
+        for name in names {
+            let _ = app_reset::remove_entry(&to.join(name));
+        }
+        let _ = std::fs::remove_file(to.join(app_reset::LOCK_FILE));
+    }
+}
+
+/// Point the app at `to`: lock it, then record it as the data dir
+fn switch_to(default: &Path, from: &Path, to: &Path) -> Result<(), String> {
+    instance_lock::acquire(to)?;
+
+    let location_path = default.join(LOCATION_FILE);
+    let recorded = if to == default {
+        let _ = std::fs::remove_file(store::backup_path(&location_path));
+        std::fs::remove_file(&location_path)
+            .or_else(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            })
+            .map_err(|e| format!("Failed to reset the data location: {}", e))
+    } else {
+        store::write_json(
+            &location_path,
+            &StoredLocation {
+                path: to.to_path_buf(),
+            },
+        )
+    };
+    if let Err(e) = recorded {
+        // Stay on the old dir, which is still complete
+        if let Err(relock) = instance_lock::acquire(from) {
+            tracing::error!("Failed to re-lock {}: {}", from.display(), relock);
+        }
+        return Err(e);
+    }
+
+    set_active(to);
+    Ok(())
+}
+
+/// Remove the data left in `from` after a move; failures are reported
+fn remove_old(from: &Path, default: &Path, names: &[OsString], errors: &mut Vec<String>) {
+    for name in names {
+        if let Err(e) = app_reset::remove_entry(&from.join(name)) {
+            errors.push(format!(
+                "Failed to remove old {}: {}",
+                name.to_string_lossy(),
+                e
+            ));
+        }
+    }
+    let _ = std::fs::remove_file(from.join(app_reset::LOCK_FILE));
+    // The default dir stays for the location file
+    if from != default {
+        let _ = std::fs::remove_dir(from);
+    }
+}
+
+/// Move the data dir (database, caches, sessions, lock) to `to` and restart
+/// the engines there.
+pub async fn migrate(
+    app_handle: &AppHandle,
+    pool: &EnginePool,
+    to: PathBuf,
+) -> Result<MigrationSummary, String> {
+    let from = resolve(app_handle)?;
+    let default = default_dir(app_handle)?;
+    validate(&from, &to)?;
+
+    let mut errors = vec![];
+    let stopped = app_reset::stop_engines(pool, &mut errors).await;
+    // Sidecars seal on exit too, but asynchronously
+    if let Err(e) = session_vault::seal(&from) {
+        errors.push(e);
+    }
+
+    let (move_from, move_to, move_default) = (from.clone(), to.clone(), default.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let created = !move_to.exists();
+        let copied = copy_data(&move_from, &move_to)
+            .and_then(|copied| switch_to(&move_default, &move_from, &move_to).map(|()| copied));
+        if copied.is_err() {
+            discard_copy(&move_to, created);
+        }
+        copied
+    })
+    .await
+    .map_err(|e| format!("Moving the data folder failed: {}", e))
+    .and_then(|result| result);
+
+    if let Ok((names, _)) = &result {
+        tracing::info!(
+            "Data folder moved from {} to {}",
+            from.display(),
+            to.display()
+        );
+        let (names, from, default) = (names.clone(), from.clone(), default.clone());
+        let mut cleanup_errors = tauri::async_runtime::spawn_blocking(move || {
+            let mut errors = vec![];
+            remove_old(&from, &default, &names, &mut errors);
+            errors
+        })
+        .await
+        .unwrap_or_default();
+        errors.append(&mut cleanup_errors);
+    }
+
+    // The engines pick up whichever dir is active now
+    let (engine_restarted, restart_errors) = app_reset::restart_engines(app_handle, stopped);
+
+    let (names, bytes) = result?;
+    errors.extend(restart_errors);
+    Ok(MigrationSummary {
+        previous_path: from.display().to_string(),
+        path: to.display().to_string(),
+        entry_count: names.len(),
+        bytes,
+        engine_restarted,
+        requires_app_restart: true,
+        errors,
+    })
+}
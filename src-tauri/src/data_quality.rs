@@ -0,0 +1,209 @@
+//! Dashboard Data Quality
+//!
+//! Summarizes how trustworthy the headline dashboard numbers are: how much of
+//! the portfolio could be decomposed, how old the oldest fund data is, and how
+//! many constituents come from estimation rather than real holdings files.
+//!
+//! Fund freshness is tracked by the shell in `data_freshness.json`: every
+//! successful pipeline run stamps the ETFs it decomposed, so staleness survives
+//! reports that only partially succeed.
+
+use crate::{pipeline_report, store};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Freshness store file name inside the app data dir
+pub(crate) const FRESHNESS_FILE: &str = "data_freshness.json";
+
+/// Trust summary attached to `DashboardData`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataQuality {
+    /// Share of ETF positions with usable constituent data (0-100)
+    pub coverage_percent: f64,
+    /// Age in hours of the least recently refreshed fund decomposition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stalest_fund_age_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stalest_fund_isin: Option<String>,
+    /// Constituents that come from estimated rather than sourced holdings
+    pub estimated_constituents: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_pipeline_success: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FreshnessStore {
+    last_pipeline_success: Option<String>,
+    /// ETF ISIN -> RFC 3339 timestamp of its last successful decomposition
+    #[serde(default)]
+    funds: HashMap<String, String>,
+}
+
+fn freshness_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(FRESHNESS_FILE)
+}
+
+fn is_decomposed(entry: &serde_json::Value) -> bool {
+    entry["status"].as_str() == Some("success")
+}
+
+fn is_estimated(entry: &serde_json::Value) -> bool {
+    let source = entry["source"].as_str().unwrap_or_default().to_lowercase();
+    source.contains("estimat") || source.contains("infer")
+}
+
+/// Record a successful pipeline run and stamp the ETFs it decomposed.
+pub fn record_pipeline_success(data_dir: &Path) -> Result<(), String> {
+    let path = freshness_path(data_dir);
+    let mut freshness: FreshnessStore = store::read_json(&path)?.unwrap_or_default();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    if let Ok(report) = pipeline_report::load(data_dir) {
+        for entry in pipeline_report::per_etf(&report) {
+            if let (true, Some(isin)) = (is_decomposed(entry), entry["isin"].as_str()) {
+                freshness.funds.insert(isin.to_string(), now.clone());
+            }
+        }
+    }
+
+    freshness.last_pipeline_success = Some(now);
+    store::write_json(&path, &freshness)
+}
+
+/// Assemble the data quality block from the latest report and freshness store.
+pub fn summarize(data_dir: &Path) -> Result<DataQuality, String> {
+    let freshness: FreshnessStore = store::read_json(&freshness_path(data_dir))?.unwrap_or_default();
+    let report = pipeline_report::load(data_dir).ok();
+    let entries = report.as_ref().map(pipeline_report::per_etf).unwrap_or(&[]);
+
+    let decomposed = entries.iter().filter(|entry| is_decomposed(entry)).count();
+    let coverage_percent = if entries.is_empty() {
+        100.0
+    } else {
+        decomposed as f64 / entries.len() as f64 * 100.0
+    };
+
+    let estimated_constituents = entries
+        .iter()
+        .filter(|entry| is_estimated(entry))
+        .map(|entry| entry["holdings_count"].as_u64().unwrap_or(0) as u32)
+        .sum();
+
+    let now = chrono::Utc::now();
+    let stalest = entries
+        .iter()
+        .filter_map(|entry| entry["isin"].as_str())
+        .filter_map(|isin| {
+            let stamped = freshness.funds.get(isin)?;
+            let stamped = chrono::DateTime::parse_from_rfc3339(stamped).ok()?;
+            Some((isin, now.signed_duration_since(stamped).num_minutes() as f64 / 60.0))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+    Ok(DataQuality {
+        coverage_percent,
+        stalest_fund_age_hours: stalest.map(|(_, hours)| hours),
+        stalest_fund_isin: stalest.map(|(isin, _)| isin.to_string()),
+        estimated_constituents,
+        last_pipeline_success: freshness.last_pipeline_success,
+    })
+}
+
+// =============================================================================
+// Look-through Confidence Intervals
+// =============================================================================
+
+/// Range an allocation bucket could take once unknown constituents are placed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bound {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Min/max ranges for look-through allocations (percent, 0-100)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationBounds {
+    /// Share of portfolio value whose constituents are unknown
+    pub unknown_percent: f64,
+    pub sector: HashMap<String, Bound>,
+    pub region: HashMap<String, Bound>,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Percent of each ETF (0-100) whose constituents are unknown.
+///
+/// Failed decompositions count as fully unknown; successful ones contribute
+/// whatever their constituent weights fail to add up to.
+pub fn missing_constituent_percent(data_dir: &Path) -> HashMap<String, f64> {
+    let Ok(report) = pipeline_report::load(data_dir) else {
+        return HashMap::new();
+    };
+
+    pipeline_report::per_etf(&report)
+        .iter()
+        .filter_map(|entry| {
+            let isin = entry["isin"].as_str()?;
+            let missing = if !is_decomposed(entry) {
+                100.0
+            } else {
+                match entry["weight_sum"].as_f64() {
+                    // Some adapters report fractions, most report percentages
+                    Some(sum) if sum <= 1.5 => (100.0 - sum * 100.0).clamp(0.0, 100.0),
+                    Some(sum) => (100.0 - sum).clamp(0.0, 100.0),
+                    None => 0.0,
+                }
+            };
+            Some((isin.to_string(), missing))
+        })
+        .collect()
+}
+
+/// Widen look-through allocations by the unknown share of the portfolio.
+///
+/// `etf_weights` maps ETF ISINs to portfolio weight as a fraction (0-1).
+/// Allocations are percentages over the known portion, so a bucket at `p`
+/// can fall to `p * (1 - u)` if none of the unknown is in it, or rise by `u`
+/// if all of it is.
+pub fn allocation_bounds(
+    data_dir: &Path,
+    etf_weights: &HashMap<String, f64>,
+    sector: &HashMap<String, f64>,
+    region: &HashMap<String, f64>,
+) -> AllocationBounds {
+    let unknown_percent = missing_constituent_percent(data_dir)
+        .iter()
+        .map(|(isin, missing)| etf_weights.get(isin).copied().unwrap_or(0.0) * missing)
+        .sum::<f64>()
+        .clamp(0.0, 100.0);
+
+    let widen = |allocations: &HashMap<String, f64>| {
+        allocations
+            .iter()
+            .map(|(bucket, percent)| {
+                let min = percent * (1.0 - unknown_percent / 100.0);
+                let max = (min + unknown_percent).min(100.0);
+                (
+                    bucket.clone(),
+                    Bound {
+                        min: round2(min),
+                        max: round2(max),
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>()
+    };
+
+    AllocationBounds {
+        unknown_percent: round2(unknown_percent),
+        sector: widen(sector),
+        region: widen(region),
+    }
+}
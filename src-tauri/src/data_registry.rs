@@ -0,0 +1,221 @@
+//! Data Registry
+//!
+//! Every entry the shell keeps in the app data dir and every keychain secret
+//! the app writes, in one place. `app_reset` builds its scopes and the GDPR
+//! erasure from these lists, so a store or secret missing here survives a
+//! reset. A new store joins `SHELL_STORE_FILES` or `SHELL_STORE_ENTRIES`
+//! (or `CACHE_ENTRIES` when it is rebuilt on demand), a new secret joins
+//! `KEYCHAIN_SECRETS`; the tests below fail for a store constant that did not.
+
+use crate::app_lock;
+use crate::app_settings;
+use crate::app_update;
+use crate::benchmarks;
+use crate::closed_positions;
+use crate::crash_reports;
+use crate::data_quality;
+use crate::downloads;
+use crate::email;
+use crate::error_reports;
+use crate::event_alerts;
+use crate::feature_flags;
+use crate::hive_cache;
+use crate::hive_guard;
+use crate::instrument_lifecycle;
+use crate::local_api;
+use crate::maintenance;
+use crate::pipeline_config;
+use crate::pipeline_history;
+use crate::pipeline_snapshots;
+use crate::price_alerts;
+use crate::proxy;
+use crate::sandbox;
+use crate::scheduler;
+use crate::session_vault;
+use crate::telemetry;
+
+/// Regenerable data, not backed up by a reset
+pub const CACHE_ENTRIES: &[&str] = &[
+    hive_cache::CACHE_DIR,
+    // FX rates, community distributions and self-test probes
+    "cache",
+    "dashboard",
+    "datasets",
+    benchmarks::SERIES_DIR,
+    "traces",
+    "outputs",
+];
+
+/// The engine database with its WAL/SHM files
+pub const ENGINE_DB_ENTRIES: &[&str] = &["prism.db", "prism.db-wal", "prism.db-shm"];
+
+/// JSON documents of the shell stores; their `.bak` copies go with them
+pub const SHELL_STORE_FILES: &[&str] = &[
+    app_settings::SETTINGS_FILE,
+    app_update::STATE_FILE,
+    benchmarks::BENCHMARKS_FILE,
+    closed_positions::STORE_FILE,
+    data_quality::FRESHNESS_FILE,
+    downloads::SETTINGS_FILE,
+    email::SETTINGS_FILE,
+    error_reports::RESOLVED_FILE,
+    error_reports::SUBMITTED_FILE,
+    event_alerts::ALERTS_FILE,
+    feature_flags::FLAGS_FILE,
+    hive_guard::CONFIG_FILE,
+    instrument_lifecycle::STORE_FILE,
+    maintenance::MAINTENANCE_FILE,
+    price_alerts::ALERTS_FILE,
+    sandbox::STORE_FILE,
+    scheduler::SCHEDULES_FILE,
+];
+
+/// Append-only logs that `maintenance` rotates to a `.1` file
+pub const ROTATED_LOGS: &[&str] = &[hive_guard::AUDIT_FILE, email::DELIVERY_LOG_FILE];
+
+/// Directories and logs of the shell stores; rotated logs are added with
+/// their `.1` files by `shell_store_entries`
+pub const SHELL_STORE_ENTRIES: &[&str] = &[
+    crash_reports::REPORT_DIR,
+    "insights",
+    pipeline_config::CONFIG_DIR,
+    pipeline_history::HISTORY_FILE,
+    "positions",
+    "recovery_log.ndjson",
+    pipeline_snapshots::SNAPSHOT_DIR,
+    telemetry::QUEUE_FILE,
+];
+
+/// Keychain entries written by the shell under its own service
+pub const KEYCHAIN_SECRETS: &[&str] = &[
+    app_lock::PIN_FAILURES_KEY,
+    app_lock::PIN_KEY,
+    email::PASSWORD_KEY,
+    local_api::TOKEN_KEY,
+    proxy::PASSWORD_KEY,
+    session_vault::KEY_NAME,
+];
+
+/// Keychain entries the engine writes under `keychain::ENGINE_SERVICE`
+pub const ENGINE_KEYCHAIN_SECRETS: &[&str] = &["tr_phone", "tr_pin"];
+
+/// `SHELL_STORE_ENTRIES` plus the rotated logs and their `.1` files
+pub fn shell_store_entries() -> Vec<String> {
+    let mut entries: Vec<String> = SHELL_STORE_ENTRIES.iter().map(|e| e.to_string()).collect();
+    for log in ROTATED_LOGS {
+        entries.push(log.to_string());
+        entries.push(format!("{}.1", log));
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::path::Path;
+
+    /// Store constants that deliberately stay out of the reset scopes
+    const UNREGISTERED: &[&str] = &[
+        // Kept by every reset: the reset backups and the running instance's lock
+        "reset_backups",
+        ".instance.lock",
+        // Lives in the default dir, not the data dir; `delete_all` removes it
+        "data_location.json",
+        // Only ever written inside a backup archive
+        "backup_manifest.json",
+        // Transient restore dir, removed by `data_backup` itself
+        ".restore-staging",
+        // Log dir and file: `everything` and `delete_all` remove them, the
+        // scoped resets keep the log of the reset itself
+        "logs",
+        "prism.log",
+        // Written inside registered dirs
+        "fx_rates.json",
+        "community_distributions.json",
+        "last_dashboard.json",
+    ];
+
+    fn registered() -> Vec<String> {
+        let mut names: Vec<String> = CACHE_ENTRIES
+            .iter()
+            .chain(ENGINE_DB_ENTRIES)
+            .chain(SHELL_STORE_FILES)
+            .chain(UNREGISTERED)
+            .map(|name| name.to_string())
+            .collect();
+        names.extend(shell_store_entries());
+        names
+    }
+
+    /// `(const name, value)` of every `&str` constant in the crate sources
+    fn str_constants(dir: &Path, found: &mut Vec<(String, String)>) {
+        for entry in std::fs::read_dir(dir).expect("read src dir").flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                str_constants(&path, found);
+                continue;
+            }
+            if path.extension() != Some(OsStr::new("rs")) {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).expect("read source");
+            for line in source.lines() {
+                let line = line
+                    .trim_start_matches("pub(crate) ")
+                    .trim_start_matches("pub ");
+                let Some(rest) = line.strip_prefix("const ") else {
+                    continue;
+                };
+                let Some((name, value)) = rest.split_once(": &str = \"") else {
+                    continue;
+                };
+                if let Some(value) = value.strip_suffix("\";") {
+                    found.push((name.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+
+    fn constants() -> Vec<(String, String)> {
+        let mut found = vec![];
+        str_constants(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut found,
+        );
+        found
+    }
+
+    #[test]
+    fn every_store_constant_is_registered() {
+        let registered = registered();
+        let missing: Vec<_> = constants()
+            .into_iter()
+            .filter(|(name, _)| name.ends_with("_FILE") || name.ends_with("_DIR"))
+            .filter(|(_, value)| !value.contains('/') && !value.starts_with("http"))
+            .filter(|(_, value)| !registered.contains(value))
+            .collect();
+        assert!(missing.is_empty(), "unregistered stores: {:?}", missing);
+    }
+
+    #[test]
+    fn every_keychain_key_is_registered() {
+        let missing: Vec<_> = constants()
+            .into_iter()
+            .filter(|(name, _)| name.ends_with("_KEY") || name == "KEY_NAME")
+            // Written and deleted within a single self-test
+            .filter(|(_, value)| value != "self_test_probe")
+            .filter(|(_, value)| !KEYCHAIN_SECRETS.contains(&value.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "unregistered secrets: {:?}", missing);
+    }
+
+    #[test]
+    fn rotated_logs_include_their_rotations() {
+        let entries = shell_store_entries();
+        for log in ROTATED_LOGS {
+            assert!(entries.contains(&log.to_string()));
+            assert!(entries.contains(&format!("{}.1", log)));
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Offline ETF Decomposition Dataset
+//!
+//! A versioned starter dataset of constituent data for popular European UCITS
+//! ETFs, so look-through works on first run before any manual upload. The
+//! dataset lives in `datasets/etf/` in the app data dir:
+//!
+//! - `manifest.json`: version, publish date and one entry per fund with the
+//!   SHA-256 of its file
+//! - `funds/<ISIN>.json`: the fund's constituents as `{etfIsin, name,
+//!   holdings: [{isin, name, weight}]}`, weights in percent
+//!
+//! The engine falls back to it through `hive_decomposition_request` when no
+//! adapter and no Hive entry can decompose a fund.
+//!
+//! Updates download the remote manifest, stage every fund (fetching only the
+//! changed ones through the download manager), verify each checksum and only
+//! then swap the staging directory in, so a failed or interrupted update never
+//! leaves a half-written dataset. The source is `PRISM_DATASET_URL`,
+//! defaulting to `/dataset` on the proxy, which serves releases built by
+//! `scripts/publish_dataset.py`.
+
+use crate::downloads::{self, Downloader};
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Proxy used when neither `PRISM_DATASET_URL` nor `PROXY_URL` is set
+pub const DEFAULT_PROXY_URL: &str = "https://portfolio-prism-proxy.bold-unit-582c.workers.dev";
+
+/// Marker in the staging dir naming the version being staged
+const STAGING_MARKER: &str = ".staging-version.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundEntry {
+    pub isin: String,
+    pub name: String,
+    pub sha256: String,
+    pub holdings_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetManifest {
+    pub version: String,
+    pub published_at: String,
+    pub funds: Vec<FundEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetStatus {
+    pub installed: bool,
+    pub version: Option<String>,
+    pub published_at: Option<String>,
+    pub fund_count: usize,
+    pub verified_funds: usize,
+    /// Funds whose file is missing or fails its checksum
+    pub corrupt_funds: Vec<String>,
+    pub source_url: String,
+}
+
+fn dataset_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("datasets").join("etf")
+}
+
+fn fund_path(dir: &Path, isin: &str) -> PathBuf {
+    dir.join("funds").join(format!("{}.json", isin))
+}
+
+fn valid_isin(isin: &str) -> bool {
+    isin.len() == 12 && isin.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+pub fn source_url() -> String {
+    std::env::var("PRISM_DATASET_URL").unwrap_or_else(|_| {
+        let proxy = std::env::var("PROXY_URL").unwrap_or_else(|_| DEFAULT_PROXY_URL.to_string());
+        format!("{}/dataset", proxy.trim_end_matches('/'))
+    })
+}
+
+pub fn load_manifest(data_dir: &Path) -> Result<Option<DatasetManifest>, String> {
+    store::read_json(&dataset_dir(data_dir).join("manifest.json"))
+}
+
+/// Installed version plus an integrity check of every fund file.
+pub fn status(data_dir: &Path) -> Result<DatasetStatus, String> {
+    let dir = dataset_dir(data_dir);
+    let Some(manifest) = load_manifest(data_dir)? else {
+        return Ok(DatasetStatus {
+            installed: false,
+            version: None,
+            published_at: None,
+            fund_count: 0,
+            verified_funds: 0,
+            corrupt_funds: vec![],
+            source_url: source_url(),
+        });
+    };
+
+    let corrupt_funds: Vec<String> = manifest
+        .funds
+        .iter()
+        .filter(|fund| !downloads::verify_file(&fund_path(&dir, &fund.isin), &fund.sha256))
+        .map(|fund| fund.isin.clone())
+        .collect();
+
+    Ok(DatasetStatus {
+        installed: true,
+        version: Some(manifest.version),
+        published_at: Some(manifest.published_at),
+        fund_count: manifest.funds.len(),
+        verified_funds: manifest.funds.len() - corrupt_funds.len(),
+        corrupt_funds,
+        source_url: source_url(),
+    })
+}
+
+/// Constituents of `isin` from the installed dataset, if it covers the fund
+/// and the file passes its checksum.
+pub fn lookup(data_dir: &Path, isin: &str) -> Option<Value> {
+    if !valid_isin(isin) {
+        return None;
+    }
+    let manifest = load_manifest(data_dir).ok().flatten()?;
+    let fund = manifest.funds.iter().find(|fund| fund.isin == isin)?;
+
+    let bytes = std::fs::read(fund_path(&dataset_dir(data_dir), isin)).ok()?;
+    if downloads::sha256_hex(&bytes) != fund.sha256 {
+        tracing::warn!("Offline dataset entry for {} failed its checksum", isin);
+        return None;
+    }
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Download the remote dataset when it is newer than the installed one (or
+/// always, with `force`) and swap it in once every fund is verified.
+///
+/// Only changed funds are downloaded: entries whose checksum matches the
+/// installed copy are copied over. The staging directory survives an
+/// interrupted update of the same version, so the next attempt resumes.
+pub async fn update(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    force: bool,
+) -> Result<DatasetStatus, String> {
+    let base = source_url();
+    let settings = downloads::load_settings(data_dir)?;
+    let downloader = Downloader::new(app_handle, "dataset", &settings);
+
+    let manifest_bytes = downloader
+        .fetch_bytes(&format!("{}/manifest.json", base))
+        .await?;
+    let manifest: DatasetManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("Invalid dataset manifest: {}", e))?;
+
+    let installed = load_manifest(data_dir)?;
+    if !force
+        && installed
+            .as_ref()
+            .is_some_and(|current| current.version == manifest.version)
+    {
+        return status(data_dir);
+    }
+
+    let dir = dataset_dir(data_dir);
+    let staging = dir.with_extension("staging");
+    let staged_version: Option<String> = store::read_json(&staging.join(STAGING_MARKER))?;
+    if staged_version.as_deref() != Some(manifest.version.as_str()) {
+        let _ = std::fs::remove_dir_all(&staging);
+    }
+    std::fs::create_dir_all(staging.join("funds"))
+        .map_err(|e| format!("Failed to create dataset staging dir: {}", e))?;
+    store::write_json(&staging.join(STAGING_MARKER), &manifest.version)?;
+
+    let files_total = manifest.funds.len();
+    for (files_done, fund) in manifest.funds.iter().enumerate() {
+        if !valid_isin(&fund.isin) {
+            return Err(format!("Dataset manifest has invalid ISIN: {}", fund.isin));
+        }
+        let target = fund_path(&staging, &fund.isin);
+        if downloads::verify_file(&target, &fund.sha256) {
+            // Already staged by an interrupted run
+            continue;
+        }
+
+        let current = fund_path(&dir, &fund.isin);
+        let unchanged = installed.as_ref().is_some_and(|installed| {
+            installed
+                .funds
+                .iter()
+                .any(|old| old.isin == fund.isin && old.sha256 == fund.sha256)
+        });
+        if unchanged && downloads::verify_file(&current, &fund.sha256) {
+            std::fs::copy(&current, &target)
+                .map_err(|e| format!("Failed to stage dataset entry {}: {}", fund.isin, e))?;
+            continue;
+        }
+
+        downloader
+            .fetch_to_file(
+                &format!("{}/funds/{}.json", base, fund.isin),
+                &target,
+                &fund.sha256,
+                files_done,
+                files_total,
+            )
+            .await?;
+    }
+    std::fs::write(staging.join("manifest.json"), &manifest_bytes)
+        .map_err(|e| format!("Failed to write dataset manifest: {}", e))?;
+    let _ = std::fs::remove_file(staging.join(STAGING_MARKER));
+
+    // Swap: current -> .old, staging -> current, then drop the old copy
+    let previous = dir.with_extension("old");
+    let _ = std::fs::remove_dir_all(&previous);
+    if dir.exists() {
+        std::fs::rename(&dir, &previous)
+            .map_err(|e| format!("Failed to replace dataset: {}", e))?;
+    }
+    if let Err(e) = std::fs::rename(&staging, &dir) {
+        let _ = std::fs::rename(&previous, &dir);
+        return Err(format!("Failed to install dataset: {}", e));
+    }
+    let _ = std::fs::remove_dir_all(&previous);
+
+    downloader.progress("manifest.json", 0, None, files_total, files_total);
+    status(data_dir)
+}
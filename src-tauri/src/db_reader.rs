@@ -0,0 +1,280 @@
+//! Read-only Database Fallback
+//!
+//! While the engine is crashed, restarting or not started yet,
+//! `get_dashboard_data` and `get_positions` read the last persisted positions
+//! straight from `prism.db` and flag the response `stale`. The database is
+//! opened read-only, so a sidecar coming back up is never blocked by it.
+//!
+//! Values use the prices stored by the last sync and mirror the engine's
+//! `DashboardService` calculations. Sector and region allocations, the value
+//! history and the day change need the engine's analytics; they are taken
+//! from the last dashboard the engine produced, when there is one.
+
+use crate::commands::portfolio::{
+    Allocations, DashboardData, Holding, Position, PositionsResponse,
+};
+use crate::dashboard_assembly;
+use crate::db_recovery;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a read waits while the engine holds a write lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Top holdings shown on the dashboard, as in the engine
+const TOP_HOLDINGS: usize = 10;
+
+/// Sync source whose last run dates the data
+const SYNC_SOURCE: &str = "trade_republic";
+
+/// A `positions` row joined with its asset
+struct PositionRow {
+    isin: String,
+    quantity: f64,
+    cost_basis: Option<f64>,
+    current_price: Option<f64>,
+    updated_at: Option<String>,
+    name: Option<String>,
+    symbol: Option<String>,
+    asset_class: Option<String>,
+}
+
+impl PositionRow {
+    fn price(&self) -> f64 {
+        self.current_price.or(self.cost_basis).unwrap_or(0.0)
+    }
+
+    fn avg_buy_price(&self) -> f64 {
+        self.cost_basis.unwrap_or_else(|| self.price())
+    }
+
+    fn value(&self) -> f64 {
+        self.quantity * self.price()
+    }
+
+    fn cost(&self) -> f64 {
+        self.quantity * self.avg_buy_price()
+    }
+
+    fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.isin.clone())
+    }
+}
+
+fn round_to(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
+}
+
+fn percent(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        part / whole * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn open(data_dir: &Path) -> Result<Connection, String> {
+    let path = db_recovery::db_path(data_dir);
+    if !path.exists() {
+        return Err("No portfolio data has been saved yet".to_string());
+    }
+    let connection = Connection::open_with_flags(
+        &path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("Cannot open database: {}", e))?;
+    connection
+        .busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Cannot open database: {}", e))?;
+    Ok(connection)
+}
+
+fn position_rows(connection: &Connection, portfolio_id: u32) -> Result<Vec<PositionRow>, String> {
+    let mut statement = connection
+        .prepare(
+            "SELECT p.isin, p.quantity, p.cost_basis, p.current_price, p.updated_at,
+                    a.name, a.symbol, a.asset_class
+             FROM positions p
+             LEFT JOIN assets a ON p.isin = a.isin
+             WHERE p.portfolio_id = ?1
+             ORDER BY (p.quantity * COALESCE(p.current_price, p.cost_basis, 0)) DESC",
+        )
+        .map_err(|e| format!("Failed to read positions: {}", e))?;
+    let rows = statement
+        .query_map([portfolio_id], |row| {
+            Ok(PositionRow {
+                isin: row.get(0)?,
+                quantity: row.get(1)?,
+                cost_basis: row.get(2)?,
+                current_price: row.get(3)?,
+                updated_at: row.get(4)?,
+                name: row.get(5)?,
+                symbol: row.get(6)?,
+                asset_class: row.get(7)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read positions: {}", e))?;
+    Ok(rows)
+}
+
+fn last_sync(connection: &Connection) -> Option<String> {
+    connection
+        .query_row(
+            "SELECT last_sync FROM sync_state WHERE source = ?1",
+            [SYNC_SOURCE],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()
+}
+
+/// When the last sync finished, as stored by the engine
+pub fn last_sync_time(data_dir: &Path) -> Option<String> {
+    last_sync(&open(data_dir).ok()?)
+}
+
+/// Instrument type as the engine derives it from the asset class
+fn instrument_type(asset_class: Option<&str>) -> &'static str {
+    let Some(asset_class) = asset_class.map(str::to_lowercase) else {
+        return "stock";
+    };
+    if asset_class.contains("etf") {
+        "etf"
+    } else if asset_class.contains("crypto") {
+        "crypto"
+    } else if asset_class.contains("bond") {
+        "bond"
+    } else if ["derivative", "option", "warrant"]
+        .iter()
+        .any(|kind| asset_class.contains(kind))
+    {
+        "derivative"
+    } else {
+        "stock"
+    }
+}
+
+/// Positions of `portfolio_id` as last persisted, flagged `stale`
+pub fn positions(data_dir: &Path, portfolio_id: u32) -> Result<PositionsResponse, String> {
+    let connection = open(data_dir)?;
+    let rows = position_rows(&connection, portfolio_id)?;
+
+    let total_value: f64 = rows.iter().map(PositionRow::value).sum();
+    let total_cost: f64 = rows.iter().map(PositionRow::cost).sum();
+    let mut positions: Vec<Position> = rows
+        .iter()
+        .map(|row| {
+            let (value, cost) = (row.value(), row.cost());
+            Position {
+                isin: row.isin.clone(),
+                name: row.display_name(),
+                ticker: row.symbol.clone().unwrap_or_default(),
+                instrument_type: instrument_type(row.asset_class.as_deref()).to_string(),
+                quantity: row.quantity,
+                avg_buy_price: round_to(row.avg_buy_price(), 2),
+                current_price: round_to(row.price(), 2),
+                current_value: round_to(value, 2),
+                total_cost: round_to(cost, 2),
+                pnl_eur: round_to(value - cost, 2),
+                pnl_percent: round_to(percent(value - cost, cost), 2),
+                weight: round_to(percent(value, total_value), 2),
+                currency: "EUR".to_string(),
+                notes: String::new(),
+                last_updated: row.updated_at.clone().unwrap_or_default(),
+            }
+        })
+        .collect();
+    positions.sort_by(|a, b| b.current_value.total_cmp(&a.current_value));
+
+    let total_pnl = total_value - total_cost;
+    Ok(PositionsResponse {
+        positions,
+        total_value: round_to(total_value, 2),
+        total_cost: round_to(total_cost, 2),
+        total_pnl: round_to(total_pnl, 2),
+        total_pnl_percent: round_to(percent(total_pnl, total_cost), 2),
+        last_sync_time: last_sync(&connection),
+        stale: true,
+    })
+}
+
+/// Dashboard of `portfolio_id` from the persisted positions, flagged `stale`
+pub fn dashboard(data_dir: &Path, portfolio_id: u32) -> Result<DashboardData, String> {
+    let connection = open(data_dir)?;
+    let rows = position_rows(&connection, portfolio_id)?;
+    let last = dashboard_assembly::recall::<DashboardData>(data_dir, portfolio_id);
+
+    let total_value: f64 = rows.iter().map(PositionRow::value).sum();
+    let total_cost: f64 = rows.iter().map(PositionRow::cost).sum();
+    let mut holdings: Vec<Holding> = rows
+        .iter()
+        .map(|row| {
+            let (value, cost) = (row.value(), row.cost());
+            Holding {
+                isin: row.isin.clone(),
+                name: row.display_name(),
+                ticker: row.symbol.clone(),
+                value: round_to(value, 2),
+                weight: if total_value > 0.0 {
+                    round_to(value / total_value, 4)
+                } else {
+                    0.0
+                },
+                pnl: round_to(value - cost, 2),
+                pnl_percentage: round_to(percent(value - cost, cost), 1),
+                quantity: Some(row.quantity),
+                asset_class: row.asset_class.clone(),
+            }
+        })
+        .collect();
+    holdings.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+    let mut asset_class: HashMap<String, f64> = HashMap::new();
+    for holding in &holdings {
+        let class = holding
+            .asset_class
+            .clone()
+            .unwrap_or_else(|| "Unknown".to_string());
+        *asset_class.entry(class).or_default() += holding.weight;
+    }
+    holdings.truncate(TOP_HOLDINGS);
+
+    let total_gain = total_value - total_cost;
+    let position_count = rows.len() as u32;
+    let (day_change, day_change_percent, history, sector, region) = match last {
+        Some(last) => (
+            last.day_change,
+            last.day_change_percent,
+            last.history,
+            last.allocations.sector,
+            last.allocations.region,
+        ),
+        None => (0.0, 0.0, vec![], HashMap::new(), HashMap::new()),
+    };
+
+    Ok(DashboardData {
+        total_value: round_to(total_value, 2),
+        total_gain: round_to(total_gain, 2),
+        gain_percentage: round_to(percent(total_gain, total_cost), 1),
+        day_change,
+        day_change_percent,
+        history,
+        allocations: Allocations {
+            sector,
+            region,
+            asset_class,
+            bounds: None,
+        },
+        top_holdings: holdings,
+        last_updated: last_sync(&connection),
+        is_empty: rows.is_empty(),
+        position_count,
+        data_quality: None,
+        stale: true,
+    })
+}
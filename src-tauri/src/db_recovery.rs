@@ -0,0 +1,157 @@
+//! Engine Database Recovery
+//!
+//! Runs at startup, before the sidecar opens `prism.db`:
+//!
+//! 1. `PRAGMA quick_check` on the database
+//! 2. healthy: switch to WAL journaling (survives power loss far better than
+//!    the rollback journal) and refresh `backups/prism.db.last-good` when it
+//!    is older than a day, using SQLite's online backup API
+//! 3. corrupt: move the database (and its WAL/SHM files) aside as
+//!    `prism.db.corrupt-<timestamp>` and restore the last good backup
+//!
+//! Every recovery is appended to `recovery_log.ndjson`.
+
+use crate::store;
+use chrono::Utc;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Engine database inside the app data dir
+pub const DB_FILE: &str = "prism.db";
+
+/// Minimum age of the last-good backup before it is refreshed
+const BACKUP_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryRecord {
+    pub timestamp: String,
+    pub problem: String,
+    pub corrupt_copy: String,
+    pub restored_from: Option<String>,
+}
+
+/// Startup check outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbCheck {
+    /// No database yet (first launch)
+    Missing,
+    Healthy,
+    /// Corrupt database replaced by the last good backup
+    Restored,
+    /// Corrupt database moved aside without a backup; the engine starts fresh
+    Reset,
+}
+
+pub fn db_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DB_FILE)
+}
+
+fn backup_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("backups").join(format!("{}.last-good", DB_FILE))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `Ok(())` when SQLite reports no corruption.
+pub(crate) fn quick_check(path: &Path) -> Result<(), String> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .map_err(|e| format!("Cannot open database: {}", e))?;
+    let result: String = connection
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+    if result != "ok" {
+        return Err(format!("Integrity check reported: {}", result));
+    }
+    Ok(())
+}
+
+fn enable_wal(path: &Path) -> Result<(), String> {
+    let connection = Connection::open(path).map_err(|e| format!("Cannot open database: {}", e))?;
+    let mode: String = connection
+        .query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to enable WAL: {}", e))?;
+    if !mode.eq_ignore_ascii_case("wal") {
+        return Err(format!("Database stayed in {} journal mode", mode));
+    }
+    Ok(())
+}
+
+/// Online backup into a temp file, renamed into place once complete.
+fn refresh_backup(path: &Path, backup: &Path) -> Result<(), String> {
+    let fresh = std::fs::metadata(backup)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < BACKUP_INTERVAL);
+    if fresh {
+        return Ok(());
+    }
+
+    if let Some(parent) = backup.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let temp = with_suffix(backup, ".tmp");
+    let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open database: {}", e))?;
+    source
+        .backup(rusqlite::DatabaseName::Main, &temp, None)
+        .map_err(|e| format!("Database backup failed: {}", e))?;
+    std::fs::rename(&temp, backup).map_err(|e| format!("Failed to store backup: {}", e))
+}
+
+/// Check the engine database and repair it from the last good backup.
+pub fn check_and_recover(data_dir: &Path) -> Result<DbCheck, String> {
+    let path = db_path(data_dir);
+    if !path.exists() {
+        return Ok(DbCheck::Missing);
+    }
+    let backup = backup_path(data_dir);
+
+    let problem = match quick_check(&path) {
+        Ok(()) => {
+            enable_wal(&path)?;
+            if let Err(e) = refresh_backup(&path, &backup) {
+                tracing::warn!("Skipping database backup: {}", e);
+            }
+            return Ok(DbCheck::Healthy);
+        }
+        Err(problem) => problem,
+    };
+    tracing::error!("Engine database is corrupt: {}", problem);
+
+    let corrupt = with_suffix(&path, &format!(".corrupt-{}", Utc::now().timestamp()));
+    std::fs::rename(&path, &corrupt)
+        .map_err(|e| format!("Failed to move corrupt database aside: {}", e))?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::rename(with_suffix(&path, suffix), with_suffix(&corrupt, suffix));
+    }
+
+    let restored = backup.exists() && quick_check(&backup).is_ok();
+    if restored {
+        std::fs::copy(&backup, &path).map_err(|e| format!("Failed to restore backup: {}", e))?;
+    }
+
+    let record = RecoveryRecord {
+        timestamp: Utc::now().to_rfc3339(),
+        problem,
+        corrupt_copy: corrupt.display().to_string(),
+        restored_from: restored.then(|| backup.display().to_string()),
+    };
+    if let Err(e) = store::append_ndjson(&data_dir.join("recovery_log.ndjson"), &record) {
+        tracing::warn!("Failed to record database recovery: {}", e);
+    }
+
+    Ok(if restored {
+        DbCheck::Restored
+    } else {
+        DbCheck::Reset
+    })
+}
@@ -0,0 +1,140 @@
+//! Deep Links
+//!
+//! `prism://` URLs let documentation and notifications link straight into
+//! app actions:
+//! - `prism://sync`: sync the scheduled portfolio now, as from the tray
+//! - `prism://report/latest` or `prism://report/<id>`: open a pipeline report
+//! - `prism://upload?isin=LU...`: open the holdings upload, optionally for one
+//!   ETF
+//!
+//! The installer registers the scheme; on Linux and Windows it is also
+//! registered at startup so unbundled builds receive links. A link opened
+//! while the app runs goes to the running instance: macOS delivers it there
+//! directly, elsewhere the second launch forwards it through
+//! `tauri-plugin-single-instance` and exits.
+//!
+//! Every link brings the window to the front and is emitted as `deep-link`
+//! for the frontend to navigate; `sync` is started by the shell itself. A
+//! link that launched the app arrives before the webview listens, so the
+//! last link is also kept until the frontend takes it with
+//! `take_pending_deep_link`.
+
+use crate::app_lock;
+use crate::broker_import;
+use crate::scheduler;
+use crate::second_instance;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+pub const SCHEME: &str = "prism";
+
+pub const EVENT: &str = "deep-link";
+
+/// Last link received, until the frontend takes it
+static PENDING: Mutex<Option<DeepLink>> = Mutex::new(None);
+
+/// App action a link asks for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum DeepLink {
+    Sync,
+    /// `id` is a report id or `latest`
+    Report {
+        id: String,
+    },
+    Upload {
+        isin: Option<String>,
+    },
+}
+
+/// Parse a `prism://` URL.
+pub fn parse(url: &Url) -> Result<DeepLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Not a {}:// link: {}", SCHEME, url));
+    }
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    match (url.host_str().unwrap_or_default(), segments.as_slice()) {
+        ("sync", []) => Ok(DeepLink::Sync),
+        ("report", []) => Ok(DeepLink::Report {
+            id: "latest".to_string(),
+        }),
+        ("report", [id]) => Ok(DeepLink::Report { id: id.to_string() }),
+        ("upload", []) => {
+            let isin = query("isin").map(|isin| isin.to_uppercase());
+            if let Some(isin) = isin.as_deref().filter(|isin| !broker_import::is_isin(isin)) {
+                return Err(format!("Invalid ISIN in link: {}", isin));
+            }
+            Ok(DeepLink::Upload { isin })
+        }
+        _ => Err(format!("Unknown link: {}", url)),
+    }
+}
+
+/// Act on links opened from outside the app.
+fn open(app_handle: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        let link = match parse(&url) {
+            Ok(link) => link,
+            Err(e) => {
+                tracing::warn!("Ignoring deep link: {}", e);
+                continue;
+            }
+        };
+        tracing::info!("Opening deep link {:?}", link);
+        second_instance::focus_main_window(app_handle);
+
+        if link == DeepLink::Sync {
+            // Same gate as the sync commands; the frontend shows the lock screen
+            match app_lock::check("sync_portfolio") {
+                Ok(()) => {
+                    let app_handle = app_handle.clone();
+                    tauri::async_runtime::spawn(
+                        async move { scheduler::sync_now(&app_handle).await },
+                    );
+                }
+                Err(e) => tracing::info!("Not syncing from a link: {}", e),
+            }
+        }
+
+        if let Ok(mut pending) = PENDING.lock() {
+            *pending = Some(link.clone());
+        }
+        let _ = app_handle.emit(EVENT, &link);
+    }
+}
+
+/// The last link received, if the frontend has not taken it yet
+pub fn take_pending() -> Option<DeepLink> {
+    PENDING.lock().ok()?.take()
+}
+
+/// Register the scheme where needed and handle links, including the one the
+/// app was launched with.
+pub fn start(app_handle: &AppHandle) {
+    let deep_link = app_handle.deep_link();
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    if let Err(e) = deep_link.register_all() {
+        tracing::warn!("Failed to register the {}:// scheme: {}", SCHEME, e);
+    }
+
+    let handle = app_handle.clone();
+    deep_link.on_open_url(move |event| open(&handle, event.urls()));
+
+    match deep_link.get_current() {
+        Ok(Some(urls)) => open(app_handle, urls),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to read the launch link: {}", e),
+    }
+}
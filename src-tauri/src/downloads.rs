@@ -0,0 +1,295 @@
+//! Download Manager
+//!
+//! Shared machinery for large downloads (the offline dataset, history
+//! backfills):
+//!
+//! - resumable: bytes land in `<file>.part` and an interrupted transfer is
+//!   continued with an HTTP `Range` request
+//! - checksummed: a file is only moved into place once its SHA-256 matches
+//! - throttled: an optional bandwidth limit from `download_settings.json`
+//! - observable: `download-progress` events, at most a few per second
+//!
+//! Automatic dataset updates run as a maintenance task, but only inside the
+//! configured idle hours (local time) and at most once a day.
+
+use crate::{dataset, proxy, store};
+use chrono::{DateTime, Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Settings file name inside the app data dir
+pub(crate) const SETTINGS_FILE: &str = "download_settings.json";
+
+/// Minimum interval between progress events for one job
+const PROGRESS_INTERVAL_MS: u128 = 250;
+
+/// Minimum time between automatic dataset checks
+const AUTO_UPDATE_INTERVAL_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadSettings {
+    /// Bandwidth cap in KiB/s; `None` means unlimited
+    #[serde(default)]
+    pub bandwidth_limit_kbps: Option<u64>,
+    /// Update the offline dataset automatically during idle hours
+    #[serde(default = "default_auto_update")]
+    pub auto_update: bool,
+    /// Idle window start, local hour (inclusive)
+    #[serde(default = "default_idle_start")]
+    pub idle_start_hour: u32,
+    /// Idle window end, local hour (exclusive); may wrap past midnight
+    #[serde(default = "default_idle_end")]
+    pub idle_end_hour: u32,
+    /// Last automatic dataset check
+    #[serde(default)]
+    pub last_auto_update: Option<DateTime<Utc>>,
+}
+
+fn default_auto_update() -> bool {
+    true
+}
+
+fn default_idle_start() -> u32 {
+    2
+}
+
+fn default_idle_end() -> u32 {
+    6
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self {
+            bandwidth_limit_kbps: None,
+            auto_update: default_auto_update(),
+            idle_start_hour: default_idle_start(),
+            idle_end_hour: default_idle_end(),
+            last_auto_update: None,
+        }
+    }
+}
+
+impl DownloadSettings {
+    fn in_idle_window(&self, hour: u32) -> bool {
+        if self.idle_start_hour <= self.idle_end_hour {
+            (self.idle_start_hour..self.idle_end_hour).contains(&hour)
+        } else {
+            hour >= self.idle_start_hour || hour < self.idle_end_hour
+        }
+    }
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILE)
+}
+
+pub fn load_settings(data_dir: &Path) -> Result<DownloadSettings, String> {
+    Ok(store::read_json(&settings_path(data_dir))?.unwrap_or_default())
+}
+
+pub fn save_settings(data_dir: &Path, settings: &DownloadSettings) -> Result<(), String> {
+    if settings.idle_start_hour > 23 || settings.idle_end_hour > 23 {
+        return Err("Idle hours must be between 0 and 23".to_string());
+    }
+    store::write_json(&settings_path(data_dir), settings)
+}
+
+/// Payload of the `download-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    pub job: String,
+    pub file: String,
+    pub bytes_done: u64,
+    pub bytes_total: Option<u64>,
+    pub files_done: usize,
+    pub files_total: usize,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Whether `path` exists and hashes to `sha256`
+pub fn verify_file(path: &Path, sha256: &str) -> bool {
+    fs::read(path)
+        .map(|bytes| sha256_hex(&bytes) == sha256)
+        .unwrap_or(false)
+}
+
+/// One download job (e.g. a dataset update) with shared client and limits
+pub struct Downloader {
+    client: reqwest::Client,
+    app_handle: AppHandle,
+    job: String,
+    bytes_per_sec: Option<u64>,
+}
+
+impl Downloader {
+    pub fn new(app_handle: &AppHandle, job: &str, settings: &DownloadSettings) -> Self {
+        Self {
+            client: proxy::client(),
+            app_handle: app_handle.clone(),
+            job: job.to_string(),
+            bytes_per_sec: settings.bandwidth_limit_kbps.map(|kbps| kbps.max(1) * 1024),
+        }
+    }
+
+    async fn send(&self, url: &str, resume_from: u64) -> Result<reqwest::Response, String> {
+        let mut request = self.client.get(url);
+        if resume_from > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+        }
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Download of {} failed: {}", url, e))
+    }
+
+    /// Fetch a small document (e.g. a manifest) into memory.
+    pub async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, String> {
+        let response = self.send(url, 0).await?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Download of {} failed: {}", url, e))?;
+        Ok(bytes.to_vec())
+    }
+
+    /// Download `url` to `dest`, resuming a previous partial transfer, and
+    /// move it into place only if it hashes to `sha256`.
+    pub async fn fetch_to_file(
+        &self,
+        url: &str,
+        dest: &Path,
+        sha256: &str,
+        files_done: usize,
+        files_total: usize,
+    ) -> Result<(), String> {
+        let part = dest.with_extension("part");
+        let resume_from = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+        // A complete .part left behind by an interrupted rename
+        if resume_from > 0 && verify_file(&part, sha256) {
+            return fs::rename(&part, dest).map_err(|e| format!("Failed to move download: {}", e));
+        }
+
+        let mut response = self.send(url, resume_from).await?;
+        // 206 continues the partial file; anything else restarts it
+        let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let mut bytes_done = if resumed { resume_from } else { 0 };
+        let bytes_total = response.content_length().map(|len| len + bytes_done);
+
+        if let Some(parent) = part.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&part)
+            .map_err(|e| format!("Failed to open {}: {}", part.display(), e))?;
+
+        let file_name = dest
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let started = Instant::now();
+        let mut transferred = 0u64;
+        let mut last_event = Instant::now();
+
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Download of {} interrupted: {}", url, e))?
+        {
+            file.write_all(&chunk)
+                .map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+            bytes_done += chunk.len() as u64;
+            transferred += chunk.len() as u64;
+
+            if let Some(limit) = self.bytes_per_sec {
+                let expected = Duration::from_secs_f64(transferred as f64 / limit as f64);
+                if let Some(ahead) = expected.checked_sub(started.elapsed()) {
+                    tokio::time::sleep(ahead).await;
+                }
+            }
+
+            if last_event.elapsed().as_millis() >= PROGRESS_INTERVAL_MS {
+                last_event = Instant::now();
+                self.progress(&file_name, bytes_done, bytes_total, files_done, files_total);
+            }
+        }
+        drop(file);
+
+        if !verify_file(&part, sha256) {
+            let _ = fs::remove_file(&part);
+            return Err(format!("Checksum mismatch for {}", file_name));
+        }
+        fs::rename(&part, dest)
+            .map_err(|e| format!("Failed to move {} into place: {}", file_name, e))?;
+        self.progress(&file_name, bytes_done, bytes_total, files_done + 1, files_total);
+        Ok(())
+    }
+
+    pub fn progress(
+        &self,
+        file: &str,
+        bytes_done: u64,
+        bytes_total: Option<u64>,
+        files_done: usize,
+        files_total: usize,
+    ) {
+        let _ = self.app_handle.emit(
+            "download-progress",
+            DownloadProgress {
+                job: self.job.clone(),
+                file: file.to_string(),
+                bytes_done,
+                bytes_total,
+                files_done,
+                files_total,
+            },
+        );
+    }
+}
+
+/// Run the automatic dataset update if it is enabled and due. Outside the
+/// idle hours it only runs when `force` is set (a manual maintenance run).
+///
+/// Returns whether an update check ran.
+pub async fn auto_update_if_due(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    force: bool,
+) -> Result<bool, String> {
+    let mut settings = load_settings(data_dir)?;
+
+    let due = settings.last_auto_update.is_none_or(|last| {
+        Utc::now() - last >= chrono::Duration::hours(AUTO_UPDATE_INTERVAL_HOURS)
+    });
+    if !settings.auto_update || !due || !(force || settings.in_idle_window(Local::now().hour())) {
+        return Ok(false);
+    }
+
+    let result = dataset::update(app_handle, data_dir, false).await;
+
+    // Record the attempt either way; a failing source retries tomorrow
+    settings.last_auto_update = Some(Utc::now());
+    save_settings(data_dir, &settings)?;
+
+    let status = result?;
+    tracing::info!(
+        "Scheduled dataset check complete (version {})",
+        status.version.unwrap_or_default()
+    );
+    Ok(true)
+}
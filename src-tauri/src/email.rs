@@ -0,0 +1,236 @@
+//! Email Delivery via User SMTP
+//!
+//! Optional, strictly opt-in delivery of generated reports and critical
+//! alerts through the user's own SMTP server. Connection settings live in
+//! `email_settings.json`; the SMTP password is kept in the OS keychain and
+//! never written to disk. Every delivery attempt is appended to
+//! `email_deliveries.ndjson` so users can verify what was sent and when.
+
+use crate::{keychain, store};
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Keychain key for the SMTP password
+pub const PASSWORD_KEY: &str = "smtp_password";
+
+/// Settings file name inside the app data dir
+pub(crate) const SETTINGS_FILE: &str = "email_settings.json";
+
+/// Delivery log file name inside the app data dir
+pub(crate) const DELIVERY_LOG_FILE: &str = "email_deliveries.ndjson";
+
+/// SMTP connection and delivery preferences (no secrets).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpSettings {
+    /// Master switch; nothing is ever sent while this is false.
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub from_address: String,
+    pub to_address: String,
+    /// Use STARTTLS on the submission port instead of implicit TLS.
+    #[serde(default = "default_starttls")]
+    pub starttls: bool,
+    #[serde(default)]
+    pub send_monthly_reports: bool,
+    #[serde(default)]
+    pub send_critical_alerts: bool,
+    #[serde(default)]
+    pub send_daily_digest: bool,
+}
+
+fn default_starttls() -> bool {
+    true
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 587,
+            username: String::new(),
+            from_address: String::new(),
+            to_address: String::new(),
+            starttls: true,
+            send_monthly_reports: false,
+            send_critical_alerts: false,
+            send_daily_digest: false,
+        }
+    }
+}
+
+/// What triggered a delivery
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryKind {
+    Test,
+    MonthlyReport,
+    CriticalAlert,
+    DailyDigest,
+}
+
+/// One entry in the delivery log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryRecord {
+    pub timestamp: String,
+    pub kind: DeliveryKind,
+    pub subject: String,
+    pub recipient: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILE)
+}
+
+fn delivery_log_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DELIVERY_LOG_FILE)
+}
+
+/// Load SMTP settings, falling back to disabled defaults.
+pub fn load_settings(data_dir: &Path) -> Result<SmtpSettings, String> {
+    Ok(store::read_json(&settings_path(data_dir))?.unwrap_or_default())
+}
+
+/// Validate and persist SMTP settings. `password` replaces the stored
+/// keychain entry when provided; an empty password clears it.
+pub fn save_settings(
+    data_dir: &Path,
+    settings: &SmtpSettings,
+    password: Option<&str>,
+) -> Result<(), String> {
+    if settings.enabled {
+        validate_settings(settings)?;
+    }
+
+    match password {
+        Some("") => keychain::delete_secret(PASSWORD_KEY)?,
+        Some(value) => keychain::set_secret(PASSWORD_KEY, value)?,
+        None => {}
+    }
+
+    store::write_json(&settings_path(data_dir), settings)
+}
+
+/// Whether an SMTP password is stored in the keychain.
+pub fn has_password() -> bool {
+    matches!(keychain::get_secret(PASSWORD_KEY), Ok(Some(_)))
+}
+
+fn validate_settings(settings: &SmtpSettings) -> Result<(), String> {
+    if settings.host.trim().is_empty() {
+        return Err("SMTP host is required".to_string());
+    }
+    if settings.port == 0 {
+        return Err("SMTP port must be between 1 and 65535".to_string());
+    }
+    settings
+        .from_address
+        .parse::<Mailbox>()
+        .map_err(|e| format!("Invalid sender address: {}", e))?;
+    settings
+        .to_address
+        .parse::<Mailbox>()
+        .map_err(|e| format!("Invalid recipient address: {}", e))?;
+    Ok(())
+}
+
+/// Send an email using the stored settings and record the attempt.
+///
+/// Blocking: callers on the async runtime should wrap this in
+/// `spawn_blocking`.
+pub fn deliver(
+    data_dir: &Path,
+    kind: DeliveryKind,
+    subject: &str,
+    body: &str,
+) -> Result<(), String> {
+    let settings = load_settings(data_dir)?;
+    if !settings.enabled {
+        return Err("Email delivery is disabled".to_string());
+    }
+    match kind {
+        DeliveryKind::MonthlyReport if !settings.send_monthly_reports => {
+            return Err("Monthly report emails are disabled".to_string());
+        }
+        DeliveryKind::CriticalAlert if !settings.send_critical_alerts => {
+            return Err("Critical alert emails are disabled".to_string());
+        }
+        DeliveryKind::DailyDigest if !settings.send_daily_digest => {
+            return Err("Daily digest emails are disabled".to_string());
+        }
+        _ => {}
+    }
+
+    let result = send(&settings, subject, body);
+
+    let record = DeliveryRecord {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        kind,
+        subject: subject.to_string(),
+        recipient: settings.to_address.clone(),
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+    if let Err(e) = store::append_ndjson(&delivery_log_path(data_dir), &record) {
+        tracing::warn!("Failed to record email delivery: {}", e);
+    }
+
+    result
+}
+
+fn send(settings: &SmtpSettings, subject: &str, body: &str) -> Result<(), String> {
+    validate_settings(settings)?;
+
+    let message = Message::builder()
+        .from(
+            settings
+                .from_address
+                .parse()
+                .map_err(|e| format!("Invalid sender address: {}", e))?,
+        )
+        .to(settings
+            .to_address
+            .parse()
+            .map_err(|e| format!("Invalid recipient address: {}", e))?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body.to_string())
+        .map_err(|e| format!("Failed to build email: {}", e))?;
+
+    let builder = if settings.starttls {
+        SmtpTransport::starttls_relay(&settings.host)
+    } else {
+        SmtpTransport::relay(&settings.host)
+    }
+    .map_err(|e| format!("Failed to configure SMTP transport: {}", e))?
+    .port(settings.port);
+
+    let builder = match keychain::get_secret(PASSWORD_KEY)? {
+        Some(password) => {
+            builder.credentials(Credentials::new(settings.username.clone(), password))
+        }
+        None => builder,
+    };
+
+    builder
+        .build()
+        .send(&message)
+        .map(|_| ())
+        .map_err(|e| format!("SMTP delivery failed: {}", e))
+}
+
+/// Read the most recent delivery log entries (oldest first).
+pub fn recent_deliveries(data_dir: &Path, limit: usize) -> Result<Vec<DeliveryRecord>, String> {
+    store::read_ndjson_tail(&delivery_log_path(data_dir), limit)
+}
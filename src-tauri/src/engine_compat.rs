@@ -0,0 +1,88 @@
+//! Shell/Engine Version Compatibility
+//!
+//! The shell and the `prism-headless` sidecar are released together, but a
+//! hand-replaced sidecar or a partial update can pair them up differently.
+//! The version in the ready signal is checked against the range this shell
+//! speaks the protocol of. An engine outside it is stopped and
+//! `engine-version-mismatch` is emitted with both versions, instead of
+//! commands failing later with schema parse errors.
+
+use crate::python_engine::EngineRole;
+use semver::Version;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+pub const EVENT: &str = "engine-version-mismatch";
+
+/// Oldest engine version this shell supports
+const MIN_ENGINE_VERSION: &str = "0.1.0";
+
+/// First engine version this shell no longer supports
+const MAX_ENGINE_VERSION: &str = "0.2.0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Mismatch {
+    /// The engine predates the shell; updating the app fixes it
+    EngineTooOld,
+    /// The engine is newer than the shell understands
+    EngineTooNew,
+    /// The ready signal carried no semantic version
+    Unrecognized,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionMismatch {
+    pub role: &'static str,
+    pub kind: Mismatch,
+    pub engine_version: String,
+    pub shell_version: String,
+    /// Supported engine versions, e.g. `>=0.1.0, <0.2.0`
+    pub supported: String,
+}
+
+impl VersionMismatch {
+    pub fn message(&self) -> String {
+        format!(
+            "Engine version {} is not compatible with app version {} (supported: {})",
+            self.engine_version, self.shell_version, self.supported
+        )
+    }
+}
+
+fn classify(engine_version: &str) -> Option<Mismatch> {
+    let min = Version::parse(MIN_ENGINE_VERSION).expect("valid minimum engine version");
+    let max = Version::parse(MAX_ENGINE_VERSION).expect("valid maximum engine version");
+    let Ok(version) = Version::parse(engine_version.trim().trim_start_matches('v')) else {
+        return Some(Mismatch::Unrecognized);
+    };
+    if version < min {
+        Some(Mismatch::EngineTooOld)
+    } else if version >= max {
+        Some(Mismatch::EngineTooNew)
+    } else {
+        None
+    }
+}
+
+/// Check the version an engine reported in its ready signal.
+pub fn check(
+    app_handle: &AppHandle,
+    role: EngineRole,
+    engine_version: &str,
+) -> Option<VersionMismatch> {
+    classify(engine_version).map(|kind| VersionMismatch {
+        role: role.as_str(),
+        kind,
+        engine_version: engine_version.to_string(),
+        shell_version: app_handle.package_info().version.to_string(),
+        supported: format!(">={}, <{}", MIN_ENGINE_VERSION, MAX_ENGINE_VERSION),
+    })
+}
+
+/// Log `mismatch` and emit `engine-version-mismatch`.
+pub fn report(app_handle: &AppHandle, mismatch: &VersionMismatch) {
+    tracing::error!("Python {}: {}", mismatch.role, mismatch.message());
+    let _ = app_handle.emit(EVENT, mismatch);
+}
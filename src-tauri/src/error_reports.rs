@@ -0,0 +1,415 @@
+//! Historical Error Reports
+//!
+//! Aggregates the error rows the engine captures in `system_logs` into one
+//! entry per error signature, with occurrence counts and first/last seen
+//! timestamps, so users can tell a recurring error from a one-off.
+//!
+//! Resolution state is owned by the shell (`resolved_errors.json`): marking a
+//! report resolved records when, and a report that occurs again afterwards is
+//! flagged as `recurred`.
+//!
+//! With error reporting enabled in the settings, the open reports of a
+//! session can be submitted as one bundle, grouped by category, to the
+//! reporting endpoint behind the proxy (which files the `[AUTO]` issues).
+//! Messages are scrubbed of e-mail addresses, user names in paths, long
+//! numbers (phone and account numbers) and IBANs first. Signatures already
+//! submitted are only sent again once they recur (`submitted_errors.json`).
+
+use crate::dataset;
+use crate::proxy;
+use crate::redaction;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Resolution store file name inside the app data dir
+pub(crate) const RESOLVED_FILE: &str = "resolved_errors.json";
+
+/// Signatures already submitted, with the `last_seen` they were sent at
+pub(crate) const SUBMITTED_FILE: &str = "submitted_errors.json";
+
+/// Timeout for the submission request
+const SUBMIT_TIMEOUT_SECS: u64 = 15;
+
+/// Shortest digit run scrubbed as a phone or account number
+const MIN_SCRUBBED_DIGITS: usize = 7;
+
+/// One aggregated error signature
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub signature: String,
+    pub level: String,
+    pub category: String,
+    pub component: String,
+    pub message: String,
+    pub occurrences: u32,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub sessions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<String>,
+    /// Occurred again after being marked resolved
+    pub recurred: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ResolvedStore {
+    /// Signature -> UTC resolution time in SQLite `CURRENT_TIMESTAMP` format
+    #[serde(default)]
+    resolved: HashMap<String, String>,
+}
+
+fn resolved_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(RESOLVED_FILE)
+}
+
+fn text(row: &Value, key: &str) -> String {
+    match &row[key] {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Signature used for deduplication: the engine's error hash when present,
+/// otherwise category + component + message.
+fn signature(row: &Value) -> String {
+    let hash = text(row, "error_hash");
+    if !hash.is_empty() {
+        return hash;
+    }
+    format!(
+        "{}|{}|{}",
+        text(row, "category"),
+        text(row, "component"),
+        text(row, "message")
+    )
+}
+
+/// Aggregate raw `system_logs` rows, optionally restricted to one session.
+pub fn aggregate(
+    data_dir: &Path,
+    rows: &[Value],
+    session_id: Option<&str>,
+) -> Result<Vec<ErrorReport>, String> {
+    let resolved: ResolvedStore = store::read_json(&resolved_path(data_dir))?.unwrap_or_default();
+    let mut reports: HashMap<String, ErrorReport> = HashMap::new();
+    let mut sessions: HashMap<String, BTreeSet<String>> = HashMap::new();
+
+    for row in rows {
+        let session = text(row, "session_id");
+        if session_id.is_some_and(|wanted| wanted != session) {
+            continue;
+        }
+
+        let signature = signature(row);
+        let timestamp = text(row, "timestamp");
+        let report = reports.entry(signature.clone()).or_insert_with(|| ErrorReport {
+            signature: signature.clone(),
+            level: text(row, "level"),
+            category: text(row, "category"),
+            component: text(row, "component"),
+            message: text(row, "message"),
+            occurrences: 0,
+            first_seen: timestamp.clone(),
+            last_seen: timestamp.clone(),
+            sessions: vec![],
+            resolved_at: None,
+            recurred: false,
+        });
+
+        report.occurrences += 1;
+        if timestamp < report.first_seen {
+            report.first_seen = timestamp.clone();
+        }
+        if timestamp > report.last_seen {
+            report.last_seen = timestamp;
+        }
+        if !session.is_empty() {
+            sessions.entry(signature).or_default().insert(session);
+        }
+    }
+
+    let mut reports: Vec<ErrorReport> = reports
+        .into_values()
+        .map(|mut report| {
+            report.sessions = sessions
+                .remove(&report.signature)
+                .map(|set| set.into_iter().collect())
+                .unwrap_or_default();
+            if let Some(resolved_at) = resolved.resolved.get(&report.signature) {
+                report.recurred = report.last_seen > *resolved_at;
+                report.resolved_at = Some(resolved_at.clone());
+            }
+            report
+        })
+        .collect();
+
+    reports.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+    Ok(reports)
+}
+
+/// Mark a signature resolved (or reopen it).
+pub fn set_resolved(data_dir: &Path, signature: &str, resolved: bool) -> Result<(), String> {
+    let path = resolved_path(data_dir);
+    let mut store_data: ResolvedStore = store::read_json(&path)?.unwrap_or_default();
+
+    if resolved {
+        // `system_logs.timestamp` is SQLite CURRENT_TIMESTAMP (UTC, no zone);
+        // match that format so lexical comparison against `last_seen` holds.
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S");
+        store_data
+            .resolved
+            .insert(signature.to_string(), now.to_string());
+    } else {
+        store_data.resolved.remove(signature);
+    }
+
+    store::write_json(&path, &store_data)
+}
+
+// =============================================================================
+// Submission
+// =============================================================================
+
+/// One deduplicated error in a submitted bundle
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledError {
+    pub signature: String,
+    pub level: String,
+    pub component: String,
+    /// Scrubbed message
+    pub message: String,
+    pub occurrences: u32,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorBundle {
+    pub created_at: DateTime<Utc>,
+    pub session_id: Option<String>,
+    pub app_version: String,
+    pub engine_version: Option<String>,
+    /// `<os>-<arch>`
+    pub platform: String,
+    /// Errors by category
+    pub categories: BTreeMap<String, Vec<BundledError>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionResult {
+    /// `false` when there was nothing new to send
+    pub submitted: bool,
+    pub error_count: usize,
+    /// Exactly what was sent
+    pub bundle: ErrorBundle,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SubmittedStore {
+    /// Signature -> `last_seen` of the submitted occurrence
+    #[serde(default)]
+    submitted: HashMap<String, String>,
+}
+
+fn submitted_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SUBMITTED_FILE)
+}
+
+fn report_url() -> String {
+    std::env::var("PRISM_REPORT_URL").unwrap_or_else(|_| {
+        let proxy =
+            std::env::var("PROXY_URL").unwrap_or_else(|_| dataset::DEFAULT_PROXY_URL.to_string());
+        format!("{}/report", proxy.trim_end_matches('/'))
+    })
+}
+
+/// Replace the user name after `/Users/`, `/home/` or `\Users\`.
+fn scrub_home_paths(text: &str) -> String {
+    let mut scrubbed = text.to_string();
+    for marker in ["/Users/", "/home/", "\\Users\\"] {
+        let mut from = 0;
+        while let Some(found) = scrubbed[from..].find(marker) {
+            let start = from + found + marker.len();
+            let end = scrubbed[start..]
+                .find(|c: char| c == '/' || c == '\\' || c == '\'' || c == '"' || c.is_whitespace())
+                .map_or(scrubbed.len(), |offset| start + offset);
+            scrubbed.replace_range(start..end, "<user>");
+            from = start + "<user>".len();
+        }
+    }
+    scrubbed
+}
+
+fn is_iban(word: &str) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    (15..=34).contains(&chars.len())
+        && chars[..2].iter().all(char::is_ascii_uppercase)
+        && chars[2..4].iter().all(char::is_ascii_digit)
+        && chars[4..].iter().all(char::is_ascii_alphanumeric)
+}
+
+/// Replace e-mail addresses and IBANs, word by word.
+fn scrub_words(text: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(|chunk| {
+            let word = chunk.trim_end();
+            let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+            let replacement = if core.contains('@')
+                && core
+                    .rsplit('@')
+                    .next()
+                    .is_some_and(|domain| domain.contains('.'))
+            {
+                Some("<email>")
+            } else if is_iban(core) {
+                Some("<iban>")
+            } else {
+                None
+            };
+            match replacement {
+                Some(replacement) => chunk.replacen(core, replacement, 1),
+                None => chunk.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Replace standalone runs of `MIN_SCRUBBED_DIGITS` or more digits, optionally
+/// with a leading `+` and single spaces between groups.
+fn scrub_numbers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let standalone = i == 0 || !chars[i - 1].is_alphanumeric();
+        let starts = chars[i].is_ascii_digit()
+            || (chars[i] == '+' && chars.get(i + 1).is_some_and(char::is_ascii_digit));
+        if !(standalone && starts) {
+            scrubbed.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        let mut digits = usize::from(chars[i].is_ascii_digit());
+        while end < chars.len() {
+            if chars[end].is_ascii_digit() {
+                digits += 1;
+                end += 1;
+            } else if chars[end] == ' ' && chars.get(end + 1).is_some_and(char::is_ascii_digit) {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        let bounded = chars.get(end).is_none_or(|c| !c.is_alphanumeric());
+        if digits >= MIN_SCRUBBED_DIGITS && bounded {
+            scrubbed.push_str("<number>");
+        } else {
+            scrubbed.extend(&chars[i..end]);
+        }
+        i = end;
+    }
+    scrubbed
+}
+
+/// Strip secrets and personal data from an error message before it leaves
+/// the device.
+pub fn scrub(text: &str) -> String {
+    scrub_numbers(&scrub_words(&scrub_home_paths(&redaction::redact(text))))
+}
+
+/// Bundle the open reports of `session_id` (all sessions when `None`) that
+/// were not submitted before or recurred since.
+pub fn bundle(
+    data_dir: &Path,
+    rows: &[Value],
+    session_id: Option<&str>,
+    app_version: String,
+    engine_version: Option<String>,
+) -> Result<ErrorBundle, String> {
+    let submitted: SubmittedStore =
+        store::read_json(&submitted_path(data_dir))?.unwrap_or_default();
+    let mut categories: BTreeMap<String, Vec<BundledError>> = BTreeMap::new();
+
+    for report in aggregate(data_dir, rows, session_id)? {
+        if report.resolved_at.is_some() && !report.recurred {
+            continue;
+        }
+        if submitted
+            .submitted
+            .get(&report.signature)
+            .is_some_and(|last_seen| *last_seen >= report.last_seen)
+        {
+            continue;
+        }
+        let category = if report.category.is_empty() {
+            "uncategorized".to_string()
+        } else {
+            report.category
+        };
+        categories.entry(category).or_default().push(BundledError {
+            signature: report.signature,
+            level: report.level,
+            component: report.component,
+            message: scrub(&report.message),
+            occurrences: report.occurrences,
+            first_seen: report.first_seen,
+            last_seen: report.last_seen,
+        });
+    }
+
+    Ok(ErrorBundle {
+        created_at: Utc::now(),
+        session_id: session_id.map(str::to_string),
+        app_version,
+        engine_version,
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        categories,
+    })
+}
+
+/// Post `bundle` to the reporting endpoint and remember what was sent.
+pub async fn submit(data_dir: &Path, bundle: ErrorBundle) -> Result<SubmissionResult, String> {
+    let error_count = bundle.categories.values().map(Vec::len).sum();
+    if error_count == 0 {
+        return Ok(SubmissionResult {
+            submitted: false,
+            error_count,
+            bundle,
+        });
+    }
+
+    proxy::client()
+        .post(report_url())
+        .json(&bundle)
+        .timeout(Duration::from_secs(SUBMIT_TIMEOUT_SECS))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to submit error report: {}", e))?;
+
+    let path = submitted_path(data_dir);
+    let mut store_data: SubmittedStore = store::read_json(&path)?.unwrap_or_default();
+    for error in bundle.categories.values().flatten() {
+        store_data
+            .submitted
+            .insert(error.signature.clone(), error.last_seen.clone());
+    }
+    store::write_json(&path, &store_data)?;
+
+    Ok(SubmissionResult {
+        submitted: true,
+        error_count,
+        bundle,
+    })
+}
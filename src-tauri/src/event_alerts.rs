@@ -0,0 +1,441 @@
+//! Ex-Date and Earnings Alerts
+//!
+//! Rules like "notify me 3 days before the ex-date of any holding paying more
+//! than €50" are evaluated once a day against the engine's dividend and
+//! earnings calendar (`get_event_calendar`) for the ISINs currently held.
+//!
+//! - a matching event notifies once; snoozing it silences it until a date
+//! - the day's matches are kept as `upcoming` for the daily digest, which is
+//!   also emailed when `sendDailyDigest` is enabled in the email settings
+//!
+//! State lives in `event_alerts.json`.
+
+use crate::commands::portfolio::{PortfoliosResponse, PositionsResponse};
+use crate::email::{self, DeliveryKind};
+use crate::price_alerts::{self, PriceAlert};
+use crate::python_engine::PythonEngine;
+use crate::store;
+use chrono::{Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+/// Alerts file inside the app data dir
+pub(crate) const ALERTS_FILE: &str = "event_alerts.json";
+
+/// Longest look-ahead a rule may use
+const MAX_DAYS_BEFORE: u32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+    ExDividend,
+    Earnings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventAlertRule {
+    pub id: u32,
+    pub kinds: Vec<EventKind>,
+    pub days_before: u32,
+    /// Minimum expected dividend payout (quantity x amount per share); only
+    /// applies to ex-dividend events
+    #[serde(default)]
+    pub min_amount: Option<f64>,
+    pub enabled: bool,
+}
+
+/// One entry of the engine's event calendar
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarEvent {
+    isin: String,
+    kind: EventKind,
+    date: NaiveDate,
+    #[serde(default)]
+    amount_per_share: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarResponse {
+    events: Vec<CalendarEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingEvent {
+    pub rule_id: u32,
+    pub isin: String,
+    pub name: String,
+    pub kind: EventKind,
+    pub date: NaiveDate,
+    pub expected_amount: Option<f64>,
+    pub snoozed_until: Option<NaiveDate>,
+}
+
+impl UpcomingEvent {
+    fn key(&self) -> String {
+        event_key(self.rule_id, &self.isin, self.kind, self.date)
+    }
+}
+
+fn event_key(rule_id: u32, isin: &str, kind: EventKind, date: NaiveDate) -> String {
+    format!("{}:{}:{:?}:{}", rule_id, isin, kind, date)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snooze {
+    key: String,
+    until: NaiveDate,
+    /// Event date; the snooze is dropped once the event has passed
+    event_date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlertsFile {
+    #[serde(default)]
+    rules: Vec<EventAlertRule>,
+    #[serde(default)]
+    snoozes: Vec<Snooze>,
+    /// Keys of events already notified
+    #[serde(default)]
+    notified: Vec<String>,
+    #[serde(default)]
+    upcoming: Vec<UpcomingEvent>,
+    #[serde(default)]
+    last_check: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyDigest {
+    pub date: NaiveDate,
+    pub upcoming_events: Vec<UpcomingEvent>,
+    /// Price alerts triggered in the last 24 hours
+    pub triggered_price_alerts: Vec<PriceAlert>,
+}
+
+fn alerts_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(ALERTS_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<AlertsFile, String> {
+    Ok(store::read_json(&alerts_path(data_dir))?.unwrap_or_default())
+}
+
+fn save(data_dir: &Path, file: &AlertsFile) -> Result<(), String> {
+    store::write_json(&alerts_path(data_dir), file)
+}
+
+pub fn rules(data_dir: &Path) -> Result<Vec<EventAlertRule>, String> {
+    Ok(load(data_dir)?.rules)
+}
+
+/// Create a rule, or update rule `id` when given.
+pub fn set_rule(
+    data_dir: &Path,
+    id: Option<u32>,
+    kinds: Vec<EventKind>,
+    days_before: u32,
+    min_amount: Option<f64>,
+    enabled: bool,
+) -> Result<EventAlertRule, String> {
+    if kinds.is_empty() {
+        return Err("Choose at least one event type".to_string());
+    }
+    if days_before > MAX_DAYS_BEFORE {
+        return Err(format!(
+            "Alerts can look at most {} days ahead",
+            MAX_DAYS_BEFORE
+        ));
+    }
+    if min_amount.is_some_and(|amount| !amount.is_finite() || amount < 0.0) {
+        return Err("Minimum amount must be zero or positive".to_string());
+    }
+
+    let mut file = load(data_dir)?;
+    let rule = EventAlertRule {
+        id: match id {
+            Some(id) => id,
+            None => file.rules.iter().map(|rule| rule.id).max().unwrap_or(0) + 1,
+        },
+        kinds,
+        days_before,
+        min_amount,
+        enabled,
+    };
+    match (
+        id,
+        file.rules
+            .iter_mut()
+            .find(|existing| existing.id == rule.id),
+    ) {
+        (_, Some(existing)) => *existing = rule.clone(),
+        (None, None) => file.rules.push(rule.clone()),
+        (Some(id), None) => return Err(format!("Event alert {} not found", id)),
+    }
+    // Re-evaluate with the changed rule on the next scheduler tick
+    file.last_check = None;
+    save(data_dir, &file)?;
+    Ok(rule)
+}
+
+pub fn delete_rule(data_dir: &Path, id: u32) -> Result<(), String> {
+    let mut file = load(data_dir)?;
+    let before = file.rules.len();
+    file.rules.retain(|rule| rule.id != id);
+    if file.rules.len() == before {
+        return Err(format!("Event alert {} not found", id));
+    }
+    file.upcoming.retain(|event| event.rule_id != id);
+    save(data_dir, &file)
+}
+
+/// Silence one upcoming event until `until` (inclusive).
+pub fn snooze(
+    data_dir: &Path,
+    rule_id: u32,
+    isin: &str,
+    kind: EventKind,
+    date: NaiveDate,
+    until: NaiveDate,
+) -> Result<(), String> {
+    let mut file = load(data_dir)?;
+    let key = event_key(rule_id, isin, kind, date);
+    let Some(event) = file.upcoming.iter_mut().find(|event| event.key() == key) else {
+        return Err("No such upcoming event".to_string());
+    };
+    event.snoozed_until = Some(until);
+
+    file.snoozes.retain(|snooze| snooze.key != key);
+    file.snoozes.push(Snooze {
+        key: key.clone(),
+        until,
+        event_date: date,
+    });
+    // Notify again once the snooze ends
+    file.notified.retain(|notified| *notified != key);
+    save(data_dir, &file)
+}
+
+/// Held quantity and name per ISIN across real portfolios
+async fn holdings(engine: &PythonEngine) -> Result<HashMap<String, (String, f64)>, String> {
+    let data = engine.request("list_portfolios", json!({})).await?;
+    let portfolios: PortfoliosResponse =
+        serde_json::from_value(data).map_err(|e| format!("Invalid portfolio list: {}", e))?;
+
+    let mut held: HashMap<String, (String, f64)> = HashMap::new();
+    for portfolio in portfolios.portfolios {
+        let data = engine
+            .request("get_positions", json!({ "portfolioId": portfolio.id }))
+            .await?;
+        let response: PositionsResponse =
+            serde_json::from_value(data).map_err(|e| format!("Invalid positions: {}", e))?;
+        for position in response.positions {
+            held.entry(position.isin)
+                .or_insert_with(|| (position.name, 0.0))
+                .1 += position.quantity;
+        }
+    }
+    Ok(held)
+}
+
+/// Evaluate every rule against the calendar. Runs at most once per day
+/// unless `force` is set; returns whether an evaluation ran.
+pub async fn evaluate(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    force: bool,
+) -> Result<bool, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let mut file = load(&data_dir)?;
+    let today = Local::now().date_naive();
+    if !force && file.last_check == Some(today) {
+        return Ok(false);
+    }
+
+    let rules: Vec<&EventAlertRule> = file.rules.iter().filter(|rule| rule.enabled).collect();
+    let horizon = rules.iter().map(|rule| rule.days_before).max();
+    let mut upcoming = vec![];
+
+    if let Some(horizon) = horizon {
+        let held = holdings(engine).await?;
+        let isins: Vec<&String> = held.keys().collect();
+        let to = today + chrono::Duration::days(horizon as i64);
+        let data = engine
+            .request(
+                "get_event_calendar",
+                json!({ "isins": isins, "from": today.to_string(), "to": to.to_string() }),
+            )
+            .await?;
+        let calendar: CalendarResponse =
+            serde_json::from_value(data).map_err(|e| format!("Invalid event calendar: {}", e))?;
+
+        for event in &calendar.events {
+            let Some((name, quantity)) = held.get(&event.isin) else {
+                continue;
+            };
+            let expected_amount = event.amount_per_share.map(|amount| amount * quantity);
+            let days_until = (event.date - today).num_days();
+
+            for rule in &rules {
+                let below_minimum = event.kind == EventKind::ExDividend
+                    && rule
+                        .min_amount
+                        .is_some_and(|min| expected_amount.unwrap_or(0.0) < min);
+                if !rule.kinds.contains(&event.kind)
+                    || days_until < 0
+                    || days_until > rule.days_before as i64
+                    || below_minimum
+                {
+                    continue;
+                }
+                upcoming.push(UpcomingEvent {
+                    rule_id: rule.id,
+                    isin: event.isin.clone(),
+                    name: name.clone(),
+                    kind: event.kind,
+                    date: event.date,
+                    expected_amount,
+                    snoozed_until: None,
+                });
+            }
+        }
+    }
+
+    file.snoozes.retain(|snooze| snooze.event_date >= today);
+    let snoozes: HashMap<&str, NaiveDate> = file
+        .snoozes
+        .iter()
+        .map(|snooze| (snooze.key.as_str(), snooze.until))
+        .collect();
+    for event in &mut upcoming {
+        event.snoozed_until = snoozes.get(event.key().as_str()).copied();
+        let snoozed = event.snoozed_until.is_some_and(|until| until >= today);
+        if !snoozed && !file.notified.contains(&event.key()) {
+            notify(app_handle, event);
+            file.notified.push(event.key());
+        }
+    }
+
+    let current: Vec<String> = upcoming.iter().map(UpcomingEvent::key).collect();
+    file.notified.retain(|key| current.contains(key));
+    file.upcoming = upcoming;
+    file.last_check = Some(today);
+    save(&data_dir, &file)?;
+
+    send_digest_email(&data_dir).await;
+    Ok(true)
+}
+
+fn notify(app_handle: &AppHandle, event: &UpcomingEvent) {
+    let what = match event.kind {
+        EventKind::ExDividend => "Ex-dividend date",
+        EventKind::Earnings => "Earnings",
+    };
+    let mut body = format!("{}: {} on {}", event.name, what.to_lowercase(), event.date);
+    if let Some(amount) = event
+        .expected_amount
+        .filter(|_| event.kind == EventKind::ExDividend)
+    {
+        body.push_str(&format!(" (expected payout {:.2})", amount));
+    }
+
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(what)
+        .body(&body)
+        .show()
+    {
+        tracing::warn!("Failed to show event alert notification: {}", e);
+    }
+    let _ = app_handle.emit("event-alert-triggered", event);
+}
+
+/// Upcoming events and recent price alerts for today's digest
+pub fn digest(data_dir: &Path) -> Result<DailyDigest, String> {
+    let day_ago = Utc::now() - chrono::Duration::hours(24);
+    Ok(DailyDigest {
+        date: Local::now().date_naive(),
+        upcoming_events: load(data_dir)?.upcoming,
+        triggered_price_alerts: price_alerts::list(data_dir)?
+            .into_iter()
+            .filter(|alert| alert.triggered_at.is_some_and(|at| at >= day_ago))
+            .collect(),
+    })
+}
+
+pub fn digest_text(digest: &DailyDigest) -> String {
+    let mut lines = vec![
+        format!("Portfolio Prism daily digest for {}", digest.date),
+        String::new(),
+    ];
+
+    lines.push("Upcoming events".to_string());
+    if digest.upcoming_events.is_empty() {
+        lines.push("  None".to_string());
+    }
+    for event in &digest.upcoming_events {
+        let kind = match event.kind {
+            EventKind::ExDividend => "ex-dividend",
+            EventKind::Earnings => "earnings",
+        };
+        let amount = event
+            .expected_amount
+            .filter(|_| event.kind == EventKind::ExDividend)
+            .map(|amount| format!(", expected {:.2}", amount))
+            .unwrap_or_default();
+        lines.push(format!(
+            "  {} {}: {} ({}{})",
+            event.date, kind, event.name, event.isin, amount
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push("Price alerts triggered".to_string());
+    if digest.triggered_price_alerts.is_empty() {
+        lines.push("  None".to_string());
+    }
+    for alert in &digest.triggered_price_alerts {
+        lines.push(format!(
+            "  {}: {:.2} (threshold {:.2})",
+            alert.isin,
+            alert.last_price.unwrap_or_default(),
+            alert.threshold
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Email the digest when enabled; failures are only logged.
+async fn send_digest_email(data_dir: &Path) {
+    let enabled = email::load_settings(data_dir)
+        .map(|settings| settings.enabled && settings.send_daily_digest)
+        .unwrap_or(false);
+    let Ok(digest) = digest(data_dir) else {
+        return;
+    };
+    if !enabled {
+        return;
+    }
+
+    let subject = format!("Portfolio Prism daily digest ({})", digest.date);
+    let body = digest_text(&digest);
+    let data_dir = data_dir.to_path_buf();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        email::deliver(&data_dir, DeliveryKind::DailyDigest, &subject, &body)
+    })
+    .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!("Failed to send daily digest: {}", e),
+        Err(e) => tracing::warn!("Failed to send daily digest: {}", e),
+    }
+}
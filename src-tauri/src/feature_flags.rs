@@ -0,0 +1,130 @@
+//! Feature Flags
+//!
+//! Gates experimental commands and subsystems so risky code can ship disabled.
+//! Defaults are compiled in; local overrides live in `feature_flags.json` in
+//! the app data dir. There is deliberately no remote flag service.
+
+use crate::store;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Overrides file name inside the app data dir
+pub(crate) const FLAGS_FILE: &str = "feature_flags.json";
+
+/// Compiled-in flags: (name, default, description)
+const FLAG_DEFINITIONS: &[(&str, bool, &str)] = &[
+    ("new_importers", false, "Broker statement importers beyond Trade Republic"),
+    ("projection_engine", false, "Portfolio projection and scenario analysis"),
+    ("plugin_system", false, "Third-party analytics plugins"),
+    ("progressive_dashboard", true, "Stream dashboard sections as they become available"),
+    ("worker_sidecar", false, "Run long jobs on a secondary engine process"),
+    ("ipc_recording", false, "Record engine commands and responses to a trace file"),
+    ("mock_data", false, "Serve fixture data while the engine is down (debug builds only)"),
+    ("monthly_insights", false, "Monthly behavioral insights computed on this device"),
+    ("community_comparison", false, "Rank allocation metrics against Hive aggregate statistics"),
+];
+
+/// A flag as reported to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlag {
+    pub name: String,
+    pub enabled: bool,
+    pub default: bool,
+    pub overridden: bool,
+    pub description: String,
+}
+
+/// Flag registry with compiled defaults and local overrides
+pub struct FeatureFlags {
+    path: PathBuf,
+    overrides: RwLock<HashMap<String, bool>>,
+}
+
+impl FeatureFlags {
+    /// Load overrides from the data dir. Unknown or unreadable overrides are
+    /// ignored so a bad file can never enable code by accident.
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(FLAGS_FILE);
+        let overrides = match store::read_json::<HashMap<String, bool>>(&path) {
+            Ok(Some(overrides)) => overrides
+                .into_iter()
+                .filter(|(name, _)| definition(name).is_some())
+                .collect(),
+            Ok(None) => HashMap::new(),
+            Err(e) => {
+                tracing::warn!("Ignoring feature flag overrides: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path,
+            overrides: RwLock::new(overrides),
+        }
+    }
+
+    /// Whether `name` is enabled. Unknown flags are always disabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        let Some((_, default, _)) = definition(name) else {
+            return false;
+        };
+        self.overrides
+            .read()
+            .ok()
+            .and_then(|overrides| overrides.get(name).copied())
+            .unwrap_or(*default)
+    }
+
+    /// Return `Err` if `name` is disabled, for gating commands.
+    pub fn require(&self, name: &str) -> Result<(), String> {
+        if self.is_enabled(name) {
+            Ok(())
+        } else {
+            Err(format!("Feature '{}' is disabled", name))
+        }
+    }
+
+    /// All known flags with their effective values
+    pub fn list(&self) -> Vec<FeatureFlag> {
+        let overrides = self
+            .overrides
+            .read()
+            .map(|overrides| overrides.clone())
+            .unwrap_or_default();
+
+        FLAG_DEFINITIONS
+            .iter()
+            .map(|(name, default, description)| FeatureFlag {
+                name: name.to_string(),
+                enabled: overrides.get(*name).copied().unwrap_or(*default),
+                default: *default,
+                overridden: overrides.contains_key(*name),
+                description: description.to_string(),
+            })
+            .collect()
+    }
+
+    /// Set a local override. `None` resets the flag to its compiled default.
+    pub fn set(&self, name: &str, enabled: Option<bool>) -> Result<(), String> {
+        if definition(name).is_none() {
+            return Err(format!("Unknown feature flag: {}", name));
+        }
+
+        let mut overrides = self
+            .overrides
+            .write()
+            .map_err(|_| "Feature flag store is poisoned".to_string())?;
+        match enabled {
+            Some(value) => overrides.insert(name.to_string(), value),
+            None => overrides.remove(name),
+        };
+        store::write_json(&self.path, &*overrides)
+    }
+}
+
+fn definition(name: &str) -> Option<&'static (&'static str, bool, &'static str)> {
+    FLAG_DEFINITIONS.iter().find(|(flag, _, _)| *flag == name)
+}
@@ -0,0 +1,254 @@
+//! FX Rates
+//!
+//! Daily euro reference rates from the ECB, cached in `cache/fx_rates.json`.
+//! The engine reports each position in its trading currency and sums them
+//! as they are; the shell converts positions and dashboard figures into the
+//! display currency from the settings, so USD and CHF positions show in one
+//! currency without another round trip to the engine. Dashboard figures are
+//! in euros, the brokerage's currency.
+//!
+//! Converting never waits on the network: it reads the cache and, when the
+//! cache is older than `CACHE_TTL_HOURS`, refreshes it in the background.
+//! Without any cached rates, responses are left in their own currencies.
+
+use crate::app_settings;
+use crate::commands::portfolio::{DashboardData, PositionsResponse};
+use crate::proxy;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Euro foreign exchange reference rates, published on working days
+const ECB_DAILY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+
+/// Cache file inside `cache/` in the app data dir
+const CACHE_FILE: &str = "fx_rates.json";
+
+/// How long fetched rates are used without refetching
+const CACHE_TTL_HOURS: i64 = 12;
+
+/// Timeout for the ECB request
+const FETCH_TIMEOUT_SECS: u64 = 15;
+
+/// Currency the ECB quotes against
+const BASE_CURRENCY: &str = "EUR";
+
+/// Set while a background refresh runs
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FxRates {
+    pub base: String,
+    /// ECB reference date (YYYY-MM-DD)
+    pub date: String,
+    pub fetched_at: DateTime<Utc>,
+    /// Units of each currency per euro
+    pub rates: BTreeMap<String, f64>,
+    /// Served from cache after a failed refresh
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl FxRates {
+    fn is_fresh(&self) -> bool {
+        Utc::now() - self.fetched_at < chrono::Duration::hours(CACHE_TTL_HOURS)
+    }
+
+    fn rate(&self, currency: &str) -> Option<f64> {
+        let currency = currency.trim().to_uppercase();
+        if currency == BASE_CURRENCY {
+            return Some(1.0);
+        }
+        self.rates
+            .get(&currency)
+            .copied()
+            .filter(|rate| *rate > 0.0)
+    }
+
+    /// Factor converting amounts in `from` into `to`
+    pub fn factor(&self, from: &str, to: &str) -> Option<f64> {
+        Some(self.rate(to)? / self.rate(from)?)
+    }
+}
+
+fn cache_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("cache").join(CACHE_FILE)
+}
+
+/// Parse the ECB daily feed: `<Cube time="...">` holding one
+/// `<Cube currency="USD" rate="1.0876"/>` per currency.
+fn parse(xml: &str) -> Result<FxRates, String> {
+    let document =
+        roxmltree::Document::parse(xml).map_err(|e| format!("Invalid ECB response: {}", e))?;
+    let date = document
+        .descendants()
+        .find_map(|node| node.attribute("time"))
+        .ok_or("ECB response has no reference date")?
+        .to_string();
+    let rates: BTreeMap<String, f64> = document
+        .descendants()
+        .filter_map(|node| {
+            let currency = node.attribute("currency")?;
+            let rate = node.attribute("rate")?.parse().ok()?;
+            Some((currency.to_string(), rate))
+        })
+        .collect();
+    if rates.is_empty() {
+        return Err("ECB response has no rates".to_string());
+    }
+    Ok(FxRates {
+        base: BASE_CURRENCY.to_string(),
+        date,
+        fetched_at: Utc::now(),
+        rates,
+        stale: false,
+    })
+}
+
+async fn fetch_remote() -> Result<FxRates, String> {
+    let xml = proxy::client()
+        .get(ECB_DAILY_URL)
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("ECB request failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Invalid ECB response: {}", e))?;
+    parse(&xml)
+}
+
+/// Rates from cache when fresh, otherwise from the ECB; a stale copy is
+/// served when the ECB is unreachable.
+pub async fn rates(data_dir: &Path, force: bool) -> Result<FxRates, String> {
+    let path = cache_path(data_dir);
+    let cached: Option<FxRates> = store::read_json(&path)?;
+    if let Some(entry) = cached.as_ref().filter(|entry| !force && entry.is_fresh()) {
+        return Ok(entry.clone());
+    }
+
+    match fetch_remote().await {
+        Ok(rates) => {
+            store::write_json(&path, &rates)?;
+            Ok(rates)
+        }
+        Err(e) => match cached {
+            Some(mut entry) => {
+                tracing::warn!("FX rate refresh failed, serving stale cache: {}", e);
+                entry.stale = true;
+                Ok(entry)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Cached rates of any age, refreshing them in the background when due
+fn cached(data_dir: &Path) -> Option<FxRates> {
+    let cached: Option<FxRates> = store::read_json(&cache_path(data_dir))
+        .inspect_err(|e| tracing::warn!("Failed to read FX rates: {}", e))
+        .ok()
+        .flatten();
+    if cached.as_ref().is_none_or(|entry| !entry.is_fresh())
+        && !REFRESHING.swap(true, Ordering::SeqCst)
+    {
+        let data_dir = data_dir.to_path_buf();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = rates(&data_dir, false).await {
+                tracing::warn!("Failed to refresh FX rates: {}", e);
+            }
+            REFRESHING.store(false, Ordering::SeqCst);
+        });
+    }
+    cached
+}
+
+fn round_to(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
+}
+
+/// Convert positions into the display currency and recompute the totals and
+/// weights. Left as they are when a currency has no rate.
+pub fn convert_positions(data_dir: &Path, response: &mut PositionsResponse) {
+    let target = app_settings::load_general(data_dir).currency;
+    if response
+        .positions
+        .iter()
+        .all(|p| p.currency.eq_ignore_ascii_case(&target))
+    {
+        return;
+    }
+    let Some(rates) = cached(data_dir) else {
+        tracing::warn!("No FX rates yet; positions keep their own currencies");
+        return;
+    };
+    let factors: Option<Vec<f64>> = response
+        .positions
+        .iter()
+        .map(|p| rates.factor(&p.currency, &target))
+        .collect();
+    let Some(factors) = factors else {
+        tracing::warn!("Missing FX rate for a position; positions keep their own currencies");
+        return;
+    };
+
+    for (position, factor) in response.positions.iter_mut().zip(factors) {
+        position.avg_buy_price = round_to(position.avg_buy_price * factor, 2);
+        position.current_price = round_to(position.current_price * factor, 2);
+        position.current_value = round_to(position.current_value * factor, 2);
+        position.total_cost = round_to(position.total_cost * factor, 2);
+        position.pnl_eur = round_to(position.pnl_eur * factor, 2);
+        position.currency = target.clone();
+    }
+
+    let total_value: f64 = response.positions.iter().map(|p| p.current_value).sum();
+    let total_cost: f64 = response.positions.iter().map(|p| p.total_cost).sum();
+    for position in &mut response.positions {
+        position.weight = if total_value > 0.0 {
+            round_to(position.current_value / total_value * 100.0, 2)
+        } else {
+            0.0
+        };
+    }
+    let total_pnl = total_value - total_cost;
+    response.total_value = round_to(total_value, 2);
+    response.total_cost = round_to(total_cost, 2);
+    response.total_pnl = round_to(total_pnl, 2);
+    response.total_pnl_percent = if total_cost > 0.0 {
+        round_to(total_pnl / total_cost * 100.0, 2)
+    } else {
+        0.0
+    };
+}
+
+/// Convert the dashboard's euro figures into the display currency.
+/// Percentages and weights are unaffected.
+pub fn convert_dashboard(data_dir: &Path, dashboard: &mut DashboardData) {
+    let target = app_settings::load_general(data_dir).currency;
+    if target.eq_ignore_ascii_case(BASE_CURRENCY) {
+        return;
+    }
+    let Some(factor) = cached(data_dir).and_then(|rates| rates.factor(BASE_CURRENCY, &target))
+    else {
+        tracing::warn!("No FX rate for {}; dashboard stays in euros", target);
+        return;
+    };
+
+    dashboard.total_value = round_to(dashboard.total_value * factor, 2);
+    dashboard.total_gain = round_to(dashboard.total_gain * factor, 2);
+    dashboard.day_change = round_to(dashboard.day_change * factor, 2);
+    for point in &mut dashboard.history {
+        point.value = round_to(point.value * factor, 2);
+    }
+    for holding in &mut dashboard.top_holdings {
+        holding.value = round_to(holding.value * factor, 2);
+        holding.pnl = round_to(holding.pnl * factor, 2);
+    }
+}
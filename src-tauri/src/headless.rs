@@ -0,0 +1,244 @@
+//! Headless Mode
+//!
+//! `portfolio-prism --headless <command>` runs one operation without the GUI
+//! and exits, e.g. for cron-driven syncs:
+//! - `sync [--portfolio ID]`: sync a portfolio (default: the scheduled one)
+//! - `pipeline [--portfolio ID]`: run the analysis pipeline
+//! - `export --csv PATH [--portfolio ID]`: write the positions to a CSV file
+//!
+//! The app starts without a window, tray or menu, spawns the primary sidecar
+//! on the usual data dir and sends the command through `PythonEngine`. The
+//! outcome is printed to stdout as one JSON object, `{"ok": true,
+//! "command": ..., "result": ...}` or `{"ok": false, "command": ...,
+//! "error": ...}`; logs go to stderr. Exit codes: 0 success, 1 failure,
+//! 2 bad usage.
+//!
+//! Sync never prompts for a login, so it needs a saved Trade Republic
+//! session. The running app holds the data dir, so headless runs fail while
+//! it is open. Windows release builds have no console; redirect stdout to a
+//! file there.
+
+use crate::app_settings;
+use crate::commands::portfolio;
+use crate::db_recovery;
+use crate::hive_cache::HiveCache;
+use crate::instance_lock;
+use crate::logging;
+use crate::pipeline_progress::PipelineRun;
+use crate::proxy;
+use crate::python_engine::{EnginePool, EngineRole, EngineState, PythonEngine};
+use crate::sandbox;
+use crate::scheduler;
+use crate::session_vault;
+use crate::store;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Context, Manager, Wry};
+
+pub const FLAG: &str = "--headless";
+
+const USAGE: &str = "Usage: portfolio-prism --headless sync [--portfolio ID]\n       \
+                     portfolio-prism --headless pipeline [--portfolio ID]\n       \
+                     portfolio-prism --headless export --csv PATH [--portfolio ID]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessCommand {
+    Sync {
+        portfolio_id: Option<u32>,
+    },
+    Pipeline {
+        portfolio_id: Option<u32>,
+    },
+    Export {
+        csv: PathBuf,
+        portfolio_id: Option<u32>,
+    },
+}
+
+impl HeadlessCommand {
+    fn name(&self) -> &'static str {
+        match self {
+            HeadlessCommand::Sync { .. } => "sync",
+            HeadlessCommand::Pipeline { .. } => "pipeline",
+            HeadlessCommand::Export { .. } => "export",
+        }
+    }
+}
+
+/// The headless command in `args` (with the executable first); `None` when
+/// `--headless` is absent and the GUI should start.
+pub fn parse_args(args: &[String]) -> Option<Result<HeadlessCommand, String>> {
+    let position = args.iter().position(|arg| arg == FLAG)?;
+    let mut rest = args[position + 1..].iter();
+    let command = rest.next().map(String::as_str);
+
+    let mut portfolio_id = None;
+    let mut csv = None;
+    while let Some(arg) = rest.next() {
+        let mut value = |name: &str| {
+            rest.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        let parsed = match arg.as_str() {
+            "--portfolio" => value(arg).and_then(|id| {
+                id.parse()
+                    .map(|id| portfolio_id = Some(id))
+                    .map_err(|_| format!("Invalid portfolio id: {}", id))
+            }),
+            "--csv" => value(arg).map(|path| csv = Some(PathBuf::from(path))),
+            other => Err(format!("Unknown option: {}", other)),
+        };
+        if let Err(e) = parsed {
+            return Some(Err(e));
+        }
+    }
+
+    Some(match (command, csv) {
+        (Some("sync"), None) => Ok(HeadlessCommand::Sync { portfolio_id }),
+        (Some("pipeline"), None) => Ok(HeadlessCommand::Pipeline { portfolio_id }),
+        (Some("export"), Some(csv)) => Ok(HeadlessCommand::Export { csv, portfolio_id }),
+        (Some("export"), None) => Err("export needs --csv PATH".to_string()),
+        (Some("sync" | "pipeline"), Some(_)) => Err("--csv only applies to export".to_string()),
+        (Some(other), _) => Err(format!("Unknown command: {}", other)),
+        (None, _) => Err("No command given".to_string()),
+    })
+}
+
+/// Print the outcome as JSON and return the exit code
+fn print_outcome(command: Option<&str>, outcome: Result<Value, String>) -> i32 {
+    let (line, code) = match outcome {
+        Ok(result) => (
+            json!({ "ok": true, "command": command, "result": result }),
+            0,
+        ),
+        Err(error) => (
+            json!({ "ok": false, "command": command, "error": error }),
+            1,
+        ),
+    };
+    println!("{}", line);
+    code
+}
+
+/// Run `command` headless and exit the process.
+pub fn run(mut context: Context<Wry>, command: Result<HeadlessCommand, String>) -> ! {
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            print_outcome(None, Err(e));
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    // No window: the config's windows are only for the GUI
+    context.config_mut().app.windows.clear();
+
+    let app = tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .setup(move |app| {
+            #[cfg(target_os = "macos")]
+            app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let name = command.name();
+                let outcome = execute(&app_handle, command).await;
+                if let Some(engine) = app_handle.try_state::<Arc<PythonEngine>>() {
+                    engine.shutdown().await;
+                }
+                let code = print_outcome(Some(name), outcome);
+                app_handle.exit(code);
+            });
+            Ok(())
+        })
+        .build(context)
+        .expect("error while building tauri application");
+    app.run(|app_handle, event| {
+        if let tauri::RunEvent::Exit = event {
+            session_vault::close(app_handle);
+        }
+    });
+    std::process::exit(0)
+}
+
+/// Start the primary engine on the data dir, as the GUI does without the
+/// optional worker
+fn start_engine(app_handle: &AppHandle) -> Result<(), String> {
+    let data_dir = store::data_dir(app_handle)?;
+    logging::init(&data_dir);
+    instance_lock::acquire(&data_dir).map_err(|e| format!("{} (is Portfolio Prism open?)", e))?;
+    session_vault::sweep_stale();
+    if let Err(e) = db_recovery::check_and_recover(&data_dir) {
+        tracing::error!("Database recovery check failed: {}", e);
+    }
+
+    app_handle.manage(HiveCache::new(&data_dir));
+    app_handle.manage(PipelineRun::default());
+
+    let engine = Arc::new(PythonEngine::new());
+    let settings = app_settings::load_general(&data_dir);
+    app_settings::apply_timeouts(&engine, &settings.timeouts);
+    proxy::configure(&settings.proxy);
+    let worker = Arc::new(PythonEngine::new());
+    worker.transition(
+        EngineState::Dead,
+        Some("Not used in headless mode".to_string()),
+    );
+    app_handle.manage(EnginePool::new(engine.clone(), worker));
+    app_handle.manage(engine.clone());
+
+    crate::spawn_engine(app_handle, engine, EngineRole::Primary, &data_dir)
+}
+
+async fn execute(app_handle: &AppHandle, command: HeadlessCommand) -> Result<Value, String> {
+    start_engine(app_handle)?;
+    match command {
+        HeadlessCommand::Sync { portfolio_id } => {
+            let result = scheduler::run_sync_now(app_handle, portfolio_id).await?;
+            Ok(json!(result))
+        }
+        HeadlessCommand::Pipeline { portfolio_id } => {
+            let result = scheduler::run_pipeline_now(app_handle, portfolio_id).await?;
+            if !result.success {
+                return Err(result.errors.join("; "));
+            }
+            Ok(json!(result))
+        }
+        HeadlessCommand::Export { csv, portfolio_id } => {
+            let portfolio_id = match portfolio_id {
+                Some(portfolio_id) => portfolio_id,
+                None => {
+                    scheduler::sync_status(&store::data_dir(app_handle)?)?
+                        .settings
+                        .portfolio_id
+                }
+            };
+            sandbox::reject(portfolio_id, "exported headless")?;
+            let engine = app_handle.state::<Arc<PythonEngine>>().inner().clone();
+            if !engine.is_connected().await {
+                return Err(engine.unavailable().into());
+            }
+            let response = portfolio::fetch_positions(app_handle, &engine, portfolio_id).await?;
+
+            let mut writer = csv::Writer::from_path(&csv)
+                .map_err(|e| format!("Failed to create {}: {}", csv.display(), e))?;
+            for position in &response.positions {
+                writer
+                    .serialize(position)
+                    .map_err(|e| format!("Failed to write {}: {}", csv.display(), e))?;
+            }
+            writer
+                .flush()
+                .map_err(|e| format!("Failed to write {}: {}", csv.display(), e))?;
+            Ok(json!({
+                "path": csv.display().to_string(),
+                "portfolioId": portfolio_id,
+                "rows": response.positions.len(),
+                "totalValue": response.total_value,
+            }))
+        }
+    }
+}
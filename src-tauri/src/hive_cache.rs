@@ -0,0 +1,282 @@
+//! Hive Decomposition Cache
+//!
+//! Pulls community-sourced ETF decompositions from the Hive and keeps them in
+//! `hive_cache/` in the app data dir with a fetch time and a version (the
+//! newest `last_updated` among the rows). The engine asks for them through a
+//! `hive_decomposition_request` event when its own adapters fail, which turns
+//! many "requires manual upload" dead-ends into a look-through result.
+//!
+//! Fresh entries are served without touching the network. When the Hive is
+//! unreachable, a stale entry is served (flagged `stale`) rather than nothing.
+
+use crate::proxy;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Cache directory inside the app data dir
+pub(crate) const CACHE_DIR: &str = "hive_cache";
+
+/// How long a cached decomposition is served without refetching
+const CACHE_TTL_HOURS: i64 = 7 * 24;
+
+/// Entries older than this are evicted during maintenance; younger stale
+/// entries are kept as the offline fallback
+const EVICT_AFTER_HOURS: i64 = 8 * CACHE_TTL_HOURS;
+
+/// Timeout for a single Hive request
+const FETCH_TIMEOUT_SECS: u64 = 15;
+
+/// One row of `get_etf_holdings_rpc`
+#[derive(Debug, Deserialize)]
+struct HiveRow {
+    holding_isin: String,
+    weight: f64,
+    #[serde(default)]
+    confidence_score: Option<f64>,
+    #[serde(default)]
+    last_updated: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiveHolding {
+    pub isin: String,
+    pub weight: f64,
+    pub confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiveDecomposition {
+    pub etf_isin: String,
+    /// Newest `last_updated` date among the community rows
+    pub version: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub holdings: Vec<HiveHolding>,
+    /// Served from cache after a failed refresh
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl HiveDecomposition {
+    fn is_fresh(&self) -> bool {
+        Utc::now() - self.fetched_at < chrono::Duration::hours(CACHE_TTL_HOURS)
+    }
+}
+
+/// Session counters; reset on every launch
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheCounters {
+    hits: u64,
+    misses: u64,
+    remote_fetches: u64,
+    stale_served: u64,
+    failures: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiveCacheStats {
+    pub entries: usize,
+    pub fresh_entries: usize,
+    pub total_bytes: u64,
+    pub oldest_fetch: Option<DateTime<Utc>>,
+    pub newest_fetch: Option<DateTime<Utc>>,
+    pub ttl_hours: i64,
+    pub hits: u64,
+    pub misses: u64,
+    pub remote_fetches: u64,
+    pub stale_served: u64,
+    pub failures: u64,
+}
+
+pub struct HiveCache {
+    dir: PathBuf,
+    counters: Mutex<CacheCounters>,
+}
+
+impl HiveCache {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join(CACHE_DIR),
+            counters: Mutex::new(CacheCounters::default()),
+        }
+    }
+
+    fn entry_path(&self, isin: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", isin))
+    }
+
+    fn count(&self, update: impl FnOnce(&mut CacheCounters)) {
+        if let Ok(mut counters) = self.counters.lock() {
+            update(&mut counters);
+        }
+    }
+
+    /// Decomposition for `isin`, from cache when fresh, otherwise from the Hive.
+    pub async fn get(&self, isin: &str, force: bool) -> Result<HiveDecomposition, String> {
+        // The ISIN becomes a file name; never let the engine pick a path
+        if isin.len() != 12 || !isin.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid ISIN: {}", isin));
+        }
+        let path = self.entry_path(isin);
+        let cached: Option<HiveDecomposition> = store::read_json(&path)?;
+
+        if let Some(entry) = cached.as_ref().filter(|entry| !force && entry.is_fresh()) {
+            self.count(|c| c.hits += 1);
+            return Ok(entry.clone());
+        }
+        self.count(|c| c.misses += 1);
+
+        match self.fetch_remote(isin).await {
+            Ok(decomposition) => {
+                self.count(|c| c.remote_fetches += 1);
+                store::write_json(&path, &decomposition)?;
+                Ok(decomposition)
+            }
+            Err(e) => match cached {
+                Some(mut entry) => {
+                    tracing::warn!("Hive refresh for {} failed, serving stale cache: {}", isin, e);
+                    self.count(|c| c.stale_served += 1);
+                    entry.stale = true;
+                    Ok(entry)
+                }
+                None => {
+                    self.count(|c| c.failures += 1);
+                    Err(e)
+                }
+            },
+        }
+    }
+
+    async fn fetch_remote(&self, isin: &str) -> Result<HiveDecomposition, String> {
+        let url = std::env::var("SUPABASE_URL").unwrap_or_default();
+        let key = std::env::var("SUPABASE_ANON_KEY").unwrap_or_default();
+        if url.is_empty() || key.is_empty() {
+            return Err("Hive is not configured".to_string());
+        }
+
+        let rows: Vec<HiveRow> = proxy::client()
+            .post(format!(
+                "{}/rest/v1/rpc/get_etf_holdings_rpc",
+                url.trim_end_matches('/')
+            ))
+            .header("apikey", &key)
+            .bearer_auth(&key)
+            .json(&json!({ "p_etf_isin": isin }))
+            .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Hive request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Hive response: {}", e))?;
+
+        if rows.is_empty() {
+            return Err(format!("No community decomposition for {}", isin));
+        }
+
+        Ok(HiveDecomposition {
+            etf_isin: isin.to_string(),
+            version: rows.iter().filter_map(|row| row.last_updated.clone()).max(),
+            fetched_at: Utc::now(),
+            holdings: rows
+                .into_iter()
+                .map(|row| HiveHolding {
+                    isin: row.holding_isin,
+                    weight: row.weight,
+                    confidence: row.confidence_score,
+                })
+                .collect(),
+            stale: false,
+        })
+    }
+
+    /// Delete entries too old to be useful even as a stale fallback.
+    pub fn evict_expired(&self) -> Result<usize, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read Hive cache: {}", e)),
+        };
+
+        let cutoff = Utc::now() - chrono::Duration::hours(EVICT_AFTER_HOURS);
+        let mut evicted = 0;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let expired = match store::read_json::<HiveDecomposition>(&path) {
+                Ok(Some(entry)) => entry.fetched_at < cutoff,
+                // Unreadable entries would be refetched anyway
+                _ => true,
+            };
+            if expired && std::fs::remove_file(&path).is_ok() {
+                evicted += 1;
+            }
+        }
+        Ok(evicted)
+    }
+
+    pub fn stats(&self) -> Result<HiveCacheStats, String> {
+        let counters = self
+            .counters
+            .lock()
+            .map(|counters| counters.clone())
+            .unwrap_or_default();
+
+        let mut stats = HiveCacheStats {
+            entries: 0,
+            fresh_entries: 0,
+            total_bytes: 0,
+            oldest_fetch: None,
+            newest_fetch: None,
+            ttl_hours: CACHE_TTL_HOURS,
+            hits: counters.hits,
+            misses: counters.misses,
+            remote_fetches: counters.remote_fetches,
+            stale_served: counters.stale_served,
+            failures: counters.failures,
+        };
+
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(stats),
+            Err(e) => return Err(format!("Failed to read Hive cache: {}", e)),
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(Some(entry)) = store::read_json::<HiveDecomposition>(&path) else {
+                continue;
+            };
+
+            stats.entries += 1;
+            if entry.is_fresh() {
+                stats.fresh_entries += 1;
+            }
+            stats.total_bytes += std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            stats.oldest_fetch = Some(
+                stats
+                    .oldest_fetch
+                    .map_or(entry.fetched_at, |oldest| oldest.min(entry.fetched_at)),
+            );
+            stats.newest_fetch = Some(
+                stats
+                    .newest_fetch
+                    .map_or(entry.fetched_at, |newest| newest.max(entry.fetched_at)),
+            );
+        }
+
+        Ok(stats)
+    }
+}
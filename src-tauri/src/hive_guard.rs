@@ -0,0 +1,269 @@
+//! Hive Contribution Anonymization
+//!
+//! Every contribution the engine wants to upload to the Hive is routed
+//! through the shell first: the engine announces it with a
+//! `hive_contribution_request` event and uploads only what comes back with
+//! `hive_contribution_approved`. The guard enforces the privacy promise in
+//! Rust instead of trusting Python:
+//!
+//! - each contribution kind (`holdings`, `assets`, `asset`, `listing`,
+//!   `mapping`, `alias`) has its own allow-list; unknown kinds are blocked
+//! - fields that could identify a user or their wealth (quantities, values,
+//!   account identifiers) reject the whole contribution
+//! - any other field outside the allow-list is stripped
+//! - every decision is written to `hive_audit.ndjson` with a digest of the
+//!   schema that was actually sent
+//!
+//! Holdings contributions carry weights only, so nothing about position
+//! sizes leaves the machine; their `currency` is the fund's own and is passed
+//! through as sent.
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Audit log file name inside the app data dir
+pub(crate) const AUDIT_FILE: &str = "hive_audit.ndjson";
+
+/// Guard configuration file name inside the app data dir
+pub(crate) const CONFIG_FILE: &str = "hive_privacy.json";
+
+/// Top-level fields a holdings contribution may carry
+const ALLOWED_FIELDS: &[&str] = &["etfIsin", "etfName", "source", "asOfDate", "currency", "holdings"];
+
+/// Fields each constituent row may carry
+const ALLOWED_HOLDING_FIELDS: &[&str] = &[
+    "isin", "name", "ticker", "weight", "sector", "geography", "assetClass", "currency",
+];
+
+/// Fields of each row of an `assets` contribution
+const ALLOWED_ASSET_FIELDS: &[&str] =
+    &["isin", "name", "assetClass", "baseCurrency", "enrichmentStatus"];
+
+/// Allow-lists of the single-record contribution kinds. `contributorHash`
+/// is left out on purpose: it would link contributions to one user.
+const RECORD_SCHEMAS: &[(&str, &[&str])] = &[
+    (
+        "asset",
+        &["isin", "ticker", "exchange", "name", "assetClass", "baseCurrency", "tradingCurrency"],
+    ),
+    ("listing", &["isin", "ticker", "exchange", "currency"]),
+    ("mapping", &["isin", "provider", "providerId"]),
+    (
+        "alias",
+        &[
+            "alias", "isin", "aliasType", "language", "source", "confidence", "currency",
+            "exchange", "currencySource",
+        ],
+    ),
+];
+
+/// Field name fragments that block a contribution outright
+const FORBIDDEN_FRAGMENTS: &[&str] = &[
+    "quantity", "shares", "value", "amount", "price", "cost", "pnl", "account", "iban", "phone",
+    "email", "user", "session", "portfolio",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HivePrivacyConfig {
+    /// ISO 4217 code the user contributes in. Contributions carry weights
+    /// and no amounts, so nothing is converted or restamped with it.
+    pub contribution_currency: String,
+}
+
+impl Default for HivePrivacyConfig {
+    fn default() -> Self {
+        Self {
+            contribution_currency: "EUR".to_string(),
+        }
+    }
+}
+
+/// Outcome of validating one contribution
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardDecision {
+    pub allowed: bool,
+    pub schema_digest: String,
+    pub stripped_fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Sanitized contribution; only present when allowed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contribution: Option<Value>,
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONFIG_FILE)
+}
+
+pub fn load_config(data_dir: &Path) -> Result<HivePrivacyConfig, String> {
+    Ok(store::read_json(&config_path(data_dir))?.unwrap_or_default())
+}
+
+pub fn set_contribution_currency(data_dir: &Path, currency: &str) -> Result<HivePrivacyConfig, String> {
+    let currency = currency.trim().to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Invalid currency code: {}", currency));
+    }
+
+    let config = HivePrivacyConfig {
+        contribution_currency: currency,
+    };
+    store::write_json(&config_path(data_dir), &config)?;
+    Ok(config)
+}
+
+fn forbidden(field: &str) -> bool {
+    let field = field.to_lowercase();
+    FORBIDDEN_FRAGMENTS.iter().any(|fragment| field.contains(fragment))
+}
+
+/// Keep allowed fields of `object`, recording stripped ones under `path`.
+fn filter_object(
+    object: &Map<String, Value>,
+    allowed: &[&str],
+    path: &str,
+    stripped: &mut BTreeSet<String>,
+) -> Result<Map<String, Value>, String> {
+    let mut kept = Map::new();
+    for (key, value) in object {
+        let field_path = format!("{}{}", path, key);
+        if forbidden(key) {
+            return Err(format!("Contribution contains forbidden field '{}'", field_path));
+        }
+        if allowed.contains(&key.as_str()) {
+            kept.insert(key.clone(), value.clone());
+        } else {
+            stripped.insert(field_path);
+        }
+    }
+    Ok(kept)
+}
+
+fn schema_digest(contribution: &Value) -> String {
+    let mut fields = BTreeSet::new();
+    if let Some(object) = contribution.as_object() {
+        for (key, value) in object {
+            fields.insert(key.clone());
+            if let Some(rows) = value.as_array() {
+                for row in rows.iter().filter_map(|row| row.as_object()) {
+                    fields.extend(row.keys().map(|field| format!("{}[].{}", key, field)));
+                }
+            }
+        }
+    }
+
+    let joined = fields.into_iter().collect::<Vec<_>>().join(",");
+    format!("{:x}", Sha256::digest(joined.as_bytes()))
+}
+
+/// Keep the allowed fields of each row in `object[field]`.
+fn filter_rows(
+    object: &mut Map<String, Value>,
+    field: &str,
+    allowed: &[&str],
+    stripped: &mut BTreeSet<String>,
+) -> Result<(), String> {
+    let rows = object
+        .get(field)
+        .and_then(|rows| rows.as_array())
+        .ok_or_else(|| format!("Contribution has no {} list", field))?;
+
+    let mut clean_rows = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        let row = row
+            .as_object()
+            .ok_or_else(|| format!("{}[{}] must be an object", field, index))?;
+        let clean = filter_object(row, allowed, &format!("{}[].", field), stripped)?;
+
+        if field == "holdings" {
+            let weight = clean.get("weight").and_then(|weight| weight.as_f64());
+            if !weight.is_some_and(|weight| (0.0..=100.0).contains(&weight)) {
+                return Err(format!(
+                    "holdings[{}].weight must be a number between 0 and 100",
+                    index
+                ));
+            }
+        }
+        clean_rows.push(Value::Object(clean));
+    }
+
+    object.insert(field.to_string(), Value::Array(clean_rows));
+    Ok(())
+}
+
+fn sanitize(
+    kind: &str,
+    contribution: &Value,
+    stripped: &mut BTreeSet<String>,
+) -> Result<Value, String> {
+    let object = contribution
+        .as_object()
+        .ok_or_else(|| "Contribution must be a JSON object".to_string())?;
+
+    let sanitized = match kind {
+        "holdings" => {
+            // Weights carry no amounts, so the fund currency passes through unchanged
+            let mut sanitized = filter_object(object, ALLOWED_FIELDS, "", stripped)?;
+            filter_rows(&mut sanitized, "holdings", ALLOWED_HOLDING_FIELDS, stripped)?;
+            sanitized
+        }
+        "assets" => {
+            let mut sanitized = filter_object(object, &["assets"], "", stripped)?;
+            filter_rows(&mut sanitized, "assets", ALLOWED_ASSET_FIELDS, stripped)?;
+            sanitized
+        }
+        kind => {
+            let (_, allowed) = RECORD_SCHEMAS
+                .iter()
+                .find(|(name, _)| *name == kind)
+                .ok_or_else(|| format!("Unknown contribution kind '{}'", kind))?;
+            filter_object(object, allowed, "", stripped)?
+        }
+    };
+    Ok(Value::Object(sanitized))
+}
+
+/// Validate a contribution of `kind`, audit the decision and return it.
+pub fn review(data_dir: &Path, kind: &str, contribution: &Value) -> GuardDecision {
+    let mut stripped = BTreeSet::new();
+    let result = sanitize(kind, contribution, &mut stripped);
+
+    let decision = match result {
+        Ok(sanitized) => GuardDecision {
+            allowed: true,
+            schema_digest: schema_digest(&sanitized),
+            stripped_fields: stripped.into_iter().collect(),
+            reason: None,
+            contribution: Some(sanitized),
+        },
+        Err(reason) => GuardDecision {
+            allowed: false,
+            schema_digest: schema_digest(contribution),
+            stripped_fields: stripped.into_iter().collect(),
+            reason: Some(reason),
+            contribution: None,
+        },
+    };
+
+    let audit = serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "kind": kind,
+        "etfIsin": contribution["etfIsin"],
+        "isin": contribution["isin"],
+        "allowed": decision.allowed,
+        "schemaDigest": decision.schema_digest,
+        "strippedFields": decision.stripped_fields,
+        "reason": decision.reason,
+    });
+    if let Err(e) = store::append_ndjson(&data_dir.join(AUDIT_FILE), &audit) {
+        tracing::warn!("Failed to write hive audit log: {}", e);
+    }
+
+    decision
+}
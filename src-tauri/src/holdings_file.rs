@@ -0,0 +1,458 @@
+//! Holdings Spreadsheets
+//!
+//! Fund providers (Amundi and others) publish constituent lists as Excel
+//! workbooks with a few lines of fund information above the table. The
+//! engine only parses text uploads, so spreadsheets are converted here:
+//!
+//! 1. the first sheet is read
+//! 2. the header row is the first row naming a weight column and an ISIN,
+//!    name or ticker column
+//! 3. rows below it become normalized holdings until the first empty row or
+//!    a totals row (footnotes usually follow after a gap)
+//!
+//! Weights are forwarded in percent; a column of fractions (weights summing to
+//! about one) is scaled up.
+//!
+//! Large CSV files go through the same conversion so their rows can be sent
+//! to the engine in batches. `validate` runs the detection over CSV and Excel
+//! files without converting anything, so a bad upload fails with a
+//! column-level report before the engine sees it.
+
+use crate::broker_import::{self, is_isin, parse_number, SkippedRow};
+use crate::commands::pipeline::ManualHoldingDraft;
+use calamine::{open_workbook_auto, Data, Reader};
+use serde::Serialize;
+use std::path::Path;
+
+/// Extensions converted in the shell
+pub const SPREADSHEET_EXTENSIONS: &[&str] = &["xlsx", "xls"];
+
+/// Rows searched for the header
+const MAX_HEADER_SCAN_ROWS: usize = 30;
+
+/// Names of a totals row below the table
+const TOTAL_LABELS: &[&str] = &["total", "totals", "summe", "gesamt", "total général"];
+
+/// Largest weight sum still read as fractions rather than percent
+const FRACTION_SUM_LIMIT: f64 = 1.5;
+
+/// Weight totals (percent) accepted without a warning
+const WEIGHT_TOTAL_RANGE: (f64, f64) = (90.0, 110.0);
+
+/// Sample values reported per column
+const SAMPLE_VALUES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColumnRole {
+    Isin,
+    Name,
+    Ticker,
+    Weight,
+}
+
+/// Role of a header cell; checked in this order so e.g. "ISIN Code" is not
+/// taken for a ticker
+fn role_of(header: &str) -> Option<ColumnRole> {
+    let header = header.trim().to_lowercase();
+    let any = |fragments: &[&str]| fragments.iter().any(|f| header.contains(f));
+    if header.is_empty() {
+        None
+    } else if any(&["isin"]) {
+        Some(ColumnRole::Isin)
+    } else if any(&["weight", "gewicht", "poids", "anteil", "% of", "%"]) {
+        Some(ColumnRole::Weight)
+    } else if any(&["ticker", "symbol", "bloomberg"]) {
+        Some(ColumnRole::Ticker)
+    } else if any(&[
+        "name",
+        "bezeichnung",
+        "description",
+        "security",
+        "instrument",
+        "holding",
+        "issuer",
+        "emittent",
+        "wertpapier",
+        "libellé",
+    ]) {
+        Some(ColumnRole::Name)
+    } else {
+        None
+    }
+}
+
+/// Column index per role; the first matching column wins
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColumnMapping {
+    pub isin: Option<usize>,
+    pub name: Option<usize>,
+    pub ticker: Option<usize>,
+    pub weight: Option<usize>,
+}
+
+impl ColumnMapping {
+    pub fn detect(header: &[String]) -> Self {
+        let mut mapping = Self::default();
+        for (index, cell) in header.iter().enumerate() {
+            let slot = match role_of(cell) {
+                Some(ColumnRole::Isin) => &mut mapping.isin,
+                Some(ColumnRole::Name) => &mut mapping.name,
+                Some(ColumnRole::Ticker) => &mut mapping.ticker,
+                Some(ColumnRole::Weight) => &mut mapping.weight,
+                None => continue,
+            };
+            slot.get_or_insert(index);
+        }
+        mapping
+    }
+
+    /// Enough columns to identify holdings and their weights
+    pub fn is_usable(&self) -> bool {
+        self.weight.is_some()
+            && (self.isin.is_some() || self.name.is_some() || self.ticker.is_some())
+    }
+}
+
+/// A holdings file converted in the shell
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingsSheet {
+    /// Sheet name; `None` for CSV files
+    pub sheet: Option<String>,
+    /// 1-based row of the header in the sheet
+    pub header_row: usize,
+    pub columns: Vec<String>,
+    pub holdings: Vec<ManualHoldingDraft>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Whether `path` is an Excel workbook, which the engine cannot read
+pub fn is_spreadsheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SPREADSHEET_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        cell => cell.to_string().trim().to_string(),
+    }
+}
+
+/// Index of the header row and its column mapping
+pub fn detect_header(rows: &[Vec<String>]) -> Option<(usize, ColumnMapping)> {
+    rows.iter()
+        .take(MAX_HEADER_SCAN_ROWS)
+        .enumerate()
+        .map(|(index, row)| (index, ColumnMapping::detect(row)))
+        .find(|(_, mapping)| mapping.is_usable())
+}
+
+/// Parse a weight cell: a plain number, optionally with a `%` sign
+fn parse_weight(value: &str) -> Option<f64> {
+    parse_number(value.trim().trim_end_matches('%')).filter(|weight| *weight >= 0.0)
+}
+
+/// Holdings below the header row; `rows` are the whole sheet.
+pub fn normalize_rows(
+    rows: &[Vec<String>],
+    header_index: usize,
+    mapping: &ColumnMapping,
+) -> (Vec<ManualHoldingDraft>, Vec<SkippedRow>) {
+    let cell = |row: &[String], index: Option<usize>| -> String {
+        index
+            .and_then(|index| row.get(index))
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let mut holdings = vec![];
+    let mut skipped = vec![];
+    for (index, row) in rows.iter().enumerate().skip(header_index + 1) {
+        let line = index + 1;
+        if row.iter().all(|cell| cell.is_empty()) {
+            if holdings.is_empty() {
+                continue;
+            }
+            break;
+        }
+        let isin = cell(row, mapping.isin).to_uppercase();
+        let name = cell(row, mapping.name);
+        // A totals row closes the table
+        if isin.is_empty() && TOTAL_LABELS.contains(&name.to_lowercase().as_str()) {
+            break;
+        }
+        let ticker = Some(cell(row, mapping.ticker)).filter(|ticker| !ticker.is_empty());
+        let Some(weight) = parse_weight(&cell(row, mapping.weight)) else {
+            skipped.push(SkippedRow {
+                line,
+                reason: "Missing or invalid weight".to_string(),
+            });
+            continue;
+        };
+        if !isin.is_empty() && !is_isin(&isin) {
+            skipped.push(SkippedRow {
+                line,
+                reason: "Invalid ISIN".to_string(),
+            });
+            continue;
+        }
+        if isin.is_empty() && name.is_empty() && ticker.is_none() {
+            skipped.push(SkippedRow {
+                line,
+                reason: "No ISIN, name or ticker".to_string(),
+            });
+            continue;
+        }
+        holdings.push(ManualHoldingDraft {
+            isin,
+            name,
+            ticker,
+            weight,
+        });
+    }
+
+    if weights_are_fractions(&holdings) {
+        for holding in &mut holdings {
+            holding.weight *= 100.0;
+        }
+    }
+    (holdings, skipped)
+}
+
+fn weights_are_fractions(holdings: &[ManualHoldingDraft]) -> bool {
+    let total: f64 = holdings.iter().map(|holding| holding.weight).sum();
+    total > 0.0 && total <= FRACTION_SUM_LIMIT
+}
+
+/// Cells of the first sheet as text, with the sheet name
+pub fn read_first_sheet(path: &Path) -> Result<(String, Vec<Vec<String>>), String> {
+    let mut workbook = open_workbook_auto(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let sheet = workbook
+        .sheet_names()
+        .first()
+        .cloned()
+        .ok_or_else(|| "The workbook has no sheets".to_string())?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| "The workbook has no sheets".to_string())?
+        .map_err(|e| format!("Failed to read sheet {}: {}", sheet, e))?;
+    let rows = range
+        .rows()
+        .map(|row| row.iter().map(cell_text).collect())
+        .collect();
+    Ok((sheet, rows))
+}
+
+/// Convert a holdings file (first sheet of a workbook, or a CSV) to
+/// holdings.
+pub fn convert(path: &Path) -> Result<HoldingsSheet, String> {
+    let (_, sheet, rows) = read_rows(path)?;
+    let source = sheet.as_ref().map_or_else(
+        || "the file".to_string(),
+        |sheet| format!("sheet {}", sheet),
+    );
+    let (header_index, mapping) = detect_header(&rows).ok_or_else(|| {
+        format!(
+            "No header row with a weight and an ISIN, name or ticker column in the first {} rows \
+             of {}",
+            MAX_HEADER_SCAN_ROWS, source
+        )
+    })?;
+    let (holdings, skipped) = normalize_rows(&rows, header_index, &mapping);
+    if holdings.is_empty() {
+        return Err(format!("No holdings below the header row of {}", source));
+    }
+
+    Ok(HoldingsSheet {
+        sheet,
+        header_row: header_index + 1,
+        columns: rows[header_index].clone(),
+        holdings,
+        skipped,
+    })
+}
+
+// =============================================================================
+// Validation
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedColumn {
+    pub index: usize,
+    pub name: String,
+    /// Role the column would be used for, if any
+    pub role: Option<ColumnRole>,
+    /// First values below the header
+    pub samples: Vec<String>,
+}
+
+/// Column names suggested for each role
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingSuggestion {
+    pub isin: Option<String>,
+    pub name: Option<String>,
+    pub ticker: Option<String>,
+    pub weight: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingsFileReport {
+    /// `csv`, `xlsx` or `xls`
+    pub format: String,
+    pub sheet: Option<String>,
+    /// 1-based header row; `None` when no header was recognized
+    pub header_row: Option<usize>,
+    pub columns: Vec<DetectedColumn>,
+    /// Data rows below the header
+    pub row_count: usize,
+    pub valid_rows: usize,
+    pub unparseable_rows: Vec<SkippedRow>,
+    pub mapping: MappingSuggestion,
+    /// Sum of the readable weights, in percent
+    pub weight_total: f64,
+    pub weights_are_fractions: bool,
+    /// Actionable problems and warnings
+    pub issues: Vec<String>,
+    /// Whether an upload would produce any holdings
+    pub valid: bool,
+}
+
+/// Rows of a CSV or Excel file with the format and (for Excel) sheet name
+fn read_rows(path: &Path) -> Result<(String, Option<String>, Vec<Vec<String>>), String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    if is_spreadsheet(path) {
+        let (sheet, rows) = read_first_sheet(path)?;
+        return Ok((extension, Some(sheet), rows));
+    }
+    if extension != "csv" {
+        return Err(format!(
+            "Only CSV and Excel files can be checked before upload; .{} files are parsed by \
+             the engine",
+            extension
+        ));
+    }
+    let text = broker_import::read_text(path)?;
+    Ok((extension, None, broker_import::csv_rows(&text)?))
+}
+
+/// Check a holdings file and describe how it would be read.
+pub fn validate(path: &Path) -> Result<HoldingsFileReport, String> {
+    let (format, sheet, rows) = read_rows(path)?;
+    let Some((header_index, mapping)) = detect_header(&rows) else {
+        let first = rows
+            .iter()
+            .find(|row| row.iter().any(|cell| !cell.is_empty()));
+        return Ok(HoldingsFileReport {
+            format,
+            sheet,
+            header_row: None,
+            columns: first
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, name)| DetectedColumn {
+                    index,
+                    name: name.clone(),
+                    role: role_of(name),
+                    samples: vec![],
+                })
+                .collect(),
+            row_count: rows.len(),
+            valid_rows: 0,
+            unparseable_rows: vec![],
+            mapping: MappingSuggestion::default(),
+            weight_total: 0.0,
+            weights_are_fractions: false,
+            issues: vec![format!(
+                "No header row found in the first {} rows. Name the columns, e.g. \"ISIN\", \
+                 \"Name\" and \"Weight (%)\".",
+                MAX_HEADER_SCAN_ROWS
+            )],
+            valid: false,
+        });
+    };
+
+    let header = &rows[header_index];
+    let body: Vec<&Vec<String>> = rows[header_index + 1..]
+        .iter()
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+    let columns: Vec<DetectedColumn> = header
+        .iter()
+        .enumerate()
+        .map(|(index, name)| DetectedColumn {
+            index,
+            name: name.clone(),
+            role: [mapping.isin, mapping.name, mapping.ticker, mapping.weight]
+                .contains(&Some(index))
+                .then(|| role_of(name))
+                .flatten(),
+            samples: body
+                .iter()
+                .filter_map(|row| row.get(index).filter(|cell| !cell.is_empty()).cloned())
+                .take(SAMPLE_VALUES)
+                .collect(),
+        })
+        .collect();
+    let column_name = |index: Option<usize>| index.and_then(|index| header.get(index)).cloned();
+
+    let (holdings, unparseable_rows) = normalize_rows(&rows, header_index, &mapping);
+    let weights_are_fractions = weights_are_fractions(&holdings);
+    let weight_total: f64 = holdings.iter().map(|holding| holding.weight).sum::<f64>()
+        * if weights_are_fractions { 100.0 } else { 1.0 };
+
+    let mut issues = vec![];
+    if holdings.is_empty() {
+        issues.push("No readable holdings below the header row".to_string());
+    }
+    if !unparseable_rows.is_empty() {
+        issues.push(format!(
+            "{} rows cannot be read and would be skipped",
+            unparseable_rows.len()
+        ));
+    }
+    if mapping.isin.is_none() {
+        issues.push(
+            "No ISIN column; holdings will be matched by name or ticker, which is less reliable"
+                .to_string(),
+        );
+    }
+    let (low, high) = WEIGHT_TOTAL_RANGE;
+    if !holdings.is_empty() && !(low..=high).contains(&weight_total) {
+        issues.push(format!(
+            "Weights add up to {:.1}%; a complete constituent list adds up to about 100%",
+            weight_total
+        ));
+    }
+
+    Ok(HoldingsFileReport {
+        format,
+        sheet,
+        header_row: Some(header_index + 1),
+        columns,
+        row_count: body.len(),
+        valid_rows: holdings.len(),
+        mapping: MappingSuggestion {
+            isin: column_name(mapping.isin),
+            name: column_name(mapping.name),
+            ticker: column_name(mapping.ticker),
+            weight: column_name(mapping.weight),
+        },
+        weight_total,
+        weights_are_fractions,
+        valid: !holdings.is_empty(),
+        unparseable_rows,
+        issues,
+    })
+}
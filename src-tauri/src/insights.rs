@@ -0,0 +1,359 @@
+//! Monthly Behavioral Insights
+//!
+//! Opt-in (`monthly_insights` flag) feedback on investing habits, computed
+//! entirely in the shell from the transaction ledger, the value history and
+//! current weights. Nothing leaves the device.
+//!
+//! - contribution consistency: months with net purchases over the trailing
+//!   twelve months, and how much the monthly amount varies
+//! - panic sells: sales made after the instrument's traded price, or the
+//!   portfolio value, fell more than `PANIC_DROP_PERCENT` within
+//!   `PANIC_LOOKBACK_DAYS`
+//! - concentration creep: change in largest / top-five weights since the
+//!   previous month, tracked in `insights/<id>_concentration.json`
+//! - fee trend: fees of the month against the trailing six-month average
+
+use crate::commands::portfolio::{HistoryPoint, Transaction};
+use crate::store;
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Drop (in percent) that qualifies a following sale as a panic sell
+const PANIC_DROP_PERCENT: f64 = 10.0;
+
+/// Window before a sale in which the drop is measured
+const PANIC_LOOKBACK_DAYS: i64 = 30;
+
+/// Months considered for contribution consistency
+const CONSISTENCY_MONTHS: u32 = 12;
+
+/// Months averaged for the fee trend baseline
+const FEE_BASELINE_MONTHS: u32 = 6;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionConsistency {
+    pub months_considered: u32,
+    pub months_with_contributions: u32,
+    /// Share of months with net purchases (0-1)
+    pub consistency: f64,
+    pub average_monthly_contribution: f64,
+    /// Coefficient of variation of monthly net purchases; lower is steadier
+    pub variation: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicSell {
+    pub isin: String,
+    pub date: String,
+    pub amount: f64,
+    /// Largest drop seen before the sale, in percent
+    pub drop_percent: f64,
+    /// `instrument` or `portfolio`
+    pub basis: &'static str,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcentrationPoint {
+    pub largest_weight: f64,
+    pub top_five_weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcentrationCreep {
+    pub current: ConcentrationPoint,
+    pub previous: Option<ConcentrationPoint>,
+    /// Percentage-point change of the largest weight since the previous month
+    pub largest_change: Option<f64>,
+    pub top_five_change: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTrend {
+    pub month_fees: f64,
+    pub baseline_average: f64,
+    /// Monthly fee totals, oldest first
+    pub monthly: Vec<(String, f64)>,
+    pub rising: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyInsights {
+    pub portfolio_id: u32,
+    /// `YYYY-MM`
+    pub month: String,
+    pub contributions: ContributionConsistency,
+    pub panic_sells: Vec<PanicSell>,
+    pub concentration: ConcentrationCreep,
+    pub fees: FeeTrend,
+    pub generated_at: String,
+}
+
+fn concentration_path(data_dir: &Path, portfolio_id: u32) -> PathBuf {
+    data_dir
+        .join("insights")
+        .join(format!("{}_concentration.json", portfolio_id))
+}
+
+/// First day of the month named `YYYY-MM`
+pub fn parse_month(month: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid month (expected YYYY-MM): {}", month))
+}
+
+fn month_key(date: NaiveDate) -> String {
+    format!("{:04}-{:02}", date.year(), date.month())
+}
+
+fn parse_day(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// `count` month keys ending with `month`, oldest first
+fn trailing_months(month: NaiveDate, count: u32) -> Vec<String> {
+    (0..count)
+        .rev()
+        .filter_map(|back| month.checked_sub_months(Months::new(back)))
+        .map(month_key)
+        .collect()
+}
+
+fn unit_price(transaction: &Transaction) -> Option<f64> {
+    let quantity = transaction.quantity?.abs();
+    (quantity > 0.0).then(|| transaction.amount.abs() / quantity)
+}
+
+fn contribution_consistency(
+    ledger: &[(NaiveDate, &Transaction)],
+    month: NaiveDate,
+) -> ContributionConsistency {
+    let months = trailing_months(month, CONSISTENCY_MONTHS);
+    let mut net: BTreeMap<&str, f64> = months.iter().map(|m| (m.as_str(), 0.0)).collect();
+    for (date, transaction) in ledger {
+        let key = month_key(*date);
+        if let Some(total) = net.get_mut(key.as_str()) {
+            match transaction.transaction_type.as_str() {
+                "Buy" => *total += transaction.amount.abs(),
+                "Sell" => *total -= transaction.amount.abs(),
+                _ => {}
+            }
+        }
+    }
+
+    let amounts: Vec<f64> = net.values().map(|amount| amount.max(0.0)).collect();
+    let months_with_contributions = amounts.iter().filter(|amount| **amount > 0.0).count() as u32;
+    let average = amounts.iter().sum::<f64>() / amounts.len().max(1) as f64;
+    let variance = amounts.iter().map(|a| (a - average).powi(2)).sum::<f64>()
+        / amounts.len().max(1) as f64;
+
+    ContributionConsistency {
+        months_considered: amounts.len() as u32,
+        months_with_contributions,
+        consistency: months_with_contributions as f64 / amounts.len().max(1) as f64,
+        average_monthly_contribution: average,
+        variation: (average > 0.0).then(|| variance.sqrt() / average),
+    }
+}
+
+fn peak_drop(peak: f64, value: f64) -> f64 {
+    if peak > 0.0 {
+        (peak - value) / peak * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn panic_sells(
+    ledger: &[(NaiveDate, &Transaction)],
+    history: &[(NaiveDate, f64)],
+    month: &str,
+) -> Vec<PanicSell> {
+    let mut flagged = vec![];
+    for (date, sale) in ledger {
+        if sale.transaction_type != "Sell" || month_key(*date) != month {
+            continue;
+        }
+        let window_start = *date - chrono::Duration::days(PANIC_LOOKBACK_DAYS);
+        let in_window = |day: &NaiveDate| *day >= window_start && day <= date;
+
+        // Instrument: traded unit prices of the same ISIN in the window
+        let instrument_drop = unit_price(sale).and_then(|price| {
+            let peak = ledger
+                .iter()
+                .filter(|(day, t)| t.isin == sale.isin && in_window(day))
+                .filter_map(|(_, t)| unit_price(t))
+                .fold(f64::NAN, f64::max);
+            (!peak.is_nan()).then(|| peak_drop(peak, price))
+        });
+
+        // Portfolio: value history in the window
+        let values: Vec<f64> = history
+            .iter()
+            .filter(|(day, _)| in_window(day))
+            .map(|(_, value)| *value)
+            .collect();
+        let portfolio_drop = values.last().map(|last| {
+            let peak = values.iter().cloned().fold(f64::MIN, f64::max);
+            peak_drop(peak, *last)
+        });
+
+        let drop = match (instrument_drop, portfolio_drop) {
+            (Some(i), Some(p)) if p > i => Some((p, "portfolio")),
+            (Some(i), _) => Some((i, "instrument")),
+            (None, Some(p)) => Some((p, "portfolio")),
+            (None, None) => None,
+        };
+        if let Some((drop_percent, basis)) = drop.filter(|(d, _)| *d > PANIC_DROP_PERCENT) {
+            flagged.push(PanicSell {
+                isin: sale.isin.clone(),
+                date: date.to_string(),
+                amount: sale.amount.abs(),
+                drop_percent,
+                basis,
+            });
+        }
+    }
+    flagged
+}
+
+fn fee_trend(ledger: &[(NaiveDate, &Transaction)], month: NaiveDate) -> FeeTrend {
+    let months = trailing_months(month, FEE_BASELINE_MONTHS + 1);
+    let mut totals: BTreeMap<String, f64> = months.iter().map(|m| (m.clone(), 0.0)).collect();
+    for (date, transaction) in ledger {
+        if transaction.transaction_type == "Fee" {
+            if let Some(total) = totals.get_mut(&month_key(*date)) {
+                *total += transaction.amount.abs();
+            }
+        }
+    }
+
+    let monthly: Vec<(String, f64)> = totals.into_iter().collect();
+    let month_fees = monthly.last().map(|(_, fees)| *fees).unwrap_or(0.0);
+    let baseline = &monthly[..monthly.len().saturating_sub(1)];
+    let baseline_average =
+        baseline.iter().map(|(_, fees)| fees).sum::<f64>() / baseline.len().max(1) as f64;
+
+    FeeTrend {
+        month_fees,
+        baseline_average,
+        rising: month_fees > baseline_average,
+        monthly,
+    }
+}
+
+/// Record this month's concentration and compare it with the last month
+/// recorded before it.
+fn concentration_creep(
+    data_dir: &Path,
+    portfolio_id: u32,
+    month: &str,
+    weights: &[f64],
+) -> Result<ConcentrationCreep, String> {
+    let mut sorted = weights.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let current = ConcentrationPoint {
+        largest_weight: sorted.first().copied().unwrap_or(0.0),
+        top_five_weight: sorted.iter().take(5).sum(),
+    };
+
+    let path = concentration_path(data_dir, portfolio_id);
+    let mut recorded: BTreeMap<String, ConcentrationPoint> =
+        store::read_json(&path)?.unwrap_or_default();
+    let previous = recorded
+        .range(..month.to_string())
+        .next_back()
+        .map(|(_, point)| point.clone());
+    recorded.insert(month.to_string(), current.clone());
+    store::write_json(&path, &recorded)?;
+
+    Ok(ConcentrationCreep {
+        largest_change: previous
+            .as_ref()
+            .map(|p| (current.largest_weight - p.largest_weight) * 100.0),
+        top_five_change: previous
+            .as_ref()
+            .map(|p| (current.top_five_weight - p.top_five_weight) * 100.0),
+        current,
+        previous,
+    })
+}
+
+/// Compute insights for `month` (first day of the month). `weights` are
+/// the current position weights (0-1).
+pub fn compute(
+    data_dir: &Path,
+    portfolio_id: u32,
+    month: NaiveDate,
+    transactions: &[Transaction],
+    history: &[HistoryPoint],
+    weights: &[f64],
+) -> Result<MonthlyInsights, String> {
+    let key = month_key(month);
+    let mut ledger: Vec<(NaiveDate, &Transaction)> = transactions
+        .iter()
+        .filter_map(|t| Some((parse_day(&t.date)?, t)))
+        .collect();
+    ledger.sort_by_key(|(date, _)| *date);
+    let history: Vec<(NaiveDate, f64)> = history
+        .iter()
+        .filter_map(|point| Some((parse_day(&point.date)?, point.value)))
+        .collect();
+
+    Ok(MonthlyInsights {
+        portfolio_id,
+        contributions: contribution_consistency(&ledger, month),
+        panic_sells: panic_sells(&ledger, &history, &key),
+        concentration: concentration_creep(data_dir, portfolio_id, &key, weights)?,
+        fees: fee_trend(&ledger, month),
+        month: key,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Plain-text section for the monthly digest email
+pub fn digest_text(insights: &MonthlyInsights) -> String {
+    let mut lines = vec![format!("Insights for {}", insights.month), String::new()];
+
+    let c = &insights.contributions;
+    lines.push(format!(
+        "Contributions: {} of {} months, {:.2} on average",
+        c.months_with_contributions, c.months_considered, c.average_monthly_contribution
+    ));
+
+    if insights.panic_sells.is_empty() {
+        lines.push("Sales after sharp drops: none".to_string());
+    } else {
+        lines.push(format!("Sales after sharp drops: {}", insights.panic_sells.len()));
+        for sell in &insights.panic_sells {
+            lines.push(format!(
+                "  - {} on {}: {:.2} after a {:.1}% {} drop",
+                sell.isin, sell.date, sell.amount, sell.drop_percent, sell.basis
+            ));
+        }
+    }
+
+    let concentration = &insights.concentration;
+    lines.push(format!(
+        "Largest position: {:.1}% ({})",
+        concentration.current.largest_weight * 100.0,
+        concentration
+            .largest_change
+            .map(|change| format!("{:+.1} pp since last month", change))
+            .unwrap_or_else(|| "no earlier month recorded".to_string())
+    ));
+
+    let fees = &insights.fees;
+    lines.push(format!(
+        "Fees: {:.2} this month, {:.2} monthly average before",
+        fees.month_fees, fees.baseline_average
+    ));
+
+    lines.join("\n")
+}
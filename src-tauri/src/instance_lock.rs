@@ -0,0 +1,108 @@
+//! Single-Instance Lock
+//!
+//! `.instance.lock` in the app data dir holds an advisory lock for as long
+//! as the app runs, and the PID of the holder. Some file systems keep
+//! reporting the lock as held after a hard crash, so a failed lock is only
+//! trusted while the recorded PID belongs to a running Portfolio Prism
+//! process. Otherwise the stale lock file is replaced and locked afresh.
+//!
+//! The lock handle is held here until exit, or until the data dir moves and
+//! the lock is taken in the new dir.
+
+use crate::app_reset;
+use fs2::FileExt;
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use sysinfo::{Pid, System};
+
+const ALREADY_RUNNING: &str = "Another instance of Portfolio Prism is already running.";
+
+/// Handle of the held lock; closing it releases the lock
+static HELD: Mutex<Option<File>> = Mutex::new(None);
+
+fn open(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| format!("Failed to open lock file: {}", e))
+}
+
+fn write_pid(file: &mut File) -> std::io::Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    writeln!(file, "{}", std::process::id())?;
+    file.sync_all()
+}
+
+/// Whether `pid` is another running process of this executable
+pub(crate) fn is_prism_process(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+    let Some(own_name) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_name().map(OsStr::to_os_string))
+    else {
+        // Cannot tell, so trust the holder
+        return true;
+    };
+
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    if !system.refresh_process(pid) {
+        return false;
+    }
+    system.process(pid).is_some_and(|process| {
+        let exe_name = process.exe().and_then(Path::file_name);
+        exe_name == Some(own_name.as_os_str())
+            || own_name
+                .to_string_lossy()
+                .eq_ignore_ascii_case(process.name())
+    })
+}
+
+/// Lock the data dir for this process, releasing any lock held on another
+/// data dir once the new one is taken.
+pub fn acquire(data_dir: &Path) -> Result<(), String> {
+    let file = lock(data_dir)?;
+    let mut held = HELD.lock().map_err(|e| e.to_string())?;
+    *held = Some(file);
+    Ok(())
+}
+
+fn lock(data_dir: &Path) -> Result<File, String> {
+    std::fs::create_dir_all(data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+
+    let path = data_dir.join(app_reset::LOCK_FILE);
+    let mut file = open(&path)?;
+    if file.try_lock_exclusive().is_err() {
+        // Windows locks are mandatory: an unreadable file has a live holder
+        let content = std::fs::read_to_string(&path).map_err(|_| ALREADY_RUNNING.to_string())?;
+        // Lock files of older versions record no PID and are reclaimed too
+        let pid = content.trim().parse::<u32>().ok();
+        if let Some(pid) = pid.filter(|pid| is_prism_process(*pid)) {
+            return Err(format!("{} (PID {})", ALREADY_RUNNING, pid));
+        }
+
+        tracing::warn!(
+            "Reclaiming stale instance lock{}",
+            pid.map(|pid| format!(" of PID {}", pid))
+                .unwrap_or_default()
+        );
+        drop(file);
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove stale lock file: {}", e))?;
+        file = open(&path)?;
+        file.try_lock_exclusive()
+            .map_err(|_| ALREADY_RUNNING.to_string())?;
+    }
+
+    write_pid(&mut file).map_err(|e| format!("Failed to write lock file: {}", e))?;
+    Ok(file)
+}
@@ -0,0 +1,215 @@
+//! Instrument Delisting Lifecycle
+//!
+//! Turns recurring "possibly delisted" fetch failures into explicit state.
+//! Each sync records per-ISIN failure streaks in `instrument_lifecycle.json`;
+//! after `DELISTED_THRESHOLD` consecutive failed syncs an instrument becomes a
+//! likely-delisted candidate and is no longer refreshed automatically.
+//!
+//! Users resolve candidates by archiving them, replacing them with another
+//! ISIN (the mapping is passed to the engine on every sync) or forcing a retry,
+//! which puts the instrument back into normal refresh.
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Lifecycle store file name inside the app data dir
+pub(crate) const STORE_FILE: &str = "instrument_lifecycle.json";
+
+/// Consecutive failed syncs before an instrument is flagged
+pub const DELISTED_THRESHOLD: u32 = 5;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstrumentStatus {
+    Active,
+    LikelyDelisted,
+    Archived,
+    Replaced,
+}
+
+impl InstrumentStatus {
+    /// Whether the instrument is excluded from automatic refresh
+    fn paused(&self) -> bool {
+        !matches!(self, InstrumentStatus::Active)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DelistingAction {
+    Archive,
+    Replace,
+    Retry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentRecord {
+    pub isin: String,
+    pub status: InstrumentStatus,
+    pub failure_streak: u32,
+    #[serde(default)]
+    pub first_failure: Option<String>,
+    #[serde(default)]
+    pub last_failure: Option<String>,
+    #[serde(default)]
+    pub last_reason: Option<String>,
+    #[serde(default)]
+    pub replacement_isin: Option<String>,
+    pub updated_at: String,
+}
+
+impl InstrumentRecord {
+    fn new(isin: &str) -> Self {
+        Self {
+            isin: isin.to_string(),
+            status: InstrumentStatus::Active,
+            failure_streak: 0,
+            first_failure: None,
+            last_failure: None,
+            last_reason: None,
+            replacement_isin: None,
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LifecycleStore {
+    #[serde(default)]
+    instruments: HashMap<String, InstrumentRecord>,
+}
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORE_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<LifecycleStore, String> {
+    Ok(store::read_json(&store_path(data_dir))?.unwrap_or_default())
+}
+
+/// Record one sync. `attempted` is `None` for a full sync, or the ISINs a
+/// targeted sync covered; attempted ISINs without a failure reset their
+/// streak. Returns ISINs that crossed the threshold with this sync.
+pub fn record_sync(
+    data_dir: &Path,
+    attempted: Option<&[String]>,
+    failures: &[(String, String)],
+) -> Result<Vec<String>, String> {
+    let mut lifecycle = load(data_dir)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut flagged = vec![];
+
+    for record in lifecycle.instruments.values_mut() {
+        let covered = attempted.is_none_or(|isins| isins.contains(&record.isin));
+        let failed = failures.iter().any(|(isin, _)| *isin == record.isin);
+        if covered && !failed && record.status == InstrumentStatus::Active {
+            record.failure_streak = 0;
+            record.first_failure = None;
+        }
+    }
+
+    for (isin, reason) in failures {
+        let record = lifecycle
+            .instruments
+            .entry(isin.clone())
+            .or_insert_with(|| InstrumentRecord::new(isin));
+        if record.status.paused() {
+            continue;
+        }
+
+        record.failure_streak += 1;
+        record.first_failure.get_or_insert_with(|| now.clone());
+        record.last_failure = Some(now.clone());
+        record.last_reason = Some(reason.clone());
+        record.updated_at = now.clone();
+
+        if record.failure_streak >= DELISTED_THRESHOLD {
+            record.status = InstrumentStatus::LikelyDelisted;
+            flagged.push(isin.clone());
+        }
+    }
+
+    // Healthy instruments need no record
+    lifecycle
+        .instruments
+        .retain(|_, record| record.status.paused() || record.failure_streak > 0);
+
+    store::write_json(&store_path(data_dir), &lifecycle)?;
+    Ok(flagged)
+}
+
+/// ISINs that must not be refreshed automatically
+pub fn paused_isins(data_dir: &Path) -> Result<Vec<String>, String> {
+    Ok(load(data_dir)?
+        .instruments
+        .into_values()
+        .filter(|record| record.status.paused())
+        .map(|record| record.isin)
+        .collect())
+}
+
+/// Replaced ISIN -> replacement ISIN
+pub fn isin_mappings(data_dir: &Path) -> Result<HashMap<String, String>, String> {
+    Ok(load(data_dir)?
+        .instruments
+        .into_values()
+        .filter_map(|record| {
+            let replacement = record.replacement_isin?;
+            Some((record.isin, replacement))
+        })
+        .collect())
+}
+
+/// Flagged, archived and replaced instruments, most recent failure first
+pub fn candidates(data_dir: &Path) -> Result<Vec<InstrumentRecord>, String> {
+    let mut records: Vec<InstrumentRecord> = load(data_dir)?
+        .instruments
+        .into_values()
+        .filter(|record| record.status.paused())
+        .collect();
+    records.sort_by(|a, b| b.last_failure.cmp(&a.last_failure));
+    Ok(records)
+}
+
+/// Apply a user decision to a tracked instrument.
+pub fn resolve(
+    data_dir: &Path,
+    isin: &str,
+    action: DelistingAction,
+    replacement_isin: Option<String>,
+) -> Result<InstrumentRecord, String> {
+    let mut lifecycle = load(data_dir)?;
+    let record = lifecycle
+        .instruments
+        .entry(isin.to_string())
+        .or_insert_with(|| InstrumentRecord::new(isin));
+
+    match action {
+        DelistingAction::Archive => {
+            record.status = InstrumentStatus::Archived;
+        }
+        DelistingAction::Replace => {
+            let replacement =
+                replacement_isin.ok_or_else(|| "A replacement ISIN is required".to_string())?;
+            if replacement == isin {
+                return Err("Replacement ISIN must differ from the original".to_string());
+            }
+            record.status = InstrumentStatus::Replaced;
+            record.replacement_isin = Some(replacement);
+        }
+        DelistingAction::Retry => {
+            record.status = InstrumentStatus::Active;
+            record.failure_streak = 0;
+            record.first_failure = None;
+            record.replacement_isin = None;
+        }
+    }
+    record.updated_at = chrono::Utc::now().to_rfc3339();
+
+    let updated = record.clone();
+    store::write_json(&store_path(data_dir), &lifecycle)?;
+    Ok(updated)
+}
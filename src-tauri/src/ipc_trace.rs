@@ -0,0 +1,153 @@
+//! IPC Record-and-Replay
+//!
+//! Recording appends every command/response pair sent through `PythonEngine`
+//! to an NDJSON trace. Replaying loads such a trace and answers commands from
+//! it instead of a live sidecar, so a user's bug report can be reproduced
+//! without their Trade Republic account.
+//!
+//! Enabled with `PRISM_IPC_RECORD=<path>` / `PRISM_IPC_REPLAY=<path>`, or the
+//! `ipc_recording` feature flag (records into `traces/` in the app data dir).
+
+use crate::python_engine::{EngineError, EngineResponse};
+use crate::store;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Payload keys whose values are never written to a trace
+const REDACTED_KEYS: &[&str] = &["pin", "code", "phone", "password", "token"];
+
+/// One recorded command/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    pub timestamp: String,
+    pub command: String,
+    pub payload: Value,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub response: Option<EngineResponse>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+fn redact(payload: &Value) -> Value {
+    match payload {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    if REDACTED_KEYS.contains(&key.to_lowercase().as_str()) {
+                        (key.clone(), Value::String("[REDACTED]".to_string()))
+                    } else {
+                        (key.clone(), redact(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Appends command/response pairs to an NDJSON trace file
+pub struct TraceRecorder {
+    path: PathBuf,
+}
+
+impl TraceRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default trace location for flag-enabled recording
+    pub fn in_data_dir(data_dir: &Path) -> Self {
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+        Self::new(data_dir.join("traces").join(format!("ipc-{}.ndjson", stamp)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(
+        &self,
+        command: &str,
+        payload: &Value,
+        duration_ms: u64,
+        result: &Result<EngineResponse, String>,
+    ) {
+        let entry = TraceEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            command: command.to_string(),
+            payload: redact(payload),
+            duration_ms,
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+        };
+        if let Err(e) = store::append_ndjson(&self.path, &entry) {
+            tracing::warn!("Failed to record IPC trace: {}", e);
+        }
+    }
+}
+
+/// Answers commands from a recorded trace
+pub struct TracePlayer {
+    entries: Vec<TraceEntry>,
+    consumed: Mutex<Vec<bool>>,
+}
+
+impl TracePlayer {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let entries: Vec<TraceEntry> = store::read_ndjson_tail(path, usize::MAX)?;
+        if entries.is_empty() {
+            return Err(format!("IPC trace {} contains no entries", path.display()));
+        }
+
+        Ok(Self {
+            consumed: Mutex::new(vec![false; entries.len()]),
+            entries,
+        })
+    }
+
+    /// Replay the next unconsumed entry for `command`, preferring one with an
+    /// identical payload. Responses are re-stamped with the live request id.
+    pub fn respond(&self, id: u64, command: &str, payload: &Value) -> Result<EngineResponse, String> {
+        let mut consumed = self
+            .consumed
+            .lock()
+            .map_err(|_| "IPC replay state is poisoned".to_string())?;
+        let payload = redact(payload);
+
+        let candidates = || {
+            self.entries
+                .iter()
+                .enumerate()
+                .filter(|(index, entry)| !consumed[*index] && entry.command == command)
+        };
+        let index = candidates()
+            .find(|(_, entry)| entry.payload == payload)
+            .or_else(|| candidates().next())
+            .map(|(index, _)| index)
+            .ok_or_else(|| format!("No recorded response for '{}' in IPC trace", command))?;
+        consumed[index] = true;
+
+        let entry = &self.entries[index];
+        match (&entry.response, &entry.error) {
+            (Some(response), _) => Ok(EngineResponse {
+                id,
+                ..response.clone()
+            }),
+            (None, Some(error)) => Err(error.clone()),
+            (None, None) => Ok(EngineResponse {
+                id,
+                success: false,
+                data: None,
+                error: Some(EngineError {
+                    code: "REPLAY_EMPTY".to_string(),
+                    message: "Recorded entry has no response".to_string(),
+                }),
+            }),
+        }
+    }
+}
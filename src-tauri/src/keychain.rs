@@ -0,0 +1,52 @@
+//! OS keychain access
+//!
+//! Thin wrapper around the platform credential store (macOS Keychain,
+//! Windows Credential Manager, Secret Service on Linux). Secrets handled by
+//! the shell never touch the app data directory.
+
+/// Service name under which all shell secrets are stored.
+const SERVICE: &str = "com.skeptomenos.portfolioprism";
+
+/// Service name under which the engine stores the broker login.
+pub const ENGINE_SERVICE: &str = "PortfolioPrism";
+
+fn entry(key: &str) -> Result<keyring::Entry, String> {
+    service_entry(SERVICE, key)
+}
+
+fn service_entry(service: &str, key: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(service, key).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+/// Store a secret, replacing any existing value.
+pub fn set_secret(key: &str, value: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to write to keychain: {}", e))
+}
+
+/// Read a secret, returning `None` if nothing is stored under `key`.
+pub fn get_secret(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read from keychain: {}", e)),
+    }
+}
+
+/// Remove a secret. Removing a missing secret is not an error.
+pub fn delete_secret(key: &str) -> Result<(), String> {
+    delete(entry(key)?)
+}
+
+/// Remove a secret the engine stored under `ENGINE_SERVICE`.
+pub fn delete_engine_secret(key: &str) -> Result<(), String> {
+    delete(service_entry(ENGINE_SERVICE, key)?)
+}
+
+fn delete(entry: keyring::Entry) -> Result<(), String> {
+    match entry.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete from keychain: {}", e)),
+    }
+}
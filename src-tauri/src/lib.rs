@@ -7,272 +7,595 @@
 //! - Event emission to frontend
 //! - Single instance enforcement via lock file
 
+mod app_lock;
+mod app_menu;
+mod app_reset;
+mod app_settings;
+mod app_update;
+mod benchmarks;
+mod broker_import;
+mod change_explainer;
+mod closed_positions;
 mod commands;
+mod community_stats;
+mod crash_reports;
+mod dashboard_assembly;
+mod data_backup;
+mod data_location;
+mod data_quality;
+mod data_registry;
+mod dataset;
+mod db_reader;
+mod db_recovery;
+mod deep_link;
+mod downloads;
+mod email;
+mod engine_compat;
+mod event_alerts;
+mod error_reports;
+mod feature_flags;
+mod fx_rates;
+mod headless;
+mod hive_cache;
+mod hive_guard;
+mod holdings_file;
+mod insights;
+mod instance_lock;
+mod instrument_lifecycle;
+mod ipc_trace;
+mod keychain;
+mod local_api;
+mod log_buffer;
+mod logging;
+mod maintenance;
+mod mock_data;
+mod navigation;
+mod network;
+mod overlap_matrix;
+mod pipeline_config;
+mod pipeline_history;
+mod pipeline_progress;
+mod pipeline_report;
+mod pipeline_snapshots;
+mod positions_delta;
+mod price_alerts;
+mod protocol;
+mod proxy;
 mod python_engine;
+mod quote_fallback;
+mod redaction;
+mod response_cache;
+mod sandbox;
+mod scheduler;
+mod second_instance;
+mod self_test;
+mod session_vault;
+mod sidecar_check;
+mod sidecar_env;
+mod store;
+mod sync_queue;
+mod system_resume;
+mod taskbar_progress;
+mod telemetry;
+mod tray;
+mod turnover;
+mod value_waterfall;
+mod xlsx_export;
 
-use commands::{
-    commit_holdings_upload, get_dashboard_data, get_engine_health, get_hive_contribution,
-    get_overlap_analysis, get_pending_reviews, get_pipeline_report, get_positions,
-    get_recent_reports, get_true_holdings, log_event, pick_holdings_file,
-    preview_holdings_upload, run_pipeline, set_hive_contribution, sync_portfolio,
-    tr_check_saved_session, tr_get_auth_status, tr_get_stored_credentials, tr_login,
-    tr_logout, tr_restore_session, tr_submit_2fa, upload_holdings,
-};
-use python_engine::{PythonEngine, StdoutMessage};
-use std::fs::{File, OpenOptions};
+use feature_flags::FeatureFlags;
+use hive_cache::HiveCache;
+use ipc_trace::{TracePlayer, TraceRecorder};
+use python_engine::{EnginePool, EngineRole, EngineState, PythonEngine, StdoutMessage};
+use std::path::Path;
 use std::sync::Arc;
 use serde_json::json;
-use tauri::{Emitter, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_shell::process::CommandEvent;
 use tauri_plugin_shell::ShellExt;
-use fs2::FileExt;
-
-/// Escape a string for safe use in AppleScript string literals.
-/// Prevents command injection via special characters like quotes and backslashes.
-/// SECURITY: Always use this when passing user-controlled or error data to osascript.
-fn escape_applescript_string(s: &str) -> String {
-    s.chars()
-        .flat_map(|c| match c {
-            '\\' => vec!['\\', '\\'],
-            '"' => vec!['\\', '"'],
-            '\n' => vec!['\\', 'n'],
-            '\r' => vec!['\\', 'r'],
-            '\t' => vec!['\\', 't'],
-            _ => vec![c],
+
+/// Blocking native error dialog for fatal startup errors, shown before the
+/// app exits (and before any window exists) on every platform.
+fn show_fatal_error(title: &str, message: &str) {
+    rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title(title)
+        .set_description(message)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+}
+
+/// Spawn a `prism-headless` sidecar and wire its stdio to `engine`.
+fn spawn_engine(
+    app_handle: &AppHandle,
+    engine: Arc<PythonEngine>,
+    role: EngineRole,
+    data_dir: &Path,
+) -> Result<(), String> {
+    forward_engine_state(app_handle, &engine, role);
+    start_sidecar(app_handle, engine, role, data_dir)
+}
+
+/// Leave `engine` idle until its first command, then spawn its sidecar on
+/// the data dir active at that moment.
+fn spawn_engine_lazily(app_handle: &AppHandle, engine: Arc<PythonEngine>, role: EngineRole) {
+    forward_engine_state(app_handle, &engine, role);
+    let app_handle = app_handle.clone();
+    let handle = engine.clone();
+    engine.set_lazy_start(Box::new(move || {
+        let result = store::data_dir(&app_handle)
+            .and_then(|data_dir| start_sidecar(&app_handle, handle.clone(), role, &data_dir));
+        if let Err(msg) = result {
+            tracing::error!("{} spawn failed: {}", role.label(), msg);
+            handle.transition(EngineState::Dead, Some(msg));
+        }
+    }));
+}
+
+/// Start a fresh sidecar for an engine stopped with `shutdown`, e.g. after
+/// its database was reset. State forwarding from the first spawn continues.
+pub(crate) fn respawn_engine(
+    app_handle: &AppHandle,
+    engine: Arc<PythonEngine>,
+    role: EngineRole,
+) -> Result<(), String> {
+    let data_dir = store::data_dir(app_handle)?;
+    engine.transition(EngineState::Restarting, None);
+    start_sidecar(app_handle, engine, role, &data_dir)
+}
+
+fn start_sidecar(
+    app_handle: &AppHandle,
+    engine: Arc<PythonEngine>,
+    role: EngineRole,
+    data_dir: &Path,
+) -> Result<(), String> {
+    if let Some(missing) = sidecar_check::check() {
+        sidecar_check::report_missing(app_handle, &missing);
+        let msg = missing.message();
+        engine.transition(EngineState::Dead, Some(msg.clone()));
+        return Err(msg);
+    }
+    if let Some(tampered) = sidecar_check::verify() {
+        sidecar_check::report_tampered(app_handle, &tampered);
+        let msg = tampered.message();
+        engine.transition(EngineState::Dead, Some(msg.clone()));
+        return Err(msg);
+    }
+
+    // Without the keychain the session is not restored and only lasts until
+    // exit; it never falls back to plaintext in the data dir
+    let session_dir = session_vault::open(data_dir)
+        .or_else(|e| {
+            tracing::warn!("Session encryption unavailable: {}", e);
+            session_vault::private_dir()
         })
-        .collect()
+        .inspect_err(|msg| engine.transition(EngineState::Dead, Some(msg.clone())))?;
+
+    let (mut rx, child) = app_handle
+        .shell()
+        .sidecar("prism-headless")
+        .map_err(|e| format!("Failed to create sidecar: {}", e))
+        .and_then(|cmd| {
+            // Paths as `OsStr`: a lossy UTF-8 copy would point elsewhere
+            cmd.env("PRISM_DATA_DIR", sidecar_env::path_value(data_dir))
+                .env("PRISM_SESSION_DIR", sidecar_env::path_value(&session_dir))
+                .env("PRISM_ENGINE_ROLE", role.as_str())
+                .envs(app_settings::engine_env(&app_settings::load_general(data_dir)))
+                .spawn()
+                .map_err(|e| format!("Failed to spawn sidecar: {}", e))
+        })
+        .inspect_err(|msg| engine.transition(EngineState::Dead, Some(msg.clone())))?;
+
+    // Start reading stdout from the sidecar
+    let reader = {
+        let app_handle = app_handle.clone();
+        let engine = engine.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                handle_sidecar_event(&app_handle, &engine, role, event).await;
+            }
+        })
+    };
+
+    // Set the child process for stdin writing
+    tauri::async_runtime::spawn(async move {
+        engine.set_child(child).await;
+        engine.set_reader(reader).await;
+    });
+
+    Ok(())
+}
+
+/// Emit `engine-state-changed` for every state transition of `engine`.
+fn forward_engine_state(app_handle: &AppHandle, engine: &PythonEngine, role: EngineRole) {
+    let mut states = engine.subscribe_state();
+    let app_handle = app_handle.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let mut previous = states.borrow_and_update().state;
+        while states.changed().await.is_ok() {
+            let status = states.borrow_and_update().clone();
+            let _ = app_handle.emit(
+                "engine-state-changed",
+                json!({
+                    "role": role.as_str(),
+                    "previous": previous,
+                    "state": status.state,
+                    "reason": status.reason,
+                    "since": status.since,
+                }),
+            );
+            previous = status.state;
+        }
+    });
 }
 
-// Legacy greet command (can be removed later)
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
+/// Run an outgoing Hive contribution through the anonymization guard and
+/// tell the engine whether (and what) it may upload.
+///
+/// Spawned because the reply is itself a command whose response arrives on
+/// the stdout loop that delivered this event.
+fn review_hive_contribution(app_handle: &AppHandle, engine: Arc<PythonEngine>, data: serde_json::Value) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let request_id = data["requestId"].clone();
+        let kind = data["kind"].as_str().unwrap_or("holdings");
+        let data_dir = match store::data_dir(&app_handle) {
+            Ok(data_dir) => data_dir,
+            Err(e) => {
+                // Nothing can be audited, so nothing may leave
+                tracing::warn!("Blocking hive contribution: {}", e);
+                let payload = json!({ "requestId": request_id, "reason": e });
+                let _ = engine.send_command("hive_contribution_blocked", payload).await;
+                return;
+            }
+        };
+        let decision = hive_guard::review(&data_dir, kind, &data["contribution"]);
+
+        let (command, payload) = if decision.allowed {
+            (
+                "hive_contribution_approved",
+                json!({ "requestId": request_id, "contribution": decision.contribution }),
+            )
+        } else {
+            let _ = app_handle.emit("hive-contribution-blocked", &decision);
+            (
+                "hive_contribution_blocked",
+                json!({ "requestId": request_id, "reason": decision.reason }),
+            )
+        };
+
+        if let Err(e) = engine.send_command(command, payload).await {
+            tracing::warn!("Failed to deliver hive contribution decision: {}", e);
+        }
+    });
 }
 
-/// Holds the lock file handle to prevent multiple instances.
-/// Must be kept alive for the duration of the application.
-static LOCK_FILE: std::sync::OnceLock<File> = std::sync::OnceLock::new();
-
-fn acquire_instance_lock(data_dir: &std::path::Path) -> Result<File, String> {
-    std::fs::create_dir_all(data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
-    
-    let lock_path = data_dir.join(".instance.lock");
-    let file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(false)
-        .open(&lock_path)
-        .map_err(|e| format!("Failed to open lock file: {}", e))?;
-    
-    file.try_lock_exclusive()
-        .map_err(|_| "Another instance of Portfolio Prism is already running.".to_string())?;
-    
-    Ok(file)
+/// Answer an engine request for a fund decomposition from the Hive cache,
+/// falling back to the offline dataset.
+fn serve_hive_decomposition(app_handle: &AppHandle, engine: Arc<PythonEngine>, data: serde_json::Value) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let request_id = data["requestId"].clone();
+        let isin = data["isin"].as_str().unwrap_or_default().to_string();
+        let cache = app_handle.state::<HiveCache>();
+
+        let payload = match cache.get(&isin, false).await {
+            Ok(decomposition) => json!({
+                "requestId": request_id,
+                "source": "hive",
+                "decomposition": decomposition,
+            }),
+            // Fall back to the offline starter dataset
+            Err(e) => match store::data_dir(&app_handle)
+                .ok()
+                .and_then(|data_dir| dataset::lookup(&data_dir, &isin))
+            {
+                Some(fund) => json!({
+                    "requestId": request_id,
+                    "source": "offlineDataset",
+                    "decomposition": fund,
+                }),
+                None => json!({ "requestId": request_id, "decomposition": null, "error": e }),
+            },
+        };
+
+        if let Err(e) = engine.send_command("hive_decomposition_response", payload).await {
+            tracing::warn!("Failed to deliver hive decomposition for {}: {}", isin, e);
+        }
+    });
+}
+
+/// Route one stdout/stderr event from a sidecar.
+async fn handle_sidecar_event(
+    app_handle: &AppHandle,
+    engine: &Arc<PythonEngine>,
+    role: EngineRole,
+    event: CommandEvent,
+) {
+    if let CommandEvent::Stdout(line_bytes) = event {
+        let line = String::from_utf8_lossy(&line_bytes);
+        if let Some(message) = PythonEngine::parse_stdout(&line) {
+            match message {
+                StdoutMessage::Ready(signal) => {
+                    tracing::info!("Python {} ready (v{}, PID: {})", role.label(), signal.version, signal.pid);
+                    let mismatch = engine_compat::check(app_handle, role, &signal.version);
+                    if let Some(mismatch) = mismatch {
+                        engine_compat::report(app_handle, &mismatch);
+                        engine.transition(EngineState::Dead, Some(mismatch.message()));
+                        engine.shutdown().await;
+                        return;
+                    }
+                    engine.set_session_id(signal.session_id).await;
+                    engine.set_connected(signal.version).await;
+                    let _ = app_handle.emit(role.ready_event(), ());
+                }
+                StdoutMessage::Response(response) => {
+                    engine.handle_response(response).await;
+                }
+                StdoutMessage::Event(event) if event.event == "hive_contribution_request" => {
+                    review_hive_contribution(app_handle, engine.clone(), event.data);
+                }
+                StdoutMessage::Event(event) if event.event == "hive_decomposition_request" => {
+                    serve_hive_decomposition(app_handle, engine.clone(), event.data);
+                }
+                StdoutMessage::Event(event) if event.event == "data_invalidated" => {
+                    response_cache::engine_invalidated(app_handle, event.data);
+                }
+                StdoutMessage::Event(event) => {
+                    let event_name = match event.event.as_str() {
+                        "sync_progress" => "sync-progress",
+                        "pipeline_progress" => "pipeline-progress",
+                        other => other,
+                    };
+
+                    let payload = match event.event.as_str() {
+                        "sync_progress" => {
+                            let progress = event
+                                .data
+                                .get("progress")
+                                .and_then(|value| value.as_u64())
+                                .unwrap_or(0);
+                            let message = event
+                                .data
+                                .get("message")
+                                .and_then(|value| value.as_str())
+                                .unwrap_or_default();
+                            let phase = event
+                                .data
+                                .get("phase")
+                                .and_then(|value| value.as_str())
+                                .unwrap_or("sync");
+                            let status = if phase == "complete" || progress >= 100 {
+                                "complete"
+                            } else {
+                                "syncing"
+                            };
+
+                            let payload = json!({
+                                "status": status,
+                                "progress": progress,
+                                "message": message,
+                                "phase": phase,
+                            });
+                            commands::portfolio::record_sync_progress(&payload);
+                            payload
+                        }
+                        "pipeline_progress" => {
+                            let progress =
+                                pipeline_progress::PipelineProgress::from_event(&event.data);
+                            app_handle
+                                .state::<pipeline_progress::PipelineRun>()
+                                .record_stage(&progress.stage);
+                            json!(progress)
+                        }
+                        _ => event.data,
+                    };
+
+                    let _ = app_handle.emit(event_name, payload);
+                }
+            }
+        }
+    } else if let CommandEvent::Terminated(payload) = event {
+        let reason = match (payload.code, payload.signal) {
+            (Some(code), _) => format!("exited with code {}", code),
+            (None, Some(signal)) => format!("killed by signal {}", signal),
+            (None, None) => "exited".to_string(),
+        };
+        tracing::warn!("Python {} {}", role.label(), reason);
+        crash_reports::sidecar_exited(app_handle, engine, role).await;
+        session_vault::persist(app_handle);
+        engine.transition(EngineState::Dead, Some(reason));
+    } else if let CommandEvent::Stderr(line_bytes) = event {
+        let line = String::from_utf8_lossy(&line_bytes);
+        // Indentation tells traceback frames apart, so it is kept here
+        crash_reports::stderr_line(app_handle, engine, role, line.trim_end()).await;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        // Third-party noise about instruments without price history; the
+        // symbols are kept for `refresh_quote`
+        if trimmed.contains("possibly delisted") || trimmed.contains("No historical data found") {
+            quote_fallback::note_stderr(trimmed);
+            return;
+        }
+
+        logging::engine_line(role.as_str(), trimmed);
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::init();
+    let context = tauri::generate_context!();
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(command) = headless::parse_args(&args) {
+        headless::run(context, command);
+    }
+
     tauri::Builder::default()
+        // Registered first so a second launch exits before doing anything else
+        .plugin(tauri_plugin_single_instance::init(second_instance::handle))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .menu(app_menu::build)
+        .on_menu_event(|app_handle, event| app_menu::handle(app_handle, event.id().as_ref()))
         .setup(|app| {
-            let data_dir = app
-                .path()
-                .app_data_dir()
-                .expect("failed to get app data dir");
-            
-            match acquire_instance_lock(&data_dir) {
-                Ok(lock_file) => {
-                    let _ = LOCK_FILE.set(lock_file);
-                }
+            let data_dir = match store::data_dir(app.handle()) {
+                Ok(dir) => dir,
                 Err(msg) => {
-                    eprintln!("Instance lock failed: {}", msg);
-                    #[cfg(target_os = "macos")]
-                    {
-                        use std::process::Command;
-                        // SECURITY: Escape user-facing message to prevent AppleScript injection
-                        let safe_msg = escape_applescript_string(&msg);
-                        let _ = Command::new("osascript")
-                            .args(["-e", &format!(
-                                "display dialog \"{}\" buttons {{\"OK\"}} default button \"OK\" with icon stop with title \"Portfolio Prism\"",
-                                safe_msg
-                            )])
-                            .output();
-                    }
+                    // E.g. a moved data dir on a drive that is not connected
+                    show_fatal_error("Portfolio Prism", &msg);
                     std::process::exit(1);
                 }
+            };
+            logging::init(&data_dir);
+            app_lock::init(&data_dir);
+
+            if let Err(msg) = instance_lock::acquire(&data_dir) {
+                // Second launches are handed to the running instance by the
+                // single-instance plugin; this catches e.g. another build
+                // using the same data dir
+                tracing::error!("Instance lock failed: {}", msg);
+                show_fatal_error("Portfolio Prism", &msg);
+                std::process::exit(1);
+            }
+            session_vault::sweep_stale();
+
+            // Before the sidecar opens it: repair a database torn by a crash
+            match db_recovery::check_and_recover(&data_dir) {
+                Ok(db_recovery::DbCheck::Restored) => {
+                    tracing::warn!("Engine database was corrupt and has been restored from backup")
+                }
+                Ok(db_recovery::DbCheck::Reset) => {
+                    tracing::error!("Engine database was corrupt and no backup exists; starting fresh")
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Database recovery check failed: {}", e),
             }
 
+            let flags = FeatureFlags::load(&data_dir);
+            // Managed before any sidecar starts: the engine may ask for Hive
+            // decompositions as soon as it is up
+            app.manage(HiveCache::new(&data_dir));
+
             let engine = Arc::new(PythonEngine::new());
+            let settings = app_settings::load_general(&data_dir);
+            app_settings::apply_timeouts(&engine, &settings.timeouts);
+            proxy::configure(&settings.proxy);
 
-            let data_dir_str = data_dir.to_string_lossy().to_string();
+            // Commands issued during launch wait this long for the ready signal;
+            // the environment overrides the settings for development
+            let ready_wait = std::env::var("PRISM_READY_WAIT_SECS")
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(std::time::Duration::from_secs);
+            if let Some(wait) = ready_wait {
+                engine.set_ready_wait(wait);
+            }
 
-            let sidecar_result = app
-                .shell()
-                .sidecar("prism-headless")
-                .map_err(|e| format!("Failed to create sidecar: {}", e))
-                .and_then(|cmd| {
-                    cmd.env("PRISM_DATA_DIR", &data_dir_str)
-                        .spawn()
-                        .map_err(|e| format!("Failed to spawn sidecar: {}", e))
-                });
+            // IPC record-and-replay for reproducing bug reports
+            if let Ok(path) = std::env::var("PRISM_IPC_RECORD") {
+                engine.set_recorder(TraceRecorder::new(path.into()));
+            } else if flags.is_enabled("ipc_recording") {
+                let recorder = TraceRecorder::in_data_dir(&data_dir);
+                tracing::info!("Recording IPC trace to {}", recorder.path().display());
+                engine.set_recorder(recorder);
+            }
+            let replay_path = std::env::var("PRISM_IPC_REPLAY").ok();
+            if let Some(path) = &replay_path {
+                let player = TracePlayer::load(Path::new(path))
+                    .map_err(|e| format!("Failed to load IPC trace: {}", e))?;
+                tracing::info!("Replaying IPC trace from {} (sidecar not started)", path);
+                engine.set_player(player);
+                tauri::async_runtime::block_on(engine.set_connected("replay".to_string()));
+            }
 
-            let (mut rx, child) = match sidecar_result {
-                Ok(result) => result,
-                Err(msg) => {
-                    eprintln!("Sidecar spawn failed: {}", msg);
-                    #[cfg(target_os = "macos")]
-                    {
-                        use std::process::Command;
-                        let dialog_msg = format!(
+            if replay_path.is_some() {
+                // Replay answers every command; no sidecar is needed
+            } else if settings.lazy_engine_start {
+                spawn_engine_lazily(app.handle(), engine.clone(), EngineRole::Primary);
+            } else if let Err(msg) = spawn_engine(app.handle(), engine.clone(), EngineRole::Primary, &data_dir) {
+                // A missing or altered binary was reported as an event; the
+                // window stays up to explain it
+                if !sidecar_check::blocked() {
+                    tracing::error!("Sidecar spawn failed: {}", msg);
+                    show_fatal_error(
+                        "Portfolio Prism - Engine Error",
+                        &format!(
                             "Portfolio Prism failed to start the analytics engine.\n\nError: {}\n\nPlease try restarting the application. If the problem persists, reinstall the app.",
                             msg
-                        );
-                        // SECURITY: Escape user-facing message to prevent AppleScript injection
-                        let safe_msg = escape_applescript_string(&dialog_msg);
-                        let _ = Command::new("osascript")
-                            .args(["-e", &format!(
-                                "display dialog \"{}\" buttons {{\"Quit\"}} default button \"Quit\" with icon stop with title \"Portfolio Prism - Engine Error\"",
-                                safe_msg
-                            )])
-                            .output();
-                    }
+                        ),
+                    );
                     std::process::exit(1);
                 }
-            };
-
-            // Set the child process for stdin writing
-            let engine_clone = engine.clone();
-            tauri::async_runtime::spawn(async move {
-                engine_clone.set_child(child).await;
-            });
+            }
 
-            // Start reading stdout from the sidecar
-            let engine_clone = engine.clone();
-            let app_handle = app.handle().clone();
+            // Optional second sidecar for long-running jobs. Failure here is not
+            // fatal: long jobs simply fall back to the primary engine.
+            let worker = Arc::new(PythonEngine::new());
+            app_settings::apply_timeouts(&worker, &settings.timeouts);
+            if let Some(wait) = ready_wait {
+                worker.set_ready_wait(wait);
+            }
+            if flags.is_enabled("worker_sidecar")
+                || std::env::var("PRISM_ENABLE_WORKER").is_ok_and(|value| value == "1")
+            {
+                if settings.lazy_engine_start {
+                    spawn_engine_lazily(app.handle(), worker.clone(), EngineRole::Worker);
+                } else if let Err(msg) = spawn_engine(app.handle(), worker.clone(), EngineRole::Worker, &data_dir) {
+                    tracing::warn!("Worker sidecar spawn failed, using primary engine only: {}", msg);
+                }
+            } else {
+                // Never started, so commands must not wait for its ready signal
+                worker.transition(EngineState::Dead, Some("Worker sidecar disabled".to_string()));
+            }
 
-            tauri::async_runtime::spawn(async move {
-                while let Some(event) = rx.recv().await {
-                    if let CommandEvent::Stdout(line_bytes) = event {
-                        let line = String::from_utf8_lossy(&line_bytes);
-                        if let Some(message) = PythonEngine::parse_stdout(&line) {
-                            match message {
-                                StdoutMessage::Ready(signal) => {
-                                    println!("  \x1b[32m✓\x1b[0m Python Engine Ready (v{}, PID: {})", signal.version, signal.pid);
-                                    engine_clone.set_connected(signal.version).await;
-                                    let _ = app_handle.emit("engine-ready", ());
-                                }
-                                StdoutMessage::Response(response) => {
-                                    engine_clone.handle_response(response).await;
-                                }
-                                StdoutMessage::Event(event) => {
-                                    let event_name = match event.event.as_str() {
-                                        "sync_progress" => "sync-progress",
-                                        "pipeline_progress" => "pipeline-progress",
-                                        other => other,
-                                    };
-
-                                    let payload = match event.event.as_str() {
-                                        "sync_progress" => {
-                                            let progress = event
-                                                .data
-                                                .get("progress")
-                                                .and_then(|value| value.as_u64())
-                                                .unwrap_or(0);
-                                            let message = event
-                                                .data
-                                                .get("message")
-                                                .and_then(|value| value.as_str())
-                                                .unwrap_or_default();
-                                            let phase = event
-                                                .data
-                                                .get("phase")
-                                                .and_then(|value| value.as_str())
-                                                .unwrap_or("sync");
-                                            let status = if phase == "complete" || progress >= 100 {
-                                                "complete"
-                                            } else {
-                                                "syncing"
-                                            };
-
-                                            json!({
-                                                "status": status,
-                                                "progress": progress,
-                                                "message": message,
-                                                "phase": phase,
-                                            })
-                                        }
-                                        _ => event.data,
-                                    };
-
-                                    let _ = app_handle.emit(event_name, payload);
-                                }
-                            }
-                        }
-                    } else if let CommandEvent::Stderr(line_bytes) = event {
-                        let line = String::from_utf8_lossy(&line_bytes);
-                        let trimmed = line.trim();
-                        if trimmed.is_empty() {
-                            continue;
-                        }
+            app.manage(app_reset::DataDeletion::default());
+            app.manage(maintenance::Maintenance::default());
+            app.manage(navigation::Navigation::default());
+            app.manage(pipeline_progress::PipelineRun::default());
+            maintenance::start_scheduler(app.handle().clone());
+            price_alerts::start_poller(app.handle().clone());
+            scheduler::start(app.handle().clone());
+            telemetry::start(app.handle().clone());
+            app_lock::start(app.handle().clone());
 
-                        if trimmed.contains("PRISM") && trimmed.contains("↳") {
-                            println!("{}", trimmed);
-                            continue;
-                        }
+            let cached_engines = vec![engine.clone(), worker.clone()];
+            response_cache::invalidate_on_change(app.handle(), cached_engines);
+            positions_delta::start(app.handle());
+            taskbar_progress::start(app.handle());
+            app.manage(EnginePool::new(engine.clone(), worker));
+            app.manage(flags);
 
-                        if trimmed.contains("possibly delisted") || trimmed.contains("No historical data found") {
-                            continue;
-                        }
-                        
-                        if trimmed.starts_with("DEBUG") || trimmed.contains("] DEBUG") || trimmed.contains("DEBUG:") {
-                            continue;
-                        }
+            // Make the engine available to commands via state
+            app.manage(engine);
+            system_resume::start(app.handle().clone());
+            network::start(app.handle().clone());
 
-                        let level_prefix = if trimmed.contains("Traceback") || trimmed.contains("Error:") {
-                            "\x1b[31mFATAL\x1b[0m"
-                        } else {
-                            "\x1b[90mLOG  \x1b[0m"
-                        };
+            if let Err(e) = tray::create(app.handle()) {
+                tracing::warn!("{}", e);
+            }
 
-                        println!("  \x1b[90mPRISM\x1b[0m ↳ {} {}", level_prefix, trimmed);
-                    }
-                }
+            app_update::verify_after_update(app.handle());
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = local_api::apply(&handle).await;
             });
 
-            // Make the engine available to commands via state
-            app.manage(engine);
+            // Last, so a link the app was launched with finds the engine managed
+            deep_link::start(app.handle());
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            get_engine_health,
-            get_dashboard_data,
-            get_positions,
-            sync_portfolio,
-            tr_get_auth_status,
-            tr_check_saved_session,
-            tr_get_stored_credentials,
-            tr_restore_session,
-            tr_login,
-            tr_submit_2fa,
-            tr_logout,
-            log_event,
-            get_recent_reports,
-            get_pending_reviews,
-            run_pipeline,
-            get_pipeline_report,
-            get_true_holdings,
-            get_overlap_analysis,
-            upload_holdings,
-            preview_holdings_upload,
-            commit_holdings_upload,
-            pick_holdings_file,
-            set_hive_contribution,
-            get_hive_contribution
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(commands::handler())
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                session_vault::close(app_handle);
+            }
+        });
 }
@@ -0,0 +1,412 @@
+//! Local REST API
+//!
+//! An opt-in HTTP server on 127.0.0.1 (`localApi` in the settings, port 8787
+//! by default) for spreadsheets, Home Assistant or scripts. It is read-only
+//! and answers through the same code paths as the commands:
+//! - `GET /positions?portfolioId=1`: as `get_positions`
+//! - `GET /dashboard?portfolioId=1`: as `get_dashboard_data`
+//! - `GET /report`: the latest pipeline report, as `get_pipeline_report`
+//! - `GET /events`: a WebSocket that pushes `sync-progress`,
+//!   `portfolio-updated` and `pipeline-progress` as they are emitted, each
+//!   as a text message `{"event": ..., "payload": ...}`
+//!
+//! `portfolioId` defaults to 1. Every request needs `Authorization: Bearer
+//! <token>`; browsers cannot set headers on a WebSocket, so `/events` also
+//! takes `?token=<token>`. The token is generated on first use, kept in the OS keychain and
+//! shown by `get_local_api_info`; `rotate_local_api_token` replaces it.
+//! Errors are `{"error": "..."}` with 401 for a missing or wrong token, 423
+//! while the app is locked and 503 when the data cannot be loaded. Requests
+//! do not count as activity for the auto-lock (see `app_lock`). Events are
+//! not pushed while the app is locked, and sockets close when the server
+//! stops.
+//!
+//! `apply` starts, moves or stops the server to match the settings; it runs
+//! at launch and after every settings save.
+
+use crate::app_lock;
+use crate::app_settings;
+use crate::commands::{pipeline, portfolio};
+use crate::keychain;
+use crate::pipeline_progress;
+use crate::store;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::Ipv4Addr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Listener, Manager};
+use tokio::sync::{broadcast, watch};
+
+/// Keychain entry of the bearer token
+pub(crate) const TOKEN_KEY: &str = "local_api_token";
+
+/// How long a stopping server may take to finish open requests
+const SHUTDOWN_WAIT_SECS: u64 = 5;
+
+/// Portfolio used when a request names none
+const DEFAULT_PORTFOLIO: u32 = 1;
+
+/// Tauri events pushed on `/events`
+const PUSHED_EVENTS: [&str; 3] = [
+    "sync-progress",
+    "portfolio-updated",
+    pipeline_progress::EVENT,
+];
+
+/// Events a slow socket may fall behind before it skips ahead
+const EVENT_BUFFER: usize = 256;
+
+/// Token loaded from the keychain, so requests do not hit the keychain
+static TOKEN: Mutex<Option<String>> = Mutex::new(None);
+
+static SERVER: Mutex<Option<RunningServer>> = Mutex::new(None);
+
+/// Why the server last failed to start
+static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Pushed events as JSON text, fed by the Tauri listeners
+static EVENTS: OnceLock<broadcast::Sender<String>> = OnceLock::new();
+
+struct RunningServer {
+    port: u16,
+    /// Set to `true` to stop the server and close its sockets
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiInfo {
+    pub enabled: bool,
+    pub running: bool,
+    pub url: String,
+    pub token: String,
+    /// Why the server is not running although enabled
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PortfolioQuery {
+    #[serde(default = "default_portfolio")]
+    portfolio_id: u32,
+}
+
+fn default_portfolio() -> u32 {
+    DEFAULT_PORTFOLIO
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn unavailable(message: String) -> ApiError {
+    ApiError(StatusCode::SERVICE_UNAVAILABLE, message)
+}
+
+// =============================================================================
+// Token
+// =============================================================================
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The bearer token, created on first use
+fn token() -> Result<String, String> {
+    let mut cached = TOKEN.lock().map_err(|e| e.to_string())?;
+    if let Some(token) = cached.as_ref() {
+        return Ok(token.clone());
+    }
+    let token = match keychain::get_secret(TOKEN_KEY)? {
+        Some(token) => token,
+        None => {
+            let token = new_token();
+            keychain::set_secret(TOKEN_KEY, &token)?;
+            token
+        }
+    };
+    *cached = Some(token.clone());
+    Ok(token)
+}
+
+/// Drop the cached token after it was deleted from the keychain (see
+/// `app_reset`); a new one is created on next use.
+pub fn forget_token() {
+    if let Ok(mut cached) = TOKEN.lock() {
+        *cached = None;
+    }
+}
+
+/// Replace the token; clients using the old one get 401 from now on.
+pub fn rotate_token() -> Result<String, String> {
+    let token = new_token();
+    keychain::set_secret(TOKEN_KEY, &token)?;
+    *TOKEN.lock().map_err(|e| e.to_string())? = Some(token.clone());
+    Ok(token)
+}
+
+fn bearer(headers: &HeaderMap) -> &str {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default()
+}
+
+fn authorize(headers: &HeaderMap) -> Result<(), ApiError> {
+    check_token(bearer(headers))
+}
+
+fn check_token(given: &str) -> Result<(), ApiError> {
+    let expected = token().map_err(unavailable)?;
+    if !app_lock::constant_time_eq(given.trim().as_bytes(), expected.as_bytes()) {
+        return Err(ApiError(
+            StatusCode::UNAUTHORIZED,
+            "Missing or wrong token".to_string(),
+        ));
+    }
+    // Not `check`: polling must not keep the app from locking itself
+    if app_lock::status().locked {
+        return Err(ApiError(
+            StatusCode::LOCKED,
+            app_lock::LOCKED_ERROR.to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Endpoints
+// =============================================================================
+
+async fn positions(
+    State(app_handle): State<AppHandle>,
+    headers: HeaderMap,
+    Query(query): Query<PortfolioQuery>,
+) -> Result<Response, ApiError> {
+    authorize(&headers)?;
+    let response = portfolio::get_positions(
+        app_handle.clone(),
+        query.portfolio_id,
+        app_handle.state(),
+        app_handle.state(),
+    )
+    .await
+    .map_err(unavailable)?;
+    Ok(Json(response).into_response())
+}
+
+async fn dashboard(
+    State(app_handle): State<AppHandle>,
+    headers: HeaderMap,
+    Query(query): Query<PortfolioQuery>,
+) -> Result<Response, ApiError> {
+    authorize(&headers)?;
+    let response = portfolio::get_dashboard_data(
+        app_handle.clone(),
+        query.portfolio_id,
+        app_handle.state(),
+        app_handle.state(),
+    )
+    .await
+    .map_err(unavailable)?;
+    Ok(Json(response).into_response())
+}
+
+async fn report(
+    State(app_handle): State<AppHandle>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    authorize(&headers)?;
+    let response = pipeline::get_pipeline_report(app_handle)
+        .await
+        .map_err(unavailable)?;
+    Ok(Json(response).into_response())
+}
+
+// =============================================================================
+// Event Push
+// =============================================================================
+
+/// The event feed; the Tauri listeners are registered on first use and kept
+/// for the rest of the session.
+fn event_feed(app_handle: &AppHandle) -> &'static broadcast::Sender<String> {
+    EVENTS.get_or_init(|| {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        for name in PUSHED_EVENTS {
+            let sender = sender.clone();
+            app_handle.listen(name, move |event| {
+                if sender.receiver_count() == 0 {
+                    return;
+                }
+                let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+                let _ = sender.send(json!({ "event": name, "payload": payload }).to_string());
+            });
+        }
+        sender
+    })
+}
+
+async fn events(
+    State(app_handle): State<AppHandle>,
+    headers: HeaderMap,
+    Query(query): Query<TokenQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    match query.token.as_deref() {
+        Some(token) => check_token(token.trim())?,
+        None => authorize(&headers)?,
+    }
+    let closing = SERVER
+        .lock()
+        .ok()
+        .and_then(|server| server.as_ref().map(|server| server.shutdown.subscribe()))
+        .ok_or_else(|| unavailable("The local API is stopping".to_string()))?;
+    let feed = event_feed(&app_handle).subscribe();
+    Ok(upgrade.on_upgrade(move |socket| push(socket, feed, closing)))
+}
+
+/// Forward events to one socket until either side closes
+async fn push(
+    mut socket: WebSocket,
+    mut feed: broadcast::Receiver<String>,
+    mut closing: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            event = feed.recv() => match event {
+                Ok(message) => {
+                    if app_lock::status().locked {
+                        continue;
+                    }
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Local API socket skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            // Clients have nothing to send; pings are answered by axum
+            incoming = socket.recv() => {
+                if !matches!(incoming, Some(Ok(_))) {
+                    return;
+                }
+            }
+            _ = closing.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Server
+// =============================================================================
+
+fn running_port() -> Option<u16> {
+    SERVER.lock().ok()?.as_ref().map(|server| server.port)
+}
+
+fn set_error(error: Option<String>) {
+    if let Ok(mut last) = LAST_ERROR.lock() {
+        *last = error;
+    }
+}
+
+async fn stop() {
+    let Some(mut server) = SERVER.lock().ok().and_then(|mut server| server.take()) else {
+        return;
+    };
+    let _ = server.shutdown.send(true);
+    let wait = Duration::from_secs(SHUTDOWN_WAIT_SECS);
+    if tokio::time::timeout(wait, &mut server.task).await.is_err() {
+        server.task.abort();
+    }
+    tracing::info!("Local API on port {} stopped", server.port);
+}
+
+async fn start(app_handle: &AppHandle, port: u16) -> Result<(), String> {
+    token()?;
+    event_feed(app_handle);
+    let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .await
+        .map_err(|e| format!("Cannot listen on port {}: {}", port, e))?;
+    let router = Router::new()
+        .route("/positions", get(positions))
+        .route("/dashboard", get(dashboard))
+        .route("/report", get(report))
+        .route("/events", get(events))
+        .with_state(app_handle.clone());
+
+    let (shutdown, mut stopped) = watch::channel(false);
+    let task = tauri::async_runtime::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async move {
+            let _ = stopped.changed().await;
+        });
+        if let Err(e) = server.await {
+            tracing::warn!("Local API stopped: {}", e);
+        }
+    });
+    if let Ok(mut server) = SERVER.lock() {
+        *server = Some(RunningServer {
+            port,
+            shutdown,
+            task,
+        });
+    }
+    tracing::info!("Local API listening on http://127.0.0.1:{}", port);
+    Ok(())
+}
+
+/// Start, move or stop the server to match the settings.
+pub async fn apply(app_handle: &AppHandle) -> Result<(), String> {
+    let settings = app_settings::load_general(&store::data_dir(app_handle)?).local_api;
+    if settings.enabled && running_port() == Some(settings.port) {
+        return Ok(());
+    }
+    stop().await;
+    set_error(None);
+    if !settings.enabled {
+        return Ok(());
+    }
+    start(app_handle, settings.port).await.inspect_err(|e| {
+        tracing::warn!("Local API not started: {}", e);
+        set_error(Some(e.clone()));
+    })
+}
+
+/// Settings, state and token of the local API
+pub fn info(app_handle: &AppHandle) -> Result<LocalApiInfo, String> {
+    let settings = app_settings::load_general(&store::data_dir(app_handle)?).local_api;
+    Ok(LocalApiInfo {
+        enabled: settings.enabled,
+        running: running_port().is_some(),
+        url: format!("http://127.0.0.1:{}", settings.port),
+        token: token()?,
+        error: LAST_ERROR.lock().ok().and_then(|error| error.clone()),
+    })
+}
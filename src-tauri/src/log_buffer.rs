@@ -0,0 +1,128 @@
+//! Recent Log Buffer
+//!
+//! Keeps the last `CAPACITY` log events in memory for the diagnostics view.
+//! `BufferLayer` is one of the sinks installed by `logging::init`, so it sees
+//! the shell's own events and the stderr lines relayed from the sidecars.
+
+use crate::redaction;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Entries kept; older ones are dropped
+const CAPACITY: usize = 2000;
+
+static BUFFER: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+/// Most to least severe, so `level <= min` selects `min` and above
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    /// `shell` or the engine role (`primary`, `worker`)
+    pub source: String,
+    /// Rust module path of shell events
+    pub target: Option<String>,
+    pub message: String,
+}
+
+pub fn push(entry: LogEntry) {
+    if let Ok(mut buffer) = BUFFER.lock() {
+        if buffer.len() == CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry);
+    }
+}
+
+/// The last `limit` entries at `min_level` or more severe, oldest first.
+pub fn recent(min_level: LogLevel, limit: usize) -> Vec<LogEntry> {
+    let Ok(buffer) = BUFFER.lock() else {
+        return vec![];
+    };
+    let mut entries: Vec<LogEntry> = buffer
+        .iter()
+        .rev()
+        .filter(|entry| entry.level <= min_level)
+        .take(limit)
+        .cloned()
+        .collect();
+    entries.reverse();
+    entries
+}
+
+/// Message and sidecar role of an event
+#[derive(Default)]
+struct EventFields {
+    message: String,
+    role: Option<String>,
+}
+
+impl Visit for EventFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            "role" => self.role = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            "role" => self.role = Some(format!("{:?}", value)),
+            _ => {}
+        }
+    }
+}
+
+/// Copies every enabled event into the buffer, redacted. Events with a `role`
+/// field are sidecar lines and are attributed to that engine.
+pub struct BufferLayer;
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let (source, target) = match fields.role {
+            Some(role) => (role, None),
+            None => ("shell".to_string(), Some(metadata.target().to_string())),
+        };
+        push(LogEntry {
+            timestamp: Utc::now(),
+            level: metadata.level().into(),
+            source,
+            target,
+            message: redaction::redact(&fields.message),
+        });
+    }
+}
@@ -0,0 +1,167 @@
+//! Logging
+//!
+//! Every shell log event and every stderr line relayed from the engine
+//! sidecars goes through `tracing` to three sinks:
+//!
+//! - the console (colored; what a `tauri dev` terminal shows)
+//! - `logs/prism.log` in the app data dir, rotated by size with
+//!   `KEPT_FILES` older generations (`prism.log.1`, `prism.log.2`, ...)
+//! - the in-memory buffer behind `get_recent_logs`
+//!
+//! Each sink redacts secrets (see `redaction`) before anything is written.
+//! `RUST_LOG` overrides the default `info` filter. Records of dependencies
+//! using the `log` crate are forwarded as well.
+
+use crate::log_buffer::BufferLayer;
+use crate::redaction;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::Level;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Log directory inside the app data dir
+pub const LOG_DIR: &str = "logs";
+
+const LOG_FILE: &str = "prism.log";
+
+/// Size at which the current log file is rotated
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated generations kept next to the current file
+const KEPT_FILES: usize = 3;
+
+pub fn log_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOG_DIR)
+}
+
+/// Append-only log file that rotates itself once it reaches `MAX_FILE_BYTES`
+struct RotatingFile {
+    dir: PathBuf,
+    file: Option<File>,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(LOG_FILE))?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            file: Some(file),
+            written,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let path = |generation: usize| match generation {
+            0 => self.dir.join(LOG_FILE),
+            n => self.dir.join(format!("{}.{}", LOG_FILE, n)),
+        };
+        let _ = std::fs::remove_file(path(KEPT_FILES));
+        for generation in (0..KEPT_FILES).rev() {
+            let _ = std::fs::rename(path(generation), path(generation + 1));
+        }
+        self.file = Some(File::create(path(0))?);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > MAX_FILE_BYTES {
+            self.rotate()?;
+        }
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| std::io::Error::other("log file is not open"))?;
+        let written = file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), |file| file.flush())
+    }
+}
+
+/// Writer that redacts each formatted event before passing it on. The fmt
+/// layers write one whole event per `write` call.
+struct Redacted<W: Write>(W);
+
+impl<W: Write> Write for Redacted<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redaction::redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Level of a Python log line from the first level name in it; tracebacks
+/// count as errors and anything unrecognized as info.
+fn engine_level(line: &str) -> Level {
+    if line.contains("Traceback") || line.contains("Error:") {
+        return Level::ERROR;
+    }
+    line.split(|c: char| !c.is_ascii_alphabetic())
+        .find_map(|word| match word {
+            "CRITICAL" | "FATAL" | "ERROR" => Some(Level::ERROR),
+            "WARNING" | "WARN" => Some(Level::WARN),
+            "INFO" => Some(Level::INFO),
+            "DEBUG" => Some(Level::DEBUG),
+            _ => None,
+        })
+        .unwrap_or(Level::INFO)
+}
+
+/// Log a stderr line of the sidecar running as `role` at the level of its
+/// Python logging prefix.
+pub fn engine_line(role: &str, line: &str) {
+    match engine_level(line) {
+        Level::ERROR => tracing::error!(target: "sidecar", role, "{}", line),
+        Level::WARN => tracing::warn!(target: "sidecar", role, "{}", line),
+        Level::INFO => tracing::info!(target: "sidecar", role, "{}", line),
+        _ => tracing::debug!(target: "sidecar", role, "{}", line),
+    }
+}
+
+/// Install the global subscriber. Without a writable log dir, logging goes to
+/// the console and the buffer only.
+pub fn init(data_dir: &Path) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (file, file_error) = match RotatingFile::open(log_dir(data_dir)) {
+        Ok(file) => (Some(file), None),
+        Err(e) => (None, Some(e)),
+    };
+    let file_layer = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(Mutex::new(Redacted(file)))
+    });
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(|| Redacted(std::io::stderr())))
+        .with(file_layer)
+        .with(BufferLayer)
+        .try_init();
+    if installed.is_ok() {
+        if let Some(e) = file_error {
+            tracing::warn!("Logging to the console only, log file unavailable: {}", e);
+        }
+    }
+}
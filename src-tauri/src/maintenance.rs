@@ -0,0 +1,504 @@
+//! Maintenance Coordinator
+//!
+//! Housekeeping that should never compete with interactive use:
+//!
+//! - `hive_cache`: evict Hive decompositions too old to serve even as stale
+//! - `vacuum`: compact the engine's SQLite database
+//! - `log_rotation`: rotate NDJSON logs past a size limit (one generation kept)
+//! - `trace_pruning`: delete old IPC traces and diagnostic bundles
+//! - `crash_pruning`: delete old engine crash reports
+//! - `dataset`: the automatic offline dataset update
+//!
+//! A background loop starts a run at most once a day, either inside the
+//! configured nightly window or when the machine is on AC power and no
+//! command has been invoked for a while. Scheduled runs stop between tasks as
+//! soon as the user becomes active again; the remaining tasks are reported as
+//! deferred. `run_maintenance_now` runs every task regardless.
+
+use crate::crash_reports;
+use crate::data_registry;
+use crate::hive_cache::HiveCache;
+use crate::python_engine::PythonEngine;
+use crate::{downloads, store};
+use chrono::{DateTime, Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Settings and last-run file inside the app data dir
+pub(crate) const MAINTENANCE_FILE: &str = "maintenance.json";
+
+/// How often the scheduler wakes up
+const SCHEDULER_INTERVAL_SECS: u64 = 10 * 60;
+
+/// Minimum time between scheduled runs
+const RUN_INTERVAL_HOURS: i64 = 20;
+
+/// Directories whose files are pruned after `trace_retention_days`
+const PRUNED_DIRS: &[&str] = &["traces"];
+
+/// Last invoked command, unix seconds
+static LAST_ACTIVITY: AtomicI64 = AtomicI64::new(0);
+
+/// Record interactive use; called for every invoked command.
+pub fn record_activity() {
+    LAST_ACTIVITY.store(Utc::now().timestamp(), Ordering::Relaxed);
+}
+
+/// Time since the last invoked command
+pub fn idle_for() -> chrono::Duration {
+    chrono::Duration::seconds(Utc::now().timestamp() - LAST_ACTIVITY.load(Ordering::Relaxed))
+}
+
+// =============================================================================
+// Settings and Status
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceSettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Nightly window start, local hour (inclusive)
+    #[serde(default = "default_window_start")]
+    pub window_start_hour: u32,
+    /// Nightly window end, local hour (exclusive); may wrap past midnight
+    #[serde(default = "default_window_end")]
+    pub window_end_hour: u32,
+    /// Also run outside the window when idle on AC power
+    #[serde(default = "default_enabled")]
+    pub run_when_idle: bool,
+    /// Minutes without a command before the app counts as idle
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u32,
+    #[serde(default = "default_max_log_bytes")]
+    pub max_log_bytes: u64,
+    #[serde(default = "default_trace_retention_days")]
+    pub trace_retention_days: u32,
+    #[serde(default = "default_crash_report_retention_days")]
+    pub crash_report_retention_days: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_window_start() -> u32 {
+    2
+}
+
+fn default_window_end() -> u32 {
+    5
+}
+
+fn default_idle_minutes() -> u32 {
+    15
+}
+
+fn default_max_log_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+fn default_trace_retention_days() -> u32 {
+    14
+}
+
+fn default_crash_report_retention_days() -> u32 {
+    90
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            window_start_hour: default_window_start(),
+            window_end_hour: default_window_end(),
+            run_when_idle: default_enabled(),
+            idle_minutes: default_idle_minutes(),
+            max_log_bytes: default_max_log_bytes(),
+            trace_retention_days: default_trace_retention_days(),
+            crash_report_retention_days: default_crash_report_retention_days(),
+        }
+    }
+}
+
+impl MaintenanceSettings {
+    fn in_window(&self, hour: u32) -> bool {
+        if self.window_start_hour <= self.window_end_hour {
+            (self.window_start_hour..self.window_end_hour).contains(&hour)
+        } else {
+            hour >= self.window_start_hour || hour < self.window_end_hour
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        idle_for() >= chrono::Duration::minutes(self.idle_minutes as i64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceTrigger {
+    Window,
+    Idle,
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskOutcome {
+    pub task: String,
+    /// `ok`, `failed`, `skipped` or `deferred`
+    pub status: String,
+    pub detail: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceRun {
+    pub trigger: MaintenanceTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub tasks: Vec<TaskOutcome>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MaintenanceFile {
+    #[serde(default)]
+    settings: MaintenanceSettings,
+    #[serde(default)]
+    last_run: Option<MaintenanceRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceStatus {
+    pub running: bool,
+    pub settings: MaintenanceSettings,
+    pub last_run: Option<MaintenanceRun>,
+    /// `None` when the power source cannot be determined
+    pub on_ac_power: Option<bool>,
+    pub idle_seconds: i64,
+}
+
+fn file_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(MAINTENANCE_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<MaintenanceFile, String> {
+    Ok(store::read_json(&file_path(data_dir))?.unwrap_or_default())
+}
+
+pub fn save_settings(data_dir: &Path, settings: MaintenanceSettings) -> Result<(), String> {
+    if settings.window_start_hour > 23 || settings.window_end_hour > 23 {
+        return Err("Maintenance window hours must be between 0 and 23".to_string());
+    }
+    let mut file = load(data_dir)?;
+    file.settings = settings;
+    store::write_json(&file_path(data_dir), &file)
+}
+
+// =============================================================================
+// Coordinator
+// =============================================================================
+
+/// Shared run guard; managed as Tauri state
+#[derive(Default)]
+pub struct Maintenance {
+    running: AtomicBool,
+}
+
+impl Maintenance {
+    pub fn status(&self, data_dir: &Path) -> Result<MaintenanceStatus, String> {
+        let file = load(data_dir)?;
+        Ok(MaintenanceStatus {
+            running: self.running.load(Ordering::SeqCst),
+            settings: file.settings,
+            last_run: file.last_run,
+            on_ac_power: on_ac_power(),
+            idle_seconds: idle_for().num_seconds(),
+        })
+    }
+
+    /// Run every task once. Fails if a run is already in progress.
+    pub async fn run(
+        &self,
+        app_handle: &AppHandle,
+        trigger: MaintenanceTrigger,
+    ) -> Result<MaintenanceRun, String> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err("Maintenance is already running".to_string());
+        }
+        let result = run_tasks(app_handle, trigger).await;
+        self.running.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+const TASKS: &[&str] = &[
+    "hive_cache",
+    "vacuum",
+    "log_rotation",
+    "trace_pruning",
+    "crash_pruning",
+    "dataset",
+];
+
+async fn run_tasks(
+    app_handle: &AppHandle,
+    trigger: MaintenanceTrigger,
+) -> Result<MaintenanceRun, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let settings = load(&data_dir)?.settings;
+    let started_at = Utc::now();
+    let _ = app_handle.emit("maintenance-started", json!({ "trigger": trigger }));
+
+    let mut tasks = vec![];
+    for task in TASKS {
+        // Scheduled runs yield to the user between tasks
+        if trigger != MaintenanceTrigger::Manual && idle_for() < chrono::Duration::minutes(1) {
+            tasks.push(TaskOutcome {
+                task: task.to_string(),
+                status: "deferred".to_string(),
+                detail: Some("App in use".to_string()),
+                duration_ms: 0,
+            });
+            continue;
+        }
+
+        let started = std::time::Instant::now();
+        let result = run_task(app_handle, &data_dir, &settings, task, trigger).await;
+        let (status, detail) = match result {
+            Ok(Some(detail)) => ("ok", Some(detail)),
+            Ok(None) => ("skipped", None),
+            Err(e) => {
+                tracing::warn!("Maintenance task {} failed: {}", task, e);
+                ("failed", Some(e))
+            }
+        };
+        tasks.push(TaskOutcome {
+            task: task.to_string(),
+            status: status.to_string(),
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    let run = MaintenanceRun {
+        trigger,
+        started_at,
+        finished_at: Utc::now(),
+        tasks,
+    };
+    let mut file = load(&data_dir)?;
+    file.last_run = Some(run.clone());
+    store::write_json(&file_path(&data_dir), &file)?;
+    let _ = app_handle.emit("maintenance-finished", &run);
+    Ok(run)
+}
+
+/// `Ok(None)` means there was nothing to do.
+async fn run_task(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    settings: &MaintenanceSettings,
+    task: &str,
+    trigger: MaintenanceTrigger,
+) -> Result<Option<String>, String> {
+    match task {
+        "hive_cache" => {
+            let evicted = app_handle.state::<HiveCache>().evict_expired()?;
+            Ok((evicted > 0).then(|| format!("Evicted {} cache entries", evicted)))
+        }
+        "vacuum" => {
+            let engine = app_handle.state::<Arc<PythonEngine>>();
+            if !engine.is_connected().await {
+                return Err(engine.unavailable().into());
+            }
+            engine.request("vacuum_database", json!({})).await?;
+            Ok(Some("Database compacted".to_string()))
+        }
+        "log_rotation" => {
+            let mut rotated = vec![];
+            // Rotated once they exceed `max_log_bytes`
+            for name in data_registry::ROTATED_LOGS {
+                if rotate_log(&data_dir.join(name), settings.max_log_bytes)? {
+                    rotated.push(*name);
+                }
+            }
+            Ok((!rotated.is_empty()).then(|| format!("Rotated {}", rotated.join(", "))))
+        }
+        "trace_pruning" => {
+            let max_age = Duration::from_secs(settings.trace_retention_days as u64 * 24 * 3600);
+            let mut pruned = 0;
+            for dir in PRUNED_DIRS {
+                pruned += prune_dir(&data_dir.join(dir), max_age)?;
+            }
+            Ok((pruned > 0).then(|| format!("Deleted {} old files", pruned)))
+        }
+        "crash_pruning" => {
+            let days = settings.crash_report_retention_days as u64;
+            let max_age = Duration::from_secs(days * 24 * 3600);
+            let pruned = prune_dir(&data_dir.join(crash_reports::REPORT_DIR), max_age)?;
+            Ok((pruned > 0).then(|| format!("Deleted {} old crash reports", pruned)))
+        }
+        "dataset" => {
+            let force = trigger == MaintenanceTrigger::Manual;
+            let ran = downloads::auto_update_if_due(app_handle, data_dir, force).await?;
+            Ok(ran.then(|| "Dataset checked".to_string()))
+        }
+        other => Err(format!("Unknown maintenance task: {}", other)),
+    }
+}
+
+/// Move `path` to `path.1` (replacing an older generation) once it is too big.
+fn rotate_log(path: &Path, max_bytes: u64) -> Result<bool, String> {
+    let size = match std::fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(format!("Failed to stat {}: {}", path.display(), e)),
+    };
+    if size <= max_bytes {
+        return Ok(false);
+    }
+
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    std::fs::rename(path, &rotated)
+        .map_err(|e| format!("Failed to rotate {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+/// Delete files in `dir` last modified longer than `max_age` ago.
+fn prune_dir(dir: &Path, max_age: Duration) -> Result<usize, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+    };
+
+    let mut pruned = 0;
+    for entry in entries.flatten() {
+        let old = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if old && entry.path().is_file() && std::fs::remove_file(entry.path()).is_ok() {
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
+
+// =============================================================================
+// Power Source
+// =============================================================================
+
+/// Whether the machine runs on mains power; `None` if unknown.
+#[cfg(target_os = "macos")]
+pub fn on_ac_power() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    Some(text.contains("'AC Power'"))
+}
+
+/// Whether the machine runs on mains power; `None` if unknown.
+#[cfg(target_os = "linux")]
+pub fn on_ac_power() -> Option<bool> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut found_mains = false;
+    let mut found_battery = false;
+    for supply in supplies.flatten().map(|entry| entry.path()) {
+        let kind = std::fs::read_to_string(supply.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" => {
+                found_mains = true;
+                if std::fs::read_to_string(supply.join("online")).is_ok_and(|s| s.trim() == "1") {
+                    return Some(true);
+                }
+            }
+            "Battery" => found_battery = true,
+            _ => {}
+        }
+    }
+    if !found_battery {
+        // Desktops without a battery often report no mains supply at all
+        return Some(true);
+    }
+    found_mains.then_some(false)
+}
+
+/// Whether the machine runs on mains power; `None` if unknown.
+#[cfg(target_os = "windows")]
+pub fn on_ac_power() -> Option<bool> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // SAFETY: `status` is a valid, writable SYSTEM_POWER_STATUS
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    match status.ACLineStatus {
+        0 => Some(false),
+        1 => Some(true),
+        // 255: unknown
+        _ => None,
+    }
+}
+
+/// Whether the machine runs on mains power; `None` if unknown.
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+pub fn on_ac_power() -> Option<bool> {
+    None
+}
+
+// =============================================================================
+// Scheduler
+// =============================================================================
+
+/// Background loop starting scheduled runs in the window or when idle on AC.
+pub fn start_scheduler(app_handle: AppHandle) {
+    record_activity();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+
+            let Ok(data_dir) = store::data_dir(&app_handle) else {
+                continue;
+            };
+            let file = match load(&data_dir) {
+                Ok(file) => file,
+                Err(e) => {
+                    tracing::warn!("Skipping scheduled maintenance: {}", e);
+                    continue;
+                }
+            };
+            let settings = file.settings;
+            let due = file.last_run.is_none_or(|run| {
+                Utc::now() - run.started_at >= chrono::Duration::hours(RUN_INTERVAL_HOURS)
+            });
+            if !settings.enabled || !due || !settings.is_idle() {
+                continue;
+            }
+
+            let trigger = if settings.in_window(Local::now().hour()) {
+                MaintenanceTrigger::Window
+            } else if settings.run_when_idle && on_ac_power().unwrap_or(false) {
+                MaintenanceTrigger::Idle
+            } else {
+                continue;
+            };
+
+            let maintenance = app_handle.state::<Maintenance>();
+            if let Err(e) = maintenance.run(&app_handle, trigger).await {
+                tracing::warn!("Scheduled maintenance failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,98 @@
+//! Development Mock Data
+//!
+//! Fixture responses for UI work without a running engine. Only served when
+//! the `mock_data` feature flag is on in a debug build; release builds and
+//! the default configuration report `[ENGINE_UNAVAILABLE]` instead, so fake
+//! holdings can never be mistaken for a successful sync.
+
+use crate::feature_flags::FeatureFlags;
+use serde_json::{json, Value};
+
+/// Whether commands may answer from fixtures while the engine is down
+pub fn enabled(flags: &FeatureFlags) -> bool {
+    cfg!(debug_assertions) && flags.is_enabled("mock_data")
+}
+
+pub fn dashboard() -> Value {
+    json!({
+        "totalValue": 25000.0,
+        "totalGain": 3250.0,
+        "gainPercentage": 14.94,
+        "dayChange": 120.5,
+        "dayChangePercent": 0.48,
+        "history": [],
+        "allocations": {
+            "sector": { "Technology": 62.0, "Communication Services": 38.0 },
+            "region": { "North America": 100.0 },
+            "assetClass": { "Equity": 100.0 }
+        },
+        "topHoldings": [
+            {
+                "isin": "US0378331005",
+                "name": "Apple Inc.",
+                "ticker": "AAPL",
+                "value": 15500.0,
+                "weight": 0.62,
+                "pnl": 2100.0,
+                "pnlPercentage": 15.67,
+                "assetClass": "Equity"
+            },
+            {
+                "isin": "US5949181045",
+                "name": "Microsoft Corp.",
+                "ticker": "MSFT",
+                "value": 9500.0,
+                "weight": 0.38,
+                "pnl": 1150.0,
+                "pnlPercentage": 13.77,
+                "assetClass": "Equity"
+            }
+        ],
+        "lastUpdated": null,
+        "isEmpty": false,
+        "positionCount": 2
+    })
+}
+
+pub fn positions() -> Value {
+    json!({
+        "positions": [
+            {
+                "isin": "US0378331005",
+                "name": "Apple Inc.",
+                "ticker": "AAPL",
+                "instrumentType": "stock",
+                "quantity": 80.0,
+                "avgBuyPrice": 167.5,
+                "currentPrice": 193.75,
+                "currentValue": 15500.0,
+                "totalCost": 13400.0,
+                "pnlEur": 2100.0,
+                "pnlPercent": 15.67,
+                "weight": 0.62,
+                "currency": "EUR",
+                "lastUpdated": "1970-01-01T00:00:00Z"
+            },
+            {
+                "isin": "US5949181045",
+                "name": "Microsoft Corp.",
+                "ticker": "MSFT",
+                "instrumentType": "stock",
+                "quantity": 25.0,
+                "avgBuyPrice": 334.0,
+                "currentPrice": 380.0,
+                "currentValue": 9500.0,
+                "totalCost": 8350.0,
+                "pnlEur": 1150.0,
+                "pnlPercent": 13.77,
+                "weight": 0.38,
+                "currency": "EUR",
+                "lastUpdated": "1970-01-01T00:00:00Z"
+            }
+        ],
+        "totalValue": 25000.0,
+        "totalCost": 21750.0,
+        "totalPnl": 3250.0,
+        "totalPnlPercent": 14.94
+    })
+}
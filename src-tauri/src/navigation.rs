@@ -0,0 +1,155 @@
+//! Keyboard and Accessibility Navigation
+//!
+//! The shell keeps a registry of the app's views and the actions each view
+//! offers, so keyboard-only users and OS accessibility tooling can drive the
+//! app without going through the webview's DOM:
+//!
+//! - the built-in views mirror the sidebar; the frontend may replace a view's
+//!   entry (e.g. to add actions) with `register_navigable_views`
+//! - `focus_view` emits `navigation-focus`, which the frontend answers by
+//!   switching views and moving keyboard focus
+//! - every focus change and registry update is emitted as
+//!   `navigation-changed` with the full state
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigableAction {
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub shortcut: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigableView {
+    pub id: String,
+    pub label: String,
+    /// Keyboard shortcut, in Tauri accelerator syntax
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<NavigableAction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationState {
+    pub views: Vec<NavigableView>,
+    pub focused_view: Option<String>,
+}
+
+/// Payload of the `navigation-focus` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationFocus {
+    pub view_id: String,
+    pub action_id: Option<String>,
+}
+
+/// Views of the sidebar, in sidebar order
+const BUILTIN_VIEWS: &[(&str, &str)] = &[
+    ("dashboard", "Dashboard"),
+    ("trade-republic", "Trade Republic"),
+    ("xray", "X-Ray"),
+    ("holdings", "Holdings"),
+    ("health", "Health"),
+];
+
+/// View registry; managed as Tauri state
+pub struct Navigation {
+    state: Mutex<NavigationState>,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        let views = BUILTIN_VIEWS
+            .iter()
+            .enumerate()
+            .map(|(index, (id, label))| NavigableView {
+                id: id.to_string(),
+                label: label.to_string(),
+                shortcut: Some(format!("CmdOrCtrl+{}", index + 1)),
+                actions: vec![],
+            })
+            .collect();
+
+        Self {
+            state: Mutex::new(NavigationState {
+                views,
+                focused_view: None,
+            }),
+        }
+    }
+}
+
+impl Navigation {
+    pub fn snapshot(&self) -> Result<NavigationState, String> {
+        self.state.lock().map(|state| state.clone()).map_err(|e| e.to_string())
+    }
+
+    /// Add views, replacing registered views with the same id.
+    pub fn register(
+        &self,
+        app_handle: &AppHandle,
+        views: Vec<NavigableView>,
+    ) -> Result<NavigationState, String> {
+        if let Some(view) = views.iter().find(|view| view.id.trim().is_empty()) {
+            return Err(format!("View \"{}\" has no id", view.label));
+        }
+
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        for view in views {
+            match state.views.iter_mut().find(|existing| existing.id == view.id) {
+                Some(existing) => *existing = view,
+                None => state.views.push(view),
+            }
+        }
+        let snapshot = state.clone();
+        drop(state);
+
+        let _ = app_handle.emit("navigation-changed", &snapshot);
+        Ok(snapshot)
+    }
+
+    /// Ask the frontend to focus a view (and optionally one of its actions).
+    pub fn focus(
+        &self,
+        app_handle: &AppHandle,
+        view_id: &str,
+        action_id: Option<String>,
+    ) -> Result<NavigationState, String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        let view = state
+            .views
+            .iter()
+            .find(|view| view.id == view_id)
+            .ok_or_else(|| format!("Unknown view: {}", view_id))?;
+        if let Some(action_id) = &action_id {
+            if !view.actions.iter().any(|action| &action.id == action_id) {
+                return Err(format!("View {} has no action {}", view_id, action_id));
+            }
+        }
+        state.focused_view = Some(view_id.to_string());
+        let snapshot = state.clone();
+        drop(state);
+
+        // Keyboard focus only lands in the webview if its window is focused
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.set_focus();
+        }
+        let _ = app_handle.emit(
+            "navigation-focus",
+            NavigationFocus {
+                view_id: view_id.to_string(),
+                action_id,
+            },
+        );
+        let _ = app_handle.emit("navigation-changed", &snapshot);
+        Ok(snapshot)
+    }
+}
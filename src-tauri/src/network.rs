@@ -0,0 +1,127 @@
+//! Network Status
+//!
+//! Whether Trade Republic can be reached, so a sync started without a
+//! connection is queued (`sync_queue`) instead of failing with whatever the
+//! broker library makes of a dead socket. The check resolves and connects to
+//! the Trade Republic API host; it does not send anything. A background task
+//! repeats it every `ONLINE_CHECK_SECS`, or `OFFLINE_CHECK_SECS` while
+//! offline, emits `network-status` on every change and runs the queued syncs
+//! once the connection is back. With a proxy configured (see `proxy`) the
+//! check connects to the proxy instead.
+//!
+//! Until the first check completes the app counts as online.
+
+use crate::proxy;
+use crate::sync_queue;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+pub const EVENT: &str = "network-status";
+
+/// Host the broker connection goes to
+const PROBE_HOST: &str = "api.traderepublic.com:443";
+
+/// How long name resolution plus the connect may take
+const PROBE_TIMEOUT_SECS: u64 = 5;
+
+const ONLINE_CHECK_SECS: u64 = 30;
+
+const OFFLINE_CHECK_SECS: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStatus {
+    pub online: bool,
+    /// When `online` last changed
+    pub since: String,
+    /// Why the last check failed, while offline
+    pub reason: Option<String>,
+}
+
+fn state() -> &'static watch::Sender<NetworkStatus> {
+    static STATE: OnceLock<watch::Sender<NetworkStatus>> = OnceLock::new();
+    STATE.get_or_init(|| {
+        watch::channel(NetworkStatus {
+            online: true,
+            since: Utc::now().to_rfc3339(),
+            reason: None,
+        })
+        .0
+    })
+}
+
+pub fn status() -> NetworkStatus {
+    state().borrow().clone()
+}
+
+pub fn is_online() -> bool {
+    state().borrow().online
+}
+
+async fn probe() -> Result<(), String> {
+    let connect = async {
+        let stream = match proxy::address() {
+            Some(address) => TcpStream::connect(address)
+                .await
+                .map_err(|e| format!("Cannot reach the proxy: {}", e))?,
+            None => TcpStream::connect(PROBE_HOST)
+                .await
+                .map_err(|e| format!("Cannot reach Trade Republic: {}", e))?,
+        };
+        drop(stream);
+        Ok(())
+    };
+    tokio::time::timeout(Duration::from_secs(PROBE_TIMEOUT_SECS), connect)
+        .await
+        .unwrap_or_else(|_| Err("The connection check timed out".to_string()))
+}
+
+/// Check the connection now and record the result; returns whether online.
+pub async fn check_now(app_handle: &AppHandle) -> bool {
+    let result = probe().await;
+    let online = result.is_ok();
+    let changed = state().send_if_modified(|status| {
+        let reason = result.err();
+        if status.online == online {
+            status.reason = reason;
+            return false;
+        }
+        *status = NetworkStatus {
+            online,
+            since: Utc::now().to_rfc3339(),
+            reason,
+        };
+        true
+    });
+    if changed {
+        let status = status();
+        match &status.reason {
+            Some(reason) => tracing::warn!("Offline: {}", reason),
+            None => tracing::info!("Back online"),
+        }
+        let _ = app_handle.emit(EVENT, &status);
+    }
+    online
+}
+
+/// Check the connection periodically and run queued syncs while online.
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if check_now(&app_handle).await {
+                sync_queue::run_pending(&app_handle).await;
+            }
+            let wait = if is_online() {
+                ONLINE_CHECK_SECS
+            } else {
+                OFFLINE_CHECK_SECS
+            };
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+        }
+    });
+}
@@ -0,0 +1,292 @@
+//! ETF Overlap Matrix
+//!
+//! Pairwise overlap of the ETFs in a portfolio, built from the look-through
+//! rows of `get_true_holdings`. The overlap of two funds is the weight they
+//! hold in common: the sum over their shared constituents of the smaller of
+//! the two weights, so identical funds overlap 100% and funds without a
+//! common constituent 0%.
+//!
+//! A fund the pipeline could not decompose has no overlap with anything; its
+//! row and column are `null` instead of 0 so it does not read as distinct.
+//!
+//! Constituents a fund does not report can only add to an overlap, so each
+//! pair also carries a range: at least the known overlap, at most the known
+//! overlap plus both funds' unknown shares.
+
+use crate::commands::pipeline::AssetExposure;
+use crate::data_quality::Bound;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Shared constituents listed per pair, largest common weight first
+const TOP_SHARED: usize = 10;
+
+/// One look-through row, as far as the overlap needs it
+pub struct Constituent<'a> {
+    pub isin: Option<&'a str>,
+    pub name: &'a str,
+    pub sources: &'a [AssetExposure],
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlapFund {
+    pub isin: String,
+    pub name: String,
+    /// Whether the last pipeline run has constituents for the fund
+    pub decomposed: bool,
+    /// Share of the fund's weight without known constituents, if reported
+    pub missing_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedConstituent {
+    pub isin: Option<String>,
+    pub name: String,
+    /// Weight in fund `a`, in percent
+    pub weight_a: f64,
+    /// Weight in fund `b`, in percent
+    pub weight_b: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundPair {
+    pub a: String,
+    pub b: String,
+    pub overlap_percent: f64,
+    /// Range of `overlap_percent` once unknown constituents are placed
+    pub overlap_bound: Bound,
+    pub shared_count: usize,
+    pub top_shared: Vec<SharedConstituent>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlapMatrix {
+    pub funds: Vec<OverlapFund>,
+    /// `matrix[i][j]`: overlap of `funds[i]` and `funds[j]` in percent,
+    /// `None` when either fund is not decomposed
+    pub matrix: Vec<Vec<Option<f64>>>,
+    /// Every pair of decomposed funds, largest overlap first
+    pub pairs: Vec<FundPair>,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Overlap of `funds` (ISIN, name) from the look-through `holdings`;
+/// `missing` is the unknown constituent share per fund ISIN.
+pub fn build<'a>(
+    funds: &[(String, String)],
+    holdings: impl IntoIterator<Item = Constituent<'a>>,
+    missing: &HashMap<String, f64>,
+) -> OverlapMatrix {
+    let index: HashMap<&str, usize> = funds
+        .iter()
+        .enumerate()
+        .map(|(i, (isin, _))| (isin.as_str(), i))
+        .collect();
+    let count = funds.len();
+    let mut decomposed = vec![false; count];
+    let mut common = vec![vec![0.0; count]; count];
+    let mut shared: HashMap<(usize, usize), Vec<SharedConstituent>> = HashMap::new();
+
+    for holding in holdings {
+        // A fund can list the same constituent more than once, e.g. per share class
+        let mut weights: Vec<(usize, f64)> = Vec::new();
+        for source in holding.sources {
+            let Some(&i) = index.get(source.etf.as_str()) else {
+                continue;
+            };
+            decomposed[i] = true;
+            match weights.iter_mut().find(|(fund, _)| *fund == i) {
+                Some((_, weight)) => *weight += source.weight,
+                None => weights.push((i, source.weight)),
+            }
+        }
+        weights.sort_by_key(|(fund, _)| *fund);
+
+        for (n, &(i, weight_i)) in weights.iter().enumerate() {
+            for &(j, weight_j) in &weights[n + 1..] {
+                common[i][j] += weight_i.min(weight_j);
+                shared.entry((i, j)).or_default().push(SharedConstituent {
+                    isin: holding.isin.map(str::to_string),
+                    name: holding.name.to_string(),
+                    weight_a: round2(weight_i * 100.0),
+                    weight_b: round2(weight_j * 100.0),
+                });
+            }
+        }
+    }
+
+    let mut matrix = vec![vec![None; count]; count];
+    let mut pairs = Vec::new();
+    for i in 0..count {
+        if !decomposed[i] {
+            continue;
+        }
+        matrix[i][i] = Some(100.0);
+        for j in i + 1..count {
+            if !decomposed[j] {
+                continue;
+            }
+            let overlap = round2((common[i][j] * 100.0).min(100.0));
+            matrix[i][j] = Some(overlap);
+            matrix[j][i] = Some(overlap);
+
+            let mut top_shared = shared.remove(&(i, j)).unwrap_or_default();
+            let shared_count = top_shared.len();
+            top_shared.sort_by(|x, y| {
+                y.weight_a
+                    .min(y.weight_b)
+                    .total_cmp(&x.weight_a.min(x.weight_b))
+            });
+            top_shared.truncate(TOP_SHARED);
+            let unknown = missing.get(&funds[i].0).copied().unwrap_or(0.0)
+                + missing.get(&funds[j].0).copied().unwrap_or(0.0);
+            pairs.push(FundPair {
+                a: funds[i].0.clone(),
+                b: funds[j].0.clone(),
+                overlap_percent: overlap,
+                overlap_bound: Bound {
+                    min: overlap,
+                    max: round2((overlap + unknown).min(100.0)),
+                },
+                shared_count,
+                top_shared,
+            });
+        }
+    }
+    pairs.sort_by(|x, y| y.overlap_percent.total_cmp(&x.overlap_percent));
+
+    OverlapMatrix {
+        funds: funds
+            .iter()
+            .zip(decomposed)
+            .map(|((isin, name), decomposed)| OverlapFund {
+                missing_percent: missing.get(isin).copied(),
+                isin: isin.clone(),
+                name: name.clone(),
+                decomposed,
+            })
+            .collect(),
+        matrix,
+        pairs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "IE00B4L5Y983";
+    const B: &str = "IE00B5BMR087";
+    const C: &str = "LU0274208692";
+
+    fn funds(isins: &[&str]) -> Vec<(String, String)> {
+        isins
+            .iter()
+            .map(|isin| (isin.to_string(), format!("Fund {}", isin)))
+            .collect()
+    }
+
+    fn exposure(etf: &str, weight: f64) -> AssetExposure {
+        AssetExposure {
+            etf: etf.to_string(),
+            value: 0.0,
+            weight,
+        }
+    }
+
+    /// Overlap of `isins` from look-through rows of (ISIN, per-fund exposures)
+    fn build_from(
+        isins: &[&str],
+        data: &[(&str, Vec<AssetExposure>)],
+        missing: &HashMap<String, f64>,
+    ) -> OverlapMatrix {
+        build(
+            &funds(isins),
+            data.iter().map(|(isin, sources)| Constituent {
+                isin: Some(isin),
+                name: isin,
+                sources,
+            }),
+            missing,
+        )
+    }
+
+    #[test]
+    fn identical_funds_overlap_fully() {
+        let data = [
+            ("US0378331005", vec![exposure(A, 0.6), exposure(B, 0.6)]),
+            ("US5949181045", vec![exposure(A, 0.4), exposure(B, 0.4)]),
+        ];
+
+        let overlap = build_from(&[A, B], &data, &HashMap::new());
+
+        assert_eq!(overlap.matrix[0][1], Some(100.0));
+        assert_eq!(overlap.matrix[1][0], Some(100.0));
+        assert_eq!(overlap.pairs[0].shared_count, 2);
+    }
+
+    #[test]
+    fn disjoint_funds_do_not_overlap() {
+        let data = [
+            ("US0378331005", vec![exposure(A, 1.0)]),
+            ("US5949181045", vec![exposure(B, 1.0)]),
+        ];
+
+        let overlap = build_from(&[A, B], &data, &HashMap::new());
+
+        assert_eq!(overlap.matrix[0][1], Some(0.0));
+        assert_eq!(overlap.pairs[0].shared_count, 0);
+    }
+
+    #[test]
+    fn undecomposed_funds_have_no_overlap() {
+        let data = [("US0378331005", vec![exposure(A, 0.5), exposure(B, 0.5)])];
+
+        let overlap = build_from(&[A, B, C], &data, &HashMap::new());
+
+        assert!(!overlap.funds[2].decomposed);
+        assert_eq!(overlap.matrix[2], vec![None, None, None]);
+        assert_eq!(overlap.matrix[0][2], None);
+        assert_eq!(overlap.matrix[0][1], Some(50.0));
+        assert_eq!(overlap.pairs.len(), 1);
+    }
+
+    #[test]
+    fn sums_duplicate_rows_of_a_fund() {
+        // Two share classes of the same constituent in fund A
+        let data = [(
+            "US0378331005",
+            vec![exposure(A, 0.2), exposure(A, 0.3), exposure(B, 0.4)],
+        )];
+
+        let overlap = build_from(&[A, B], &data, &HashMap::new());
+
+        assert_eq!(overlap.matrix[0][1], Some(40.0));
+        let shared = &overlap.pairs[0].top_shared[0];
+        assert_eq!((shared.weight_a, shared.weight_b), (50.0, 40.0));
+    }
+
+    #[test]
+    fn bounds_widen_by_unknown_shares_up_to_100() {
+        let data = [("US0378331005", vec![exposure(A, 0.3), exposure(B, 0.3)])];
+        let missing = HashMap::from([(A.to_string(), 20.0), (B.to_string(), 60.0)]);
+
+        let overlap = build_from(&[A, B], &data, &missing);
+
+        let bound = &overlap.pairs[0].overlap_bound;
+        assert_eq!((bound.min, bound.max), (30.0, 100.0));
+        assert_eq!(overlap.funds[0].missing_percent, Some(20.0));
+
+        let missing = HashMap::from([(A.to_string(), 10.0)]);
+        let overlap = build_from(&[A, B], &data, &missing);
+        let bound = &overlap.pairs[0].overlap_bound;
+        assert_eq!((bound.min, bound.max), (30.0, 40.0));
+    }
+}
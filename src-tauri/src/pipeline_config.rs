@@ -0,0 +1,132 @@
+//! Per-Portfolio Pipeline Configuration
+//!
+//! Which pipeline stages run, which data sources the engine may use, and how
+//! often the pipeline should refresh, stored per portfolio in
+//! `pipeline_config/{id}.json`. The config travels with every `run_pipeline`
+//! request; portfolios without one get the engine defaults.
+//!
+//! Updates are validated against the capabilities the engine declares
+//! (`get_pipeline_capabilities`), so a typo never silently disables a stage.
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Config directory inside the app data dir
+pub(crate) const CONFIG_DIR: &str = "pipeline_config";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefreshCadence {
+    /// Only when the user runs the pipeline
+    Manual,
+    /// After every successful portfolio sync
+    AfterSync,
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineConfig {
+    /// Stages to run, in engine order; empty means every stage
+    #[serde(default)]
+    pub enabled_stages: Vec<String>,
+    /// Data sources the engine may query; empty means every source
+    #[serde(default)]
+    pub data_sources: Vec<String>,
+    #[serde(default = "default_cadence")]
+    pub refresh_cadence: RefreshCadence,
+}
+
+fn default_cadence() -> RefreshCadence {
+    RefreshCadence::Manual
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            enabled_stages: vec![],
+            data_sources: vec![],
+            refresh_cadence: default_cadence(),
+        }
+    }
+}
+
+/// Stages and data sources the engine supports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineCapabilities {
+    pub stages: Vec<String>,
+    pub data_sources: Vec<String>,
+    /// Stages that cannot be disabled (e.g. loading holdings)
+    #[serde(default)]
+    pub required_stages: Vec<String>,
+}
+
+fn config_path(data_dir: &Path, portfolio_id: u32) -> PathBuf {
+    data_dir.join(CONFIG_DIR).join(format!("{}.json", portfolio_id))
+}
+
+pub fn load(data_dir: &Path, portfolio_id: u32) -> Result<Option<PipelineConfig>, String> {
+    store::read_json(&config_path(data_dir, portfolio_id))
+}
+
+pub fn save(data_dir: &Path, portfolio_id: u32, config: &PipelineConfig) -> Result<(), String> {
+    store::write_json(&config_path(data_dir, portfolio_id), config)
+}
+
+/// Drop the config of a deleted portfolio.
+pub fn forget(data_dir: &Path, portfolio_id: u32) {
+    let _ = std::fs::remove_file(config_path(data_dir, portfolio_id));
+}
+
+/// Check a config against the engine's capabilities and normalize it
+/// (duplicates removed, stages in engine order).
+pub fn validate(
+    mut config: PipelineConfig,
+    capabilities: &PipelineCapabilities,
+) -> Result<PipelineConfig, String> {
+    if let Some(unknown) = config
+        .enabled_stages
+        .iter()
+        .find(|stage| !capabilities.stages.contains(stage))
+    {
+        return Err(format!(
+            "Unknown pipeline stage: {} (available: {})",
+            unknown,
+            capabilities.stages.join(", ")
+        ));
+    }
+    if let Some(unknown) = config
+        .data_sources
+        .iter()
+        .find(|source| !capabilities.data_sources.contains(source))
+    {
+        return Err(format!(
+            "Unknown data source: {} (available: {})",
+            unknown,
+            capabilities.data_sources.join(", ")
+        ));
+    }
+
+    if !config.enabled_stages.is_empty() {
+        if let Some(missing) = capabilities
+            .required_stages
+            .iter()
+            .find(|stage| !config.enabled_stages.contains(stage))
+        {
+            return Err(format!("Pipeline stage {} cannot be disabled", missing));
+        }
+        config.enabled_stages = capabilities
+            .stages
+            .iter()
+            .filter(|stage| config.enabled_stages.contains(stage))
+            .cloned()
+            .collect();
+    }
+    config.data_sources.sort();
+    config.data_sources.dedup();
+
+    Ok(config)
+}
@@ -0,0 +1,98 @@
+//! Pipeline Run History
+//!
+//! One record per `run_pipeline` / `run_pipeline_stage` call, appended by the
+//! shell to `pipeline_history.ndjson` whatever the outcome, so recurring
+//! failures (e.g. a Hive RPC missing on the server) show up over time rather
+//! than only in the latest health report. Stage timings come from the
+//! engine's progress events. The log is trimmed to `MAX_RECORDS`.
+
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub(crate) const HISTORY_FILE: &str = "pipeline_history.ndjson";
+
+/// Records kept after trimming
+const MAX_RECORDS: usize = 500;
+
+/// Error messages stored per run; `error_count` still counts all of them
+const MAX_ERRORS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineRunRecord {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// `success`, `failed` or `cancelled`
+    pub status: String,
+    /// Single stage of `run_pipeline_stage`; `None` for full runs
+    pub stage: Option<String>,
+    pub portfolio_id: Option<u32>,
+    pub stage_timings: Vec<StageTiming>,
+    pub error_count: usize,
+    pub errors: Vec<String>,
+}
+
+impl PipelineRunRecord {
+    pub fn new(
+        started_at: DateTime<Utc>,
+        stage: Option<&str>,
+        portfolio_id: Option<u32>,
+        status: &str,
+        stage_timings: Vec<StageTiming>,
+        errors: &[String],
+    ) -> Self {
+        let finished_at = Utc::now();
+        Self {
+            started_at,
+            finished_at,
+            duration_ms: (finished_at - started_at).num_milliseconds().max(0) as u64,
+            status: status.to_string(),
+            stage: stage.map(str::to_string),
+            portfolio_id,
+            stage_timings,
+            error_count: errors.len(),
+            errors: errors.iter().take(MAX_ERRORS).cloned().collect(),
+        }
+    }
+}
+
+fn history_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(HISTORY_FILE)
+}
+
+/// Append a run and trim the log once it holds twice `MAX_RECORDS`.
+pub fn record(data_dir: &Path, record: &PipelineRunRecord) -> Result<(), String> {
+    let path = history_path(data_dir);
+    store::append_ndjson(&path, record)?;
+
+    let records: Vec<PipelineRunRecord> = store::read_ndjson_tail(&path, usize::MAX)?;
+    if records.len() <= MAX_RECORDS * 2 {
+        return Ok(());
+    }
+    let mut content = String::new();
+    for record in &records[records.len() - MAX_RECORDS..] {
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize pipeline history: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    store::write_atomic(&path, content.as_bytes())
+}
+
+/// The most recent runs, newest first.
+pub fn recent(data_dir: &Path, limit: usize) -> Result<Vec<PipelineRunRecord>, String> {
+    let mut records: Vec<PipelineRunRecord> =
+        store::read_ndjson_tail(&history_path(data_dir), limit)?;
+    records.reverse();
+    Ok(records)
+}
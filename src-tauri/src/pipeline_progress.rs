@@ -0,0 +1,224 @@
+//! Pipeline Progress
+//!
+//! The engine reports `pipeline_progress` events while `run_pipeline` is
+//! running. They are normalized here into `pipeline-progress` Tauri events
+//! with an overall percent: each stage (fetch, decompose, aggregate, report)
+//! covers a fixed share of the run and the engine's progress within the stage
+//! is scaled into it. The shell adds `started`, `complete`, `failed` and
+//! `cancelled` events around the command so the frontend sees the run end
+//! even when the engine sends nothing.
+//!
+//! `PipelineRun` (managed state) tracks the single active run so
+//! `cancel_pipeline` can resolve it early, and times its stages for the run
+//! history.
+
+use crate::pipeline_history::StageTiming;
+use crate::python_engine::{EngineRole, PythonEngine};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+pub const EVENT: &str = "pipeline-progress";
+
+/// Stages in run order with the overall percent at which each starts and ends
+const STAGES: &[(&str, f64, f64)] = &[
+    ("fetch", 0.0, 20.0),
+    ("decompose", 20.0, 75.0),
+    ("aggregate", 75.0, 90.0),
+    ("report", 90.0, 100.0),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineProgress {
+    /// `running`, `complete`, `failed` or `cancelled`
+    pub status: &'static str,
+    /// `started`, a pipeline stage, `complete`, `failed` or `cancelled`
+    pub stage: String,
+    /// Overall progress (0-100)
+    pub percent: f64,
+    /// ISIN of the ETF being processed, if the stage works per fund
+    pub current_etf: Option<String>,
+    /// 1-based position of `current_etf` among `etf_count` funds
+    pub etf_index: Option<u32>,
+    pub etf_count: Option<u32>,
+    pub message: String,
+}
+
+impl PipelineProgress {
+    pub fn started() -> Self {
+        Self::marker("started", "Starting pipeline")
+    }
+
+    pub fn complete() -> Self {
+        let mut progress = Self::marker("complete", "Pipeline complete");
+        progress.status = "complete";
+        progress.percent = 100.0;
+        progress
+    }
+
+    pub fn failed(error: &str) -> Self {
+        let mut progress = Self::marker("failed", error);
+        progress.status = "failed";
+        progress
+    }
+
+    pub fn cancelled() -> Self {
+        let mut progress = Self::marker("cancelled", "Pipeline cancelled");
+        progress.status = "cancelled";
+        progress
+    }
+
+    fn marker(stage: &str, message: &str) -> Self {
+        Self {
+            status: "running",
+            stage: stage.to_string(),
+            percent: 0.0,
+            current_etf: None,
+            etf_index: None,
+            etf_count: None,
+            message: message.to_string(),
+        }
+    }
+
+    /// Normalize the data of an engine `pipeline_progress` event. Accepts
+    /// `stage` or `phase`, stage progress as `progress` or `percent` (0-100),
+    /// and the current fund as `etf`, `currentEtf` or `isin` with `current`
+    /// and `total` counts. Without stage progress, the fund counts are used.
+    pub fn from_event(data: &Value) -> Self {
+        let text = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| data.get(*key).and_then(Value::as_str))
+                .map(str::to_string)
+        };
+        let number = |keys: &[&str]| keys.iter().find_map(|key| data.get(*key)?.as_f64());
+
+        let stage = text(&["stage", "phase"]).unwrap_or_else(|| "running".to_string());
+        let etf_index = number(&["current", "etfIndex"]).map(|n| n as u32);
+        let etf_count = number(&["total", "etfCount"]).map(|n| n as u32);
+        let within = number(&["progress", "percent"])
+            .or_else(|| match (etf_index, etf_count) {
+                (Some(index), Some(count)) if count > 0 => {
+                    Some(f64::from(index) / f64::from(count) * 100.0)
+                }
+                _ => None,
+            })
+            .unwrap_or(0.0)
+            .clamp(0.0, 100.0);
+        let percent = match STAGES.iter().find(|(name, _, _)| *name == stage) {
+            Some((_, start, end)) => start + (end - start) * within / 100.0,
+            // Unknown stages report overall progress themselves
+            None => within,
+        };
+
+        Self {
+            status: "running",
+            message: text(&["message"]).unwrap_or_else(|| stage.clone()),
+            stage,
+            percent: (percent * 10.0).round() / 10.0,
+            current_etf: text(&["etf", "currentEtf", "isin"]),
+            etf_index,
+            etf_count,
+        }
+    }
+}
+
+pub fn emit(app_handle: &AppHandle, progress: &PipelineProgress) {
+    let _ = app_handle.emit(EVENT, progress);
+}
+
+/// Engine running the active pipeline and the trigger that resolves its
+/// `run_pipeline` call as cancelled
+struct ActiveRun {
+    engine: Arc<PythonEngine>,
+    role: EngineRole,
+    cancel: oneshot::Sender<()>,
+}
+
+/// The active pipeline run, if any; managed as Tauri state
+#[derive(Default)]
+pub struct PipelineRun {
+    active: Mutex<Option<ActiveRun>>,
+    /// Stages reported by the engine during the current run, with the time
+    /// each was first seen
+    stages: Mutex<Vec<(String, Instant)>>,
+}
+
+impl PipelineRun {
+    /// Register a run on `engine`. The receiver fires when it is cancelled.
+    pub fn start(
+        &self,
+        engine: Arc<PythonEngine>,
+        role: EngineRole,
+    ) -> Result<oneshot::Receiver<()>, String> {
+        let mut active = self.active.lock().map_err(|e| e.to_string())?;
+        if active.as_ref().is_some_and(|run| !run.cancel.is_closed()) {
+            return Err("The pipeline is already running".to_string());
+        }
+        let (cancel, cancelled) = oneshot::channel();
+        *active = Some(ActiveRun {
+            engine,
+            role,
+            cancel,
+        });
+        if let Ok(mut stages) = self.stages.lock() {
+            stages.clear();
+        }
+        Ok(cancelled)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active
+            .lock()
+            .is_ok_and(|active| active.as_ref().is_some_and(|run| !run.cancel.is_closed()))
+    }
+
+    /// Note a stage from a progress event; repeated events of the same stage
+    /// keep its start time.
+    pub fn record_stage(&self, stage: &str) {
+        if let Ok(mut stages) = self.stages.lock() {
+            if stages.last().is_none_or(|(last, _)| last != stage) {
+                stages.push((stage.to_string(), Instant::now()));
+            }
+        }
+    }
+
+    /// Forget the run once its receiver is dropped and return its stage
+    /// timings; each stage lasts until the next one starts. A run started
+    /// after a cancellation still holds its receiver and is kept.
+    pub fn finish(&self) -> Vec<StageTiming> {
+        if let Ok(mut active) = self.active.lock() {
+            if active.as_ref().is_some_and(|run| run.cancel.is_closed()) {
+                *active = None;
+            }
+        }
+        let Ok(mut stages) = self.stages.lock() else {
+            return vec![];
+        };
+        let finished = Instant::now();
+        let ends: Vec<Instant> = stages
+            .iter()
+            .skip(1)
+            .map(|(_, start)| *start)
+            .chain([finished])
+            .collect();
+        stages
+            .drain(..)
+            .zip(ends)
+            .map(|((stage, start), end)| StageTiming {
+                stage,
+                duration_ms: end.duration_since(start).as_millis() as u64,
+            })
+            .collect()
+    }
+
+    /// Resolve the active run as cancelled and return the engine running it.
+    pub fn cancel(&self) -> Option<(Arc<PythonEngine>, EngineRole)> {
+        let run = self.active.lock().ok()?.take()?;
+        run.cancel.send(()).ok()?;
+        Some((run.engine, run.role))
+    }
+}
@@ -0,0 +1,52 @@
+//! Pipeline Health Report Access
+//!
+//! The Python pipeline writes `outputs/pipeline_health.json` after every run.
+//! The shell reads it directly (no engine round-trip) so diagnostics work
+//! even when the engine is busy or down.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Location of the latest pipeline health report.
+pub fn report_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("outputs").join("pipeline_health.json")
+}
+
+/// Load the latest pipeline health report.
+pub fn load(data_dir: &Path) -> Result<Value, String> {
+    let report_path = report_path(data_dir);
+
+    if !report_path.exists() {
+        return Err("Report file not found".to_string());
+    }
+
+    let content =
+        fs::read_to_string(report_path).map_err(|e| format!("Failed to read report: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse report: {}", e))
+}
+
+/// Per-ETF decomposition entries (`decomposition.per_etf`), if present.
+pub fn per_etf(report: &Value) -> &[Value] {
+    report["decomposition"]["per_etf"]
+        .as_array()
+        .map(|entries| entries.as_slice())
+        .unwrap_or(&[])
+}
+
+/// Parse the report timestamp. The engine writes naive local ISO timestamps;
+/// RFC 3339 is accepted as well.
+pub fn timestamp(report: &Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    let raw = report["timestamp"].as_str()?;
+
+    if let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(raw) {
+        return Some(parsed.with_timezone(&chrono::Utc));
+    }
+
+    raw.parse::<chrono::NaiveDateTime>()
+        .ok()?
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|local| local.with_timezone(&chrono::Utc))
+}
@@ -0,0 +1,236 @@
+//! Pipeline Snapshots
+//!
+//! After every successful pipeline run the shell records what the user saw:
+//! direct positions, the look-through holdings and the per-ETF decomposition
+//! entries of the pipeline report. Snapshots live in `snapshots/{id}.json`
+//! (the id is the UTC capture time) and the newest `MAX_SNAPSHOTS` are kept.
+//! `change_explainer` diffs two of them.
+
+use crate::commands::portfolio::{PortfoliosResponse, PositionsResponse};
+use crate::pipeline_report;
+use crate::python_engine::PythonEngine;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Snapshot directory inside the app data dir
+pub(crate) const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Snapshots kept on disk
+const MAX_SNAPSHOTS: usize = 30;
+
+/// A direct position, merged across real portfolios
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotPosition {
+    pub name: String,
+    pub quantity: f64,
+    /// Price in the instrument currency
+    pub price: f64,
+    pub currency: String,
+    /// Value in the portfolio currency
+    pub value: f64,
+}
+
+/// One look-through row of `get_true_holdings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotHolding {
+    pub name: String,
+    pub value: f64,
+    #[serde(default)]
+    pub sector: Option<String>,
+    #[serde(default)]
+    pub geography: Option<String>,
+}
+
+/// Decomposition outcome of one ETF from the pipeline report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDecomposition {
+    pub status: Option<String>,
+    pub source: Option<String>,
+    pub holdings_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineSnapshot {
+    pub id: String,
+    pub taken_at: DateTime<Utc>,
+    pub total_value: f64,
+    /// Keyed by ISIN
+    pub positions: BTreeMap<String, SnapshotPosition>,
+    /// Keyed by ISIN (or name for unresolved rows)
+    pub holdings: BTreeMap<String, SnapshotHolding>,
+    /// Keyed by ETF ISIN
+    pub decompositions: BTreeMap<String, SnapshotDecomposition>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub taken_at: DateTime<Utc>,
+    pub total_value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LookThroughRow {
+    #[serde(default)]
+    isin: Option<String>,
+    stock: String,
+    total_value: f64,
+    #[serde(default)]
+    sector: Option<String>,
+    #[serde(default)]
+    geography: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LookThroughResponse {
+    holdings: Vec<LookThroughRow>,
+}
+
+fn snapshot_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(SNAPSHOT_DIR)
+}
+
+fn snapshot_path(data_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    // The id becomes a file name; only accept what `capture` generates
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid snapshot id: {}", id));
+    }
+    Ok(snapshot_dir(data_dir).join(format!("{}.json", id)))
+}
+
+pub fn load(data_dir: &Path, id: &str) -> Result<PipelineSnapshot, String> {
+    store::read_json(&snapshot_path(data_dir, id)?)?
+        .ok_or_else(|| format!("Snapshot {} not found", id))
+}
+
+fn ids(data_dir: &Path) -> Result<Vec<String>, String> {
+    let entries = match std::fs::read_dir(snapshot_dir(data_dir)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Failed to read snapshots: {}", e)),
+    };
+
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            (path.extension()? == "json").then_some(())?;
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Snapshots, newest first
+pub fn list(data_dir: &Path) -> Result<Vec<SnapshotSummary>, String> {
+    let mut summaries = vec![];
+    for id in ids(data_dir)?.into_iter().rev() {
+        if let Ok(snapshot) = load(data_dir, &id) {
+            summaries.push(SnapshotSummary {
+                id: snapshot.id,
+                taken_at: snapshot.taken_at,
+                total_value: snapshot.total_value,
+            });
+        }
+    }
+    Ok(summaries)
+}
+
+/// Record the current state and prune old snapshots.
+pub async fn capture(
+    app_handle: &tauri::AppHandle,
+    engine: &PythonEngine,
+) -> Result<PipelineSnapshot, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let taken_at = Utc::now();
+
+    let mut positions = BTreeMap::new();
+    let data = engine.request("list_portfolios", json!({})).await?;
+    let portfolios: PortfoliosResponse =
+        serde_json::from_value(data).map_err(|e| format!("Invalid portfolio list: {}", e))?;
+    for portfolio in portfolios.portfolios {
+        let data = engine
+            .request("get_positions", json!({ "portfolioId": portfolio.id }))
+            .await?;
+        let response: PositionsResponse =
+            serde_json::from_value(data).map_err(|e| format!("Invalid positions: {}", e))?;
+        for position in response.positions {
+            let entry = positions
+                .entry(position.isin)
+                .or_insert_with(|| SnapshotPosition {
+                    name: position.name,
+                    quantity: 0.0,
+                    price: position.current_price,
+                    currency: position.currency,
+                    value: 0.0,
+                });
+            entry.quantity += position.quantity;
+            entry.value += position.current_value;
+        }
+    }
+
+    let data = engine.request("get_true_holdings", json!({})).await?;
+    let look_through: LookThroughResponse =
+        serde_json::from_value(data).map_err(|e| format!("Invalid true holdings: {}", e))?;
+    let holdings = look_through
+        .holdings
+        .into_iter()
+        .map(|row| {
+            (
+                row.isin.unwrap_or_else(|| row.stock.clone()),
+                SnapshotHolding {
+                    name: row.stock,
+                    value: row.total_value,
+                    sector: row.sector,
+                    geography: row.geography,
+                },
+            )
+        })
+        .collect();
+
+    let report = pipeline_report::load(&data_dir).ok();
+    let decompositions = report
+        .as_ref()
+        .map(pipeline_report::per_etf)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|entry| {
+            Some((
+                entry["isin"].as_str()?.to_string(),
+                SnapshotDecomposition {
+                    status: entry["status"].as_str().map(str::to_string),
+                    source: entry["source"].as_str().map(str::to_string),
+                    holdings_count: entry["holdings_count"].as_u64(),
+                },
+            ))
+        })
+        .collect();
+
+    let snapshot = PipelineSnapshot {
+        id: taken_at.format("%Y%m%dT%H%M%S%3fZ").to_string(),
+        taken_at,
+        total_value: positions.values().map(|position| position.value).sum(),
+        positions,
+        holdings,
+        decompositions,
+    };
+    store::write_json(&snapshot_path(&data_dir, &snapshot.id)?, &snapshot)?;
+
+    let ids = ids(&data_dir)?;
+    for id in ids.iter().take(ids.len().saturating_sub(MAX_SNAPSHOTS)) {
+        let _ = std::fs::remove_file(snapshot_path(&data_dir, id)?);
+    }
+
+    Ok(snapshot)
+}
@@ -0,0 +1,141 @@
+//! Position Deltas
+//!
+//! Reloading the full positions table after every sync re-renders every row
+//! of a large portfolio. The shell remembers the positions it last returned
+//! for each portfolio; when the portfolio changes (`portfolio-updated`) it
+//! loads the new positions, diffs them by ISIN and emits `positions-delta`
+//! with the added, removed and changed rows plus the new totals.
+//!
+//! Portfolios whose positions the frontend has not loaded yet get no delta;
+//! their first `get_positions` call returns the full table as before.
+
+use crate::commands::portfolio::{self, Position, PositionsResponse};
+use crate::python_engine::PythonEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+pub const EVENT: &str = "positions-delta";
+
+/// Emitted whenever a portfolio's data changed
+const PORTFOLIO_UPDATED: &str = "portfolio-updated";
+
+/// Positions last sent to the frontend, by portfolio
+static BASELINES: Mutex<BTreeMap<u32, Vec<Position>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PortfolioUpdated {
+    portfolio_id: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionsDelta {
+    pub portfolio_id: u32,
+    pub added: Vec<Position>,
+    /// ISINs of positions that are gone
+    pub removed: Vec<String>,
+    /// Positions with any field changed, in full
+    pub changed: Vec<Position>,
+    pub total_value: f64,
+    pub total_cost: f64,
+    pub total_pnl: f64,
+    pub total_pnl_percent: f64,
+    pub last_sync_time: Option<String>,
+}
+
+impl PositionsDelta {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Record the positions returned to the frontend for `portfolio_id`.
+pub fn remember(portfolio_id: u32, response: &PositionsResponse) {
+    if let Ok(mut baselines) = BASELINES.lock() {
+        baselines.insert(portfolio_id, response.positions.clone());
+    }
+}
+
+fn diff(portfolio_id: u32, previous: &[Position], current: &PositionsResponse) -> PositionsDelta {
+    let previous: HashMap<&str, &Position> = previous
+        .iter()
+        .map(|position| (position.isin.as_str(), position))
+        .collect();
+    let mut added = vec![];
+    let mut changed = vec![];
+    for position in &current.positions {
+        match previous.get(position.isin.as_str()) {
+            None => added.push(position.clone()),
+            Some(old) if *old != position => changed.push(position.clone()),
+            Some(_) => {}
+        }
+    }
+    let current_isins: HashSet<&str> = current
+        .positions
+        .iter()
+        .map(|position| position.isin.as_str())
+        .collect();
+    let removed = previous
+        .keys()
+        .filter(|isin| !current_isins.contains(*isin))
+        .map(|isin| isin.to_string())
+        .collect();
+
+    PositionsDelta {
+        portfolio_id,
+        added,
+        removed,
+        changed,
+        total_value: current.total_value,
+        total_cost: current.total_cost,
+        total_pnl: current.total_pnl,
+        total_pnl_percent: current.total_pnl_percent,
+        last_sync_time: current.last_sync_time.clone(),
+    }
+}
+
+/// Reload the positions of `portfolio_id` and emit what changed since the
+/// frontend last loaded them.
+async fn publish(app_handle: AppHandle, engine: Arc<PythonEngine>, portfolio_id: u32) {
+    let Some(previous) = BASELINES
+        .lock()
+        .ok()
+        .and_then(|baselines| baselines.get(&portfolio_id).cloned())
+    else {
+        return;
+    };
+
+    let current = match portfolio::fetch_positions(&app_handle, &engine, portfolio_id).await {
+        Ok(current) => current,
+        Err(e) => {
+            tracing::warn!("Failed to load positions for a delta: {}", e);
+            return;
+        }
+    };
+    let delta = diff(portfolio_id, &previous, &current);
+    remember(portfolio_id, &current);
+    if !delta.is_empty() {
+        let _ = app_handle.emit(EVENT, &delta);
+    }
+}
+
+/// Emit `positions-delta` after every portfolio change.
+pub fn start(app_handle: &AppHandle) {
+    let handle = app_handle.clone();
+    app_handle.listen(PORTFOLIO_UPDATED, move |event| {
+        let Ok(updated) = serde_json::from_str::<PortfolioUpdated>(event.payload()) else {
+            return;
+        };
+        let Some(engine) = handle.try_state::<Arc<PythonEngine>>() else {
+            return;
+        };
+        let engine = engine.inner().clone();
+        // Listeners run in no particular order; make sure the reload does not
+        // hit responses cached before the change
+        engine.invalidate_cache();
+        tauri::async_runtime::spawn(publish(handle.clone(), engine, updated.portfolio_id));
+    });
+}
@@ -0,0 +1,263 @@
+//! Price Alerts
+//!
+//! User-defined thresholds on instrument prices, stored in
+//! `price_alerts.json`. A background loop asks the engine for quotes of every
+//! active alert and fires a native notification (plus a `price-alert-triggered`
+//! event) when a threshold is crossed. A triggered alert is disarmed so it
+//! fires once; setting it again re-arms it. When critical alert emails are
+//! enabled, the notification is also sent by email.
+
+use crate::email::{self, DeliveryKind};
+use crate::event_alerts;
+use crate::python_engine::PythonEngine;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Alerts file inside the app data dir
+pub(crate) const ALERTS_FILE: &str = "price_alerts.json";
+
+/// How often active alerts are checked
+const POLL_INTERVAL_SECS: u64 = 5 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertCondition {
+    /// Price at or above the threshold
+    Above,
+    /// Price at or below the threshold
+    Below,
+}
+
+impl AlertCondition {
+    fn is_met(self, price: f64, threshold: f64) -> bool {
+        match self {
+            Self::Above => price >= threshold,
+            Self::Below => price <= threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceAlert {
+    pub id: u32,
+    pub isin: String,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_price: Option<f64>,
+    #[serde(default)]
+    pub last_checked: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub triggered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Quote {
+    isin: String,
+    #[serde(default)]
+    name: Option<String>,
+    price: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotesResponse {
+    quotes: Vec<Quote>,
+}
+
+fn alerts_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(ALERTS_FILE)
+}
+
+pub fn list(data_dir: &Path) -> Result<Vec<PriceAlert>, String> {
+    Ok(store::read_json(&alerts_path(data_dir))?.unwrap_or_default())
+}
+
+fn save(data_dir: &Path, alerts: &[PriceAlert]) -> Result<(), String> {
+    store::write_json(&alerts_path(data_dir), &alerts)
+}
+
+/// Create an alert, or re-arm the existing one for the same ISIN and condition.
+pub fn set(
+    data_dir: &Path,
+    isin: String,
+    condition: AlertCondition,
+    threshold: f64,
+) -> Result<PriceAlert, String> {
+    if !threshold.is_finite() || threshold <= 0.0 {
+        return Err("Alert threshold must be a positive price".to_string());
+    }
+
+    let mut alerts = list(data_dir)?;
+    let alert = match alerts
+        .iter_mut()
+        .find(|alert| alert.isin == isin && alert.condition == condition)
+    {
+        Some(alert) => {
+            alert.threshold = threshold;
+            alert.active = true;
+            alert.triggered_at = None;
+            alert.clone()
+        }
+        None => {
+            let alert = PriceAlert {
+                id: alerts.iter().map(|alert| alert.id).max().unwrap_or(0) + 1,
+                isin,
+                condition,
+                threshold,
+                active: true,
+                created_at: Utc::now(),
+                last_price: None,
+                last_checked: None,
+                triggered_at: None,
+            };
+            alerts.push(alert.clone());
+            alert
+        }
+    };
+    save(data_dir, &alerts)?;
+    Ok(alert)
+}
+
+pub fn delete(data_dir: &Path, id: u32) -> Result<(), String> {
+    let mut alerts = list(data_dir)?;
+    let before = alerts.len();
+    alerts.retain(|alert| alert.id != id);
+    if alerts.len() == before {
+        return Err(format!("Price alert {} not found", id));
+    }
+    save(data_dir, &alerts)
+}
+
+/// Check every active alert once; returns the alerts that fired.
+pub async fn check(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+) -> Result<Vec<PriceAlert>, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let mut alerts = list(&data_dir)?;
+
+    let mut isins: Vec<&str> = alerts
+        .iter()
+        .filter(|alert| alert.active)
+        .map(|alert| alert.isin.as_str())
+        .collect();
+    isins.sort_unstable();
+    isins.dedup();
+    if isins.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let data = engine.request("get_quotes", json!({ "isins": isins })).await?;
+    let response: QuotesResponse =
+        serde_json::from_value(data).map_err(|e| format!("Invalid quotes response: {}", e))?;
+    let quotes: HashMap<String, Quote> = response
+        .quotes
+        .into_iter()
+        .map(|quote| (quote.isin.clone(), quote))
+        .collect();
+
+    let now = Utc::now();
+    let mut fired = vec![];
+    for alert in alerts.iter_mut().filter(|alert| alert.active) {
+        let Some(quote) = quotes.get(&alert.isin) else {
+            continue;
+        };
+        alert.last_price = Some(quote.price);
+        alert.last_checked = Some(now);
+        if !alert.condition.is_met(quote.price, alert.threshold) {
+            continue;
+        }
+
+        alert.active = false;
+        alert.triggered_at = Some(now);
+        notify(app_handle, alert, quote.name.as_deref());
+        fired.push(alert.clone());
+    }
+
+    save(&data_dir, &alerts)?;
+    Ok(fired)
+}
+
+fn notify(app_handle: &AppHandle, alert: &PriceAlert, name: Option<&str>) {
+    let direction = match alert.condition {
+        AlertCondition::Above => "risen above",
+        AlertCondition::Below => "fallen below",
+    };
+    let body = format!(
+        "{} has {} {:.2} (now {:.2})",
+        name.unwrap_or(&alert.isin),
+        direction,
+        alert.threshold,
+        alert.last_price.unwrap_or_default()
+    );
+
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("Price alert")
+        .body(&body)
+        .show()
+    {
+        tracing::warn!("Failed to show price alert notification: {}", e);
+    }
+    let _ = app_handle.emit("price-alert-triggered", alert);
+
+    if let Ok(data_dir) = store::data_dir(app_handle) {
+        tauri::async_runtime::spawn(send_alert_email(data_dir, body));
+    }
+}
+
+/// Email a triggered alert when enabled; failures are only logged.
+async fn send_alert_email(data_dir: PathBuf, body: String) {
+    let enabled = email::load_settings(&data_dir)
+        .map(|settings| settings.enabled && settings.send_critical_alerts)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    let subject = format!("Portfolio Prism price alert: {}", body);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        email::deliver(&data_dir, DeliveryKind::CriticalAlert, &subject, &body)
+    })
+    .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!("Failed to send price alert email: {}", e),
+        Err(e) => tracing::warn!("Failed to send price alert email: {}", e),
+    }
+}
+
+/// Background loop checking active alerts (and the daily ex-date and
+/// earnings alerts) while the engine is connected.
+pub fn start_poller(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+
+            let engine = app_handle.state::<Arc<PythonEngine>>().inner().clone();
+            if !engine.is_connected().await {
+                continue;
+            }
+            if let Err(e) = check(&app_handle, &engine).await {
+                tracing::warn!("Price alert check failed: {}", e);
+            }
+            // Evaluates at most once per day
+            if let Err(e) = event_alerts::evaluate(&app_handle, &engine, false).await {
+                tracing::warn!("Event alert check failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,108 @@
+//! Engine Response Validation
+//!
+//! Deserializes engine payloads into the shell's typed structs. When the
+//! engine's output does not match, the error names the exact field and what
+//! was found there instead of a generic "failed to parse", and the mismatch is
+//! emitted as `engine-protocol-error` so the frontend can report it.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use serde_path_to_error::{Path, Segment};
+use tauri::{AppHandle, Emitter};
+
+/// Payload of the `engine-protocol-error` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolError {
+    pub command: String,
+    /// Path of the offending field, e.g. `positions[3].weight`
+    pub field: String,
+    /// JSON type found at `field`, or `missing`
+    pub found: String,
+    /// Deserializer message, e.g. `invalid type: string "abc", expected f64`
+    pub detail: String,
+    pub message: String,
+}
+
+fn push_key(field: &mut String, key: &str) {
+    if !field.is_empty() {
+        field.push('.');
+    }
+    field.push_str(key);
+}
+
+fn json_type(value: Option<&Value>) -> &'static str {
+    match value {
+        None => "missing",
+        Some(Value::Null) => "null",
+        Some(Value::Bool(_)) => "boolean",
+        Some(Value::Number(_)) => "number",
+        Some(Value::String(_)) => "string",
+        Some(Value::Array(_)) => "array",
+        Some(Value::Object(_)) => "object",
+    }
+}
+
+fn describe(command: &str, data: &Value, path: &Path, detail: String) -> ProtocolError {
+    let mut field = String::new();
+    let mut node = Some(data);
+
+    for segment in path.iter() {
+        match segment {
+            Segment::Seq { index } => {
+                field.push_str(&format!("[{}]", index));
+                node = node.and_then(|value| value.get(*index));
+            }
+            Segment::Map { key } => {
+                push_key(&mut field, key);
+                node = node.and_then(|value| value.get(key));
+            }
+            Segment::Enum { variant } => push_key(&mut field, variant),
+            Segment::Unknown => {
+                push_key(&mut field, "?");
+                node = None;
+            }
+        }
+    }
+
+    // Missing fields are reported against the enclosing struct; name the field
+    if let Some(missing) = detail
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next())
+    {
+        push_key(&mut field, missing);
+        node = None;
+    }
+
+    if field.is_empty() {
+        field.push_str("<root>");
+    }
+    let found = json_type(node).to_string();
+    let message = format!(
+        "Engine returned invalid data for '{}': field `{}` ({}): {}",
+        command, field, found, detail
+    );
+
+    ProtocolError {
+        command: command.to_string(),
+        field,
+        found,
+        detail,
+        message,
+    }
+}
+
+/// Deserialize `data` returned by `command`, reporting schema mismatches.
+pub fn parse<T: DeserializeOwned>(
+    app_handle: &AppHandle,
+    command: &str,
+    data: Value,
+) -> Result<T, String> {
+    serde_path_to_error::deserialize(&data).map_err(|e| {
+        let error = describe(command, &data, e.path(), e.inner().to_string());
+        tracing::warn!("{}", error.message);
+        let _ = app_handle.emit("engine-protocol-error", &error);
+        error.message
+    })
+}
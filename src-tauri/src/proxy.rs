@@ -0,0 +1,166 @@
+//! HTTP Proxy
+//!
+//! For networks that only reach the internet through an HTTP proxy. The
+//! proxy is part of the app settings (`proxy`: URL, username, bypass list);
+//! its password is kept in the OS keychain and set with `set_proxy_password`.
+//!
+//! - The engine gets `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` (and their
+//!   lowercase forms) at spawn, so a change reaches it at its next start.
+//! - The shell's own requests (Hive, datasets, FX rates, quotes, reports,
+//!   updates) go through `client()` or `apply`, which follow every settings
+//!   save.
+//! - The network check connects to the proxy instead of Trade Republic.
+//!
+//! Without an enabled proxy nothing is set, and the system's proxy
+//! environment, if any, applies as before.
+
+use crate::app_settings::ProxySettings;
+use crate::keychain;
+use std::sync::RwLock;
+use tauri::Url;
+use zeroize::Zeroizing;
+
+/// Keychain entry of the proxy password
+pub const PASSWORD_KEY: &str = "proxy_password";
+
+/// Shared client for the shell's requests, rebuilt by `configure`
+static CLIENT: RwLock<Option<reqwest::Client>> = RwLock::new(None);
+
+/// Enabled proxy, as applied by the last `configure`
+static CURRENT: RwLock<Option<ProxySettings>> = RwLock::new(None);
+
+/// Check a proxy URL as entered in the settings.
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid proxy URL {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Proxy URL must start with http:// or https://".to_string());
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("Proxy URL has no host: {}", url));
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err("Put the proxy username and password in their own fields".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a proxy password is stored
+pub fn has_password() -> bool {
+    matches!(keychain::get_secret(PASSWORD_KEY), Ok(Some(_)))
+}
+
+/// Store the proxy password; `None` or an empty one removes it.
+pub fn set_password(password: Option<&str>) -> Result<(), String> {
+    match password {
+        None | Some("") => keychain::delete_secret(PASSWORD_KEY),
+        Some(password) => keychain::set_secret(PASSWORD_KEY, password),
+    }
+}
+
+/// Proxy URL with the username and keychain password filled in
+fn url_with_credentials(settings: &ProxySettings) -> Result<Zeroizing<String>, String> {
+    let mut url = Url::parse(&settings.url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    if !settings.username.is_empty() {
+        let password = keychain::get_secret(PASSWORD_KEY)?.map(Zeroizing::new);
+        url.set_username(&settings.username)
+            .and_then(|_| url.set_password(password.as_deref().map(String::as_str)))
+            .map_err(|_| "Proxy URL cannot carry credentials".to_string())?;
+    }
+    Ok(Zeroizing::new(url.to_string()))
+}
+
+fn enabled(settings: &ProxySettings) -> Option<&ProxySettings> {
+    Some(settings).filter(|settings| settings.enabled && !settings.url.is_empty())
+}
+
+/// Environment variables that send the engine through the proxy
+pub fn engine_env(settings: &ProxySettings) -> Vec<(&'static str, String)> {
+    let Some(settings) = enabled(settings) else {
+        return vec![];
+    };
+    let url = match url_with_credentials(settings) {
+        Ok(url) => url,
+        Err(e) => {
+            tracing::warn!("Engine starts without the proxy: {}", e);
+            return vec![];
+        }
+    };
+    let bypass = settings.bypass.join(",");
+    let mut env = vec![];
+    for name in ["HTTPS_PROXY", "HTTP_PROXY", "https_proxy", "http_proxy"] {
+        env.push((name, url.to_string()));
+    }
+    if !bypass.is_empty() {
+        env.push(("NO_PROXY", bypass.clone()));
+        env.push(("no_proxy", bypass));
+    }
+    env
+}
+
+/// Route a client's requests through the configured proxy.
+pub fn apply(builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, String> {
+    let current = CURRENT.read().map_err(|e| e.to_string())?.clone();
+    let Some(settings) = current else {
+        return Ok(builder);
+    };
+    let url = url_with_credentials(&settings)?;
+    let proxy = reqwest::Proxy::all(url.as_str())
+        .map_err(|e| format!("Invalid proxy: {}", e))?
+        .no_proxy(reqwest::NoProxy::from_string(&settings.bypass.join(",")));
+    Ok(builder.proxy(proxy))
+}
+
+/// Shared HTTP client for the shell, through the proxy when one is set.
+pub fn client() -> reqwest::Client {
+    if let Some(client) = CLIENT.read().ok().and_then(|client| client.clone()) {
+        return client;
+    }
+    let client = apply(reqwest::Client::builder())
+        .and_then(|builder| {
+            builder
+                .build()
+                .map_err(|e| format!("Failed to create HTTP client: {}", e))
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!("Not using the proxy: {}", e);
+            reqwest::Client::new()
+        });
+    if let Ok(mut cached) = CLIENT.write() {
+        *cached = Some(client.clone());
+    }
+    client
+}
+
+/// Proxy URL for clients configured by URL, e.g. the updater
+pub fn url() -> Result<Option<Url>, String> {
+    let current = CURRENT.read().map_err(|e| e.to_string())?.clone();
+    current
+        .map(|settings| {
+            let url = url_with_credentials(&settings)?;
+            Url::parse(&url).map_err(|e| format!("Invalid proxy URL: {}", e))
+        })
+        .transpose()
+}
+
+/// `host:port` of the proxy, which the network check connects to instead
+/// of Trade Republic
+pub fn address() -> Option<String> {
+    let settings = CURRENT.read().ok()?.clone()?;
+    let url = Url::parse(&settings.url).ok()?;
+    Some(format!(
+        "{}:{}",
+        url.host_str()?,
+        url.port_or_known_default()?
+    ))
+}
+
+/// Apply a settings or password change to the shell's requests.
+pub fn configure(settings: &ProxySettings) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = enabled(settings).cloned();
+    }
+    // Rebuilt on next use
+    if let Ok(mut client) = CLIENT.write() {
+        *client = None;
+    }
+}
@@ -15,55 +15,165 @@
 //!
 //! ## Mutex-Protected State
 //! All mutable state is protected by `tokio::sync::Mutex` (async-aware):
-//! - `child`: Exclusive access to child process stdin writes
+//! - `writer`: Sender half of the stdin writer channel (held only to clone it)
 //! - `pending`: Maps command IDs to response channels
-//! - `connected`: Engine connection status flag
 //! - `version`: Engine version from ready signal
 //!
+//! Connection state lives in a `tokio::sync::watch` channel (`state`) rather
+//! than a mutex so the shell can subscribe to transitions and forward them to
+//! the frontend as `engine-state-changed` events.
+//!
+//! ## Connection State Machine
+//! ```text
+//! Idle ──first command──> Spawning
+//! Spawning ──ready──> Ready <──response── Degraded
+//!                       │ timeout/write error ↑
+//!                       └─────────────────────┘
+//! any ──restart──> Restarting ──ready──> Ready
+//! any ──process exit──> Dead
+//! ```
+//! Commands are accepted in `Ready` and `Degraded`.
+//!
+//! ## Launch Buffering
+//! The frontend fires its first queries before the sidecar has printed its
+//! ready signal. While the engine is `Spawning` or `Restarting`,
+//! `is_connected` (and therefore `send_command`) waits on the state channel
+//! for up to the ready wait (`set_ready_wait`) instead of failing at once.
+//! `Dead` never waits.
+//!
+//! ## Lazy Start
+//! With `lazy_engine_start` the sidecar is not spawned at launch. The engine
+//! sits in `Idle` holding a starter (`set_lazy_start`); the first
+//! `is_connected` call takes it, moves to `Spawning` and runs it, then waits
+//! like any launch. The window opens without paying the Python startup cost.
+//!
 //! ## Request/Response Matching
 //! The pending request pattern ensures correct response routing:
 //! 1. `send_command` generates unique ID, creates oneshot channel, inserts into `pending`
-//! 2. Command is written to stdin (under `child` lock)
+//! 2. Command is handed to the stdin writer task, which acknowledges the write
 //! 3. `handle_response` extracts ID, removes from `pending`, sends response
 //! 4. `send_command` awaits on channel with timeout
 //!
 //! **Critical invariant**: Response IDs MUST match request IDs. The Python engine
 //! echoes the request ID in responses. Mismatched IDs would cause orphaned channels.
 //!
+//! ## Stdin Writer Task
+//! The `CommandChild` is owned by a dedicated task fed through an mpsc channel.
+//! A slow or blocked write therefore only delays the commands queued behind it
+//! in the channel, never an unrelated caller waiting on a mutex. The same task
+//! serializes shutdown: a `Shutdown` message is processed after every write
+//! queued before it, then the child is killed.
+//!
 //! ## Lock Ordering
 //! When acquiring multiple locks, always follow this order to prevent deadlock:
-//! 1. `child` (if writing to stdin)
-//! 2. `pending` (for channel management)
-//! 3. `connected`/`version` (status checks)
+//! 1. `writer` (to clone the sender; released before awaiting)
+//! 2. `inflight` (coalescing bookkeeping; never held across an await)
+//! 3. `pending` (for channel management)
+//! 4. `version` (status checks)
+//!
+//! ## In-flight Deduplication
+//! Read-only commands listed in `COALESCED_COMMANDS` are keyed by command name
+//! plus serialized payload. While one is pending, identical calls register as
+//! waiters in `inflight` instead of writing to stdin again; the leader fans its
+//! result out to every waiter. The leader always removes the key before
+//! fanning out, so a late caller either joins the waiters or starts a fresh
+//! request - never waits on a request that has already completed.
+//!
+//! ## Response Cache
+//! The dashboard, positions and overlap commands are also answered from a
+//! TTL cache (`response_cache`) under the same key before coalescing. It is
+//! cleared on `portfolio-updated` and whenever a sidecar becomes ready, and
+//! scopes of it are dropped on the engine's `data_invalidated` events.
+//!
+//! ## Secret Payloads
+//! Every serialized command line lives in a `Zeroizing` buffer that is wiped
+//! once the writer task has written it. `send_secret_command` (PINs, 2FA
+//! codes) additionally serializes straight into such a buffer, skips
+//! coalescing and records only an empty payload in IPC traces, so the secret
+//! is never copied into a `Value` or logged.
 //!
 //! ## Cleanup on Failure
 //! All error paths in `send_command` remove the pending entry before returning,
 //! preventing memory leaks from orphaned oneshot channels.
 
+use crate::ipc_trace::{TracePlayer, TraceRecorder};
+use crate::response_cache::{ResponseCache, Scope};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
-use tauri::async_runtime::Mutex;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tauri::async_runtime::{JoinHandle, Mutex};
 use tauri_plugin_shell::process::CommandChild;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio::time::{timeout, Duration};
+use zeroize::Zeroizing;
+
+/// Default timeout for command responses
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 30;
 
-/// Timeout for command responses
-const COMMAND_TIMEOUT_SECS: u64 = 30;
+/// Default time a command waits for the ready signal during launch
+const DEFAULT_READY_WAIT_SECS: u64 = 10;
 
 /// Maximum payload size in bytes (10MB)
 const MAX_PAYLOAD_SIZE: usize = 10 * 1024 * 1024;
 
+/// Initial size of a secret payload buffer, large enough that serializing a
+/// login never reallocates (and leaves an unwiped copy behind)
+const SECRET_PAYLOAD_CAPACITY: usize = 1024;
+
 /// Maximum command name length
 const MAX_COMMAND_LEN: usize = 64;
 
+/// Read-only commands whose identical concurrent calls share one request
+const COALESCED_COMMANDS: &[&str] = &[
+    "get_dashboard_data",
+    "get_positions",
+    "get_true_holdings",
+    "get_overlap_analysis",
+    "get_health",
+];
+
+/// Commands remembered for crash reports
+const RECENT_COMMANDS: usize = 20;
+
+/// Callers waiting on an identical in-flight request
+type Waiters = Vec<oneshot::Sender<Result<EngineResponse, String>>>;
+
+/// Writes that may queue for the stdin writer before senders wait
+const WRITER_QUEUE_SIZE: usize = 64;
+
+/// Messages handled by the stdin writer task
+enum StdinMessage {
+    /// Write a line and report the outcome
+    Write {
+        line: Zeroizing<Vec<u8>>,
+        ack: oneshot::Sender<Result<(), String>>,
+    },
+    /// Kill the child after all previously queued writes
+    Shutdown { done: oneshot::Sender<()> },
+}
+
 /// Ready signal from Python engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadySignal {
     pub status: String,
     pub version: String,
     pub pid: u32,
+    /// Session the engine tags its `system_logs` rows with
+    #[serde(default, alias = "sessionId")]
+    pub session_id: Option<String>,
+}
+
+/// A command written to the sidecar, kept for crash reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentCommand {
+    pub id: u64,
+    pub command: String,
+    pub sent_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Response from Python engine
@@ -84,49 +194,280 @@ pub struct EngineError {
     pub message: String,
 }
 
+/// Lifecycle state of a sidecar connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EngineState {
+    /// Not started yet; the first command spawns it (lazy start)
+    Idle,
+    /// Process spawned, waiting for the ready signal
+    Spawning,
+    /// Ready signal received, commands flowing normally
+    Ready,
+    /// Connected, but recent commands timed out or failed to write
+    Degraded,
+    /// Being stopped and respawned by the shell
+    Restarting,
+    /// Process exited or could not be spawned
+    Dead,
+}
+
+impl EngineState {
+    /// Whether commands may be sent in this state
+    pub fn accepts_commands(&self) -> bool {
+        matches!(self, EngineState::Ready | EngineState::Degraded)
+    }
+}
+
+/// Current state plus the reason for the last transition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineStatus {
+    pub state: EngineState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub since: String,
+}
+
+impl EngineStatus {
+    fn new(state: EngineState, reason: Option<String>) -> Self {
+        Self {
+            state,
+            reason,
+            since: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Error for commands issued while the engine cannot serve them. Rendered with
+/// a stable `[ENGINE_UNAVAILABLE]` code so the UI can show an explicit
+/// unavailable state instead of a generic failure.
+#[derive(Debug, Clone)]
+pub struct EngineUnavailable {
+    pub state: EngineState,
+    pub reason: Option<String>,
+}
+
+impl fmt::Display for EngineUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[ENGINE_UNAVAILABLE] Analytics engine is {:?}", self.state)?;
+        if let Some(reason) = &self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<EngineUnavailable> for String {
+    fn from(error: EngineUnavailable) -> Self {
+        error.to_string()
+    }
+}
+
 /// Manages communication with Python sidecar
 pub struct PythonEngine {
-    /// Child process for writing to stdin
-    child: Mutex<Option<CommandChild>>,
+    /// Channel to the task that owns the child process and writes stdin
+    writer: Mutex<Option<mpsc::Sender<StdinMessage>>>,
     /// Pending requests waiting for responses
     pending: Mutex<HashMap<u64, oneshot::Sender<EngineResponse>>>,
+    /// Coalesced read requests in flight, keyed by command + payload
+    inflight: Mutex<HashMap<String, Waiters>>,
+    /// Recent read responses, keyed like `inflight`
+    cache: ResponseCache,
     /// Next command ID
     next_id: AtomicU64,
-    /// Whether engine is connected
-    connected: Mutex<bool>,
+    /// Connection state machine
+    state: watch::Sender<EngineStatus>,
     /// Engine version (from ready signal)
     version: Mutex<Option<String>>,
+    /// Engine session ID (from ready signal)
+    session_id: Mutex<Option<String>>,
+    /// Last `RECENT_COMMANDS` commands written to the sidecar
+    recent_commands: Mutex<VecDeque<RecentCommand>>,
+    /// Records command/response pairs when IPC tracing is enabled
+    recorder: OnceLock<TraceRecorder>,
+    /// Answers commands from a recorded trace instead of the sidecar
+    player: OnceLock<TracePlayer>,
+    /// How long commands wait for the ready signal, in milliseconds
+    ready_wait_ms: AtomicU64,
+    /// How long a command waits for its response, in seconds
+    command_timeout_secs: AtomicU64,
+    /// Stdout reader task of the current sidecar; it ends when the process
+    /// has exited
+    reader: Mutex<Option<JoinHandle<()>>>,
+    /// Spawns the sidecar on first use while `Idle`; never held across an
+    /// await
+    starter: std::sync::Mutex<Option<LazyStart>>,
 }
 
+/// Spawns the sidecar of an engine started lazily
+pub type LazyStart = Box<dyn FnOnce() + Send>;
+
 impl PythonEngine {
     /// Create a new Python engine manager
     pub fn new() -> Self {
         Self {
-            child: Mutex::new(None),
+            writer: Mutex::new(None),
             pending: Mutex::new(HashMap::new()),
+            inflight: Mutex::new(HashMap::new()),
+            cache: ResponseCache::default(),
             next_id: AtomicU64::new(1),
-            connected: Mutex::new(false),
+            state: watch::channel(EngineStatus::new(EngineState::Spawning, None)).0,
             version: Mutex::new(None),
+            session_id: Mutex::new(None),
+            recent_commands: Mutex::new(VecDeque::new()),
+            recorder: OnceLock::new(),
+            player: OnceLock::new(),
+            ready_wait_ms: AtomicU64::new(DEFAULT_READY_WAIT_SECS * 1000),
+            command_timeout_secs: AtomicU64::new(DEFAULT_COMMAND_TIMEOUT_SECS),
+            reader: Mutex::new(None),
+            starter: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Defer spawning to the first command: park the engine in `Idle` with
+    /// `start`, which spawns the sidecar.
+    pub fn set_lazy_start(&self, start: LazyStart) {
+        if let Ok(mut starter) = self.starter.lock() {
+            *starter = Some(start);
         }
+        self.transition(EngineState::Idle, None);
     }
 
-    /// Set the child process (called when sidecar is spawned)
+    /// Run the lazy starter if the engine is still `Idle`.
+    fn start_if_idle(&self) {
+        if self.status().state != EngineState::Idle {
+            return;
+        }
+        let start = self.starter.lock().ok().and_then(|mut starter| starter.take());
+        if let Some(start) = start {
+            tracing::info!("Starting engine on first use");
+            self.transition(EngineState::Spawning, None);
+            start();
+        }
+    }
+
+    /// Set the child process (called when sidecar is spawned).
+    ///
+    /// Ownership moves into a dedicated writer task; any previous writer is
+    /// dropped, which ends its task once its queue drains.
     pub async fn set_child(&self, child: CommandChild) {
-        let mut guard = self.child.lock().await;
-        *guard = Some(child);
+        let (tx, rx) = mpsc::channel(WRITER_QUEUE_SIZE);
+        tauri::async_runtime::spawn(run_stdin_writer(child, rx));
+        *self.writer.lock().await = Some(tx);
+    }
+
+    /// Remember the stdout reader task of the current sidecar.
+    pub async fn set_reader(&self, reader: JoinHandle<()>) {
+        *self.reader.lock().await = Some(reader);
+    }
+
+    /// Wait until the sidecar's stdout reader has seen the process exit, so
+    /// files the engine held open can be replaced. Returns `false` on timeout.
+    pub async fn wait_for_exit(&self, wait: Duration) -> bool {
+        let reader = self.reader.lock().await.take();
+        match reader {
+            Some(reader) => timeout(wait, reader).await.is_ok(),
+            None => true,
+        }
+    }
+
+    /// Append every command/response pair to a trace file.
+    pub fn set_recorder(&self, recorder: TraceRecorder) {
+        let _ = self.recorder.set(recorder);
+    }
+
+    /// Answer commands from a recorded trace. No sidecar is needed.
+    pub fn set_player(&self, player: TracePlayer) {
+        let _ = self.player.set(player);
+    }
+
+    /// Stop the sidecar after in-flight writes complete.
+    pub async fn shutdown(&self) {
+        let writer = self.writer.lock().await.take();
+        if let Some(writer) = writer {
+            let (done, done_rx) = oneshot::channel();
+            if writer.send(StdinMessage::Shutdown { done }).await.is_ok() {
+                let _ = done_rx.await;
+            }
+        }
+        self.transition(EngineState::Dead, Some("shut down by shell".to_string()));
     }
 
     /// Mark engine as connected with version
     pub async fn set_connected(&self, version: String) {
-        let mut connected = self.connected.lock().await;
-        *connected = true;
         let mut ver = self.version.lock().await;
         *ver = Some(version);
+        // A restarted sidecar may be looking at different data
+        self.cache.clear();
+        self.transition(EngineState::Ready, None);
     }
 
-    /// Check if engine is connected
+    /// Drop cached read responses, e.g. after the portfolio changed
+    pub fn invalidate_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Drop the cached read responses covering `scope`
+    pub fn invalidate_cache_scope(&self, scope: Scope) {
+        self.cache.invalidate(scope);
+    }
+
+    /// Set how long commands issued before the ready signal wait for it
+    pub fn set_ready_wait(&self, wait: Duration) {
+        self.ready_wait_ms.store(wait.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Set how long a command waits for its response
+    pub fn set_command_timeout(&self, timeout: Duration) {
+        self.command_timeout_secs.store(timeout.as_secs().max(1), Ordering::Relaxed);
+    }
+
+    /// Check if engine is connected. While the engine is still starting,
+    /// waits up to the ready wait for the ready signal. An `Idle` engine is
+    /// started first.
     pub async fn is_connected(&self) -> bool {
-        *self.connected.lock().await
+        self.start_if_idle();
+        let mut rx = self.state.subscribe();
+        let wait = Duration::from_millis(self.ready_wait_ms.load(Ordering::Relaxed));
+        let settled = rx.wait_for(|status| {
+            status.state.accepts_commands() || status.state == EngineState::Dead
+        });
+
+        match timeout(wait, settled).await {
+            Ok(Ok(status)) => status.state.accepts_commands(),
+            _ => false,
+        }
+    }
+
+    /// Current connection state
+    pub fn status(&self) -> EngineStatus {
+        self.state.borrow().clone()
+    }
+
+    /// Error describing why the engine cannot take commands right now
+    pub fn unavailable(&self) -> EngineUnavailable {
+        let status = self.status();
+        EngineUnavailable {
+            state: status.state,
+            reason: status.reason,
+        }
+    }
+
+    /// Subscribe to state transitions
+    pub fn subscribe_state(&self) -> watch::Receiver<EngineStatus> {
+        self.state.subscribe()
+    }
+
+    /// Move to `state`, recording `reason`. No-op if already in `state`.
+    pub fn transition(&self, state: EngineState, reason: Option<String>) {
+        self.state.send_if_modified(|current| {
+            if current.state == state {
+                return false;
+            }
+            *current = EngineStatus::new(state, reason);
+            true
+        });
     }
 
     /// Get engine version
@@ -134,8 +475,40 @@ impl PythonEngine {
         self.version.lock().await.clone()
     }
 
+    pub async fn set_session_id(&self, session_id: Option<String>) {
+        *self.session_id.lock().await = session_id;
+    }
+
+    pub async fn session_id(&self) -> Option<String> {
+        self.session_id.lock().await.clone()
+    }
+
+    /// Commands most recently written to the sidecar, oldest first
+    pub async fn recent_commands(&self) -> Vec<RecentCommand> {
+        self.recent_commands.lock().await.iter().cloned().collect()
+    }
+
+    /// Send a command and return its data, turning an unsuccessful response
+    /// into `Err` with the engine's message.
+    pub async fn request(&self, command: &str, payload: Value) -> Result<Value, String> {
+        let response = self.send_command(command, payload).await?;
+        if response.success {
+            return response
+                .data
+                .ok_or_else(|| format!("No data in {} response", command));
+        }
+        Err(response
+            .error
+            .map(|e| e.message)
+            .unwrap_or_else(|| format!("{} failed", command)))
+    }
+
     /// Send a command to the Python engine
     ///
+    /// Identical concurrent calls to read-only commands are coalesced onto a
+    /// single engine request, and recent responses to some of them are served
+    /// from the response cache (see module docs).
+    ///
     /// # Validation
     /// - Command must be 1-64 lowercase chars (letters, digits, underscores)
     /// - Command must start with a lowercase letter
@@ -144,6 +517,127 @@ impl PythonEngine {
         &self,
         command: &str,
         payload: Value,
+    ) -> Result<EngineResponse, String> {
+        if !COALESCED_COMMANDS.contains(&command) {
+            return self.dispatch(command, payload).await;
+        }
+
+        let key = format!("{}:{}", command, payload);
+        if let Some(response) = self.cache.get(&key) {
+            return Ok(response);
+        }
+
+        let waiter = {
+            let mut inflight = self.inflight.lock().await;
+            match inflight.get_mut(&key) {
+                Some(waiters) => {
+                    let (tx, rx) = oneshot::channel();
+                    waiters.push(tx);
+                    Some(rx)
+                }
+                None => {
+                    inflight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        if let Some(rx) = waiter {
+            // The leader applies its own timeout; the margin only guards
+            // against a leader that was cancelled before fanning out.
+            let command_timeout = self.command_timeout_secs.load(Ordering::Relaxed);
+            return match timeout(Duration::from_secs(command_timeout + 5), rx).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(_)) => Err("Coalesced request was dropped".to_string()),
+                Err(_) => {
+                    self.inflight.lock().await.remove(&key);
+                    Err(format!(
+                        "Command timed out after {} seconds",
+                        command_timeout
+                    ))
+                }
+            };
+        }
+
+        let generation = self.cache.generation();
+        let result = self.dispatch(command, payload).await;
+        if let Ok(response) = &result {
+            self.cache.insert(command, &key, response, generation);
+        }
+
+        let waiters = self.inflight.lock().await.remove(&key).unwrap_or_default();
+        for waiter in waiters {
+            let _ = waiter.send(result.clone());
+        }
+
+        result
+    }
+
+    /// Send a command whose payload carries secrets, e.g. a PIN or 2FA code.
+    /// Never coalesced; the payload is serialized into a zeroized buffer and
+    /// replaced by `{}` in IPC traces.
+    pub async fn send_secret_command<T: Serialize>(
+        &self,
+        command: &str,
+        payload: &T,
+    ) -> Result<EngineResponse, String> {
+        let mut serialized = Zeroizing::new(Vec::with_capacity(SECRET_PAYLOAD_CAPACITY));
+        serde_json::to_writer(&mut *serialized, payload)
+            .map_err(|e| format!("Failed to serialize payload: {}", e))?;
+
+        let started = Instant::now();
+        let result = match self.player.get() {
+            Some(player) => {
+                let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+                player.respond(id, command, &json!({}))
+            }
+            None => self.write_command(command, serialized).await,
+        };
+
+        crate::telemetry::record_engine_command(command, started.elapsed(), &result);
+        if let Some(recorder) = self.recorder.get() {
+            recorder.record(command, &json!({}), started.elapsed().as_millis() as u64, &result);
+        }
+
+        result
+    }
+
+    /// Send a single command (no coalescing), via replay or the live sidecar,
+    /// recording the exchange if tracing is enabled.
+    async fn dispatch(&self, command: &str, payload: Value) -> Result<EngineResponse, String> {
+        let started = Instant::now();
+        let traced_payload = self.recorder.get().map(|_| payload.clone());
+
+        let result = match self.player.get() {
+            Some(player) => {
+                let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+                player.respond(id, command, &payload)
+            }
+            None => self.dispatch_live(command, payload).await,
+        };
+
+        crate::telemetry::record_engine_command(command, started.elapsed(), &result);
+        if let (Some(recorder), Some(payload)) = (self.recorder.get(), traced_payload) {
+            recorder.record(command, &payload, started.elapsed().as_millis() as u64, &result);
+        }
+
+        result
+    }
+
+    /// Serialize `payload` and send it to the live sidecar
+    async fn dispatch_live(&self, command: &str, payload: Value) -> Result<EngineResponse, String> {
+        let mut serialized = Zeroizing::new(Vec::new());
+        serde_json::to_writer(&mut *serialized, &payload)
+            .map_err(|e| format!("Failed to serialize payload: {}", e))?;
+        self.write_command(command, serialized).await
+    }
+
+    /// Validate, write and await a single command on the sidecar. `payload`
+    /// is the serialized JSON payload.
+    async fn write_command(
+        &self,
+        command: &str,
+        payload: Zeroizing<Vec<u8>>,
     ) -> Result<EngineResponse, String> {
         // === Command name validation ===
         if command.is_empty() || command.len() > MAX_COMMAND_LEN {
@@ -171,23 +665,32 @@ impl PythonEngine {
         }
 
         // === Payload size validation ===
-        let payload_str = serde_json::to_string(&payload)
-            .map_err(|e| format!("Failed to serialize payload: {}", e))?;
-        if payload_str.len() > MAX_PAYLOAD_SIZE {
+        if payload.len() > MAX_PAYLOAD_SIZE {
             return Err(format!(
                 "Payload too large: {} bytes (max {} bytes)",
-                payload_str.len(),
+                payload.len(),
                 MAX_PAYLOAD_SIZE
             ));
         }
 
         // Check if connected
         if !self.is_connected().await {
-            return Err("Python engine not connected".to_string());
+            return Err(self.unavailable().into());
         }
 
         // Generate command ID
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        {
+            let mut recent = self.recent_commands.lock().await;
+            if recent.len() == RECENT_COMMANDS {
+                recent.pop_front();
+            }
+            recent.push_back(RecentCommand {
+                id,
+                command: command.to_string(),
+                sent_at: chrono::Utc::now(),
+            });
+        }
 
         // Create response channel
         let (tx, rx) = oneshot::channel();
@@ -198,31 +701,42 @@ impl PythonEngine {
             pending.insert(id, tx);
         }
 
-        // Build command JSON
-        let cmd = json!({
-            "id": id,
-            "command": command,
-            "payload": payload
-        });
+        // Build the command line around the payload without copying it into
+        // a `Value`; the name was validated above and needs no escaping
+        let header = format!("{{\"id\":{},\"command\":\"{}\",\"payload\":", id, command);
+        let mut line = Zeroizing::new(Vec::with_capacity(header.len() + payload.len() + 2));
+        line.extend_from_slice(header.as_bytes());
+        line.extend_from_slice(&payload);
+        line.extend_from_slice(b"}\n");
+        drop(payload);
 
-        // Send to stdin via child.write()
-        {
-            let mut child_guard = self.child.lock().await;
-            if let Some(ref mut child) = *child_guard {
-                let msg = format!("{}\n", cmd);
-                if let Err(e) = child.write(msg.as_bytes()) {
-                    // Remove pending request
-                    self.pending.lock().await.remove(&id);
-                    return Err(format!("Failed to write to stdin: {}", e));
-                }
-            } else {
-                self.pending.lock().await.remove(&id);
-                return Err("Child process not available".to_string());
-            }
+        // Hand the line to the stdin writer task
+        let writer = self.writer.lock().await.clone();
+        let Some(writer) = writer else {
+            self.pending.lock().await.remove(&id);
+            return Err("Child process not available".to_string());
+        };
+
+        let (ack, ack_rx) = oneshot::channel();
+        let written = match writer.send(StdinMessage::Write { line, ack }).await {
+            Ok(()) => ack_rx
+                .await
+                .unwrap_or_else(|_| Err("stdin writer stopped".to_string())),
+            Err(_) => Err("stdin writer stopped".to_string()),
+        };
+        if let Err(e) = written {
+            // Remove pending request
+            self.pending.lock().await.remove(&id);
+            self.transition(
+                EngineState::Degraded,
+                Some(format!("stdin write failed: {}", e)),
+            );
+            return Err(format!("Failed to write to stdin: {}", e));
         }
 
         // Wait for response with timeout
-        match timeout(Duration::from_secs(COMMAND_TIMEOUT_SECS), rx).await {
+        let command_timeout = self.command_timeout_secs.load(Ordering::Relaxed);
+        match timeout(Duration::from_secs(command_timeout), rx).await {
             Ok(Ok(response)) => Ok(response),
             Ok(Err(_)) => {
                 self.pending.lock().await.remove(&id);
@@ -230,9 +744,13 @@ impl PythonEngine {
             }
             Err(_) => {
                 self.pending.lock().await.remove(&id);
+                self.transition(
+                    EngineState::Degraded,
+                    Some(format!("'{}' timed out", command)),
+                );
                 Err(format!(
                     "Command timed out after {} seconds",
-                    COMMAND_TIMEOUT_SECS
+                    command_timeout
                 ))
             }
         }
@@ -240,6 +758,11 @@ impl PythonEngine {
 
     /// Handle a response from the Python engine
     pub async fn handle_response(&self, response: EngineResponse) {
+        // Any response proves the engine is alive again
+        if self.state.borrow().state == EngineState::Degraded {
+            self.transition(EngineState::Ready, None);
+        }
+
         let mut pending = self.pending.lock().await;
         if let Some(tx) = pending.remove(&response.id) {
             let _ = tx.send(response);
@@ -267,6 +790,25 @@ impl PythonEngine {
     }
 }
 
+/// Owns the child process and performs every stdin write in order.
+async fn run_stdin_writer(mut child: CommandChild, mut rx: mpsc::Receiver<StdinMessage>) {
+    while let Some(message) = rx.recv().await {
+        match message {
+            StdinMessage::Write { line, ack } => {
+                let result = child.write(&line).map_err(|e| e.to_string());
+                let _ = ack.send(result);
+            }
+            StdinMessage::Shutdown { done } => {
+                if let Err(e) = child.kill() {
+                    tracing::warn!("Failed to kill sidecar: {}", e);
+                }
+                let _ = done.send(());
+                return;
+            }
+        }
+    }
+}
+
 /// Event from Python engine (emitted during long-running operations)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EngineEvent {
@@ -287,3 +829,74 @@ impl Default for PythonEngine {
         Self::new()
     }
 }
+
+/// Which sidecar process an engine instance talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineRole {
+    /// Serves interactive queries (dashboard, positions, auth)
+    Primary,
+    /// Dedicated to long pipeline/decomposition jobs
+    Worker,
+}
+
+impl EngineRole {
+    /// Value passed to the sidecar as `PRISM_ENGINE_ROLE`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EngineRole::Primary => "primary",
+            EngineRole::Worker => "worker",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EngineRole::Primary => "Engine",
+            EngineRole::Worker => "Worker",
+        }
+    }
+
+    /// Tauri event emitted when this sidecar signals ready
+    pub fn ready_event(&self) -> &'static str {
+        match self {
+            EngineRole::Primary => "engine-ready",
+            EngineRole::Worker => "worker-ready",
+        }
+    }
+}
+
+/// Commands routed to the worker sidecar when one is connected
+const WORKER_COMMANDS: &[&str] = &["run_pipeline"];
+
+/// Primary engine plus an optional worker for long-running jobs.
+///
+/// With a single Python process, a multi-minute pipeline run holds the only
+/// stdin and every dashboard query queues behind it. Routing long jobs to a
+/// second process keeps the primary responsive.
+pub struct EnginePool {
+    primary: Arc<PythonEngine>,
+    worker: Arc<PythonEngine>,
+}
+
+impl EnginePool {
+    pub fn new(primary: Arc<PythonEngine>, worker: Arc<PythonEngine>) -> Self {
+        Self { primary, worker }
+    }
+
+    /// Engine that should handle `command`: the worker for long jobs when it
+    /// is connected, otherwise the primary.
+    pub async fn for_command(&self, command: &str) -> Arc<PythonEngine> {
+        if WORKER_COMMANDS.contains(&command) && self.worker.is_connected().await {
+            self.worker.clone()
+        } else {
+            self.primary.clone()
+        }
+    }
+
+    pub fn primary(&self) -> Arc<PythonEngine> {
+        self.primary.clone()
+    }
+
+    pub fn worker(&self) -> Arc<PythonEngine> {
+        self.worker.clone()
+    }
+}
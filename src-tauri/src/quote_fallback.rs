@@ -0,0 +1,402 @@
+//! Native Quote Fallback
+//!
+//! The engine prices instruments through yfinance. When a quote comes back
+//! empty, yfinance prints a "possibly delisted" warning, which is kept out of
+//! the engine log; the symbols in those warnings are recorded here as missing
+//! quotes (`get_missing_quotes`).
+//!
+//! `refresh_quote` fixes one of them without a full pipeline run: it fetches
+//! the last price from Yahoo, falling back to Stooq, converts it to euros
+//! (the currency the engine stores prices in) and writes it to the
+//! instrument's positions. Instruments the engine has a quote for are left
+//! to the regular sync.
+
+use crate::db_recovery;
+use crate::fx_rates;
+use crate::proxy;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const YAHOO_SEARCH_URL: &str = "https://query2.finance.yahoo.com/v1/finance/search";
+const YAHOO_CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const STOOQ_QUOTE_URL: &str = "https://stooq.com/q/l/";
+
+/// Timeout for each quote request
+const FETCH_TIMEOUT_SECS: u64 = 10;
+
+/// How long a write waits while the engine holds the database
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Yahoo rejects requests without a browser-like user agent
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; PortfolioPrism)";
+
+/// Symbols the engine found no quote for, with when that was last reported
+static MISSING: Mutex<BTreeMap<String, DateTime<Utc>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingQuote {
+    /// Symbol as the engine requested it
+    pub symbol: String,
+    /// Instrument the symbol belongs to, when the database knows it
+    pub isin: Option<String>,
+    pub last_reported: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuoteSource {
+    Yahoo,
+    Stooq,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteRefresh {
+    pub isin: String,
+    pub symbol: String,
+    pub source: QuoteSource,
+    /// Price as quoted, in `quoted_currency`
+    pub quoted_price: f64,
+    pub quoted_currency: String,
+    /// Price written to the positions, in euros
+    pub price: f64,
+    pub as_of: Option<DateTime<Utc>>,
+    /// Portfolios holding the instrument
+    pub portfolio_ids: Vec<u32>,
+}
+
+struct Quote {
+    source: QuoteSource,
+    price: f64,
+    currency: String,
+    as_of: Option<DateTime<Utc>>,
+}
+
+/// Record the symbol of a yfinance "possibly delisted" line from stderr,
+/// e.g. `$VWCE.DE: possibly delisted; no price data found (period=1d)`.
+pub fn note_stderr(line: &str) {
+    let Some((prefix, _)) = line.split_once(": possibly delisted") else {
+        return;
+    };
+    let symbol = prefix
+        .rsplit([' ', '['])
+        .next()
+        .unwrap_or_default()
+        .trim_matches(|c: char| matches!(c, '$' | '\'' | '"' | ']' | '-'));
+    if symbol.is_empty() {
+        return;
+    }
+    if let Ok(mut missing) = MISSING.lock() {
+        missing.insert(symbol.to_uppercase(), Utc::now());
+    }
+}
+
+fn open(data_dir: &Path, flags: OpenFlags) -> Result<Connection, String> {
+    let path = db_recovery::db_path(data_dir);
+    if !path.exists() {
+        return Err("No portfolio data has been saved yet".to_string());
+    }
+    let connection = Connection::open_with_flags(&path, flags | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| format!("Cannot open database: {}", e))?;
+    connection
+        .busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Cannot open database: {}", e))?;
+    Ok(connection)
+}
+
+fn symbol_of(connection: &Connection, isin: &str) -> Option<String> {
+    connection
+        .query_row("SELECT symbol FROM assets WHERE isin = ?1", [isin], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .optional()
+        .ok()
+        .flatten()
+        .flatten()
+        .filter(|symbol| !symbol.trim().is_empty())
+}
+
+fn isin_of(connection: &Connection, symbol: &str) -> Option<String> {
+    connection
+        .query_row(
+            "SELECT isin FROM assets WHERE UPPER(symbol) = ?1 OR isin = ?1",
+            [symbol],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()
+}
+
+/// Missing quotes reported by the engine this session
+pub fn missing(data_dir: &Path) -> Vec<MissingQuote> {
+    let reported: Vec<(String, DateTime<Utc>)> = MISSING
+        .lock()
+        .map(|missing| missing.iter().map(|(s, at)| (s.clone(), *at)).collect())
+        .unwrap_or_default();
+    let connection = open(data_dir, OpenFlags::SQLITE_OPEN_READ_ONLY).ok();
+    reported
+        .into_iter()
+        .map(|(symbol, last_reported)| MissingQuote {
+            isin: connection.as_ref().and_then(|c| isin_of(c, &symbol)),
+            symbol,
+            last_reported,
+        })
+        .collect()
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    proxy::apply(reqwest::Client::builder())?
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooSearch {
+    #[serde(default)]
+    quotes: Vec<YahooSearchQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooSearchQuote {
+    symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooChart {
+    chart: YahooChartBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooChartBody {
+    #[serde(default)]
+    result: Option<Vec<YahooChartResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooChartResult {
+    meta: YahooMeta,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooMeta {
+    regular_market_price: Option<f64>,
+    currency: Option<String>,
+    regular_market_time: Option<i64>,
+}
+
+/// Yahoo symbol for an ISIN without one in the database
+async fn yahoo_symbol(client: &reqwest::Client, isin: &str) -> Result<String, String> {
+    let search: YahooSearch = client
+        .get(YAHOO_SEARCH_URL)
+        .query(&[("q", isin), ("quotesCount", "1"), ("newsCount", "0")])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Yahoo search failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Yahoo search response: {}", e))?;
+    search
+        .quotes
+        .into_iter()
+        .next()
+        .map(|quote| quote.symbol)
+        .ok_or_else(|| format!("Yahoo knows no symbol for {}", isin))
+}
+
+async fn yahoo_quote(client: &reqwest::Client, symbol: &str) -> Result<Quote, String> {
+    let chart: YahooChart = client
+        .get(format!("{}/{}", YAHOO_CHART_URL, symbol))
+        .query(&[("range", "5d"), ("interval", "1d")])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Yahoo quote failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Yahoo quote: {}", e))?;
+    let meta = chart
+        .chart
+        .result
+        .and_then(|results| results.into_iter().next())
+        .map(|result| result.meta)
+        .ok_or_else(|| format!("Yahoo has no quote for {}", symbol))?;
+    let price = meta
+        .regular_market_price
+        .filter(|price| *price > 0.0)
+        .ok_or_else(|| format!("Yahoo has no price for {}", symbol))?;
+    Ok(Quote {
+        source: QuoteSource::Yahoo,
+        price,
+        currency: meta.currency.unwrap_or_else(|| "EUR".to_string()),
+        as_of: meta
+            .regular_market_time
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single()),
+    })
+}
+
+/// Stooq symbol and quote currency for a Yahoo symbol; Stooq only covers
+/// some exchanges
+fn stooq_symbol(symbol: &str) -> Option<(String, &'static str)> {
+    let (base, suffix) = symbol.rsplit_once('.').unwrap_or((symbol, ""));
+    let (market, currency) = match suffix.to_uppercase().as_str() {
+        "" => ("us", "USD"),
+        "DE" | "F" => ("de", "EUR"),
+        "L" => ("uk", "GBp"),
+        "T" => ("jp", "JPY"),
+        "HK" => ("hk", "HKD"),
+        _ => return None,
+    };
+    Some((format!("{}.{}", base.to_lowercase(), market), currency))
+}
+
+async fn stooq_quote(client: &reqwest::Client, symbol: &str) -> Result<Quote, String> {
+    let (stooq, currency) =
+        stooq_symbol(symbol).ok_or_else(|| format!("Stooq does not cover {}", symbol))?;
+    // Symbol,Date,Time,Close; missing fields read N/D
+    let body = client
+        .get(STOOQ_QUOTE_URL)
+        .query(&[
+            ("s", stooq.as_str()),
+            ("f", "sd2t2c"),
+            ("h", ""),
+            ("e", "csv"),
+        ])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Stooq quote failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Invalid Stooq quote: {}", e))?;
+    let row = body
+        .lines()
+        .nth(1)
+        .ok_or_else(|| format!("Stooq has no quote for {}", symbol))?;
+    let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+    let price = fields
+        .get(3)
+        .and_then(|close| close.parse::<f64>().ok())
+        .filter(|price| *price > 0.0)
+        .ok_or_else(|| format!("Stooq has no price for {}", symbol))?;
+    let as_of = match (fields.get(1), fields.get(2)) {
+        (Some(date), Some(time)) => chrono::NaiveDateTime::parse_from_str(
+            &format!("{} {}", date, time),
+            "%Y-%m-%d %H:%M:%S",
+        )
+        .ok()
+        .map(|at| at.and_utc()),
+        _ => None,
+    };
+    Ok(Quote {
+        source: QuoteSource::Stooq,
+        price,
+        currency: currency.to_string(),
+        as_of,
+    })
+}
+
+/// Quote in euros; pence are converted to pounds first
+async fn to_eur(data_dir: &Path, quote: &Quote) -> Result<f64, String> {
+    let (price, currency) = match quote.currency.as_str() {
+        "GBp" | "GBX" => (quote.price / 100.0, "GBP"),
+        currency => (quote.price, currency),
+    };
+    if currency.eq_ignore_ascii_case("EUR") {
+        return Ok(price);
+    }
+    let factor = fx_rates::rates(data_dir, false)
+        .await?
+        .factor(currency, "EUR")
+        .ok_or_else(|| format!("No FX rate for {}", currency))?;
+    Ok(price * factor)
+}
+
+/// Write `price` (in euros) to every position in `isin`; returns the
+/// portfolios holding it.
+fn write_price(data_dir: &Path, isin: &str, price: f64) -> Result<Vec<u32>, String> {
+    let connection = open(data_dir, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    let updated = connection
+        .execute(
+            "UPDATE positions SET current_price = ?1, updated_at = CURRENT_TIMESTAMP
+             WHERE isin = ?2",
+            params![price, isin],
+        )
+        .map_err(|e| format!("Failed to save the price: {}", e))?;
+    if updated == 0 {
+        return Err(format!("No position holds {}", isin));
+    }
+    let mut statement = connection
+        .prepare("SELECT DISTINCT portfolio_id FROM positions WHERE isin = ?1")
+        .map_err(|e| format!("Failed to read positions: {}", e))?;
+    let portfolio_ids = statement
+        .query_map([isin], |row| row.get(0))
+        .and_then(|rows| rows.collect::<Result<Vec<u32>, _>>())
+        .map_err(|e| format!("Failed to read positions: {}", e))?;
+    Ok(portfolio_ids)
+}
+
+/// Fetch a fresh price for `isin` (validated) and write it to its positions.
+pub async fn refresh(data_dir: &Path, isin: &str) -> Result<QuoteRefresh, String> {
+    let connection = open(data_dir, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let known_symbol = symbol_of(&connection, isin);
+    drop(connection);
+
+    let reported = MISSING.lock().is_ok_and(|missing| {
+        missing.contains_key(isin)
+            || known_symbol
+                .as_ref()
+                .is_some_and(|symbol| missing.contains_key(&symbol.to_uppercase()))
+    });
+    if !reported {
+        return Err(format!(
+            "The engine has not reported a missing quote for {}; sync to refresh it",
+            isin
+        ));
+    }
+
+    let client = client()?;
+    let symbol = match known_symbol {
+        Some(symbol) => symbol,
+        None => yahoo_symbol(&client, isin).await?,
+    };
+    let quote = match yahoo_quote(&client, &symbol).await {
+        Ok(quote) => quote,
+        Err(yahoo) => stooq_quote(&client, &symbol)
+            .await
+            .map_err(|stooq| format!("No quote for {}: {}; {}", isin, yahoo, stooq))?,
+    };
+    let price = to_eur(data_dir, &quote).await?;
+
+    let (write_dir, write_isin) = (data_dir.to_path_buf(), isin.to_string());
+    let portfolio_ids =
+        tauri::async_runtime::spawn_blocking(move || write_price(&write_dir, &write_isin, price))
+            .await
+            .map_err(|e| format!("Failed to save the price: {}", e))??;
+
+    if let Ok(mut missing) = MISSING.lock() {
+        missing.remove(isin);
+        missing.remove(&symbol.to_uppercase());
+    }
+    tracing::info!("Refreshed quote for {} from {:?}", isin, quote.source);
+    Ok(QuoteRefresh {
+        isin: isin.to_string(),
+        symbol,
+        source: quote.source,
+        quoted_price: quote.price,
+        quoted_currency: quote.currency,
+        price,
+        as_of: quote.as_of,
+        portfolio_ids,
+    })
+}
@@ -0,0 +1,219 @@
+//! Engine Response Cache
+//!
+//! Switching between views asks the engine for the dashboard, the positions
+//! and the look-through holdings (which every overlap view is built from)
+//! again, and each is a heavy query on the Python side. Successful responses
+//! to these read-only commands are kept per command and payload for a short
+//! TTL and served without a round trip to the sidecar.
+//!
+//! Entries are dropped whenever a portfolio changes (`portfolio-updated`)
+//! and when a sidecar becomes ready, since a restarted engine may be looking
+//! at different data. A response that was in flight during an invalidation
+//! is not cached.
+//!
+//! The engine also reports what its own writes made stale with a
+//! `data_invalidated` event and a scope. Only the commands in that scope are
+//! dropped, on both engines, and the event is forwarded to the frontend as
+//! `data-invalidated`:
+//! - `positions`: holdings and totals (dashboard, positions)
+//! - `allocations`: sector, region and asset class breakdowns (dashboard)
+//! - `report`: look-through and overlap analytics (true holdings)
+//!
+//! An unknown scope drops everything.
+//!
+//! After a successful sync the dashboard, positions and true holdings are
+//! fetched in the background (`prefetch`), so the first view opened after it
+//! is served from the warm cache.
+
+use crate::python_engine::{EnginePool, EngineResponse, PythonEngine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+/// Event after which cached responses no longer reflect the portfolio
+pub const INVALIDATING_EVENT: &str = "portfolio-updated";
+
+/// Sent by the engine (`data_invalidated`) and forwarded under this name
+pub const DATA_INVALIDATED_EVENT: &str = "data-invalidated";
+
+/// Cached commands and how long a response stays valid
+const CACHED_COMMANDS: &[(&str, Duration)] = &[
+    ("get_dashboard_data", Duration::from_secs(60)),
+    ("get_positions", Duration::from_secs(60)),
+    ("get_true_holdings", Duration::from_secs(300)),
+];
+
+/// Part of the portfolio data an engine write made stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Scope {
+    Positions,
+    Allocations,
+    Report,
+}
+
+impl Scope {
+    /// Cached commands whose responses cover this scope
+    fn commands(self) -> &'static [&'static str] {
+        match self {
+            Scope::Positions => &["get_dashboard_data", "get_positions"],
+            Scope::Allocations => &["get_dashboard_data"],
+            Scope::Report => &["get_true_holdings"],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Invalidated {
+    scope: Scope,
+}
+
+struct Entry {
+    response: EngineResponse,
+    expires_at: Instant,
+}
+
+/// Responses keyed by command + serialized payload; the lock is never held
+/// across an await
+#[derive(Default)]
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Bumped on every invalidation
+    generation: AtomicU64,
+}
+
+impl ResponseCache {
+    fn ttl(command: &str) -> Option<Duration> {
+        CACHED_COMMANDS
+            .iter()
+            .find(|(name, _)| *name == command)
+            .map(|(_, ttl)| *ttl)
+    }
+
+    /// Current generation; pass it to `insert` for a request about to start
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Unexpired response for `key`
+    pub fn get(&self, key: &str) -> Option<EngineResponse> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Keep `response` if `command` is cached, the response succeeded and
+    /// nothing was invalidated since `generation`.
+    pub fn insert(&self, command: &str, key: &str, response: &EngineResponse, generation: u64) {
+        let Some(ttl) = Self::ttl(command) else {
+            return;
+        };
+        if !response.success {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        // Checked under the lock, so a concurrent `clear` cannot slip between
+        if self.generation() != generation {
+            return;
+        }
+        let now = Instant::now();
+        entries.retain(|_, entry| entry.expires_at > now);
+        entries.insert(
+            key.to_string(),
+            Entry {
+                response: response.clone(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// Drop the cached responses of the commands in `scope`
+    pub fn invalidate(&self, scope: Scope) {
+        if let Ok(mut entries) = self.entries.lock() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            let commands = scope.commands();
+            entries.retain(|key, _| {
+                let command = key.split(':').next().unwrap_or_default();
+                !commands.contains(&command)
+            });
+        }
+    }
+
+    /// Drop every cached response
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            entries.clear();
+        }
+    }
+}
+
+/// Clear the caches of `engines` whenever a portfolio changes.
+pub fn invalidate_on_change(app_handle: &AppHandle, engines: Vec<Arc<PythonEngine>>) {
+    app_handle.listen(INVALIDATING_EVENT, move |_| {
+        tracing::debug!("Portfolio changed, clearing cached engine responses");
+        for engine in &engines {
+            engine.invalidate_cache();
+        }
+    });
+}
+
+/// Handle a `data_invalidated` event from either engine: drop the affected
+/// entries on both and tell the frontend.
+pub fn engine_invalidated(app_handle: &AppHandle, data: Value) {
+    let Some(pool) = app_handle.try_state::<EnginePool>() else {
+        return;
+    };
+    let engines = [pool.primary(), pool.worker()];
+    let payload = match serde_json::from_value::<Invalidated>(data.clone()) {
+        Ok(Invalidated { scope }) => {
+            tracing::debug!("Engine invalidated {:?}", scope);
+            for engine in &engines {
+                engine.invalidate_cache_scope(scope);
+            }
+            json!({ "scope": scope })
+        }
+        Err(e) => {
+            tracing::warn!("Unknown invalidation {}, clearing all: {}", data, e);
+            for engine in &engines {
+                engine.invalidate_cache();
+            }
+            data
+        }
+    };
+    let _ = app_handle.emit(DATA_INVALIDATED_EVENT, payload);
+}
+
+/// Warm the cache for `portfolio_id` after a sync. The payloads match the
+/// ones the commands send, so their cache keys do too.
+pub fn prefetch(engine: Arc<PythonEngine>, portfolio_id: u32) {
+    tauri::async_runtime::spawn(async move {
+        let portfolio = json!({ "portfolioId": portfolio_id });
+        let (dashboard, positions, true_holdings) = tokio::join!(
+            engine.send_command("get_dashboard_data", portfolio.clone()),
+            engine.send_command("get_positions", portfolio),
+            engine.send_command("get_true_holdings", json!({})),
+        );
+        for (command, result) in [
+            ("get_dashboard_data", dashboard),
+            ("get_positions", positions),
+            ("get_true_holdings", true_holdings),
+        ] {
+            if let Err(e) = result {
+                tracing::debug!("Prefetch of {} failed: {}", command, e);
+            }
+        }
+    });
+}
@@ -0,0 +1,393 @@
+//! Paper-Trading Sandbox Portfolios
+//!
+//! Sandbox portfolios record hypothetical trades without touching the
+//! engine's database. They live entirely in `sandbox_portfolios.json` and use
+//! ids from `SANDBOX_ID_BASE` upwards, so every command can tell the two
+//! portfolio kinds apart from the id alone.
+//!
+//! Trades are valued with real synced prices: the current prices of the
+//! user's real positions, falling back to the last hypothetical trade price
+//! for instruments not held anywhere.
+
+use crate::commands::portfolio::{
+    Allocations, DashboardData, Holding, Portfolio, PortfolioKind, Position, PositionsResponse,
+    Transaction,
+};
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Store file name inside the app data dir
+pub(crate) const STORE_FILE: &str = "sandbox_portfolios.json";
+
+/// First sandbox portfolio id; engine portfolios stay below this
+pub const SANDBOX_ID_BASE: u32 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxTrade {
+    pub id: u32,
+    pub isin: String,
+    #[serde(default)]
+    pub name: String,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Set once the trade was executed for real and turned into a note
+    #[serde(default)]
+    pub converted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SandboxPortfolio {
+    id: u32,
+    name: String,
+    currency: String,
+    created_at: String,
+    #[serde(default)]
+    trades: Vec<SandboxTrade>,
+    #[serde(default)]
+    next_trade_id: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SandboxStore {
+    #[serde(default)]
+    portfolios: Vec<SandboxPortfolio>,
+}
+
+/// Current price and name of an instrument from real synced positions
+#[derive(Debug, Clone)]
+pub struct SyncedPrice {
+    pub name: String,
+    pub price: f64,
+    pub instrument_type: String,
+    pub last_updated: String,
+}
+
+/// Whether `portfolio_id` refers to a sandbox portfolio
+pub fn is_sandbox(portfolio_id: u32) -> bool {
+    portfolio_id >= SANDBOX_ID_BASE
+}
+
+/// Error for commands that only apply to real portfolios
+pub fn reject(portfolio_id: u32, action: &str) -> Result<(), String> {
+    if is_sandbox(portfolio_id) {
+        return Err(format!("Sandbox portfolios cannot be {}", action));
+    }
+    Ok(())
+}
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORE_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<SandboxStore, String> {
+    Ok(store::read_json(&store_path(data_dir))?.unwrap_or_default())
+}
+
+fn save(data_dir: &Path, sandboxes: &SandboxStore) -> Result<(), String> {
+    store::write_json(&store_path(data_dir), sandboxes)
+}
+
+fn find(sandboxes: &mut SandboxStore, portfolio_id: u32) -> Result<&mut SandboxPortfolio, String> {
+    sandboxes
+        .portfolios
+        .iter_mut()
+        .find(|sandbox| sandbox.id == portfolio_id)
+        .ok_or_else(|| format!("Sandbox portfolio {} does not exist", portfolio_id))
+}
+
+fn get(sandboxes: &SandboxStore, portfolio_id: u32) -> Result<&SandboxPortfolio, String> {
+    sandboxes
+        .portfolios
+        .iter()
+        .find(|sandbox| sandbox.id == portfolio_id)
+        .ok_or_else(|| format!("Sandbox portfolio {} does not exist", portfolio_id))
+}
+
+fn summary(sandbox: &SandboxPortfolio) -> Portfolio {
+    Portfolio {
+        id: sandbox.id,
+        name: sandbox.name.clone(),
+        currency: sandbox.currency.clone(),
+        created_at: Some(sandbox.created_at.clone()),
+        kind: PortfolioKind::Sandbox,
+    }
+}
+
+pub fn list(data_dir: &Path) -> Result<Vec<Portfolio>, String> {
+    Ok(load(data_dir)?.portfolios.iter().map(summary).collect())
+}
+
+pub fn create(data_dir: &Path, name: &str, currency: &str) -> Result<Portfolio, String> {
+    let mut sandboxes = load(data_dir)?;
+    let id = sandboxes
+        .portfolios
+        .iter()
+        .map(|sandbox| sandbox.id + 1)
+        .max()
+        .unwrap_or(SANDBOX_ID_BASE);
+    let sandbox = SandboxPortfolio {
+        id,
+        name: name.to_string(),
+        currency: currency.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        trades: vec![],
+        next_trade_id: 1,
+    };
+    let portfolio = summary(&sandbox);
+    sandboxes.portfolios.push(sandbox);
+    save(data_dir, &sandboxes)?;
+    Ok(portfolio)
+}
+
+pub fn rename(data_dir: &Path, portfolio_id: u32, name: &str) -> Result<Portfolio, String> {
+    let mut sandboxes = load(data_dir)?;
+    let sandbox = find(&mut sandboxes, portfolio_id)?;
+    sandbox.name = name.to_string();
+    let portfolio = summary(sandbox);
+    save(data_dir, &sandboxes)?;
+    Ok(portfolio)
+}
+
+pub fn delete(data_dir: &Path, portfolio_id: u32) -> Result<(), String> {
+    let mut sandboxes = load(data_dir)?;
+    find(&mut sandboxes, portfolio_id)?;
+    sandboxes.portfolios.retain(|sandbox| sandbox.id != portfolio_id);
+    save(data_dir, &sandboxes)
+}
+
+fn held_quantity(trades: &[SandboxTrade], isin: &str) -> f64 {
+    trades
+        .iter()
+        .filter(|trade| trade.isin == isin)
+        .map(|trade| match trade.side {
+            TradeSide::Buy => trade.quantity,
+            TradeSide::Sell => -trade.quantity,
+        })
+        .sum()
+}
+
+/// Record a hypothetical trade. Sales cannot exceed the sandbox holding.
+pub fn record_trade(
+    data_dir: &Path,
+    portfolio_id: u32,
+    mut trade: SandboxTrade,
+) -> Result<SandboxTrade, String> {
+    let valid = |value: f64| value.is_finite() && value > 0.0;
+    if !valid(trade.quantity) || !valid(trade.price) {
+        return Err("Quantity and price must be positive".to_string());
+    }
+
+    let mut sandboxes = load(data_dir)?;
+    let sandbox = find(&mut sandboxes, portfolio_id)?;
+    if trade.side == TradeSide::Sell
+        && held_quantity(&sandbox.trades, &trade.isin) + 1e-9 < trade.quantity
+    {
+        return Err(format!("Sandbox does not hold enough {} to sell", trade.isin));
+    }
+
+    trade.id = sandbox.next_trade_id.max(1);
+    sandbox.next_trade_id = trade.id + 1;
+    sandbox.trades.push(trade.clone());
+    save(data_dir, &sandboxes)?;
+    Ok(trade)
+}
+
+/// Mark a trade as actually executed, returning it with the sandbox name.
+/// The trade stays in the sandbox so its history is unchanged.
+pub fn mark_converted(
+    data_dir: &Path,
+    portfolio_id: u32,
+    trade_id: u32,
+) -> Result<(String, SandboxTrade), String> {
+    let mut sandboxes = load(data_dir)?;
+    let sandbox = find(&mut sandboxes, portfolio_id)?;
+    let name = sandbox.name.clone();
+    let trade = sandbox
+        .trades
+        .iter_mut()
+        .find(|trade| trade.id == trade_id)
+        .ok_or_else(|| format!("Sandbox trade {} does not exist", trade_id))?;
+    if trade.converted_at.is_some() {
+        return Err(format!("Sandbox trade {} was already converted", trade_id));
+    }
+    trade.converted_at = Some(chrono::Utc::now().to_rfc3339());
+    let trade = trade.clone();
+    save(data_dir, &sandboxes)?;
+    Ok((name, trade))
+}
+
+/// Sandbox trades in ledger form, for `get_transactions`
+pub fn transactions(data_dir: &Path, portfolio_id: u32) -> Result<Vec<Transaction>, String> {
+    let sandboxes = load(data_dir)?;
+    let sandbox = get(&sandboxes, portfolio_id)?;
+    Ok(sandbox
+        .trades
+        .iter()
+        .map(|trade| Transaction {
+            id: format!("sandbox-{}", trade.id),
+            portfolio_id,
+            isin: trade.isin.clone(),
+            transaction_type: format!("{:?}", trade.side),
+            date: trade.date.clone(),
+            quantity: Some(trade.quantity),
+            amount: trade.quantity * trade.price,
+            currency: sandbox.currency.clone(),
+            notes: trade.note.clone(),
+        })
+        .collect())
+}
+
+/// Value the sandbox with `prices` (ISIN -> synced price).
+pub fn positions(
+    data_dir: &Path,
+    portfolio_id: u32,
+    prices: &HashMap<String, SyncedPrice>,
+) -> Result<PositionsResponse, String> {
+    let sandboxes = load(data_dir)?;
+    let sandbox = get(&sandboxes, portfolio_id)?;
+
+    // Average-cost bookkeeping per ISIN, in trade order
+    struct Lot<'a> {
+        name: &'a str,
+        quantity: f64,
+        cost: f64,
+        last_price: f64,
+        last_date: &'a str,
+    }
+    let mut lots: BTreeMap<&str, Lot> = BTreeMap::new();
+    let mut trades: Vec<&SandboxTrade> = sandbox.trades.iter().collect();
+    trades.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+    for trade in trades {
+        let lot = lots.entry(trade.isin.as_str()).or_insert(Lot {
+            name: &trade.name,
+            quantity: 0.0,
+            cost: 0.0,
+            last_price: trade.price,
+            last_date: &trade.date,
+        });
+        match trade.side {
+            TradeSide::Buy => {
+                lot.quantity += trade.quantity;
+                lot.cost += trade.quantity * trade.price;
+            }
+            TradeSide::Sell => {
+                let average = if lot.quantity > 0.0 { lot.cost / lot.quantity } else { 0.0 };
+                lot.quantity -= trade.quantity;
+                lot.cost -= trade.quantity * average;
+            }
+        }
+        lot.last_price = trade.price;
+        lot.last_date = &trade.date;
+    }
+
+    let mut positions: Vec<Position> = lots
+        .into_iter()
+        .filter(|(_, lot)| lot.quantity > 1e-9)
+        .map(|(isin, lot)| {
+            let synced = prices.get(isin);
+            let current_price = synced.map(|p| p.price).unwrap_or(lot.last_price);
+            let current_value = lot.quantity * current_price;
+            let pnl = current_value - lot.cost;
+            Position {
+                isin: isin.to_string(),
+                name: synced
+                    .map(|p| p.name.clone())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| lot.name.to_string()),
+                ticker: String::new(),
+                instrument_type: synced
+                    .map(|p| p.instrument_type.clone())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                quantity: lot.quantity,
+                avg_buy_price: lot.cost / lot.quantity,
+                current_price,
+                current_value,
+                total_cost: lot.cost,
+                pnl_eur: pnl,
+                pnl_percent: if lot.cost > 0.0 { pnl / lot.cost * 100.0 } else { 0.0 },
+                weight: 0.0,
+                currency: sandbox.currency.clone(),
+                notes: String::new(),
+                last_updated: synced
+                    .map(|p| p.last_updated.clone())
+                    .unwrap_or_else(|| lot.last_date.to_string()),
+            }
+        })
+        .collect();
+
+    let total_value: f64 = positions.iter().map(|p| p.current_value).sum();
+    let total_cost: f64 = positions.iter().map(|p| p.total_cost).sum();
+    for position in &mut positions {
+        if total_value > 0.0 {
+            position.weight = position.current_value / total_value;
+        }
+    }
+    let total_pnl = total_value - total_cost;
+
+    Ok(PositionsResponse {
+        positions,
+        total_value,
+        total_cost,
+        total_pnl,
+        total_pnl_percent: if total_cost > 0.0 { total_pnl / total_cost * 100.0 } else { 0.0 },
+        last_sync_time: None,
+        stale: false,
+    })
+}
+
+/// Dashboard view of a valued sandbox
+pub fn dashboard(valued: &PositionsResponse) -> DashboardData {
+    let mut top_holdings: Vec<Holding> = valued
+        .positions
+        .iter()
+        .map(|p| Holding {
+            isin: p.isin.clone(),
+            name: p.name.clone(),
+            ticker: None,
+            value: p.current_value,
+            weight: p.weight,
+            pnl: p.pnl_eur,
+            pnl_percentage: p.pnl_percent,
+            quantity: Some(p.quantity),
+            asset_class: None,
+        })
+        .collect();
+    top_holdings.sort_by(|a, b| b.value.total_cmp(&a.value));
+    top_holdings.truncate(10);
+
+    DashboardData {
+        total_value: valued.total_value,
+        total_gain: valued.total_pnl,
+        gain_percentage: valued.total_pnl_percent,
+        day_change: 0.0,
+        day_change_percent: 0.0,
+        history: vec![],
+        allocations: Allocations {
+            sector: HashMap::new(),
+            region: HashMap::new(),
+            asset_class: HashMap::new(),
+            bounds: None,
+        },
+        top_holdings,
+        last_updated: Some(chrono::Utc::now().to_rfc3339()),
+        is_empty: valued.positions.is_empty(),
+        position_count: valued.positions.len() as u32,
+        data_quality: None,
+        stale: false,
+    }
+}
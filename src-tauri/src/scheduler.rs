@@ -0,0 +1,527 @@
+//! Scheduled Jobs
+//!
+//! Automatic pipeline runs and portfolio syncs while the app is open. A
+//! schedule is either an interval (every N hours since the last run) or a
+//! daily local time; the outcome is shown as a native notification.
+//!
+//! - Pipeline: a due run only starts once no command has been invoked for
+//!   `idle_minutes` and no pipeline is already running.
+//! - Sync (opt-in): skipped while a pipeline or another sync keeps the engine
+//!   busy and retried on the next tick. Without a valid Trade Republic
+//!   session (restoring a saved one is tried first) the slot is recorded as
+//!   skipped; the app never prompts for a login in the background.
+//!
+//! Settings and the last runs live in `schedules.json`. Runs missed while the
+//! app was closed are caught up once, not once per missed slot.
+
+use crate::commands::auth::{AuthResponse, AuthStatus, SessionCheck};
+use crate::commands::pipeline::{execute_pipeline, pipeline_payload, PipelineResult};
+use crate::commands::portfolio::{self, PortfolioSyncResult};
+use crate::maintenance;
+use crate::pipeline_progress::PipelineRun;
+use crate::python_engine::{EnginePool, PythonEngine};
+use crate::{protocol, sandbox, store};
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Settings and last-run file inside the app data dir
+pub(crate) const SCHEDULES_FILE: &str = "schedules.json";
+
+/// How often the scheduler checks for due jobs
+const SCHEDULER_INTERVAL_SECS: u64 = 60;
+
+// =============================================================================
+// Schedules
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Schedule {
+    /// Every `hours` hours after the last run
+    Interval { hours: u32 },
+    /// Once a day at a local `HH:MM`
+    Daily { time: String },
+}
+
+impl Schedule {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Schedule::Interval { hours } if *hours == 0 || *hours > 24 * 7 => {
+                Err("Schedule interval must be between 1 and 168 hours".to_string())
+            }
+            Schedule::Daily { time } => parse_time(time).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Next time a run is due after `last_run`; in the past when overdue
+    pub fn next_run(&self, last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Interval { hours } => last_run
+                .map(|last| last + chrono::Duration::hours(i64::from(*hours)))
+                .unwrap_or(now),
+            Schedule::Daily { time } => {
+                let Ok(time) = parse_time(time) else {
+                    return DateTime::<Utc>::MAX_UTC;
+                };
+                // Yesterday's, today's and tomorrow's slot (a slot inside a DST
+                // gap is skipped)
+                let today = now.with_timezone(&Local).date_naive();
+                let slots: Vec<DateTime<Utc>> = [today.pred_opt(), Some(today), today.succ_opt()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|date| Local.from_local_datetime(&date.and_time(time)).earliest())
+                    .map(|slot| slot.with_timezone(&Utc))
+                    .collect();
+                let latest = slots.iter().rev().find(|slot| **slot <= now);
+                match latest {
+                    Some(latest) if last_run.is_none_or(|last| last < *latest) => *latest,
+                    _ => slots
+                        .into_iter()
+                        .find(|slot| *slot > now)
+                        .unwrap_or(DateTime::<Utc>::MAX_UTC),
+                }
+            }
+        }
+    }
+
+    pub fn is_due(&self, last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        self.next_run(last_run, now) <= now
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid schedule time: {} (expected HH:MM)", time))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_schedule")]
+    pub schedule: Schedule,
+    /// Minutes without a command before a due run starts
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u32,
+    /// Portfolio whose pipeline config is used; engine defaults otherwise
+    #[serde(default)]
+    pub portfolio_id: Option<u32>,
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_schedule() -> Schedule {
+    Schedule::Daily {
+        time: "07:00".to_string(),
+    }
+}
+
+fn default_idle_minutes() -> u32 {
+    5
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+impl Default for PipelineSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: default_schedule(),
+            idle_minutes: default_idle_minutes(),
+            portfolio_id: None,
+            notify: default_notify(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_sync_schedule")]
+    pub schedule: Schedule,
+    #[serde(default = "default_sync_portfolio")]
+    pub portfolio_id: u32,
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_sync_schedule() -> Schedule {
+    Schedule::Daily {
+        time: "08:00".to_string(),
+    }
+}
+
+fn default_sync_portfolio() -> u32 {
+    1
+}
+
+impl Default for SyncSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: default_sync_schedule(),
+            portfolio_id: default_sync_portfolio(),
+            notify: default_notify(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRun {
+    pub started_at: DateTime<Utc>,
+    /// `success`, `failed`, `cancelled` or `skipped`
+    pub status: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchedulesFile {
+    #[serde(default)]
+    pipeline: PipelineSchedule,
+    #[serde(default)]
+    pipeline_last_run: Option<ScheduledRun>,
+    #[serde(default)]
+    sync: SyncSchedule,
+    #[serde(default)]
+    sync_last_run: Option<ScheduledRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineScheduleStatus {
+    pub settings: PipelineSchedule,
+    pub last_run: Option<ScheduledRun>,
+    /// `None` while disabled
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncScheduleStatus {
+    pub settings: SyncSchedule,
+    pub last_run: Option<ScheduledRun>,
+    /// `None` while disabled
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+fn file_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SCHEDULES_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<SchedulesFile, String> {
+    Ok(store::read_json(&file_path(data_dir))?.unwrap_or_default())
+}
+
+pub fn pipeline_status(data_dir: &Path) -> Result<PipelineScheduleStatus, String> {
+    let file = load(data_dir)?;
+    let last_run = file.pipeline_last_run.as_ref().map(|run| run.started_at);
+    Ok(PipelineScheduleStatus {
+        next_run: file
+            .pipeline
+            .enabled
+            .then(|| file.pipeline.schedule.next_run(last_run, Utc::now())),
+        settings: file.pipeline,
+        last_run: file.pipeline_last_run,
+    })
+}
+
+pub fn save_pipeline_schedule(data_dir: &Path, settings: PipelineSchedule) -> Result<(), String> {
+    settings.schedule.validate()?;
+    let mut file = load(data_dir)?;
+    file.pipeline = settings;
+    store::write_json(&file_path(data_dir), &file)
+}
+
+pub fn sync_status(data_dir: &Path) -> Result<SyncScheduleStatus, String> {
+    let file = load(data_dir)?;
+    let last_run = file.sync_last_run.as_ref().map(|run| run.started_at);
+    Ok(SyncScheduleStatus {
+        next_run: file
+            .sync
+            .enabled
+            .then(|| file.sync.schedule.next_run(last_run, Utc::now())),
+        settings: file.sync,
+        last_run: file.sync_last_run,
+    })
+}
+
+pub fn save_sync_schedule(data_dir: &Path, settings: SyncSchedule) -> Result<(), String> {
+    settings.schedule.validate()?;
+    sandbox::reject(settings.portfolio_id, "synced")?;
+    let mut file = load(data_dir)?;
+    file.sync = settings;
+    store::write_json(&file_path(data_dir), &file)
+}
+
+// =============================================================================
+// Scheduler
+// =============================================================================
+
+/// Background loop starting due jobs while the app is idle.
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(SCHEDULER_INTERVAL_SECS)).await;
+
+            let Ok(data_dir) = store::data_dir(&app_handle) else {
+                continue;
+            };
+            if let Err(e) = run_sync_if_due(&app_handle, &data_dir).await {
+                tracing::warn!("Scheduled sync failed: {}", e);
+            }
+            if let Err(e) = run_pipeline_if_due(&app_handle, &data_dir).await {
+                tracing::warn!("Scheduled pipeline run failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn run_pipeline_if_due(app_handle: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    let file = load(data_dir)?;
+    let settings = file.pipeline;
+    let last_run = file.pipeline_last_run.map(|run| run.started_at);
+    if !settings.enabled
+        || !settings.schedule.is_due(last_run, Utc::now())
+        || maintenance::idle_for() < chrono::Duration::minutes(i64::from(settings.idle_minutes))
+    {
+        return Ok(());
+    }
+
+    let run = app_handle.state::<PipelineRun>();
+    let pool = app_handle.state::<EnginePool>();
+    // Retried on the next tick rather than recorded as a failed run
+    if run.is_running() || !pool.primary().is_connected().await {
+        return Ok(());
+    }
+
+    let started_at = Utc::now();
+    let result = match pipeline_payload(app_handle, settings.portfolio_id) {
+        Ok(payload) => {
+            execute_pipeline(
+                app_handle,
+                &pool,
+                &run,
+                payload,
+                None,
+                settings.portfolio_id,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    let scheduled_run = ScheduledRun {
+        started_at,
+        status: match &result {
+            Ok(p) if p.cancelled => "cancelled",
+            Ok(p) if p.success => "success",
+            _ => "failed",
+        }
+        .to_string(),
+        detail: match &result {
+            Ok(p) => p.errors.first().cloned(),
+            Err(e) => Some(e.clone()),
+        },
+    };
+    let mut file = load(data_dir)?;
+    file.pipeline_last_run = Some(scheduled_run);
+    store::write_json(&file_path(data_dir), &file)?;
+
+    if settings.notify {
+        notify_pipeline(app_handle, &result);
+    }
+    Ok(())
+}
+
+async fn run_sync_if_due(app_handle: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    let file = load(data_dir)?;
+    let settings = file.sync;
+    let last_run = file.sync_last_run.map(|run| run.started_at);
+    if !settings.enabled || !settings.schedule.is_due(last_run, Utc::now()) {
+        return Ok(());
+    }
+
+    // Busy engines are retried on the next tick rather than recorded
+    let engine = app_handle.state::<EnginePool>().primary();
+    if !engine.is_connected().await
+        || portfolio::sync_in_progress()
+        || app_handle.state::<PipelineRun>().is_running()
+    {
+        return Ok(());
+    }
+
+    let started_at = Utc::now();
+    let scheduled_run = match ensure_session(app_handle, &engine).await {
+        Ok(true) => {
+            let result =
+                portfolio::sync_coalesced(app_handle, &engine, settings.portfolio_id, false).await;
+            if settings.notify {
+                notify_sync(app_handle, &result);
+            }
+            ScheduledRun {
+                started_at,
+                status: if result.is_ok() { "success" } else { "failed" }.to_string(),
+                detail: match &result {
+                    Ok(result) => Some(sync_summary(result)),
+                    Err(e) => Some(e.clone()),
+                },
+            }
+        }
+        Ok(false) => ScheduledRun {
+            started_at,
+            status: "skipped".to_string(),
+            detail: Some("No valid Trade Republic session".to_string()),
+        },
+        Err(e) => ScheduledRun {
+            started_at,
+            status: "failed".to_string(),
+            detail: Some(e),
+        },
+    };
+
+    let mut file = load(data_dir)?;
+    file.sync_last_run = Some(scheduled_run);
+    store::write_json(&file_path(data_dir), &file)
+}
+
+// =============================================================================
+// Run Now
+// =============================================================================
+
+/// Sync `portfolio_id`, or the scheduled portfolio, right away. Like a
+/// scheduled sync it never prompts for a login. Not recorded as a scheduled
+/// run.
+pub async fn run_sync_now(
+    app_handle: &AppHandle,
+    portfolio_id: Option<u32>,
+) -> Result<PortfolioSyncResult, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let portfolio_id = match portfolio_id {
+        Some(portfolio_id) => portfolio_id,
+        None => load(&data_dir)?.sync.portfolio_id,
+    };
+    let engine = app_handle.state::<EnginePool>().primary();
+    if !engine.is_connected().await {
+        return Err(engine.unavailable().into());
+    }
+    if app_handle.state::<PipelineRun>().is_running() {
+        return Err("Wait for the running pipeline to finish".to_string());
+    }
+    if !ensure_session(app_handle, &engine).await? {
+        return Err("Sign in to Trade Republic in the app first".to_string());
+    }
+    portfolio::sync_coalesced(app_handle, &engine, portfolio_id, false).await
+}
+
+/// Run the pipeline right away with the config of `portfolio_id`, or of the
+/// scheduled portfolio.
+pub async fn run_pipeline_now(
+    app_handle: &AppHandle,
+    portfolio_id: Option<u32>,
+) -> Result<PipelineResult, String> {
+    let data_dir = store::data_dir(app_handle)?;
+    let portfolio_id = match portfolio_id {
+        Some(portfolio_id) => Some(portfolio_id),
+        None => load(&data_dir)?.pipeline.portfolio_id,
+    };
+    let payload = pipeline_payload(app_handle, portfolio_id)?;
+    let pool = app_handle.state::<EnginePool>();
+    let run = app_handle.state::<PipelineRun>();
+    execute_pipeline(app_handle, &pool, &run, payload, None, portfolio_id).await
+}
+
+/// Sync the scheduled portfolio (tray "Sync now"); the outcome is a
+/// notification.
+pub async fn sync_now(app_handle: &AppHandle) {
+    let result = run_sync_now(app_handle, None).await;
+    notify_sync(app_handle, &result);
+}
+
+/// Run the pipeline for the scheduled portfolio (tray "Run pipeline"); the
+/// outcome is a notification.
+pub async fn pipeline_now(app_handle: &AppHandle) {
+    let result = run_pipeline_now(app_handle, None).await;
+    notify_pipeline(app_handle, &result);
+}
+
+/// Whether the engine holds an authenticated Trade Republic session,
+/// restoring a saved one if needed
+async fn ensure_session(app_handle: &AppHandle, engine: &PythonEngine) -> Result<bool, String> {
+    let data = engine.request("tr_get_auth_status", json!({})).await?;
+    let status: AuthStatus = protocol::parse(app_handle, "tr_get_auth_status", data)?;
+    if status.auth_state == "authenticated" {
+        return Ok(true);
+    }
+
+    let data = engine.request("tr_check_saved_session", json!({})).await?;
+    let session: SessionCheck = protocol::parse(app_handle, "tr_check_saved_session", data)?;
+    if !session.has_session {
+        return Ok(false);
+    }
+    let data = engine.request("tr_restore_session", json!({})).await?;
+    let restored: AuthResponse = protocol::parse(app_handle, "tr_restore_session", data)?;
+    Ok(restored.auth_state == "authenticated")
+}
+
+fn sync_summary(result: &PortfolioSyncResult) -> String {
+    let mut summary = format!(
+        "{} positions synced: {} new, {} updated",
+        result.synced_positions, result.new_positions, result.updated_positions
+    );
+    if !result.failures.is_empty() {
+        summary.push_str(&format!(", {} failed", result.failures.len()));
+    }
+    summary
+}
+
+fn notify_sync(app_handle: &AppHandle, result: &Result<PortfolioSyncResult, String>) {
+    match result {
+        Ok(result) => notify(app_handle, "Portfolio synced", &sync_summary(result)),
+        Err(e) => notify(app_handle, "Portfolio sync failed", e),
+    }
+}
+
+fn notify_pipeline(app_handle: &AppHandle, result: &Result<PipelineResult, String>) {
+    let (title, body) = match result {
+        Ok(p) if p.cancelled => return,
+        Ok(p) if p.success => (
+            "Pipeline finished",
+            format!("Analysis completed in {}s", p.duration_ms / 1000),
+        ),
+        Ok(p) => (
+            "Pipeline failed",
+            p.errors
+                .first()
+                .cloned()
+                .unwrap_or_else(|| "The analysis failed".to_string()),
+        ),
+        Err(e) => ("Pipeline failed", e.clone()),
+    };
+
+    notify(app_handle, title, &body);
+}
+
+fn notify(app_handle: &AppHandle, title: &str, body: &str) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+    {
+        tracing::warn!("Failed to show scheduler notification: {}", e);
+    }
+}
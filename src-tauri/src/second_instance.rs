@@ -0,0 +1,44 @@
+//! Second Launch Handling
+//!
+//! Launching the app while it already runs does not start a second copy.
+//! `tauri-plugin-single-instance` signals the running instance (local socket
+//! on macOS, D-Bus on Linux, a window message on Windows) and exits the new
+//! process before it touches the data dir. The running instance brings its
+//! window to the front and emits `second-instance` with the arguments and
+//! working directory of the launch, so files and links opened through a
+//! second launch still reach the frontend.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const EVENT: &str = "second-instance";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondLaunch {
+    /// Command-line arguments, without the executable
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+/// Show, restore and focus the main window.
+pub fn focus_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Plugin callback, run in the first instance for every later launch.
+pub fn handle(app_handle: &AppHandle, args: Vec<String>, cwd: String) {
+    tracing::info!("App launched again, focusing the running instance");
+    focus_main_window(app_handle);
+    let _ = app_handle.emit(
+        EVENT,
+        SecondLaunch {
+            args: args.into_iter().skip(1).collect(),
+            cwd,
+        },
+    );
+}
@@ -0,0 +1,209 @@
+//! App Health Self-Test
+//!
+//! End-to-end checks against the real infrastructure using harmless data, so
+//! users can attach a single pass/fail report to bug reports. Every check is
+//! independent: one failure never prevents the others from running.
+
+use crate::python_engine::{EngineState, PythonEngine};
+use crate::{keychain, store};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use serde_json::json;
+use std::future::Future;
+use std::path::Path;
+use std::time::Instant;
+use tauri::AppHandle;
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+
+/// Size of the synthetic payload used for the IPC round-trip (1 MB)
+const ROUND_TRIP_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+/// Keychain key used (and removed again) by the keychain check
+const KEYCHAIN_PROBE_KEY: &str = "self_test_probe";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    pub started_at: String,
+    pub app_version: String,
+    pub platform: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+async fn check<F>(name: &'static str, run: F) -> SelfTestCheck
+where
+    F: Future<Output = Result<Option<String>, String>>,
+{
+    let started = Instant::now();
+    let (status, detail) = match run.await {
+        Ok(Some(detail)) => (CheckStatus::Pass, detail),
+        Ok(None) => (CheckStatus::Skip, "Not available on this system".to_string()),
+        Err(e) => (CheckStatus::Fail, e),
+    };
+
+    SelfTestCheck {
+        name,
+        status,
+        detail,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+async fn check_spawn(engine: &PythonEngine) -> Result<Option<String>, String> {
+    // Starts an engine that is idle in lazy mode
+    engine.is_connected().await;
+    let status = engine.status();
+    if status.state != EngineState::Ready {
+        return Err(format!(
+            "Engine is {:?}{}",
+            status.state,
+            status
+                .reason
+                .map(|reason| format!(" ({})", reason))
+                .unwrap_or_default()
+        ));
+    }
+    let version = engine.get_version().await.unwrap_or_else(|| "unknown".to_string());
+    Ok(Some(format!("Engine ready, v{}", version)))
+}
+
+async fn check_round_trip(engine: &PythonEngine) -> Result<Option<String>, String> {
+    let padding = "x".repeat(ROUND_TRIP_PAYLOAD_BYTES);
+    let started = Instant::now();
+    let response = engine
+        .send_command("get_health", json!({ "selfTestPadding": padding }))
+        .await?;
+    if !response.success {
+        return Err(response
+            .error
+            .map(|e| e.message)
+            .unwrap_or_else(|| "Engine rejected round-trip".to_string()));
+    }
+    Ok(Some(format!(
+        "{} KB round-trip in {} ms",
+        ROUND_TRIP_PAYLOAD_BYTES / 1024,
+        started.elapsed().as_millis()
+    )))
+}
+
+async fn check_cache(data_dir: &Path) -> Result<Option<String>, String> {
+    let probe_path = data_dir.join("cache").join("self_test_probe.json");
+    let written = json!({ "probe": chrono::Utc::now().to_rfc3339() });
+
+    store::write_json(&probe_path, &written)?;
+    let read: Option<serde_json::Value> = store::read_json(&probe_path)?;
+    let _ = std::fs::remove_file(&probe_path);
+
+    if read.as_ref() != Some(&written) {
+        return Err("Cache read returned different data than was written".to_string());
+    }
+    Ok(Some("Cache directory is writable".to_string()))
+}
+
+async fn check_sqlite(engine: &PythonEngine) -> Result<Option<String>, String> {
+    let response = engine.send_command("get_health", json!({})).await?;
+    let db_path = response
+        .data
+        .as_ref()
+        .and_then(|data| data["dbPath"].as_str())
+        .map(|path| path.to_string())
+        .ok_or_else(|| "Engine did not report a database path".to_string())?;
+
+    // Reads every page, so keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || integrity_check(Path::new(&db_path)))
+        .await
+        .map_err(|e| format!("Integrity check did not finish: {}", e))?
+}
+
+/// Run `PRAGMA integrity_check` on a read-only connection.
+fn integrity_check(db_path: &Path) -> Result<Option<String>, String> {
+    let connection = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open database: {}", e))?;
+    let mut statement = connection
+        .prepare("PRAGMA integrity_check")
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+    let problems = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+
+    if problems.first().map(String::as_str) != Some("ok") {
+        return Err(format!("Integrity check reported: {}", problems.join("; ")));
+    }
+    Ok(Some("Database integrity check passed".to_string()))
+}
+
+async fn check_notifications(app_handle: &AppHandle) -> Result<Option<String>, String> {
+    let state = app_handle
+        .notification()
+        .permission_state()
+        .map_err(|e| format!("Cannot query notification permission: {}", e))?;
+
+    match state {
+        PermissionState::Granted => Ok(Some("Notifications are allowed".to_string())),
+        PermissionState::Denied => {
+            Err("Notifications are blocked in the system settings".to_string())
+        }
+        _ => Ok(Some("Notification permission has not been requested yet".to_string())),
+    }
+}
+
+async fn check_keychain() -> Result<Option<String>, String> {
+    let probe = chrono::Utc::now().timestamp_millis().to_string();
+    keychain::set_secret(KEYCHAIN_PROBE_KEY, &probe)?;
+    let read = keychain::get_secret(KEYCHAIN_PROBE_KEY);
+    keychain::delete_secret(KEYCHAIN_PROBE_KEY)?;
+
+    if read?.as_deref() != Some(probe.as_str()) {
+        return Err("Keychain returned a different value than was stored".to_string());
+    }
+    Ok(Some("Keychain read/write works".to_string()))
+}
+
+/// Run every check and assemble the report.
+pub async fn run(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+    data_dir: &Path,
+    app_version: String,
+) -> SelfTestReport {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let started = Instant::now();
+
+    let checks = vec![
+        check("sidecarSpawn", check_spawn(engine)).await,
+        check("ipcRoundTrip", check_round_trip(engine)).await,
+        check("cacheReadWrite", check_cache(data_dir)).await,
+        check("sqliteIntegrity", check_sqlite(engine)).await,
+        check("notifications", check_notifications(app_handle)).await,
+        check("keychainAccess", check_keychain()).await,
+    ];
+
+    SelfTestReport {
+        started_at,
+        app_version,
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        passed: checks.iter().all(|check| check.status != CheckStatus::Fail),
+        duration_ms: started.elapsed().as_millis() as u64,
+        checks,
+    }
+}
@@ -0,0 +1,294 @@
+//! Session Vault
+//!
+//! The engine keeps the Trade Republic session cookies (`tr_cookies.txt`) and
+//! the remembered login (`config/.credentials.json`) in files. In the app
+//! data dir they only exist encrypted (`<file>.enc`, ChaCha20-Poly1305 with a
+//! key held in the OS keychain), so a copied data dir or backup does not leak
+//! a brokerage session.
+//!
+//! Before a sidecar starts, the files are decrypted into a private temp dir
+//! that is handed to it as `PRISM_SESSION_DIR`. After every auth command and
+//! when a sidecar exits, the plaintext files there are sealed back into the
+//! data dir; a file the engine deleted (logout) drops its encrypted copy. On
+//! app exit the temp dir is sealed and wiped. Plaintext files left in the
+//! data dir by older versions are encrypted and shredded on first use.
+//!
+//! The temp dir has an unguessable name and is created in one step with
+//! owner-only access, so a directory planted by another user is never used.
+//! Dirs left behind by a crashed run are shredded at the next start
+//! (`sweep_stale`).
+
+use crate::app_reset;
+use crate::keychain;
+use crate::store;
+use crate::instance_lock;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+/// Session files relative to the data dir or the session dir
+const SESSION_FILES: &[&str] = &["tr_cookies.txt", "config/.credentials.json"];
+
+/// Keychain entry holding the hex-encoded encryption key
+pub(crate) const KEY_NAME: &str = "session_encryption_key";
+
+/// Header of an encrypted file, followed by the nonce and the ciphertext
+const MAGIC: &[u8] = b"PRSE1";
+
+const NONCE_LEN: usize = 12;
+
+fn encrypted_path(data_dir: &Path, file: &str) -> PathBuf {
+    data_dir.join(format!("{}.enc", file))
+}
+
+/// Name prefix of session dirs in the temp dir, followed by `<pid>-<random>`
+const DIR_PREFIX: &str = "portfolio-prism-session-";
+
+static SESSION_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Whether this process created the session dir (until it is wiped)
+static CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Temp dir the decrypted session files live in while the app runs
+pub fn session_dir() -> PathBuf {
+    SESSION_DIR
+        .get_or_init(|| {
+            let name = format!(
+                "{}{}-{:016x}",
+                DIR_PREFIX,
+                std::process::id(),
+                OsRng.next_u64()
+            );
+            std::env::temp_dir().join(name)
+        })
+        .clone()
+}
+
+/// The session dir, created owner-only if this process has not yet done so.
+/// An existing directory of that name was not made by us and is refused.
+pub fn private_dir() -> Result<PathBuf, String> {
+    let dir = session_dir();
+    if CREATED.load(Ordering::SeqCst) && dir.is_dir() {
+        return Ok(dir);
+    }
+
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(&dir)
+        .map_err(|e| format!("Failed to create session dir {}: {}", dir.display(), e))?;
+    CREATED.store(true, Ordering::SeqCst);
+    Ok(dir)
+}
+
+fn cipher() -> Result<ChaCha20Poly1305, String> {
+    if let Some(hex) = keychain::get_secret(KEY_NAME)? {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("zz"), 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .ok()
+            .filter(|bytes| bytes.len() == 32)
+            .ok_or_else(|| "Session encryption key in the keychain is invalid".to_string())?;
+        return Ok(ChaCha20Poly1305::new(Key::from_slice(&bytes)));
+    }
+
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    let hex: String = key.iter().map(|byte| format!("{:02x}", byte)).collect();
+    keychain::set_secret(KEY_NAME, &hex)?;
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+fn encrypt(cipher: &ChaCha20Poly1305, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt session file".to_string())?;
+    Ok([MAGIC, nonce.as_slice(), &ciphertext].concat())
+}
+
+fn decrypt(cipher: &ChaCha20Poly1305, data: &[u8]) -> Result<Vec<u8>, String> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|body| body.len() > NONCE_LEN)
+        .ok_or_else(|| "Not an encrypted session file".to_string())?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Session file cannot be decrypted with this keychain's key".to_string())
+}
+
+/// Create a subdir inside the (already private) session dir.
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to restrict {}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Encrypt plaintext session files an older version left in the data dir.
+fn migrate_plaintext(data_dir: &Path, cipher: &ChaCha20Poly1305) -> Result<(), String> {
+    for file in SESSION_FILES {
+        let plain = data_dir.join(file);
+        let Ok(content) = std::fs::read(&plain) else {
+            continue;
+        };
+        store::write_atomic(&encrypted_path(data_dir, file), &encrypt(cipher, &content)?)?;
+        app_reset::shred_file(&plain)
+            .map_err(|e| format!("Failed to remove plaintext {}: {}", file, e))?;
+    }
+    Ok(())
+}
+
+/// Decrypt the session files into the session dir and return it. Files
+/// already there are newer than their encrypted copies and are kept.
+pub fn open(data_dir: &Path) -> Result<PathBuf, String> {
+    let cipher = cipher()?;
+    migrate_plaintext(data_dir, &cipher)?;
+
+    let dir = private_dir()?;
+    for file in SESSION_FILES {
+        let target = dir.join(file);
+        let source = encrypted_path(data_dir, file);
+        if target.exists() || !source.exists() {
+            continue;
+        }
+        let data = std::fs::read(&source)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        match decrypt(&cipher, &data) {
+            Ok(plaintext) => {
+                if let Some(parent) = target.parent() {
+                    create_private_dir(parent)?;
+                }
+                std::fs::write(&target, plaintext)
+                    .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+            }
+            Err(e) => {
+                // E.g. restored from another machine: the user logs in again
+                tracing::warn!("Discarding saved session {}: {}", file, e);
+                let _ = std::fs::remove_file(store::backup_path(&source));
+                let _ = std::fs::remove_file(&source);
+            }
+        }
+    }
+    Ok(dir)
+}
+
+/// Encrypt the session files from the session dir into the data dir.
+pub fn seal(data_dir: &Path) -> Result<(), String> {
+    let dir = session_dir();
+    if !dir.exists() {
+        return Ok(());
+    }
+    let cipher = cipher()?;
+    for file in SESSION_FILES {
+        let target = encrypted_path(data_dir, file);
+        match std::fs::read(dir.join(file)) {
+            Ok(content) => store::write_atomic(&target, &encrypt(&cipher, &content)?)?,
+            Err(_) => {
+                // Logged out: the encrypted copy goes too
+                let _ = std::fs::remove_file(store::backup_path(&target));
+                let _ = std::fs::remove_file(&target);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Seal after an auth command, logging failures.
+pub fn persist(app_handle: &AppHandle) {
+    if let Err(e) = store::data_dir(app_handle).and_then(|data_dir| seal(&data_dir)) {
+        tracing::warn!("Failed to save session: {}", e);
+    }
+}
+
+fn shred_dir(dir: &Path) {
+    for file in SESSION_FILES {
+        let _ = app_reset::shred_file(&dir.join(file));
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Shred the decrypted session files.
+pub fn wipe() {
+    shred_dir(&session_dir());
+    CREATED.store(false, Ordering::SeqCst);
+}
+
+/// Shred session dirs a crashed or killed run left in the temp dir. Dirs of
+/// another user or of a still running instance are left alone.
+pub fn sweep_stale() {
+    let own = session_dir();
+    let Ok(entries) = std::fs::read_dir(std::env::temp_dir()) else {
+        return;
+    };
+    #[cfg(unix)]
+    let own_uid = {
+        use std::os::unix::fs::MetadataExt;
+        private_dir()
+            .and_then(|dir| std::fs::metadata(dir).map_err(|e| e.to_string()))
+            .map(|metadata| metadata.uid())
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix(DIR_PREFIX))
+            .and_then(|rest| rest.split('-').next())
+            .and_then(|pid| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if path == own || pid == std::process::id() || instance_lock::is_prism_process(pid) {
+            continue;
+        }
+        // Never follow a symlink out of the temp dir
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_dir() {
+            continue;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if own_uid.as_ref().ok() != Some(&metadata.uid()) {
+                continue;
+            }
+        }
+        tracing::info!("Removing stale session dir {}", path.display());
+        shred_dir(&path);
+    }
+}
+
+/// Shred the decrypted session files and drop their encrypted copies, e.g.
+/// to log out while the engine is not running.
+pub fn discard(data_dir: &Path) {
+    wipe();
+    for file in SESSION_FILES {
+        let target = encrypted_path(data_dir, file);
+        let _ = std::fs::remove_file(store::backup_path(&target));
+        let _ = std::fs::remove_file(&target);
+    }
+}
+
+/// Seal and wipe on app exit.
+pub fn close(app_handle: &AppHandle) {
+    persist(app_handle);
+    wipe();
+}
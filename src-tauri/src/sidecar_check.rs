@@ -0,0 +1,278 @@
+//! Sidecar Checks
+//!
+//! The engine ships as the `prism-headless` binary next to the app
+//! executable, and both are checked before every spawn.
+//!
+//! Antivirus quarantine, a partial install or an extraction that dropped the
+//! executable bit leave the shell without it. Instead of exiting, the app
+//! keeps its window and emits `sidecar-missing` with steps for the platform,
+//! so the frontend can show an error page. The last finding is kept for
+//! frontends that subscribe after the event went out.
+//!
+//! The SHA-256 of the bundled binary is built into the shell (`build.rs`).
+//! A binary that does not match is not started and `engine-tampered` is
+//! emitted, so a copy of the app with a swapped engine cannot run code with
+//! the user's brokerage session. On macOS the bundler re-signs the binary,
+//! so there a valid signature from the app's own team is accepted too.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter};
+
+pub const MISSING_EVENT: &str = "sidecar-missing";
+pub const TAMPERED_EVENT: &str = "engine-tampered";
+
+const SIDECAR: &str = "prism-headless";
+
+/// SHA-256 of the bundled binary; unset in builds without one
+const EXPECTED_SHA256: Option<&str> = option_env!("PRISM_SIDECAR_SHA256");
+
+static MISSING: Mutex<Option<SidecarMissing>> = Mutex::new(None);
+static TAMPERED: Mutex<Option<SidecarTampered>> = Mutex::new(None);
+
+/// Size and modification time of the binary that last passed verification,
+/// so respawns do not hash it again
+static VERIFIED: Mutex<Option<(u64, SystemTime)>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MissingReason {
+    NotFound,
+    NotExecutable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarMissing {
+    /// Where the binary was expected
+    pub path: String,
+    pub reason: MissingReason,
+    /// `macos`, `windows` or `linux`
+    pub platform: &'static str,
+    /// Steps for the user, in order
+    pub remediation: Vec<String>,
+}
+
+impl SidecarMissing {
+    pub fn message(&self) -> String {
+        match self.reason {
+            MissingReason::NotFound => format!("Analytics engine not found at {}", self.path),
+            MissingReason::NotExecutable => {
+                format!("Analytics engine at {} is not executable", self.path)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarTampered {
+    pub path: String,
+    pub expected_sha256: String,
+    /// Empty when the binary could not be read
+    pub actual_sha256: String,
+}
+
+impl SidecarTampered {
+    pub fn message(&self) -> String {
+        format!(
+            "Analytics engine at {} failed its integrity check and was not started",
+            self.path
+        )
+    }
+}
+
+/// Path the shell plugin resolves the sidecar to
+fn sidecar_path() -> Option<PathBuf> {
+    let exe = tauri::utils::platform::current_exe().ok()?;
+    let name = format!("{}{}", SIDECAR, std::env::consts::EXE_SUFFIX);
+    Some(exe.parent()?.join(name))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+fn remediation(path: &Path, reason: MissingReason) -> Vec<String> {
+    let path = path.display();
+    match (std::env::consts::OS, reason) {
+        ("macos", MissingReason::NotFound) => vec![
+            "Quit Portfolio Prism and move it to the Applications folder.".into(),
+            "Download the latest DMG and replace the installed app.".into(),
+            "If security software removed the engine, allow Portfolio Prism and reinstall.".into(),
+        ],
+        ("macos", MissingReason::NotExecutable) => vec![
+            "Quit Portfolio Prism and reinstall it from the latest DMG.".into(),
+            format!("Or run in Terminal: chmod +x \"{}\"", path),
+        ],
+        ("windows", _) => vec![
+            format!(
+                "Check whether your antivirus quarantined {}; restore it and add an exception.",
+                path
+            ),
+            "Run the Portfolio Prism installer again to repair the installation.".into(),
+        ],
+        (_, MissingReason::NotFound) => vec![
+            "Reinstall Portfolio Prism from your package or the latest AppImage.".into(),
+            "If you extracted the app by hand, keep prism-headless next to the app binary.".into(),
+        ],
+        (_, MissingReason::NotExecutable) => vec![
+            format!("Run: chmod +x \"{}\"", path),
+            "Make sure the app is not on a file system mounted with noexec.".into(),
+        ],
+    }
+}
+
+/// Look for the sidecar binary, remembering what is wrong with it.
+pub fn check() -> Option<SidecarMissing> {
+    let path = sidecar_path()?;
+    let reason = if !path.is_file() {
+        Some(MissingReason::NotFound)
+    } else if !is_executable(&path) {
+        Some(MissingReason::NotExecutable)
+    } else {
+        None
+    };
+    let missing = reason.map(|reason| SidecarMissing {
+        path: path.display().to_string(),
+        reason,
+        platform: std::env::consts::OS,
+        remediation: remediation(&path, reason),
+    });
+
+    if let Ok(mut last) = MISSING.lock() {
+        last.clone_from(&missing);
+    }
+    missing
+}
+
+/// Log `missing` and emit `sidecar-missing`.
+pub fn report_missing(app_handle: &AppHandle, missing: &SidecarMissing) {
+    tracing::error!("{}", missing.message());
+    let _ = app_handle.emit(MISSING_EVENT, missing);
+}
+
+fn sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn fingerprint(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Compare the binary with the checksum built into the shell.
+pub fn verify() -> Option<SidecarTampered> {
+    let (Some(expected), Some(path)) = (EXPECTED_SHA256, sidecar_path()) else {
+        return None;
+    };
+    let fingerprint = fingerprint(&path);
+    if fingerprint.is_some()
+        && VERIFIED
+            .lock()
+            .is_ok_and(|verified| *verified == fingerprint)
+    {
+        return None;
+    }
+
+    let actual = sha256(&path)
+        .inspect_err(|e| tracing::warn!("Failed to hash {}: {}", path.display(), e))
+        .unwrap_or_default();
+    let tampered =
+        (actual != expected && !platform::signed_by_own_team(&path)).then(|| SidecarTampered {
+            path: path.display().to_string(),
+            expected_sha256: expected.to_string(),
+            actual_sha256: actual,
+        });
+
+    if tampered.is_none() {
+        if let Ok(mut verified) = VERIFIED.lock() {
+            *verified = fingerprint;
+        }
+    }
+    if let Ok(mut last) = TAMPERED.lock() {
+        last.clone_from(&tampered);
+    }
+    tampered
+}
+
+/// Log `tampered` and emit `engine-tampered`.
+pub fn report_tampered(app_handle: &AppHandle, tampered: &SidecarTampered) {
+    tracing::error!(
+        "{} (expected {}, found {})",
+        tampered.message(),
+        tampered.expected_sha256,
+        tampered.actual_sha256
+    );
+    let _ = app_handle.emit(TAMPERED_EVENT, tampered);
+}
+
+/// Whether the last checks found the sidecar missing or tampered with
+pub fn blocked() -> bool {
+    MISSING.lock().is_ok_and(|missing| missing.is_some())
+        || TAMPERED.lock().is_ok_and(|tampered| tampered.is_some())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::Path;
+    use std::process::Command;
+
+    const CODESIGN: &str = "/usr/bin/codesign";
+
+    /// Team identifier of a validly signed binary
+    fn team(path: &Path) -> Option<String> {
+        let verified = Command::new(CODESIGN)
+            .args(["--verify", "--strict"])
+            .arg(path)
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !verified {
+            return None;
+        }
+        let output = Command::new(CODESIGN)
+            .args(["--display", "--verbose=2"])
+            .arg(path)
+            .output()
+            .ok()?;
+        // codesign prints the details on stderr
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .find_map(|line| line.strip_prefix("TeamIdentifier="))
+            .filter(|team| *team != "not set")
+            .map(str::to_string)
+    }
+
+    /// Whether `path` carries a valid signature of the team that signed the app
+    pub fn signed_by_own_team(path: &Path) -> bool {
+        let Some(own) = tauri::utils::platform::current_exe()
+            .ok()
+            .and_then(|exe| team(&exe))
+        else {
+            return false;
+        };
+        team(path).is_some_and(|team| team == own)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use std::path::Path;
+
+    pub fn signed_by_own_team(_path: &Path) -> bool {
+        false
+    }
+}
@@ -0,0 +1,166 @@
+//! Sidecar Path Variables
+//!
+//! Paths reach the sidecar through environment variables and must arrive
+//! byte for byte: a data dir under an accented user name or an arbitrary
+//! byte sequence on Linux is still a valid data dir. Values are passed as
+//! `OsStr`, never through a lossy UTF-8 conversion. Python decodes them with
+//! `surrogateescape` on POSIX, so `os.environ` round-trips any byte path.
+//!
+//! Windows passes environment blocks as UTF-16, but parts of the bundled
+//! runtime still go through the ANSI code page. Paths outside ASCII are
+//! therefore handed over in their 8.3 short form when the volume has one.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Environment value for `path` that the sidecar resolves to the same file
+pub fn path_value(path: &Path) -> OsString {
+    #[cfg(target_os = "windows")]
+    if !path.to_str().is_some_and(|text| text.is_ascii()) {
+        if let Some(short) = platform::short_path(path) {
+            return short;
+        }
+    }
+    path.as_os_str().to_os_string()
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::Path;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetShortPathNameW;
+
+    /// 8.3 alias of an existing path, if it has a pure ASCII one
+    pub fn short_path(path: &Path) -> Option<OsString> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let long = PCWSTR(wide.as_ptr());
+
+        // SAFETY: `wide` is NUL-terminated and outlives both calls
+        let needed = unsafe { GetShortPathNameW(long, None) } as usize;
+        if needed == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u16; needed];
+        let written = unsafe { GetShortPathNameW(long, Some(&mut buffer)) } as usize;
+        if written == 0 || written >= needed {
+            return None;
+        }
+        buffer.truncate(written);
+
+        let short = OsString::from_wide(&buffer);
+        // Volumes without 8.3 names return the long path unchanged
+        short.to_str().is_some_and(str::is_ascii).then_some(short)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::path_value;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fresh directory under the system temp dir, removed on drop
+    struct TempDir {
+        root: PathBuf,
+        path: PathBuf,
+    }
+
+    impl TempDir {
+        fn new(name: impl AsRef<std::ffi::OsStr>) -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let root = std::env::temp_dir().join(format!(
+                "prism-sidecar-env-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let path = root.join(name.as_ref());
+            std::fs::create_dir_all(&path).expect("create temp dir");
+            Self { root, path }
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// Value of `PRISM_DATA_DIR` as seen by a child process
+    #[cfg(unix)]
+    fn child_sees(value: &std::ffi::OsStr) -> PathBuf {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let output = std::process::Command::new("sh")
+            .args(["-c", "printf '%s' \"$PRISM_DATA_DIR\""])
+            .env("PRISM_DATA_DIR", value)
+            .output()
+            .expect("run sh");
+        PathBuf::from(OsStr::from_bytes(&output.stdout))
+    }
+
+    fn assert_resolves_to_dir(dir: &Path) {
+        let value = path_value(dir);
+        let resolved = Path::new(&value);
+        assert!(
+            resolved.is_dir(),
+            "{:?} does not resolve to {:?}",
+            value,
+            dir
+        );
+        assert_eq!(
+            resolved.canonicalize().unwrap(),
+            dir.canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn ascii_path_is_passed_unchanged() {
+        let dir = Path::new("/Users/alex/Library/Application Support/PortfolioPrism");
+        assert_eq!(path_value(dir), dir.as_os_str());
+    }
+
+    #[test]
+    fn accented_path_resolves_to_the_same_dir() {
+        let dir = TempDir::new("Zoë Müller/Åpp Dàta");
+        assert_resolves_to_dir(&dir.path);
+    }
+
+    #[test]
+    fn non_latin_path_resolves_to_the_same_dir() {
+        let dir = TempDir::new("ユーザー/данные");
+        assert_resolves_to_dir(&dir.path);
+    }
+
+    #[test]
+    fn unusual_characters_resolve_to_the_same_dir() {
+        let dir = TempDir::new("O'Brien & Co (2) #1 $HOME; %TEMP%");
+        assert_resolves_to_dir(&dir.path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn accented_path_reaches_child_unchanged() {
+        let dir = TempDir::new("José Müller");
+        assert_eq!(child_sees(&path_value(&dir.path)), dir.path);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn non_utf8_path_reaches_child_unchanged() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // Latin-1 "Zoë", which is not valid UTF-8
+        let name = OsStr::from_bytes(b"Zo\xEB");
+        assert!(name.to_str().is_none());
+        let dir = TempDir::new(name);
+
+        let value = path_value(&dir.path);
+        assert_eq!(value, dir.path.as_os_str());
+        assert_eq!(child_sees(&value), dir.path);
+        assert_resolves_to_dir(&dir.path);
+    }
+}
@@ -0,0 +1,143 @@
+//! Shell-owned JSON stores
+//!
+//! Small helpers for persisting shell state (settings, logs, caches) as JSON
+//! files inside the app data directory. The Python engine owns the SQLite
+//! database; everything the Rust shell needs to remember lives here.
+//!
+//! Documents are written atomically (temp file, fsync, rename) and the
+//! previous version is kept as `<file>.bak`. A document that no longer
+//! parses (torn by power loss before this layer existed, or edited by hand)
+//! is restored from that backup on read.
+
+use crate::data_location;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Resolve the app data directory for the running application: the
+/// platform default or wherever the user moved it.
+pub fn data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    data_location::resolve(app_handle)
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Backup kept next to every JSON document
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".bak")
+}
+
+/// Read a JSON document, returning `None` if the file does not exist yet.
+///
+/// A document that fails to parse is replaced by its `.bak` when that parses.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let error = match serde_json::from_str(&content) {
+        Ok(value) => return Ok(Some(value)),
+        Err(e) => format!("Failed to parse {}: {}", path.display(), e),
+    };
+
+    let backup = backup_path(path);
+    let Some(value) = fs::read_to_string(&backup)
+        .ok()
+        .and_then(|content| serde_json::from_str::<T>(&content).ok())
+    else {
+        return Err(error);
+    };
+
+    tracing::warn!("{}; restoring {}", error, backup.display());
+    let corrupt = sibling(path, &format!(".corrupt-{}", chrono::Utc::now().timestamp()));
+    let _ = fs::rename(path, corrupt);
+    fs::copy(&backup, path).map_err(|e| format!("Failed to restore {}: {}", path.display(), e))?;
+    Ok(Some(value))
+}
+
+/// Replace `path` with `bytes` so that a crash leaves either the old or the
+/// new content, never a mix. The previous content is kept as `.bak`.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+
+    let temp = sibling(path, ".tmp");
+    let mut file =
+        fs::File::create(&temp).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    file.write_all(bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    drop(file);
+
+    if path.exists() {
+        // Best effort: a missing backup only weakens recovery
+        let _ = fs::copy(path, backup_path(path));
+    }
+    fs::rename(&temp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+
+    // Persist the rename itself; directories cannot be opened on Windows
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Write a JSON document atomically, creating parent directories as needed.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    write_atomic(path, content.as_bytes())
+}
+
+/// Append one record to a newline-delimited JSON log.
+pub fn append_ndjson<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let line = serde_json::to_string(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line)
+        .and_then(|_| file.sync_data())
+        .map_err(|e| format!("Failed to append {}: {}", path.display(), e))
+}
+
+/// Read the last `limit` records of a newline-delimited JSON log.
+///
+/// Lines that fail to parse are skipped rather than failing the whole read,
+/// so a torn final line after a crash does not hide the rest of the log.
+pub fn read_ndjson_tail<T: DeserializeOwned>(path: &Path, limit: usize) -> Result<Vec<T>, String> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut records: Vec<T> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+
+    if records.len() > limit {
+        records.drain(..records.len() - limit);
+    }
+
+    Ok(records)
+}
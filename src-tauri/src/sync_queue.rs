@@ -0,0 +1,120 @@
+//! Deferred Syncs
+//!
+//! Syncs started while Trade Republic cannot be reached (see `network`) wait
+//! here and run once the connection returns, one after another and through
+//! the same coalescing as `sync_portfolio`. The command answers a queued sync
+//! with a `[SYNC_QUEUED]` error so the UI can say so instead of showing a
+//! failure. The queue is emitted as `sync-queue` whenever it changes, and
+//! every deferred run ends with `queued-sync-finished`.
+//!
+//! A portfolio is queued once; queuing it again with `force` makes the queued
+//! sync forced. The queue lives in memory, so syncs still waiting when the
+//! app quits are dropped.
+
+use crate::commands::portfolio::{self, PortfolioSyncResult};
+use crate::python_engine::PythonEngine;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const QUEUE_EVENT: &str = "sync-queue";
+
+pub const FINISHED_EVENT: &str = "queued-sync-finished";
+
+static QUEUE: Mutex<Vec<QueuedSync>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedSync {
+    pub portfolio_id: u32,
+    pub force: bool,
+    pub queued_at: String,
+}
+
+/// Payload of `queued-sync-finished`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedSyncFinished {
+    pub portfolio_id: u32,
+    pub result: Option<PortfolioSyncResult>,
+    pub error: Option<String>,
+}
+
+/// Syncs waiting for the connection, oldest first
+pub fn pending() -> Vec<QueuedSync> {
+    QUEUE.lock().map(|queue| queue.clone()).unwrap_or_default()
+}
+
+fn emit_queue(app_handle: &AppHandle) {
+    let _ = app_handle.emit(QUEUE_EVENT, pending());
+}
+
+/// Queue a sync of `portfolio_id`; returns the error for the command to
+/// answer with.
+pub fn enqueue(app_handle: &AppHandle, portfolio_id: u32, force: bool) -> String {
+    if let Ok(mut queue) = QUEUE.lock() {
+        match queue
+            .iter_mut()
+            .find(|sync| sync.portfolio_id == portfolio_id)
+        {
+            Some(queued) => queued.force |= force,
+            None => queue.push(QueuedSync {
+                portfolio_id,
+                force,
+                queued_at: Utc::now().to_rfc3339(),
+            }),
+        }
+    }
+    tracing::info!("Offline, queued the sync of portfolio {}", portfolio_id);
+    emit_queue(app_handle);
+    format!(
+        "[SYNC_QUEUED] Trade Republic cannot be reached; portfolio {} will sync when the \
+         connection returns",
+        portfolio_id
+    )
+}
+
+/// Run the queued syncs in order. Stops early while the engine is down or
+/// another sync runs; the rest is tried on the next network check.
+pub async fn run_pending(app_handle: &AppHandle) {
+    loop {
+        let Some(next) = QUEUE.lock().ok().and_then(|queue| queue.first().cloned()) else {
+            return;
+        };
+        let engine = app_handle.state::<Arc<PythonEngine>>().inner().clone();
+        if portfolio::sync_in_progress() || !engine.is_connected().await {
+            return;
+        }
+
+        tracing::info!(
+            "Back online, running the queued sync of portfolio {}",
+            next.portfolio_id
+        );
+        let result =
+            portfolio::sync_coalesced(app_handle, &engine, next.portfolio_id, next.force).await;
+        if let Err(e) = &result {
+            tracing::warn!(
+                "Queued sync of portfolio {} failed: {}",
+                next.portfolio_id,
+                e
+            );
+        }
+        if let Ok(mut queue) = QUEUE.lock() {
+            queue.retain(|sync| sync.portfolio_id != next.portfolio_id);
+        }
+        emit_queue(app_handle);
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e)),
+        };
+        let _ = app_handle.emit(
+            FINISHED_EVENT,
+            QueuedSyncFinished {
+                portfolio_id: next.portfolio_id,
+                result,
+                error,
+            },
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Sleep and Wake
+//!
+//! After the machine slept, e.g. overnight on a MacBook, the Trade Republic
+//! websocket and sometimes the sidecar are dead while the engine still reads
+//! `Ready`. Tauri has no resume event on every platform, so resume is told
+//! from the clock: a task ticks every `TICK_SECS`, and a tick that arrives
+//! far later in wall-clock time than scheduled means the system was suspended
+//! in between.
+//!
+//! On resume each running engine is pinged with `get_health`; one that does
+//! not answer within `PING_TIMEOUT_SECS` is restarted. The primary's Trade
+//! Republic session is then reconnected from its saved cookies
+//! (`tr_reconnect_session`). The outcome is emitted as `engine-resumed` so the
+//! UI can offer a re-sync. Engines are left alone while a sync or pipeline is
+//! running: a long command would make the ping time out.
+
+use crate::commands::auth::AuthResponse;
+use crate::commands::portfolio;
+use crate::pipeline_progress::PipelineRun;
+use crate::protocol;
+use crate::python_engine::{EnginePool, EngineRole, PythonEngine};
+use crate::session_vault;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const EVENT: &str = "engine-resumed";
+
+/// How often the clock is compared
+const TICK_SECS: u64 = 15;
+
+/// Wall-clock time beyond the tick that counts as a suspend; larger than any
+/// scheduling delay, small enough to catch a short nap
+const RESUME_GAP_SECS: i64 = 90;
+
+/// How long a woken engine has to answer the ping
+const PING_TIMEOUT_SECS: u64 = 10;
+
+/// How long a stopped sidecar may take to exit before it is respawned
+const EXIT_WAIT_SECS: u64 = 5;
+
+/// What the resume check did with one engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EngineOutcome {
+    /// Answered the ping
+    Responsive,
+    /// Did not answer and was restarted
+    Restarted,
+    /// Did not answer and could not be restarted
+    RestartFailed,
+    /// Not running (idle, disabled or dead), nothing to check
+    NotRunning,
+    /// Not checked because a sync or pipeline was running
+    Busy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineCheck {
+    pub role: &'static str,
+    pub outcome: EngineOutcome,
+}
+
+/// Payload of `engine-resumed`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineResumed {
+    /// Roughly how long the system was asleep
+    pub slept_seconds: i64,
+    pub engines: Vec<EngineCheck>,
+    /// Trade Republic session after the reconnect, if it was tried
+    pub auth_state: Option<String>,
+    pub session_error: Option<String>,
+}
+
+/// Watch the clock and check the engines after every resume.
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let tick = Duration::from_secs(TICK_SECS);
+        let mut last = Utc::now();
+        loop {
+            tokio::time::sleep(tick).await;
+            let now = Utc::now();
+            let gap = (now - last).num_seconds() - TICK_SECS as i64;
+            last = now;
+            if gap > RESUME_GAP_SECS {
+                on_resume(&app_handle, gap).await;
+                // The checks may take a while; that is not another sleep
+                last = Utc::now();
+            }
+        }
+    });
+}
+
+async fn on_resume(app_handle: &AppHandle, slept_seconds: i64) {
+    tracing::info!("System resumed after about {} minutes", slept_seconds / 60);
+    let pool = app_handle.state::<EnginePool>();
+    let busy = portfolio::sync_in_progress() || app_handle.state::<PipelineRun>().is_running();
+
+    let mut engines = Vec::new();
+    for (role, engine) in [
+        (EngineRole::Primary, pool.primary()),
+        (EngineRole::Worker, pool.worker()),
+    ] {
+        let outcome = if busy {
+            EngineOutcome::Busy
+        } else {
+            check_engine(app_handle, engine, role).await
+        };
+        engines.push(EngineCheck {
+            role: role.as_str(),
+            outcome,
+        });
+    }
+
+    // A restarted sidecar has no session to reconnect; the next sync
+    // restores the saved one
+    let (auth_state, session_error) = match engines[0].outcome {
+        EngineOutcome::Responsive => match reconnect_session(app_handle, &pool.primary()).await {
+            Ok(auth_state) => (Some(auth_state), None),
+            Err(e) => {
+                tracing::warn!("Session reconnect after resume failed: {}", e);
+                (None, Some(e))
+            }
+        },
+        _ => (None, None),
+    };
+
+    let _ = app_handle.emit(
+        EVENT,
+        EngineResumed {
+            slept_seconds,
+            engines,
+            auth_state,
+            session_error,
+        },
+    );
+}
+
+/// Ping `engine` and restart it if it does not answer
+async fn check_engine(
+    app_handle: &AppHandle,
+    engine: Arc<PythonEngine>,
+    role: EngineRole,
+) -> EngineOutcome {
+    if !engine.status().state.accepts_commands() {
+        return EngineOutcome::NotRunning;
+    }
+    let ping = tokio::time::timeout(
+        Duration::from_secs(PING_TIMEOUT_SECS),
+        engine.request("get_health", json!({})),
+    )
+    .await;
+    let problem = match ping {
+        Ok(Ok(_)) => return EngineOutcome::Responsive,
+        Ok(Err(e)) => e,
+        Err(_) => format!("no answer within {} seconds", PING_TIMEOUT_SECS),
+    };
+
+    tracing::warn!(
+        "Python {} unresponsive after resume ({}), restarting it",
+        role.label(),
+        problem
+    );
+    engine.shutdown().await;
+    if !engine
+        .wait_for_exit(Duration::from_secs(EXIT_WAIT_SECS))
+        .await
+    {
+        tracing::warn!("Python {} did not exit in time", role.label());
+    }
+    match crate::respawn_engine(app_handle, engine, role) {
+        Ok(()) => EngineOutcome::Restarted,
+        Err(e) => {
+            tracing::error!("Failed to restart {}: {}", role.label(), e);
+            EngineOutcome::RestartFailed
+        }
+    }
+}
+
+/// Replace the session's dead websocket; returns the resulting auth state
+async fn reconnect_session(
+    app_handle: &AppHandle,
+    engine: &PythonEngine,
+) -> Result<String, String> {
+    let data = engine.request("tr_reconnect_session", json!({})).await?;
+    session_vault::persist(app_handle);
+    let response: AuthResponse = protocol::parse(app_handle, "tr_reconnect_session", data)?;
+    Ok(response.auth_state)
+}
@@ -0,0 +1,115 @@
+//! Taskbar Progress
+//!
+//! Mirrors running syncs and pipeline runs on the dock icon (macOS) or the
+//! taskbar button (Windows), so the app can sit in the background during a
+//! long operation. Driven by the `sync-progress` and `pipeline-progress`
+//! events; with both running, the one further behind is shown.
+//!
+//! When the last operation ends the bar is cleared and, if the window is not
+//! focused, the dock icon bounces once / the taskbar button flashes.
+//! A failed sync sends no final progress event, so `sync_finished` is called
+//! when the sync command returns.
+
+use crate::pipeline_progress;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Listener, Manager, UserAttentionType};
+
+const SYNC_PROGRESS: &str = "sync-progress";
+
+struct Running {
+    /// Percent of the running sync
+    sync: Option<f64>,
+    /// Percent of the running pipeline
+    pipeline: Option<f64>,
+}
+
+static RUNNING: Mutex<Running> = Mutex::new(Running {
+    sync: None,
+    pipeline: None,
+});
+
+#[derive(Debug, Deserialize)]
+struct SyncProgress {
+    status: String,
+    #[serde(default)]
+    progress: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PipelineProgress {
+    status: String,
+    #[serde(default)]
+    percent: f64,
+}
+
+/// Show the state of `RUNNING` on the main window
+fn apply(app_handle: &AppHandle, ended: bool) {
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return;
+    };
+    let percent = match RUNNING.lock() {
+        Ok(running) => match (running.sync, running.pipeline) {
+            (Some(sync), Some(pipeline)) => Some(sync.min(pipeline)),
+            (sync, pipeline) => sync.or(pipeline),
+        },
+        Err(_) => return,
+    };
+    let state = match percent {
+        Some(percent) => ProgressBarState {
+            status: Some(ProgressBarStatus::Normal),
+            progress: Some(percent.clamp(0.0, 100.0).round() as u64),
+        },
+        None => ProgressBarState {
+            status: Some(ProgressBarStatus::None),
+            progress: None,
+        },
+    };
+    if let Err(e) = window.set_progress_bar(state) {
+        tracing::debug!("Failed to set taskbar progress: {}", e);
+    }
+    if ended && percent.is_none() && !window.is_focused().unwrap_or(true) {
+        let _ = window.request_user_attention(Some(UserAttentionType::Informational));
+    }
+}
+
+/// Update one operation; `None` marks it as ended
+fn update(app_handle: &AppHandle, slot: fn(&mut Running) -> &mut Option<f64>, value: Option<f64>) {
+    let ended = match RUNNING.lock() {
+        Ok(mut running) => {
+            let current = slot(&mut running);
+            let ended = current.is_some() && value.is_none();
+            *current = value;
+            ended
+        }
+        Err(_) => return,
+    };
+    apply(app_handle, ended);
+}
+
+/// Clear the sync's progress once `sync_portfolio` returned, successful or not.
+pub fn sync_finished(app_handle: &AppHandle) {
+    update(app_handle, |running| &mut running.sync, None);
+}
+
+/// Follow sync and pipeline progress events.
+pub fn start(app_handle: &AppHandle) {
+    let handle = app_handle.clone();
+    app_handle.listen(SYNC_PROGRESS, move |event| {
+        let Ok(progress) = serde_json::from_str::<SyncProgress>(event.payload()) else {
+            return;
+        };
+        let value = (progress.status == "syncing").then_some(progress.progress);
+        update(&handle, |running| &mut running.sync, value);
+    });
+
+    let handle = app_handle.clone();
+    app_handle.listen(pipeline_progress::EVENT, move |event| {
+        let Ok(progress) = serde_json::from_str::<PipelineProgress>(event.payload()) else {
+            return;
+        };
+        let value = (progress.status == "running").then_some(progress.percent);
+        update(&handle, |running| &mut running.pipeline, value);
+    });
+}
@@ -0,0 +1,265 @@
+//! Anonymous Usage Telemetry
+//!
+//! Opt-in (`telemetry` setting, off by default). While enabled, the shell
+//! counts:
+//!
+//! - invocations per IPC command (feature usage)
+//! - round-trip latency per engine command
+//! - failed engine commands per error code (or `transport` when no response
+//!   came back)
+//!
+//! Only command names, error codes, counts and durations are collected;
+//! payloads, messages, holdings and values never are, and there is no
+//! installation or user ID. Counters are folded into a batch every
+//! `BATCH_MINUTES`, queued in `telemetry_queue.ndjson` and sent to the
+//! proxy's `/telemetry` endpoint. Opting out drops the counters and the queue.
+//! `preview` shows exactly what is queued and what is being counted.
+
+use crate::app_settings;
+use crate::dataset;
+use crate::proxy;
+use crate::python_engine::EngineResponse;
+use crate::store;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+pub(crate) const QUEUE_FILE: &str = "telemetry_queue.ndjson";
+
+/// How often counters are folded into a queued batch and the queue is sent
+const BATCH_MINUTES: u64 = 60;
+
+/// Batches kept while the endpoint is unreachable; older ones are dropped
+const MAX_QUEUED_BATCHES: usize = 48;
+
+/// Timeout for the upload request
+const SEND_TIMEOUT_SECS: u64 = 15;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static CURRENT: Mutex<Option<Counters>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub count: u64,
+    pub mean_ms: u64,
+    pub max_ms: u64,
+    #[serde(skip)]
+    total_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Counters {
+    /// IPC command -> invocations
+    pub commands: BTreeMap<String, u64>,
+    /// Engine command -> round-trip latency
+    pub engine_latencies: BTreeMap<String, LatencyStats>,
+    /// Engine error code -> failures
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl Counters {
+    fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.engine_latencies.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// One batch as sent to the endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryBatch {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub app_version: String,
+    /// `<os>-<arch>`
+    pub platform: String,
+    #[serde(flatten)]
+    pub counters: Counters,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPreview {
+    pub enabled: bool,
+    pub endpoint: String,
+    /// Batches waiting to be sent, oldest first
+    pub queued: Vec<TelemetryBatch>,
+    /// Counted since the last batch; becomes the next one
+    pub current: Counters,
+}
+
+fn queue_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(QUEUE_FILE)
+}
+
+fn endpoint() -> String {
+    std::env::var("PRISM_TELEMETRY_URL").unwrap_or_else(|_| {
+        let proxy =
+            std::env::var("PROXY_URL").unwrap_or_else(|_| dataset::DEFAULT_PROXY_URL.to_string());
+        format!("{}/telemetry", proxy.trim_end_matches('/'))
+    })
+}
+
+/// Turn collection on or off. Turning it off drops everything not yet sent.
+pub fn set_enabled(data_dir: &Path, enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        if let Ok(mut current) = CURRENT.lock() {
+            *current = None;
+        }
+        let _ = std::fs::remove_file(queue_path(data_dir));
+    }
+}
+
+fn with_counters(update: impl FnOnce(&mut Counters)) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(mut current) = CURRENT.lock() {
+        update(current.get_or_insert_with(Counters::default));
+    }
+}
+
+/// Count an IPC command invocation.
+pub fn record_command(command: &str) {
+    with_counters(|counters| {
+        *counters.commands.entry(command.to_string()).or_default() += 1;
+    });
+}
+
+/// Record the latency and outcome of an engine command.
+pub fn record_engine_command(
+    command: &str,
+    elapsed: Duration,
+    result: &Result<EngineResponse, String>,
+) {
+    with_counters(|counters| {
+        let elapsed_ms = elapsed.as_millis() as u64;
+        let stats = counters
+            .engine_latencies
+            .entry(command.to_string())
+            .or_default();
+        stats.count += 1;
+        stats.total_ms += elapsed_ms;
+        stats.mean_ms = stats.total_ms / stats.count;
+        stats.max_ms = stats.max_ms.max(elapsed_ms);
+
+        let code = match result {
+            Ok(response) if response.success => return,
+            Ok(response) => response
+                .error
+                .as_ref()
+                .map_or("unknown", |error| error.code.as_str()),
+            Err(_) => "transport",
+        };
+        *counters.errors.entry(code.to_string()).or_default() += 1;
+    });
+}
+
+/// Fold the current counters into a queued batch.
+fn enqueue(data_dir: &Path, period_start: DateTime<Utc>, app_version: &str) -> Result<(), String> {
+    let Some(counters) = CURRENT.lock().map_err(|e| e.to_string())?.take() else {
+        return Ok(());
+    };
+    if counters.is_empty() {
+        return Ok(());
+    }
+    let batch = TelemetryBatch {
+        period_start,
+        period_end: Utc::now(),
+        app_version: app_version.to_string(),
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        counters,
+    };
+    store::append_ndjson(&queue_path(data_dir), &batch)
+}
+
+fn write_queue(data_dir: &Path, batches: &[TelemetryBatch]) -> Result<(), String> {
+    let path = queue_path(data_dir);
+    if batches.is_empty() {
+        let _ = std::fs::remove_file(path);
+        return Ok(());
+    }
+    let mut content = String::new();
+    for batch in batches {
+        let line = serde_json::to_string(batch)
+            .map_err(|e| format!("Failed to serialize telemetry: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    store::write_atomic(&path, content.as_bytes())
+}
+
+/// Send every queued batch in one request; the queue is cleared on success
+/// and capped at `MAX_QUEUED_BATCHES` otherwise.
+async fn send_queue(data_dir: &Path) -> Result<(), String> {
+    let mut batches: Vec<TelemetryBatch> =
+        store::read_ndjson_tail(&queue_path(data_dir), usize::MAX)?;
+    if batches.is_empty() {
+        return Ok(());
+    }
+
+    let sent = proxy::client()
+        .post(endpoint())
+        .json(&batches)
+        .timeout(Duration::from_secs(SEND_TIMEOUT_SECS))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = sent {
+        if batches.len() > MAX_QUEUED_BATCHES {
+            batches.drain(..batches.len() - MAX_QUEUED_BATCHES);
+            write_queue(data_dir, &batches)?;
+        }
+        return Err(format!("Failed to send telemetry: {}", e));
+    }
+    write_queue(data_dir, &[])
+}
+
+/// Load the opt-in and start batching and sending in the background.
+pub fn start(app_handle: AppHandle) {
+    let Ok(data_dir) = store::data_dir(&app_handle) else {
+        return;
+    };
+    set_enabled(&data_dir, app_settings::load_general(&data_dir).telemetry);
+
+    tauri::async_runtime::spawn(async move {
+        let app_version = app_handle.package_info().version.to_string();
+        let mut period_start = Utc::now();
+        loop {
+            tokio::time::sleep(Duration::from_secs(BATCH_MINUTES * 60)).await;
+            if !ENABLED.load(Ordering::Relaxed) {
+                period_start = Utc::now();
+                continue;
+            }
+            if let Err(e) = enqueue(&data_dir, period_start, &app_version) {
+                tracing::warn!("Failed to queue telemetry: {}", e);
+            }
+            period_start = Utc::now();
+            if let Err(e) = send_queue(&data_dir).await {
+                tracing::debug!("{}", e);
+            }
+        }
+    });
+}
+
+/// What is queued for sending and what is being counted right now.
+pub fn preview(data_dir: &Path) -> Result<TelemetryPreview, String> {
+    Ok(TelemetryPreview {
+        enabled: ENABLED.load(Ordering::Relaxed),
+        endpoint: endpoint(),
+        queued: store::read_ndjson_tail(&queue_path(data_dir), usize::MAX)?,
+        current: CURRENT
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .unwrap_or_default(),
+    })
+}
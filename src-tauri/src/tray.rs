@@ -0,0 +1,144 @@
+//! System Tray
+//!
+//! A tray icon (menu bar item on macOS) for people who keep the window
+//! minimized. Its menu shows the primary engine's state and when the last
+//! sync finished, and offers quick actions:
+//! - Sync now / Run pipeline: the scheduler's jobs, started right away with
+//!   the scheduled portfolio; the outcome arrives as a notification
+//! - Open Portfolio Prism: shows and focuses the main window
+//! - Quit
+//!
+//! The status lines follow `engine-state-changed` transitions and every
+//! `portfolio-updated`.
+
+use crate::db_reader;
+use crate::python_engine::{EnginePool, EngineState};
+use crate::scheduler;
+use crate::second_instance;
+use crate::store;
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Listener, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+
+const SYNC_NOW: &str = "sync-now";
+const RUN_PIPELINE: &str = "run-pipeline";
+const OPEN: &str = "open";
+const QUIT: &str = "quit";
+
+fn engine_label(state: EngineState) -> &'static str {
+    match state {
+        EngineState::Idle => "Engine: idle",
+        EngineState::Spawning => "Engine: starting",
+        EngineState::Ready => "Engine: ready",
+        EngineState::Degraded => "Engine: degraded",
+        EngineState::Restarting => "Engine: restarting",
+        EngineState::Dead => "Engine: stopped",
+    }
+}
+
+/// Last sync in local time; the engine stores either RFC 3339 or SQLite's
+/// `YYYY-MM-DD HH:MM:SS` in UTC
+fn sync_label(app_handle: &AppHandle) -> String {
+    let Some(last_sync) = store::data_dir(app_handle)
+        .ok()
+        .and_then(|data_dir| db_reader::last_sync_time(&data_dir))
+    else {
+        return "Last sync: never".to_string();
+    };
+    let parsed = DateTime::parse_from_rfc3339(&last_sync)
+        .map(|at| at.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(&last_sync, "%Y-%m-%d %H:%M:%S").map(|at| at.and_utc())
+        });
+    match parsed {
+        Ok(at) => format!(
+            "Last sync: {}",
+            at.with_timezone(&Local).format("%d %b %H:%M")
+        ),
+        Err(_) => format!("Last sync: {}", last_sync),
+    }
+}
+
+fn on_menu_event(app_handle: &AppHandle, id: &str) {
+    match id {
+        SYNC_NOW => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move { scheduler::sync_now(&app_handle).await });
+        }
+        RUN_PIPELINE => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move { scheduler::pipeline_now(&app_handle).await });
+        }
+        OPEN => second_instance::focus_main_window(app_handle),
+        QUIT => app_handle.exit(0),
+        _ => {}
+    }
+}
+
+/// Keep the engine line in step with the primary engine's state
+fn follow_engine(app_handle: &AppHandle, item: MenuItem<Wry>) {
+    let mut states = app_handle.state::<EnginePool>().primary().subscribe_state();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = states.borrow_and_update().state;
+            let _ = item.set_text(engine_label(state));
+            if states.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Create the tray icon. Call after the engine pool is managed.
+pub fn create(app_handle: &AppHandle) -> Result<(), String> {
+    let build = || -> tauri::Result<()> {
+        let engine = MenuItem::with_id(
+            app_handle,
+            "engine",
+            engine_label(EngineState::Spawning),
+            false,
+            None::<&str>,
+        )?;
+        let last_sync = MenuItem::with_id(
+            app_handle,
+            "last-sync",
+            sync_label(app_handle),
+            false,
+            None::<&str>,
+        )?;
+        let menu = Menu::with_items(
+            app_handle,
+            &[
+                &engine,
+                &last_sync,
+                &PredefinedMenuItem::separator(app_handle)?,
+                &MenuItem::with_id(app_handle, SYNC_NOW, "Sync now", true, None::<&str>)?,
+                &MenuItem::with_id(app_handle, RUN_PIPELINE, "Run pipeline", true, None::<&str>)?,
+                &PredefinedMenuItem::separator(app_handle)?,
+                &MenuItem::with_id(app_handle, OPEN, "Open Portfolio Prism", true, None::<&str>)?,
+                &MenuItem::with_id(app_handle, QUIT, "Quit", true, None::<&str>)?,
+            ],
+        )?;
+
+        let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+            .tooltip("Portfolio Prism")
+            .menu(&menu)
+            .show_menu_on_left_click(true)
+            .on_menu_event(|app_handle, event| on_menu_event(app_handle, event.id().as_ref()));
+        if let Some(icon) = app_handle.default_window_icon() {
+            tray = tray.icon(icon.clone());
+        }
+        tray.build(app_handle)?;
+
+        follow_engine(app_handle, engine);
+        let handle = app_handle.clone();
+        app_handle.listen("portfolio-updated", move |_| {
+            let _ = last_sync.set_text(sync_label(&handle));
+        });
+        Ok(())
+    };
+    build().map_err(|e| format!("Failed to create the tray icon: {}", e))
+}
@@ -0,0 +1,118 @@
+//! Turnover Analytics
+//!
+//! Trading-activity metrics computed from the transaction ledger:
+//!
+//! - turnover ratio: the smaller of purchases and sales in the range divided
+//!   by the average portfolio value (the usual fund definition)
+//! - average holding period: sold quantities are matched FIFO against earlier
+//!   purchase lots, weighted by quantity
+//! - trade frequency: buys and sells per month of the range
+//!
+//! Lots are matched over the full ledger so that sales in the range are paired
+//! with purchases made before it.
+
+use crate::commands::portfolio::Transaction;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Average days per month, for trade frequency
+const DAYS_PER_MONTH: f64 = 30.44;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnoverMetrics {
+    pub from: String,
+    pub to: String,
+    pub purchases: f64,
+    pub sales: f64,
+    pub average_value: f64,
+    /// `None` when the average portfolio value is unknown
+    pub turnover_ratio: Option<f64>,
+    /// `None` when nothing was sold in the range
+    pub average_holding_days: Option<f64>,
+    pub trade_count: usize,
+    pub trades_per_month: f64,
+}
+
+struct Lot {
+    date: NaiveDate,
+    quantity: f64,
+}
+
+fn parse_day(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Compute metrics for `[from, to]` from the full ledger up to `to`.
+pub fn compute(
+    transactions: &[Transaction],
+    from: NaiveDate,
+    to: NaiveDate,
+    average_value: f64,
+) -> TurnoverMetrics {
+    let mut trades: Vec<(NaiveDate, &Transaction)> = transactions
+        .iter()
+        .filter(|t| matches!(t.transaction_type.as_str(), "Buy" | "Sell"))
+        .filter_map(|t| Some((parse_day(&t.date)?, t)))
+        .filter(|(date, _)| *date <= to)
+        .collect();
+    trades.sort_by_key(|(date, _)| *date);
+
+    let mut lots: HashMap<&str, VecDeque<Lot>> = HashMap::new();
+    let mut purchases = 0.0;
+    let mut sales = 0.0;
+    let mut trade_count = 0;
+    let mut held_quantity = 0.0;
+    let mut held_days = 0.0;
+
+    for (date, trade) in trades {
+        let in_range = date >= from;
+        let quantity = trade.quantity.unwrap_or(0.0).abs();
+        let queue = lots.entry(trade.isin.as_str()).or_default();
+
+        if trade.transaction_type == "Buy" {
+            queue.push_back(Lot { date, quantity });
+            if in_range {
+                purchases += trade.amount.abs();
+            }
+        } else {
+            let mut remaining = quantity;
+            while remaining > 0.0 {
+                let Some(lot) = queue.front_mut() else {
+                    break;
+                };
+                let matched = remaining.min(lot.quantity);
+                if in_range {
+                    held_quantity += matched;
+                    held_days += matched * (date - lot.date).num_days() as f64;
+                }
+                lot.quantity -= matched;
+                remaining -= matched;
+                if lot.quantity <= f64::EPSILON {
+                    queue.pop_front();
+                }
+            }
+            if in_range {
+                sales += trade.amount.abs();
+            }
+        }
+        if in_range {
+            trade_count += 1;
+        }
+    }
+
+    let months = ((to - from).num_days() + 1) as f64 / DAYS_PER_MONTH;
+
+    TurnoverMetrics {
+        from: from.to_string(),
+        to: to.to_string(),
+        purchases,
+        sales,
+        average_value,
+        turnover_ratio: (average_value > 0.0).then(|| purchases.min(sales) / average_value),
+        average_holding_days: (held_quantity > 0.0).then(|| held_days / held_quantity),
+        trade_count,
+        trades_per_month: trade_count as f64 / months,
+    }
+}
@@ -0,0 +1,272 @@
+//! Value Waterfall
+//!
+//! Decomposes the change of a portfolio's value over a range into what the
+//! investor put in or took out and what the market did, shaped for a
+//! waterfall chart (start bar, one bar per component, end bar):
+//!
+//! - contributions: purchases and inbound transfers
+//! - withdrawals: sale proceeds and outbound transfers (negative)
+//! - dividends: dividends and interest received
+//! - fees: fees paid (negative)
+//! - market growth: the remainder of the observed change
+//!
+//! Start and end values come from the engine's value history (the last point
+//! on or before each date); dividends and fees are assumed to settle into
+//! that value. Reconciliation checks report whether the pieces sum to the
+//! observed delta and how trustworthy the inputs are.
+
+use crate::commands::portfolio::{HistoryPoint, Transaction};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Largest gap between a range boundary and the value point used for it
+const MAX_BOUNDARY_GAP_DAYS: i64 = 7;
+
+/// Rounding tolerance for the sum check
+const SUM_TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaterfallRange {
+    #[serde(rename = "1M")]
+    OneMonth,
+    #[serde(rename = "3M")]
+    ThreeMonths,
+    #[serde(rename = "6M")]
+    SixMonths,
+    #[serde(rename = "YTD")]
+    YearToDate,
+    #[serde(rename = "1Y")]
+    OneYear,
+    #[serde(rename = "ALL")]
+    All,
+}
+
+impl WaterfallRange {
+    /// Start date of the range ending `today`; `None` for `All`
+    pub fn start(self, today: NaiveDate) -> Option<NaiveDate> {
+        let months_back = |months| today.checked_sub_months(chrono::Months::new(months));
+        match self {
+            Self::OneMonth => months_back(1),
+            Self::ThreeMonths => months_back(3),
+            Self::SixMonths => months_back(6),
+            Self::YearToDate => NaiveDate::from_ymd_opt(today.year(), 1, 1),
+            Self::OneYear => months_back(12),
+            Self::All => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepKind {
+    /// Absolute bar (start and end value)
+    Total,
+    /// Floating bar from `base` to `base + amount`
+    Delta,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaterfallStep {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: StepKind,
+    pub amount: f64,
+    /// Bottom of a delta bar before the step (0 for totals)
+    pub base: f64,
+    /// Running value after the step
+    pub running_total: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueWaterfall {
+    pub portfolio_id: u32,
+    pub range: WaterfallRange,
+    pub from: String,
+    pub to: String,
+    pub start_value: f64,
+    pub end_value: f64,
+    pub steps: Vec<WaterfallStep>,
+    pub checks: Vec<ReconciliationCheck>,
+    /// Whether every check passed
+    pub reconciled: bool,
+}
+
+fn parse_day(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Last value on or before `date`, with its date
+fn value_at(history: &[(NaiveDate, f64)], date: NaiveDate) -> Option<(NaiveDate, f64)> {
+    history.iter().rev().find(|(day, _)| *day <= date).copied()
+}
+
+#[derive(Default)]
+struct Flows {
+    contributions: f64,
+    withdrawals: f64,
+    dividends: f64,
+    fees: f64,
+    undated: usize,
+}
+
+/// Sum the ledger flows in `(from, to]`; the start value already includes
+/// everything up to `from`.
+fn flows(transactions: &[Transaction], from: NaiveDate, to: NaiveDate) -> Flows {
+    let mut flows = Flows::default();
+    for transaction in transactions {
+        let Some(date) = parse_day(&transaction.date) else {
+            flows.undated += 1;
+            continue;
+        };
+        if date <= from || date > to {
+            continue;
+        }
+        let amount = transaction.amount;
+        match transaction.transaction_type.as_str() {
+            "Buy" => flows.contributions += amount.abs(),
+            "Sell" => flows.withdrawals -= amount.abs(),
+            "Transfer" if amount >= 0.0 => flows.contributions += amount,
+            "Transfer" => flows.withdrawals += amount,
+            "Dividend" | "Interest" => flows.dividends += amount.abs(),
+            "Fee" => flows.fees -= amount.abs(),
+            _ => {}
+        }
+    }
+    flows
+}
+
+/// Build the waterfall for `[from, to]`. `current_value` stands in for the
+/// end value when the history stops before `to`.
+pub fn compute(
+    portfolio_id: u32,
+    range: WaterfallRange,
+    from: NaiveDate,
+    to: NaiveDate,
+    transactions: &[Transaction],
+    history: &[HistoryPoint],
+    current_value: f64,
+) -> ValueWaterfall {
+    let mut history: Vec<(NaiveDate, f64)> = history
+        .iter()
+        .filter_map(|point| Some((parse_day(&point.date)?, point.value)))
+        .collect();
+    history.sort_by_key(|(day, _)| *day);
+
+    // Before the first value point the portfolio was empty
+    let start = value_at(&history, from);
+    let start_value = start.map(|(_, value)| value).unwrap_or(0.0);
+    let end = value_at(&history, to).filter(|(day, _)| (to - *day).num_days() == 0);
+    let end_value = end.map(|(_, value)| value).unwrap_or(current_value);
+
+    let flows = flows(transactions, from, to);
+    let delta = end_value - start_value;
+    let growth = delta - flows.contributions - flows.withdrawals - flows.dividends - flows.fees;
+
+    let mut steps = vec![WaterfallStep {
+        key: "start",
+        label: "Start value",
+        kind: StepKind::Total,
+        amount: start_value,
+        base: 0.0,
+        running_total: start_value,
+    }];
+    let mut running = start_value;
+    for (key, label, amount) in [
+        ("contributions", "Contributions", flows.contributions),
+        ("withdrawals", "Withdrawals", flows.withdrawals),
+        ("dividends", "Dividends", flows.dividends),
+        ("fees", "Fees", flows.fees),
+        ("marketGrowth", "Market growth", growth),
+    ] {
+        steps.push(WaterfallStep {
+            key,
+            label,
+            kind: StepKind::Delta,
+            amount,
+            base: running,
+            running_total: running + amount,
+        });
+        running += amount;
+    }
+    steps.push(WaterfallStep {
+        key: "end",
+        label: "End value",
+        kind: StepKind::Total,
+        amount: end_value,
+        base: 0.0,
+        running_total: end_value,
+    });
+
+    let pieces: f64 = steps
+        .iter()
+        .filter(|step| matches!(step.kind, StepKind::Delta))
+        .map(|step| step.amount)
+        .sum();
+    let start_gap = start.map(|(day, _)| (from - day).num_days());
+    let mut checks = vec![
+        ReconciliationCheck {
+            name: "sumMatchesDelta",
+            passed: (pieces - delta).abs() <= SUM_TOLERANCE,
+            detail: format!(
+                "Components sum to {:.2}, observed change {:.2}",
+                pieces, delta
+            ),
+        },
+        ReconciliationCheck {
+            name: "startValueCovered",
+            passed: (start.is_none() && history.first().is_some_and(|(day, _)| *day > from))
+                || start_gap.is_some_and(|gap| gap <= MAX_BOUNDARY_GAP_DAYS),
+            detail: match start {
+                Some((day, _)) => format!("Start value taken from {}", day),
+                None => "No value history before the start; assumed empty".to_string(),
+            },
+        },
+        ReconciliationCheck {
+            name: "endValueCovered",
+            passed: end.is_some()
+                || value_at(&history, to)
+                    .is_some_and(|(day, _)| (to - day).num_days() <= MAX_BOUNDARY_GAP_DAYS),
+            detail: match end {
+                Some((day, _)) => format!("End value taken from {}", day),
+                None => "End value is the current portfolio value".to_string(),
+            },
+        },
+        ReconciliationCheck {
+            name: "ledgerDated",
+            passed: flows.undated == 0,
+            detail: format!("{} transactions without a readable date", flows.undated),
+        },
+    ];
+    // Growth cannot lose more than everything that was invested
+    let invested = start_value + flows.contributions;
+    checks.push(ReconciliationCheck {
+        name: "growthPlausible",
+        passed: growth >= -invested - SUM_TOLERANCE,
+        detail: format!(
+            "Market growth {:.2} against {:.2} invested",
+            growth, invested
+        ),
+    });
+
+    ValueWaterfall {
+        portfolio_id,
+        range,
+        from: from.to_string(),
+        to: to.to_string(),
+        start_value,
+        end_value,
+        reconciled: checks.iter().all(|check| check.passed),
+        steps,
+        checks,
+    }
+}
@@ -0,0 +1,261 @@
+//! XLSX Export
+//!
+//! Writes a portfolio to a multi-sheet Excel workbook for archiving:
+//!
+//! - `Positions`: one row per position with cost, value and P&L
+//! - `Allocations`: sector, region and asset-class weights
+//! - `True Holdings`: the look-through holdings with their source funds
+//! - `Overlap`: the pairwise fund overlap matrix
+//!
+//! Look-through sheets hold a single note when the data is unavailable (e.g.
+//! for sandbox portfolios or before the first pipeline run).
+
+use crate::commands::portfolio::{Allocations, PositionsResponse};
+use crate::overlap_matrix::OverlapMatrix;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedSheet {
+    pub name: String,
+    pub rows: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub path: String,
+    pub sheets: Vec<ExportedSheet>,
+}
+
+pub struct ExportData {
+    pub positions: PositionsResponse,
+    pub allocations: Allocations,
+    /// `get_true_holdings` response
+    pub true_holdings: Option<Value>,
+    /// Overlap of the portfolio's ETFs (see `overlap_matrix`)
+    pub overlap: Option<OverlapMatrix>,
+}
+
+fn xlsx_error(e: XlsxError) -> String {
+    format!("Failed to write workbook: {}", e)
+}
+
+fn header(sheet: &mut Worksheet, columns: &[(&str, f64)]) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    for (col, (title, width)) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, &bold)?;
+        sheet.set_column_width(col as u16, *width)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_positions(sheet: &mut Worksheet, positions: &PositionsResponse) -> Result<u32, XlsxError> {
+    header(
+        sheet,
+        &[
+            ("ISIN", 14.0),
+            ("Name", 32.0),
+            ("Type", 10.0),
+            ("Quantity", 12.0),
+            ("Avg. buy price", 14.0),
+            ("Price", 12.0),
+            ("Currency", 9.0),
+            ("Cost", 14.0),
+            ("Value", 14.0),
+            ("P&L", 14.0),
+            ("P&L %", 9.0),
+            ("Weight %", 9.0),
+            ("Notes", 40.0),
+        ],
+    )?;
+
+    let money = Format::new().set_num_format("#,##0.00");
+    let mut row = 0;
+    for position in &positions.positions {
+        row += 1;
+        sheet.write_string(row, 0, &position.isin)?;
+        sheet.write_string(row, 1, &position.name)?;
+        sheet.write_string(row, 2, &position.instrument_type)?;
+        sheet.write_number(row, 3, position.quantity)?;
+        sheet.write_number_with_format(row, 4, position.avg_buy_price, &money)?;
+        sheet.write_number_with_format(row, 5, position.current_price, &money)?;
+        sheet.write_string(row, 6, &position.currency)?;
+        sheet.write_number_with_format(row, 7, position.total_cost, &money)?;
+        sheet.write_number_with_format(row, 8, position.current_value, &money)?;
+        sheet.write_number_with_format(row, 9, position.pnl_eur, &money)?;
+        sheet.write_number(row, 10, position.pnl_percent)?;
+        sheet.write_number(row, 11, position.weight)?;
+        sheet.write_string(row, 12, &position.notes)?;
+    }
+
+    let bold_money = Format::new().set_bold().set_num_format("#,##0.00");
+    sheet.write_string_with_format(row + 1, 1, "Total", &Format::new().set_bold())?;
+    sheet.write_number_with_format(row + 1, 7, positions.total_cost, &bold_money)?;
+    sheet.write_number_with_format(row + 1, 8, positions.total_value, &bold_money)?;
+    sheet.write_number_with_format(row + 1, 9, positions.total_pnl, &bold_money)?;
+    sheet.write_number(row + 1, 10, positions.total_pnl_percent)?;
+    Ok(row)
+}
+
+fn write_allocations(sheet: &mut Worksheet, allocations: &Allocations) -> Result<u32, XlsxError> {
+    header(
+        sheet,
+        &[("Dimension", 14.0), ("Bucket", 32.0), ("Weight %", 10.0)],
+    )?;
+
+    let dimensions: [(&str, &HashMap<String, f64>); 3] = [
+        ("Sector", &allocations.sector),
+        ("Region", &allocations.region),
+        ("Asset class", &allocations.asset_class),
+    ];
+    let mut row = 0;
+    for (dimension, weights) in dimensions {
+        let mut weights: Vec<(&String, &f64)> = weights.iter().collect();
+        weights.sort_by(|a, b| b.1.total_cmp(a.1));
+        for (bucket, weight) in weights {
+            row += 1;
+            sheet.write_string(row, 0, dimension)?;
+            sheet.write_string(row, 1, bucket)?;
+            sheet.write_number(row, 2, *weight)?;
+        }
+    }
+    Ok(row)
+}
+
+fn write_note(sheet: &mut Worksheet, note: &str) -> Result<u32, XlsxError> {
+    sheet.write_string(0, 0, note)?;
+    Ok(0)
+}
+
+fn write_true_holdings(sheet: &mut Worksheet, data: Option<&Value>) -> Result<u32, XlsxError> {
+    let Some(holdings) = data.and_then(|data| data["holdings"].as_array()) else {
+        return write_note(
+            sheet,
+            "True holdings are not available; run the pipeline first.",
+        );
+    };
+
+    header(
+        sheet,
+        &[
+            ("ISIN", 14.0),
+            ("Name", 32.0),
+            ("Ticker", 10.0),
+            ("Value", 14.0),
+            ("Sector", 20.0),
+            ("Country", 16.0),
+            ("Held via", 60.0),
+        ],
+    )?;
+
+    let money = Format::new().set_num_format("#,##0.00");
+    let mut row = 0;
+    for holding in holdings {
+        row += 1;
+        let text = |key: &str| holding[key].as_str().unwrap_or_default().to_string();
+        let sources: Vec<String> = holding["sources"]
+            .as_array()
+            .map(|sources| {
+                sources
+                    .iter()
+                    .map(|source| {
+                        format!(
+                            "{} ({:.2}%)",
+                            source["etf"].as_str().unwrap_or("?"),
+                            source["weight"].as_f64().unwrap_or(0.0)
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        sheet.write_string(row, 0, text("isin"))?;
+        sheet.write_string(row, 1, text("stock"))?;
+        sheet.write_string(row, 2, text("ticker"))?;
+        sheet.write_number_with_format(
+            row,
+            3,
+            holding["totalValue"].as_f64().unwrap_or(0.0),
+            &money,
+        )?;
+        sheet.write_string(row, 4, text("sector"))?;
+        sheet.write_string(row, 5, text("geography"))?;
+        sheet.write_string(row, 6, sources.join(", "))?;
+    }
+    Ok(row)
+}
+
+fn write_overlap(sheet: &mut Worksheet, overlap: Option<&OverlapMatrix>) -> Result<u32, XlsxError> {
+    let Some(overlap) = overlap.filter(|overlap| overlap.funds.iter().any(|fund| fund.decomposed))
+    else {
+        return write_note(
+            sheet,
+            "Overlap data is not available; run the pipeline first.",
+        );
+    };
+
+    let bold = Format::new().set_bold();
+    sheet.set_column_width(0, 32.0)?;
+    for (index, fund) in overlap.funds.iter().enumerate() {
+        let col = index as u16 + 1;
+        sheet.write_string_with_format(0, col, &fund.name, &bold)?;
+        sheet.write_string_with_format(index as u32 + 1, 0, &fund.name, &bold)?;
+        sheet.set_column_width(col, 14.0)?;
+    }
+    for (row, cells) in overlap.matrix.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            if let Some(value) = cell {
+                sheet.write_number(row as u32 + 1, col as u16 + 1, *value)?;
+            }
+        }
+    }
+    sheet.set_freeze_panes(1, 1)?;
+    Ok(overlap.funds.len() as u32)
+}
+
+fn add_sheet(
+    workbook: &mut Workbook,
+    sheets: &mut Vec<ExportedSheet>,
+    name: &str,
+    write_sheet: impl FnOnce(&mut Worksheet) -> Result<u32, XlsxError>,
+) -> Result<(), String> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(name).map_err(xlsx_error)?;
+    let rows = write_sheet(sheet).map_err(xlsx_error)?;
+    sheets.push(ExportedSheet {
+        name: name.to_string(),
+        rows,
+    });
+    Ok(())
+}
+
+/// Write the workbook to `path`.
+pub fn write(path: &Path, data: &ExportData) -> Result<ExportSummary, String> {
+    let mut workbook = Workbook::new();
+    let mut sheets = vec![];
+
+    add_sheet(&mut workbook, &mut sheets, "Positions", |sheet| {
+        write_positions(sheet, &data.positions)
+    })?;
+    add_sheet(&mut workbook, &mut sheets, "Allocations", |sheet| {
+        write_allocations(sheet, &data.allocations)
+    })?;
+    add_sheet(&mut workbook, &mut sheets, "True Holdings", |sheet| {
+        write_true_holdings(sheet, data.true_holdings.as_ref())
+    })?;
+    add_sheet(&mut workbook, &mut sheets, "Overlap", |sheet| {
+        write_overlap(sheet, data.overlap.as_ref())
+    })?;
+
+    workbook.save(path).map_err(xlsx_error)?;
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        sheets,
+    })
+}